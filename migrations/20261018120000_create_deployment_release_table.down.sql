@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS deployment_release_no_update ON deployment_release;
+DROP FUNCTION IF EXISTS deployment_release_immutable();
+DROP TABLE IF EXISTS deployment_release;
@@ -0,0 +1,28 @@
+-- Immutable release records: one row per successful deploy of a deployment.
+CREATE TABLE IF NOT EXISTS deployment_release (
+    id SERIAL PRIMARY KEY,
+    deployment_hash VARCHAR(128) NOT NULL REFERENCES deployment(deployment_hash) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    compose_content TEXT NOT NULL,
+    env_hashes JSONB NOT NULL DEFAULT '{}'::jsonb,
+    image_digests JSONB NOT NULL DEFAULT '{}'::jsonb,
+    stacker_yml TEXT NOT NULL DEFAULT '',
+    bundle_hash VARCHAR(64) NOT NULL,
+    rollback_of INTEGER,
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (deployment_hash, version)
+);
+
+CREATE INDEX idx_deployment_release_hash ON deployment_release(deployment_hash);
+
+-- Releases are append-only; a rollback records a new release instead.
+CREATE OR REPLACE FUNCTION deployment_release_immutable() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'deployment_release rows are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER deployment_release_no_update
+    BEFORE UPDATE ON deployment_release
+    FOR EACH ROW EXECUTE FUNCTION deployment_release_immutable();
@@ -0,0 +1,30 @@
+WITH tool_policy(subject, tool) AS (
+    VALUES
+        ('group_user', 'list_deployment_releases'),
+        ('group_user', 'rollback_deployment_release')
+)
+DELETE FROM public.casbin_rule cr
+USING tool_policy tp
+WHERE cr.ptype = 'p'
+  AND cr.v0 = tp.subject
+  AND cr.v1 = '/mcp/tools/' || tp.tool
+  AND cr.v2 = 'CALL'
+  AND cr.v3 = ''
+  AND cr.v4 = ''
+  AND cr.v5 = '';
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/deployments/:deployment_hash/releases', 'GET'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/releases', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/releases/:version/rollback', 'POST')
+)
+DELETE FROM public.casbin_rule cr
+USING route_policy rp
+WHERE cr.ptype = 'p'
+  AND cr.v0 = rp.subject
+  AND cr.v1 = rp.route
+  AND cr.v2 = rp.action
+  AND cr.v3 = ''
+  AND cr.v4 = ''
+  AND cr.v5 = '';
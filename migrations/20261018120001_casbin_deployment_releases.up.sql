@@ -0,0 +1,22 @@
+-- Casbin ACL for deployment release records and the matching MCP tools.
+
+WITH tool_policy(subject, tool) AS (
+    VALUES
+        ('group_user', 'list_deployment_releases'),
+        ('group_user', 'rollback_deployment_release')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, '/mcp/tools/' || tool, 'CALL', '', '', ''
+FROM tool_policy
+ON CONFLICT DO NOTHING;
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/deployments/:deployment_hash/releases', 'GET'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/releases', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/releases/:version/rollback', 'POST')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS deployment_lease_audit;
+DROP TABLE IF EXISTS deployment_lease;
@@ -0,0 +1,26 @@
+-- Deploy mutex: at most one active lease per deployment. Expired rows are
+-- taken over by the next acquirer.
+CREATE TABLE IF NOT EXISTS deployment_lease (
+    deployment_hash VARCHAR(128) PRIMARY KEY REFERENCES deployment(deployment_hash) ON DELETE CASCADE,
+    lease_id VARCHAR(64) NOT NULL,
+    owner VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    reason TEXT NOT NULL DEFAULT '',
+    acquired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Leases broken with --force, kept for audit.
+CREATE TABLE IF NOT EXISTS deployment_lease_audit (
+    id SERIAL PRIMARY KEY,
+    deployment_hash VARCHAR(128) NOT NULL REFERENCES deployment(deployment_hash) ON DELETE CASCADE,
+    action VARCHAR(32) NOT NULL,
+    actor_user_id VARCHAR(255) NOT NULL,
+    actor_owner VARCHAR(255) NOT NULL,
+    previous_owner VARCHAR(255) NOT NULL,
+    previous_reason TEXT NOT NULL DEFAULT '',
+    reason TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_deployment_lease_audit_hash ON deployment_lease_audit(deployment_hash);
@@ -0,0 +1,14 @@
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/deployments/:deployment_hash/lease', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/lease/:lease_id', 'DELETE')
+)
+DELETE FROM public.casbin_rule cr
+USING route_policy rp
+WHERE cr.ptype = 'p'
+  AND cr.v0 = rp.subject
+  AND cr.v1 = rp.route
+  AND cr.v2 = rp.action
+  AND cr.v3 = ''
+  AND cr.v4 = ''
+  AND cr.v5 = '';
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_preview_environment_expiry;
+DROP TABLE IF EXISTS preview_environment;
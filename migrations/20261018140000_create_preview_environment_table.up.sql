@@ -0,0 +1,20 @@
+-- Ephemeral per-branch preview environments sharing one deployment's server.
+-- Active rows past expires_at are torn down by the preview reaper.
+CREATE TABLE IF NOT EXISTS preview_environment (
+    id SERIAL PRIMARY KEY,
+    deployment_hash VARCHAR(128) NOT NULL REFERENCES deployment(deployment_hash) ON DELETE CASCADE,
+    slug VARCHAR(63) NOT NULL,
+    branch VARCHAR(255) NOT NULL,
+    app_code VARCHAR(255) NOT NULL,
+    compose_project VARCHAR(255) NOT NULL,
+    domain VARCHAR(255) NOT NULL,
+    forward_port INTEGER NOT NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'active',
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (deployment_hash, slug)
+);
+
+CREATE INDEX idx_preview_environment_expiry ON preview_environment(status, expires_at);
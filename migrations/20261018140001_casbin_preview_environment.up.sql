@@ -0,0 +1,12 @@
+-- Casbin ACL for preview environments.
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/deployments/:deployment_hash/previews', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/previews', 'GET'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/previews/:slug', 'DELETE')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
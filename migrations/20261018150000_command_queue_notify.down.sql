@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS command_queue_notify_trigger ON command_queue;
+DROP FUNCTION IF EXISTS stacker_notify_command_queued();
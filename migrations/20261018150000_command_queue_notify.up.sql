@@ -0,0 +1,15 @@
+-- Wake agent command waiters as soon as a command is queued instead of
+-- waiting for their next poll. The payload is the deployment hash.
+CREATE OR REPLACE FUNCTION stacker_notify_command_queued()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('agent_command_queued', NEW.deployment_hash);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS command_queue_notify_trigger ON command_queue;
+CREATE TRIGGER command_queue_notify_trigger
+    AFTER INSERT ON command_queue
+    FOR EACH ROW
+    EXECUTE FUNCTION stacker_notify_command_queued();
@@ -0,0 +1,13 @@
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('agent', '/api/v1/agent/commands/stream/:deployment_hash', 'GET')
+)
+DELETE FROM public.casbin_rule cr
+USING route_policy rp
+WHERE cr.ptype = 'p'
+  AND cr.v0 = rp.subject
+  AND cr.v1 = rp.route
+  AND cr.v2 = rp.action
+  AND cr.v3 = ''
+  AND cr.v4 = ''
+  AND cr.v5 = '';
@@ -0,0 +1,10 @@
+-- Casbin ACL for the agent command stream (only agents can open it).
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('agent', '/api/v1/agent/commands/stream/:deployment_hash', 'GET')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
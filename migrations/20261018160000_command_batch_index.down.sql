@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_commands_batch_id;
@@ -0,0 +1,4 @@
+-- Command batches live in commands.metadata->'batch'; look members up by batch id.
+CREATE INDEX IF NOT EXISTS idx_commands_batch_id
+    ON commands (deployment_hash, (metadata->'batch'->>'id'))
+    WHERE metadata ? 'batch';
@@ -0,0 +1,18 @@
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/agent/commands/batch', 'POST'),
+        ('group_admin', '/api/v1/agent/commands/batch', 'POST'),
+        ('client', '/api/v1/agent/commands/batch', 'POST'),
+        ('group_user', '/api/v1/agent/commands/batch/:deployment_hash/:batch_id', 'GET'),
+        ('group_admin', '/api/v1/agent/commands/batch/:deployment_hash/:batch_id', 'GET'),
+        ('client', '/api/v1/agent/commands/batch/:deployment_hash/:batch_id', 'GET')
+)
+DELETE FROM public.casbin_rule cr
+USING route_policy rp
+WHERE cr.ptype = 'p'
+  AND cr.v0 = rp.subject
+  AND cr.v1 = rp.route
+  AND cr.v2 = rp.action
+  AND cr.v3 = ''
+  AND cr.v4 = ''
+  AND cr.v5 = '';
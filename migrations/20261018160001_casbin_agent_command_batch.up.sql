@@ -0,0 +1,15 @@
+-- Casbin ACL for agent command batches.
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/agent/commands/batch', 'POST'),
+        ('group_admin', '/api/v1/agent/commands/batch', 'POST'),
+        ('client', '/api/v1/agent/commands/batch', 'POST'),
+        ('group_user', '/api/v1/agent/commands/batch/:deployment_hash/:batch_id', 'GET'),
+        ('group_admin', '/api/v1/agent/commands/batch/:deployment_hash/:batch_id', 'GET'),
+        ('client', '/api/v1/agent/commands/batch/:deployment_hash/:batch_id', 'GET')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,25 @@
+-- Recurring agent commands. The command scheduler queues a command for each
+-- enabled schedule whose next_run_at has passed, then moves next_run_at to
+-- the following cron match.
+CREATE TABLE IF NOT EXISTS agent_command_schedule (
+    id SERIAL PRIMARY KEY,
+    schedule_id VARCHAR(64) NOT NULL UNIQUE,
+    deployment_hash VARCHAR(128) NOT NULL REFERENCES deployment(deployment_hash) ON DELETE CASCADE,
+    name VARCHAR(255),
+    cron VARCHAR(128) NOT NULL,
+    command_type VARCHAR(100) NOT NULL,
+    parameters JSONB,
+    priority VARCHAR(20) NOT NULL DEFAULT 'normal',
+    timeout_seconds INTEGER,
+    missed_run_policy VARCHAR(20) NOT NULL DEFAULT 'skip',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    last_command_id VARCHAR(64)
+);
+
+CREATE INDEX idx_agent_command_schedule_due ON agent_command_schedule(enabled, next_run_at);
+CREATE INDEX idx_agent_command_schedule_deployment ON agent_command_schedule(deployment_hash);
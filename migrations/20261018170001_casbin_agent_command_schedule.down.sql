@@ -0,0 +1,31 @@
+WITH tool_policy(subject, tool) AS (
+    VALUES
+        ('group_user', 'create_agent_schedule'),
+        ('group_user', 'list_agent_schedules'),
+        ('group_user', 'delete_agent_schedule')
+)
+DELETE FROM public.casbin_rule cr
+USING tool_policy tp
+WHERE cr.ptype = 'p'
+  AND cr.v0 = tp.subject
+  AND cr.v1 = '/mcp/tools/' || tp.tool
+  AND cr.v2 = 'CALL'
+  AND cr.v3 = ''
+  AND cr.v4 = ''
+  AND cr.v5 = '';
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/deployments/:deployment_hash/schedules', 'GET'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/schedules', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/schedules/:schedule_id', 'DELETE')
+)
+DELETE FROM public.casbin_rule cr
+USING route_policy rp
+WHERE cr.ptype = 'p'
+  AND cr.v0 = rp.subject
+  AND cr.v1 = rp.route
+  AND cr.v2 = rp.action
+  AND cr.v3 = ''
+  AND cr.v4 = ''
+  AND cr.v5 = '';
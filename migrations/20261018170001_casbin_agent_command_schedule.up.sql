@@ -0,0 +1,23 @@
+-- Casbin ACL for scheduled agent commands and the matching MCP tools.
+
+WITH tool_policy(subject, tool) AS (
+    VALUES
+        ('group_user', 'create_agent_schedule'),
+        ('group_user', 'list_agent_schedules'),
+        ('group_user', 'delete_agent_schedule')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, '/mcp/tools/' || tool, 'CALL', '', '', ''
+FROM tool_policy
+ON CONFLICT DO NOTHING;
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/deployments/:deployment_hash/schedules', 'GET'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/schedules', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/schedules/:schedule_id', 'DELETE')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
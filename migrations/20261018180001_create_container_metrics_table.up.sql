@@ -0,0 +1,22 @@
+-- Container resource samples pushed by Status Panel agents. The table is
+-- partitioned by day so the metrics maintenance task can drop expired days
+-- instead of deleting rows; rows outside an existing day land in the
+-- default partition.
+CREATE TABLE IF NOT EXISTS container_metrics (
+    deployment_hash VARCHAR(128) NOT NULL,
+    app_code VARCHAR(255) NOT NULL,
+    container VARCHAR(255),
+    recorded_at TIMESTAMPTZ NOT NULL,
+    cpu_percent DOUBLE PRECISION NOT NULL,
+    memory_bytes BIGINT NOT NULL,
+    memory_limit_bytes BIGINT,
+    net_rx_bytes BIGINT NOT NULL DEFAULT 0,
+    net_tx_bytes BIGINT NOT NULL DEFAULT 0,
+    block_read_bytes BIGINT NOT NULL DEFAULT 0,
+    block_write_bytes BIGINT NOT NULL DEFAULT 0
+) PARTITION BY RANGE (recorded_at);
+
+CREATE TABLE IF NOT EXISTS container_metrics_default PARTITION OF container_metrics DEFAULT;
+
+CREATE INDEX idx_container_metrics_deployment_time
+    ON container_metrics(deployment_hash, recorded_at);
@@ -0,0 +1,21 @@
+-- Casbin ACL for container metrics: agents push samples, users read them
+-- through the API and the get_container_metrics MCP tool.
+
+WITH tool_policy(subject, tool) AS (
+    VALUES
+        ('group_user', 'get_container_metrics')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, '/mcp/tools/' || tool, 'CALL', '', '', ''
+FROM tool_policy
+ON CONFLICT DO NOTHING;
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('agent', '/api/v1/agent/metrics', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/metrics', 'GET')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,12 @@
+-- Casbin ACL for interactive shell sessions: users open the session socket,
+-- the agent attaches its PTY to it.
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('group_user', '/api/v1/deployments/:deployment_hash/shell', 'GET'),
+        ('agent', '/api/v1/agent/shell/:session_id', 'GET')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,4 @@
+-- Structured capabilities reported by the agent at registration: protocol
+-- version, supported command types with parameter schema versions, and
+-- feature flags. NULL for agents that only send a flat capability list.
+ALTER TABLE agents ADD COLUMN capability_manifest JSONB;
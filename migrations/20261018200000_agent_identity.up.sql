@@ -0,0 +1,25 @@
+-- Overlap-window token rotation. An offered token is kept only as a hash
+-- until the agent first authenticates with it; the replaced token keeps
+-- working until previous_expires_at.
+CREATE TABLE agent_token_rotation (
+    agent_id UUID PRIMARY KEY REFERENCES agents(id) ON DELETE CASCADE,
+    rotated_at TIMESTAMPTZ,
+    pending_token_hash VARCHAR(64),
+    pending_expires_at TIMESTAMPTZ,
+    previous_token_hash VARCHAR(64),
+    previous_expires_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Client certificates signed for agents in mTLS mode.
+CREATE TABLE agent_certificates (
+    id SERIAL PRIMARY KEY,
+    agent_id UUID NOT NULL REFERENCES agents(id) ON DELETE CASCADE,
+    serial_number VARCHAR(128) NOT NULL,
+    fingerprint_sha256 VARCHAR(64) NOT NULL UNIQUE,
+    not_after TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_agent_certificates_agent_id ON agent_certificates(agent_id);
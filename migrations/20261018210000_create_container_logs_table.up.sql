@@ -0,0 +1,20 @@
+-- Container log lines streamed by Status Panel agents. Partitioned by day
+-- like container_metrics so the log maintenance task drops expired days;
+-- lines outside an existing day land in the default partition. Searches
+-- match words through the full-text index on the message.
+CREATE TABLE IF NOT EXISTS container_logs (
+    deployment_hash VARCHAR(128) NOT NULL,
+    app_code VARCHAR(255) NOT NULL,
+    container VARCHAR(255),
+    logged_at TIMESTAMPTZ NOT NULL,
+    stream VARCHAR(16) NOT NULL,
+    message TEXT NOT NULL
+) PARTITION BY RANGE (logged_at);
+
+CREATE TABLE IF NOT EXISTS container_logs_default PARTITION OF container_logs DEFAULT;
+
+CREATE INDEX idx_container_logs_deployment_time
+    ON container_logs(deployment_hash, logged_at);
+
+CREATE INDEX idx_container_logs_message_search
+    ON container_logs USING GIN (to_tsvector('simple', message));
@@ -0,0 +1,21 @@
+-- Casbin ACL for shipped container logs: agents push lines, users search
+-- them through the API and the search_logs MCP tool.
+
+WITH tool_policy(subject, tool) AS (
+    VALUES
+        ('group_user', 'search_logs')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, '/mcp/tools/' || tool, 'CALL', '', '', ''
+FROM tool_policy
+ON CONFLICT DO NOTHING;
+
+WITH route_policy(subject, route, action) AS (
+    VALUES
+        ('agent', '/api/v1/agent/logs', 'POST'),
+        ('group_user', '/api/v1/deployments/:deployment_hash/logs', 'GET')
+)
+INSERT INTO public.casbin_rule (ptype, v0, v1, v2, v3, v4, v5)
+SELECT 'p', subject, route, action, '', '', ''
+FROM route_policy
+ON CONFLICT DO NOTHING;
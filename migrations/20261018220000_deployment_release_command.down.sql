@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS deployment_release_no_delete ON deployment_release;
+DROP FUNCTION IF EXISTS deployment_release_no_delete();
+DROP INDEX IF EXISTS idx_deployment_release_command;
+ALTER TABLE deployment_release DROP COLUMN IF EXISTS command_id;
@@ -0,0 +1,21 @@
+-- Tie each release to the deploy_app command that shipped it, at most once.
+ALTER TABLE deployment_release ADD COLUMN IF NOT EXISTS command_id VARCHAR(64);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_deployment_release_command
+    ON deployment_release(command_id)
+    WHERE command_id IS NOT NULL;
+
+-- Releases cannot be deleted either, except by the cascade from their
+-- deployment: by then the parent row is already gone.
+CREATE OR REPLACE FUNCTION deployment_release_no_delete() RETURNS trigger AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM deployment WHERE deployment_hash = OLD.deployment_hash) THEN
+        RAISE EXCEPTION 'deployment_release rows are immutable';
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER deployment_release_no_delete
+    BEFORE DELETE ON deployment_release
+    FOR EACH ROW EXECUTE FUNCTION deployment_release_no_delete();
@@ -16,6 +16,8 @@
 
 use clap::{Args, CommandFactory, Parser, Subcommand};
 use stacker::console::commands::cli::secrets::RemoteSecretScope;
+use stacker::forms::status_panel::{BackupRetention, DatabaseEngine};
+use stacker::models::MissedRunPolicy;
 
 fn print_banner() {
     let version = env!("CARGO_PKG_VERSION");
@@ -49,6 +51,10 @@ fn print_banner() {
 struct Cli {
     #[command(subcommand)]
     command: Option<StackerCommands>,
+    /// Container engine for local commands: docker, podman or nerdctl
+    /// (default: auto-detect from PATH)
+    #[arg(long, global = true, value_name = "RUNTIME", value_parser = ["docker", "podman", "nerdctl"])]
+    container_runtime: Option<String>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -179,6 +185,21 @@ enum StackerCommands {
         /// marketplace. Only pass this after reviewing the hook scripts.
         #[arg(long)]
         allow_untrusted_hooks: bool,
+        /// Watch app health after a remote deploy and re-deploy the latest
+        /// server release of any app that fails the health gate
+        #[arg(long)]
+        auto_rollback: bool,
+        /// Seconds to watch app health with --auto-rollback
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = 120,
+            requires = "auto_rollback"
+        )]
+        health_window: u64,
+        /// Break another client's deploy lease (recorded in the lease audit log)
+        #[arg(long)]
+        force: bool,
     },
     /// Attach this directory to an existing deployment from the dashboard
     Connect {
@@ -268,6 +289,9 @@ enum StackerCommands {
         /// Show logs since timestamp (e.g. "2h", "2024-01-01")
         #[arg(long)]
         since: Option<String>,
+        /// Search retained logs of all containers for words or "quoted phrases"
+        #[arg(long, conflicts_with = "follow")]
+        search: Option<String>,
     },
     /// Show deployment status
     Status {
@@ -283,6 +307,26 @@ enum StackerCommands {
         #[command(subcommand)]
         command: DeploymentCommands,
     },
+    /// Releases recorded by remote deploys
+    Releases {
+        #[command(subcommand)]
+        command: ReleasesCommands,
+    },
+    /// Air-gapped bundles: export a stack with its images, install it offline
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommands,
+    },
+    /// Volume backups taken and restored by the Status Panel agent
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommands,
+    },
+    /// Per-branch preview environments on a shared server
+    Preview {
+        #[command(subcommand)]
+        command: PreviewCommands,
+    },
     /// Explain path and topology decisions
     Explain {
         #[command(subcommand)]
@@ -297,14 +341,27 @@ enum StackerCommands {
         #[arg(long, short = 'y')]
         confirm: bool,
     },
-    /// Roll back a marketplace deployment to a prior template version
+    /// Roll back to a recorded release or a prior marketplace template version
     Rollback {
+        /// Release to re-apply (`previous`, `3` or `r3`; see `stacker releases list`)
+        #[arg(
+            value_name = "RELEASE",
+            conflicts_with = "version",
+            required_unless_present = "version"
+        )]
+        release: Option<String>,
         /// Marketplace template version to redeploy
         #[arg(long, value_name = "VERSION")]
-        version: String,
+        version: Option<String>,
+        /// Print the rollback plan without applying it
+        #[arg(long, conflicts_with = "version")]
+        plan: bool,
         /// Skip confirmation prompt (required)
         #[arg(long, short = 'y')]
         confirm: bool,
+        /// Break another client's deploy lease (recorded in the lease audit log)
+        #[arg(long, conflicts_with = "version")]
+        force: bool,
     },
     /// Configuration management
     Config {
@@ -432,6 +489,26 @@ Use explicit --scope service or --scope server to activate remote mode.",
         #[command(subcommand)]
         command: CiCommands,
     },
+    /// Build, tag and push app images (docker buildx) ahead of a remote deploy
+    Build {
+        /// Path to stacker.yml (default: ./stacker.yml)
+        #[arg(long, value_name = "FILE")]
+        file: Option<String>,
+        /// Target platforms, e.g. linux/amd64,linux/arm64
+        #[arg(long, value_delimiter = ',', value_name = "PLATFORMS")]
+        platform: Vec<String>,
+        /// Image repository (default: deploy.registry.repository)
+        #[arg(long, value_name = "REPO")]
+        repository: Option<String>,
+        /// Load into the local image store instead of pushing (single platform only)
+        #[arg(long)]
+        no_push: bool,
+    },
+    /// Export stacker.yml to Kubernetes manifests or a Helm chart
+    Export {
+        #[command(subcommand)]
+        command: ExportCommands,
+    },
     /// Connect containerized apps with data pipes
     Pipe {
         #[command(subcommand)]
@@ -668,19 +745,28 @@ enum ServiceCommands {
         #[arg(long, value_name = "FILE")]
         file: Option<String>,
     },
-    /// Import custom services from a local Docker Compose file after a safety review
+    /// Import custom services from a Docker Compose file (local, GitHub or URL) after a safety review
     Import {
         /// Target custom service name for a single selected service
         name: String,
         /// Local Docker Compose file to review and import
-        #[arg(long, value_name = "PATH")]
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["from_github", "from_url"])]
         from_compose: Option<std::path::PathBuf>,
-        /// Planned future source; currently returns a safe not-yet-implemented error
-        #[arg(long, value_name = "OWNER/REPO")]
+        /// GitHub repository (owner/repo or URL) to fetch the Compose file from
+        #[arg(long, value_name = "OWNER/REPO", conflicts_with = "from_url")]
         from_github: Option<String>,
-        /// Planned future source; currently returns a safe not-yet-implemented error
-        #[arg(long, value_name = "URL")]
+        /// HTTPS URL of a Compose file to fetch; requires --sha256
+        #[arg(long, value_name = "URL", requires = "sha256")]
         from_url: Option<String>,
+        /// Branch, tag or commit to pin a --from-github import to (default: default branch)
+        #[arg(long = "ref", value_name = "REF", requires = "from_github")]
+        git_ref: Option<String>,
+        /// Compose file path inside the repository (default: compose.yaml, docker-compose.yml, ...)
+        #[arg(long, value_name = "PATH", requires = "from_github")]
+        compose_path: Option<String>,
+        /// Expected SHA-256 of the --from-url compose file; the import fails on a mismatch
+        #[arg(long, value_name = "HEX", requires = "from_url")]
+        sha256: Option<String>,
         /// Compose service name to import. Omit to import all image-backed services.
         #[arg(long, value_name = "COMPOSE_SERVICE")]
         service: Option<String>,
@@ -764,7 +850,7 @@ enum DeploymentCommands {
     },
     /// Preview or apply a deployment rollback
     Rollback {
-        /// Roll back to `previous` or a specific marketplace template version
+        /// Roll back to `previous`, a marketplace template version, or a release number
         #[arg(long, value_name = "TARGET")]
         to: String,
         /// Print a read-only rollback plan instead of applying it
@@ -779,6 +865,151 @@ enum DeploymentCommands {
         /// Confirm rollback apply
         #[arg(long, short = 'y')]
         confirm: bool,
+        /// Break another client's deploy lease (recorded in the lease audit log)
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ReleasesCommands {
+    /// List recorded releases, newest first
+    List {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Override deployment hash instead of using stacker.yml
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum BundleCommands {
+    /// Write a tarball with the rendered compose, saved images, proxy config and install script
+    Export {
+        /// Path to stacker.yml (default: ./stacker.yml)
+        #[arg(long, value_name = "FILE")]
+        file: Option<String>,
+        /// Output tarball (default: ./<project>-bundle.tar.gz)
+        #[arg(long, short = 'o', value_name = "PATH")]
+        output: Option<String>,
+    },
+    /// Verify, load and start a bundle on this host
+    Install {
+        /// Bundle tarball written by `stacker bundle export`
+        tarball: String,
+        /// Directory to install into
+        #[arg(long, default_value = ".")]
+        dir: String,
+        /// Load images but do not start the stack
+        #[arg(long)]
+        no_start: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum BackupCommands {
+    /// Archive an app's volumes, dumping its database first
+    Create {
+        /// App code whose volumes to back up
+        app: String,
+        /// Volume to archive (repeatable; default: all volumes of the app)
+        #[arg(long = "volume", value_name = "NAME")]
+        volumes: Vec<String>,
+        /// Database to dump (default: detected from the service image in stacker.yml)
+        #[arg(long, value_name = "ENGINE", value_parser = ["postgres", "mysql", "redis"], conflicts_with = "no_dump")]
+        database: Option<String>,
+        /// Archive volumes only, without a database dump
+        #[arg(long)]
+        no_dump: bool,
+        /// Store the backup in this S3 bucket instead of on the server
+        #[arg(long, value_name = "BUCKET")]
+        s3_bucket: Option<String>,
+        /// Key prefix inside the bucket
+        #[arg(long, value_name = "PREFIX", requires = "s3_bucket")]
+        s3_prefix: Option<String>,
+        /// Endpoint of an S3-compatible service
+        #[arg(long, value_name = "URL", requires = "s3_bucket")]
+        s3_endpoint: Option<String>,
+        /// Bucket region
+        #[arg(long, value_name = "REGION", requires = "s3_bucket")]
+        s3_region: Option<String>,
+        /// Keep only the newest N backups of this app and target
+        #[arg(long, value_name = "N")]
+        keep_last: Option<u32>,
+        /// Remove backups of this app and target older than N days
+        #[arg(long, value_name = "DAYS")]
+        max_age_days: Option<u32>,
+        /// Stop the container while its volumes are archived
+        #[arg(long)]
+        stop: bool,
+        /// Seconds to wait for the agent
+        #[arg(long, default_value_t = stacker::console::commands::cli::backup::DEFAULT_BACKUP_TIMEOUT_SECS)]
+        timeout: u64,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+    /// List backups still kept, newest first
+    List {
+        /// Only backups of this app
+        #[arg(long)]
+        app: Option<String>,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+    /// Restore an app's volumes (and database dump) from a backup
+    Restore {
+        /// Backup id from `stacker backup list`
+        backup_id: String,
+        /// Volume to restore (repeatable; default: every volume in the backup)
+        #[arg(long = "volume", value_name = "NAME")]
+        volumes: Vec<String>,
+        /// Do not replay the database dump
+        #[arg(long)]
+        no_database: bool,
+        /// Seconds to wait for the agent
+        #[arg(long, default_value_t = stacker::console::commands::cli::backup::DEFAULT_BACKUP_TIMEOUT_SECS)]
+        timeout: u64,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PreviewCommands {
+    /// Deploy the current branch to <branch>.<deploy.preview.base_domain>
+    Up {
+        /// Branch to preview (defaults to the CI branch or `git rev-parse`)
+        #[arg(long)]
+        branch: Option<String>,
+        /// Hours until the preview is torn down (overrides deploy.preview.ttl_hours)
+        #[arg(long, value_name = "HOURS", value_parser = clap::value_parser!(u32).range(1..=720))]
+        ttl: Option<u32>,
+    },
+    /// Tear down the preview of the current branch
+    Down {
+        /// Branch whose preview to remove
+        #[arg(long)]
+        branch: Option<String>,
+    },
+    /// List active previews
+    List {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -806,12 +1037,16 @@ enum ConfigCommands {
     Validate {
         #[arg(long, value_name = "FILE")]
         file: Option<String>,
+        /// Also report features the given export target cannot express (k8s)
+        #[arg(long, value_name = "TARGET")]
+        target: Option<String>,
     },
     /// Show resolved configuration
     Show {
         #[arg(long, value_name = "FILE")]
         file: Option<String>,
-        /// Show paths, hash/version metadata, and contributing layers without values
+        /// Show paths, hash/version metadata, contributing layers and the source
+        /// file of every merged value (secret-like values redacted)
         #[arg(long)]
         resolved: bool,
     },
@@ -1151,6 +1386,31 @@ enum SecretsAppsCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum ExportCommands {
+    /// Generate Deployments, Services, PVCs and an Ingress
+    K8s {
+        /// Path to stacker.yml (default: ./stacker.yml)
+        #[arg(long, value_name = "FILE")]
+        file: Option<String>,
+        /// Output directory (default: ./k8s)
+        #[arg(long, short = 'o', value_name = "DIR")]
+        output: Option<String>,
+        /// Print a single multi-document YAML stream instead of writing files
+        #[arg(long, conflicts_with = "output")]
+        stdout: bool,
+    },
+    /// Generate a Helm chart with images, replicas and ingress hosts as values
+    Helm {
+        /// Path to stacker.yml (default: ./stacker.yml)
+        #[arg(long, value_name = "FILE")]
+        file: Option<String>,
+        /// Chart directory (default: ./helm)
+        #[arg(long, short = 'o', value_name = "DIR")]
+        output: Option<String>,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 enum CiCommands {
     /// Export a CI/CD pipeline configuration file
@@ -1330,6 +1590,21 @@ enum AgentCommands {
         #[arg(long)]
         deployment: Option<String>,
     },
+    /// Show container CPU, memory, network and disk usage
+    Stats {
+        /// App code (default: all containers)
+        #[arg(long)]
+        app: Option<String>,
+        /// Summarise pushed samples over a window (e.g. 15m, 1h, 7d) instead of a live reading
+        #[arg(long, value_name = "WINDOW")]
+        since: Option<String>,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
     /// Fetch container logs from the remote deployment
     Logs {
         /// App code to fetch logs for (default: statuspanel + statuspanel_agent)
@@ -1529,6 +1804,53 @@ enum AgentCommands {
         #[arg(long)]
         deployment: Option<String>,
     },
+    /// Open an interactive shell in an app container
+    Shell {
+        /// App code of the container
+        app: String,
+        /// Program to run instead of the container's default shell
+        #[arg(long)]
+        shell: Option<String>,
+        /// Close the session after this many seconds without input (max 3600)
+        #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u32).range(1..=3600))]
+        idle_timeout: Option<u32>,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+    /// Copy a file into or out of an app container (`<local> <app>:<path>` or the reverse)
+    Cp {
+        /// Local file, or `<app>:<path>` in a container
+        source: String,
+        /// Local file or directory, or `<app>:<path>` in a container
+        destination: String,
+        /// Print a JSON summary
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+    /// Run agent commands on a cron schedule
+    Schedule {
+        #[command(subcommand)]
+        command: AgentScheduleCommands,
+    },
+    /// Upgrade the Status Panel agent to the latest or a given release
+    Upgrade {
+        /// Release to install (default: latest known to the server)
+        #[arg(long)]
+        version: Option<String>,
+        /// Image to pull instead of the default Status Panel image
+        #[arg(long)]
+        image: Option<String>,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
     /// Install the Status Panel agent on an existing deployed server
     Install {
         /// Path to stacker.yml (default: ./stacker.yml)
@@ -1547,6 +1869,55 @@ enum AgentCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum AgentScheduleCommands {
+    /// Queue a command every time a cron expression (UTC) matches
+    Add {
+        /// Cron expression, e.g. "0 3 * * *" or "@daily"
+        cron: String,
+        /// Command type (e.g. restart, health, probe_endpoints)
+        command_type: String,
+        /// JSON parameters
+        #[arg(long)]
+        params: Option<String>,
+        /// Label shown in `stacker agent schedule list`
+        #[arg(long)]
+        name: Option<String>,
+        /// Command priority (low, normal, high, critical)
+        #[arg(long)]
+        priority: Option<String>,
+        /// Agent-side timeout in seconds
+        #[arg(long)]
+        timeout: Option<i32>,
+        /// What to do with a run the server missed, e.g. while it was down
+        #[arg(long, value_name = "POLICY", value_parser = ["skip", "run-once"], default_value = "skip")]
+        missed_run: String,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+    /// List command schedules of the deployment
+    List {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+    /// Delete a command schedule
+    Rm {
+        /// Schedule id from `stacker agent schedule list`
+        schedule_id: String,
+        /// Deployment hash
+        #[arg(long)]
+        deployment: Option<String>,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 enum AgentListCommands {
     /// List apps deployed for the target deployment
@@ -1776,6 +2147,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    if let Some(runtime) = &cli.container_runtime {
+        std::env::set_var(stacker::cli::proxy_manager::CONTAINER_RUNTIME_ENV, runtime);
+    }
+
     let Some(subcommand) = cli.command else {
         print_banner();
         let mut cmd = Cli::command();
@@ -1917,6 +2292,9 @@ fn get_command(
             apply_plan,
             no_hooks,
             allow_untrusted_hooks,
+            auto_rollback,
+            health_window,
+            force,
         } => Box::new(
             stacker::console::commands::cli::deploy::DeployCommand::new(
                 target,
@@ -1934,7 +2312,9 @@ fn get_command(
             .with_runtime(runtime)
             .with_plan(plan)
             .with_apply_plan(apply_plan)
-            .with_hook_flags(no_hooks, allow_untrusted_hooks),
+            .with_hook_flags(no_hooks, allow_untrusted_hooks)
+            .with_auto_rollback(auto_rollback, health_window)
+            .with_force_lease(force),
         ),
         StackerCommands::Connect { handoff } => {
             Box::new(stacker::console::commands::cli::connect::ConnectCommand::new(handoff))
@@ -1944,9 +2324,11 @@ fn get_command(
             follow,
             tail,
             since,
-        } => Box::new(stacker::console::commands::cli::logs::LogsCommand::new(
-            service, follow, tail, since,
-        )),
+            search,
+        } => Box::new(
+            stacker::console::commands::cli::logs::LogsCommand::new(service, follow, tail, since)
+                .with_search(search),
+        ),
         StackerCommands::Status { json, watch } => Box::new(
             stacker::console::commands::cli::status::StatusCommand::new(json, watch),
         ),
@@ -1967,10 +2349,12 @@ fn get_command(
                 apply_plan,
                 deployment,
                 confirm,
+                force,
             } => Box::new(
                 stacker::console::commands::cli::deployment::DeploymentRollbackCommand::new(
                     to, plan, apply_plan, confirm, deployment,
-                ),
+                )
+                .with_force_lease(force),
             ),
         },
         StackerCommands::Explain { command } => match command {
@@ -1984,13 +2368,119 @@ fn get_command(
         StackerCommands::Destroy { volumes, confirm } => Box::new(
             stacker::console::commands::cli::destroy::DestroyCommand::new(volumes, confirm),
         ),
-        StackerCommands::Rollback { version, confirm } => Box::new(
-            stacker::console::commands::cli::rollback::RollbackCommand::new(version, confirm),
+        StackerCommands::Releases { command } => match command {
+            ReleasesCommands::List { json, deployment } => Box::new(
+                stacker::console::commands::cli::releases::ReleasesListCommand::new(
+                    json, deployment,
+                ),
+            ),
+        },
+        StackerCommands::Bundle { command } => match command {
+            BundleCommands::Export { file, output } => Box::new(
+                stacker::console::commands::cli::bundle::BundleExportCommand::new(file, output),
+            ),
+            BundleCommands::Install {
+                tarball,
+                dir,
+                no_start,
+            } => Box::new(
+                stacker::console::commands::cli::bundle::BundleInstallCommand::new(
+                    tarball, dir, no_start,
+                ),
+            ),
+        },
+        StackerCommands::Backup { command } => match command {
+            BackupCommands::Create {
+                app,
+                volumes,
+                database,
+                no_dump,
+                s3_bucket,
+                s3_prefix,
+                s3_endpoint,
+                s3_region,
+                keep_last,
+                max_age_days,
+                stop,
+                timeout,
+                json,
+                deployment,
+            } => Box::new(
+                stacker::console::commands::cli::backup::BackupCreateCommand::new(
+                    app,
+                    volumes,
+                    database.as_deref().and_then(DatabaseEngine::from_name),
+                    no_dump,
+                    stacker::console::commands::cli::backup::BackupS3Options {
+                        bucket: s3_bucket,
+                        prefix: s3_prefix,
+                        endpoint: s3_endpoint,
+                        region: s3_region,
+                    },
+                    BackupRetention {
+                        keep_last,
+                        max_age_days,
+                    },
+                    stop,
+                    timeout,
+                    json,
+                    deployment,
+                ),
+            ),
+            BackupCommands::List {
+                app,
+                json,
+                deployment,
+            } => Box::new(
+                stacker::console::commands::cli::backup::BackupListCommand::new(
+                    app, json, deployment,
+                ),
+            ),
+            BackupCommands::Restore {
+                backup_id,
+                volumes,
+                no_database,
+                timeout,
+                json,
+                deployment,
+            } => Box::new(
+                stacker::console::commands::cli::backup::BackupRestoreCommand::new(
+                    backup_id,
+                    volumes,
+                    no_database,
+                    timeout,
+                    json,
+                    deployment,
+                ),
+            ),
+        },
+        StackerCommands::Preview { command } => match command {
+            PreviewCommands::Up { branch, ttl } => Box::new(
+                stacker::console::commands::cli::preview::PreviewUpCommand::new(branch, ttl),
+            ),
+            PreviewCommands::Down { branch } => {
+                Box::new(stacker::console::commands::cli::preview::PreviewDownCommand::new(branch))
+            }
+            PreviewCommands::List { json } => {
+                Box::new(stacker::console::commands::cli::preview::PreviewListCommand::new(json))
+            }
+        },
+        StackerCommands::Rollback {
+            release,
+            version,
+            plan,
+            confirm,
+            force,
+        } => Box::new(
+            stacker::console::commands::cli::rollback::RollbackCommand::new(version, confirm)
+                .with_release(release, plan)
+                .with_force_lease(force),
         ),
         StackerCommands::Config { command: cfg_cmd } => match cfg_cmd {
-            ConfigCommands::Validate { file } => {
-                Box::new(stacker::console::commands::cli::config::ConfigValidateCommand::new(file))
-            }
+            ConfigCommands::Validate { file, target } => Box::new(
+                stacker::console::commands::cli::config::ConfigValidateCommand::new(file)
+                    .with_target(target),
+            ),
             ConfigCommands::Show { file, resolved } => Box::new(
                 stacker::console::commands::cli::config::ConfigShowCommand::new(file, resolved),
             ),
@@ -2156,6 +2646,9 @@ fn get_command(
                 from_compose,
                 from_github,
                 from_url,
+                git_ref,
+                compose_path,
+                sha256,
                 service,
                 rename,
                 file,
@@ -2174,7 +2667,9 @@ fn get_command(
                     review,
                     yes,
                     json,
-                ),
+                )
+                .with_remote_options(git_ref, compose_path)
+                .with_sha256(sha256),
             ),
             ServiceCommands::Deploy {
                 name,
@@ -2381,6 +2876,30 @@ fn get_command(
                 stacker::console::commands::cli::ci::CiValidateCommand::new(platform),
             ),
         },
+        StackerCommands::Build {
+            file,
+            platform,
+            repository,
+            no_push,
+        } => Box::new(stacker::console::commands::cli::build::BuildCommand::new(
+            file, platform, repository, no_push,
+        )),
+        StackerCommands::Export {
+            command: export_cmd,
+        } => match export_cmd {
+            ExportCommands::K8s {
+                file,
+                output,
+                stdout,
+            } => Box::new(
+                stacker::console::commands::cli::export::ExportK8sCommand::new(
+                    file, output, stdout,
+                ),
+            ),
+            ExportCommands::Helm { file, output } => Box::new(
+                stacker::console::commands::cli::export::ExportHelmCommand::new(file, output),
+            ),
+        },
         StackerCommands::Pipe { command: pipe_cmd } => {
             use stacker::console::commands::cli::pipe;
             match pipe_cmd {
@@ -2487,6 +3006,12 @@ fn get_command(
                 } => Box::new(agent::AgentHealthCommand::new(
                     app, json, deployment, system,
                 )),
+                AgentCommands::Stats {
+                    app,
+                    since,
+                    json,
+                    deployment,
+                } => Box::new(agent::AgentStatsCommand::new(app, since, json, deployment)),
                 AgentCommands::Logs {
                     app,
                     limit,
@@ -2613,6 +3138,69 @@ fn get_command(
                     json,
                     deployment,
                 )),
+                AgentCommands::Shell {
+                    app,
+                    shell,
+                    idle_timeout,
+                    deployment,
+                } => Box::new(agent::AgentShellCommand::new(
+                    app,
+                    shell,
+                    idle_timeout,
+                    deployment,
+                )),
+                AgentCommands::Schedule { command } => match command {
+                    AgentScheduleCommands::Add {
+                        cron,
+                        command_type,
+                        params,
+                        name,
+                        priority,
+                        timeout,
+                        missed_run,
+                        json,
+                        deployment,
+                    } => Box::new(agent::AgentScheduleAddCommand::new(
+                        cron,
+                        command_type,
+                        params,
+                        name,
+                        priority,
+                        timeout,
+                        MissedRunPolicy::from_name(&missed_run).unwrap_or_default(),
+                        json,
+                        deployment,
+                    )),
+                    AgentScheduleCommands::List { json, deployment } => {
+                        Box::new(agent::AgentScheduleListCommand::new(json, deployment))
+                    }
+                    AgentScheduleCommands::Rm {
+                        schedule_id,
+                        deployment,
+                    } => Box::new(agent::AgentScheduleRemoveCommand::new(
+                        schedule_id,
+                        deployment,
+                    )),
+                },
+                AgentCommands::Cp {
+                    source,
+                    destination,
+                    json,
+                    deployment,
+                } => Box::new(agent::AgentCpCommand::new(
+                    source,
+                    destination,
+                    json,
+                    deployment,
+                )),
+                AgentCommands::Upgrade {
+                    version,
+                    image,
+                    json,
+                    deployment,
+                } => Box::new(agent::AgentUpgradeCommand::new(
+                    version, image, json, deployment,
+                )),
                 AgentCommands::Install {
                     file,
                     persist_config,
@@ -2806,6 +3394,364 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_deploy_parses_auto_rollback_window() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "deploy",
+            "--auto-rollback",
+            "--health-window",
+            "300",
+        ])
+        .unwrap();
+
+        match cli.command.unwrap() {
+            StackerCommands::Deploy {
+                auto_rollback,
+                health_window,
+                ..
+            } => {
+                assert!(auto_rollback);
+                assert_eq!(health_window, 300);
+            }
+            _ => panic!("expected deploy command"),
+        }
+        assert!(Cli::try_parse_from(["stacker", "deploy", "--health-window", "300"]).is_err());
+    }
+
+    #[test]
+    fn test_rollback_parses_release_or_version() {
+        let cli = Cli::try_parse_from(["stacker", "rollback", "r3", "--plan"]).unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Rollback {
+                release,
+                version,
+                plan,
+                ..
+            } => {
+                assert_eq!(release.as_deref(), Some("r3"));
+                assert!(version.is_none());
+                assert!(plan);
+            }
+            _ => panic!("expected rollback command"),
+        }
+
+        let cli = Cli::try_parse_from(["stacker", "rollback", "--version", "1.2.0", "-y"]).unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Rollback {
+                release, version, ..
+            } => {
+                assert!(release.is_none());
+                assert_eq!(version.as_deref(), Some("1.2.0"));
+            }
+            _ => panic!("expected rollback command"),
+        }
+
+        assert!(Cli::try_parse_from(["stacker", "rollback"]).is_err());
+        assert!(Cli::try_parse_from(["stacker", "rollback", "2", "--version", "1.2.0"]).is_err());
+    }
+
+    #[test]
+    fn test_preview_parses_branch_and_ttl() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "preview",
+            "up",
+            "--branch",
+            "feature/login",
+            "--ttl",
+            "24",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Preview {
+                command: PreviewCommands::Up { branch, ttl },
+            } => {
+                assert_eq!(branch.as_deref(), Some("feature/login"));
+                assert_eq!(ttl, Some(24));
+            }
+            _ => panic!("expected preview up command"),
+        }
+
+        assert!(Cli::try_parse_from(["stacker", "preview", "up", "--ttl", "0"]).is_err());
+        assert!(Cli::try_parse_from(["stacker", "preview", "down"]).is_ok());
+    }
+
+    #[test]
+    fn test_agent_stats_parses_since_window() {
+        let cli =
+            Cli::try_parse_from(["stacker", "agent", "stats", "--since", "1h", "--app", "web"])
+                .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Agent {
+                command: AgentCommands::Stats { app, since, .. },
+            } => {
+                assert_eq!(app.as_deref(), Some("web"));
+                assert_eq!(since.as_deref(), Some("1h"));
+            }
+            _ => panic!("expected agent stats command"),
+        }
+    }
+
+    #[test]
+    fn test_agent_upgrade_parses_version() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "agent",
+            "upgrade",
+            "--version",
+            "0.4.2",
+            "--deployment",
+            "dep-1",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Agent {
+                command:
+                    AgentCommands::Upgrade {
+                        version,
+                        image,
+                        deployment,
+                        ..
+                    },
+            } => {
+                assert_eq!(version.as_deref(), Some("0.4.2"));
+                assert_eq!(image, None);
+                assert_eq!(deployment.as_deref(), Some("dep-1"));
+            }
+            _ => panic!("expected agent upgrade command"),
+        }
+    }
+
+    #[test]
+    fn test_agent_cp_parses_source_and_destination() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "agent",
+            "cp",
+            "./nginx.conf",
+            "web:/etc/nginx/nginx.conf",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Agent {
+                command:
+                    AgentCommands::Cp {
+                        source,
+                        destination,
+                        json,
+                        deployment,
+                    },
+            } => {
+                assert_eq!(source, "./nginx.conf");
+                assert_eq!(destination, "web:/etc/nginx/nginx.conf");
+                assert!(!json);
+                assert_eq!(deployment, None);
+            }
+            _ => panic!("expected agent cp command"),
+        }
+    }
+
+    #[test]
+    fn test_logs_search_parses_and_conflicts_with_follow() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "logs",
+            "--search",
+            "timeout",
+            "--since",
+            "24h",
+            "--service",
+            "api",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Logs {
+                service,
+                since,
+                search,
+                follow,
+                ..
+            } => {
+                assert_eq!(search.as_deref(), Some("timeout"));
+                assert_eq!(since.as_deref(), Some("24h"));
+                assert_eq!(service.as_deref(), Some("api"));
+                assert!(!follow);
+            }
+            _ => panic!("expected logs command"),
+        }
+        assert!(Cli::try_parse_from(["stacker", "logs", "--search", "x", "--follow"]).is_err());
+    }
+
+    #[test]
+    fn test_agent_shell_parses_app_and_idle_timeout() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "agent",
+            "shell",
+            "web",
+            "--shell",
+            "/bin/bash",
+            "--idle-timeout",
+            "600",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Agent {
+                command:
+                    AgentCommands::Shell {
+                        app,
+                        shell,
+                        idle_timeout,
+                        ..
+                    },
+            } => {
+                assert_eq!(app, "web");
+                assert_eq!(shell.as_deref(), Some("/bin/bash"));
+                assert_eq!(idle_timeout, Some(600));
+            }
+            _ => panic!("expected agent shell command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["stacker", "agent", "shell", "web", "--idle-timeout", "0"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_agent_schedule_add_parses_cron_and_policy() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "agent",
+            "schedule",
+            "add",
+            "0 3 * * *",
+            "restart",
+            "--params",
+            r#"{"app_code":"worker"}"#,
+            "--missed-run",
+            "run-once",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Agent {
+                command:
+                    AgentCommands::Schedule {
+                        command:
+                            AgentScheduleCommands::Add {
+                                cron,
+                                command_type,
+                                params,
+                                missed_run,
+                                ..
+                            },
+                    },
+            } => {
+                assert_eq!(cron, "0 3 * * *");
+                assert_eq!(command_type, "restart");
+                assert_eq!(params.as_deref(), Some(r#"{"app_code":"worker"}"#));
+                assert_eq!(missed_run, "run-once");
+            }
+            _ => panic!("expected agent schedule add command"),
+        }
+    }
+
+    #[test]
+    fn test_backup_create_parses_s3_target_and_retention() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "backup",
+            "create",
+            "db",
+            "--volume",
+            "pg_data",
+            "--s3-bucket",
+            "nightly",
+            "--keep-last",
+            "7",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Backup {
+                command:
+                    BackupCommands::Create {
+                        app,
+                        volumes,
+                        database,
+                        s3_bucket,
+                        keep_last,
+                        timeout,
+                        ..
+                    },
+            } => {
+                assert_eq!(app, "db");
+                assert_eq!(volumes, vec!["pg_data".to_string()]);
+                assert_eq!(database, None);
+                assert_eq!(s3_bucket.as_deref(), Some("nightly"));
+                assert_eq!(keep_last, Some(7));
+                assert_eq!(timeout, 900);
+            }
+            _ => panic!("expected backup create command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["stacker", "backup", "create", "db", "--s3-prefix", "x"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_bundle_install_parses_dir_and_no_start() {
+        let cli = Cli::try_parse_from([
+            "stacker",
+            "bundle",
+            "install",
+            "shop-bundle.tar.gz",
+            "--dir",
+            "/opt/shop",
+            "--no-start",
+        ])
+        .unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Bundle {
+                command:
+                    BundleCommands::Install {
+                        tarball,
+                        dir,
+                        no_start,
+                    },
+            } => {
+                assert_eq!(tarball, "shop-bundle.tar.gz");
+                assert_eq!(dir, "/opt/shop");
+                assert!(no_start);
+            }
+            _ => panic!("expected bundle install command"),
+        }
+
+        assert!(Cli::try_parse_from(["stacker", "bundle", "install"]).is_err());
+        assert!(Cli::try_parse_from(["stacker", "bundle", "export", "-o", "x.tar.gz"]).is_ok());
+    }
+
+    #[test]
+    fn test_deploy_and_rollback_parse_force_lease() {
+        let cli = Cli::try_parse_from(["stacker", "deploy", "--force"]).unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Deploy { force, .. } => assert!(force),
+            _ => panic!("expected deploy command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["stacker", "rollback", "previous", "-y", "--force"]).unwrap();
+        match cli.command.unwrap() {
+            StackerCommands::Rollback { force, .. } => assert!(force),
+            _ => panic!("expected rollback command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["stacker", "rollback", "--version", "1.2.0", "--force"]).is_err()
+        );
+    }
+
     #[test]
     fn test_whoami_parses() {
         let cli = Cli::try_parse_from(["stacker", "whoami"]).unwrap();
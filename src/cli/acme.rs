@@ -0,0 +1,694 @@
+//! ACME (Let's Encrypt) certificates for `ssl: auto` domains behind the
+//! generated nginx proxy.
+//!
+//! A certbot companion service obtains certificates over HTTP-01 using a
+//! webroot shared with nginx, and renews them on a loop. nginx starts with
+//! HTTP only (challenge location + redirect); a small wrapper script enables
+//! each domain's HTTPS server block once its certificate exists and reloads
+//! nginx periodically so renewed certificates are picked up.
+//!
+//! ```text
+//! .stacker/nginx/
+//!   conf.d/            # HTTP server blocks (always loaded)
+//!   https/<domain>.conf  # HTTPS blocks, enabled once the cert is issued
+//!   htpasswd/          # basic-auth users for protected routes
+//!   acme-nginx.sh      # nginx wrapper: bootstrap + periodic reload
+//!   acme-certbot.sh    # certbot issuance + renewal loop
+//! ```
+//!
+//! Traefik and Caddy obtain certificates themselves; this module only
+//! supplies their ACME account settings (email, CA directory, storage).
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::cli::config_parser::{AcmeConfig, DomainConfig, ProxyType, SslMode, StackerConfig};
+use crate::cli::error::{CliError, Severity, ValidationIssue};
+use crate::cli::generator::compose::ComposeService;
+use crate::cli::install_runner::{resolve_compose_cmd, CommandExecutor};
+use crate::cli::proxy_manager::{
+    generate_nginx_https_block, generate_nginx_redirect_block, generate_nginx_server_block,
+    nginx_htpasswd_files,
+};
+use crate::cli::stacker_client::{AgentEnqueueRequest, StackerClient};
+use crate::forms::status_panel::{CertificateStatusCommandReport, CertificateStatusCommandRequest};
+
+pub const CERTBOT_SERVICE: &str = "certbot";
+pub const CERTBOT_IMAGE: &str = "certbot/certbot:latest";
+/// Webroot shared by nginx (read) and certbot (write) for HTTP-01 challenges.
+pub const ACME_WEBROOT: &str = "/var/www/certbot";
+pub const WEBROOT_VOLUME: &str = "certbot-webroot";
+pub const CERTS_VOLUME: &str = "letsencrypt";
+/// Directory under the compose output dir holding the nginx assets.
+pub const NGINX_ASSETS_DIR: &str = "nginx";
+/// Certificate resolver referenced by Traefik routers for `ssl: auto`.
+pub const TRAEFIK_CERT_RESOLVER: &str = "letsencrypt";
+/// Named volume holding Traefik's `acme.json`.
+pub const TRAEFIK_ACME_VOLUME: &str = "traefik-acme";
+pub const LETSENCRYPT_STAGING_DIRECTORY: &str =
+    "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+const NGINX_SCRIPT: &str = "acme-nginx.sh";
+const CERTBOT_SCRIPT: &str = "acme-certbot.sh";
+
+/// certbot starts renewing at 30 days remaining and retries twice a day, so
+/// a certificate still below this threshold means renewal is failing.
+const EXPIRY_WARNING_DAYS: i64 = 14;
+/// `certbot certificates` output the renewal loop leaves in the certificate
+/// volume. The agent's read-only `certificate_status` command returns it
+/// without running certbot in the container.
+pub const CERTIFICATE_STATUS_FILE: &str = "/etc/letsencrypt/stacker-certificates.txt";
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Which domains need ACME
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// `ssl: auto` domains served by the generated nginx proxy. Nginx Proxy
+/// Manager and Traefik handle their own certificates.
+pub fn auto_ssl_domains(config: &StackerConfig) -> Vec<&DomainConfig> {
+    if config.proxy.proxy_type != ProxyType::Nginx {
+        return Vec::new();
+    }
+    config
+        .proxy
+        .domains
+        .iter()
+        .filter(|d| d.ssl == SslMode::Auto)
+        .collect()
+}
+
+pub fn acme_enabled(config: &StackerConfig) -> bool {
+    !auto_ssl_domains(config).is_empty()
+}
+
+/// `ssl: auto` domains for every proxy that issues its own certificates:
+/// the generated nginx (via certbot), Traefik and Caddy.
+pub fn acme_managed_domains(config: &StackerConfig) -> Vec<&DomainConfig> {
+    if !matches!(
+        config.proxy.proxy_type,
+        ProxyType::Nginx | ProxyType::Traefik | ProxyType::Caddy
+    ) {
+        return Vec::new();
+    }
+    config
+        .proxy
+        .domains
+        .iter()
+        .filter(|d| d.ssl == SslMode::Auto)
+        .collect()
+}
+
+/// The ACME directory to use, or `None` for the Let's Encrypt production default.
+pub fn acme_directory(acme: &AcmeConfig) -> Option<&str> {
+    match acme.server.as_deref() {
+        Some(server) => Some(server),
+        None if acme.staging => Some(LETSENCRYPT_STAGING_DIRECTORY),
+        None => None,
+    }
+}
+
+/// Warnings reported by `stacker config validate` for ACME-managed domains.
+pub fn acme_issues(config: &StackerConfig) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let domains = acme_managed_domains(config);
+
+    for (index, domain) in config.proxy.domains.iter().enumerate() {
+        if !domains.iter().any(|d| d.domain == domain.domain) {
+            continue;
+        }
+        if !is_public_hostname(&domain.domain) {
+            issues.push(ValidationIssue {
+                severity: Severity::Warning,
+                code: "ACME001".to_string(),
+                message: format!(
+                    "'{}' is not a public DNS name; Let's Encrypt cannot issue for it. Use ssl: off or ssl: manual",
+                    domain.domain
+                ),
+                field: Some(format!("proxy.domains[{index}].ssl")),
+            });
+        }
+    }
+
+    let has_email = config
+        .proxy
+        .acme
+        .as_ref()
+        .and_then(|acme| acme.email.as_deref())
+        .is_some_and(|email| !email.trim().is_empty());
+    if !domains.is_empty() && !has_email {
+        issues.push(ValidationIssue {
+            severity: Severity::Info,
+            code: "ACME002".to_string(),
+            message: "no proxy.acme.email set; certificates are requested without an account email, so expiry notices will not be sent".to_string(),
+            field: Some("proxy.acme.email".to_string()),
+        });
+    }
+
+    issues
+}
+
+fn is_public_hostname(domain: &str) -> bool {
+    let lower = domain.to_ascii_lowercase();
+    if lower == "localhost"
+        || lower.ends_with(".localhost")
+        || lower.ends_with(".local")
+        || lower.ends_with(".internal")
+        || !lower.contains('.')
+    {
+        return false;
+    }
+    lower.parse::<std::net::IpAddr>().is_err()
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Compose wiring
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Mount the challenge webroot, certificates and bootstrap script into
+/// the nginx service and run it through the wrapper.
+pub fn attach_to_nginx(svc: &mut ComposeService) {
+    svc.volumes.push(format!(
+        "./{NGINX_ASSETS_DIR}/https:/etc/nginx/stacker-https:ro"
+    ));
+    svc.volumes.push(format!(
+        "./{NGINX_ASSETS_DIR}/{NGINX_SCRIPT}:/stacker/{NGINX_SCRIPT}:ro"
+    ));
+    svc.volumes
+        .push(format!("{WEBROOT_VOLUME}:{ACME_WEBROOT}:ro"));
+    svc.volumes
+        .push(format!("{CERTS_VOLUME}:/etc/letsencrypt:ro"));
+    svc.command = Some(format!("/bin/sh /stacker/{NGINX_SCRIPT}"));
+}
+
+/// Static Traefik arguments for an HTTP-01 certificate resolver, plus the
+/// `acme.json` volume the resolver stores certificates in.
+pub fn attach_to_traefik(svc: &mut ComposeService, acme: &AcmeConfig) {
+    let resolver = format!("--certificatesresolvers.{TRAEFIK_CERT_RESOLVER}.acme");
+    let mut args = vec![
+        format!("{resolver}.httpchallenge=true"),
+        format!("{resolver}.httpchallenge.entrypoint=web"),
+        format!("{resolver}.storage=/letsencrypt/acme.json"),
+    ];
+    if let Some(email) = acme.email.as_deref().filter(|e| !e.trim().is_empty()) {
+        args.push(format!("{resolver}.email={email}"));
+    }
+    if let Some(directory) = acme_directory(acme) {
+        args.push(format!("{resolver}.caserver={directory}"));
+    }
+
+    let command = svc.command.get_or_insert_with(String::new);
+    for arg in args {
+        if !command.is_empty() {
+            command.push(' ');
+        }
+        command.push_str(&arg);
+    }
+    svc.volumes
+        .push(format!("{TRAEFIK_ACME_VOLUME}:/letsencrypt"));
+}
+
+/// The certbot companion service.
+pub fn certbot_service() -> ComposeService {
+    ComposeService {
+        name: CERTBOT_SERVICE.to_string(),
+        image: Some(CERTBOT_IMAGE.to_string()),
+        entrypoint: Some(format!("/bin/sh /stacker/{CERTBOT_SCRIPT}")),
+        volumes: vec![
+            format!("{WEBROOT_VOLUME}:{ACME_WEBROOT}"),
+            format!("{CERTS_VOLUME}:/etc/letsencrypt"),
+            format!("./{NGINX_ASSETS_DIR}/{CERTBOT_SCRIPT}:/stacker/{CERTBOT_SCRIPT}:ro"),
+        ],
+        depends_on: vec!["nginx".to_string()],
+        ..Default::default()
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// nginx assets
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Files referenced by the generated nginx service, keyed by path relative
+/// to [`NGINX_ASSETS_DIR`].
+pub fn nginx_assets(config: &StackerConfig) -> Result<BTreeMap<String, String>, CliError> {
+    let mut files = BTreeMap::new();
+    if config.proxy.proxy_type != ProxyType::Nginx {
+        return Ok(files);
+    }
+
+    for domain in &config.proxy.domains {
+        let filename = format!("{}.conf", domain.domain.replace(['.', '/'], "_"));
+        let content = if domain.ssl == SslMode::Auto {
+            files.insert(
+                format!("https/{}.conf", domain.domain),
+                generate_nginx_https_block(domain)?,
+            );
+            generate_nginx_redirect_block(domain)?
+        } else {
+            generate_nginx_server_block(domain)?
+        };
+        files.insert(format!("conf.d/{filename}"), content);
+        for (name, users) in nginx_htpasswd_files(domain) {
+            files.insert(format!("htpasswd/{name}"), users);
+        }
+    }
+
+    let domains = auto_ssl_domains(config);
+    if !domains.is_empty() {
+        files.insert(
+            "conf.d/00-stacker-acme.conf".to_string(),
+            "# Generated by stacker: HTTPS server blocks enabled once ACME certificates exist\ninclude /etc/nginx/stacker-enabled/*.conf;\n".to_string(),
+        );
+        files.insert(NGINX_SCRIPT.to_string(), NGINX_ACME_SCRIPT.to_string());
+        let acme = config.proxy.acme.clone().unwrap_or_default();
+        files.insert(
+            CERTBOT_SCRIPT.to_string(),
+            render_certbot_script(&domains, &acme),
+        );
+    }
+
+    Ok(files)
+}
+
+/// Write [`nginx_assets`] next to a generated compose file. Stale domain
+/// configs from a previous run are removed first.
+pub fn write_nginx_assets(output_dir: &Path, config: &StackerConfig) -> Result<(), CliError> {
+    let files = nginx_assets(config)?;
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let root = output_dir.join(NGINX_ASSETS_DIR);
+    for sub in ["conf.d", "https", "htpasswd"] {
+        let dir = root.join(sub);
+        if dir.is_dir() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(&dir)?;
+    }
+    for (relative, content) in &files {
+        let path: PathBuf = root.join(relative);
+        std::fs::write(&path, content)?;
+    }
+    Ok(())
+}
+
+/// nginx wrapper: start with whatever HTTPS blocks already have certificates,
+/// enable the rest as certbot issues them, and reload every 6 hours.
+const NGINX_ACME_SCRIPT: &str = r#"#!/bin/sh
+# Generated by stacker: serve HTTP first, enable each ssl: auto HTTPS server
+# block once its certificate exists, reload periodically for renewals.
+AVAILABLE=/etc/nginx/stacker-https
+ENABLED=/etc/nginx/stacker-enabled
+mkdir -p "$ENABLED"
+
+enable_https() {
+  enabled=1
+  for conf in "$AVAILABLE"/*.conf; do
+    [ -e "$conf" ] || continue
+    domain=$(basename "$conf" .conf)
+    if [ -s "/etc/letsencrypt/live/$domain/fullchain.pem" ] && [ ! -e "$ENABLED/$domain.conf" ]; then
+      cp "$conf" "$ENABLED/$domain.conf"
+      echo "stacker: enabling HTTPS for $domain"
+      enabled=0
+    fi
+  done
+  return $enabled
+}
+
+enable_https
+nginx -g 'daemon off;' &
+nginx_pid=$!
+trap 'kill -TERM $nginx_pid' TERM INT
+
+elapsed=0
+while kill -0 "$nginx_pid" 2>/dev/null; do
+  sleep 60 & wait $!
+  elapsed=$((elapsed + 60))
+  if enable_https || [ "$elapsed" -ge 21600 ]; then
+    elapsed=0
+    nginx -s reload
+  fi
+done
+wait "$nginx_pid"
+"#;
+
+/// certbot loop: issue missing certificates (retrying every 5 minutes until
+/// DNS and port 80 are reachable), then `certbot renew` twice a day.
+pub fn render_certbot_script(domains: &[&DomainConfig], acme: &AcmeConfig) -> String {
+    let mut flags = vec![
+        "--non-interactive".to_string(),
+        "--agree-tos".to_string(),
+        "--keep-until-expiring".to_string(),
+    ];
+    match acme.email.as_deref().map(str::trim) {
+        Some(email) if !email.is_empty() => {
+            flags.push(format!("--email {}", shell_quote(email)));
+        }
+        _ => flags.push("--register-unsafely-without-email".to_string()),
+    }
+    if let Some(server) = acme.server.as_deref() {
+        flags.push(format!("--server {}", shell_quote(server)));
+    } else if acme.staging {
+        flags.push("--staging".to_string());
+    }
+
+    let names: Vec<&str> = domains.iter().map(|d| d.domain.as_str()).collect();
+
+    let mut script = String::new();
+    script.push_str("#!/bin/sh\n");
+    script.push_str(
+        "# Generated by stacker: ACME HTTP-01 issuance and renewal for ssl: auto domains.\n",
+    );
+    script.push_str("trap exit TERM\n\n");
+    script.push_str(&format!("DOMAINS=\"{}\"\n\n", names.join(" ")));
+    script.push_str("issue() {\n");
+    script.push_str(&format!(
+        "  certbot certonly --webroot -w {ACME_WEBROOT} --cert-name \"$1\" -d \"$1\" \\\n    {}\n",
+        flags.join(" ")
+    ));
+    script.push_str("}\n\n");
+    script.push_str("sleep 5\n");
+    script.push_str("while :; do\n");
+    script.push_str("  pending=0\n");
+    script.push_str("  for domain in $DOMAINS; do\n");
+    script.push_str("    if [ ! -s \"/etc/letsencrypt/live/$domain/fullchain.pem\" ]; then\n");
+    script.push_str("      issue \"$domain\" || pending=1\n");
+    script.push_str("    fi\n");
+    script.push_str("  done\n");
+    script.push_str(&format!(
+        "  certbot renew --webroot -w {ACME_WEBROOT} --non-interactive --quiet\n"
+    ));
+    script.push_str(&format!(
+        "  certbot certificates > {CERTIFICATE_STATUS_FILE}.tmp 2>/dev/null \\
+    && mv {CERTIFICATE_STATUS_FILE}.tmp {CERTIFICATE_STATUS_FILE}\n"
+    ));
+    script.push_str("  if [ \"$pending\" -eq 1 ]; then\n");
+    script.push_str("    sleep 300 & wait $!\n");
+    script.push_str("  else\n");
+    script.push_str("    sleep 12h & wait $!\n");
+    script.push_str("  fi\n");
+    script.push_str("done\n");
+    script
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r#"'\''"#))
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Certificate expiry — `stacker status` / `stacker proxy detect`
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// One entry of `certbot certificates`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CertificateStatus {
+    pub name: String,
+    pub domains: Vec<String>,
+    pub expires: Option<String>,
+    pub days_remaining: Option<i64>,
+    pub valid: bool,
+}
+
+impl CertificateStatus {
+    /// One-line human summary, flagged when renewal appears to be failing.
+    pub fn summary(&self) -> String {
+        let expires = self.expires.as_deref().unwrap_or("unknown expiry");
+        match (self.valid, self.days_remaining) {
+            (false, _) => format!("✗ {} — expired ({})", self.name, expires),
+            (true, Some(days)) if days < EXPIRY_WARNING_DAYS => format!(
+                "⚠ {} — expires {} ({} days, renewal overdue)",
+                self.name, expires, days
+            ),
+            (true, Some(days)) => format!("✓ {} — expires {} ({} days)", self.name, expires, days),
+            (true, None) => format!("✓ {} — expires {}", self.name, expires),
+        }
+    }
+}
+
+/// Parse the human output of `certbot certificates`:
+///
+/// ```text
+///   Certificate Name: app.example.com
+///     Domains: app.example.com
+///     Expiry Date: 2026-01-15 10:00:00+00:00 (VALID: 89 days)
+/// ```
+pub fn parse_certbot_certificates(output: &str) -> Vec<CertificateStatus> {
+    let mut certs: Vec<CertificateStatus> = Vec::new();
+    for line in output.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("Certificate Name:") {
+            certs.push(CertificateStatus {
+                name: name.trim().to_string(),
+                domains: Vec::new(),
+                expires: None,
+                days_remaining: None,
+                valid: true,
+            });
+            continue;
+        }
+        let Some(current) = certs.last_mut() else {
+            continue;
+        };
+        if let Some(domains) = line.strip_prefix("Domains:") {
+            current.domains = domains.split_whitespace().map(str::to_string).collect();
+        } else if let Some(expiry) = line.strip_prefix("Expiry Date:") {
+            let expiry = expiry.trim();
+            let (date, state) = match expiry.split_once('(') {
+                Some((date, state)) => (date.trim(), state.trim_end_matches(')')),
+                None => (expiry, ""),
+            };
+            current.expires = Some(date.to_string());
+            current.valid = !state.starts_with("INVALID");
+            current.days_remaining = state
+                .strip_prefix("VALID:")
+                .and_then(|rest| rest.split_whitespace().next())
+                .and_then(|days| days.parse().ok());
+        }
+    }
+    certs
+}
+
+/// Ask the local certbot companion for its certificates. Returns an empty
+/// list when the stack has no certbot service or it is not running.
+pub fn local_certificate_status(
+    compose_path: &Path,
+    executor: &dyn CommandExecutor,
+) -> Vec<CertificateStatus> {
+    let Ok((cmd, prefix)) = resolve_compose_cmd(executor) else {
+        return Vec::new();
+    };
+    let compose = compose_path.to_string_lossy();
+    let mut args: Vec<&str> = prefix;
+    args.extend([
+        "-f",
+        compose.as_ref(),
+        "exec",
+        "-T",
+        CERTBOT_SERVICE,
+        "certbot",
+        "certificates",
+    ]);
+    match executor.execute(cmd, &args) {
+        Ok(output) if output.exit_code == 0 => parse_certbot_certificates(&output.stdout),
+        _ => Vec::new(),
+    }
+}
+
+/// Ask a remote deployment's agent for the certificates of its certbot
+/// companion, read from the status file the renewal loop keeps up to date.
+/// `Ok(vec![])` means the file does not exist yet (nothing issued).
+pub async fn remote_certificate_status(
+    client: &StackerClient,
+    deployment_hash: &str,
+) -> Result<Vec<CertificateStatus>, CliError> {
+    const TIMEOUT_SECS: u64 = 30;
+    const POLL_INTERVAL_SECS: u64 = 2;
+
+    let params = CertificateStatusCommandRequest {
+        app_code: CERTBOT_SERVICE.to_string(),
+    };
+    let request = AgentEnqueueRequest::new(deployment_hash, "certificate_status")
+        .with_parameters(&params)
+        .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+    let info = client
+        .agent_poll_result(&request, TIMEOUT_SECS, POLL_INTERVAL_SECS)
+        .await?;
+    let report: CertificateStatusCommandReport = info
+        .result
+        .clone()
+        .and_then(|result| serde_json::from_value(result).ok())
+        .ok_or_else(|| CliError::AgentCommandFailed {
+            command_id: info.command_id.clone(),
+            error: "certificate status: the agent returned no report".to_string(),
+        })?;
+    // The loop writes the listing after its first pass; until then there is
+    // nothing to report.
+    Ok(report
+        .listing
+        .as_deref()
+        .map(parse_certbot_certificates)
+        .unwrap_or_default())
+}
+
+/// Whether a compose file declares the certbot companion.
+pub fn compose_has_certbot(compose_path: &Path) -> bool {
+    std::fs::read_to_string(compose_path)
+        .ok()
+        .and_then(|raw| serde_yaml::from_str::<serde_yaml::Value>(&raw).ok())
+        .and_then(|doc| doc.get("services").cloned())
+        .is_some_and(|services| services.get(CERTBOT_SERVICE).is_some())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Tests
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::config_parser::{AppType, ConfigBuilder, ProxyConfig};
+
+    fn nginx_config(domains: Vec<(&str, SslMode)>, acme: Option<AcmeConfig>) -> StackerConfig {
+        ConfigBuilder::new()
+            .name("acme-app")
+            .app_type(AppType::Node)
+            .proxy(ProxyConfig {
+                proxy_type: ProxyType::Nginx,
+                auto_detect: false,
+                domains: domains
+                    .into_iter()
+                    .map(|(domain, ssl)| DomainConfig {
+                        domain: domain.to_string(),
+                        ssl,
+                        upstream: "app:3000".to_string(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                config: None,
+                acme,
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_nginx_assets_split_http_and_https_for_auto_domains() {
+        let config = nginx_config(
+            vec![
+                ("app.example.com", SslMode::Auto),
+                ("plain.example.com", SslMode::Off),
+            ],
+            None,
+        );
+        let files = nginx_assets(&config).unwrap();
+
+        let http = &files["conf.d/app_example_com.conf"];
+        assert!(http.contains("location /.well-known/acme-challenge/"));
+        assert!(http.contains("root /var/www/certbot;"));
+        assert!(!http.contains("listen 443"));
+
+        let https = &files["https/app.example.com.conf"];
+        assert!(https.contains("listen 443 ssl http2;"));
+        assert!(https.contains("/etc/letsencrypt/live/app.example.com/fullchain.pem"));
+
+        assert!(files["conf.d/plain_example_com.conf"].contains("proxy_pass http://app:3000;"));
+        assert!(!files.contains_key("https/plain.example.com.conf"));
+        assert!(files["conf.d/00-stacker-acme.conf"].contains("stacker-enabled"));
+        assert!(files.contains_key("acme-nginx.sh"));
+        assert!(files["acme-certbot.sh"].contains("DOMAINS=\"app.example.com\""));
+    }
+
+    #[test]
+    fn test_nginx_assets_without_auto_domains_skip_acme_files() {
+        let config = nginx_config(vec![("plain.example.com", SslMode::Off)], None);
+        let files = nginx_assets(&config).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(!acme_enabled(&config));
+    }
+
+    #[test]
+    fn test_certbot_script_flags() {
+        let config = nginx_config(
+            vec![("app.example.com", SslMode::Auto)],
+            Some(AcmeConfig {
+                email: Some("ops@example.com".to_string()),
+                staging: true,
+                server: None,
+            }),
+        );
+        let domains = auto_ssl_domains(&config);
+        let script = render_certbot_script(&domains, config.proxy.acme.as_ref().unwrap());
+        assert!(script.contains("--email 'ops@example.com'"));
+        assert!(script.contains("--staging"));
+        assert!(script.contains("certbot renew"));
+        assert!(script.contains(&format!("mv {CERTIFICATE_STATUS_FILE}.tmp")));
+        assert!(!script.contains("--register-unsafely-without-email"));
+
+        let pebble = AcmeConfig {
+            server: Some("https://pebble:14000/dir".to_string()),
+            staging: true,
+            ..Default::default()
+        };
+        let script = render_certbot_script(&domains, &pebble);
+        assert!(script.contains("--server 'https://pebble:14000/dir'"));
+        assert!(!script.contains("--staging"));
+        assert!(script.contains("--register-unsafely-without-email"));
+    }
+
+    #[test]
+    fn test_acme_issues_flag_private_domains_and_missing_email() {
+        let config = nginx_config(
+            vec![
+                ("app.localhost", SslMode::Auto),
+                ("app.example.com", SslMode::Auto),
+                ("10.0.0.5", SslMode::Off),
+            ],
+            None,
+        );
+        let issues = acme_issues(&config);
+        let codes: Vec<&str> = issues.iter().map(|i| i.code.as_str()).collect();
+        assert_eq!(codes, vec!["ACME001", "ACME002"]);
+        assert_eq!(issues[0].field.as_deref(), Some("proxy.domains[0].ssl"));
+    }
+
+    #[test]
+    fn test_parse_certbot_certificates() {
+        let output = "\
+Saving debug log to /var/log/letsencrypt/letsencrypt.log
+
+- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+Found the following certs:
+  Certificate Name: app.example.com
+    Serial Number: 4b1d
+    Key Type: ECDSA
+    Domains: app.example.com
+    Expiry Date: 2026-12-01 10:00:00+00:00 (VALID: 44 days)
+    Certificate Path: /etc/letsencrypt/live/app.example.com/fullchain.pem
+  Certificate Name: old.example.com
+    Domains: old.example.com www.old.example.com
+    Expiry Date: 2026-10-01 10:00:00+00:00 (INVALID: EXPIRED)
+";
+        let certs = parse_certbot_certificates(output);
+        assert_eq!(certs.len(), 2);
+        assert_eq!(certs[0].name, "app.example.com");
+        assert_eq!(certs[0].days_remaining, Some(44));
+        assert!(certs[0].valid);
+        assert!(certs[0].summary().starts_with('✓'));
+        assert_eq!(certs[1].domains.len(), 2);
+        assert!(!certs[1].valid);
+        assert!(certs[1].summary().contains("expired"));
+    }
+
+    #[test]
+    fn test_summary_flags_overdue_renewal_below_the_renewal_window() {
+        let cert = |days| CertificateStatus {
+            name: "app.example.com".to_string(),
+            domains: vec!["app.example.com".to_string()],
+            expires: Some("2026-11-01".to_string()),
+            days_remaining: Some(days),
+            valid: true,
+        };
+        // certbot is still inside its normal renewal window.
+        assert!(cert(25).summary().starts_with('✓'));
+        assert!(cert(10).summary().contains("renewal overdue"));
+    }
+}
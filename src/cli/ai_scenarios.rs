@@ -733,6 +733,7 @@ mod tests {
                 domain: "status.try.direct".to_string(),
                 ssl: crate::cli::config_parser::SslMode::Auto,
                 upstream: "app:80".to_string(),
+                ..Default::default()
             });
         config.deploy.cloud = Some(crate::cli::config_parser::CloudConfig {
             provider: crate::cli::config_parser::CloudProvider::Hetzner,
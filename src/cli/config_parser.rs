@@ -5,6 +5,7 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_valid::Validate;
 
+use crate::cli::config_compose::{declares_sources, load_composed, ComposedConfig, RootLayer};
 use crate::cli::error::{CliError, Severity, ValidationIssue};
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -71,6 +72,38 @@ impl Default for DeployTarget {
     }
 }
 
+/// How a remote `deploy_app` replaces running containers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployStrategy {
+    /// Recreate containers in place (brief outage).
+    Recreate,
+    /// Start a new container set next to the old one, switch the proxy
+    /// upstream once it is healthy, then drain and remove the old set.
+    BlueGreen,
+}
+
+impl Default for DeployStrategy {
+    fn default() -> Self {
+        Self::Recreate
+    }
+}
+
+impl DeployStrategy {
+    pub fn is_recreate(&self) -> bool {
+        *self == Self::Recreate
+    }
+}
+
+impl fmt::Display for DeployStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Recreate => write!(f, "recreate"),
+            Self::BlueGreen => write!(f, "blue_green"),
+        }
+    }
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // ProxyType — reverse proxy flavors
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -81,6 +114,7 @@ pub enum ProxyType {
     Nginx,
     NginxProxyManager,
     Traefik,
+    Caddy,
     None,
 }
 
@@ -90,6 +124,7 @@ impl fmt::Display for ProxyType {
             Self::Nginx => write!(f, "nginx"),
             Self::NginxProxyManager => write!(f, "nginx-proxy-manager"),
             Self::Traefik => write!(f, "traefik"),
+            Self::Caddy => write!(f, "caddy"),
             Self::None => write!(f, "none"),
         }
     }
@@ -229,6 +264,11 @@ pub struct AppSource {
     /// Docker compose healthcheck for this service.
     #[serde(default)]
     pub healthcheck: Option<ComposeHealthcheck>,
+
+    /// Name of the `deploy.targets` profile whose server runs this service.
+    /// Unset means the service runs on the stack's primary server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<String>,
 }
 
 fn default_app_path() -> PathBuf {
@@ -364,14 +404,37 @@ pub struct ProxyConfig {
 
     #[serde(default)]
     pub config: Option<PathBuf>,
+
+    /// ACME settings for `ssl: auto` domains served by the nginx proxy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acme: Option<AcmeConfig>,
 }
 
 fn default_auto_detect() -> bool {
     true
 }
 
+/// ACME (Let's Encrypt) issuance settings used by the certbot companion.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AcmeConfig {
+    /// Account email for expiry notices. Without it certbot registers anonymously.
+    #[serde(default)]
+    pub email: Option<String>,
+
+    /// Issue from the Let's Encrypt staging directory (untrusted, generous rate limits).
+    #[serde(default)]
+    pub staging: bool,
+
+    /// Custom ACME directory URL (private CA, or Pebble for testing).
+    #[serde(default)]
+    pub server: Option<String>,
+}
+
 /// Per-domain routing and SSL settings.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `upstream` serves `/`; `routes` send path prefixes elsewhere. Policy
+/// fields set on the domain apply to every route that does not override them.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DomainConfig {
     pub domain: String,
 
@@ -379,6 +442,241 @@ pub struct DomainConfig {
     pub ssl: SslMode,
 
     pub upstream: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteConfig>,
+
+    #[serde(flatten)]
+    pub policy: RoutePolicy,
+}
+
+impl DomainConfig {
+    /// Every location served for this domain — `/` first, then the routes —
+    /// with domain-level policy folded into each route.
+    pub fn effective_routes(&self) -> Vec<RouteConfig> {
+        let mut routes = vec![RouteConfig {
+            path: "/".to_string(),
+            upstream: self.upstream.clone(),
+            policy: self.policy.clone(),
+        }];
+        routes.extend(self.routes.iter().map(|route| RouteConfig {
+            path: route.path.clone(),
+            upstream: route.upstream.clone(),
+            policy: route.policy.inherit(&self.policy),
+        }));
+        routes
+    }
+
+    /// Route policies Traefik labels cannot express. [`traefik_labels`]
+    /// leaves them out, so callers warn instead of dropping them silently.
+    ///
+    /// [`traefik_labels`]: crate::cli::proxy_manager::traefik_labels
+    pub fn unsupported_traefik_policies(&self) -> Vec<String> {
+        let mut unsupported = Vec::new();
+        for route in self.effective_routes() {
+            if route.policy.timeout.is_some() {
+                unsupported.push(format!(
+                    "{}{}: timeout is not supported by Traefik labels and is ignored; configure serversTransport in a Traefik file provider",
+                    self.domain, route.path
+                ));
+            }
+            if route.policy.websocket == Some(false) {
+                unsupported.push(format!(
+                    "{}{}: websocket: false is ignored; Traefik always forwards websocket upgrades",
+                    self.domain, route.path
+                ));
+            }
+        }
+        unsupported
+    }
+
+    /// Problems with this domain's routes and policies: malformed paths,
+    /// routes that overlap each other or `/`, and invalid policy values.
+    pub fn route_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let path_re = regex::Regex::new(r"^/[A-Za-z0-9._~%/-]*$").unwrap();
+        let mut seen: Vec<(String, &str)> = Vec::new();
+
+        for route in &self.routes {
+            if !path_re.is_match(&route.path) {
+                errors.push(format!(
+                    "route path '{}' must start with '/' and contain only URL path characters",
+                    route.path
+                ));
+                continue;
+            }
+            let normalized = route.path.trim_end_matches('/').to_string();
+            if normalized.is_empty() {
+                errors.push(format!(
+                    "route path '{}' overlaps the domain upstream; set `upstream` instead",
+                    route.path
+                ));
+                continue;
+            }
+            if let Some((_, first)) = seen.iter().find(|(path, _)| *path == normalized) {
+                errors.push(format!(
+                    "routes '{}' and '{}' match the same prefix",
+                    first, route.path
+                ));
+                continue;
+            }
+            if route.upstream.trim().is_empty() {
+                errors.push(format!("route '{}' has no upstream", route.path));
+            }
+            seen.push((normalized, route.path.as_str()));
+        }
+
+        errors.extend(self.policy.errors("domain"));
+        for route in &self.routes {
+            errors.extend(route.policy.errors(&format!("route '{}'", route.path)));
+        }
+        errors
+    }
+}
+
+/// A path-prefix route under a domain.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RouteConfig {
+    /// Path prefix, e.g. `/api`. Longest prefix wins.
+    pub path: String,
+
+    pub upstream: String,
+
+    #[serde(flatten)]
+    pub policy: RoutePolicy,
+}
+
+/// Proxy behaviour for a domain or route. Unset fields inherit from the domain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct RoutePolicy {
+    /// Forward `Upgrade`/`Connection` headers for websocket traffic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket: Option<bool>,
+
+    /// Maximum request body in nginx size syntax (`512k`, `20m`, `1g`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_max_body_size: Option<String>,
+
+    /// Upstream read/send timeout in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub basic_auth: Option<BasicAuthConfig>,
+
+    /// Client IPs or CIDR ranges allowed through; everyone else gets 403.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_ips: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+impl RoutePolicy {
+    fn errors(&self, scope: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Some(size) = &self.client_max_body_size {
+            let size_re = regex::Regex::new(r"^[0-9]+[kKmMgG]?$").unwrap();
+            if !size_re.is_match(size) {
+                errors.push(format!(
+                    "{scope}: client_max_body_size '{size}' must look like 512k, 20m or 1g"
+                ));
+            }
+        }
+        if self.timeout == Some(0) {
+            errors.push(format!("{scope}: timeout must be at least 1 second"));
+        }
+        if let Some(auth) = &self.basic_auth {
+            if auth.users.is_empty() {
+                errors.push(format!("{scope}: basic_auth.users is empty"));
+            }
+            for user in &auth.users {
+                if !user.contains(':') || user.contains(char::is_whitespace) {
+                    errors.push(format!(
+                        "{scope}: basic_auth entry '{}' is not an htpasswd line (user:hash)",
+                        user.split(':').next().unwrap_or_default()
+                    ));
+                }
+            }
+        }
+        for entry in &self.allow_ips {
+            if !is_ip_or_cidr(entry) {
+                errors.push(format!(
+                    "{scope}: allow_ips entry '{entry}' is not an IP address or CIDR range"
+                ));
+            }
+        }
+        if let Some(limit) = &self.rate_limit {
+            if limit.requests_per_second == 0 {
+                errors.push(format!(
+                    "{scope}: rate_limit.requests_per_second must be greater than 0"
+                ));
+            }
+        }
+        errors
+    }
+
+    /// This policy with unset fields taken from `parent`.
+    pub fn inherit(&self, parent: &RoutePolicy) -> RoutePolicy {
+        RoutePolicy {
+            websocket: self.websocket.or(parent.websocket),
+            client_max_body_size: self
+                .client_max_body_size
+                .clone()
+                .or_else(|| parent.client_max_body_size.clone()),
+            timeout: self.timeout.or(parent.timeout),
+            basic_auth: self
+                .basic_auth
+                .clone()
+                .or_else(|| parent.basic_auth.clone()),
+            allow_ips: if self.allow_ips.is_empty() {
+                parent.allow_ips.clone()
+            } else {
+                self.allow_ips.clone()
+            },
+            rate_limit: self
+                .rate_limit
+                .clone()
+                .or_else(|| parent.rate_limit.clone()),
+        }
+    }
+}
+
+fn is_ip_or_cidr(value: &str) -> bool {
+    let (addr, prefix) = match value.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix)),
+        None => (value, None),
+    };
+    let Ok(ip) = addr.parse::<std::net::IpAddr>() else {
+        return false;
+    };
+    match prefix {
+        None => true,
+        Some(prefix) => {
+            let max = if ip.is_ipv4() { 32 } else { 128 };
+            prefix.parse::<u8>().is_ok_and(|bits| bits <= max)
+        }
+    }
+}
+
+/// HTTP basic auth for a domain or route.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct BasicAuthConfig {
+    /// htpasswd entries (`user:$apr1$...`, `user:{SHA}...`); generate with `htpasswd -nb`.
+    pub users: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realm: Option<String>,
+}
+
+/// Per-client-IP request rate limit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct RateLimitConfig {
+    pub requests_per_second: u32,
+
+    /// Requests allowed above the rate before rejecting with 429.
+    #[serde(default)]
+    pub burst: u32,
 }
 
 /// Docker registry credentials for pulling private images during deployment.
@@ -400,6 +698,10 @@ pub struct RegistryConfig {
     /// Use for private registries like `ghcr.io`, `registry.example.com`.
     #[serde(default)]
     pub server: Option<String>,
+
+    /// Image repository `stacker build` pushes to (e.g. `ghcr.io/acme/shop`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
 }
 
 /// Per-target deployment profile in multi-target configs.
@@ -422,6 +724,11 @@ pub struct DeployProfileConfig {
 
     #[serde(default)]
     pub registry: Option<RegistryConfig>,
+
+    /// Address services on other placement hosts use to reach this server:
+    /// a private network or WireGuard address. Defaults to `server.host`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_address: Option<String>,
 }
 
 impl DeployProfileConfig {
@@ -470,6 +777,75 @@ pub struct DeployConfig {
     /// to the legacy single-target shape before executing.
     #[serde(default)]
     pub targets: BTreeMap<String, DeployProfileConfig>,
+
+    /// Rollout strategy for remote `deploy_app` commands.
+    #[serde(default, skip_serializing_if = "DeployStrategy::is_recreate")]
+    pub strategy: DeployStrategy,
+
+    /// Tuning for `strategy: blue_green`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blue_green: Option<BlueGreenConfig>,
+
+    /// Per-branch preview environments (`stacker preview up`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<PreviewConfig>,
+}
+
+/// Where `stacker preview up` deploys a branch: a shared server from
+/// `deploy.targets`, reachable at `<branch>.<base_domain>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewConfig {
+    /// `deploy.targets` profile of the shared server; the default target
+    /// when omitted. The profile needs a `deployment_hash`.
+    #[serde(default)]
+    pub target: Option<String>,
+
+    /// Wildcard domain pointed at the shared server, e.g. `preview.example.com`.
+    pub base_domain: String,
+
+    /// Service and port the preview domain forwards to, e.g. `web:3000`.
+    /// Defaults to the first `proxy.domains` upstream.
+    #[serde(default)]
+    pub upstream: Option<String>,
+
+    /// Hours until an untouched preview is torn down.
+    #[serde(default = "default_preview_ttl_hours")]
+    pub ttl_hours: u32,
+}
+
+fn default_preview_ttl_hours() -> u32 {
+    72
+}
+
+/// Blue/green rollout tuning. The health probe path defaults to
+/// `monitoring.healthcheck.endpoint`; without one the agent waits for the
+/// compose healthcheck.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlueGreenConfig {
+    /// Seconds to wait for the new container set to become healthy.
+    #[serde(default = "default_blue_green_health_timeout")]
+    pub health_timeout: u32,
+
+    /// Seconds the old set keeps serving in-flight requests after the switch.
+    #[serde(default = "default_blue_green_drain_timeout")]
+    pub drain_timeout: u32,
+}
+
+impl Default for BlueGreenConfig {
+    fn default() -> Self {
+        Self {
+            health_timeout: default_blue_green_health_timeout(),
+            drain_timeout: default_blue_green_drain_timeout(),
+        }
+    }
+}
+
+fn default_blue_green_health_timeout() -> u32 {
+    120
+}
+
+fn default_blue_green_drain_timeout() -> u32 {
+    30
 }
 
 impl DeployConfig {
@@ -487,6 +863,15 @@ impl DeployConfig {
         })
     }
 
+    /// The `deploy.targets` profile [`DeployConfig::resolve`] picks, or `None`
+    /// for single-target configs.
+    pub fn target_name(&self, requested: Option<&str>) -> Option<String> {
+        if !self.uses_named_targets() {
+            return None;
+        }
+        self.resolve_named_target_name(requested).ok()
+    }
+
     fn resolve_named_target_name(&self, requested: Option<&str>) -> Result<String, CliError> {
         if let Some(requested_name) = requested.map(str::trim).filter(|value| !value.is_empty()) {
             if self.targets.contains_key(requested_name) {
@@ -557,6 +942,9 @@ impl DeployConfig {
             registry: profile.registry.clone(),
             default_target: self.default_target.clone(),
             targets: self.targets.clone(),
+            strategy: self.strategy,
+            blue_green: self.blue_green.clone(),
+            preview: self.preview.clone(),
         })
     }
 }
@@ -753,6 +1141,21 @@ pub struct TargetConfigContract {
     pub secret: Vec<String>,
 }
 
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// ConfigSourceRef — `extends:` / `include:` targets
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// A base file or fragment composed into stacker.yml. Either a local path
+/// (relative to the declaring file), a URL pinned inline as
+/// `https://...#sha256=<hex>`, or the explicit `{ url, sha256 }` form.
+/// See `cli::config_compose` for the merge rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConfigSourceRef {
+    Location(String),
+    Pinned { url: String, sha256: String },
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // StackerConfig — the root configuration type
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -789,6 +1192,14 @@ impl Default for ConfigOrigin {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Validate)]
 pub struct StackerConfig {
+    /// Base config this file is layered on top of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<ConfigSourceRef>,
+
+    /// Fragments merged after `extends` and before this file's own values.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<ConfigSourceRef>,
+
     #[validate(min_length = 1)]
     #[validate(max_length = 128)]
     pub name: String,
@@ -861,42 +1272,60 @@ impl StackerConfig {
     /// validation, or sending to the server).  If you plan to mutate the
     /// config and write it back to disk, use [`from_file_raw`] instead so
     /// that `${VAR}` placeholders are preserved.
+    ///
+    /// Any `extends`/`include` sources are merged first — see
+    /// `cli::config_compose` for the layering rules.
     pub fn from_file(path: &Path) -> Result<Self, CliError> {
-        if !path.exists() {
-            return Err(CliError::ConfigNotFound {
-                path: path.to_path_buf(),
-            });
-        }
+        Self::from_composed(path, load_composed(path)?)
+    }
 
-        let raw_content = std::fs::read_to_string(path)?;
-        let origin = detect_origin_from_raw(&raw_content);
-        let mut parsed: serde_yaml::Value = serde_yaml::from_str(&raw_content)?;
-        let env_file_vars = load_env_file_vars_from_yaml(path, &raw_content);
+    /// [`from_file`] for a config already composed from `path`, so callers
+    /// that also need the sources do not read remote bases twice.
+    pub fn from_composed(path: &Path, composed: ComposedConfig) -> Result<Self, CliError> {
+        let mut parsed = composed.value;
+        let merged_content = serde_yaml::to_string(&parsed)?;
+        let env_file_vars = load_env_file_vars_from_yaml(path, &merged_content);
         resolve_env_placeholders_in_value(&mut parsed, &env_file_vars)?;
         let mut config = deserialize_config_value(parsed)?;
-        config.origin = origin;
+        config.origin = composed.origin;
         Ok(config)
     }
 
     /// Load config from a file path **without** resolving `${VAR}` placeholders.
     ///
-    /// Use this when you need to modify the config and write it back to disk
-    /// (e.g. `stacker service add`, `stacker config fix`).  The `${VAR}`
-    /// references are kept as-is so they are not replaced with sensitive
-    /// values when the file is serialized back.
+    /// `extends`/`include` are composed, so this is the config as deployed
+    /// with placeholders intact. To modify the config and write it back to
+    /// disk, use [`from_file_layer`] instead.
     pub fn from_file_raw(path: &Path) -> Result<Self, CliError> {
+        let composed = load_composed(path)?;
+        let mut config = deserialize_config_value(composed.value)?;
+        config.origin = composed.origin;
+        Ok(config)
+    }
+
+    /// Load config for editing (e.g. `stacker service add`, `stacker config
+    /// fix`), with `${VAR}` placeholders kept as-is. The config is composed
+    /// like [`from_file_raw`], so the command reads and validates inherited
+    /// values too. Write it back with [`RootLayer::render`], which applies
+    /// only the edits made to the root file's own layer, so base values are
+    /// never inlined into a composing file.
+    pub fn from_file_layer(path: &Path) -> Result<(Self, RootLayer), CliError> {
         if !path.exists() {
             return Err(CliError::ConfigNotFound {
                 path: path.to_path_buf(),
             });
         }
+        let content = std::fs::read_to_string(path)?;
+        let raw: serde_yaml::Value = serde_yaml::from_str(&content)?;
+        if !declares_sources(&raw) {
+            let mut config = deserialize_config_value(raw)?;
+            config.origin = detect_origin_from_raw(&content);
+            return Ok((config, RootLayer::plain()));
+        }
 
-        let raw_content = std::fs::read_to_string(path)?;
-        let origin = detect_origin_from_raw(&raw_content);
-        let parsed: serde_yaml::Value = serde_yaml::from_str(&raw_content)?;
-        let mut config = deserialize_config_value(parsed)?;
-        config.origin = origin;
-        Ok(config)
+        let config = Self::from_file_raw(path)?;
+        let loaded = serde_yaml::to_value(&config)?;
+        Ok((config, RootLayer::composed(raw, loaded)))
     }
 
     /// Load config from a YAML string (useful for tests).
@@ -1012,6 +1441,9 @@ impl StackerConfig {
                             registry: profile.registry.clone(),
                             default_target: None,
                             targets: BTreeMap::new(),
+                            strategy: self.deploy.strategy,
+                            blue_green: None,
+                            preview: None,
                         };
                         validate_deploy_semantics(
                             &mut issues,
@@ -1052,6 +1484,11 @@ impl StackerConfig {
             });
         }
 
+        validate_proxy_domains(&mut issues, &self.proxy);
+        validate_deploy_strategy(&mut issues, self);
+        validate_preview(&mut issues, self);
+        validate_placement(&mut issues, self);
+
         // Port conflict detection across services
         let mut port_map: HashMap<String, Vec<String>> = HashMap::new();
         for svc in &self.services {
@@ -1090,7 +1527,7 @@ impl StackerConfig {
 /// the marker can appear after a shebang-style banner. Once the scan hits
 /// a non-comment non-blank line, further lines are ignored — the marker
 /// must live at the top of the file.
-fn detect_origin_from_raw(raw: &str) -> ConfigOrigin {
+pub(crate) fn detect_origin_from_raw(raw: &str) -> ConfigOrigin {
     for line in raw.lines() {
         let trimmed = line.trim();
         if trimmed.is_empty() {
@@ -1157,6 +1594,232 @@ fn format_config_parse_message(field_path: &str, source: &serde_yaml::Error) ->
     source_message
 }
 
+fn validate_proxy_domains(issues: &mut Vec<ValidationIssue>, proxy: &ProxyConfig) {
+    let mut seen: Vec<String> = Vec::new();
+    for (index, domain) in proxy.domains.iter().enumerate() {
+        let name = domain.domain.to_ascii_lowercase();
+        if seen.contains(&name) {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                code: "E007".to_string(),
+                message: format!(
+                    "proxy domain '{}' is listed more than once; merge the entries and use routes for path-based upstreams",
+                    domain.domain
+                ),
+                field: Some(format!("proxy.domains[{index}].domain")),
+            });
+        } else {
+            seen.push(name);
+        }
+
+        if proxy.proxy_type == ProxyType::Traefik {
+            for message in domain.unsupported_traefik_policies() {
+                issues.push(ValidationIssue {
+                    severity: Severity::Warning,
+                    code: "W003".to_string(),
+                    message,
+                    field: Some(format!("proxy.domains[{index}]")),
+                });
+            }
+        }
+
+        if proxy.proxy_type == ProxyType::Caddy {
+            for route in domain.effective_routes() {
+                if route.policy.rate_limit.is_some() {
+                    issues.push(ValidationIssue {
+                        severity: Severity::Warning,
+                        code: "W004".to_string(),
+                        message: format!(
+                            "{}{}: rate_limit needs a Caddy rate-limit plugin and is ignored in the generated Caddyfile",
+                            domain.domain, route.path
+                        ),
+                        field: Some(format!("proxy.domains[{index}]")),
+                    });
+                }
+                let non_bcrypt = route.policy.basic_auth.as_ref().is_some_and(|auth| {
+                    auth.users.iter().any(|user| {
+                        !user
+                            .split_once(':')
+                            .is_some_and(|(_, hash)| hash.starts_with("$2"))
+                    })
+                });
+                if non_bcrypt {
+                    issues.push(ValidationIssue {
+                        severity: Severity::Warning,
+                        code: "W004".to_string(),
+                        message: format!(
+                            "{}{}: Caddy basic_auth only accepts bcrypt hashes (`caddy hash-password`)",
+                            domain.domain, route.path
+                        ),
+                        field: Some(format!("proxy.domains[{index}]")),
+                    });
+                }
+            }
+        }
+
+        for message in domain.route_errors() {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                code: "E008".to_string(),
+                message: format!("{}: {}", domain.domain, message),
+                field: Some(format!("proxy.domains[{index}]")),
+            });
+        }
+    }
+}
+
+fn validate_deploy_strategy(issues: &mut Vec<ValidationIssue>, config: &StackerConfig) {
+    if config.deploy.strategy != DeployStrategy::BlueGreen {
+        return;
+    }
+
+    if config.proxy.proxy_type == ProxyType::None {
+        issues.push(ValidationIssue {
+            severity: Severity::Error,
+            code: "E009".to_string(),
+            message: "deploy.strategy blue_green switches traffic through the managed proxy; set proxy.type".to_string(),
+            field: Some("deploy.strategy".to_string()),
+        });
+    }
+
+    if !config.deploy.uses_named_targets() && config.deploy.target == DeployTarget::Local {
+        issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            code: "W005".to_string(),
+            message: "deploy.strategy blue_green only applies to remote deploy_app; local deploys recreate containers".to_string(),
+            field: Some("deploy.strategy".to_string()),
+        });
+    }
+
+    if let Some(blue_green) = &config.deploy.blue_green {
+        if !(10..=1800).contains(&blue_green.health_timeout) {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                code: "E009".to_string(),
+                message: "deploy.blue_green.health_timeout must be between 10 and 1800 seconds"
+                    .to_string(),
+                field: Some("deploy.blue_green.health_timeout".to_string()),
+            });
+        }
+        if blue_green.drain_timeout > 600 {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                code: "E009".to_string(),
+                message: "deploy.blue_green.drain_timeout must be at most 600 seconds".to_string(),
+                field: Some("deploy.blue_green.drain_timeout".to_string()),
+            });
+        }
+    }
+}
+
+fn validate_preview(issues: &mut Vec<ValidationIssue>, config: &StackerConfig) {
+    let Some(preview) = &config.deploy.preview else {
+        return;
+    };
+    let mut error = |message: String, field: &str| {
+        issues.push(ValidationIssue {
+            severity: Severity::Error,
+            code: "E010".to_string(),
+            message,
+            field: Some(field.to_string()),
+        });
+    };
+
+    if preview.base_domain.trim().is_empty() || preview.base_domain.contains('*') {
+        error(
+            "deploy.preview.base_domain must be a domain such as preview.example.com (without '*')"
+                .to_string(),
+            "deploy.preview.base_domain",
+        );
+    }
+    if config.proxy.proxy_type == ProxyType::None {
+        error(
+            "deploy.preview routes <branch>.<base_domain> through the managed proxy; set proxy.type"
+                .to_string(),
+            "deploy.preview",
+        );
+    }
+    if preview.upstream.is_none() && config.proxy.domains.is_empty() {
+        error(
+            "deploy.preview.upstream is required when proxy.domains is empty".to_string(),
+            "deploy.preview.upstream",
+        );
+    }
+    if preview.ttl_hours == 0 || preview.ttl_hours > 720 {
+        error(
+            "deploy.preview.ttl_hours must be between 1 and 720".to_string(),
+            "deploy.preview.ttl_hours",
+        );
+    }
+    match preview.target.as_deref() {
+        Some(target) if !config.deploy.targets.contains_key(target) => error(
+            format!(
+                "deploy.preview.target '{}' does not match any entry in deploy.targets",
+                target
+            ),
+            "deploy.preview.target",
+        ),
+        None if !config.deploy.uses_named_targets() => error(
+            "deploy.preview needs a shared server from deploy.targets".to_string(),
+            "deploy.preview.target",
+        ),
+        _ => {}
+    }
+}
+
+fn validate_placement(issues: &mut Vec<ValidationIssue>, config: &StackerConfig) {
+    let primary = config.deploy.target_name(None);
+    for (index, service) in config.services.iter().enumerate() {
+        let Some(placement) = service.placement.as_deref() else {
+            continue;
+        };
+        let mut error = |message: String| {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                code: "E011".to_string(),
+                message,
+                field: Some(format!("services[{index}].placement")),
+            });
+        };
+
+        if !config.deploy.uses_named_targets() {
+            error(format!(
+                "service '{}' sets placement but deploy.targets is empty; placement names a deploy.targets profile",
+                service.name
+            ));
+            continue;
+        }
+        let Some(profile) = config.deploy.targets.get(placement) else {
+            error(format!(
+                "service '{}' placement '{}' does not match any entry in deploy.targets",
+                service.name, placement
+            ));
+            continue;
+        };
+        if primary.as_deref() == Some(placement) {
+            continue;
+        }
+        if profile.deployment_hash.is_none() {
+            error(format!(
+                "deploy.targets.{} hosts '{}' and needs a deployment_hash for its agent",
+                placement, service.name
+            ));
+        }
+        if profile.private_address.is_none() {
+            error(format!(
+                "deploy.targets.{} needs private_address so other hosts can reach '{}'",
+                placement, service.name
+            ));
+        }
+        if service.ports.is_empty() {
+            error(format!(
+                "service '{}' runs on another host; list the ports its peers connect to under ports",
+                service.name
+            ));
+        }
+    }
+}
+
 fn validate_deploy_semantics(
     issues: &mut Vec<ValidationIssue>,
     project: &ProjectConfig,
@@ -1510,6 +2173,8 @@ impl ConfigBuilder {
         };
 
         Ok(StackerConfig {
+            extends: None,
+            include: Vec::new(),
             name,
             version: self.version,
             organization: self.organization,
@@ -1540,6 +2205,9 @@ impl ConfigBuilder {
                 registry: self.registry,
                 default_target: None,
                 targets: BTreeMap::new(),
+                strategy: DeployStrategy::default(),
+                blue_green: None,
+                preview: None,
             },
             install: InstallConfig::default(),
             environments: BTreeMap::new(),
@@ -1993,6 +2661,234 @@ proxy:
         assert_eq!(config.proxy.domains[1].ssl, SslMode::Off);
     }
 
+    #[test]
+    fn test_parse_proxy_routes_inherit_domain_policy() {
+        let yaml = r#"
+name: routes-test
+proxy:
+  type: nginx
+  domains:
+    - domain: app.example.com
+      upstream: web:3000
+      client_max_body_size: 20m
+      allow_ips: [10.0.0.0/8]
+      routes:
+        - path: /api
+          upstream: api:8080
+          rate_limit: { requests_per_second: 10, burst: 20 }
+        - path: /ws
+          upstream: api:8080
+          websocket: true
+          allow_ips: [192.168.1.10]
+"#;
+        let config = StackerConfig::from_str(yaml).unwrap();
+        let domain = &config.proxy.domains[0];
+        assert_eq!(domain.routes.len(), 2);
+        assert_eq!(domain.policy.client_max_body_size.as_deref(), Some("20m"));
+
+        let routes = domain.effective_routes();
+        assert_eq!(routes[0].path, "/");
+        assert_eq!(
+            routes[1].policy.client_max_body_size.as_deref(),
+            Some("20m")
+        );
+        assert_eq!(routes[1].policy.allow_ips, vec!["10.0.0.0/8"]);
+        assert_eq!(routes[1].policy.rate_limit.as_ref().unwrap().burst, 20);
+        assert_eq!(routes[2].policy.websocket, Some(true));
+        assert_eq!(routes[2].policy.allow_ips, vec!["192.168.1.10"]);
+        assert!(config.validate_semantics().is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_overlapping_routes_and_duplicate_domains() {
+        let yaml = r#"
+name: routes-test
+proxy:
+  type: nginx
+  domains:
+    - domain: app.example.com
+      upstream: web:3000
+      routes:
+        - path: /api
+          upstream: api:8080
+        - path: /api/
+          upstream: api2:8080
+        - path: /
+          upstream: other:80
+        - path: api
+          upstream: api:8080
+          allow_ips: [not-an-ip]
+          rate_limit: { requests_per_second: 0 }
+    - domain: APP.example.com
+      upstream: web:3000
+"#;
+        let config = StackerConfig::from_str(yaml).unwrap();
+        let issues = config.validate_semantics();
+        assert!(issues.iter().any(|i| i.code == "E007"));
+        let e008: Vec<&str> = issues
+            .iter()
+            .filter(|i| i.code == "E008")
+            .map(|i| i.message.as_str())
+            .collect();
+        assert!(e008.iter().any(|m| m.contains("'/api' and '/api/'")));
+        assert!(e008
+            .iter()
+            .any(|m| m.contains("overlaps the domain upstream")));
+        assert!(e008.iter().any(|m| m.contains("must start with '/'")));
+        assert!(e008.iter().any(|m| m.contains("not-an-ip")));
+        assert!(e008.iter().any(|m| m.contains("requests_per_second")));
+    }
+
+    #[test]
+    fn test_validate_warns_about_policies_traefik_cannot_render() {
+        let yaml = r#"
+name: routes-test
+proxy:
+  type: traefik
+  domains:
+    - domain: app.example.com
+      upstream: web:3000
+      routes:
+        - path: /api
+          upstream: api:8080
+          timeout: 120
+        - path: /events
+          upstream: api:8080
+          websocket: false
+        - path: /ws
+          upstream: api:8080
+          websocket: true
+"#;
+        let config = StackerConfig::from_str(yaml).unwrap();
+        let w003: Vec<String> = config
+            .validate_semantics()
+            .into_iter()
+            .filter(|i| i.code == "W003")
+            .map(|i| i.message)
+            .collect();
+        assert_eq!(w003.len(), 2);
+        assert!(w003[0].contains("/api: timeout"));
+        assert!(w003[1].contains("/events: websocket: false"));
+    }
+
+    #[test]
+    fn test_validate_blue_green_strategy() {
+        let yaml = r#"
+name: bg-test
+deploy:
+  target: server
+  server:
+    host: 203.0.113.10
+  strategy: blue_green
+  blue_green:
+    health_timeout: 5
+"#;
+        let config = StackerConfig::from_str(yaml).unwrap();
+        assert_eq!(config.deploy.strategy, DeployStrategy::BlueGreen);
+        assert_eq!(config.deploy.blue_green.as_ref().unwrap().drain_timeout, 30);
+        let issues = config.validate_semantics();
+        let e009: Vec<&str> = issues
+            .iter()
+            .filter(|i| i.code == "E009")
+            .map(|i| i.message.as_str())
+            .collect();
+        assert!(e009.iter().any(|m| m.contains("set proxy.type")));
+        assert!(e009.iter().any(|m| m.contains("health_timeout")));
+        assert!(!issues.iter().any(|i| i.code == "W005"));
+
+        let recreate = StackerConfig::from_str("name: plain\n").unwrap();
+        let yaml = serde_yaml::to_string(&recreate).unwrap();
+        assert!(!yaml.contains("strategy"));
+    }
+
+    #[test]
+    fn test_validate_preview_config() {
+        let yaml = r#"
+name: preview-test
+proxy:
+  type: nginx-proxy-manager
+  domains:
+    - domain: shop.example.com
+      upstream: web:3000
+deploy:
+  default_target: staging
+  targets:
+    staging:
+      deployment_hash: dep-staging
+      server:
+        host: 203.0.113.10
+  preview:
+    target: staging
+    base_domain: preview.example.com
+"#;
+        let config = StackerConfig::from_str(yaml).unwrap();
+        let preview = config.deploy.preview.as_ref().unwrap();
+        assert_eq!(preview.ttl_hours, 72);
+        assert!(!config.validate_semantics().iter().any(|i| i.code == "E010"));
+        let resolved = config.deploy.resolve(preview.target.as_deref()).unwrap();
+        assert_eq!(resolved.deployment_hash.as_deref(), Some("dep-staging"));
+
+        let mut broken = config.clone();
+        let preview = broken.deploy.preview.as_mut().unwrap();
+        preview.target = Some("prod".to_string());
+        preview.base_domain = "*.preview.example.com".to_string();
+        let e010: Vec<String> = broken
+            .validate_semantics()
+            .into_iter()
+            .filter(|i| i.code == "E010")
+            .map(|i| i.message)
+            .collect();
+        assert!(e010.iter().any(|m| m.contains("'prod'")));
+        assert!(e010.iter().any(|m| m.contains("base_domain")));
+    }
+
+    #[test]
+    fn test_validate_service_placement() {
+        let yaml = r#"
+name: placement-test
+services:
+  - name: postgres
+    image: postgres:16
+    ports: ["5432"]
+    placement: db
+deploy:
+  default_target: web
+  targets:
+    web:
+      deployment_hash: dep-web
+      server:
+        host: 203.0.113.10
+    db:
+      deployment_hash: dep-db
+      private_address: 10.0.0.5
+      server:
+        host: 203.0.113.20
+"#;
+        let config = StackerConfig::from_str(yaml).unwrap();
+        assert_eq!(config.services[0].placement.as_deref(), Some("db"));
+        assert_eq!(config.deploy.target_name(None).as_deref(), Some("web"));
+        assert!(!config.validate_semantics().iter().any(|i| i.code == "E011"));
+
+        let mut broken = config.clone();
+        broken.services[0].ports.clear();
+        broken.deploy.targets.get_mut("db").unwrap().deployment_hash = None;
+        broken.deploy.targets.get_mut("db").unwrap().private_address = None;
+        broken.services.push(ServiceDefinition {
+            placement: Some("cache".to_string()),
+            ..broken.services[0].clone()
+        });
+        let e011: Vec<String> = broken
+            .validate_semantics()
+            .into_iter()
+            .filter(|i| i.code == "E011")
+            .map(|i| i.message)
+            .collect();
+        assert!(e011.iter().any(|m| m.contains("deployment_hash")));
+        assert!(e011.iter().any(|m| m.contains("ports")));
+        assert!(e011.iter().any(|m| m.contains("private_address")));
+        assert!(e011.iter().any(|m| m.contains("'cache'")));
+    }
+
     #[test]
     fn test_parse_ai_section_with_ollama() {
         let yaml = r#"
@@ -2371,6 +3267,7 @@ deploy:
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             })
             .deploy_target(DeployTarget::Cloud)
             .cloud(CloudConfig {
@@ -2445,6 +3342,7 @@ deploy:
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             })
             .add_service(ServiceDefinition {
                 name: "redis".to_string(),
@@ -2455,6 +3353,7 @@ deploy:
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             })
             .add_service(ServiceDefinition {
                 name: "minio".to_string(),
@@ -2465,6 +3364,7 @@ deploy:
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             })
             .build()
             .unwrap();
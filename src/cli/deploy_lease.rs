@@ -0,0 +1,140 @@
+//! Server-side deploy lease for `stacker deploy` and the rollback commands.
+//!
+//! Unlike `deployment_lock::DeploymentLock`, which only remembers where the
+//! last deploy went, the lease is held on the Stacker server so a teammate
+//! or CI job deploying the same deployment at the same time is refused
+//! instead of interleaving commands in the agent queue.
+
+use std::future::Future;
+
+use crate::cli::error::CliError;
+use crate::cli::runtime::CliRuntime;
+use crate::cli::stacker_client::StackerClient;
+use crate::models::DeploymentLease;
+use crate::services::deployment_lease::LeaseRequest;
+
+/// Lease lifetime requested by the CLI. A crashed client blocks others for
+/// at most this long; `--force` breaks the lease sooner.
+pub const DEPLOY_LEASE_TTL_SECS: i64 = 3600;
+
+/// `alice@example.com (laptop)`, or `(ci on runner-3)` inside CI.
+pub fn lease_owner(email: Option<&str>) -> String {
+    let user = email
+        .filter(|email| !email.trim().is_empty())
+        .unwrap_or("unknown user");
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "unknown host".to_string());
+    if std::env::var_os("CI").is_some() {
+        format!("{} (ci on {})", user, host)
+    } else {
+        format!("{} ({})", user, host)
+    }
+}
+
+pub fn lease_request(
+    email: Option<&str>,
+    reason: &str,
+    ttl_secs: i64,
+    force: bool,
+) -> LeaseRequest {
+    if force {
+        eprintln!("  ⚠ --force: taking the deploy lease even if someone else holds it (recorded in the lease audit log)");
+    }
+    LeaseRequest {
+        owner: lease_owner(email),
+        reason: reason.to_string(),
+        ttl_secs: Some(ttl_secs),
+        lease_id: None,
+        force,
+    }
+}
+
+/// Run `f` with the deploy lease held, releasing it afterwards whether `f`
+/// succeeded or not.
+pub async fn with_deploy_lease<T, F, Fut>(
+    client: &StackerClient,
+    deployment_hash: &str,
+    request: &LeaseRequest,
+    f: F,
+) -> Result<T, CliError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<T, CliError>>,
+{
+    let lease = client
+        .acquire_deployment_lease(deployment_hash, request)
+        .await?;
+    let result = f(lease.lease_id.clone()).await;
+    if let Err(err) = client
+        .release_deployment_lease(deployment_hash, &lease.lease_id)
+        .await
+    {
+        eprintln!(
+            "  ⚠ Deploy lease not released (it expires on its own): {}",
+            err
+        );
+    }
+    result
+}
+
+/// Holds the deploy lease for a synchronous command and releases it on drop.
+pub struct DeployLeaseGuard {
+    ctx: CliRuntime,
+    lease: DeploymentLease,
+}
+
+impl DeployLeaseGuard {
+    pub fn acquire(
+        deployment_hash: &str,
+        reason: &str,
+        ttl_secs: i64,
+        force: bool,
+    ) -> Result<Self, CliError> {
+        let ctx = CliRuntime::new("deploy")?;
+        let request = lease_request(ctx.creds.email.as_deref(), reason, ttl_secs, force);
+        let lease = ctx.block_on(
+            ctx.client
+                .acquire_deployment_lease(deployment_hash, &request),
+        )?;
+        Ok(Self { ctx, lease })
+    }
+
+    pub fn lease_id(&self) -> &str {
+        &self.lease.lease_id
+    }
+}
+
+impl Drop for DeployLeaseGuard {
+    fn drop(&mut self) {
+        let released = self.ctx.block_on(
+            self.ctx
+                .client
+                .release_deployment_lease(&self.lease.deployment_hash, &self.lease.lease_id),
+        );
+        if let Err(err) = released {
+            eprintln!(
+                "  ⚠ Deploy lease not released (it expires on its own): {}",
+                err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lease_request_names_owner_and_reason() {
+        let request = lease_request(Some("alice@example.com"), "stacker deploy", 600, false);
+        assert!(request.owner.starts_with("alice@example.com ("));
+        assert_eq!(request.reason, "stacker deploy");
+        assert_eq!(request.ttl_secs, Some(600));
+        assert!(!request.force);
+        assert!(lease_owner(None).starts_with("unknown user ("));
+    }
+}
@@ -116,6 +116,7 @@ pub enum CliError {
         command_id: String,
         error: String,
     },
+    FileTransferFailed(String),
 
     // IO errors
     Io(std::io::Error),
@@ -172,7 +173,7 @@ impl fmt::Display for CliError {
             Self::ContainerRuntimeUnavailable => {
                 write!(
                     f,
-                    "Docker is not running. Install Docker or start the Docker daemon."
+                    "Docker is not running. Install Docker (or Podman/nerdctl) or start the daemon; override detection with --container-runtime."
                 )
             }
             Self::CommandFailed { command, exit_code } => {
@@ -266,6 +267,9 @@ impl fmt::Display for CliError {
             Self::AgentCommandFailed { command_id, error } => {
                 write!(f, "Agent command '{command_id}' failed: {error}")
             }
+            Self::FileTransferFailed(msg) => {
+                write!(f, "File copy failed: {msg}")
+            }
             Self::Io(err) => {
                 write!(f, "I/O error: {err}")
             }
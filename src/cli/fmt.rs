@@ -24,6 +24,22 @@ pub fn pretty_json(value: &serde_json::Value) -> String {
     serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
 }
 
+/// Format a byte count with binary units, e.g. `5.0 MiB`.
+pub fn bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
 /// Display an optional string, returning the provided default when `None`.
 pub fn display_opt(opt: Option<&str>, default: &str) -> String {
     opt.unwrap_or(default).to_string()
@@ -43,6 +59,12 @@ mod tests {
         assert_eq!(truncate("abc", 3), "abc");
     }
 
+    #[test]
+    fn bytes_uses_binary_units() {
+        assert_eq!(bytes(512), "512 B");
+        assert_eq!(bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+
     #[test]
     fn truncate_long_string() {
         assert_eq!(truncate("hello world", 6), "hello…");
@@ -3,10 +3,12 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::path::Path;
 
+use crate::cli::acme;
 use crate::cli::config_parser::{
-    AppType, ComposeHealthcheck, DomainConfig, ProxyType, ServiceDefinition, StackerConfig,
+    AppType, ComposeHealthcheck, DomainConfig, ProxyType, ServiceDefinition, SslMode, StackerConfig,
 };
 use crate::cli::error::CliError;
+use crate::cli::proxy_manager;
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // ComposeService — represents one service in docker-compose
@@ -27,6 +29,8 @@ pub struct ComposeService {
     pub labels: HashMap<String, String>,
     /// Container runtime (e.g., "kata"). None or "runc" means default.
     pub runtime: Option<String>,
+    /// Override the container ENTRYPOINT (docker-compose `entrypoint:`).
+    pub entrypoint: Option<String>,
     /// Override the container CMD (docker-compose `command:`).
     pub command: Option<String>,
     /// Docker compose healthcheck for this service.
@@ -48,6 +52,7 @@ impl Default for ComposeService {
             networks: vec!["app-network".to_string()],
             labels: HashMap::new(),
             runtime: None,
+            entrypoint: None,
             command: None,
             healthcheck: None,
         }
@@ -143,9 +148,41 @@ impl TryFrom<&StackerConfig> for ComposeDefinition {
 
         // --- Proxy service ---
         if let Some(proxy_svc) = build_proxy_service(config) {
+            for vol in &proxy_svc.volumes {
+                if let Some(named) = extract_named_volume(vol) {
+                    if !named_volumes.contains(&named) {
+                        named_volumes.push(named);
+                    }
+                }
+            }
             compose.services.push(proxy_svc);
         }
 
+        // --- Traefik routers/middlewares as labels on the upstream services ---
+        if config.proxy.proxy_type == ProxyType::Traefik {
+            for domain in &config.proxy.domains {
+                for message in domain.unsupported_traefik_policies() {
+                    eprintln!("  ⚠ {} (W003)", message);
+                }
+            }
+            let labels = proxy_manager::traefik_labels(&config.proxy.domains)?;
+            for svc in compose.services.iter_mut() {
+                if let Some(service_labels) = labels.get(&svc.name) {
+                    svc.labels.extend(service_labels.clone());
+                }
+            }
+        }
+
+        // --- ACME companion for `ssl: auto` nginx domains ---
+        if acme::acme_enabled(config) {
+            compose.services.push(acme::certbot_service());
+            for named in [acme::WEBROOT_VOLUME, acme::CERTS_VOLUME] {
+                if !named_volumes.iter().any(|v| v == named) {
+                    named_volumes.push(named.to_string());
+                }
+            }
+        }
+
         // --- Set top-level volumes ---
         compose.volumes = named_volumes;
 
@@ -261,6 +298,20 @@ fn build_proxy_service(config: &StackerConfig) -> Option<ComposeService> {
             };
             svc.volumes
                 .push("./nginx/conf.d:/etc/nginx/conf.d:ro".to_string());
+            if config
+                .proxy
+                .domains
+                .iter()
+                .any(|d| !proxy_manager::nginx_htpasswd_files(d).is_empty())
+            {
+                svc.volumes.push(format!(
+                    "./nginx/htpasswd:{}:ro",
+                    proxy_manager::NGINX_HTPASSWD_DIR
+                ));
+            }
+            if acme::acme_enabled(config) {
+                acme::attach_to_nginx(&mut svc);
+            }
             Some(svc)
         }
         ProxyType::NginxProxyManager => {
@@ -295,6 +346,49 @@ fn build_proxy_service(config: &StackerConfig) -> Option<ComposeService> {
             };
             svc.volumes
                 .push("/var/run/docker.sock:/var/run/docker.sock:ro".to_string());
+            // Routes come from container labels (see `proxy_manager::traefik_labels`).
+            svc.command = Some(
+                [
+                    "--providers.docker=true",
+                    "--providers.docker.exposedbydefault=false",
+                    "--entrypoints.web.address=:80",
+                    "--entrypoints.websecure.address=:443",
+                ]
+                .join(" "),
+            );
+            if !acme::acme_managed_domains(config).is_empty() {
+                let acme_config = config.proxy.acme.clone().unwrap_or_default();
+                acme::attach_to_traefik(&mut svc, &acme_config);
+            }
+            Some(svc)
+        }
+        ProxyType::Caddy => {
+            let mut svc = ComposeService {
+                name: "caddy".to_string(),
+                image: Some("caddy:2-alpine".to_string()),
+                ports: vec!["80:80".to_string(), "443:443".to_string()],
+                depends_on: vec!["app".to_string()],
+                ..Default::default()
+            };
+            // The Caddyfile is written by `proxy_manager::write_caddy_assets`.
+            svc.volumes.push(format!(
+                "./{}/Caddyfile:/etc/caddy/Caddyfile:ro",
+                proxy_manager::CADDY_ASSETS_DIR
+            ));
+            if config
+                .proxy
+                .domains
+                .iter()
+                .any(|d| d.ssl == SslMode::Manual)
+            {
+                svc.volumes.push(format!(
+                    "./{}/ssl:{}:ro",
+                    proxy_manager::CADDY_ASSETS_DIR,
+                    proxy_manager::CADDY_SSL_DIR
+                ));
+            }
+            svc.volumes.push("caddy-data:/data".to_string());
+            svc.volumes.push("caddy-config:/config".to_string());
             Some(svc)
         }
         ProxyType::None => None,
@@ -385,6 +479,10 @@ impl ComposeDefinition {
                 }
             }
 
+            if let Some(ref entrypoint) = svc.entrypoint {
+                out.push_str(&format!("    entrypoint: {}\n", yaml_quote(entrypoint)));
+            }
+
             if let Some(ref cmd) = svc.command {
                 out.push_str(&format!("    command: {}\n", yaml_quote(cmd)));
             }
@@ -425,7 +523,7 @@ impl ComposeDefinition {
                 let mut keys: Vec<&String> = svc.labels.keys().collect();
                 keys.sort();
                 for k in keys {
-                    out.push_str(&format!("      {}: \"{}\"\n", k, svc.labels[k]));
+                    out.push_str(&format!("      {}: {}\n", k, yaml_quote(&svc.labels[k])));
                 }
             }
 
@@ -553,6 +651,7 @@ mod tests {
             depends_on: Vec::new(),
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = ConfigBuilder::new()
             .name("with-db")
@@ -577,6 +676,7 @@ mod tests {
                 auto_detect: true,
                 domains: Vec::new(),
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -589,6 +689,160 @@ mod tests {
         assert!(proxy.ports.contains(&"80:80".to_string()));
         assert!(proxy.ports.contains(&"443:443".to_string()));
         assert!(proxy.depends_on.contains(&"app".to_string()));
+        assert!(!compose.services.iter().any(|s| s.name == "certbot"));
+    }
+
+    #[test]
+    fn test_compose_nginx_ssl_auto_adds_certbot_companion() {
+        let config = ConfigBuilder::new()
+            .name("acme-app")
+            .app_type(AppType::Node)
+            .proxy(ProxyConfig {
+                proxy_type: ProxyType::Nginx,
+                auto_detect: false,
+                domains: vec![DomainConfig {
+                    domain: "app.example.com".into(),
+                    ssl: SslMode::Auto,
+                    upstream: "app:3000".into(),
+                    ..Default::default()
+                }],
+                config: None,
+                acme: None,
+            })
+            .build()
+            .unwrap();
+
+        let compose = ComposeDefinition::try_from(&config).unwrap();
+        let nginx = compose.services.iter().find(|s| s.name == "nginx").unwrap();
+        assert_eq!(
+            nginx.command.as_deref(),
+            Some("/bin/sh /stacker/acme-nginx.sh")
+        );
+        assert!(nginx
+            .volumes
+            .contains(&"certbot-webroot:/var/www/certbot:ro".to_string()));
+
+        let certbot = compose
+            .services
+            .iter()
+            .find(|s| s.name == "certbot")
+            .unwrap();
+        assert_eq!(certbot.image.as_deref(), Some("certbot/certbot:latest"));
+        assert!(certbot.depends_on.contains(&"nginx".to_string()));
+        assert!(compose.volumes.contains(&"letsencrypt".to_string()));
+
+        let yaml = compose.render();
+        assert!(yaml.contains("entrypoint: \"/bin/sh /stacker/acme-certbot.sh\""));
+    }
+
+    #[test]
+    fn test_compose_traefik_routes_become_upstream_labels() {
+        let config = ConfigBuilder::new()
+            .name("traefik-app")
+            .app_type(AppType::Node)
+            .proxy(ProxyConfig {
+                proxy_type: ProxyType::Traefik,
+                auto_detect: false,
+                domains: vec![DomainConfig {
+                    domain: "app.example.com".into(),
+                    ssl: SslMode::Off,
+                    upstream: "app:3000".into(),
+                    ..Default::default()
+                }],
+                config: None,
+                acme: None,
+            })
+            .build()
+            .unwrap();
+
+        let compose = ComposeDefinition::try_from(&config).unwrap();
+        let app = compose.services.iter().find(|s| s.name == "app").unwrap();
+        assert_eq!(
+            app.labels
+                .get("traefik.http.routers.app-example-com-root.rule"),
+            Some(&"Host(`app.example.com`)".to_string())
+        );
+        let traefik = compose
+            .services
+            .iter()
+            .find(|s| s.name == "traefik")
+            .unwrap();
+        assert!(traefik
+            .command
+            .as_deref()
+            .unwrap()
+            .contains("--providers.docker.exposedbydefault=false"));
+        assert!(compose.render().contains("traefik.enable: \"true\""));
+    }
+
+    #[test]
+    fn test_compose_traefik_ssl_auto_adds_cert_resolver() {
+        let config = ConfigBuilder::new()
+            .name("traefik-app")
+            .app_type(AppType::Node)
+            .proxy(ProxyConfig {
+                proxy_type: ProxyType::Traefik,
+                auto_detect: false,
+                domains: vec![DomainConfig {
+                    domain: "app.example.com".into(),
+                    ssl: SslMode::Auto,
+                    upstream: "app:3000".into(),
+                    ..Default::default()
+                }],
+                config: None,
+                acme: Some(crate::cli::config_parser::AcmeConfig {
+                    email: Some("ops@example.com".into()),
+                    staging: false,
+                    server: None,
+                }),
+            })
+            .build()
+            .unwrap();
+
+        let compose = ComposeDefinition::try_from(&config).unwrap();
+        let traefik = compose
+            .services
+            .iter()
+            .find(|s| s.name == "traefik")
+            .unwrap();
+        let command = traefik.command.as_deref().unwrap();
+        assert!(command
+            .contains("--certificatesresolvers.letsencrypt.acme.httpchallenge.entrypoint=web"));
+        assert!(command.contains("--certificatesresolvers.letsencrypt.acme.email=ops@example.com"));
+        assert!(traefik
+            .volumes
+            .contains(&"traefik-acme:/letsencrypt".to_string()));
+        assert!(compose.volumes.contains(&"traefik-acme".to_string()));
+        assert!(!compose.services.iter().any(|s| s.name == "certbot"));
+    }
+
+    #[test]
+    fn test_compose_caddy_proxy_mounts_caddyfile() {
+        let config = ConfigBuilder::new()
+            .name("caddy-app")
+            .app_type(AppType::Node)
+            .proxy(ProxyConfig {
+                proxy_type: ProxyType::Caddy,
+                auto_detect: false,
+                domains: vec![DomainConfig {
+                    domain: "app.example.com".into(),
+                    ssl: SslMode::Auto,
+                    upstream: "app:3000".into(),
+                    ..Default::default()
+                }],
+                config: None,
+                acme: None,
+            })
+            .build()
+            .unwrap();
+
+        let compose = ComposeDefinition::try_from(&config).unwrap();
+        let caddy = compose.services.iter().find(|s| s.name == "caddy").unwrap();
+        assert!(caddy
+            .volumes
+            .contains(&"./caddy/Caddyfile:/etc/caddy/Caddyfile:ro".to_string()));
+        assert!(compose.volumes.contains(&"caddy-data".to_string()));
+        assert!(compose.volumes.contains(&"caddy-config".to_string()));
     }
 
     #[test]
@@ -609,6 +863,7 @@ mod tests {
                 auto_detect: true,
                 domains: Vec::new(),
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -656,6 +911,7 @@ mod tests {
             depends_on: Vec::new(),
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = ConfigBuilder::new()
             .name("with-vol")
@@ -736,6 +992,7 @@ mod tests {
             depends_on: Vec::new(),
             command: None,
             healthcheck: None,
+            placement: None,
         };
 
         let compose_svc = ComposeService::from(&svc_def);
@@ -762,6 +1019,7 @@ mod tests {
             depends_on: Vec::new(),
             command: None,
             healthcheck: None,
+            placement: None,
         };
 
         let compose_svc = ComposeService::from(&svc_def);
@@ -857,6 +1115,7 @@ mod tests {
                 auto_detect: true,
                 domains: Vec::new(),
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -941,6 +1200,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = ConfigBuilder::new()
             .name("npm-proxied")
@@ -953,8 +1213,10 @@ mod tests {
                     domain: "api.example.com".into(),
                     ssl: SslMode::Auto,
                     upstream: "api:8080".into(),
+                    ..Default::default()
                 }],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -990,6 +1252,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = ConfigBuilder::new()
             .name("partial-proxy")
@@ -1002,8 +1265,10 @@ mod tests {
                     domain: "app.example.com".into(),
                     ssl: SslMode::Off,
                     upstream: "app:3000".into(),
+                    ..Default::default()
                 }],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -1027,6 +1292,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = ConfigBuilder::new()
             .name("traefik-app")
@@ -1039,8 +1305,10 @@ mod tests {
                     domain: "web.example.com".into(),
                     ssl: SslMode::Auto,
                     upstream: "web:80".into(),
+                    ..Default::default()
                 }],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -0,0 +1,1073 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::cli::config_parser::{ProxyType, RoutePolicy, SslMode, StackerConfig};
+use crate::cli::error::{CliError, Severity, ValidationIssue};
+use crate::cli::generator::compose::{ComposeDefinition, ComposeService};
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Kubernetes export — Deployments, Services, PVCs, Ingress
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//
+// The compose generator is the source of truth for which containers a
+// stacker.yml produces; this module maps that `ComposeDefinition` onto
+// Kubernetes objects instead of re-reading `StackerConfig` field by field.
+//
+// Secrets are never materialized: every `${VAR}` env value becomes a
+// `secretKeyRef` into one `<name>-secrets` Secret the operator creates.
+
+/// Default storage request for PVCs derived from named volumes.
+const DEFAULT_PVC_SIZE: &str = "1Gi";
+
+/// Proxy containers are replaced by the Ingress and never exported.
+const PROXY_SERVICE_NAMES: &[&str] = &["nginx", "proxy-manager", "traefik", "caddy"];
+
+/// One Kubernetes object, kept as JSON so it can be rendered as plain YAML
+/// or templated into a Helm chart.
+#[derive(Debug, Clone)]
+pub struct KubernetesObject {
+    pub kind: String,
+    pub name: String,
+    pub body: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct KubernetesManifests {
+    pub app_name: String,
+    pub objects: Vec<KubernetesObject>,
+    /// Name of the Secret all `secretKeyRef`s point at.
+    pub secret_name: String,
+    /// Keys the operator must provide in `secret_name`.
+    pub secret_keys: Vec<String>,
+    /// Per-service image and replica defaults (Helm `values.yaml`).
+    pub workloads: BTreeMap<String, WorkloadValues>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkloadValues {
+    pub image: String,
+    pub replicas: u32,
+}
+
+impl TryFrom<&StackerConfig> for KubernetesManifests {
+    type Error = CliError;
+
+    fn try_from(config: &StackerConfig) -> Result<Self, Self::Error> {
+        let compose = ComposeDefinition::try_from(config)?;
+        let app_name = k8s_name(&config.name);
+        let secret_name = format!("{}-secrets", app_name);
+        let mut objects = Vec::new();
+        let mut secret_keys = Vec::new();
+        let mut workloads = BTreeMap::new();
+
+        for volume in &compose.volumes {
+            objects.push(pvc_object(&app_name, volume));
+        }
+
+        for svc in exported_services(&compose) {
+            let image = match &svc.image {
+                Some(image) => image.clone(),
+                None => format!("{}-{}:latest", app_name, svc.name),
+            };
+            for value in svc.environment.values() {
+                for key in placeholder_keys(value) {
+                    if !secret_keys.contains(&key) {
+                        secret_keys.push(key);
+                    }
+                }
+            }
+
+            objects.push(deployment_object(&app_name, svc, &image, &secret_name));
+            objects.push(service_object(&app_name, svc));
+            workloads.insert(svc.name.clone(), WorkloadValues { image, replicas: 1 });
+        }
+
+        if let Some(ingress) = ingress_object(&app_name, config) {
+            objects.push(ingress);
+        }
+
+        secret_keys.sort();
+        Ok(Self {
+            app_name,
+            objects,
+            secret_name,
+            secret_keys,
+            workloads,
+        })
+    }
+}
+
+impl KubernetesManifests {
+    /// Render every object as one multi-document YAML stream.
+    pub fn render(&self) -> Result<String, CliError> {
+        let mut out = String::new();
+        if !self.secret_keys.is_empty() {
+            out.push_str(&format!(
+                "# Requires Secret '{}' with keys: {}\n",
+                self.secret_name,
+                self.secret_keys.join(", ")
+            ));
+        }
+        for object in &self.objects {
+            out.push_str("---\n");
+            out.push_str(&to_yaml(&object.body)?);
+        }
+        Ok(out)
+    }
+
+    /// Write one file per object into `dir` (`<kind>-<name>.yaml`).
+    pub fn write_to_dir(&self, dir: &Path) -> Result<Vec<String>, CliError> {
+        std::fs::create_dir_all(dir)?;
+        let mut written = Vec::new();
+        for object in &self.objects {
+            let file_name = format!("{}-{}.yaml", object.kind.to_lowercase(), object.name);
+            std::fs::write(dir.join(&file_name), to_yaml(&object.body)?)?;
+            written.push(file_name);
+        }
+        Ok(written)
+    }
+
+    /// Write a Helm chart into `dir`: `Chart.yaml`, `values.yaml` and one
+    /// template per object with image, replicas and ingress hosts driven
+    /// by values.
+    pub fn write_helm_chart(&self, dir: &Path, version: &str) -> Result<Vec<String>, CliError> {
+        let templates_dir = dir.join("templates");
+        std::fs::create_dir_all(&templates_dir)?;
+        let mut written = Vec::new();
+
+        let chart = json!({
+            "apiVersion": "v2",
+            "name": self.app_name,
+            "description": format!("Helm chart generated by stacker for {}", self.app_name),
+            "type": "application",
+            "version": "0.1.0",
+            "appVersion": version,
+        });
+        std::fs::write(dir.join("Chart.yaml"), to_yaml(&chart)?)?;
+        written.push("Chart.yaml".to_string());
+
+        std::fs::write(dir.join("values.yaml"), to_yaml(&self.helm_values())?)?;
+        written.push("values.yaml".to_string());
+
+        for object in &self.objects {
+            let file_name = format!(
+                "templates/{}-{}.yaml",
+                object.kind.to_lowercase(),
+                object.name
+            );
+            let templated = helm_template(object);
+            std::fs::write(
+                dir.join(&file_name),
+                unquote_templates(&to_yaml(&templated)?),
+            )?;
+            written.push(file_name);
+        }
+
+        Ok(written)
+    }
+
+    fn helm_values(&self) -> Value {
+        let services: serde_json::Map<String, Value> = self
+            .workloads
+            .iter()
+            .map(|(name, workload)| {
+                (
+                    // Templates look services up by their component label.
+                    values_key(&k8s_name(name)),
+                    json!({ "image": workload.image, "replicas": workload.replicas }),
+                )
+            })
+            .collect();
+        let hosts: Vec<Value> = self
+            .objects
+            .iter()
+            .filter(|object| object.kind == "Ingress")
+            .flat_map(|object| {
+                object.body["spec"]["rules"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .filter_map(|rule| rule["host"].as_str().map(|host| json!(host)))
+            .collect();
+
+        json!({
+            "services": services,
+            "secretName": self.secret_name,
+            "ingress": {
+                "enabled": !hosts.is_empty(),
+                "hosts": hosts,
+            },
+        })
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Object builders
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+fn exported_services(compose: &ComposeDefinition) -> impl Iterator<Item = &ComposeService> {
+    compose
+        .services
+        .iter()
+        .filter(|svc| !PROXY_SERVICE_NAMES.contains(&svc.name.as_str()))
+}
+
+fn labels(app_name: &str, component: &str) -> Value {
+    json!({
+        "app.kubernetes.io/name": app_name,
+        "app.kubernetes.io/component": k8s_name(component),
+        "app.kubernetes.io/managed-by": "stacker",
+    })
+}
+
+fn deployment_object(
+    app_name: &str,
+    svc: &ComposeService,
+    image: &str,
+    secret_name: &str,
+) -> KubernetesObject {
+    let name = k8s_name(&svc.name);
+    let mut env_keys: Vec<&String> = svc.environment.keys().collect();
+    env_keys.sort();
+    let env: Vec<Value> = env_keys
+        .into_iter()
+        .map(|key| {
+            let value = &svc.environment[key];
+            match placeholder_keys(value).as_slice() {
+                [secret_key] if value.trim() == format!("${{{}}}", secret_key) => json!({
+                    "name": key,
+                    "valueFrom": { "secretKeyRef": { "name": secret_name, "key": secret_key } },
+                }),
+                _ => json!({ "name": key, "value": value }),
+            }
+        })
+        .collect();
+
+    let ports: Vec<Value> = svc
+        .ports
+        .iter()
+        .filter_map(|port| container_port(port))
+        .map(|port| json!({ "containerPort": port }))
+        .collect();
+
+    let mut volume_mounts = Vec::new();
+    let mut volumes = Vec::new();
+    for volume in &svc.volumes {
+        let Some((source, target)) = named_volume_mount(volume) else {
+            continue;
+        };
+        let volume_name = k8s_name(&source);
+        volume_mounts.push(json!({ "name": volume_name, "mountPath": target }));
+        volumes.push(json!({
+            "name": volume_name,
+            "persistentVolumeClaim": { "claimName": format!("{}-{}", app_name, volume_name) },
+        }));
+    }
+
+    let mut container = json!({
+        "name": name,
+        "image": image,
+    });
+    if !env.is_empty() {
+        container["env"] = json!(env);
+    }
+    if !ports.is_empty() {
+        container["ports"] = json!(ports);
+    }
+    if !volume_mounts.is_empty() {
+        container["volumeMounts"] = json!(volume_mounts);
+    }
+    if let Some(command) = &svc.command {
+        // Compose splits a string `command` into arguments for the image's
+        // ENTRYPOINT rather than running it through a shell.
+        let args = split_command(command)
+            .unwrap_or_else(|| command.split_whitespace().map(ToOwned::to_owned).collect());
+        container["args"] = json!(args);
+    }
+    if let Some(healthcheck) = &svc.healthcheck {
+        container["livenessProbe"] = json!({
+            "exec": { "command": probe_command(&healthcheck.test) },
+            "periodSeconds": duration_seconds(&healthcheck.interval).unwrap_or(30),
+            "timeoutSeconds": duration_seconds(&healthcheck.timeout).unwrap_or(5),
+            "failureThreshold": healthcheck.retries.max(1),
+        });
+    }
+
+    let mut pod_spec = json!({ "containers": [container] });
+    if !volumes.is_empty() {
+        pod_spec["volumes"] = json!(volumes);
+    }
+    if let Some(runtime) = svc.runtime.as_deref().filter(|rt| *rt != "runc") {
+        pod_spec["runtimeClassName"] = json!(runtime);
+    }
+
+    KubernetesObject {
+        kind: "Deployment".to_string(),
+        name: format!("{}-{}", app_name, name),
+        body: json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": format!("{}-{}", app_name, name), "labels": labels(app_name, &svc.name) },
+            "spec": {
+                "replicas": 1,
+                "selector": { "matchLabels": labels(app_name, &svc.name) },
+                "template": {
+                    "metadata": { "labels": labels(app_name, &svc.name) },
+                    "spec": pod_spec,
+                },
+            },
+        }),
+    }
+}
+
+/// A ClusterIP Service named after the compose service, so in-cluster DNS
+/// (`postgres`, `redis`, ...) keeps working for the other workloads.
+/// Services without ports get a headless Service, which still resolves.
+fn service_object(app_name: &str, svc: &ComposeService) -> KubernetesObject {
+    let ports: Vec<Value> = svc
+        .ports
+        .iter()
+        .filter_map(|port| container_port(port))
+        .map(|port| json!({ "name": format!("p{}", port), "port": port, "targetPort": port }))
+        .collect();
+
+    let mut spec = json!({
+        "type": "ClusterIP",
+        "selector": labels(app_name, &svc.name),
+    });
+    if ports.is_empty() {
+        spec["clusterIP"] = json!("None");
+    } else {
+        spec["ports"] = json!(ports);
+    }
+
+    let name = k8s_name(&svc.name);
+    KubernetesObject {
+        kind: "Service".to_string(),
+        name: name.clone(),
+        body: json!({
+            "apiVersion": "v1",
+            "kind": "Service",
+            "metadata": { "name": name, "labels": labels(app_name, &svc.name) },
+            "spec": spec,
+        }),
+    }
+}
+
+fn pvc_object(app_name: &str, volume: &str) -> KubernetesObject {
+    let name = format!("{}-{}", app_name, k8s_name(volume));
+    KubernetesObject {
+        kind: "PersistentVolumeClaim".to_string(),
+        name: name.clone(),
+        body: json!({
+            "apiVersion": "v1",
+            "kind": "PersistentVolumeClaim",
+            "metadata": { "name": name, "labels": labels(app_name, volume) },
+            "spec": {
+                "accessModes": ["ReadWriteOnce"],
+                "resources": { "requests": { "storage": DEFAULT_PVC_SIZE } },
+            },
+        }),
+    }
+}
+
+/// Ingress rules derived from `proxy.domains`. `ssl: auto` domains get a
+/// cert-manager annotation and a TLS secret per host. Routes whose upstream
+/// is not `<service>:<port>` are skipped with a warning (K8S006).
+fn ingress_object(app_name: &str, config: &StackerConfig) -> Option<KubernetesObject> {
+    if config.proxy.domains.is_empty() {
+        return None;
+    }
+
+    let mut rules = Vec::new();
+    let mut tls = Vec::new();
+    for domain in &config.proxy.domains {
+        let mut paths = Vec::new();
+        for route in domain.effective_routes() {
+            let Some((service, port)) = upstream_target(&route.upstream) else {
+                eprintln!(
+                    "  ⚠ Skipping Ingress route {}{}: upstream '{}' is not <service>:<port>",
+                    domain.domain, route.path, route.upstream
+                );
+                continue;
+            };
+            paths.push(json!({
+                "path": route.path,
+                "pathType": "Prefix",
+                "backend": { "service": { "name": k8s_name(&service), "port": { "number": port } } },
+            }));
+        }
+        if paths.is_empty() {
+            continue;
+        }
+        rules.push(json!({
+            "host": domain.domain,
+            "http": { "paths": paths },
+        }));
+        if domain.ssl == SslMode::Auto {
+            tls.push(json!({
+                "hosts": [domain.domain],
+                "secretName": format!("{}-tls", k8s_name(&domain.domain)),
+            }));
+        }
+    }
+
+    if rules.is_empty() {
+        return None;
+    }
+
+    let name = format!("{}-ingress", app_name);
+    let mut body = json!({
+        "apiVersion": "networking.k8s.io/v1",
+        "kind": "Ingress",
+        "metadata": { "name": name, "labels": labels(app_name, "ingress") },
+        "spec": { "rules": rules },
+    });
+    if !tls.is_empty() {
+        body["metadata"]["annotations"] =
+            json!({ "cert-manager.io/cluster-issuer": "letsencrypt" });
+        body["spec"]["tls"] = json!(tls);
+    }
+
+    Some(KubernetesObject {
+        kind: "Ingress".to_string(),
+        name,
+        body,
+    })
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Compatibility report — `stacker config validate --target k8s`
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Report stacker.yml features that have no Kubernetes equivalent or are
+/// only approximated by the export.
+pub fn unsupported_features(config: &StackerConfig) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let compose = match ComposeDefinition::try_from(config) {
+        Ok(compose) => compose,
+        Err(err) => {
+            issues.push(k8s_issue(Severity::Error, "K8S000", err.to_string(), None));
+            return issues;
+        }
+    };
+
+    for svc in exported_services(&compose) {
+        let field = if svc.name == "app" {
+            "app".to_string()
+        } else {
+            format!("services.{}", svc.name)
+        };
+        if svc.image.is_none() {
+            issues.push(k8s_issue(
+                Severity::Error,
+                "K8S001",
+                format!(
+                    "'{}' is built from source; Kubernetes needs a pushed image. Set app.image or push one with `stacker build`.",
+                    svc.name
+                ),
+                Some(format!("{field}.image")),
+            ));
+        }
+        for volume in &svc.volumes {
+            if named_volume_mount(volume).is_none() {
+                issues.push(k8s_issue(
+                    Severity::Warning,
+                    "K8S002",
+                    format!(
+                        "bind mount '{}' on '{}' is skipped; use a named volume (PVC) or a ConfigMap",
+                        volume, svc.name
+                    ),
+                    Some(format!("{field}.volumes")),
+                ));
+            }
+        }
+        for port in &svc.ports {
+            if port.split(':').count() > 2 {
+                issues.push(k8s_issue(
+                    Severity::Warning,
+                    "K8S003",
+                    format!(
+                        "host-IP binding in port '{}' on '{}' is dropped; expose it through a Service or Ingress",
+                        port, svc.name
+                    ),
+                    Some(format!("{field}.ports")),
+                ));
+            }
+        }
+        if let Some(command) = &svc.command {
+            if split_command(command).is_none() {
+                issues.push(k8s_issue(
+                    Severity::Error,
+                    "K8S010",
+                    format!(
+                        "command on '{}' has an unterminated quote and cannot be split into container args",
+                        svc.name
+                    ),
+                    Some(format!("{field}.command")),
+                ));
+            }
+        }
+        if let Some(healthcheck) = &svc.healthcheck {
+            if !healthcheck.test.starts_with("CMD") {
+                issues.push(k8s_issue(
+                    Severity::Info,
+                    "K8S004",
+                    format!(
+                        "healthcheck on '{}' is translated to an exec liveness probe via `sh -c`",
+                        svc.name
+                    ),
+                    Some(format!("{field}.healthcheck")),
+                ));
+            }
+        }
+    }
+
+    if matches!(
+        config.proxy.proxy_type,
+        ProxyType::NginxProxyManager | ProxyType::Traefik | ProxyType::Caddy
+    ) || config.proxy.config.is_some()
+    {
+        issues.push(k8s_issue(
+            Severity::Warning,
+            "K8S005",
+            format!(
+                "proxy.type '{}' and custom proxy config are replaced by an Ingress; only proxy.domains are exported",
+                config.proxy.proxy_type
+            ),
+            Some("proxy".to_string()),
+        ));
+    }
+    for domain in &config.proxy.domains {
+        for route in domain.effective_routes() {
+            if upstream_target(&route.upstream).is_none() {
+                issues.push(k8s_issue(
+                    Severity::Error,
+                    "K8S006",
+                    format!(
+                        "upstream '{}' for {}{} must be <service>:<port> to become an Ingress backend",
+                        route.upstream,
+                        domain.domain,
+                        if route.path == "/" { "" } else { &route.path }
+                    ),
+                    Some("proxy.domains".to_string()),
+                ));
+            }
+        }
+        if domain
+            .effective_routes()
+            .iter()
+            .any(|route| route.policy != RoutePolicy::default())
+        {
+            issues.push(k8s_issue(
+                Severity::Warning,
+                "K8S009",
+                format!(
+                    "route policies for {} (websocket, body size, timeouts, auth, allow_ips, rate_limit) are not exported; set them as ingress-controller annotations",
+                    domain.domain
+                ),
+                Some("proxy.domains".to_string()),
+            ));
+        }
+    }
+
+    let hooks = &config.hooks;
+    if hooks.pre_build.is_some() || hooks.post_deploy.is_some() || hooks.on_failure.is_some() {
+        issues.push(k8s_issue(
+            Severity::Warning,
+            "K8S007",
+            "hooks are not exported; run them from your CI pipeline or as Kubernetes Jobs"
+                .to_string(),
+            Some("hooks".to_string()),
+        ));
+    }
+    if config.monitoring.status_panel {
+        issues.push(k8s_issue(
+            Severity::Info,
+            "K8S008",
+            "the Status Panel agent targets Docker hosts and is not part of the export".to_string(),
+            Some("monitoring.status_panel".to_string()),
+        ));
+    }
+
+    issues
+}
+
+fn k8s_issue(
+    severity: Severity,
+    code: &str,
+    message: String,
+    field: Option<String>,
+) -> ValidationIssue {
+    ValidationIssue {
+        severity,
+        code: code.to_string(),
+        message,
+        field,
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Helpers
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Lowercase RFC 1123 label: `My_App` → `my-app`.
+pub fn k8s_name(value: &str) -> String {
+    let mut name: String = value
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    while name.contains("--") {
+        name = name.replace("--", "-");
+    }
+    let name = name.trim_matches('-');
+    let name: String = name.chars().take(63).collect();
+    if name.is_empty() {
+        "app".to_string()
+    } else {
+        name.trim_end_matches('-').to_string()
+    }
+}
+
+/// `"8080:80"` → 80, `"127.0.0.1:8080:80/tcp"` → 80, `"3000"` → 3000.
+fn container_port(port: &str) -> Option<u16> {
+    port.rsplit(':')
+        .next()?
+        .split('/')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// `"pgdata:/var/lib/postgresql/data"` → `("pgdata", "/var/lib/...")`.
+/// Bind mounts return `None`.
+fn named_volume_mount(volume: &str) -> Option<(String, String)> {
+    let mut parts = volume.split(':');
+    let source = parts.next()?;
+    let target = parts.next()?;
+    if source.is_empty() || source.starts_with('.') || source.starts_with('/') {
+        return None;
+    }
+    Some((source.to_string(), target.to_string()))
+}
+
+/// `"app:3000"` / `"http://app:3000/"` → `("app", 3000)`.
+fn upstream_target(upstream: &str) -> Option<(String, u16)> {
+    let host = upstream
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()?;
+    let (service, port) = host.split_once(':')?;
+    if service.is_empty() {
+        return None;
+    }
+    Some((service.to_string(), port.parse().ok()?))
+}
+
+/// Split a compose `command` string into arguments with shell quoting rules
+/// and no expansion, as compose does. `None` for an unterminated quote.
+fn split_command(command: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => current.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => {
+                            let escaped = chars.next()?;
+                            if !matches!(escaped, '"' | '\\' | '$' | '`') {
+                                current.push('\\');
+                            }
+                            current.push(escaped);
+                        }
+                        c => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.push(chars.next()?);
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        args.push(current);
+    }
+    Some(args)
+}
+
+fn placeholder_keys(value: &str) -> Vec<String> {
+    let re = regex::Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid regex");
+    re.captures_iter(value)
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
+
+/// Compose `CMD-SHELL foo` / `CMD foo bar` → exec probe command.
+fn probe_command(test: &str) -> Vec<String> {
+    let trimmed = test.trim();
+    if let Some(rest) = trimmed.strip_prefix("CMD-SHELL") {
+        return vec!["sh".into(), "-c".into(), rest.trim().to_string()];
+    }
+    if let Some(rest) = trimmed.strip_prefix("CMD") {
+        return rest.split_whitespace().map(ToOwned::to_owned).collect();
+    }
+    vec!["sh".into(), "-c".into(), trimmed.to_string()]
+}
+
+/// `"30s"` → 30, `"2m"` → 120.
+fn duration_seconds(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit())?);
+    let number: u64 = number.parse().ok()?;
+    match unit {
+        "s" => Some(number),
+        "m" => Some(number * 60),
+        "h" => Some(number * 3600),
+        _ => None,
+    }
+}
+
+fn to_yaml(value: &Value) -> Result<String, CliError> {
+    serde_yaml::to_string(value)
+        .map_err(|e| CliError::GeneratorError(format!("Failed to render manifest: {e}")))
+}
+
+/// Helm values keys must be valid Go template identifiers.
+fn values_key(name: &str) -> String {
+    let mut key = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            key.push(if upper { c.to_ascii_uppercase() } else { c });
+            upper = false;
+        } else {
+            upper = !key.is_empty();
+        }
+    }
+    key
+}
+
+/// Swap image, replicas and ingress hosts for `.Values` lookups.
+fn helm_template(object: &KubernetesObject) -> Value {
+    let mut body = object.body.clone();
+    match object.kind.as_str() {
+        "Deployment" => {
+            let component = body["metadata"]["labels"]["app.kubernetes.io/component"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            let key = values_key(&component);
+            body["spec"]["replicas"] = json!(format!("{{{{ .Values.services.{key}.replicas }}}}"));
+            if let Some(containers) = body["spec"]["template"]["spec"]["containers"].as_array_mut()
+            {
+                for container in containers {
+                    container["image"] =
+                        json!(format!("{{{{ .Values.services.{key}.image | quote }}}}"));
+                    if let Some(env) = container["env"].as_array_mut() {
+                        for entry in env {
+                            if entry["valueFrom"]["secretKeyRef"].is_object() {
+                                entry["valueFrom"]["secretKeyRef"]["name"] =
+                                    json!("{{ .Values.secretName | quote }}");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        "Ingress" => {
+            if let Some(rules) = body["spec"]["rules"].as_array_mut() {
+                for (index, rule) in rules.iter_mut().enumerate() {
+                    rule["host"] = json!(format!(
+                        "{{{{ index .Values.ingress.hosts {index} | quote }}}}"
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+    body
+}
+
+/// serde_yaml quotes `{{ ... }}` scalars; Helm needs them bare so that
+/// `replicas` renders as an integer.
+fn unquote_templates(rendered: &str) -> String {
+    let re = regex::Regex::new(r#"'(\{\{.*?\}\})'|"(\{\{.*?\}\})""#).expect("valid regex");
+    let body = re
+        .replace_all(rendered, |caps: &regex::Captures| {
+            caps.get(1)
+                .or_else(|| caps.get(2))
+                .map(|m| m.as_str().replace("''", "'").replace("\\\"", "\""))
+                .unwrap_or_default()
+        })
+        .to_string();
+
+    if rendered.contains("kind: Ingress") {
+        format!(
+            "{{{{- if .Values.ingress.enabled }}}}\n{}{{{{- end }}}}\n",
+            body
+        )
+    } else {
+        body
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Tests
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> StackerConfig {
+        StackerConfig::from_str(yaml).unwrap()
+    }
+
+    const SAMPLE: &str = r#"
+name: My_Shop
+app:
+  type: node
+  image: ghcr.io/acme/shop:1.0
+  ports: ["3000:3000"]
+  environment:
+    NODE_ENV: production
+services:
+  - name: postgres
+    image: postgres:16
+    ports: ["5432"]
+    volumes: ["pgdata:/var/lib/postgresql/data"]
+proxy:
+  type: nginx
+  domains:
+    - domain: shop.example.com
+      ssl: auto
+      upstream: app:3000
+"#;
+
+    #[test]
+    fn test_k8s_name_sanitizes() {
+        assert_eq!(k8s_name("My_Shop"), "my-shop");
+        assert_eq!(k8s_name("--a..b--"), "a-b");
+        assert_eq!(k8s_name("___"), "app");
+    }
+
+    #[test]
+    fn test_manifests_cover_workloads_pvcs_and_ingress() {
+        let manifests = KubernetesManifests::try_from(&config(SAMPLE)).unwrap();
+        let kinds: Vec<(&str, &str)> = manifests
+            .objects
+            .iter()
+            .map(|o| (o.kind.as_str(), o.name.as_str()))
+            .collect();
+
+        assert!(kinds.contains(&("PersistentVolumeClaim", "my-shop-pgdata")));
+        assert!(kinds.contains(&("Deployment", "my-shop-app")));
+        assert!(kinds.contains(&("Deployment", "my-shop-postgres")));
+        assert!(kinds.contains(&("Service", "postgres")));
+        assert!(kinds.contains(&("Ingress", "my-shop-ingress")));
+        assert!(!kinds.iter().any(|(_, name)| name.contains("nginx")));
+
+        let ingress = manifests
+            .objects
+            .iter()
+            .find(|o| o.kind == "Ingress")
+            .unwrap();
+        assert_eq!(
+            ingress.body["spec"]["rules"][0]["http"]["paths"][0]["backend"]["service"]["name"],
+            "app"
+        );
+        assert_eq!(
+            ingress.body["spec"]["tls"][0]["hosts"][0],
+            "shop.example.com"
+        );
+    }
+
+    #[test]
+    fn test_placeholder_env_becomes_secret_reference() {
+        let mut cfg = config(SAMPLE);
+        cfg.app.environment.insert(
+            "DATABASE_PASSWORD".to_string(),
+            "${DB_PASSWORD}".to_string(),
+        );
+        let manifests = KubernetesManifests::try_from(&cfg).unwrap();
+
+        assert_eq!(manifests.secret_keys, vec!["DB_PASSWORD"]);
+        let app = manifests
+            .objects
+            .iter()
+            .find(|o| o.name == "my-shop-app")
+            .unwrap();
+        let env = app.body["spec"]["template"]["spec"]["containers"][0]["env"]
+            .as_array()
+            .unwrap();
+        let secret = env
+            .iter()
+            .find(|e| e["name"] == "DATABASE_PASSWORD")
+            .unwrap();
+        assert_eq!(secret["valueFrom"]["secretKeyRef"]["key"], "DB_PASSWORD");
+        assert!(secret.get("value").is_none());
+        assert!(manifests
+            .render()
+            .unwrap()
+            .contains("# Requires Secret 'my-shop-secrets'"));
+    }
+
+    #[test]
+    fn test_helm_chart_templates_images_and_replicas() {
+        let manifests = KubernetesManifests::try_from(&config(SAMPLE)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let written = manifests.write_helm_chart(dir.path(), "1.0").unwrap();
+
+        assert!(written.contains(&"Chart.yaml".to_string()));
+        let deployment =
+            std::fs::read_to_string(dir.path().join("templates/deployment-my-shop-app.yaml"))
+                .unwrap();
+        assert!(deployment.contains("replicas: {{ .Values.services.app.replicas }}"));
+        assert!(deployment.contains("image: {{ .Values.services.app.image | quote }}"));
+
+        let values = std::fs::read_to_string(dir.path().join("values.yaml")).unwrap();
+        assert!(values.contains("ghcr.io/acme/shop:1.0"));
+        assert!(values.contains("shop.example.com"));
+    }
+
+    #[test]
+    fn test_unsupported_features_reports_build_and_bind_mounts() {
+        let cfg = config(
+            r#"
+name: local-app
+app:
+  type: node
+  volumes: ["./src:/app/src"]
+hooks:
+  pre_build: ./scripts/build.sh
+"#,
+        );
+        let codes: Vec<String> = unsupported_features(&cfg)
+            .into_iter()
+            .map(|issue| issue.code)
+            .collect();
+        assert!(codes.contains(&"K8S001".to_string()));
+        assert!(codes.contains(&"K8S002".to_string()));
+        assert!(codes.contains(&"K8S007".to_string()));
+    }
+
+    #[test]
+    fn test_command_is_split_into_args() {
+        assert_eq!(
+            split_command(r#"worker --queue "high prio" --name='a b' c\ d"#).unwrap(),
+            vec!["worker", "--queue", "high prio", "--name=a b", "c d"]
+        );
+        assert_eq!(
+            split_command("sh -c 'migrate && serve'").unwrap(),
+            vec!["sh", "-c", "migrate && serve"]
+        );
+        assert!(split_command("echo 'oops").is_none());
+
+        let mut cfg = config(SAMPLE);
+        cfg.app.command = Some("serve --port 3000".to_string());
+        let manifests = KubernetesManifests::try_from(&cfg).unwrap();
+        let app = manifests
+            .objects
+            .iter()
+            .find(|o| o.name == "my-shop-app")
+            .unwrap();
+        assert_eq!(
+            app.body["spec"]["template"]["spec"]["containers"][0]["args"],
+            json!(["serve", "--port", "3000"])
+        );
+    }
+
+    #[test]
+    fn test_every_service_gets_dns_and_bad_routes_are_skipped() {
+        let cfg = config(
+            r#"
+name: shop
+app:
+  type: node
+  image: shop:1
+  ports: ["3000:3000"]
+services:
+  - name: Queue_Worker
+    image: worker:1
+proxy:
+  type: nginx
+  domains:
+    - domain: shop.example.com
+      upstream: app:3000
+    - domain: broken.example.com
+      upstream: app
+"#,
+        );
+        let manifests = KubernetesManifests::try_from(&cfg).unwrap();
+        let worker = manifests
+            .objects
+            .iter()
+            .find(|o| o.kind == "Service" && o.name == "queue-worker")
+            .unwrap();
+        assert_eq!(worker.body["spec"]["clusterIP"], "None");
+        assert!(worker.body["spec"].get("ports").is_none());
+
+        let ingress = manifests
+            .objects
+            .iter()
+            .find(|o| o.kind == "Ingress")
+            .unwrap();
+        let rules = ingress.body["spec"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0]["host"], "shop.example.com");
+
+        // Values and templates agree on the key for names with capitals
+        // and underscores.
+        let dir = tempfile::tempdir().unwrap();
+        manifests.write_helm_chart(dir.path(), "1.0").unwrap();
+        let deployment = std::fs::read_to_string(
+            dir.path()
+                .join("templates/deployment-shop-queue-worker.yaml"),
+        )
+        .unwrap();
+        assert!(deployment.contains(".Values.services.queueWorker.image"));
+        let values = std::fs::read_to_string(dir.path().join("values.yaml")).unwrap();
+        assert!(values.contains("queueWorker:"));
+    }
+
+    #[test]
+    fn test_container_port_and_duration_parsing() {
+        assert_eq!(container_port("8080:80"), Some(80));
+        assert_eq!(container_port("127.0.0.1:8080:80/tcp"), Some(80));
+        assert_eq!(container_port("3000"), Some(3000));
+        assert_eq!(duration_seconds("30s"), Some(30));
+        assert_eq!(duration_seconds("2m"), Some(120));
+    }
+}
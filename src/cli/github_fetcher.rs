@@ -1,3 +1,5 @@
+use sha2::{Digest, Sha256};
+
 use crate::cli::config_parser::ComposeHealthcheck;
 use crate::cli::error::CliError;
 
@@ -140,6 +142,234 @@ pub fn default_healthcheck(image: &str) -> Option<ComposeHealthcheck> {
     }
 }
 
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Remote compose fetching
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Compose file names probed, in order, when no explicit path is given.
+pub const COMPOSE_FILE_CANDIDATES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yml",
+    "docker-compose.yaml",
+];
+
+const GITHUB_API_BASE: &str = "https://api.github.com";
+const GITHUB_RAW_BASE: &str = "https://raw.githubusercontent.com";
+const FETCH_TIMEOUT_SECS: u64 = 30;
+/// Compose files are small; refuse anything that looks like a mistake.
+const MAX_COMPOSE_BYTES: usize = 1024 * 1024;
+
+/// A compose document fetched from GitHub or a plain URL, with enough
+/// metadata to pin and later re-fetch the exact same content.
+#[derive(Debug, Clone)]
+pub struct FetchedCompose {
+    pub content: String,
+    /// Final URL the content was read from.
+    pub url: String,
+    /// `owner/repo` for GitHub sources.
+    pub repository: Option<String>,
+    /// Ref the user asked for (branch, tag or commit); `None` means default branch.
+    pub requested_ref: Option<String>,
+    /// Commit SHA the ref resolved to at fetch time.
+    pub resolved_commit: Option<String>,
+    /// Path of the compose file inside the repository.
+    pub compose_path: Option<String>,
+    pub sha256: String,
+}
+
+/// Fetch a compose file from a GitHub repository, pinned to the commit that
+/// `git_ref` (or the default branch) resolves to right now.
+pub fn fetch_github_compose(
+    repo: &str,
+    git_ref: Option<&str>,
+    compose_path: Option<&str>,
+) -> Result<FetchedCompose, CliError> {
+    let (owner, repo) = parse_github_url(repo)?;
+    let client = http_client()?;
+    let requested_ref = git_ref
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToOwned::to_owned);
+    let commit = resolve_github_commit(
+        &client,
+        &owner,
+        &repo,
+        requested_ref.as_deref().unwrap_or("HEAD"),
+    )?;
+
+    let candidates: Vec<String> = match compose_path {
+        Some(path) => vec![path.trim_start_matches('/').to_string()],
+        None => COMPOSE_FILE_CANDIDATES
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    };
+
+    for candidate in &candidates {
+        let url = format!(
+            "{}/{}/{}/{}/{}",
+            GITHUB_RAW_BASE, owner, repo, commit, candidate
+        );
+        if let Some(content) = get_text(&client, &url)? {
+            return Ok(FetchedCompose {
+                sha256: sha256_hex(content.as_bytes()),
+                content,
+                url,
+                repository: Some(format!("{}/{}", owner, repo)),
+                requested_ref,
+                resolved_commit: Some(commit),
+                compose_path: Some(candidate.clone()),
+            });
+        }
+    }
+
+    Err(CliError::ConfigValidation(format!(
+        "No compose file found in {}/{} at {} (tried: {}). Use --compose-path to point at it.",
+        owner,
+        repo,
+        commit,
+        candidates.join(", ")
+    )))
+}
+
+/// Fetch a compose file from an HTTPS URL, pinned to its SHA-256: a URL can
+/// serve different content on every request.
+pub fn fetch_compose_url(url: &str, sha256: &str) -> Result<FetchedCompose, CliError> {
+    let url = url.trim();
+    if !url.starts_with("https://") {
+        return Err(CliError::ConfigValidation(format!(
+            "Refusing to import from '{}': only https:// URLs are supported",
+            url
+        )));
+    }
+
+    let client = http_client()?;
+    let content = get_text(&client, url)?.ok_or_else(|| {
+        CliError::ConfigValidation(format!("Compose file not found at {} (HTTP 404)", url))
+    })?;
+
+    let digest = sha256_hex(content.as_bytes());
+    check_pinned_sha256(url, &digest, sha256)?;
+
+    Ok(FetchedCompose {
+        sha256: digest,
+        content,
+        url: url.to_string(),
+        repository: None,
+        requested_ref: None,
+        resolved_commit: None,
+        compose_path: None,
+    })
+}
+
+/// Refuse content whose digest differs from the one the user pinned.
+fn check_pinned_sha256(url: &str, actual: &str, expected: &str) -> Result<(), CliError> {
+    let expected = expected.trim();
+    if expected.eq_ignore_ascii_case(actual) {
+        return Ok(());
+    }
+    Err(CliError::ConfigValidation(format!(
+        "Compose file at {} has sha256 {}, expected {}",
+        url, actual, expected
+    )))
+}
+
+fn http_client() -> Result<reqwest::blocking::Client, CliError> {
+    reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .user_agent("stacker-cli")
+        .build()
+        .map_err(|e| CliError::ConfigValidation(format!("HTTP client error: {e}")))
+}
+
+/// Resolve a branch, tag or commit to a full commit SHA via the GitHub API.
+/// Honors `GITHUB_TOKEN` for private repositories and rate limits.
+fn resolve_github_commit(
+    client: &reqwest::blocking::Client,
+    owner: &str,
+    repo: &str,
+    git_ref: &str,
+) -> Result<String, CliError> {
+    let url = format!(
+        "{}/repos/{}/{}/commits/{}",
+        GITHUB_API_BASE, owner, repo, git_ref
+    );
+    let mut request = client
+        .get(&url)
+        .header("Accept", "application/vnd.github.sha");
+    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+        if !token.trim().is_empty() {
+            request = request.bearer_auth(token.trim());
+        }
+    }
+
+    let resp = request
+        .send()
+        .map_err(|e| CliError::ConfigValidation(format!("Failed to reach GitHub: {e}")))?;
+    if !resp.status().is_success() {
+        return Err(CliError::ConfigValidation(format!(
+            "Could not resolve ref '{}' in {}/{} (HTTP {})",
+            git_ref,
+            owner,
+            repo,
+            resp.status()
+        )));
+    }
+
+    let sha = resp
+        .text()
+        .map_err(|e| CliError::ConfigValidation(format!("Invalid GitHub response: {e}")))?
+        .trim()
+        .to_string();
+    if !is_commit_sha(&sha) {
+        return Err(CliError::ConfigValidation(format!(
+            "GitHub returned an unexpected commit id for '{}': {}",
+            git_ref, sha
+        )));
+    }
+    Ok(sha)
+}
+
+/// GET `url` as text. `Ok(None)` on 404 so callers can probe candidates.
+fn get_text(client: &reqwest::blocking::Client, url: &str) -> Result<Option<String>, CliError> {
+    let resp = client
+        .get(url)
+        .send()
+        .map_err(|e| CliError::ConfigValidation(format!("Failed to fetch {url}: {e}")))?;
+
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(CliError::ConfigValidation(format!(
+            "Failed to fetch {url}: HTTP {}",
+            resp.status()
+        )));
+    }
+
+    let body = resp
+        .text()
+        .map_err(|e| CliError::ConfigValidation(format!("Failed to read {url}: {e}")))?;
+    if body.len() > MAX_COMPOSE_BYTES {
+        return Err(CliError::ConfigValidation(format!(
+            "{url} is larger than {} bytes; refusing to import",
+            MAX_COMPOSE_BYTES
+        )));
+    }
+    Ok(Some(body))
+}
+
+pub fn is_commit_sha(value: &str) -> bool {
+    value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Tests
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -297,6 +527,31 @@ mod tests {
         assert!(is_infra_image("Nginx:Alpine"));
     }
 
+    // ── Remote fetch helpers ─────────────────────────
+
+    #[test]
+    fn test_is_commit_sha() {
+        assert!(is_commit_sha("0123456789abcdef0123456789abcdef01234567"));
+        assert!(!is_commit_sha("main"));
+        assert!(!is_commit_sha("0123456"));
+    }
+
+    #[test]
+    fn test_fetch_compose_url_rejects_plain_http() {
+        let err = fetch_compose_url("http://example.com/compose.yml", "00ff").unwrap_err();
+        assert!(format!("{}", err).contains("only https://"));
+    }
+
+    #[test]
+    fn test_pinned_sha256_must_match() {
+        let digest = sha256_hex(b"services: {}\n");
+        let url = "https://example.com/compose.yml";
+        assert!(check_pinned_sha256(url, &digest, &digest).is_ok());
+        assert!(check_pinned_sha256(url, &digest, &digest.to_uppercase()).is_ok());
+        let err = check_pinned_sha256(url, &digest, "00ff").unwrap_err();
+        assert!(format!("{}", err).contains("expected 00ff"));
+    }
+
     // ── Default healthcheck tests ────────────────────
 
     #[test]
@@ -0,0 +1,656 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::error::CliError;
+use crate::cli::generator::compose::ComposeDefinition;
+use crate::cli::install_runner::CommandExecutor;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Image builds — `stacker build`
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//
+// Every compose service with a build context (the generated `app`, or each
+// `build:` service of a user-supplied compose file) is built with
+// `docker buildx`, tagged `<version>` and `sha-<git sha>`, and pushed. The
+// pushed digests land in `.stacker/build.json`; remote deploys rewrite those
+// services to `repository@digest` so the server runs exactly what was built.
+
+/// Build record filename inside `.stacker/`.
+pub const BUILD_RECORD_FILE: &str = "build.json";
+
+/// One image to build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildTarget {
+    /// Compose service name.
+    pub service: String,
+    pub context: PathBuf,
+    pub dockerfile: Option<PathBuf>,
+    pub args: BTreeMap<String, String>,
+    /// Repository without tag, e.g. `ghcr.io/acme/shop`.
+    pub repository: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    pub platforms: Vec<String>,
+    pub push: bool,
+    pub tags: Vec<String>,
+}
+
+/// Persisted result of the last `stacker build`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BuildRecord {
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    pub platforms: Vec<String>,
+    pub pushed: bool,
+    pub built_at: String,
+    /// Compose service name → built image.
+    pub images: BTreeMap<String, BuiltImage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BuiltImage {
+    pub repository: String,
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+}
+
+impl BuiltImage {
+    /// `repository@digest` when pushed, otherwise the first tag.
+    pub fn reference(&self) -> String {
+        match &self.digest {
+            Some(digest) => format!("{}@{}", self.repository, digest),
+            None => format!(
+                "{}:{}",
+                self.repository,
+                self.tags.first().map(String::as_str).unwrap_or("latest")
+            ),
+        }
+    }
+}
+
+impl BuildRecord {
+    pub fn new(version: String, git_sha: Option<String>, options: &BuildOptions) -> Self {
+        Self {
+            version,
+            git_sha,
+            platforms: options.platforms.clone(),
+            pushed: options.push,
+            built_at: Utc::now().to_rfc3339(),
+            images: BTreeMap::new(),
+        }
+    }
+
+    pub fn path(project_dir: &Path) -> PathBuf {
+        project_dir.join(".stacker").join(BUILD_RECORD_FILE)
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<PathBuf, CliError> {
+        let path = Self::path(project_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| {
+            CliError::ConfigValidation(format!("Failed to serialize build record: {e}"))
+        })?;
+        std::fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    pub fn load(project_dir: &Path) -> Result<Option<Self>, CliError> {
+        let path = Self::path(project_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content).map(Some).map_err(|e| {
+            CliError::ConfigValidation(format!("Invalid build record {}: {e}", path.display()))
+        })
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Planning
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Base repository: `--repository`, then `deploy.registry.repository`, then
+/// `<registry server>/<username>/<project name>`.
+pub fn resolve_repository(
+    config: &StackerConfig,
+    explicit: Option<&str>,
+) -> Result<String, CliError> {
+    if let Some(repo) = explicit.filter(|r| !r.trim().is_empty()) {
+        return Ok(repo.trim().trim_end_matches('/').to_string());
+    }
+    let registry = config.deploy.registry.as_ref();
+    if let Some(repo) = registry.and_then(|r| r.repository.as_deref()) {
+        return Ok(repo.trim().trim_end_matches('/').to_string());
+    }
+    let username = std::env::var("STACKER_DOCKER_USERNAME")
+        .ok()
+        .filter(|u| !u.is_empty())
+        .or_else(|| registry.and_then(|r| r.username.clone()));
+    match username {
+        Some(user) => {
+            let name = repository_segment(&config.name);
+            match registry.and_then(|r| r.server.as_deref()) {
+                Some(server) if !is_docker_hub(server) => Ok(format!(
+                    "{}/{}/{}",
+                    server
+                        .trim_start_matches("https://")
+                        .trim_end_matches('/'),
+                    user,
+                    name
+                )),
+                _ => Ok(format!("{}/{}", user, name)),
+            }
+        }
+        None => Err(CliError::ConfigValidation(
+            "No image repository configured. Pass --repository or set deploy.registry.repository (e.g. ghcr.io/acme/shop)."
+                .to_string(),
+        )),
+    }
+}
+
+/// Build targets from the compose file the deploy would use: a user-supplied
+/// compose (`deploy.compose_file`) contributes every service with `build:`,
+/// otherwise the generated definition contributes the `app` service.
+pub fn plan_build_targets(
+    project_dir: &Path,
+    config: &StackerConfig,
+    repository: &str,
+    generated_dockerfile: &Path,
+) -> Result<Vec<BuildTarget>, CliError> {
+    let mut targets = Vec::new();
+
+    if let Some(compose_file) = &config.deploy.compose_file {
+        let compose_path = project_dir.join(compose_file);
+        let base = compose_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| project_dir.to_path_buf());
+        let content = std::fs::read_to_string(&compose_path).map_err(|_| {
+            CliError::ConfigValidation(format!(
+                "Compose file not found: {}",
+                compose_path.display()
+            ))
+        })?;
+        let doc: serde_yaml::Value = serde_yaml::from_str(&content)?;
+        let services = doc
+            .get("services")
+            .and_then(|s| s.as_mapping())
+            .cloned()
+            .unwrap_or_default();
+        let buildable: Vec<_> = services
+            .iter()
+            .filter_map(|(name, svc)| Some((name.as_str()?.to_string(), svc.get("build")?.clone())))
+            .collect();
+        let single = buildable.len() == 1;
+        for (name, build) in buildable {
+            let (context, dockerfile, args) = parse_compose_build(&build);
+            targets.push(BuildTarget {
+                repository: service_repository(repository, &name, single),
+                service: name,
+                context: base.join(context),
+                dockerfile: dockerfile.map(|df| base.join(df)),
+                args,
+            });
+        }
+        return Ok(targets);
+    }
+
+    let compose = ComposeDefinition::try_from(config)?;
+    for svc in compose.services.iter().filter(|svc| svc.image.is_none()) {
+        let Some(context) = &svc.build_context else {
+            continue;
+        };
+        let dockerfile = match &svc.dockerfile {
+            Some(df) => Some(project_dir.join(df)),
+            None => Some(generated_dockerfile.to_path_buf()),
+        };
+        let args = config
+            .app
+            .build
+            .as_ref()
+            .map(|b| b.args.clone().into_iter().collect())
+            .unwrap_or_default();
+        targets.push(BuildTarget {
+            repository: service_repository(repository, &svc.name, true),
+            service: svc.name.clone(),
+            context: project_dir.join(context),
+            dockerfile,
+            args,
+        });
+    }
+    Ok(targets)
+}
+
+/// `<version>` and `sha-<short sha>` (plus `-dirty` for uncommitted trees).
+pub fn image_tags(version: &str, git_sha: Option<&str>, dirty: bool) -> Vec<String> {
+    let mut tags = vec![sanitize_tag(version)];
+    if let Some(sha) = git_sha {
+        let short: String = sha.chars().take(12).collect();
+        tags.push(if dirty {
+            format!("sha-{short}-dirty")
+        } else {
+            format!("sha-{short}")
+        });
+    }
+    tags.dedup();
+    tags
+}
+
+/// `docker buildx build` argument list for one target.
+pub fn buildx_args(
+    target: &BuildTarget,
+    options: &BuildOptions,
+    metadata_file: &Path,
+) -> Vec<String> {
+    let mut args = vec!["buildx".to_string(), "build".to_string()];
+    if !options.platforms.is_empty() {
+        args.push("--platform".to_string());
+        args.push(options.platforms.join(","));
+    }
+    for tag in &options.tags {
+        args.push("--tag".to_string());
+        args.push(format!("{}:{}", target.repository, tag));
+    }
+    if let Some(dockerfile) = &target.dockerfile {
+        args.push("--file".to_string());
+        args.push(dockerfile.to_string_lossy().to_string());
+    }
+    for (key, value) in &target.args {
+        args.push("--build-arg".to_string());
+        args.push(format!("{key}={value}"));
+    }
+    args.push("--label".to_string());
+    args.push(format!("io.stacker.service={}", target.service));
+    args.push(if options.push { "--push" } else { "--load" }.to_string());
+    args.push("--metadata-file".to_string());
+    args.push(metadata_file.to_string_lossy().to_string());
+    args.push(target.context.to_string_lossy().to_string());
+    args
+}
+
+/// `--load` only works for a single platform; multi-arch builds must push.
+pub fn validate_build_options(options: &BuildOptions) -> Result<(), CliError> {
+    if !options.push && options.platforms.len() > 1 {
+        return Err(CliError::ConfigValidation(
+            "Multi-platform builds cannot be loaded into the local image store; drop --no-push or build one platform".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Pull `containerimage.digest` out of a buildx `--metadata-file`.
+pub fn digest_from_metadata(content: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    value
+        .get("containerimage.digest")
+        .and_then(|d| d.as_str())
+        .filter(|d| d.starts_with("sha256:"))
+        .map(ToOwned::to_owned)
+}
+
+/// Run buildx for each target and collect the results into `record`.
+pub fn run_builds(
+    targets: &[BuildTarget],
+    options: &BuildOptions,
+    work_dir: &Path,
+    executor: &dyn CommandExecutor,
+    record: &mut BuildRecord,
+) -> Result<(), CliError> {
+    validate_build_options(options)?;
+    std::fs::create_dir_all(work_dir)?;
+
+    for target in targets {
+        let metadata_file = work_dir.join(format!("buildx-{}.json", target.service));
+        let _ = std::fs::remove_file(&metadata_file);
+        let args = buildx_args(target, options, &metadata_file);
+        let args_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = executor.execute("docker", &args_refs)?;
+        if !output.success() {
+            return Err(CliError::CommandFailed {
+                command: format!("docker buildx build ({})", target.service),
+                exit_code: output.exit_code,
+            });
+        }
+
+        let digest = std::fs::read_to_string(&metadata_file)
+            .ok()
+            .and_then(|content| digest_from_metadata(&content));
+        if options.push && digest.is_none() {
+            return Err(CliError::ConfigValidation(format!(
+                "buildx did not report a pushed digest for '{}'",
+                target.service
+            )));
+        }
+        record.images.insert(
+            target.service.clone(),
+            BuiltImage {
+                repository: target.repository.clone(),
+                tags: options.tags.clone(),
+                digest: if options.push { digest } else { None },
+            },
+        );
+    }
+    Ok(())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Deploy integration — pin compose services to built digests
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Replace `build:` with `image: repository@digest` for every service in
+/// `record`. Returns the pinned `(service, reference)` pairs; services that
+/// were not pushed are left alone.
+pub fn pin_compose_images(
+    compose_content: &str,
+    record: &BuildRecord,
+) -> Result<(String, Vec<(String, String)>), CliError> {
+    let mut doc: serde_yaml::Value = serde_yaml::from_str(compose_content)?;
+    let mut pinned = Vec::new();
+
+    if let Some(services) = doc.get_mut("services").and_then(|s| s.as_mapping_mut()) {
+        for (name, image) in &record.images {
+            if image.digest.is_none() {
+                continue;
+            }
+            let Some(svc) = services
+                .get_mut(serde_yaml::Value::String(name.clone()))
+                .and_then(|s| s.as_mapping_mut())
+            else {
+                continue;
+            };
+            let reference = image.reference();
+            svc.remove(serde_yaml::Value::String("build".to_string()));
+            svc.insert(
+                serde_yaml::Value::String("image".to_string()),
+                serde_yaml::Value::String(reference.clone()),
+            );
+            pinned.push((name.clone(), reference));
+        }
+    }
+
+    Ok((serde_yaml::to_string(&doc)?, pinned))
+}
+
+/// Current commit and whether the work tree has uncommitted changes.
+pub fn git_revision(project_dir: &Path, executor: &dyn CommandExecutor) -> (Option<String>, bool) {
+    let dir = project_dir.to_string_lossy().to_string();
+    let sha = executor
+        .execute("git", &["-C", &dir, "rev-parse", "HEAD"])
+        .ok()
+        .filter(|out| out.success())
+        .map(|out| out.stdout.trim().to_string())
+        .filter(|sha| !sha.is_empty());
+    let dirty = sha.is_some()
+        && executor
+            .execute("git", &["-C", &dir, "status", "--porcelain"])
+            .map(|out| out.success() && !out.stdout.trim().is_empty())
+            .unwrap_or(false);
+    (sha, dirty)
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Helpers
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// The single (or `app`) service uses the base repository; additional
+/// services get `<repository>-<service>`.
+fn service_repository(repository: &str, service: &str, single: bool) -> String {
+    if single || service == "app" {
+        repository.to_string()
+    } else {
+        format!("{}-{}", repository, repository_segment(service))
+    }
+}
+
+fn parse_compose_build(
+    build: &serde_yaml::Value,
+) -> (String, Option<String>, BTreeMap<String, String>) {
+    if let Some(context) = build.as_str() {
+        return (context.to_string(), None, BTreeMap::new());
+    }
+    let context = build
+        .get("context")
+        .and_then(|c| c.as_str())
+        .unwrap_or(".")
+        .to_string();
+    let dockerfile = build
+        .get("dockerfile")
+        .and_then(|d| d.as_str())
+        .map(|df| Path::new(&context).join(df).to_string_lossy().to_string());
+    let mut args = BTreeMap::new();
+    match build.get("args") {
+        Some(serde_yaml::Value::Mapping(map)) => {
+            for (key, value) in map {
+                if let Some(key) = key.as_str() {
+                    let value = match value {
+                        serde_yaml::Value::String(s) => s.clone(),
+                        serde_yaml::Value::Null => continue,
+                        other => serde_yaml::to_string(other)
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string(),
+                    };
+                    args.insert(key.to_string(), value);
+                }
+            }
+        }
+        Some(serde_yaml::Value::Sequence(items)) => {
+            for item in items.iter().filter_map(|i| i.as_str()) {
+                if let Some((key, value)) = item.split_once('=') {
+                    args.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+    (context, dockerfile, args)
+}
+
+fn repository_segment(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_string()
+}
+
+fn sanitize_tag(value: &str) -> String {
+    let tag: String = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(128)
+        .collect();
+    if tag.is_empty() {
+        "latest".to_string()
+    } else {
+        tag
+    }
+}
+
+fn is_docker_hub(server: &str) -> bool {
+    let lower = server.to_lowercase();
+    lower.contains("docker.io") || lower.contains("index.docker.io")
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Tests
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::install_runner::CommandOutput;
+    use std::sync::Mutex;
+
+    struct MockExecutor {
+        calls: Mutex<Vec<(String, Vec<String>)>>,
+        digest: &'static str,
+    }
+
+    impl CommandExecutor for MockExecutor {
+        fn execute(&self, program: &str, args: &[&str]) -> Result<CommandOutput, CliError> {
+            self.calls.lock().unwrap().push((
+                program.to_string(),
+                args.iter().map(|s| s.to_string()).collect(),
+            ));
+            if let Some(pos) = args.iter().position(|a| *a == "--metadata-file") {
+                std::fs::write(
+                    args[pos + 1],
+                    format!(r#"{{"containerimage.digest":"{}"}}"#, self.digest),
+                )
+                .unwrap();
+            }
+            Ok(CommandOutput {
+                exit_code: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+            })
+        }
+    }
+
+    fn options(push: bool, platforms: &[&str]) -> BuildOptions {
+        BuildOptions {
+            platforms: platforms.iter().map(|p| p.to_string()).collect(),
+            push,
+            tags: vec!["1.2.0".to_string(), "sha-abc123".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_image_tags_include_version_and_sha() {
+        assert_eq!(
+            image_tags("1.2.0", Some("abcdef0123456789"), false),
+            vec!["1.2.0", "sha-abcdef012345"]
+        );
+        assert_eq!(
+            image_tags("1.2.0", Some("abcdef0123456789"), true)[1],
+            "sha-abcdef012345-dirty"
+        );
+        assert_eq!(image_tags("v1/beta", None, false), vec!["v1-beta"]);
+    }
+
+    #[test]
+    fn test_buildx_args_for_multi_arch_push() {
+        let target = BuildTarget {
+            service: "app".to_string(),
+            context: PathBuf::from("/p"),
+            dockerfile: Some(PathBuf::from("/p/.stacker/Dockerfile")),
+            args: BTreeMap::from([("NODE_ENV".to_string(), "production".to_string())]),
+            repository: "ghcr.io/acme/shop".to_string(),
+        };
+        let args = buildx_args(
+            &target,
+            &options(true, &["linux/amd64", "linux/arm64"]),
+            Path::new("/tmp/meta.json"),
+        )
+        .join(" ");
+        assert!(args.starts_with("buildx build --platform linux/amd64,linux/arm64"));
+        assert!(args.contains("--tag ghcr.io/acme/shop:1.2.0 --tag ghcr.io/acme/shop:sha-abc123"));
+        assert!(args.contains("--build-arg NODE_ENV=production"));
+        assert!(args.contains("--push"));
+        assert!(args.ends_with("--metadata-file /tmp/meta.json /p"));
+    }
+
+    #[test]
+    fn test_multi_arch_without_push_is_rejected() {
+        assert!(validate_build_options(&options(false, &["linux/amd64", "linux/arm64"])).is_err());
+        assert!(validate_build_options(&options(false, &["linux/amd64"])).is_ok());
+    }
+
+    #[test]
+    fn test_plan_targets_from_user_compose_build_services() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("compose.yml"),
+            "services:\n  api:\n    build: ./api\n  worker:\n    build:\n      context: ./worker\n      dockerfile: Dockerfile.prod\n      args:\n        MODE: queue\n  redis:\n    image: redis:7\n",
+        )
+        .unwrap();
+        let config = StackerConfig::from_str(
+            "name: shop\napp:\n  type: node\ndeploy:\n  compose_file: compose.yml\n",
+        )
+        .unwrap();
+
+        let targets =
+            plan_build_targets(dir.path(), &config, "ghcr.io/acme/shop", Path::new("/x")).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].service, "api");
+        assert_eq!(targets[0].repository, "ghcr.io/acme/shop-api");
+        assert_eq!(targets[1].repository, "ghcr.io/acme/shop-worker");
+        assert_eq!(
+            targets[1].dockerfile.as_deref(),
+            Some(dir.path().join("./worker/Dockerfile.prod").as_path())
+        );
+        assert_eq!(targets[1].args["MODE"], "queue");
+    }
+
+    #[test]
+    fn test_run_builds_records_digests_and_pins_compose() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let executor = MockExecutor {
+            calls: Mutex::new(Vec::new()),
+            digest: "sha256:1111",
+        };
+        let target = BuildTarget {
+            service: "app".to_string(),
+            context: dir.path().to_path_buf(),
+            dockerfile: None,
+            args: BTreeMap::new(),
+            repository: "ghcr.io/acme/shop".to_string(),
+        };
+        let opts = options(true, &["linux/amd64"]);
+        let mut record = BuildRecord::new("1.2.0".to_string(), Some("abc123".to_string()), &opts);
+        run_builds(&[target], &opts, dir.path(), &executor, &mut record).unwrap();
+
+        assert_eq!(
+            record.images["app"].reference(),
+            "ghcr.io/acme/shop@sha256:1111"
+        );
+        record.save(dir.path()).unwrap();
+        assert_eq!(BuildRecord::load(dir.path()).unwrap(), Some(record.clone()));
+
+        let (pinned_yaml, pinned) = pin_compose_images(
+            "services:\n  app:\n    build:\n      context: .\n    ports: ['3000:3000']\n  db:\n    image: postgres:16\n",
+            &record,
+        )
+        .unwrap();
+        assert_eq!(pinned.len(), 1);
+        assert!(pinned_yaml.contains("image: ghcr.io/acme/shop@sha256:1111"));
+        assert!(!pinned_yaml.contains("build:"));
+        assert!(pinned_yaml.contains("image: postgres:16"));
+    }
+
+    #[test]
+    fn test_digest_from_metadata() {
+        assert_eq!(
+            digest_from_metadata(r#"{"containerimage.digest":"sha256:abc","image.name":"x"}"#),
+            Some("sha256:abc".to_string())
+        );
+        assert_eq!(digest_from_metadata("{}"), None);
+    }
+}
@@ -5,6 +5,7 @@ use crate::cli::compose_targets;
 use crate::cli::config_parser::{CloudOrchestrator, DeployTarget, StackerConfig};
 use crate::cli::credentials::{CredentialsManager, StoredCredentials};
 use crate::cli::error::CliError;
+use crate::cli::proxy_manager::RuntimeKind;
 use crate::cli::stacker_client::{self, StackerClient};
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -467,26 +468,22 @@ fn extract_port_from_docker_ps_entry(spec: &str) -> Option<String> {
         .filter(|p| !p.is_empty())
 }
 
-/// Ask Docker for the host ports currently bound by THIS compose project's containers.
+/// Ask the container runtime for the host ports currently bound by THIS
+/// compose project's containers.
 ///
-/// Uses `docker compose -f <path> ps --format "{{.Ports}}"`.
-/// Returns an empty set if Docker is unavailable or the project has no running containers.
+/// Uses `<compose> -f <path> ps --format "{{.Ports}}"` via [`resolve_compose_cmd`].
+/// Returns an empty set if the runtime is unavailable or the project has no running containers.
 fn get_own_compose_running_ports(
     compose_path: &Path,
     executor: &dyn CommandExecutor,
 ) -> std::collections::HashSet<String> {
+    let Ok((cmd, base_args)) = resolve_compose_cmd(executor) else {
+        return Default::default();
+    };
     let compose_str = compose_path.to_string_lossy();
-    let out = match executor.execute(
-        "docker",
-        &[
-            "compose",
-            "-f",
-            &compose_str,
-            "ps",
-            "--format",
-            "{{.Ports}}",
-        ],
-    ) {
+    let mut args: Vec<&str> = base_args;
+    args.extend(["-f", compose_str.as_ref(), "ps", "--format", "{{.Ports}}"]);
+    let out = match executor.execute(cmd, &args) {
         Ok(o) if o.success() => o,
         _ => return Default::default(),
     };
@@ -679,16 +676,30 @@ fn check_local_host_port_conflicts(
 
 /// Detect which compose invocation is available on this host.
 ///
-/// Returns `("docker", vec!["compose"])` when the Docker Compose plugin is
-/// installed (`docker compose version` exits 0), or `("docker-compose", vec![])`
-/// when only the standalone tool is available.
-fn resolve_compose_cmd(executor: &dyn CommandExecutor) -> (&'static str, Vec<&'static str>) {
-    if let Ok(out) = executor.execute("docker", &["compose", "version"]) {
+/// Uses the runtime from [`RuntimeKind::resolve`] (`--container-runtime`,
+/// then auto-detection). Returns `("<runtime>", vec!["compose"])` when the
+/// compose subcommand works (`<runtime> compose version` exits 0), otherwise
+/// the standalone tool (`docker-compose` / `podman-compose`). Fails when
+/// `STACKER_CONTAINER_RUNTIME` names an unknown runtime.
+pub fn resolve_compose_cmd(
+    executor: &dyn CommandExecutor,
+) -> Result<(&'static str, Vec<&'static str>), CliError> {
+    Ok(resolve_compose_cmd_for(RuntimeKind::resolve()?, executor))
+}
+
+fn resolve_compose_cmd_for(
+    kind: RuntimeKind,
+    executor: &dyn CommandExecutor,
+) -> (&'static str, Vec<&'static str>) {
+    if let Ok(out) = executor.execute(kind.binary(), &["compose", "version"]) {
         if out.success() {
-            return ("docker", vec!["compose"]);
+            return (kind.binary(), vec!["compose"]);
         }
     }
-    ("docker-compose", vec![])
+    match kind.standalone_compose() {
+        Some(tool) => (tool, vec![]),
+        None => (kind.binary(), vec!["compose"]),
+    }
 }
 
 pub struct LocalDeploy;
@@ -735,7 +746,7 @@ impl DeployStrategy for LocalDeploy {
             });
         }
 
-        let (cmd, base_args) = resolve_compose_cmd(executor);
+        let (cmd, base_args) = resolve_compose_cmd(executor)?;
         let mut args: Vec<String> = base_args.iter().map(|s| s.to_string()).collect();
 
         if let Some(ref env_file) = config.env_file {
@@ -788,7 +799,7 @@ impl DeployStrategy for LocalDeploy {
     ) -> Result<(), CliError> {
         let compose_path = context.compose_path.to_string_lossy().to_string();
 
-        let (cmd, base_args) = resolve_compose_cmd(executor);
+        let (cmd, base_args) = resolve_compose_cmd(executor)?;
         let mut args: Vec<String> = base_args.iter().map(|s| s.to_string()).collect();
 
         if let Some(ref env_file) = config.env_file {
@@ -1793,6 +1804,7 @@ async fn cleanup_stale_managed_proxy_container(
             delete_config: false,
             remove_volumes: false,
             remove_image: false,
+            compose_project: None,
         };
         let request =
             stacker_client::AgentEnqueueRequest::new(&deployment.deployment_hash, "remove_app")
@@ -3338,6 +3350,30 @@ mod tests {
         assert!(args.contains(&"--build".to_string()));
     }
 
+    #[test]
+    fn test_resolve_compose_cmd_per_runtime() {
+        let ok = MockExecutor::success();
+        assert_eq!(
+            resolve_compose_cmd_for(RuntimeKind::Podman, &ok),
+            ("podman", vec!["compose"])
+        );
+        assert_eq!(ok.last_call().0, "podman");
+
+        let missing = MockExecutor::failure("unknown command");
+        assert_eq!(
+            resolve_compose_cmd_for(RuntimeKind::Docker, &missing),
+            ("docker-compose", vec![])
+        );
+        assert_eq!(
+            resolve_compose_cmd_for(RuntimeKind::Podman, &missing),
+            ("podman-compose", vec![])
+        );
+        assert_eq!(
+            resolve_compose_cmd_for(RuntimeKind::Nerdctl, &missing),
+            ("nerdctl", vec!["compose"])
+        );
+    }
+
     #[test]
     fn test_local_deploy_failure() {
         let config = ConfigBuilder::new().name("local-app").build().unwrap();
@@ -3519,6 +3555,7 @@ mod tests {
                 username: Some("syncopia-user".to_string()),
                 password: Some("secret".to_string()),
                 server: None,
+                repository: None,
             })
             .build()
             .unwrap();
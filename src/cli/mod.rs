@@ -1,14 +1,17 @@
+pub mod acme;
 pub mod ai_client;
 pub mod ai_field_matcher;
 pub mod ai_pipe_suggest;
 pub mod ai_scanner;
 pub mod ai_scenarios;
+pub mod auto_rollback;
 pub mod ci_export;
 pub mod cloud_env;
 pub mod compose_service_sync;
 pub mod compose_targets;
 pub mod config_bundle;
 pub mod config_check;
+pub mod config_compose;
 pub mod config_contract;
 pub mod config_diff;
 pub mod config_inventory;
@@ -16,6 +19,7 @@ pub mod config_parser;
 pub mod config_promote;
 pub mod credentials;
 pub mod debug;
+pub mod deploy_lease;
 pub mod deployment_lock;
 pub mod detector;
 pub mod error;
@@ -23,14 +27,20 @@ pub mod field_matcher;
 pub mod fmt;
 pub mod generator;
 pub mod github_fetcher;
+pub mod image_build;
 pub mod install_runner;
 pub mod local_compose;
 pub mod local_pipe_store;
 pub mod ml_field_matcher;
+pub mod offline_bundle;
+pub mod placement;
+pub mod preview;
 pub mod progress;
 pub mod proxy_manager;
+pub mod release_record;
 pub mod runtime;
 pub mod service_catalog;
 pub mod service_import;
 pub mod stacker_client;
+pub mod terminal;
 pub mod user_config;
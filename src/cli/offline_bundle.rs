@@ -0,0 +1,804 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::cli::config_bundle::{build_config_bundle, is_secret_like_path};
+use crate::cli::error::CliError;
+use crate::cli::install_runner::{resolve_compose_cmd, CommandExecutor};
+use crate::cli::proxy_manager::RuntimeKind;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Offline bundles — `stacker bundle export|install`
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+//
+// An offline bundle is a single `.tar.gz` for servers without registry or
+// internet access. Inside one top-level `<project>-bundle/` directory it
+// carries the rendered compose (bind-mount paths rewritten by the config
+// bundle), env templates with every value blanked, the stack's images as a
+// `docker save` archive, the generated proxy assets the compose mounts,
+// an `install.sh`, and `SHA256SUMS` over all of it. Secrets never leave the
+// workstation: secret-like files ship as templates or are listed as
+// required files the operator provides on the target.
+
+pub const BUNDLE_MANIFEST: &str = "manifest.json";
+pub const BUNDLE_CHECKSUMS: &str = "SHA256SUMS";
+pub const BUNDLE_IMAGES: &str = "images.tar";
+pub const BUNDLE_COMPOSE: &str = "docker-compose.yml";
+pub const BUNDLE_INSTALL_SCRIPT: &str = "install.sh";
+const TEMPLATE_SUFFIX: &str = ".template";
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// One image in `images.tar`, pinned by its content digest.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BundleImage {
+    /// Compose services running the image.
+    pub services: Vec<String>,
+    /// Tag reference the compose uses after `docker load`.
+    pub reference: String,
+    /// Image ID (`sha256:…` of the image config) checked after loading.
+    pub id: String,
+    /// Registry digest the image was pulled by, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_digest: Option<String>,
+}
+
+/// `manifest.json` at the bundle root.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OfflineBundleManifest {
+    pub version: u32,
+    /// Compose project name used on the target.
+    pub project: String,
+    pub created_at: String,
+    pub images: Vec<BundleImage>,
+    /// Files the stack needs but the bundle does not carry (secrets). Each
+    /// has a `<path>.template` next to it when it is an env file.
+    #[serde(default)]
+    pub required_files: Vec<String>,
+}
+
+/// Inputs for [`export_offline_bundle`].
+pub struct BundleExport<'a> {
+    pub project_dir: &'a Path,
+    pub project_name: &'a str,
+    pub compose_path: &'a Path,
+    /// Base directory the compose's relative bind mounts resolve against.
+    pub reference_base: &'a Path,
+    /// Env file whose keys become `.env.template`.
+    pub env_file: Option<&'a Path>,
+    /// Generated proxy asset directories the compose mounts, relative to
+    /// the compose file (`nginx`, `caddy`).
+    pub proxy_assets: Vec<PathBuf>,
+    pub output: &'a Path,
+}
+
+/// Render, stage, checksum and pack an offline bundle.
+pub fn export_offline_bundle(
+    export: &BundleExport<'_>,
+    executor: &dyn CommandExecutor,
+) -> Result<OfflineBundleManifest, CliError> {
+    let project = compose_project_name(export.project_name);
+    let staging = tempfile::tempdir()?;
+    let root_name = format!("{project}-bundle");
+    let root = staging.path().join(&root_name);
+    std::fs::create_dir_all(&root)?;
+
+    let artifacts = build_config_bundle(
+        export.project_dir,
+        "offline",
+        export.compose_path,
+        None,
+        export.reference_base,
+    )?;
+    let project_root = export.project_dir.canonicalize()?;
+    let mut required_files = BTreeSet::new();
+
+    for file in &artifacts.manifest.files {
+        let source = project_root.join(&file.source_path);
+        let destination = safe_relative(&file.destination_path)?;
+        stage_file(&source, &root, &destination, &mut required_files)?;
+    }
+
+    let compose_dir = export
+        .compose_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+    for directory in &export.proxy_assets {
+        stage_directory(
+            &compose_dir.join(directory),
+            &root,
+            directory,
+            &mut required_files,
+        )?;
+    }
+
+    let compose_content = std::fs::read_to_string(&artifacts.remote_compose_path)?;
+    let mut compose: serde_yaml::Value = serde_yaml::from_str(&compose_content)?;
+
+    if let Some(env_file) = export.env_file.filter(|path| path.is_file()) {
+        let content = std::fs::read_to_string(env_file)?;
+        std::fs::write(
+            root.join(format!(".env{TEMPLATE_SUFFIX}")),
+            env_template(&content),
+        )?;
+        required_files.insert(".env".to_string());
+    }
+
+    let runtime = RuntimeKind::resolve()?.binary();
+    let images = save_images(&mut compose, &root.join(BUNDLE_IMAGES), runtime, executor)?;
+    std::fs::write(root.join(BUNDLE_COMPOSE), serde_yaml::to_string(&compose)?)?;
+
+    let manifest = OfflineBundleManifest {
+        version: BUNDLE_FORMAT_VERSION,
+        project: project.clone(),
+        created_at: Utc::now().to_rfc3339(),
+        images,
+        required_files: required_files.into_iter().collect(),
+    };
+    std::fs::write(root.join(BUNDLE_INSTALL_SCRIPT), install_script(&manifest))?;
+    std::fs::write(
+        root.join(BUNDLE_MANIFEST),
+        serde_json::to_string_pretty(&manifest).map_err(|err| {
+            bundle_error("bundle export", format!("cannot write manifest: {err}"))
+        })?,
+    )?;
+    write_checksums(&root)?;
+    pack(&root, &root_name, export.output)?;
+    Ok(manifest)
+}
+
+/// Result of [`install_offline_bundle`].
+#[derive(Debug, Clone)]
+pub struct BundleInstall {
+    /// Directory the bundle was installed into.
+    pub root: PathBuf,
+    pub manifest: OfflineBundleManifest,
+    /// Required files still to be filled in; the stack was not started.
+    pub pending: Vec<String>,
+}
+
+/// Unpack a bundle next to `dest_dir`, verify it, move it into place, load
+/// its images and — when every required file is present — start it.
+/// Required files of an earlier install (the operator's secrets) carry over.
+pub fn install_offline_bundle(
+    tarball: &Path,
+    dest_dir: &Path,
+    start: bool,
+    executor: &dyn CommandExecutor,
+) -> Result<BundleInstall, CliError> {
+    std::fs::create_dir_all(dest_dir)?;
+    let staging = tempfile::tempdir_in(dest_dir)?;
+    let unpacked = unpack(tarball, staging.path())?;
+    verify_checksums(&unpacked)?;
+    let manifest: OfflineBundleManifest = serde_json::from_str(&std::fs::read_to_string(
+        unpacked.join(BUNDLE_MANIFEST),
+    )?)
+    .map_err(|err| {
+        bundle_error(
+            "bundle install",
+            format!("invalid {BUNDLE_MANIFEST}: {err}"),
+        )
+    })?;
+    if manifest.version > BUNDLE_FORMAT_VERSION {
+        return Err(bundle_error(
+            "bundle install",
+            format!(
+                "bundle format {} is newer than this stacker supports ({})",
+                manifest.version, BUNDLE_FORMAT_VERSION
+            ),
+        ));
+    }
+
+    let root = dest_dir.join(unpacked.file_name().unwrap_or_default());
+    if root.exists() {
+        for required in &manifest.required_files {
+            let previous = root.join(safe_relative(required)?);
+            if previous.is_file() {
+                let carried = unpacked.join(safe_relative(required)?);
+                if let Some(parent) = carried.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::copy(&previous, carried)?;
+            }
+        }
+        std::fs::remove_dir_all(&root)?;
+    }
+    std::fs::rename(&unpacked, &root)?;
+
+    let runtime = RuntimeKind::resolve()?.binary();
+    let images = root.join(BUNDLE_IMAGES).to_string_lossy().to_string();
+    run(executor, runtime, &["load", "-i", &images])?;
+    for image in &manifest.images {
+        let loaded = image_id(executor, runtime, &image.reference)?;
+        if loaded.as_deref() != Some(image.id.as_str()) {
+            return Err(bundle_error(
+                "bundle install",
+                format!(
+                    "image {} loaded as {} but the manifest pins {}",
+                    image.reference,
+                    loaded.as_deref().unwrap_or("nothing"),
+                    image.id
+                ),
+            ));
+        }
+    }
+
+    let mut pending = Vec::new();
+    for required in &manifest.required_files {
+        let path = root.join(safe_relative(required)?);
+        if path.exists() {
+            continue;
+        }
+        let template = root.join(format!("{required}{TEMPLATE_SUFFIX}"));
+        if template.is_file() {
+            std::fs::copy(&template, &path)?;
+        }
+        pending.push(required.clone());
+    }
+
+    if start && pending.is_empty() {
+        let compose = root.join(BUNDLE_COMPOSE).to_string_lossy().to_string();
+        let (program, prefix) = resolve_compose_cmd(executor)?;
+        let mut args: Vec<&str> = prefix;
+        args.extend([
+            "-p",
+            manifest.project.as_str(),
+            "-f",
+            compose.as_str(),
+            "up",
+            "-d",
+        ]);
+        run(executor, program, &args)?;
+    }
+    Ok(BundleInstall {
+        root,
+        manifest,
+        pending,
+    })
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Export helpers
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Compose project names allow lowercase letters, digits, `-` and `_`.
+pub fn compose_project_name(name: &str) -> String {
+    let slug: String = name
+        .to_ascii_lowercase()
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches('-').to_string();
+    if slug.is_empty() {
+        "stack".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Copy one file into the bundle, or template/skip it when it is secret-like.
+fn stage_file(
+    source: &Path,
+    root: &Path,
+    destination: &Path,
+    required_files: &mut BTreeSet<String>,
+) -> Result<(), CliError> {
+    let relative = destination.to_string_lossy().replace('\\', "/");
+    let target = root.join(destination);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if !is_secret_like_path(&relative) {
+        std::fs::copy(source, &target)?;
+        return Ok(());
+    }
+
+    if is_env_file(&relative) {
+        let content = std::fs::read_to_string(source)?;
+        std::fs::write(
+            root.join(format!("{relative}{TEMPLATE_SUFFIX}")),
+            env_template(&content),
+        )?;
+    }
+    required_files.insert(relative);
+    Ok(())
+}
+
+fn stage_directory(
+    source: &Path,
+    root: &Path,
+    destination: &Path,
+    required_files: &mut BTreeSet<String>,
+) -> Result<(), CliError> {
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        let nested = destination.join(entry.file_name());
+        if path.is_dir() {
+            stage_directory(&path, root, &nested, required_files)?;
+        } else if path.is_file() {
+            stage_file(&path, root, &nested, required_files)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_env_file(path: &str) -> bool {
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path)
+        .to_ascii_lowercase();
+    name == ".env" || name.ends_with(".env") || name.starts_with(".env.")
+}
+
+/// Keep comments and keys of a dotenv file, blank every value.
+pub fn env_template(content: &str) -> String {
+    let mut out = String::new();
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            out.push_str(line);
+        } else if let Some((key, _)) = trimmed.split_once('=') {
+            out.push_str(key.trim_end());
+            out.push('=');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Make sure every service image is local, pin it by ID, `docker save` them
+/// all, and stop the compose from building or pulling on the target.
+fn save_images(
+    compose: &mut serde_yaml::Value,
+    archive: &Path,
+    runtime: &str,
+    executor: &dyn CommandExecutor,
+) -> Result<Vec<BundleImage>, CliError> {
+    let mut images: BTreeMap<String, BundleImage> = BTreeMap::new();
+    let Some(services) = compose.get_mut("services").and_then(|s| s.as_mapping_mut()) else {
+        return Ok(Vec::new());
+    };
+
+    for (name, service) in services.iter_mut() {
+        let name = name.as_str().unwrap_or_default().to_string();
+        let Some(service) = service.as_mapping_mut() else {
+            continue;
+        };
+        let Some(reference) = service
+            .get("image")
+            .and_then(|image| image.as_str())
+            .map(str::to_string)
+        else {
+            return Err(bundle_error(
+                "bundle export",
+                format!(
+                    "service '{name}' has no image; run `stacker build` or set an image so it can be saved"
+                ),
+            ));
+        };
+
+        let mut id = image_id(executor, runtime, &reference)?;
+        if id.is_none() {
+            eprintln!("  Pulling {}", reference);
+            run(executor, runtime, &["pull", &reference])?;
+            id = image_id(executor, runtime, &reference)?;
+        }
+        let id = id.ok_or_else(|| {
+            bundle_error(
+                "bundle export",
+                format!("image {reference} is not available"),
+            )
+        })?;
+
+        // `docker load` does not restore digest references, so a digest-pinned
+        // image travels under a tag derived from its digest.
+        let (tagged, repo_digest) = match reference.split_once('@') {
+            Some((repository, digest)) => {
+                let short = digest.trim_start_matches("sha256:");
+                let tagged = format!(
+                    "{}:stacker-{}",
+                    strip_tag(repository),
+                    &short[..short.len().min(12)]
+                );
+                run(executor, runtime, &["tag", &reference, &tagged])?;
+                (tagged, Some(reference.clone()))
+            }
+            None => (
+                reference.clone(),
+                repo_digest(executor, runtime, &reference),
+            ),
+        };
+
+        service.remove("build");
+        service.insert("image".into(), tagged.clone().into());
+        service.insert("pull_policy".into(), "never".into());
+        images
+            .entry(tagged.clone())
+            .or_insert_with(|| BundleImage {
+                services: Vec::new(),
+                reference: tagged,
+                id,
+                repo_digest,
+            })
+            .services
+            .push(name);
+    }
+
+    if images.is_empty() {
+        return Ok(Vec::new());
+    }
+    let archive = archive.to_string_lossy().to_string();
+    let mut args = vec!["save", "-o", archive.as_str()];
+    args.extend(images.keys().map(String::as_str));
+    eprintln!("  Saving {} image(s)...", images.len());
+    run(executor, runtime, &args)?;
+    Ok(images.into_values().collect())
+}
+
+/// Drop the tag from an image name. Only a `:` after the last `/` starts a
+/// tag; earlier ones are registry ports (`registry.local:5000/app`).
+fn strip_tag(repository: &str) -> &str {
+    let name_start = repository.rfind('/').map_or(0, |slash| slash + 1);
+    match repository[name_start..].find(':') {
+        Some(colon) => &repository[..name_start + colon],
+        None => repository,
+    }
+}
+
+/// Image ID as `sha256:<hex>`; podman prints the bare hex.
+fn image_id(
+    executor: &dyn CommandExecutor,
+    runtime: &str,
+    reference: &str,
+) -> Result<Option<String>, CliError> {
+    let output = executor.execute(
+        runtime,
+        &["image", "inspect", "--format", "{{.Id}}", reference],
+    )?;
+    let id = output.stdout.trim();
+    let id = if id.starts_with("sha256:") {
+        id.to_string()
+    } else {
+        format!("sha256:{id}")
+    };
+    Ok(Some(id).filter(|id| output.success() && id.len() > "sha256:".len()))
+}
+
+fn repo_digest(executor: &dyn CommandExecutor, runtime: &str, reference: &str) -> Option<String> {
+    executor
+        .execute(
+            runtime,
+            &[
+                "image",
+                "inspect",
+                "--format",
+                "{{if .RepoDigests}}{{index .RepoDigests 0}}{{end}}",
+                reference,
+            ],
+        )
+        .ok()
+        .filter(|output| output.success())
+        .map(|output| output.stdout.trim().to_string())
+        .filter(|digest| !digest.is_empty())
+}
+
+fn install_script(manifest: &OfflineBundleManifest) -> String {
+    let mut script = String::from(
+        r#"#!/bin/sh
+# Generated by `stacker bundle export`: verify, load and start this bundle
+# on a host without registry or internet access.
+set -eu
+cd "$(dirname "$0")"
+
+sha256sum -c --quiet SHA256SUMS
+
+# Same runtime choice as stacker: STACKER_CONTAINER_RUNTIME, else the first
+# of docker, podman, nerdctl on PATH.
+runtime=${STACKER_CONTAINER_RUNTIME:-}
+case "$runtime" in
+  "") for candidate in docker podman nerdctl; do
+        if command -v "$candidate" >/dev/null 2>&1; then runtime=$candidate; break; fi
+      done ;;
+  docker|podman|nerdctl) ;;
+  containerd) runtime=nerdctl ;;
+  *) echo "Unknown container runtime '$runtime'. Supported: docker, podman, nerdctl" >&2; exit 1 ;;
+esac
+if [ -z "$runtime" ]; then
+  echo "No container runtime found; install docker, podman or nerdctl" >&2
+  exit 1
+fi
+if "$runtime" compose version >/dev/null 2>&1; then
+  compose="$runtime compose"
+elif [ "$runtime" = docker ] && command -v docker-compose >/dev/null 2>&1; then
+  compose=docker-compose
+elif [ "$runtime" = podman ] && command -v podman-compose >/dev/null 2>&1; then
+  compose=podman-compose
+else
+  echo "No compose tool found for $runtime" >&2
+  exit 1
+fi
+
+"$runtime" load -i images.tar
+
+check_image() {
+  actual=$("$runtime" image inspect --format '{{.Id}}' "$1")
+  case "$actual" in sha256:*) ;; *) actual="sha256:$actual" ;; esac
+  if [ "$actual" != "$2" ]; then
+    echo "image $1 is $actual, expected $2" >&2
+    exit 1
+  fi
+}
+"#,
+    );
+    for image in &manifest.images {
+        script.push_str(&format!(
+            "check_image '{}' '{}'\n",
+            image.reference, image.id
+        ));
+    }
+
+    script.push_str("\npending=0\n");
+    for required in &manifest.required_files {
+        script.push_str(&format!(
+            "if [ ! -e '{required}' ]; then\n  [ -f '{required}{TEMPLATE_SUFFIX}' ] && cp '{required}{TEMPLATE_SUFFIX}' '{required}'\n  echo \"Fill in {required} and re-run $0\" >&2\n  pending=1\nfi\n"
+        ));
+    }
+    script.push_str(&format!(
+        "[ \"$pending\" -eq 0 ] || exit 1\n\n$compose -p '{}' -f {} up -d\n",
+        manifest.project, BUNDLE_COMPOSE
+    ));
+    script
+}
+
+fn write_checksums(root: &Path) -> Result<(), CliError> {
+    let mut lines = String::new();
+    for relative in bundle_files(root)? {
+        let digest = sha256_file(&root.join(&relative))?;
+        lines.push_str(&format!("{digest}  {relative}\n"));
+    }
+    std::fs::write(root.join(BUNDLE_CHECKSUMS), lines)?;
+    Ok(())
+}
+
+/// Every file under `root` except `SHA256SUMS`, as sorted `/` paths.
+fn bundle_files(root: &Path) -> Result<Vec<String>, CliError> {
+    fn walk(dir: &Path, root: &Path, out: &mut Vec<String>) -> Result<(), CliError> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                walk(&path, root, out)?;
+            } else {
+                let relative = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                if relative != BUNDLE_CHECKSUMS {
+                    out.push(relative);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    walk(root, root, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn pack(root: &Path, root_name: &str, output: &Path) -> Result<(), CliError> {
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let encoder = GzEncoder::new(File::create(output)?, Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    tar.mode(tar::HeaderMode::Deterministic);
+    tar.append_dir_all(root_name, root)?;
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Install helpers
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Extract the archive; all entries must share one top-level directory.
+fn unpack(tarball: &Path, dest_dir: &Path) -> Result<PathBuf, CliError> {
+    std::fs::create_dir_all(dest_dir)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(tarball)?));
+    let mut root_name: Option<PathBuf> = None;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let Some(Component::Normal(top)) = path.components().next() else {
+            return Err(bundle_error(
+                "bundle install",
+                format!("unexpected archive entry {}", path.display()),
+            ));
+        };
+        match &root_name {
+            Some(existing) if existing.as_os_str() != top => {
+                return Err(bundle_error(
+                    "bundle install",
+                    "archive has more than one top-level directory",
+                ));
+            }
+            Some(_) => {}
+            None => root_name = Some(PathBuf::from(top)),
+        }
+        // `unpack_in` refuses entries that would escape `dest_dir`.
+        if !entry.unpack_in(dest_dir)? {
+            return Err(bundle_error(
+                "bundle install",
+                format!(
+                    "archive entry {} escapes the target directory",
+                    path.display()
+                ),
+            ));
+        }
+    }
+    root_name
+        .map(|name| dest_dir.join(name))
+        .ok_or_else(|| bundle_error("bundle install", "archive is empty"))
+}
+
+/// Check `SHA256SUMS` against the unpacked files, both ways: every listed
+/// file matches and the archive carries nothing unlisted.
+pub fn verify_checksums(root: &Path) -> Result<(), CliError> {
+    let sums = File::open(root.join(BUNDLE_CHECKSUMS))
+        .map_err(|_| bundle_error("bundle install", format!("{BUNDLE_CHECKSUMS} is missing")))?;
+    let mut listed = BTreeSet::new();
+    for line in BufReader::new(sums).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((expected, relative)) = line.split_once("  ") else {
+            return Err(bundle_error(
+                "bundle install",
+                format!("malformed {BUNDLE_CHECKSUMS} line: {line}"),
+            ));
+        };
+        let path = root.join(safe_relative(relative)?);
+        let actual = sha256_file(&path)
+            .map_err(|_| bundle_error("bundle install", format!("{relative} is missing")))?;
+        if actual != expected {
+            return Err(bundle_error(
+                "bundle install",
+                format!("checksum mismatch for {relative}"),
+            ));
+        }
+        listed.insert(relative.to_string());
+    }
+    if let Some(extra) = bundle_files(root)?
+        .into_iter()
+        .find(|file| !listed.contains(file))
+    {
+        return Err(bundle_error(
+            "bundle install",
+            format!("{extra} is not listed in {BUNDLE_CHECKSUMS}"),
+        ));
+    }
+    Ok(())
+}
+
+fn sha256_file(path: &Path) -> Result<String, CliError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// A bundle path must be relative and stay inside the bundle.
+fn safe_relative(path: &str) -> Result<PathBuf, CliError> {
+    let relative = Path::new(path.trim_start_matches("./"));
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(bundle_error(
+            "bundle",
+            format!("path '{path}' must stay inside the bundle"),
+        ));
+    }
+    Ok(relative.to_path_buf())
+}
+
+fn run(executor: &dyn CommandExecutor, program: &str, args: &[&str]) -> Result<(), CliError> {
+    let output = executor.execute(program, args)?;
+    if output.success() {
+        return Ok(());
+    }
+    Err(CliError::CommandFailed {
+        command: format!("{} {}", program, args.join(" ")),
+        exit_code: output.exit_code,
+    })
+}
+
+fn bundle_error(feature: &str, reason: impl Into<String>) -> CliError {
+    CliError::FeatureFailed {
+        feature: feature.to_string(),
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_bundle_unpacks_and_detects_tampering() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().join("shop-bundle");
+        std::fs::create_dir_all(root.join("nginx/conf.d")).unwrap();
+        std::fs::write(root.join(BUNDLE_COMPOSE), "services: {}\n").unwrap();
+        std::fs::write(root.join("nginx/conf.d/shop.conf"), "server {}\n").unwrap();
+        std::fs::write(
+            root.join(".env.template"),
+            env_template("# database\nexport DB_PASSWORD=hunter2\nAPI_KEY = abc\n"),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join(".env.template")).unwrap(),
+            "# database\nexport DB_PASSWORD=\nAPI_KEY=\n"
+        );
+        write_checksums(&root).unwrap();
+
+        let tarball = workspace.path().join("shop-bundle.tar.gz");
+        pack(&root, "shop-bundle", &tarball).unwrap();
+        let target = workspace.path().join("target");
+        let unpacked = unpack(&tarball, &target).unwrap();
+        assert_eq!(unpacked, target.join("shop-bundle"));
+        verify_checksums(&unpacked).unwrap();
+
+        std::fs::write(unpacked.join("nginx/conf.d/shop.conf"), "server { evil }\n").unwrap();
+        let err = verify_checksums(&unpacked).unwrap_err().to_string();
+        assert!(err.contains("checksum mismatch for nginx/conf.d/shop.conf"));
+
+        std::fs::write(unpacked.join("nginx/conf.d/shop.conf"), "server {}\n").unwrap();
+        std::fs::write(unpacked.join("extra.sh"), "true\n").unwrap();
+        let err = verify_checksums(&unpacked).unwrap_err().to_string();
+        assert!(err.contains("extra.sh is not listed"));
+
+        assert!(safe_relative("../etc/passwd").is_err());
+        assert_eq!(compose_project_name("My Shop!"), "my-shop");
+    }
+
+    #[test]
+    fn strip_tag_keeps_registry_ports() {
+        assert_eq!(
+            strip_tag("registry.local:5000/app"),
+            "registry.local:5000/app"
+        );
+        assert_eq!(
+            strip_tag("registry.local:5000/team/app:1.2"),
+            "registry.local:5000/team/app"
+        );
+        assert_eq!(strip_tag("nginx:1.27"), "nginx");
+        assert_eq!(strip_tag("nginx"), "nginx");
+    }
+}
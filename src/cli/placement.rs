@@ -0,0 +1,407 @@
+//! Multi-host placement: `services[].placement` pins a service to the server
+//! of another `deploy.targets` profile.
+//!
+//! A remote deploy splits the rendered compose per host. Each host keeps its
+//! own services and reaches peers on other hosts through `extra_hosts`
+//! entries pointing at that host's `private_address`, where the peer's ports
+//! are published. The primary host ships through the regular deploy; every
+//! other host gets its slice through `deploy_app` on its own agent.
+
+use std::collections::BTreeSet;
+
+use serde_yaml::{Mapping, Value};
+
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::error::CliError;
+
+/// One server of a multi-host stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacementHost {
+    /// `deploy.targets` profile name.
+    pub target: String,
+    /// Agent deployment of the host; required for every host but the primary.
+    pub deployment_hash: Option<String>,
+    /// Private address peers on other hosts connect to.
+    pub address: Option<String>,
+    /// Services placed on this host. The primary also runs every service
+    /// without a placement, the app included.
+    pub services: Vec<String>,
+}
+
+/// Hosts of a stack, primary (the resolved deploy target) first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacementPlan {
+    pub hosts: Vec<PlacementHost>,
+}
+
+impl PlacementPlan {
+    pub fn primary(&self) -> &PlacementHost {
+        &self.hosts[0]
+    }
+
+    pub fn peers(&self) -> &[PlacementHost] {
+        &self.hosts[1..]
+    }
+
+    pub fn host_of(&self, service: &str) -> &PlacementHost {
+        self.peers()
+            .iter()
+            .find(|host| host.services.iter().any(|name| name == service))
+            .unwrap_or_else(|| self.primary())
+    }
+}
+
+/// Group placed services by host. `None` when every service runs on the
+/// primary target, which keeps single-host deploys untouched.
+pub fn plan_placement(
+    config: &StackerConfig,
+    primary: &str,
+) -> Result<Option<PlacementPlan>, CliError> {
+    let mut hosts = vec![PlacementHost {
+        target: primary.to_string(),
+        deployment_hash: None,
+        address: None,
+        services: Vec::new(),
+    }];
+    for service in &config.services {
+        let target = service.placement.as_deref().unwrap_or(primary);
+        match hosts.iter_mut().find(|host| host.target == target) {
+            Some(host) => host.services.push(service.name.clone()),
+            None => hosts.push(PlacementHost {
+                target: target.to_string(),
+                deployment_hash: None,
+                address: None,
+                services: vec![service.name.clone()],
+            }),
+        }
+    }
+    if hosts.len() == 1 {
+        return Ok(None);
+    }
+
+    for host in &mut hosts {
+        let profile = config.deploy.targets.get(&host.target).ok_or_else(|| {
+            CliError::ConfigValidation(format!(
+                "placement '{}' does not match any entry in deploy.targets",
+                host.target
+            ))
+        })?;
+        host.deployment_hash = profile.deployment_hash.clone();
+        host.address = profile.private_address.clone();
+    }
+    // Placed services are reached across hosts; never publish them on a
+    // public address.
+    if let Some(host) = hosts[1..].iter().find(|host| host.address.is_none()) {
+        return Err(CliError::ConfigValidation(format!(
+            "deploy.targets.{} needs private_address so other hosts can reach {}",
+            host.target,
+            host.services.join(", ")
+        )));
+    }
+    Ok(Some(PlacementPlan { hosts }))
+}
+
+/// Split a compose file into one compose per host, in plan order.
+///
+/// Services referenced across hosts (every placed service, plus primary
+/// services a placed one `depends_on`) publish their ports on their host's
+/// address only, with the host port equal to the container port, so the
+/// service name resolves to the same `name:port` on every host.
+pub fn split_compose(
+    content: &str,
+    plan: &PlacementPlan,
+) -> Result<Vec<(String, String)>, CliError> {
+    let doc: Value = serde_yaml::from_str(content)?;
+    let services = doc
+        .get("services")
+        .and_then(Value::as_mapping)
+        .cloned()
+        .unwrap_or_default();
+    for host in plan.peers() {
+        if let Some(missing) = host
+            .services
+            .iter()
+            .find(|name| !services.contains_key(name.as_str()))
+        {
+            return Err(CliError::ConfigValidation(format!(
+                "service '{}' is placed on {} but missing from the compose file",
+                missing, host.target
+            )));
+        }
+    }
+
+    let host_of = |name: &str| plan.host_of(name).target.clone();
+    let mut exported: BTreeSet<String> = plan
+        .peers()
+        .iter()
+        .flat_map(|host| host.services.iter().cloned())
+        .collect();
+    for (name, service) in &services {
+        let name = name.as_str().unwrap_or_default();
+        for dependency in depends_on(service) {
+            if host_of(&dependency) != host_of(name) {
+                exported.insert(dependency);
+            }
+        }
+    }
+
+    let mut split = Vec::new();
+    for host in &plan.hosts {
+        let mut kept = Mapping::new();
+        for (key, service) in &services {
+            let name = key.as_str().unwrap_or_default();
+            if host_of(name) != host.target {
+                continue;
+            }
+            let mut service = service.clone();
+            if let Some(mapping) = service.as_mapping_mut() {
+                retain_local_dependencies(mapping, |dep| host_of(dep) == host.target);
+                let peers = services
+                    .keys()
+                    .filter_map(Value::as_str)
+                    .filter(|peer| host_of(peer) != host.target)
+                    .filter_map(|peer| {
+                        plan.host_of(peer)
+                            .address
+                            .as_ref()
+                            .map(|address| format!("{peer}:{address}"))
+                    });
+                add_extra_hosts(mapping, peers);
+                if exported.contains(name) {
+                    let address = host.address.as_deref().ok_or_else(|| {
+                        CliError::ConfigValidation(format!(
+                            "deploy.targets.{} needs private_address so other hosts can reach '{}'",
+                            host.target, name
+                        ))
+                    })?;
+                    if !publish_on_address(mapping, address) {
+                        return Err(CliError::ConfigValidation(format!(
+                            "service '{}' is reached from another host but declares no ports",
+                            name
+                        )));
+                    }
+                }
+            }
+            kept.insert(key.clone(), service);
+        }
+
+        let mut host_doc = doc.clone();
+        if let Some(root) = host_doc.as_mapping_mut() {
+            root.insert(Value::from("services"), Value::Mapping(kept));
+        }
+        split.push((host.target.clone(), serde_yaml::to_string(&host_doc)?));
+    }
+    Ok(split)
+}
+
+fn depends_on(service: &Value) -> Vec<String> {
+    match service.get("depends_on") {
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(Value::Mapping(items)) => items
+            .keys()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn retain_local_dependencies(service: &mut Mapping, is_local: impl Fn(&str) -> bool) {
+    match service.get_mut("depends_on") {
+        Some(Value::Sequence(items)) => {
+            items.retain(|item| item.as_str().is_some_and(&is_local));
+        }
+        Some(Value::Mapping(items)) => {
+            *items = std::mem::take(items)
+                .into_iter()
+                .filter(|(key, _)| key.as_str().is_some_and(&is_local))
+                .collect();
+        }
+        _ => {}
+    }
+}
+
+fn add_extra_hosts(service: &mut Mapping, entries: impl Iterator<Item = String>) {
+    let entries: Vec<String> = entries.collect();
+    if entries.is_empty() {
+        return;
+    }
+    let key = Value::from("extra_hosts");
+    if !service.contains_key(&key) {
+        service.insert(key.clone(), Value::Sequence(Vec::new()));
+    }
+    match service.get_mut(&key).expect("extra_hosts inserted above") {
+        Value::Sequence(items) => items.extend(entries.into_iter().map(Value::from)),
+        Value::Mapping(items) => {
+            for entry in entries {
+                if let Some((name, address)) = entry.split_once(':') {
+                    items.insert(Value::from(name), Value::from(address));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Publish the service's ports on `address`. False when it has none.
+fn publish_on_address(service: &mut Mapping, address: &str) -> bool {
+    let Some(Value::Sequence(ports)) = service.get_mut("ports") else {
+        return false;
+    };
+    if ports.is_empty() {
+        return false;
+    }
+    for port in ports.iter_mut() {
+        match port {
+            Value::Mapping(long) => {
+                if let Some(target) = long.get("target").cloned() {
+                    long.insert(Value::from("host_ip"), Value::from(address));
+                    long.insert(Value::from("published"), target);
+                }
+            }
+            short => {
+                let spec = match short {
+                    Value::Number(number) => number.to_string(),
+                    Value::String(spec) => spec.clone(),
+                    _ => continue,
+                };
+                let container = spec.rsplit(':').next().unwrap_or(&spec);
+                let (container_port, protocol) = match container.split_once('/') {
+                    Some((port, protocol)) => (port, format!("/{protocol}")),
+                    None => (container, String::new()),
+                };
+                *short = Value::from(format!(
+                    "{address}:{container_port}:{container_port}{protocol}"
+                ));
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_compose_places_database_on_its_own_host() {
+        let config = StackerConfig::from_str(
+            r#"
+name: shop
+services:
+  - name: postgres
+    image: postgres:16
+    ports: ["5432:5432"]
+    placement: db
+deploy:
+  default_target: web
+  targets:
+    web:
+      deployment_hash: dep-web
+      server:
+        host: 203.0.113.10
+    db:
+      deployment_hash: dep-db
+      private_address: 10.0.0.5
+      server:
+        host: 203.0.113.20
+"#,
+        )
+        .unwrap();
+        let plan = plan_placement(&config, "web").unwrap().unwrap();
+        assert_eq!(plan.host_of("postgres").target, "db");
+        assert_eq!(plan.host_of("app").target, "web");
+        assert_eq!(plan.peers()[0].deployment_hash.as_deref(), Some("dep-db"));
+
+        let compose = r#"
+services:
+  app:
+    image: shop:latest
+    ports: ["80:3000"]
+    depends_on: [postgres]
+  postgres:
+    image: postgres:16
+    ports: ["5432:5432"]
+volumes:
+  pgdata: {}
+"#;
+        let split = split_compose(compose, &plan).unwrap();
+        assert_eq!(split[0].0, "web");
+        let web: Value = serde_yaml::from_str(&split[0].1).unwrap();
+        let db: Value = serde_yaml::from_str(&split[1].1).unwrap();
+
+        assert!(web["services"].get("postgres").is_none());
+        assert_eq!(
+            web["services"]["app"]["depends_on"],
+            Value::Sequence(vec![])
+        );
+        assert_eq!(
+            web["services"]["app"]["extra_hosts"][0],
+            Value::from("postgres:10.0.0.5")
+        );
+        assert_eq!(web["services"]["app"]["ports"][0], Value::from("80:3000"));
+
+        assert!(db["services"].get("app").is_none());
+        assert_eq!(
+            db["services"]["postgres"]["ports"][0],
+            Value::from("10.0.0.5:5432:5432")
+        );
+        assert!(db["volumes"].get("pgdata").is_some());
+    }
+
+    fn placed_config(private_address: Option<&str>) -> StackerConfig {
+        let private_address = private_address
+            .map(|address| format!("      private_address: {address}\n"))
+            .unwrap_or_default();
+        StackerConfig::from_str(&format!(
+            r#"
+name: shop
+services:
+  - name: postgres
+    image: postgres:16
+    placement: db
+deploy:
+  default_target: web
+  targets:
+    web:
+      deployment_hash: dep-web
+      server:
+        host: 203.0.113.10
+    db:
+      deployment_hash: dep-db
+{private_address}      server:
+        host: 203.0.113.20
+"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn placed_hosts_require_a_private_address() {
+        let err = plan_placement(&placed_config(None), "web").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("deploy.targets.db needs private_address"));
+    }
+
+    #[test]
+    fn exported_services_without_ports_are_rejected() {
+        let plan = plan_placement(&placed_config(Some("10.0.0.5")), "web")
+            .unwrap()
+            .unwrap();
+        let compose = r#"
+services:
+  app:
+    image: shop:latest
+    depends_on: [postgres]
+  postgres:
+    image: postgres:16
+"#;
+        let err = split_compose(compose, &plan).unwrap_err();
+        assert!(err.to_string().contains("'postgres'"));
+    }
+}
@@ -0,0 +1,253 @@
+//! Per-branch preview environments for `stacker preview up|down|list`.
+//!
+//! A preview deploys the current branch onto the shared server of a
+//! `deploy.targets` profile under its own compose project, and the managed
+//! proxy serves it at `<branch-slug>.<deploy.preview.base_domain>`. The
+//! Stacker server records each preview and tears it down once its TTL runs
+//! out.
+
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use crate::cli::compose_service_sync::{inject_external_network, upstream_service_name};
+use crate::cli::config_parser::{DeployTarget, PreviewConfig, StackerConfig};
+use crate::cli::error::CliError;
+use crate::cli::install_runner::CommandExecutor;
+use crate::services::preview::{preview_slug, PreviewRequest};
+
+/// Branch variables set by the CI systems `stacker ci export` targets, most
+/// specific first (pull request source branch before the pushed ref).
+const CI_BRANCH_VARS: &[&str] = &[
+    "GITHUB_HEAD_REF",
+    "CI_MERGE_REQUEST_SOURCE_BRANCH_NAME",
+    "BITBUCKET_BRANCH",
+    "CHANGE_BRANCH",
+    "GITHUB_REF_NAME",
+    "CI_COMMIT_REF_NAME",
+    "BRANCH_NAME",
+];
+
+/// Everything needed to deploy, route and register one preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewPlan {
+    /// Deployment hash of the shared server's deployment.
+    pub deployment_hash: String,
+    pub branch: String,
+    pub slug: String,
+    pub app_code: String,
+    pub compose_project: String,
+    pub domain: String,
+    pub service: String,
+    pub forward_host: String,
+    pub forward_port: u16,
+    pub ttl_secs: i64,
+}
+
+impl PreviewPlan {
+    pub fn request(&self) -> PreviewRequest {
+        PreviewRequest {
+            branch: self.branch.clone(),
+            slug: self.slug.clone(),
+            app_code: self.app_code.clone(),
+            compose_project: self.compose_project.clone(),
+            domain: self.domain.clone(),
+            forward_port: self.forward_port,
+            ttl_secs: Some(self.ttl_secs),
+        }
+    }
+}
+
+/// The branch to preview: `--branch`, then CI variables, then git.
+pub fn detect_branch(
+    explicit: Option<&str>,
+    project_dir: &Path,
+    executor: &dyn CommandExecutor,
+) -> Result<String, CliError> {
+    let from_env = || {
+        CI_BRANCH_VARS
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.trim().is_empty())
+    };
+    let from_git = || {
+        let dir = project_dir.to_string_lossy().to_string();
+        executor
+            .execute("git", &["-C", &dir, "rev-parse", "--abbrev-ref", "HEAD"])
+            .ok()
+            .filter(|out| out.success())
+            .map(|out| out.stdout.trim().to_string())
+            .filter(|branch| !branch.is_empty() && branch != "HEAD")
+    };
+
+    explicit
+        .map(str::to_string)
+        .filter(|branch| !branch.trim().is_empty())
+        .or_else(from_env)
+        .or_else(from_git)
+        .ok_or_else(|| {
+            CliError::ConfigValidation(
+                "Could not determine the git branch; pass --branch <name>".to_string(),
+            )
+        })
+}
+
+fn preview_config(config: &StackerConfig) -> Result<&PreviewConfig, CliError> {
+    config.deploy.preview.as_ref().ok_or_else(|| {
+        CliError::ConfigValidation("deploy.preview is not configured in stacker.yml".to_string())
+    })
+}
+
+/// Deployment hash of the shared server previews deploy to.
+pub fn preview_deployment_hash(config: &StackerConfig) -> Result<String, CliError> {
+    let preview = preview_config(config)?;
+    let deploy = config.deploy.resolve(preview.target.as_deref())?;
+    if deploy.target == DeployTarget::Local {
+        return Err(CliError::ConfigValidation(
+            "deploy.preview.target must name a server or cloud profile in deploy.targets"
+                .to_string(),
+        ));
+    }
+    deploy
+        .deployment_hash
+        .filter(|hash| !hash.trim().is_empty())
+        .ok_or_else(|| {
+            CliError::ConfigValidation(format!(
+                "deploy.targets.{}.deployment_hash is required for previews; deploy the shared server first",
+                preview.target.as_deref().unwrap_or("<default>")
+            ))
+        })
+}
+
+/// Resolve `deploy.preview` and its `deploy.targets` profile for `branch`.
+pub fn plan_preview(
+    config: &StackerConfig,
+    branch: &str,
+    ttl_hours: Option<u32>,
+) -> Result<PreviewPlan, CliError> {
+    let preview = preview_config(config)?;
+    let deployment_hash = preview_deployment_hash(config)?;
+
+    let slug = preview_slug(branch);
+    if slug.is_empty() {
+        return Err(CliError::ConfigValidation(format!(
+            "Branch '{}' has no characters usable in a preview domain",
+            branch
+        )));
+    }
+
+    let upstream = preview
+        .upstream
+        .clone()
+        .or_else(|| config.proxy.domains.first().map(|d| d.upstream.clone()))
+        .ok_or_else(|| {
+            CliError::ConfigValidation(
+                "deploy.preview.upstream is required when proxy.domains is empty".to_string(),
+            )
+        })?;
+    let service = upstream_service_name(&upstream).ok_or_else(|| {
+        CliError::ConfigValidation(format!("Invalid preview upstream '{}'", upstream))
+    })?;
+    let forward_port = upstream_port(&upstream).unwrap_or(80);
+
+    let project = preview_slug(
+        config
+            .project
+            .identity
+            .as_deref()
+            .filter(|identity| !identity.trim().is_empty())
+            .unwrap_or(&config.name),
+    );
+    let compose_project = format!("{}-preview-{}", project, slug);
+
+    Ok(PreviewPlan {
+        deployment_hash,
+        branch: branch.to_string(),
+        app_code: format!("preview-{}", slug),
+        domain: format!("{}.{}", slug, preview.base_domain.trim_matches('.')),
+        forward_host: format!("{}-{}-1", compose_project, service),
+        compose_project,
+        slug,
+        service,
+        forward_port,
+        ttl_secs: i64::from(ttl_hours.unwrap_or(preview.ttl_hours)) * 3600,
+    })
+}
+
+fn upstream_port(upstream: &str) -> Option<u16> {
+    let host = upstream
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()?;
+    host.rsplit_once(':')?.1.parse().ok()
+}
+
+/// Adapt the project compose for a shared server: host port bindings and
+/// fixed container names would collide with the main stack, so they are
+/// dropped, and the proxied service joins the proxy's `default_network`.
+pub fn preview_compose(compose_content: &str, service: &str) -> Result<String, CliError> {
+    let mut doc: Value = serde_yaml::from_str(compose_content)
+        .map_err(|e| CliError::ConfigValidation(format!("Invalid compose file: {}", e)))?;
+    let services = doc
+        .get_mut("services")
+        .and_then(Value::as_mapping_mut)
+        .ok_or_else(|| CliError::ConfigValidation("Compose file has no services".to_string()))?;
+    if !services.contains_key(service) {
+        return Err(CliError::ConfigValidation(format!(
+            "Preview upstream service '{}' is not in the compose file",
+            service
+        )));
+    }
+    for (_, definition) in services.iter_mut() {
+        if let Some(definition) = definition.as_mapping_mut() {
+            definition.remove("ports");
+            definition.remove("container_name");
+        }
+    }
+    inject_external_network(&mut doc, service, "default_network");
+    Ok(serde_yaml::to_string(&doc)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_and_compose_isolate_the_branch() {
+        let config = StackerConfig::from_str(
+            r#"
+name: Shop
+proxy:
+  type: nginx-proxy-manager
+  domains:
+    - domain: shop.example.com
+      upstream: web:3000
+deploy:
+  targets:
+    staging:
+      deployment_hash: dep-staging
+      server:
+        host: 203.0.113.10
+  preview:
+    base_domain: preview.example.com
+"#,
+        )
+        .unwrap();
+
+        let plan = plan_preview(&config, "feature/Checkout", None).unwrap();
+        assert_eq!(plan.deployment_hash, "dep-staging");
+        assert_eq!(plan.domain, "feature-checkout.preview.example.com");
+        assert_eq!(plan.compose_project, "shop-preview-feature-checkout");
+        assert_eq!(plan.forward_host, "shop-preview-feature-checkout-web-1");
+        assert_eq!(plan.forward_port, 3000);
+        assert_eq!(plan.ttl_secs, 72 * 3600);
+
+        let compose = "services:\n  web:\n    image: web:1\n    container_name: web\n    ports:\n      - \"3000:3000\"\n  db:\n    image: postgres:16\n    ports:\n      - \"5432:5432\"\n";
+        let rendered = preview_compose(compose, "web").unwrap();
+        assert!(!rendered.contains("ports"));
+        assert!(!rendered.contains("container_name"));
+        assert!(rendered.contains("default_network"));
+        assert!(preview_compose(compose, "api").is_err());
+    }
+}
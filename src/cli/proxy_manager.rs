@@ -1,7 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
 
-use crate::cli::config_parser::{DomainConfig, ProxyType, SslMode};
-use crate::cli::error::CliError;
+use crate::cli::acme::{acme_directory, ACME_WEBROOT, TRAEFIK_CERT_RESOLVER};
+use crate::cli::config_parser::{
+    DeployTarget, DomainConfig, ProxyConfig, ProxyType, RouteConfig, SslMode, StackerConfig,
+};
+use crate::cli::error::{CliError, Severity, ValidationIssue};
+use crate::cli::generator::compose::ComposeDefinition;
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // ContainerInfo — minimal container metadata
@@ -19,12 +25,116 @@ pub struct ContainerInfo {
 }
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
-// ContainerRuntime trait — abstraction over Docker CLI (DIP)
+// RuntimeKind — which container engine CLI to drive
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Environment variable holding the runtime override. The global
+/// `--container-runtime` flag sets it so every command sees the same choice.
+pub const CONTAINER_RUNTIME_ENV: &str = "STACKER_CONTAINER_RUNTIME";
+
+/// Docker-compatible container engines stacker can drive locally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeKind {
+    Docker,
+    Podman,
+    Nerdctl,
+}
+
+impl RuntimeKind {
+    /// Auto-detection order.
+    pub const ALL: [RuntimeKind; 3] = [Self::Docker, Self::Podman, Self::Nerdctl];
+
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+            Self::Nerdctl => "nerdctl",
+        }
+    }
+
+    /// Standalone compose tool used when the `<binary> compose` subcommand
+    /// is missing. nerdctl ships compose built in.
+    pub fn standalone_compose(&self) -> Option<&'static str> {
+        match self {
+            Self::Docker => Some("docker-compose"),
+            Self::Podman => Some("podman-compose"),
+            Self::Nerdctl => None,
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, CliError> {
+        match value.trim().to_lowercase().as_str() {
+            "docker" => Ok(Self::Docker),
+            "podman" => Ok(Self::Podman),
+            "nerdctl" | "containerd" => Ok(Self::Nerdctl),
+            other => Err(CliError::ConfigValidation(format!(
+                "Unknown container runtime '{other}'. Supported: docker, podman, nerdctl"
+            ))),
+        }
+    }
+
+    /// Runtime to use for local commands: `STACKER_CONTAINER_RUNTIME` if set,
+    /// otherwise the first engine found on `PATH`, otherwise Docker. An
+    /// unknown override is an error rather than a silent fallback.
+    pub fn resolve() -> Result<Self, CliError> {
+        resolve_runtime_kind(
+            std::env::var(CONTAINER_RUNTIME_ENV).ok().as_deref(),
+            |kind| binary_on_path(kind.binary()),
+        )
+    }
+}
+
+impl fmt::Display for RuntimeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.binary())
+    }
+}
+
+/// Pure resolution logic behind [`RuntimeKind::resolve`]. An empty override
+/// means auto-detection; an unparseable one is rejected.
+pub fn resolve_runtime_kind(
+    preference: Option<&str>,
+    installed: impl Fn(RuntimeKind) -> bool,
+) -> Result<RuntimeKind, CliError> {
+    if let Some(value) = preference.filter(|value| !value.trim().is_empty()) {
+        return RuntimeKind::parse(value).map_err(|_| {
+            CliError::ConfigValidation(format!(
+                "{CONTAINER_RUNTIME_ENV}='{}' is not a supported container runtime. \
+                 Supported: docker, podman, nerdctl",
+                value.trim()
+            ))
+        });
+    }
+    Ok(RuntimeKind::ALL
+        .into_iter()
+        .find(|kind| installed(*kind))
+        .unwrap_or(RuntimeKind::Docker))
+}
+
+fn binary_on_path(binary: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+        .unwrap_or(false)
+}
+
+/// Runtime implementation for the resolved (or overridden) engine.
+pub fn local_runtime() -> Result<Box<dyn ContainerRuntime>, CliError> {
+    Ok(match RuntimeKind::resolve()? {
+        RuntimeKind::Docker => Box::new(DockerCliRuntime),
+        RuntimeKind::Podman => Box::new(PodmanCliRuntime),
+        RuntimeKind::Nerdctl => Box::new(NerdctlCliRuntime),
+    })
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// ContainerRuntime trait — abstraction over the container CLI (DIP)
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
 /// Abstraction for interacting with the local container runtime.
 ///
-/// Production: `DockerCliRuntime` shells out to `docker` / `docker compose`.
+/// Production: `DockerCliRuntime`, `PodmanCliRuntime` and
+/// `NerdctlCliRuntime` shell out to their respective CLIs, which share
+/// Docker's `ps` / `inspect` / `compose` surface.
 /// Tests: `MockContainerRuntime` returns canned data.
 ///
 /// This is the **first** direct Docker CLI interaction in stacker —
@@ -32,48 +142,125 @@ pub struct ContainerInfo {
 pub trait ContainerRuntime: Send + Sync {
     fn is_available(&self) -> bool;
     fn list_containers(&self) -> Result<Vec<ContainerInfo>, CliError>;
+
+    fn kind(&self) -> RuntimeKind {
+        RuntimeKind::Docker
+    }
+
+    /// Raw `inspect` JSON for one container.
+    fn inspect_container(&self, id: &str) -> Result<serde_json::Value, CliError> {
+        cli_inspect_container(self.kind().binary(), id)
+    }
 }
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
-// DockerCliRuntime — production implementation
+// CLI runtimes — production implementations
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
 pub struct DockerCliRuntime;
 
 impl ContainerRuntime for DockerCliRuntime {
     fn is_available(&self) -> bool {
-        std::process::Command::new("docker")
-            .arg("info")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
+        cli_is_available("docker")
     }
 
     fn list_containers(&self) -> Result<Vec<ContainerInfo>, CliError> {
-        let output = std::process::Command::new("docker")
-            .args([
-                "ps",
-                "--format",
-                "{{.ID}}|{{.Names}}|{{.Image}}|{{.Ports}}|{{.Status}}",
-            ])
-            .output()
-            .map_err(|_| CliError::ContainerRuntimeUnavailable)?;
-
-        if !output.status.success() {
-            return Err(CliError::ContainerRuntimeUnavailable);
-        }
+        cli_list_containers("docker")
+    }
+}
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let containers = stdout
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(parse_docker_ps_line)
-            .collect();
+/// Rootless Podman. `podman ps` accepts Docker's Go-template fields.
+pub struct PodmanCliRuntime;
+
+impl ContainerRuntime for PodmanCliRuntime {
+    fn is_available(&self) -> bool {
+        cli_is_available("podman")
+    }
+
+    fn list_containers(&self) -> Result<Vec<ContainerInfo>, CliError> {
+        cli_list_containers("podman")
+    }
+
+    fn kind(&self) -> RuntimeKind {
+        RuntimeKind::Podman
+    }
+}
+
+/// containerd via nerdctl.
+pub struct NerdctlCliRuntime;
+
+impl ContainerRuntime for NerdctlCliRuntime {
+    fn is_available(&self) -> bool {
+        cli_is_available("nerdctl")
+    }
+
+    fn list_containers(&self) -> Result<Vec<ContainerInfo>, CliError> {
+        cli_list_containers("nerdctl")
+    }
+
+    fn kind(&self) -> RuntimeKind {
+        RuntimeKind::Nerdctl
+    }
+}
+
+fn cli_is_available(binary: &str) -> bool {
+    std::process::Command::new(binary)
+        .arg("info")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+fn cli_list_containers(binary: &str) -> Result<Vec<ContainerInfo>, CliError> {
+    let output = std::process::Command::new(binary)
+        .args([
+            "ps",
+            "--format",
+            "{{.ID}}|{{.Names}}|{{.Image}}|{{.Ports}}|{{.Status}}",
+        ])
+        .output()
+        .map_err(|_| CliError::ContainerRuntimeUnavailable)?;
+
+    if !output.status.success() {
+        return Err(CliError::ContainerRuntimeUnavailable);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let containers = stdout
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(parse_docker_ps_line)
+        .collect();
+
+    Ok(containers)
+}
+
+fn cli_inspect_container(binary: &str, id: &str) -> Result<serde_json::Value, CliError> {
+    let output = std::process::Command::new(binary)
+        .args(["inspect", id])
+        .output()
+        .map_err(|_| CliError::ContainerRuntimeUnavailable)?;
 
-        Ok(containers)
+    if !output.status.success() {
+        return Err(CliError::CommandFailed {
+            command: format!("{binary} inspect {id}"),
+            exit_code: output.status.code().unwrap_or(-1),
+        });
     }
+
+    parse_inspect_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// `inspect` prints a one-element array for Docker, Podman and nerdctl.
+fn parse_inspect_output(stdout: &str) -> Result<serde_json::Value, CliError> {
+    let value: serde_json::Value = serde_json::from_str(stdout.trim())
+        .map_err(|e| CliError::ConfigValidation(format!("Failed to parse inspect output: {e}")))?;
+    Ok(match value {
+        serde_json::Value::Array(mut items) if !items.is_empty() => items.remove(0),
+        other => other,
+    })
 }
 
 /// Parse a single line from `docker ps --format "{{.ID}}|{{.Names}}|{{.Image}}|{{.Ports}}|{{.Status}}"`.
@@ -119,6 +306,85 @@ fn extract_host_ports(ports_str: &str) -> Vec<u16> {
     ports
 }
 
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Runtime compatibility — reported by `stacker config validate`
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Features of a local stack that behave differently on Podman or nerdctl.
+/// Docker, and non-local targets (which always run Docker), report nothing.
+pub fn runtime_compatibility_issues(
+    config: &StackerConfig,
+    kind: RuntimeKind,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    if kind == RuntimeKind::Docker || config.deploy.target != DeployTarget::Local {
+        return issues;
+    }
+
+    if config.proxy.proxy_type == ProxyType::NginxProxyManager && !config.proxy.domains.is_empty() {
+        issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            code: "RT001".to_string(),
+            message: format!(
+                "nginx-proxy-manager upstreams are attached to the external 'default_network', which {kind} does not create; run `{kind} network create default_network` first or use proxy.type nginx"
+            ),
+            field: Some("proxy.type".to_string()),
+        });
+    }
+
+    if kind == RuntimeKind::Podman {
+        if let Ok(compose) = ComposeDefinition::try_from(config) {
+            let mut privileged: Vec<u16> = compose
+                .services
+                .iter()
+                .flat_map(|svc| svc.ports.iter())
+                .filter_map(|port| published_host_port(port))
+                .filter(|port| *port < 1024)
+                .collect();
+            privileged.sort_unstable();
+            privileged.dedup();
+            if !privileged.is_empty() {
+                issues.push(ValidationIssue {
+                    severity: Severity::Warning,
+                    code: "RT002".to_string(),
+                    message: format!(
+                        "rootless Podman cannot bind host ports below 1024 ({}); lower net.ipv4.ip_unprivileged_port_start or publish higher ports",
+                        privileged
+                            .iter()
+                            .map(|p| p.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    field: Some("ports".to_string()),
+                });
+            }
+        }
+    }
+
+    if kind == RuntimeKind::Nerdctl
+        && (config.app.healthcheck.is_some()
+            || config.services.iter().any(|svc| svc.healthcheck.is_some()))
+    {
+        issues.push(ValidationIssue {
+            severity: Severity::Info,
+            code: "RT003".to_string(),
+            message: "nerdctl compose ignores container healthchecks; deploy health waits fall back to the running state".to_string(),
+            field: Some("healthcheck".to_string()),
+        });
+    }
+
+    issues
+}
+
+/// `"80:80"` / `"127.0.0.1:443:443/tcp"` → host port; container-only ports → `None`.
+fn published_host_port(port: &str) -> Option<u16> {
+    let parts: Vec<&str> = port.split('/').next()?.split(':').collect();
+    if parts.len() < 2 {
+        return None;
+    }
+    parts[parts.len() - 2].parse().ok()
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // ProxyDetection — result of scanning running containers
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -150,6 +416,7 @@ const PROXY_SIGNATURES: &[(&str, ProxyType)] = &[
     ("jc21/nginx-proxy-manager", ProxyType::NginxProxyManager),
     ("nginx-proxy-manager", ProxyType::NginxProxyManager),
     ("traefik", ProxyType::Traefik),
+    ("caddy", ProxyType::Caddy),
     ("nginx", ProxyType::Nginx),
 ];
 
@@ -278,75 +545,219 @@ fn validate_upstream(upstream: &str) -> Result<(), CliError> {
 /// Generate an nginx `server { }` block for a single domain configuration.
 ///
 /// Produces a config suitable for inclusion in `/etc/nginx/conf.d/`.
-/// SSL directives are included when `ssl` is `Auto` or `Manual`.
+/// SSL directives are included when `ssl` is `Auto` or `Manual`; each of
+/// the domain's `routes` becomes its own `location` with its policies.
 pub fn generate_nginx_server_block(domain: &DomainConfig) -> Result<String, CliError> {
+    match domain.ssl {
+        SslMode::Auto | SslMode::Manual => Ok(format!(
+            "{}\n{}",
+            generate_nginx_redirect_block(domain)?,
+            generate_nginx_https_block(domain)?
+        )),
+        SslMode::Off => {
+            validate_domain(&domain.domain)?;
+            validate_routes(domain)?;
+            let mut block = nginx_rate_limit_zones(domain);
+            block.push_str("server {\n");
+            block.push_str("    listen 80;\n");
+            block.push_str(&format!("    server_name {};\n", domain.domain));
+            block.push_str("\n");
+            push_route_locations(&mut block, domain);
+            block.push_str("}\n");
+            Ok(block)
+        }
+    }
+}
+
+/// The plain-HTTP half of an SSL domain: redirects to HTTPS and, for
+/// `ssl: auto`, serves ACME HTTP-01 challenges from the certbot webroot.
+pub fn generate_nginx_redirect_block(domain: &DomainConfig) -> Result<String, CliError> {
     validate_domain(&domain.domain)?;
-    validate_upstream(&domain.upstream)?;
     let mut block = String::new();
-    let proxy_pass = proxy_pass_target(&domain.upstream);
+    block.push_str("server {\n");
+    block.push_str("    listen 80;\n");
+    block.push_str(&format!("    server_name {};\n", domain.domain));
+    block.push_str("\n");
+    if domain.ssl == SslMode::Auto {
+        block.push_str("    location /.well-known/acme-challenge/ {\n");
+        block.push_str(&format!("        root {};\n", ACME_WEBROOT));
+        block.push_str("    }\n");
+        block.push_str("\n");
+    }
+    block.push_str("    location / {\n");
+    block.push_str(&format!(
+        "        return 301 https://{}$request_uri;\n",
+        domain.domain
+    ));
+    block.push_str("    }\n");
+    block.push_str("}\n");
+    Ok(block)
+}
 
+/// The TLS-terminating half of an SSL domain.
+pub fn generate_nginx_https_block(domain: &DomainConfig) -> Result<String, CliError> {
+    validate_domain(&domain.domain)?;
+    validate_routes(domain)?;
+    let mut block = nginx_rate_limit_zones(domain);
     block.push_str("server {\n");
+    block.push_str("    listen 443 ssl http2;\n");
+    block.push_str(&format!("    server_name {};\n", domain.domain));
+    block.push_str("\n");
+
+    if domain.ssl == SslMode::Auto {
+        block.push_str(&format!(
+            "    ssl_certificate /etc/letsencrypt/live/{}/fullchain.pem;\n",
+            domain.domain
+        ));
+        block.push_str(&format!(
+            "    ssl_certificate_key /etc/letsencrypt/live/{}/privkey.pem;\n",
+            domain.domain
+        ));
+    } else {
+        block.push_str("    ssl_certificate /etc/nginx/ssl/cert.pem;\n");
+        block.push_str("    ssl_certificate_key /etc/nginx/ssl/key.pem;\n");
+    }
 
-    match domain.ssl {
-        SslMode::Auto | SslMode::Manual => {
-            block.push_str("    listen 80;\n");
-            block.push_str(&format!("    server_name {};\n", domain.domain));
-            block.push_str("\n");
-            block.push_str("    location / {\n");
-            block.push_str(&format!(
-                "        return 301 https://{}$request_uri;\n",
-                domain.domain
-            ));
-            block.push_str("    }\n");
-            block.push_str("}\n\n");
+    block.push_str("\n");
+    push_route_locations(&mut block, domain);
+    block.push_str("}\n");
+    Ok(block)
+}
 
-            block.push_str("server {\n");
-            block.push_str("    listen 443 ssl http2;\n");
-            block.push_str(&format!("    server_name {};\n", domain.domain));
-            block.push_str("\n");
+/// Where the generated nginx service mounts basic-auth files.
+pub const NGINX_HTPASSWD_DIR: &str = "/etc/nginx/htpasswd";
+
+/// htpasswd files for routes using `basic_auth`, as `filename → content`.
+pub fn nginx_htpasswd_files(domain: &DomainConfig) -> Vec<(String, String)> {
+    domain
+        .effective_routes()
+        .iter()
+        .filter_map(|route| {
+            let auth = route.policy.basic_auth.as_ref()?;
+            Some((
+                route_key(&domain.domain, &route.path),
+                format!("{}\n", auth.users.join("\n")),
+            ))
+        })
+        .collect()
+}
 
-            if domain.ssl == SslMode::Auto {
-                block.push_str(&format!(
-                    "    ssl_certificate /etc/letsencrypt/live/{}/fullchain.pem;\n",
-                    domain.domain
-                ));
-                block.push_str(&format!(
-                    "    ssl_certificate_key /etc/letsencrypt/live/{}/privkey.pem;\n",
-                    domain.domain
-                ));
-            } else {
-                block.push_str("    ssl_certificate /etc/nginx/ssl/cert.pem;\n");
-                block.push_str("    ssl_certificate_key /etc/nginx/ssl/key.pem;\n");
-            }
+fn validate_routes(domain: &DomainConfig) -> Result<(), CliError> {
+    for route in domain.effective_routes() {
+        validate_upstream(&route.upstream)?;
+    }
+    let errors = domain.route_errors();
+    if !errors.is_empty() {
+        return Err(CliError::ConfigValidation(format!(
+            "Invalid routes for '{}': {}",
+            domain.domain,
+            errors.join("; ")
+        )));
+    }
+    Ok(())
+}
 
-            block.push_str("\n");
-            block.push_str("    location / {\n");
-            block.push_str(&format!("        proxy_pass {};\n", proxy_pass));
-            block.push_str("        proxy_set_header Host $host;\n");
-            block.push_str("        proxy_set_header X-Real-IP $remote_addr;\n");
-            block
-                .push_str("        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n");
-            block.push_str("        proxy_set_header X-Forwarded-Proto $scheme;\n");
-            block.push_str("    }\n");
-            block.push_str("}\n");
+/// Stable identifier for a domain + path, used for nginx zones, htpasswd
+/// files and Traefik router names: `app.example.com` + `/api` → `app_example_com_api`.
+pub fn route_key(domain: &str, path: &str) -> String {
+    let slug = |value: &str| -> String {
+        value
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>()
+            .trim_matches('_')
+            .to_string()
+    };
+    let path_slug = slug(path);
+    format!(
+        "{}_{}",
+        slug(domain),
+        if path_slug.is_empty() {
+            "root"
+        } else {
+            &path_slug
         }
-        SslMode::Off => {
-            block.push_str("    listen 80;\n");
-            block.push_str(&format!("    server_name {};\n", domain.domain));
+    )
+}
+
+/// `limit_req_zone` directives (http context) for rate-limited routes.
+fn nginx_rate_limit_zones(domain: &DomainConfig) -> String {
+    let mut zones = String::new();
+    for route in domain.effective_routes() {
+        if let Some(limit) = &route.policy.rate_limit {
+            zones.push_str(&format!(
+                "limit_req_zone $binary_remote_addr zone={}:10m rate={}r/s;\n",
+                route_key(&domain.domain, &route.path),
+                limit.requests_per_second
+            ));
+        }
+    }
+    if !zones.is_empty() {
+        zones.push('\n');
+    }
+    zones
+}
+
+fn push_route_locations(block: &mut String, domain: &DomainConfig) {
+    for (index, route) in domain.effective_routes().iter().enumerate() {
+        if index > 0 {
             block.push_str("\n");
-            block.push_str("    location / {\n");
-            block.push_str(&format!("        proxy_pass {};\n", proxy_pass));
-            block.push_str("        proxy_set_header Host $host;\n");
-            block.push_str("        proxy_set_header X-Real-IP $remote_addr;\n");
-            block
-                .push_str("        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n");
-            block.push_str("        proxy_set_header X-Forwarded-Proto $scheme;\n");
-            block.push_str("    }\n");
-            block.push_str("}\n");
         }
+        push_proxy_location(block, &domain.domain, route);
     }
+}
 
-    Ok(block)
+fn push_proxy_location(block: &mut String, domain: &str, route: &RouteConfig) {
+    let policy = &route.policy;
+    let key = route_key(domain, &route.path);
+
+    block.push_str(&format!("    location {} {{\n", route.path));
+    if !policy.allow_ips.is_empty() {
+        for entry in &policy.allow_ips {
+            block.push_str(&format!("        allow {};\n", entry));
+        }
+        block.push_str("        deny all;\n");
+    }
+    if let Some(auth) = &policy.basic_auth {
+        block.push_str(&format!(
+            "        auth_basic \"{}\";\n",
+            auth.realm
+                .as_deref()
+                .unwrap_or("Restricted")
+                .replace('"', "")
+        ));
+        block.push_str(&format!(
+            "        auth_basic_user_file {}/{};\n",
+            NGINX_HTPASSWD_DIR, key
+        ));
+    }
+    if let Some(limit) = &policy.rate_limit {
+        block.push_str(&format!(
+            "        limit_req zone={} burst={} nodelay;\n",
+            key, limit.burst
+        ));
+    }
+    if let Some(size) = &policy.client_max_body_size {
+        block.push_str(&format!("        client_max_body_size {};\n", size));
+    }
+    block.push_str(&format!(
+        "        proxy_pass {};\n",
+        proxy_pass_target(&route.upstream)
+    ));
+    if policy.websocket == Some(true) {
+        block.push_str("        proxy_http_version 1.1;\n");
+        block.push_str("        proxy_set_header Upgrade $http_upgrade;\n");
+        block.push_str("        proxy_set_header Connection \"upgrade\";\n");
+    }
+    if let Some(timeout) = policy.timeout {
+        block.push_str(&format!("        proxy_read_timeout {}s;\n", timeout));
+        block.push_str(&format!("        proxy_send_timeout {}s;\n", timeout));
+    }
+    block.push_str("        proxy_set_header Host $host;\n");
+    block.push_str("        proxy_set_header X-Real-IP $remote_addr;\n");
+    block.push_str("        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n");
+    block.push_str("        proxy_set_header X-Forwarded-Proto $scheme;\n");
+    block.push_str("    }\n");
 }
 
 fn proxy_pass_target(upstream: &str) -> String {
@@ -373,6 +784,322 @@ pub fn generate_nginx_configs(
     Ok(configs)
 }
 
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Traefik labels — routers, services and middlewares per route
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Docker labels that route `proxy.domains` through Traefik, grouped by the
+/// compose service each route's upstream points at.
+pub fn traefik_labels(
+    domains: &[DomainConfig],
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, CliError> {
+    let mut by_service: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+
+    for domain in domains {
+        validate_domain(&domain.domain)?;
+        validate_routes(domain)?;
+
+        for route in domain.effective_routes() {
+            let (scheme, host, port) = split_upstream(&route.upstream);
+            let name = route_key(&domain.domain, &route.path).replace('_', "-");
+            let router = format!("traefik.http.routers.{name}");
+            let labels = by_service.entry(host.to_string()).or_default();
+            let mut set = |key: String, value: String| {
+                labels.insert(key, value);
+            };
+
+            set("traefik.enable".to_string(), "true".to_string());
+            let rule = if route.path == "/" {
+                format!("Host(`{}`)", domain.domain)
+            } else {
+                format!("Host(`{}`) && PathPrefix(`{}`)", domain.domain, route.path)
+            };
+            set(format!("{router}.service"), name.clone());
+            if domain.ssl == SslMode::Off {
+                set(format!("{router}.entrypoints"), "web".to_string());
+            } else {
+                set(format!("{router}.entrypoints"), "websecure".to_string());
+                set(format!("{router}.tls"), "true".to_string());
+                if domain.ssl == SslMode::Auto {
+                    set(
+                        format!("{router}.tls.certresolver"),
+                        TRAEFIK_CERT_RESOLVER.to_string(),
+                    );
+                }
+                // Plain-HTTP twin that only redirects to HTTPS.
+                let redirect = format!("traefik.http.routers.{name}-http");
+                set(format!("{redirect}.rule"), rule.clone());
+                set(format!("{redirect}.entrypoints"), "web".to_string());
+                set(format!("{redirect}.service"), name.clone());
+                set(format!("{redirect}.middlewares"), format!("{name}-https"));
+                set(
+                    format!("traefik.http.middlewares.{name}-https.redirectscheme.scheme"),
+                    "https".to_string(),
+                );
+                set(
+                    format!("traefik.http.middlewares.{name}-https.redirectscheme.permanent"),
+                    "true".to_string(),
+                );
+            }
+            set(format!("{router}.rule"), rule);
+            set(
+                format!("traefik.http.services.{name}.loadbalancer.server.port"),
+                port.to_string(),
+            );
+            if scheme == "https" {
+                set(
+                    format!("traefik.http.services.{name}.loadbalancer.server.scheme"),
+                    "https".to_string(),
+                );
+            }
+
+            let policy = &route.policy;
+            let middleware = |suffix: &str| format!("traefik.http.middlewares.{name}-{suffix}");
+            let mut middlewares = Vec::new();
+            if !policy.allow_ips.is_empty() {
+                set(
+                    format!("{}.ipwhitelist.sourcerange", middleware("allow")),
+                    policy.allow_ips.join(","),
+                );
+                middlewares.push(format!("{name}-allow"));
+            }
+            if let Some(auth) = &policy.basic_auth {
+                // `$` must be doubled so compose does not interpolate hashes.
+                set(
+                    format!("{}.basicauth.users", middleware("auth")),
+                    auth.users.join(",").replace('$', "$$"),
+                );
+                if let Some(realm) = &auth.realm {
+                    set(
+                        format!("{}.basicauth.realm", middleware("auth")),
+                        realm.clone(),
+                    );
+                }
+                middlewares.push(format!("{name}-auth"));
+            }
+            if let Some(limit) = &policy.rate_limit {
+                set(
+                    format!("{}.ratelimit.average", middleware("ratelimit")),
+                    limit.requests_per_second.to_string(),
+                );
+                if limit.burst > 0 {
+                    set(
+                        format!("{}.ratelimit.burst", middleware("ratelimit")),
+                        limit.burst.to_string(),
+                    );
+                }
+                middlewares.push(format!("{name}-ratelimit"));
+            }
+            if let Some(bytes) = policy
+                .client_max_body_size
+                .as_deref()
+                .and_then(size_in_bytes)
+            {
+                set(
+                    format!("{}.buffering.maxRequestBodyBytes", middleware("body")),
+                    bytes.to_string(),
+                );
+                middlewares.push(format!("{name}-body"));
+            }
+            if !middlewares.is_empty() {
+                set(format!("{router}.middlewares"), middlewares.join(","));
+            }
+        }
+    }
+
+    Ok(by_service)
+}
+
+/// `http://api:8080` → `("http", "api", "8080")`. Callers validate first.
+fn split_upstream(upstream: &str) -> (&str, &str, &str) {
+    let (scheme, rest) = match upstream.split_once("://") {
+        Some((scheme, rest)) => (scheme, rest),
+        None => ("http", upstream),
+    };
+    let (host, port) = rest.rsplit_once(':').unwrap_or((rest, "80"));
+    (scheme, host, port)
+}
+
+/// nginx size syntax (`512k`, `20m`, `1g`) → bytes.
+fn size_in_bytes(size: &str) -> Option<u64> {
+    let (digits, multiplier) = match size.chars().last()?.to_ascii_lowercase() {
+        'k' => (&size[..size.len() - 1], 1024),
+        'm' => (&size[..size.len() - 1], 1024 * 1024),
+        'g' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    digits.parse::<u64>().ok().map(|n| n * multiplier)
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Caddy — Caddyfile with automatic HTTPS
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Directory under the compose output dir holding the Caddyfile.
+pub const CADDY_ASSETS_DIR: &str = "caddy";
+/// Where `ssl: manual` certificates are mounted in the Caddy container.
+pub const CADDY_SSL_DIR: &str = "/etc/caddy/ssl";
+
+/// Generate a Caddy site block for a single domain.
+///
+/// `ssl: auto` relies on Caddy's automatic HTTPS, `ssl: off` serves plain
+/// HTTP and `ssl: manual` loads the certificate from [`CADDY_SSL_DIR`].
+/// Each route becomes a `handle` block with its policies.
+pub fn generate_caddy_site_block(domain: &DomainConfig) -> Result<String, CliError> {
+    validate_domain(&domain.domain)?;
+    validate_routes(domain)?;
+
+    let mut block = match domain.ssl {
+        SslMode::Off => format!("http://{} {{\n", domain.domain),
+        _ => format!("{} {{\n", domain.domain),
+    };
+    if domain.ssl == SslMode::Manual {
+        block.push_str(&format!(
+            "    tls {dir}/cert.pem {dir}/key.pem\n",
+            dir = CADDY_SSL_DIR
+        ));
+    }
+
+    // Caddy orders `handle` blocks by path specificity, so the catch-all
+    // root route can go last regardless of declaration order.
+    let routes = domain.effective_routes();
+    let (root, nested): (Vec<_>, Vec<_>) = routes.iter().partition(|r| r.path == "/");
+    for route in nested.into_iter().chain(root) {
+        block.push('\n');
+        push_caddy_handle(&mut block, &domain.domain, route);
+    }
+    block.push_str("}\n");
+    Ok(block)
+}
+
+fn push_caddy_handle(block: &mut String, domain: &str, route: &RouteConfig) {
+    let policy = &route.policy;
+    let key = route_key(domain, &route.path);
+
+    if route.path == "/" {
+        block.push_str("    handle {\n");
+    } else {
+        block.push_str(&format!("    handle {}* {{\n", route.path));
+    }
+    if !policy.allow_ips.is_empty() {
+        block.push_str(&format!(
+            "        @{}_denied not remote_ip {}\n",
+            key,
+            policy.allow_ips.join(" ")
+        ));
+        block.push_str(&format!("        respond @{}_denied 403\n", key));
+    }
+    if let Some(auth) = &policy.basic_auth {
+        block.push_str(&format!(
+            "        basic_auth bcrypt \"{}\" {{\n",
+            auth.realm
+                .as_deref()
+                .unwrap_or("Restricted")
+                .replace('"', "")
+        ));
+        for user in &auth.users {
+            if let Some((name, hash)) = user.split_once(':') {
+                block.push_str(&format!("            {} {}\n", name, hash));
+            }
+        }
+        block.push_str("        }\n");
+    }
+    if let Some(bytes) = policy
+        .client_max_body_size
+        .as_deref()
+        .and_then(size_in_bytes)
+    {
+        block.push_str("        request_body {\n");
+        block.push_str(&format!("            max_size {}\n", bytes));
+        block.push_str("        }\n");
+    }
+    // Websocket upgrades are proxied by default; rate limiting needs a plugin
+    // (reported as W004 by `stacker config validate`).
+    match policy.timeout {
+        Some(timeout) => {
+            block.push_str(&format!("        reverse_proxy {} {{\n", route.upstream));
+            block.push_str("            transport http {\n");
+            block.push_str(&format!("                read_timeout {}s\n", timeout));
+            block.push_str(&format!("                write_timeout {}s\n", timeout));
+            block.push_str("            }\n");
+            block.push_str("        }\n");
+        }
+        None => block.push_str(&format!("        reverse_proxy {}\n", route.upstream)),
+    }
+    block.push_str("    }\n");
+}
+
+/// Generate the complete Caddyfile: ACME global options followed by one
+/// site block per domain.
+pub fn generate_caddyfile(proxy: &ProxyConfig) -> Result<String, CliError> {
+    let mut out = String::from("# Generated by stacker from proxy.domains\n");
+
+    let mut globals = Vec::new();
+    if let Some(acme) = &proxy.acme {
+        if let Some(email) = acme.email.as_deref().filter(|e| !e.trim().is_empty()) {
+            globals.push(format!("email {}", email));
+        }
+        if let Some(directory) = acme_directory(acme) {
+            globals.push(format!("acme_ca {}", directory));
+        }
+    }
+    if !globals.is_empty() {
+        out.push_str("{\n");
+        for line in globals {
+            out.push_str(&format!("    {}\n", line));
+        }
+        out.push_str("}\n");
+    }
+
+    for domain in &proxy.domains {
+        out.push('\n');
+        out.push_str(&generate_caddy_site_block(domain)?);
+    }
+    Ok(out)
+}
+
+/// Write the Caddyfile mounted by the generated `caddy` service.
+pub fn write_caddy_assets(
+    output_dir: &std::path::Path,
+    config: &StackerConfig,
+) -> Result<(), CliError> {
+    if config.proxy.proxy_type != ProxyType::Caddy {
+        return Ok(());
+    }
+    let dir = output_dir.join(CADDY_ASSETS_DIR);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("Caddyfile"), generate_caddyfile(&config.proxy)?)?;
+    Ok(())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Snippets for `stacker proxy add`
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Proxy config for one domain in the proxy's own format: an nginx server
+/// block, Traefik compose labels, or a Caddy site block.
+pub fn generate_proxy_snippet(
+    proxy_type: ProxyType,
+    domain: &DomainConfig,
+) -> Result<String, CliError> {
+    match proxy_type {
+        ProxyType::Traefik => {
+            let mut out = String::new();
+            for (service, labels) in traefik_labels(std::slice::from_ref(domain))? {
+                out.push_str(&format!("# services.{}.labels\n", service));
+                for (key, value) in labels {
+                    out.push_str(&format!("- \"{}={}\"\n", key, value));
+                }
+            }
+            Ok(out)
+        }
+        ProxyType::Caddy => generate_caddy_site_block(domain),
+        ProxyType::Nginx | ProxyType::NginxProxyManager | ProxyType::None => {
+            generate_nginx_server_block(domain)
+        }
+    }
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Tests
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -516,6 +1243,7 @@ mod tests {
             domain: "app.example.com".to_string(),
             ssl: SslMode::Auto,
             upstream: "app:3000".to_string(),
+            ..Default::default()
         };
         let block = generate_nginx_server_block(&domain).unwrap();
         assert!(block.contains("server_name app.example.com;"));
@@ -531,6 +1259,7 @@ mod tests {
             domain: "app.example.com".to_string(),
             ssl: SslMode::Manual,
             upstream: "app:3000".to_string(),
+            ..Default::default()
         };
         let block = generate_nginx_server_block(&domain).unwrap();
         assert!(block.contains("listen 443 ssl http2;"));
@@ -544,6 +1273,7 @@ mod tests {
             domain: "app.local".to_string(),
             ssl: SslMode::Off,
             upstream: "app:8080".to_string(),
+            ..Default::default()
         };
         let block = generate_nginx_server_block(&domain).unwrap();
         assert!(block.contains("listen 80;"));
@@ -559,6 +1289,7 @@ mod tests {
             domain: "app.local".to_string(),
             ssl: SslMode::Off,
             upstream: "http://app:8080".to_string(),
+            ..Default::default()
         };
         let block = generate_nginx_server_block(&domain).unwrap();
         assert!(block.contains("proxy_pass http://app:8080;"));
@@ -572,11 +1303,13 @@ mod tests {
                 domain: "api.example.com".to_string(),
                 ssl: SslMode::Auto,
                 upstream: "api:4000".to_string(),
+                ..Default::default()
             },
             DomainConfig {
                 domain: "web.example.com".to_string(),
                 ssl: SslMode::Off,
                 upstream: "web:3000".to_string(),
+                ..Default::default()
             },
         ];
         let configs = generate_nginx_configs(&domains).unwrap();
@@ -585,6 +1318,175 @@ mod tests {
         assert!(configs.contains_key("web_example_com.conf"));
     }
 
+    fn routed_domain(ssl: SslMode) -> DomainConfig {
+        use crate::cli::config_parser::{BasicAuthConfig, RateLimitConfig, RoutePolicy};
+        DomainConfig {
+            domain: "app.example.com".to_string(),
+            ssl,
+            upstream: "web:3000".to_string(),
+            routes: vec![
+                RouteConfig {
+                    path: "/api".to_string(),
+                    upstream: "api:8080".to_string(),
+                    policy: RoutePolicy {
+                        rate_limit: Some(RateLimitConfig {
+                            requests_per_second: 10,
+                            burst: 20,
+                        }),
+                        timeout: Some(120),
+                        ..Default::default()
+                    },
+                },
+                RouteConfig {
+                    path: "/ws".to_string(),
+                    upstream: "api:8080".to_string(),
+                    policy: RoutePolicy {
+                        websocket: Some(true),
+                        ..Default::default()
+                    },
+                },
+            ],
+            policy: RoutePolicy {
+                allow_ips: vec!["10.0.0.0/8".to_string()],
+                basic_auth: Some(BasicAuthConfig {
+                    users: vec!["admin:$apr1$abc$def".to_string()],
+                    realm: None,
+                }),
+                client_max_body_size: Some("20m".to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_generate_nginx_server_block_renders_routes_and_policies() {
+        let block = generate_nginx_server_block(&routed_domain(SslMode::Off)).unwrap();
+        assert!(block.starts_with(
+            "limit_req_zone $binary_remote_addr zone=app_example_com_api:10m rate=10r/s;\n"
+        ));
+        assert!(block.contains("    location / {\n        allow 10.0.0.0/8;\n        deny all;\n"));
+        assert!(block.contains("    location /api {\n"));
+        assert!(block.contains("limit_req zone=app_example_com_api burst=20 nodelay;"));
+        assert!(block.contains("proxy_read_timeout 120s;"));
+        assert!(block.contains("    location /ws {\n"));
+        assert!(block.contains("proxy_set_header Upgrade $http_upgrade;"));
+        assert!(block.contains("auth_basic_user_file /etc/nginx/htpasswd/app_example_com_ws;"));
+        assert_eq!(block.matches("client_max_body_size 20m;").count(), 3);
+        assert_eq!(block.matches("proxy_pass http://api:8080;").count(), 2);
+
+        let files = nginx_htpasswd_files(&routed_domain(SslMode::Off));
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].1, "admin:$apr1$abc$def\n");
+    }
+
+    #[test]
+    fn test_generate_nginx_server_block_rejects_overlapping_routes() {
+        let mut domain = routed_domain(SslMode::Off);
+        domain.routes[1].path = "/api/".to_string();
+        let err = generate_nginx_server_block(&domain).unwrap_err();
+        assert!(err.to_string().contains("match the same prefix"));
+    }
+
+    #[test]
+    fn test_traefik_labels_for_routes() {
+        let labels = traefik_labels(&[routed_domain(SslMode::Auto)]).unwrap();
+        let web = &labels["web"];
+        assert_eq!(
+            web["traefik.http.routers.app-example-com-root.rule"],
+            "Host(`app.example.com`)"
+        );
+        assert_eq!(
+            web["traefik.http.routers.app-example-com-root.entrypoints"],
+            "websecure"
+        );
+        assert_eq!(
+            web["traefik.http.services.app-example-com-root.loadbalancer.server.port"],
+            "3000"
+        );
+        assert_eq!(
+            web["traefik.http.middlewares.app-example-com-root-auth.basicauth.users"],
+            "admin:$$apr1$$abc$$def"
+        );
+        assert_eq!(
+            web["traefik.http.middlewares.app-example-com-root-body.buffering.maxRequestBodyBytes"],
+            "20971520"
+        );
+
+        let api = &labels["api"];
+        assert_eq!(
+            api["traefik.http.routers.app-example-com-api.rule"],
+            "Host(`app.example.com`) && PathPrefix(`/api`)"
+        );
+        assert_eq!(
+            api["traefik.http.routers.app-example-com-api.middlewares"],
+            "app-example-com-api-allow,app-example-com-api-auth,app-example-com-api-ratelimit,app-example-com-api-body"
+        );
+        assert_eq!(
+            api["traefik.http.middlewares.app-example-com-api-ratelimit.ratelimit.burst"],
+            "20"
+        );
+        assert!(api.contains_key("traefik.http.routers.app-example-com-ws.rule"));
+    }
+
+    #[test]
+    fn test_traefik_labels_add_cert_resolver_and_https_redirect() {
+        let labels = traefik_labels(&[routed_domain(SslMode::Auto)]).unwrap();
+        let web = &labels["web"];
+        assert_eq!(
+            web["traefik.http.routers.app-example-com-root.tls.certresolver"],
+            "letsencrypt"
+        );
+        assert_eq!(
+            web["traefik.http.routers.app-example-com-root-http.entrypoints"],
+            "web"
+        );
+        assert_eq!(
+            web["traefik.http.middlewares.app-example-com-root-https.redirectscheme.scheme"],
+            "https"
+        );
+
+        let plain = traefik_labels(&[routed_domain(SslMode::Off)]).unwrap();
+        assert!(!plain["web"].contains_key("traefik.http.routers.app-example-com-root-http.rule"));
+    }
+
+    #[test]
+    fn test_caddy_site_block_for_routes() {
+        let block = generate_caddy_site_block(&routed_domain(SslMode::Auto)).unwrap();
+        assert!(block.starts_with("app.example.com {\n"));
+        assert!(block.contains("    handle /api* {\n"));
+        assert!(block.contains("        reverse_proxy api:8080 {\n"));
+        assert!(block.contains("                read_timeout 120s\n"));
+        assert!(block.contains("@app_example_com_root_denied not remote_ip 10.0.0.0/8"));
+        assert!(block.contains("            admin $apr1$abc$def\n"));
+        assert!(block.contains("            max_size 20971520\n"));
+        // The catch-all root route is emitted last.
+        assert!(block.find("handle /ws*").unwrap() < block.find("    handle {").unwrap());
+
+        let plain = generate_caddy_site_block(&routed_domain(SslMode::Off)).unwrap();
+        assert!(plain.starts_with("http://app.example.com {\n"));
+        let manual = generate_caddy_site_block(&routed_domain(SslMode::Manual)).unwrap();
+        assert!(manual.contains("    tls /etc/caddy/ssl/cert.pem /etc/caddy/ssl/key.pem\n"));
+    }
+
+    #[test]
+    fn test_caddyfile_global_acme_options() {
+        use crate::cli::config_parser::AcmeConfig;
+        let proxy = ProxyConfig {
+            proxy_type: ProxyType::Caddy,
+            auto_detect: false,
+            domains: vec![routed_domain(SslMode::Auto)],
+            config: None,
+            acme: Some(AcmeConfig {
+                email: Some("ops@example.com".to_string()),
+                staging: true,
+                server: None,
+            }),
+        };
+        let caddyfile = generate_caddyfile(&proxy).unwrap();
+        assert!(caddyfile.contains("{\n    email ops@example.com\n    acme_ca https://acme-staging-v02.api.letsencrypt.org/directory\n}\n"));
+        assert!(caddyfile.contains("app.example.com {"));
+    }
+
     // ── Port parsing tests ──────────────────────────
 
     #[test]
@@ -633,6 +1535,70 @@ mod tests {
         assert!(info.ports.is_empty());
     }
 
+    // ── Runtime selection tests ─────────────────────
+
+    #[test]
+    fn test_runtime_kind_parse() {
+        assert_eq!(RuntimeKind::parse("Podman").unwrap(), RuntimeKind::Podman);
+        assert_eq!(RuntimeKind::parse("nerdctl").unwrap(), RuntimeKind::Nerdctl);
+        assert!(RuntimeKind::parse("lxc").is_err());
+    }
+
+    #[test]
+    fn test_resolve_runtime_kind_prefers_override_then_detection() {
+        let only_podman = |kind: RuntimeKind| kind == RuntimeKind::Podman;
+        assert_eq!(
+            resolve_runtime_kind(Some("nerdctl"), only_podman).unwrap(),
+            RuntimeKind::Nerdctl
+        );
+        assert_eq!(
+            resolve_runtime_kind(None, only_podman).unwrap(),
+            RuntimeKind::Podman
+        );
+        assert_eq!(
+            resolve_runtime_kind(Some(" "), only_podman).unwrap(),
+            RuntimeKind::Podman
+        );
+        assert!(resolve_runtime_kind(Some("bogus"), only_podman).is_err());
+        assert_eq!(
+            resolve_runtime_kind(None, |_| false).unwrap(),
+            RuntimeKind::Docker
+        );
+    }
+
+    #[test]
+    fn test_runtime_compatibility_reports_npm_and_privileged_ports() {
+        let config = StackerConfig::from_str(
+            r#"
+name: podman-app
+app:
+  type: static
+  ports: ["80:8080"]
+proxy:
+  type: nginx-proxy-manager
+  domains:
+    - domain: app.example.com
+      upstream: app:8080
+deploy:
+  target: local
+"#,
+        )
+        .unwrap();
+
+        assert!(runtime_compatibility_issues(&config, RuntimeKind::Docker).is_empty());
+        let codes: Vec<String> = runtime_compatibility_issues(&config, RuntimeKind::Podman)
+            .into_iter()
+            .map(|issue| issue.code)
+            .collect();
+        assert_eq!(codes, vec!["RT001", "RT002"]);
+    }
+
+    #[test]
+    fn test_parse_inspect_output_unwraps_array() {
+        let value = parse_inspect_output(r#"[{"Id":"abc","State":{"Running":true}}]"#).unwrap();
+        assert_eq!(value["Id"], "abc");
+    }
+
     // ── Snapshot-based proxy detection tests ────────
 
     #[test]
@@ -743,6 +1709,7 @@ mod tests {
             domain: "evil.com; location /admin { return 200 'pwned'; }".to_string(),
             ssl: SslMode::Off,
             upstream: "app:3000".to_string(),
+            ..Default::default()
         };
         let result = generate_nginx_server_block(&domain);
         assert!(
@@ -757,6 +1724,7 @@ mod tests {
             domain: "safe.example.com".to_string(),
             ssl: SslMode::Off,
             upstream: "app:3000;\n        add_header X-Injected true".to_string(),
+            ..Default::default()
         };
         let result = generate_nginx_server_block(&domain);
         assert!(
@@ -771,6 +1739,7 @@ mod tests {
             domain: "../../../etc/nginx/evil".to_string(),
             ssl: SslMode::Off,
             upstream: "app:3000".to_string(),
+            ..Default::default()
         }];
         let result = generate_nginx_configs(&domains);
         assert!(result.is_err(), "Domain with slashes must be rejected");
@@ -0,0 +1,159 @@
+//! Release snapshots recorded after each successful remote deploy.
+//!
+//! The Stacker server keeps them as immutable releases; `stacker releases
+//! list` shows them and `stacker rollback <release>` re-applies one. Env
+//! values never leave the machine — only a per-service hash of the resolved
+//! environment is sent, and the stacker.yml snapshot has secret-like values
+//! redacted.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde_yaml::Value;
+use sha2::{Digest, Sha256};
+
+use crate::cli::config_compose::is_secret_like_path;
+use crate::cli::error::CliError;
+use crate::services::release::ReleaseRecord;
+
+const REDACTED: &str = "<redacted>";
+
+/// Snapshot the deployed compose (`compose_dir` resolves `env_file` paths)
+/// and the stacker.yml it was rendered from.
+pub fn build_release_record(
+    compose_content: &str,
+    compose_dir: &Path,
+    stacker_yml: &str,
+) -> Result<ReleaseRecord, CliError> {
+    let doc: Value = serde_yaml::from_str(compose_content)?;
+    let mut env_hashes = BTreeMap::new();
+    let mut image_digests = BTreeMap::new();
+
+    if let Some(services) = doc.get("services").and_then(Value::as_mapping) {
+        for (name, service) in services {
+            let Some(name) = name.as_str() else { continue };
+            env_hashes.insert(name.to_string(), service_env_hash(service, compose_dir));
+            if let Some(image) = service.get("image").and_then(Value::as_str) {
+                image_digests.insert(name.to_string(), image.to_string());
+            }
+        }
+    }
+
+    Ok(ReleaseRecord {
+        compose_content: compose_content.to_string(),
+        env_hashes,
+        image_digests,
+        stacker_yml: redact_stacker_yml(stacker_yml)?,
+        command_id: None,
+    })
+}
+
+/// SHA-256 over a service's `environment` entries (sorted) followed by the
+/// contents of its `env_file`s. Missing env files hash as empty.
+fn service_env_hash(service: &Value, compose_dir: &Path) -> String {
+    let mut entries: Vec<String> = match service.get("environment") {
+        Some(Value::Mapping(map)) => map
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}={}",
+                    key.as_str().unwrap_or_default(),
+                    scalar_to_string(value)
+                )
+            })
+            .collect(),
+        Some(Value::Sequence(items)) => items.iter().map(scalar_to_string).collect(),
+        _ => Vec::new(),
+    };
+    entries.sort();
+
+    let mut hasher = Sha256::new();
+    for entry in &entries {
+        hasher.update(entry.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    let env_files: Vec<&str> = match service.get("env_file") {
+        Some(Value::String(path)) => vec![path.as_str()],
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| {
+                item.as_str()
+                    .or_else(|| item.get("path").and_then(Value::as_str))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    for env_file in env_files {
+        let content = std::fs::read(compose_dir.join(env_file)).unwrap_or_default();
+        hasher.update(&content);
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Replace literal values under secret-looking keys; `${VAR}` placeholders
+/// are kept so the snapshot still shows where a value comes from.
+fn redact_stacker_yml(stacker_yml: &str) -> Result<String, CliError> {
+    let mut doc: Value = serde_yaml::from_str(stacker_yml)?;
+    redact_value(&mut doc);
+    Ok(serde_yaml::to_string(&doc)?)
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, child) in map.iter_mut() {
+                let secret = key.as_str().map(is_secret_like_path).unwrap_or(false);
+                match child {
+                    Value::String(s) if secret && !s.is_empty() && !s.starts_with("${") => {
+                        *s = REDACTED.to_string();
+                    }
+                    Value::Number(_) | Value::Bool(_) if secret => {
+                        *child = Value::String(REDACTED.to_string());
+                    }
+                    _ => redact_value(child),
+                }
+            }
+        }
+        Value::Sequence(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_record_hashes_env_and_redacts_secrets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("web.env"), "SESSION_KEY=abc\n").unwrap();
+        let compose = "services:\n  web:\n    image: ghcr.io/acme/web@sha256:1111\n    env_file: web.env\n    environment:\n      DB_PASSWORD: hunter2\n  db:\n    image: postgres:16\n";
+        let stacker_yml = "name: shop\ndeploy:\n  registry:\n    username: acme\n    password: hunter2\nenv:\n  API_TOKEN: ${API_TOKEN}\n";
+
+        let record = build_release_record(compose, dir.path(), stacker_yml).unwrap();
+        assert_eq!(record.image_digests["web"], "ghcr.io/acme/web@sha256:1111");
+        assert_eq!(record.image_digests["db"], "postgres:16");
+        assert_eq!(record.env_hashes.len(), 2);
+        assert!(!record.stacker_yml.contains("hunter2"));
+        assert!(record.stacker_yml.contains("password: <redacted>"));
+        assert!(record.stacker_yml.contains("${API_TOKEN}"));
+
+        std::fs::write(dir.path().join("web.env"), "SESSION_KEY=def\n").unwrap();
+        let changed = build_release_record(compose, dir.path(), stacker_yml).unwrap();
+        assert_ne!(changed.env_hashes["web"], record.env_hashes["web"]);
+        assert_eq!(changed.env_hashes["db"], record.env_hashes["db"]);
+    }
+}
@@ -117,6 +117,7 @@ impl ServiceCatalog {
                                 depends_on: Vec::new(),
                                 command: None,
                                 healthcheck: None,
+                                placement: None,
                             };
 
                             return Ok(Some(CatalogEntry {
@@ -219,6 +220,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec!["redis".into()],
         },
@@ -241,6 +243,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec!["redis".into(), "phpmyadmin".into()],
         },
@@ -261,6 +264,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -279,6 +283,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -296,6 +301,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -317,6 +323,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -338,6 +345,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -355,6 +363,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -375,6 +384,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -398,6 +408,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec!["mysql".into()],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec!["mysql".into(), "redis".into(), "traefik".into()],
         },
@@ -420,6 +431,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec!["kibana".into()],
         },
@@ -440,6 +452,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec!["elasticsearch".into()],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec!["elasticsearch".into()],
         },
@@ -458,6 +471,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -476,6 +490,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -497,6 +512,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec!["mysql".into()],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec!["mysql".into()],
         },
@@ -521,6 +537,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -538,6 +555,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -559,6 +577,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -580,6 +599,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -601,6 +621,7 @@ fn build_hardcoded_catalog() -> Vec<CatalogEntry> {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             },
             related: vec![],
         },
@@ -7,7 +7,7 @@
 use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_yaml::{Mapping, Value};
 
 use crate::cli::config_parser::ServiceDefinition;
@@ -40,6 +40,9 @@ pub struct ServiceImportReview {
     pub services: Vec<ImportedServiceReview>,
     pub risks: Vec<ImportRisk>,
     pub guidance: Vec<String>,
+    /// Differences against the previous import of the same name, if any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub upstream_changes: Vec<UpstreamChange>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -68,6 +71,44 @@ pub struct ServiceImportPlan {
     pub services: Vec<ServiceDefinition>,
 }
 
+/// Where an import came from, recorded next to stacker.yml so a later
+/// re-import can report what changed upstream.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSourceKind {
+    Compose,
+    Github,
+    Url,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProvenance {
+    pub import_name: String,
+    pub source: ImportSourceKind,
+    /// Local path, `owner/repo` or URL as given on the command line.
+    pub location: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_url: Option<String>,
+    pub sha256: String,
+    pub imported_at: String,
+    /// Sanitized services as reviewed at import time (secrets already
+    /// replaced by `${VAR}` placeholders).
+    pub services: Vec<ServiceDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct UpstreamChange {
+    pub service: String,
+    pub kind: String,
+    pub detail: String,
+}
+
 pub fn import_plan_from_compose_file(
     compose_path: &Path,
     request: &ComposeImportRequest,
@@ -162,6 +203,7 @@ pub fn import_plan_from_compose_str(
             depends_on: depends_on.clone(),
             command: None,
             healthcheck: None,
+            placement: None,
         });
         reviews.push(ImportedServiceReview {
             source_name: source_name.to_string(),
@@ -199,11 +241,127 @@ pub fn import_plan_from_compose_str(
             services: reviews,
             risks,
             guidance,
+            upstream_changes: Vec::new(),
         },
         services,
     })
 }
 
+/// Compare a fresh plan against the services recorded by a previous import.
+/// Services are matched on their destination name.
+pub fn upstream_changes(
+    previous: &ImportProvenance,
+    plan: &ServiceImportPlan,
+) -> Vec<UpstreamChange> {
+    let mut changes = Vec::new();
+
+    for service in &plan.services {
+        let Some(before) = previous.services.iter().find(|s| s.name == service.name) else {
+            changes.push(UpstreamChange {
+                service: service.name.clone(),
+                kind: "added".to_string(),
+                detail: format!("New service with image '{}'", service.image),
+            });
+            continue;
+        };
+
+        if before.image != service.image {
+            changes.push(UpstreamChange {
+                service: service.name.clone(),
+                kind: "image".to_string(),
+                detail: format!("{} → {}", before.image, service.image),
+            });
+        }
+        push_list_change(
+            &mut changes,
+            &service.name,
+            "ports",
+            &before.ports,
+            &service.ports,
+        );
+        push_list_change(
+            &mut changes,
+            &service.name,
+            "volumes",
+            &before.volumes,
+            &service.volumes,
+        );
+        push_list_change(
+            &mut changes,
+            &service.name,
+            "depends_on",
+            &before.depends_on,
+            &service.depends_on,
+        );
+        // Values may be secrets: name the variables, never print the values.
+        let mut environment = Vec::new();
+        for (key, value) in &before.environment {
+            match service.environment.get(key) {
+                None => environment.push(format!("-{key}")),
+                Some(after) if after != value => environment.push(format!("~{key}")),
+                Some(_) => {}
+            }
+        }
+        for key in service.environment.keys() {
+            if !before.environment.contains_key(key) {
+                environment.push(format!("+{key}"));
+            }
+        }
+        if !environment.is_empty() {
+            environment.sort_by(|a, b| a[1..].cmp(&b[1..]));
+            changes.push(UpstreamChange {
+                service: service.name.clone(),
+                kind: "environment".to_string(),
+                detail: environment.join(", "),
+            });
+        }
+        if before.command != service.command {
+            changes.push(UpstreamChange {
+                service: service.name.clone(),
+                kind: "command".to_string(),
+                detail: format!(
+                    "{} → {}",
+                    before.command.as_deref().unwrap_or("<image default>"),
+                    service.command.as_deref().unwrap_or("<image default>")
+                ),
+            });
+        }
+    }
+
+    for before in &previous.services {
+        if !plan.services.iter().any(|s| s.name == before.name) {
+            changes.push(UpstreamChange {
+                service: before.name.clone(),
+                kind: "removed".to_string(),
+                detail: "Service no longer present upstream".to_string(),
+            });
+        }
+    }
+
+    changes
+}
+
+fn push_list_change(
+    changes: &mut Vec<UpstreamChange>,
+    service: &str,
+    kind: &str,
+    before: &[String],
+    after: &[String],
+) {
+    let before = before.iter().collect::<BTreeSet<_>>();
+    let after = after.iter().collect::<BTreeSet<_>>();
+    let added = after.difference(&before).map(|v| format!("+{v}"));
+    let removed = before.difference(&after).map(|v| format!("-{v}"));
+    let detail = removed.chain(added).collect::<Vec<_>>();
+    if !detail.is_empty() {
+        changes.push(UpstreamChange {
+            service: service.to_string(),
+            kind: kind.to_string(),
+            detail: detail.join(", "),
+        });
+    }
+}
+
 pub fn parse_renames(values: &[String]) -> Result<Vec<(String, String)>, CliError> {
     values
         .iter()
@@ -649,6 +807,73 @@ services:
         }
     }
 
+    #[test]
+    fn reports_upstream_changes_against_previous_import() {
+        let compose = r#"
+services:
+  mailserver:
+    image: mail:1
+    ports: ["25:25"]
+    environment:
+      HOSTNAME: mail
+"#;
+        let previous_plan = import_plan_from_compose_str(compose, &request()).unwrap();
+        let previous = ImportProvenance {
+            import_name: "smtp".to_string(),
+            source: ImportSourceKind::Github,
+            location: "owner/mail".to_string(),
+            requested_ref: None,
+            resolved_commit: None,
+            compose_path: Some("compose.yml".to_string()),
+            fetched_url: None,
+            sha256: "abc".to_string(),
+            imported_at: "2026-01-01T00:00:00Z".to_string(),
+            services: previous_plan.services,
+        };
+
+        let plan = import_plan_from_compose_str(
+            r#"
+services:
+  mailserver:
+    image: mail:2
+    ports: ["25:25", "587:587"]
+    environment:
+      HOSTNAME: mail
+      RELAY_HOST: relay
+"#,
+            &request(),
+        )
+        .unwrap();
+
+        let changes = upstream_changes(&previous, &plan);
+        let kinds = changes
+            .iter()
+            .map(|change| change.kind.as_str())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(
+            kinds,
+            ["environment", "image", "ports"].into_iter().collect()
+        );
+        assert!(changes
+            .iter()
+            .any(|change| change.detail == "mail:1 → mail:2"));
+
+        let relay_changed = import_plan_from_compose_str(
+            &compose.replace("HOSTNAME: mail", "HOSTNAME: mx"),
+            &request(),
+        )
+        .unwrap();
+        let changes = upstream_changes(&previous, &relay_changed);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, "environment");
+        assert_eq!(changes[0].detail, "~HOSTNAME");
+        assert!(upstream_changes(
+            &previous,
+            &import_plan_from_compose_str(compose, &request()).unwrap()
+        )
+        .is_empty());
+    }
+
     #[test]
     fn redacts_secret_like_environment_values_in_review() {
         let plan = import_plan_from_compose_str(
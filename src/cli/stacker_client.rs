@@ -12,6 +12,16 @@ use crate::cli::config_parser::DeployTarget;
 use crate::cli::debug::cli_debug_enabled;
 use crate::cli::error::CliError;
 use crate::handoff::{DeploymentHandoffPayload, DeploymentHandoffResolveRequest};
+use crate::models::{
+    CommandSchedule, ContainerLogLine, ContainerMetricSample, DeploymentLease, DeploymentRelease,
+    PreviewEnvironment,
+};
+use crate::services::agent_compatibility::AgentCompatibility;
+use crate::services::backup::BackupRecord;
+use crate::services::command_schedule::ScheduleRequest;
+use crate::services::deployment_lease::LeaseRequest;
+use crate::services::preview::PreviewRequest;
+use crate::services::release::{ReleaseRecord, ReleaseRollbackOutcome};
 use crate::services::{
     DeployPlan, DeployPlanOperation, DeploymentEventFeed, DeploymentState, TypedErrorEnvelope,
 };
@@ -2222,20 +2232,23 @@ impl StackerClient {
         Ok(api.item)
     }
 
-    /// Fetch the latest deployment status for a project.
-    /// Returns `GET /api/v1/deployments/project/{project_id}`.
-    pub async fn get_deployment_status_by_project(
+    /// Acquire or renew the deploy lease of a deployment.
+    /// `POST /api/v1/deployments/{hash}/lease`; a lease held by another
+    /// client fails with a `deployment_locked` typed error.
+    pub async fn acquire_deployment_lease(
         &self,
-        project_id: i32,
-    ) -> Result<Option<DeploymentStatusInfo>, CliError> {
+        deployment_hash: &str,
+        request: &LeaseRequest,
+    ) -> Result<DeploymentLease, CliError> {
         let url = format!(
-            "{}/api/v1/deployments/project/{}",
-            self.base_url, project_id
+            "{}/api/v1/deployments/{}/lease",
+            self.base_url, deployment_hash
         );
         let resp = self
             .http
-            .get(&url)
+            .post(&url)
             .bearer_auth(&self.token)
+            .json(request)
             .send()
             .await
             .map_err(|e| CliError::DeployFailed {
@@ -2243,304 +2256,1055 @@ impl StackerClient {
                 reason: format!("Stacker server unreachable: {}", e),
             })?;
 
-        if resp.status().as_u16() == 404 {
-            return Ok(None);
-        }
-
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
             return Err(CliError::DeployFailed {
                 target: self.target.clone(),
                 reason: stacker_api_failure(
-                    &format!("GET /api/v1/deployments/project/{project_id}"),
+                    &format!("POST /api/v1/deployments/{deployment_hash}/lease"),
                     status,
                     &body,
                 ),
             });
         }
 
-        let api: ApiResponse<DeploymentStatusInfo> =
+        let api: ApiResponse<DeploymentLease> =
             resp.json().await.map_err(|e| CliError::DeployFailed {
                 target: self.target.clone(),
                 reason: format!("Invalid response from Stacker server: {}", e),
             })?;
 
-        Ok(api.item)
+        api.item.ok_or_else(|| CliError::DeployFailed {
+            target: self.target.clone(),
+            reason: "Stacker server returned no lease".to_string(),
+        })
     }
 
-    /// Force-complete a stuck deployment (paused or error → completed).
-    /// `POST /api/v1/deployments/{id}/force-complete`
-    /// Fetch a deployment by its hash string.
-    /// `GET /api/v1/deployments/hash/{hash}`
-    pub async fn get_deployment_by_hash(
+    /// Release a deploy lease.
+    /// `DELETE /api/v1/deployments/{hash}/lease/{lease_id}`.
+    pub async fn release_deployment_lease(
         &self,
-        hash: &str,
-    ) -> Result<Option<DeploymentStatusInfo>, CliError> {
-        let url = format!("{}/api/v1/deployments/hash/{}", self.base_url, hash);
+        deployment_hash: &str,
+        lease_id: &str,
+    ) -> Result<(), CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/lease/{}",
+            self.base_url, deployment_hash, lease_id
+        );
         let resp = self
             .http
-            .get(&url)
+            .delete(&url)
             .bearer_auth(&self.token)
             .send()
             .await
             .map_err(|e| CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
+                target: self.target.clone(),
                 reason: format!("Stacker server unreachable: {}", e),
             })?;
 
-        if resp.status().as_u16() == 404 {
-            return Ok(None);
-        }
-
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let body = resp.text().await.unwrap_or_default();
             return Err(CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
+                target: self.target.clone(),
                 reason: stacker_api_failure(
-                    &format!("GET /api/v1/deployments/hash/{hash}"),
+                    &format!("DELETE /api/v1/deployments/{deployment_hash}/lease/{lease_id}"),
                     status,
                     &body,
                 ),
             });
         }
 
-        let api: ApiResponse<DeploymentStatusInfo> =
-            resp.json().await.map_err(|e| CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
-                reason: format!("Invalid response from Stacker server: {}", e),
-            })?;
-
-        Ok(api.item)
+        Ok(())
     }
 
-    pub async fn force_complete_deployment(
+    /// Register a branch preview, or extend the expiry of an existing one.
+    /// `POST /api/v1/deployments/{hash}/previews`.
+    pub async fn register_preview(
         &self,
-        deployment_id: i32,
-        force: bool,
-    ) -> Result<DeploymentStatusInfo, CliError> {
-        let url = if force {
-            format!(
-                "{}/api/v1/deployments/{}/force-complete?force=true",
-                self.base_url, deployment_id
-            )
-        } else {
-            format!(
-                "{}/api/v1/deployments/{}/force-complete",
-                self.base_url, deployment_id
-            )
-        };
+        deployment_hash: &str,
+        request: &PreviewRequest,
+    ) -> Result<PreviewEnvironment, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/previews",
+            self.base_url, deployment_hash
+        );
         let resp = self
             .http
             .post(&url)
             .bearer_auth(&self.token)
+            .json(request)
             .send()
             .await
             .map_err(|e| CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
+                target: self.target.clone(),
                 reason: format!("Stacker server unreachable: {}", e),
             })?;
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
             return Err(CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
-                reason: stacker_api_failure_with_message(
-                    "Force-complete failed",
-                    &format!("POST /api/v1/deployments/{deployment_id}/force-complete"),
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("POST /api/v1/deployments/{deployment_hash}/previews"),
                     status,
                     &body,
-                    cli_debug_enabled(),
                 ),
             });
         }
 
-        let api: ApiResponse<DeploymentStatusInfo> =
+        let api: ApiResponse<PreviewEnvironment> =
             resp.json().await.map_err(|e| CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
+                target: self.target.clone(),
                 reason: format!("Invalid response from Stacker server: {}", e),
             })?;
 
         api.item.ok_or_else(|| CliError::DeployFailed {
-            target: crate::cli::config_parser::DeployTarget::Cloud,
-            reason: "No deployment returned in force-complete response".to_string(),
+            target: self.target.clone(),
+            reason: "Stacker server returned no preview".to_string(),
         })
     }
 
-    // ── Agent commands ───────────────────────────────
-
-    /// Enqueue a command for the Status Panel agent on a deployment.
-    ///
-    /// `POST /api/v1/agent/commands/enqueue`
-    /// Link an agent to a deployment using the CLI's stored OAuth token.
-    ///
-    /// `POST /api/v1/agent/link`
-    ///
-    /// Returns `(agent_id, agent_token, deployment_hash)` — the credentials the
-    /// status-panel binary needs in its `.env` to start polling for commands.
-    pub async fn agent_link(
+    /// Active previews of a deployment.
+    /// `GET /api/v1/deployments/{hash}/previews`.
+    pub async fn list_previews(
         &self,
         deployment_hash: &str,
-        server_fingerprint: serde_json::Value,
-    ) -> Result<(String, String, String), CliError> {
-        let url = format!("{}/api/v1/agent/link", self.base_url);
-
-        let body = serde_json::json!({
-            "session_token": self.token,
-            "deployment_id": deployment_hash,
-            "server_fingerprint": server_fingerprint,
-            "capabilities": ["status_panel", "compose_agent"],
-        });
-
+    ) -> Result<Vec<PreviewEnvironment>, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/previews",
+            self.base_url, deployment_hash
+        );
         let resp = self
             .http
-            .post(&url)
+            .get(&url)
             .bearer_auth(&self.token)
-            .json(&body)
             .send()
             .await
-            .map_err(|e| {
-                CliError::ConfigValidation(format!("Stacker server unreachable: {}", e))
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
             })?;
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let body = resp.text().await.unwrap_or_default();
-            return Err(CliError::ConfigValidation(
-                stacker_api_failure_with_message(
-                    "Agent link failed",
-                    "POST /api/v1/agent/link",
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/{deployment_hash}/previews"),
                     status,
                     &body,
-                    cli_debug_enabled(),
                 ),
-            ));
-        }
-
-        #[derive(serde::Deserialize)]
-        struct LinkResp {
-            agent_id: String,
-            agent_token: String,
-            deployment_hash: String,
+            });
         }
 
-        let link: LinkResp = resp
-            .json()
-            .await
-            .map_err(|e| CliError::ConfigValidation(format!("Invalid link response: {}", e)))?;
+        let api: ApiResponse<PreviewEnvironment> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
 
-        Ok((link.agent_id, link.agent_token, link.deployment_hash))
+        Ok(api.list.unwrap_or_default())
     }
 
-    pub async fn agent_enqueue(
-        &self,
-        request: &AgentEnqueueRequest,
-    ) -> Result<AgentCommandInfo, CliError> {
-        let url = format!("{}/api/v1/agent/commands/enqueue", self.base_url);
+    /// `GET /api/v1/deployments/{hash}/backups`.
+    pub async fn list_backups(&self, deployment_hash: &str) -> Result<Vec<BackupRecord>, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/backups",
+            self.base_url, deployment_hash
+        );
         let resp = self
             .http
-            .post(&url)
+            .get(&url)
             .bearer_auth(&self.token)
-            .json(request)
             .send()
             .await
             .map_err(|e| CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
+                target: self.target.clone(),
                 reason: format!("Stacker server unreachable: {}", e),
             })?;
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let body = resp.text().await.unwrap_or_default();
-            return Err(CliError::AgentCommandFailed {
-                command_id: String::new(),
-                error: stacker_api_failure_with_message(
-                    "Enqueue failed",
-                    "POST /api/v1/agent/commands/enqueue",
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/{deployment_hash}/backups"),
                     status,
                     &body,
-                    cli_debug_enabled(),
                 ),
             });
         }
 
-        let api: ApiResponse<AgentCommandInfo> =
-            resp.json()
-                .await
-                .map_err(|e| CliError::AgentCommandFailed {
-                    command_id: String::new(),
-                    error: format!("Invalid enqueue response: {}", e),
-                })?;
+        let api: ApiResponse<BackupRecord> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
 
-        api.item.ok_or_else(|| CliError::AgentCommandFailed {
-            command_id: String::new(),
-            error: "Empty enqueue response from server".to_string(),
-        })
+        Ok(api.list.unwrap_or_default())
     }
 
-    /// Get the status/result of a previously enqueued agent command.
-    ///
-    /// `GET /api/v1/commands/{deployment_hash}/{command_id}`
-    pub async fn agent_command_status(
+    /// `GET /api/v1/deployments/{hash}/metrics?since=<window>&app=<code>`.
+    pub async fn container_metrics(
         &self,
         deployment_hash: &str,
-        command_id: &str,
-    ) -> Result<AgentCommandInfo, CliError> {
+        since: &str,
+        app_code: Option<&str>,
+    ) -> Result<Vec<ContainerMetricSample>, CliError> {
         let url = format!(
-            "{}/api/v1/commands/{}/{}",
-            self.base_url, deployment_hash, command_id
+            "{}/api/v1/deployments/{}/metrics",
+            self.base_url, deployment_hash
         );
-        let resp = self
+        let mut req = self
             .http
             .get(&url)
             .bearer_auth(&self.token)
-            .send()
-            .await
-            .map_err(|e| CliError::DeployFailed {
-                target: crate::cli::config_parser::DeployTarget::Cloud,
-                reason: format!("Stacker server unreachable: {}", e),
-            })?;
-
-        if resp.status().as_u16() == 404 {
-            return Err(CliError::AgentCommandFailed {
-                command_id: command_id.to_string(),
-                error: "Command not found".to_string(),
-            });
+            .query(&[("since", since)]);
+        if let Some(app_code) = app_code {
+            req = req.query(&[("app", app_code)]);
         }
+        let resp = req.send().await.map_err(|e| CliError::DeployFailed {
+            target: self.target.clone(),
+            reason: format!("Stacker server unreachable: {}", e),
+        })?;
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let body = resp.text().await.unwrap_or_default();
-            return Err(CliError::AgentCommandFailed {
-                command_id: command_id.to_string(),
-                error: stacker_api_failure_with_message(
-                    "Status check failed",
-                    &format!("GET /api/v1/commands/{deployment_hash}/{command_id}"),
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/{deployment_hash}/metrics"),
                     status,
                     &body,
-                    cli_debug_enabled(),
                 ),
             });
         }
 
-        let api: ApiResponse<AgentCommandInfo> =
-            resp.json()
-                .await
-                .map_err(|e| CliError::AgentCommandFailed {
-                    command_id: command_id.to_string(),
-                    error: format!("Invalid status response: {}", e),
-                })?;
+        let api: ApiResponse<ContainerMetricSample> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
 
-        api.item.ok_or_else(|| CliError::AgentCommandFailed {
-            command_id: command_id.to_string(),
-            error: "Empty status response".to_string(),
-        })
+        Ok(api.list.unwrap_or_default())
     }
 
-    /// Enqueue a command and poll until it completes or times out.
-    ///
+    /// `GET /api/v1/deployments/{hash}/logs?search=&since=&service=&limit=`.
+    pub async fn search_logs(
+        &self,
+        deployment_hash: &str,
+        search: Option<&str>,
+        since: &str,
+        service: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<ContainerLogLine>, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/logs",
+            self.base_url, deployment_hash
+        );
+        let mut req = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.token)
+            .query(&[("since", since)]);
+        if let Some(search) = search {
+            req = req.query(&[("search", search)]);
+        }
+        if let Some(service) = service {
+            req = req.query(&[("service", service)]);
+        }
+        if let Some(limit) = limit {
+            req = req.query(&[("limit", limit)]);
+        }
+        let resp = req.send().await.map_err(|e| CliError::DeployFailed {
+            target: self.target.clone(),
+            reason: format!("Stacker server unreachable: {}", e),
+        })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/{deployment_hash}/logs"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<ContainerLogLine> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        Ok(api.list.unwrap_or_default())
+    }
+
+    /// Open an interactive shell session WebSocket.
+    /// `GET /api/v1/deployments/{hash}/shell?app=<code>&cols=&rows=`.
+    pub async fn open_shell(
+        &self,
+        deployment_hash: &str,
+        session: &ShellSessionRequest<'_>,
+    ) -> Result<ShellSocket, CliError> {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let mut params = vec![
+            ("app", session.app_code.to_string()),
+            ("cols", session.cols.to_string()),
+            ("rows", session.rows.to_string()),
+        ];
+        if let Some(shell) = session.shell {
+            params.push(("shell", shell.to_string()));
+        }
+        if let Some(idle_timeout) = session.idle_timeout {
+            params.push(("idle_timeout", idle_timeout.to_string()));
+        }
+        let mut url = reqwest::Url::parse_with_params(
+            &format!(
+                "{}/api/v1/deployments/{}/shell",
+                self.base_url, deployment_hash
+            ),
+            &params,
+        )
+        .map_err(|e| CliError::ConfigValidation(format!("Invalid Stacker server URL: {}", e)))?;
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        let _ = url.set_scheme(scheme);
+
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid shell session URL: {}", e)))?;
+        let bearer = format!("Bearer {}", self.token)
+            .parse::<tokio_tungstenite::tungstenite::http::HeaderValue>()
+            .map_err(|_| {
+                CliError::AuthFailed("Access token is not a valid header value".to_string())
+            })?;
+        request.headers_mut().insert("Authorization", bearer);
+
+        match tokio_tungstenite::connect_async(request).await {
+            Ok((socket, _)) => Ok(socket),
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                let status = response.status().as_u16();
+                let body = response
+                    .body()
+                    .as_deref()
+                    .map(String::from_utf8_lossy)
+                    .unwrap_or_default()
+                    .to_string();
+                if let Some(error) = parse_typed_error_response(&body) {
+                    return Err(error.into());
+                }
+                Err(CliError::DeployFailed {
+                    target: self.target.clone(),
+                    reason: stacker_api_failure(
+                        &format!("GET /api/v1/deployments/{deployment_hash}/shell"),
+                        status,
+                        &body,
+                    ),
+                })
+            }
+            Err(e) => Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            }),
+        }
+    }
+
+    /// Queue teardown of a preview. Returns the queued command ids.
+    /// `DELETE /api/v1/deployments/{hash}/previews/{slug}`.
+    pub async fn delete_preview(
+        &self,
+        deployment_hash: &str,
+        slug: &str,
+    ) -> Result<Vec<String>, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/previews/{}",
+            self.base_url, deployment_hash, slug
+        );
+        let resp = self
+            .http
+            .delete(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("DELETE /api/v1/deployments/{deployment_hash}/previews/{slug}"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<String> = resp.json().await.map_err(|e| CliError::DeployFailed {
+            target: self.target.clone(),
+            reason: format!("Invalid response from Stacker server: {}", e),
+        })?;
+
+        Ok(api.list.unwrap_or_default())
+    }
+
+    /// Queue an agent command on a cron schedule.
+    /// `POST /api/v1/deployments/{hash}/schedules`.
+    pub async fn create_command_schedule(
+        &self,
+        deployment_hash: &str,
+        request: &ScheduleRequest,
+    ) -> Result<CommandSchedule, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/schedules",
+            self.base_url, deployment_hash
+        );
+        let resp = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("POST /api/v1/deployments/{deployment_hash}/schedules"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<CommandSchedule> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        api.item.ok_or_else(|| CliError::DeployFailed {
+            target: self.target.clone(),
+            reason: "Stacker server returned no schedule".to_string(),
+        })
+    }
+
+    /// Command schedules of a deployment, next to run first.
+    /// `GET /api/v1/deployments/{hash}/schedules`.
+    pub async fn list_command_schedules(
+        &self,
+        deployment_hash: &str,
+    ) -> Result<Vec<CommandSchedule>, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/schedules",
+            self.base_url, deployment_hash
+        );
+        let resp = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/{deployment_hash}/schedules"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<CommandSchedule> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        Ok(api.list.unwrap_or_default())
+    }
+
+    /// Delete a command schedule.
+    /// `DELETE /api/v1/deployments/{hash}/schedules/{schedule_id}`.
+    pub async fn delete_command_schedule(
+        &self,
+        deployment_hash: &str,
+        schedule_id: &str,
+    ) -> Result<(), CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/schedules/{}",
+            self.base_url, deployment_hash, schedule_id
+        );
+        let resp = self
+            .http
+            .delete(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!(
+                        "DELETE /api/v1/deployments/{deployment_hash}/schedules/{schedule_id}"
+                    ),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Record a release after a successful deploy.
+    /// `POST /api/v1/deployments/{hash}/releases`; unchanged bundles return
+    /// the existing newest release.
+    pub async fn record_deployment_release(
+        &self,
+        deployment_hash: &str,
+        record: &ReleaseRecord,
+    ) -> Result<DeploymentRelease, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/releases",
+            self.base_url, deployment_hash
+        );
+        let resp = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.token)
+            .json(record)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("POST /api/v1/deployments/{deployment_hash}/releases"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<DeploymentRelease> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        api.item.ok_or_else(|| CliError::DeployFailed {
+            target: self.target.clone(),
+            reason: "Stacker server returned no release".to_string(),
+        })
+    }
+
+    /// List recorded releases of a deployment, newest first.
+    /// `GET /api/v1/deployments/{hash}/releases`.
+    pub async fn list_deployment_releases(
+        &self,
+        deployment_hash: &str,
+    ) -> Result<Vec<DeploymentRelease>, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/releases",
+            self.base_url, deployment_hash
+        );
+        let resp = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/{deployment_hash}/releases"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<DeploymentRelease> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        Ok(api.list.unwrap_or_default())
+    }
+
+    /// Re-apply a recorded release after revalidating the previewed plan.
+    /// `POST /api/v1/deployments/{hash}/releases/{release}/rollback`.
+    pub async fn rollback_deployment_release(
+        &self,
+        deployment_hash: &str,
+        release: &str,
+        target: &str,
+        expected_fingerprint: &str,
+        lease_id: Option<&str>,
+    ) -> Result<ReleaseRollbackOutcome, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/{}/releases/{}/rollback",
+            self.base_url, deployment_hash, release
+        );
+        let body = serde_json::json!({
+            "target": target,
+            "expectedFingerprint": expected_fingerprint,
+            "leaseId": lease_id,
+        });
+        let resp = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!(
+                        "POST /api/v1/deployments/{deployment_hash}/releases/{release}/rollback"
+                    ),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<ReleaseRollbackOutcome> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        api.item.ok_or_else(|| CliError::DeployFailed {
+            target: self.target.clone(),
+            reason: "Stacker server returned no rollback outcome".to_string(),
+        })
+    }
+
+    /// Fetch the latest deployment status for a project.
+    /// Returns `GET /api/v1/deployments/project/{project_id}`.
+    pub async fn get_deployment_status_by_project(
+        &self,
+        project_id: i32,
+    ) -> Result<Option<DeploymentStatusInfo>, CliError> {
+        let url = format!(
+            "{}/api/v1/deployments/project/{}",
+            self.base_url, project_id
+        );
+        let resp = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if resp.status().as_u16() == 404 {
+            return Ok(None);
+        }
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/project/{project_id}"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<DeploymentStatusInfo> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: self.target.clone(),
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        Ok(api.item)
+    }
+
+    /// Force-complete a stuck deployment (paused or error → completed).
+    /// `POST /api/v1/deployments/{id}/force-complete`
+    /// Fetch a deployment by its hash string.
+    /// `GET /api/v1/deployments/hash/{hash}`
+    pub async fn get_deployment_by_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Option<DeploymentStatusInfo>, CliError> {
+        let url = format!("{}/api/v1/deployments/hash/{}", self.base_url, hash);
+        let resp = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if resp.status().as_u16() == 404 {
+            return Ok(None);
+        }
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: stacker_api_failure(
+                    &format!("GET /api/v1/deployments/hash/{hash}"),
+                    status,
+                    &body,
+                ),
+            });
+        }
+
+        let api: ApiResponse<DeploymentStatusInfo> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        Ok(api.item)
+    }
+
+    pub async fn force_complete_deployment(
+        &self,
+        deployment_id: i32,
+        force: bool,
+    ) -> Result<DeploymentStatusInfo, CliError> {
+        let url = if force {
+            format!(
+                "{}/api/v1/deployments/{}/force-complete?force=true",
+                self.base_url, deployment_id
+            )
+        } else {
+            format!(
+                "{}/api/v1/deployments/{}/force-complete",
+                self.base_url, deployment_id
+            )
+        };
+        let resp = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: stacker_api_failure_with_message(
+                    "Force-complete failed",
+                    &format!("POST /api/v1/deployments/{deployment_id}/force-complete"),
+                    status,
+                    &body,
+                    cli_debug_enabled(),
+                ),
+            });
+        }
+
+        let api: ApiResponse<DeploymentStatusInfo> =
+            resp.json().await.map_err(|e| CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: format!("Invalid response from Stacker server: {}", e),
+            })?;
+
+        api.item.ok_or_else(|| CliError::DeployFailed {
+            target: crate::cli::config_parser::DeployTarget::Cloud,
+            reason: "No deployment returned in force-complete response".to_string(),
+        })
+    }
+
+    // ── Agent commands ───────────────────────────────
+
+    /// Enqueue a command for the Status Panel agent on a deployment.
+    ///
+    /// `POST /api/v1/agent/commands/enqueue`
+    /// Link an agent to a deployment using the CLI's stored OAuth token.
+    ///
+    /// `POST /api/v1/agent/link`
+    ///
+    /// Returns `(agent_id, agent_token, deployment_hash)` — the credentials the
+    /// status-panel binary needs in its `.env` to start polling for commands.
+    pub async fn agent_link(
+        &self,
+        deployment_hash: &str,
+        server_fingerprint: serde_json::Value,
+    ) -> Result<(String, String, String), CliError> {
+        let url = format!("{}/api/v1/agent/link", self.base_url);
+
+        let body = serde_json::json!({
+            "session_token": self.token,
+            "deployment_id": deployment_hash,
+            "server_fingerprint": server_fingerprint,
+            "capabilities": ["status_panel", "compose_agent"],
+        });
+
+        let resp = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| {
+                CliError::ConfigValidation(format!("Stacker server unreachable: {}", e))
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(CliError::ConfigValidation(
+                stacker_api_failure_with_message(
+                    "Agent link failed",
+                    "POST /api/v1/agent/link",
+                    status,
+                    &body,
+                    cli_debug_enabled(),
+                ),
+            ));
+        }
+
+        #[derive(serde::Deserialize)]
+        struct LinkResp {
+            agent_id: String,
+            agent_token: String,
+            deployment_hash: String,
+        }
+
+        let link: LinkResp = resp
+            .json()
+            .await
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid link response: {}", e)))?;
+
+        Ok((link.agent_id, link.agent_token, link.deployment_hash))
+    }
+
+    pub async fn agent_enqueue(
+        &self,
+        request: &AgentEnqueueRequest,
+    ) -> Result<AgentCommandInfo, CliError> {
+        let url = format!("{}/api/v1/agent/commands/enqueue", self.base_url);
+        let resp = self
+            .http
+            .post(&url)
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            if let Some(error) = parse_typed_error_response(&body) {
+                return Err(error.into());
+            }
+            return Err(CliError::AgentCommandFailed {
+                command_id: String::new(),
+                error: stacker_api_failure_with_message(
+                    "Enqueue failed",
+                    "POST /api/v1/agent/commands/enqueue",
+                    status,
+                    &body,
+                    cli_debug_enabled(),
+                ),
+            });
+        }
+
+        let api: ApiResponse<AgentCommandInfo> =
+            resp.json()
+                .await
+                .map_err(|e| CliError::AgentCommandFailed {
+                    command_id: String::new(),
+                    error: format!("Invalid enqueue response: {}", e),
+                })?;
+
+        api.item.ok_or_else(|| CliError::AgentCommandFailed {
+            command_id: String::new(),
+            error: "Empty enqueue response from server".to_string(),
+        })
+    }
+
+    /// Get the status/result of a previously enqueued agent command.
+    ///
+    /// `GET /api/v1/commands/{deployment_hash}/{command_id}`
+    pub async fn agent_command_status(
+        &self,
+        deployment_hash: &str,
+        command_id: &str,
+    ) -> Result<AgentCommandInfo, CliError> {
+        let url = format!(
+            "{}/api/v1/commands/{}/{}",
+            self.base_url, deployment_hash, command_id
+        );
+        let resp = self
+            .http
+            .get(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| CliError::DeployFailed {
+                target: crate::cli::config_parser::DeployTarget::Cloud,
+                reason: format!("Stacker server unreachable: {}", e),
+            })?;
+
+        if resp.status().as_u16() == 404 {
+            return Err(CliError::AgentCommandFailed {
+                command_id: command_id.to_string(),
+                error: "Command not found".to_string(),
+            });
+        }
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(CliError::AgentCommandFailed {
+                command_id: command_id.to_string(),
+                error: stacker_api_failure_with_message(
+                    "Status check failed",
+                    &format!("GET /api/v1/commands/{deployment_hash}/{command_id}"),
+                    status,
+                    &body,
+                    cli_debug_enabled(),
+                ),
+            });
+        }
+
+        let api: ApiResponse<AgentCommandInfo> =
+            resp.json()
+                .await
+                .map_err(|e| CliError::AgentCommandFailed {
+                    command_id: command_id.to_string(),
+                    error: format!("Invalid status response: {}", e),
+                })?;
+
+        api.item.ok_or_else(|| CliError::AgentCommandFailed {
+            command_id: command_id.to_string(),
+            error: "Empty status response".to_string(),
+        })
+    }
+
+    /// Enqueue a command and poll until it completes or times out.
+    ///
     /// This is the primary helper for CLI commands that need to wait for
     /// the agent to process a command and return a result.
     pub async fn agent_poll_result(
@@ -3367,6 +4131,21 @@ impl StackerClient {
 // Agent request/response types
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
+/// WebSocket of an interactive shell session opened with
+/// [`StackerClient::open_shell`].
+pub type ShellSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Query of `GET /api/v1/deployments/{hash}/shell`.
+#[derive(Debug, Clone)]
+pub struct ShellSessionRequest<'a> {
+    pub app_code: &'a str,
+    pub shell: Option<&'a str>,
+    pub cols: u16,
+    pub rows: u16,
+    pub idle_timeout: Option<u32>,
+}
+
 /// Request body for `POST /api/v1/agent/commands/enqueue`.
 ///
 /// Mirrors the server's `EnqueueRequest` — kept in sync so CLI payloads
@@ -3381,6 +4160,8 @@ pub struct AgentEnqueueRequest {
     pub parameters: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout_seconds: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lease_id: Option<String>,
 }
 
 impl AgentEnqueueRequest {
@@ -3392,6 +4173,7 @@ impl AgentEnqueueRequest {
             priority: None,
             parameters: None,
             timeout_seconds: None,
+            lease_id: None,
         }
     }
 
@@ -3418,6 +4200,12 @@ impl AgentEnqueueRequest {
         self.timeout_seconds = Some(seconds);
         self
     }
+
+    /// Builder: send the deploy lease this client holds.
+    pub fn with_lease(mut self, lease_id: Option<&str>) -> Self {
+        self.lease_id = lease_id.map(str::to_string);
+        self
+    }
 }
 
 /// Agent command info as returned by the commands API.
@@ -3469,6 +4257,8 @@ pub struct DeploymentCapabilitiesInfo {
     pub capabilities: Vec<String>,
     #[serde(default)]
     pub features: DeploymentCapabilityFeatures,
+    #[serde(default)]
+    pub compatibility: Option<AgentCompatibility>,
 }
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -4669,6 +5459,7 @@ mod tests {
                 auto_detect: true,
                 domains: vec![],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -4692,6 +5483,7 @@ mod tests {
                 auto_detect: true,
                 domains: vec![],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -4797,6 +5589,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let redis_service = ServiceDefinition {
             name: "redis".to_string(),
@@ -4807,6 +5600,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = crate::cli::config_parser::ConfigBuilder::new()
             .name("myproject")
@@ -4817,6 +5611,7 @@ mod tests {
                 auto_detect: true,
                 domains: vec![],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -4845,6 +5640,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let statuspanel_service = ServiceDefinition {
             name: "statuspanel".to_string(),
@@ -4855,6 +5651,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let smtp_service = ServiceDefinition {
             name: "smtp".to_string(),
@@ -4865,6 +5662,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = crate::cli::config_parser::ConfigBuilder::new()
             .name("myproject")
@@ -4876,6 +5674,7 @@ mod tests {
                 auto_detect: false,
                 domains: vec![],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -4920,6 +5719,7 @@ mod tests {
             depends_on: vec![],
             command: None,
             healthcheck: None,
+            placement: None,
         };
         let config = crate::cli::config_parser::ConfigBuilder::new()
             .name("Device API")
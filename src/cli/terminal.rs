@@ -0,0 +1,107 @@
+//! Raw terminal mode and window size for interactive sessions such as
+//! `stacker agent shell`. Uses `stty`, which every supported Unix has, instead
+//! of a terminal library.
+
+use std::io::IsTerminal;
+use std::process::{Command, Stdio};
+
+use crate::cli::error::CliError;
+
+/// Whether stdin and stdout are both attached to a terminal.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Parse `stty size` output (`<rows> <cols>`) into `(cols, rows)`.
+fn parse_size(output: &str) -> Option<(u16, u16)> {
+    let mut parts = output.split_whitespace();
+    let rows: u16 = parts.next()?.parse().ok()?;
+    let cols: u16 = parts.next()?.parse().ok()?;
+    (rows > 0 && cols > 0).then_some((cols, rows))
+}
+
+/// Current terminal size as `(cols, rows)`.
+pub fn size() -> Option<(u16, u16)> {
+    parse_size(&stty(&["size"])?)
+}
+
+/// Puts the terminal in raw mode without echo; restores the previous
+/// settings when dropped, including on error paths.
+pub struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    pub fn enable() -> Result<Self, CliError> {
+        let saved = stty(&["-g"]).ok_or_else(|| {
+            CliError::ConfigValidation("Could not read terminal settings with stty".to_string())
+        })?;
+        stty(&["raw", "-echo"]).ok_or_else(|| {
+            CliError::ConfigValidation("Could not switch the terminal to raw mode".to_string())
+        })?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[self.saved.as_str()]);
+    }
+}
+
+/// Window size changes (`SIGWINCH`); never fires where there is no such signal.
+pub struct ResizeEvents {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl ResizeEvents {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            signal: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::window_change())
+                .ok(),
+        }
+    }
+
+    /// Wait for the next resize and return the new `(cols, rows)`.
+    pub async fn next(&mut self) -> Option<(u16, u16)> {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut() {
+            signal.recv().await?;
+            return size();
+        }
+        std::future::pending().await
+    }
+}
+
+impl Default for ResizeEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stty_size_as_cols_and_rows() {
+        assert_eq!(parse_size("40 120\n"), Some((120, 40)));
+        assert_eq!(parse_size("0 0"), None);
+        assert_eq!(parse_size(""), None);
+    }
+}
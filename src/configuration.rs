@@ -18,6 +18,10 @@ pub struct Settings {
     pub agent_command_poll_timeout_secs: u64,
     #[serde(default = "Settings::default_agent_command_poll_interval_secs")]
     pub agent_command_poll_interval_secs: u64,
+    /// Latest Status Panel agent release; older agents are reported as
+    /// outdated and `stacker agent upgrade` targets it by default.
+    #[serde(default)]
+    pub agent_latest_version: Option<String>,
     #[serde(default = "Settings::default_casbin_reload_enabled")]
     pub casbin_reload_enabled: bool,
     #[serde(default = "Settings::default_casbin_reload_interval_secs")]
@@ -27,6 +31,12 @@ pub struct Settings {
     #[serde(default)]
     pub vault: VaultSettings,
     #[serde(default)]
+    pub agent_identity: AgentIdentitySettings,
+    #[serde(default)]
+    pub log_shipping: LogShippingSettings,
+    #[serde(default)]
+    pub shell: ShellSettings,
+    #[serde(default)]
     pub connectors: ConnectorConfig,
     #[serde(default)]
     pub deployment: DeploymentSettings,
@@ -55,6 +65,7 @@ impl std::fmt::Debug for Settings {
                 "agent_command_poll_interval_secs",
                 &self.agent_command_poll_interval_secs,
             )
+            .field("agent_latest_version", &self.agent_latest_version)
             .field("casbin_reload_enabled", &self.casbin_reload_enabled)
             .field(
                 "casbin_reload_interval_secs",
@@ -62,6 +73,9 @@ impl std::fmt::Debug for Settings {
             )
             .field("amqp", &self.amqp)
             .field("vault", &self.vault)
+            .field("agent_identity", &self.agent_identity)
+            .field("log_shipping", &self.log_shipping)
+            .field("shell", &self.shell)
             .field("connectors", &self.connectors)
             .field("deployment", &self.deployment)
             .field("marketplace_assets", &self.marketplace_assets)
@@ -83,10 +97,14 @@ impl Default for Settings {
             max_clients_number: 10,
             agent_command_poll_timeout_secs: Self::default_agent_command_poll_timeout_secs(),
             agent_command_poll_interval_secs: Self::default_agent_command_poll_interval_secs(),
+            agent_latest_version: None,
             casbin_reload_enabled: Self::default_casbin_reload_enabled(),
             casbin_reload_interval_secs: Self::default_casbin_reload_interval_secs(),
             amqp: AmqpSettings::default(),
             vault: VaultSettings::default(),
+            agent_identity: AgentIdentitySettings::default(),
+            log_shipping: LogShippingSettings::default(),
+            shell: ShellSettings::default(),
             connectors: ConnectorConfig::default(),
             deployment: DeploymentSettings::default(),
             marketplace_assets: MarketplaceAssetSettings::default(),
@@ -188,6 +206,257 @@ impl Default for AmqpSettings {
     }
 }
 
+/// How agents prove their identity with a client certificate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentMtlsMode {
+    /// Bearer tokens only.
+    #[default]
+    Off,
+    /// A verified certificate authenticates the agent; tokens still work.
+    Optional,
+    /// Every agent request needs a verified certificate.
+    Required,
+}
+
+impl AgentMtlsMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "optional" => Some(Self::Optional),
+            "required" => Some(Self::Required),
+            _ => None,
+        }
+    }
+}
+
+/// Agent token rotation and mTLS client certificates.
+///
+/// Client certificates are signed by the Vault PKI engine at
+/// `{vault.address}/{vault.api_prefix}/{pki_mount}/sign/{pki_role}`. TLS is
+/// terminated by the reverse proxy, which verifies the certificate against
+/// the PKI CA and forwards it in `client_cert_header` with the result in
+/// `client_verify_header`. Both headers are only honoured on connections
+/// from `trusted_proxies`, and the proxy must drop them from clients.
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct AgentIdentitySettings {
+    /// Offer agents a new token over the poll channel this often; 0 disables.
+    #[serde(default = "AgentIdentitySettings::default_token_rotation_hours")]
+    pub token_rotation_hours: u64,
+    /// How long a replaced token keeps working, and how long an offered
+    /// token waits for the agent to switch.
+    #[serde(default = "AgentIdentitySettings::default_token_overlap_secs")]
+    pub token_overlap_secs: u64,
+    #[serde(default)]
+    pub mtls_mode: AgentMtlsMode,
+    #[serde(default = "AgentIdentitySettings::default_pki_mount")]
+    pub pki_mount: String,
+    #[serde(default = "AgentIdentitySettings::default_pki_role")]
+    pub pki_role: String,
+    #[serde(default = "AgentIdentitySettings::default_certificate_ttl_hours")]
+    pub certificate_ttl_hours: u64,
+    #[serde(default = "AgentIdentitySettings::default_client_cert_header")]
+    pub client_cert_header: String,
+    #[serde(default = "AgentIdentitySettings::default_client_verify_header")]
+    pub client_verify_header: String,
+    /// Addresses or CIDR ranges of the TLS-terminating proxies, e.g.
+    /// `10.0.0.5` or `172.18.0.0/16`. Empty means no certificate is trusted.
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+}
+
+impl Default for AgentIdentitySettings {
+    fn default() -> Self {
+        Self {
+            token_rotation_hours: Self::default_token_rotation_hours(),
+            token_overlap_secs: Self::default_token_overlap_secs(),
+            mtls_mode: AgentMtlsMode::default(),
+            pki_mount: Self::default_pki_mount(),
+            pki_role: Self::default_pki_role(),
+            certificate_ttl_hours: Self::default_certificate_ttl_hours(),
+            client_cert_header: Self::default_client_cert_header(),
+            client_verify_header: Self::default_client_verify_header(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl AgentIdentitySettings {
+    fn default_token_rotation_hours() -> u64 {
+        720
+    }
+
+    fn default_token_overlap_secs() -> u64 {
+        3600
+    }
+
+    fn default_pki_mount() -> String {
+        "pki".to_string()
+    }
+
+    fn default_pki_role() -> String {
+        "status-panel-agent".to_string()
+    }
+
+    fn default_certificate_ttl_hours() -> u64 {
+        24
+    }
+
+    fn default_client_cert_header() -> String {
+        "X-Client-Cert".to_string()
+    }
+
+    fn default_client_verify_header() -> String {
+        "X-Client-Verify".to_string()
+    }
+
+    pub fn overlay_env(mut self) -> Self {
+        if let Some(hours) = std::env::var("STACKER_AGENT_TOKEN_ROTATION_HOURS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+        {
+            self.token_rotation_hours = hours;
+        }
+        if let Some(mode) = std::env::var("STACKER_AGENT_MTLS_MODE")
+            .ok()
+            .and_then(|value| AgentMtlsMode::from_name(&value))
+        {
+            self.mtls_mode = mode;
+        }
+        if let Ok(proxies) = std::env::var("STACKER_AGENT_TRUSTED_PROXIES") {
+            self.trusted_proxies = proxies
+                .split(',')
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        self
+    }
+
+    /// Whether `peer` is one of `trusted_proxies`.
+    pub fn is_trusted_proxy(&self, peer: std::net::IpAddr) -> bool {
+        self.trusted_proxies
+            .iter()
+            .any(|proxy| ip_in_range(peer, proxy))
+    }
+}
+
+/// Where container logs shipped by agents are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogShippingBackend {
+    /// The day-partitioned `container_logs` table with a full-text index.
+    #[default]
+    Postgres,
+    /// A Grafana Loki instance, pushed to and queried over its HTTP API.
+    Loki,
+}
+
+impl LogShippingBackend {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "postgres" => Some(Self::Postgres),
+            "loki" => Some(Self::Loki),
+            _ => None,
+        }
+    }
+}
+
+/// Retained container logs pushed by agents to `POST /api/v1/agent/logs`.
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct LogShippingSettings {
+    #[serde(default)]
+    pub backend: LogShippingBackend,
+    /// Days of logs kept in Postgres, and the longest search window.
+    #[serde(default = "LogShippingSettings::default_retention_days")]
+    pub retention_days: i64,
+    /// Loki base URL, e.g. `http://loki:3100`.
+    #[serde(default)]
+    pub loki_url: Option<String>,
+    /// Sent as `X-Scope-OrgID` to multi-tenant Loki.
+    #[serde(default)]
+    pub loki_tenant: Option<String>,
+}
+
+impl Default for LogShippingSettings {
+    fn default() -> Self {
+        Self {
+            backend: LogShippingBackend::default(),
+            retention_days: Self::default_retention_days(),
+            loki_url: None,
+            loki_tenant: None,
+        }
+    }
+}
+
+impl LogShippingSettings {
+    fn default_retention_days() -> i64 {
+        14
+    }
+
+    pub fn overlay_env(mut self) -> Self {
+        if let Some(backend) = std::env::var("STACKER_LOG_SHIPPING_BACKEND")
+            .ok()
+            .and_then(|value| LogShippingBackend::from_name(&value))
+        {
+            self.backend = backend;
+        }
+        if let Some(days) = std::env::var("STACKER_LOG_RETENTION_DAYS")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+        {
+            self.retention_days = days;
+        }
+        if let Some(url) = std::env::var("STACKER_LOKI_URL")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+        {
+            self.loki_url = Some(url.trim().to_string());
+        }
+        self
+    }
+}
+
+/// Interactive shell sessions relayed to agents.
+#[derive(Debug, Default, serde::Deserialize, Clone)]
+pub struct ShellSettings {
+    /// Keep typed input in the `shell_closed` audit event. Off by default,
+    /// since passwords typed into a shell would be stored with it.
+    #[serde(default)]
+    pub record_input: bool,
+}
+
+/// Whether `ip` is the address `range` or inside the CIDR `range`.
+fn ip_in_range(ip: std::net::IpAddr, range: &str) -> bool {
+    use std::net::IpAddr;
+
+    let (network, prefix) = match range.trim().split_once('/') {
+        Some((network, prefix)) => (network, prefix.parse::<u32>().ok()),
+        None => (range.trim(), None),
+    };
+    let Ok(network) = network.parse::<IpAddr>() else {
+        return false;
+    };
+    let (ip, network, bits) = match (ip.to_canonical(), network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            (u32::from(ip) as u128, u32::from(network) as u128, 32)
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => (u128::from(ip), u128::from(network), 128),
+        _ => return false,
+    };
+    let prefix = match prefix {
+        Some(prefix) if prefix <= bits => prefix,
+        Some(_) => return false,
+        None => bits,
+    };
+    if prefix == 0 {
+        return true;
+    }
+    let shift = bits - prefix;
+    (ip >> shift) == (network >> shift)
+}
+
 /// Deployment-related settings for app configuration paths
 #[derive(Debug, serde::Deserialize, Clone)]
 pub struct DeploymentSettings {
@@ -611,6 +880,8 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
     // Overlay Vault settings with environment variables if present
     config.vault = config.vault.overlay_env();
     config.payouts = config.payouts.overlay_env();
+    config.agent_identity = config.agent_identity.overlay_env();
+    config.log_shipping = config.log_shipping.overlay_env();
 
     if let Ok(timeout) = std::env::var("STACKER_AGENT_POLL_TIMEOUT_SECS") {
         if let Ok(parsed) = timeout.parse::<u64>() {
@@ -624,6 +895,11 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
         }
     }
 
+    if let Ok(version) = std::env::var("STACKER_AGENT_LATEST_VERSION") {
+        let version = version.trim();
+        config.agent_latest_version = (!version.is_empty()).then(|| version.to_string());
+    }
+
     if let Ok(timeout) = std::env::var("STACKER_AUTH_REQUEST_TIMEOUT_SECS") {
         if let Ok(parsed) = timeout.parse::<u64>() {
             config.auth_request_timeout_secs = parsed;
@@ -681,6 +957,20 @@ mod tests {
         assert!(parse_bool_env("TRUE"));
     }
 
+    #[test]
+    fn test_trusted_proxies_match_addresses_and_cidr_ranges() {
+        let identity = AgentIdentitySettings {
+            trusted_proxies: vec!["10.0.0.5".to_string(), "172.18.0.0/16".to_string()],
+            ..Default::default()
+        };
+        assert!(identity.is_trusted_proxy("10.0.0.5".parse().unwrap()));
+        assert!(identity.is_trusted_proxy("172.18.3.4".parse().unwrap()));
+        assert!(identity.is_trusted_proxy("::ffff:10.0.0.5".parse().unwrap()));
+        assert!(!identity.is_trusted_proxy("10.0.0.6".parse().unwrap()));
+        assert!(!identity.is_trusted_proxy("172.19.0.1".parse().unwrap()));
+        assert!(!AgentIdentitySettings::default().is_trusted_proxy("127.0.0.1".parse().unwrap()));
+    }
+
     #[test]
     fn test_parse_bool_env_false_values() {
         assert!(!parse_bool_env("0"));
@@ -18,8 +18,27 @@ use crate::cli::generator::compose::ComposeDefinition;
 use crate::cli::install_runner::resolve_docker_registry_credentials;
 use crate::cli::progress;
 use crate::cli::runtime::CliRuntime;
-use crate::cli::stacker_client::{AgentCommandInfo, AgentEnqueueRequest};
+use crate::cli::stacker_client::{
+    AgentCommandInfo, AgentEnqueueRequest, ShellSessionRequest, ShellSocket,
+};
+use crate::cli::terminal;
 use crate::console::commands::CallableTrait;
+use crate::forms::status_panel::{
+    decode_file_chunk, DownloadFileCommandReport, DownloadFileCommandRequest, FileTransferStatus,
+    StatsCommandReport, StatsCommandRequest, StatusPanelCommandError, UpgradeAgentCommandRequest,
+    UploadFileCommandReport, UploadFileCommandRequest, MAX_FILE_TRANSFER_BYTES,
+};
+use crate::helpers::cron::CronSchedule;
+use crate::models::MissedRunPolicy;
+use crate::services::agent_compatibility::{AgentCompatibility, UPGRADE_AGENT_COMMAND_TYPE};
+use crate::services::command_schedule::ScheduleRequest;
+use crate::services::container_metrics::{parse_window, summarize};
+use crate::services::file_transfer::{
+    download_transfer_id, parse_container_path, sha256_hex, upload_transfer_id,
+    DEFAULT_CHUNK_BYTES, DOWNLOAD_FILE_COMMAND_TYPE, UPLOAD_FILE_COMMAND_TYPE,
+};
+use crate::services::shell_session::ShellControl;
+use base64::{engine::general_purpose, Engine as _};
 use std::path::{Path, PathBuf};
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -28,6 +47,8 @@ use std::path::{Path, PathBuf};
 
 /// Default poll timeout for agent commands (seconds).
 const DEFAULT_TIMEOUT_SECS: u64 = 60;
+/// The agent pulls its new image and restarts before reporting.
+const UPGRADE_TIMEOUT_SECS: u64 = 300;
 
 /// Default poll interval (seconds).
 const DEFAULT_POLL_INTERVAL_SECS: u64 = 2;
@@ -145,6 +166,58 @@ pub(crate) fn resolve_registry_auth_for_agent_deploy(
     })
 }
 
+/// Blue/green parameters for `deploy_app` when stacker.yml sets
+/// `deploy.strategy: blue_green`.
+pub(crate) fn resolve_blue_green_for_agent_deploy(
+    project_dir: &Path,
+) -> Option<crate::forms::status_panel::BlueGreenCommandRequest> {
+    let config_path = project_dir.join("stacker.yml");
+    let config = crate::cli::config_parser::StackerConfig::from_file(&config_path).ok()?;
+    if config.deploy.strategy != crate::cli::config_parser::DeployStrategy::BlueGreen {
+        return None;
+    }
+
+    let tuning = config.deploy.blue_green.clone().unwrap_or_default();
+    Some(crate::forms::status_panel::BlueGreenCommandRequest {
+        health_endpoint: config
+            .monitoring
+            .healthcheck
+            .as_ref()
+            .map(|healthcheck| healthcheck.endpoint.clone()),
+        health_timeout_secs: tuning.health_timeout,
+        drain_secs: tuning.drain_timeout,
+    })
+}
+
+/// Print the phases of a structured `deploy_app` result, one line each.
+/// Returns `false` for free-form results so callers can fall back.
+pub(crate) fn print_deploy_phases(result: &serde_json::Value) -> bool {
+    let Ok(report) = serde_json::from_value::<crate::forms::status_panel::DeployAppCommandReport>(
+        result.clone(),
+    ) else {
+        return false;
+    };
+
+    println!("Strategy: {}", report.strategy);
+    for phase in &report.phases {
+        let status = match phase.status {
+            crate::forms::status_panel::DeployPhaseStatus::Completed => "completed",
+            crate::forms::status_panel::DeployPhaseStatus::Failed => "failed",
+            crate::forms::status_panel::DeployPhaseStatus::Skipped => "skipped",
+        };
+        println!(
+            "  {} {:<16} {}",
+            progress::status_icon(status),
+            phase.phase.as_str(),
+            phase.message.as_deref().unwrap_or("")
+        );
+    }
+    if let Some(color) = report.active_color.as_deref() {
+        println!("Active:   {}", color);
+    }
+    true
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Shared agent command execution
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -436,7 +509,7 @@ pub(crate) fn run_agent_command(
 }
 
 /// Pretty-print an `AgentCommandInfo` result.
-fn print_command_result(info: &AgentCommandInfo, json: bool) {
+pub(crate) fn print_command_result(info: &AgentCommandInfo, json: bool) {
     if json {
         if let Ok(j) = serde_json::to_string_pretty(info) {
             println!("{}", j);
@@ -706,6 +779,7 @@ impl CallableTrait for AgentHealthCommand {
             container: None,
             include_metrics: true,
             include_system: self.include_system,
+            rollout: None,
         };
 
         let request = AgentEnqueueRequest::new(&hash, "health")
@@ -722,6 +796,146 @@ impl CallableTrait for AgentHealthCommand {
     }
 }
 
+// ── Stats ────────────────────────────────────────────
+
+/// `stacker agent stats [--app <code>] [--since <window>] [--json] [--deployment <hash>]`
+///
+/// Without `--since`, asks the agent for a fresh sample; with it, summarises
+/// the samples the agent pushed over that window.
+pub struct AgentStatsCommand {
+    pub app_code: Option<String>,
+    pub since: Option<String>,
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl AgentStatsCommand {
+    pub fn new(
+        app_code: Option<String>,
+        since: Option<String>,
+        json: bool,
+        deployment: Option<String>,
+    ) -> Self {
+        Self {
+            app_code,
+            since,
+            json,
+            deployment,
+        }
+    }
+
+    fn print_history(&self, ctx: &CliRuntime, hash: &str, since: &str) -> Result<(), CliError> {
+        parse_window(since).map_err(CliError::ConfigValidation)?;
+        let samples = ctx.block_on(ctx.client.container_metrics(
+            hash,
+            since,
+            self.app_code.as_deref(),
+        ))?;
+        let summary = summarize(&samples);
+
+        if self.json {
+            let value = serde_json::to_value(&summary).map_err(|e| {
+                CliError::ConfigValidation(format!("Failed to encode stats: {}", e))
+            })?;
+            println!("{}", fmt::pretty_json(&value));
+            return Ok(());
+        }
+        if summary.is_empty() {
+            println!(
+                "No samples in the last {}. Agents push them periodically; run `stacker agent stats` for a live reading.",
+                since
+            );
+            return Ok(());
+        }
+
+        println!(
+            "{:<20} {:>8} {:>8} {:>22} {:>11} {:>11}",
+            "APP", "CPU AVG", "CPU MAX", "MEM NOW / MAX", "NET RX", "NET TX"
+        );
+        println!("{}", fmt::separator(86));
+        for app in &summary {
+            println!(
+                "{:<20} {:>7.1}% {:>7.1}% {:>22} {:>11} {:>11}",
+                fmt::truncate(&app.app_code, 19),
+                app.cpu_avg_percent,
+                app.cpu_max_percent,
+                format!(
+                    "{} / {}",
+                    fmt::bytes(app.memory_latest_bytes.max(0) as u64),
+                    fmt::bytes(app.memory_max_bytes.max(0) as u64)
+                ),
+                fmt::bytes(app.net_rx_bytes.max(0) as u64),
+                fmt::bytes(app.net_tx_bytes.max(0) as u64),
+            );
+        }
+        println!("\nOver the last {} ({} samples).", since, samples.len());
+        Ok(())
+    }
+}
+
+impl CallableTrait for AgentStatsCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = CliRuntime::new("agent stats")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+
+        if let Some(since) = self.since.as_deref() {
+            self.print_history(&ctx, &hash, since)?;
+            return Ok(());
+        }
+
+        let params = StatsCommandRequest {
+            app_code: self.app_code.clone(),
+        };
+        let request = AgentEnqueueRequest::new(&hash, "stats")
+            .with_parameters(&params)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+        let info = run_agent_command(
+            &ctx,
+            &request,
+            "Collecting container stats",
+            DEFAULT_TIMEOUT_SECS,
+        )?;
+
+        let report = info
+            .result
+            .clone()
+            .and_then(|result| serde_json::from_value::<StatsCommandReport>(result).ok());
+        match report {
+            Some(report) if !self.json => print_stats_report(&report),
+            _ => print_command_result(&info, self.json),
+        }
+        Ok(())
+    }
+}
+
+fn print_stats_report(report: &StatsCommandReport) {
+    if report.containers.is_empty() {
+        println!("No running containers.");
+        return;
+    }
+    println!(
+        "{:<20} {:>7} {:>22} {:>11} {:>11} {:>11} {:>11}",
+        "APP", "CPU", "MEMORY / LIMIT", "NET RX", "NET TX", "BLOCK R", "BLOCK W"
+    );
+    println!("{}", fmt::separator(110));
+    for sample in &report.containers {
+        let limit = sample
+            .memory_limit_bytes
+            .map(fmt::bytes)
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<20} {:>6.1}% {:>22} {:>11} {:>11} {:>11} {:>11}",
+            fmt::truncate(&sample.app_code, 19),
+            sample.cpu_percent,
+            format!("{} / {}", fmt::bytes(sample.memory_bytes), limit),
+            fmt::bytes(sample.net_rx_bytes),
+            fmt::bytes(sample.net_tx_bytes),
+            fmt::bytes(sample.block_read_bytes),
+            fmt::bytes(sample.block_write_bytes),
+        );
+    }
+}
+
 // ── Logs ─────────────────────────────────────────────
 
 /// `stacker agent logs [app] [--limit N] [--json] [--deployment <hash>]`
@@ -944,6 +1158,9 @@ impl CallableTrait for AgentDeployAppCommand {
             runtime: self.runtime.clone(),
             registry_auth: resolve_registry_auth_for_agent_deploy(&project_dir),
             config_files: local_config.config_files,
+            blue_green: resolve_blue_green_for_agent_deploy(&project_dir),
+            rollout: None,
+            compose_project: None,
         };
 
         let request = AgentEnqueueRequest::new(&hash, "deploy_app")
@@ -952,6 +1169,9 @@ impl CallableTrait for AgentDeployAppCommand {
             .with_timeout(300);
 
         let info = run_agent_command(&ctx, &request, &format!("Deploying {}", self.app_code), 300)?;
+        if !self.json && info.result.as_ref().is_some_and(print_deploy_phases) {
+            return Ok(());
+        }
         print_command_result(&info, self.json);
         Ok(())
     }
@@ -1504,6 +1724,7 @@ impl CallableTrait for AgentRemoveAppCommand {
             delete_config: true,
             remove_volumes: self.remove_volumes,
             remove_image: self.remove_image,
+            compose_project: None,
         };
 
         let request = AgentEnqueueRequest::new(&hash, "remove_app")
@@ -2074,6 +2295,18 @@ fn agent_version_label(version: &str) -> String {
 const STATUS_SEP_WIDTH: usize = 92;
 
 /// Pretty-print a snapshot summary for human consumption.
+/// Why the server considers the agent outdated, if it does.
+fn agent_outdated_notice(agent: &serde_json::Value) -> Option<String> {
+    let compatibility: AgentCompatibility =
+        serde_json::from_value(agent.get("compatibility")?.clone()).ok()?;
+    compatibility.outdated.then(|| {
+        format!(
+            "{} (run `stacker agent upgrade`)",
+            compatibility.reasons.join("; ")
+        )
+    })
+}
+
 fn print_snapshot_summary(
     snap: &serde_json::Value,
     live_containers: Option<&Vec<serde_json::Value>>,
@@ -2102,6 +2335,9 @@ fn print_snapshot_summary(
             version_label
         );
         println!("Heartbeat: {}", heartbeat);
+        if let Some(notice) = agent_outdated_notice(agent) {
+            println!("Outdated:  {}", notice);
+        }
     } else {
         println!("Agent:     not registered");
     }
@@ -2334,6 +2570,483 @@ impl CallableTrait for AgentExecCommand {
     }
 }
 
+// ── Shell ────────────────────────────────────────────
+
+/// `stacker agent shell <app> [--shell <program>] [--idle-timeout <secs>] [--deployment <hash>]`
+///
+/// Interactive terminal in an app container, relayed by the Stacker server
+/// to the agent. Ends when the shell exits or after the idle timeout.
+pub struct AgentShellCommand {
+    pub app_code: String,
+    pub shell: Option<String>,
+    pub idle_timeout: Option<u32>,
+    pub deployment: Option<String>,
+}
+
+impl AgentShellCommand {
+    pub fn new(
+        app_code: String,
+        shell: Option<String>,
+        idle_timeout: Option<u32>,
+        deployment: Option<String>,
+    ) -> Self {
+        Self {
+            app_code,
+            shell,
+            idle_timeout,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for AgentShellCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !terminal::is_interactive() {
+            return Err(Box::new(CliError::ConfigValidation(
+                "`stacker agent shell` needs an interactive terminal; use `stacker agent exec` from scripts".to_string(),
+            )));
+        }
+        let ctx = CliRuntime::new("agent shell")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+
+        eprintln!("Opening a shell in {} ({})...", self.app_code, hash);
+        let request = ShellSessionRequest {
+            app_code: &self.app_code,
+            shell: self.shell.as_deref(),
+            cols,
+            rows,
+            idle_timeout: self.idle_timeout,
+        };
+        let socket = ctx.block_on(ctx.client.open_shell(&hash, &request))?;
+        let exit_code = ctx.block_on(relay_shell(socket))?;
+
+        match exit_code {
+            Some(0) | None => eprintln!("Shell closed."),
+            Some(code) => eprintln!("Shell exited with code {}.", code),
+        }
+        Ok(())
+    }
+}
+
+fn shell_error(message: impl std::fmt::Display) -> CliError {
+    CliError::ConfigValidation(format!("Shell session failed: {}", message))
+}
+
+/// Pipe the terminal through the session until the shell exits. Returns the
+/// shell's exit code when the agent reported one.
+async fn relay_shell(socket: ShellSocket) -> Result<Option<i32>, CliError> {
+    use futures_util::{SinkExt, StreamExt};
+    use std::io::Read;
+    use tokio::io::AsyncWriteExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let (mut outgoing, mut incoming) = socket.split();
+
+    // The server answers `ready` once the agent attached the PTY.
+    loop {
+        match incoming.next().await {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                Ok(ShellControl::Ready { .. }) => break,
+                Ok(ShellControl::Error { message }) => return Err(shell_error(message)),
+                _ => {}
+            },
+            Some(Ok(Message::Close(_))) | None => {
+                return Err(shell_error(
+                    "the server closed the session before it started",
+                ))
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return Err(shell_error(e)),
+        }
+    }
+
+    let _raw = terminal::RawMode::enable()?;
+    let mut resizes = terminal::ResizeEvents::new();
+    let mut stdout = tokio::io::stdout();
+
+    // A blocking stdin read cannot be cancelled, so it runs on a detached
+    // thread rather than the runtime, which would wait for it on shutdown.
+    let (input_tx, mut input) = tokio::sync::mpsc::channel::<Vec<u8>>(64);
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin().lock();
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = stdin.read(&mut buf) {
+            if n == 0 || input_tx.blocking_send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        tokio::select! {
+            keys = input.recv() => match keys {
+                Some(keys) => outgoing
+                    .send(Message::Binary(keys))
+                    .await
+                    .map_err(shell_error)?,
+                None => break,
+            },
+            Some((cols, rows)) = resizes.next() => {
+                let resize = ShellControl::Resize { cols, rows }.to_text();
+                outgoing.send(Message::Text(resize)).await.map_err(shell_error)?;
+            }
+            frame = incoming.next() => match frame {
+                Some(Ok(Message::Binary(data))) => {
+                    stdout.write_all(&data).await?;
+                    stdout.flush().await?;
+                }
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(ShellControl::Exit { code }) => return Ok(code),
+                    Ok(ShellControl::Error { message }) => return Err(shell_error(message)),
+                    _ => {}
+                },
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(shell_error(e)),
+            },
+        }
+    }
+
+    let _ = outgoing.send(Message::Close(None)).await;
+    Ok(None)
+}
+
+// ── Copy ─────────────────────────────────────────────
+
+/// Poll timeout for one chunk (seconds).
+const CHUNK_TIMEOUT_SECS: u64 = 120;
+/// Suffix of a download in progress; rerunning the copy resumes it.
+const PARTIAL_DOWNLOAD_SUFFIX: &str = ".stacker-part";
+
+/// Which way a copy goes, from `<local>` and `<app>:<path>` arguments.
+#[derive(Debug, PartialEq, Eq)]
+enum CopyDirection<'a> {
+    Upload {
+        local: &'a str,
+        app_code: &'a str,
+        path: &'a str,
+    },
+    Download {
+        app_code: &'a str,
+        path: &'a str,
+        local: &'a str,
+    },
+}
+
+fn copy_direction<'a>(
+    source: &'a str,
+    destination: &'a str,
+) -> Result<CopyDirection<'a>, CliError> {
+    match (
+        parse_container_path(source),
+        parse_container_path(destination),
+    ) {
+        (None, Some((app_code, path))) => Ok(CopyDirection::Upload {
+            local: source,
+            app_code,
+            path,
+        }),
+        (Some((app_code, path)), None) => Ok(CopyDirection::Download {
+            app_code,
+            path,
+            local: destination,
+        }),
+        (Some(_), Some(_)) => Err(CliError::ConfigValidation(
+            "Copying between containers is not supported; copy through a local file".to_string(),
+        )),
+        (None, None) => Err(CliError::ConfigValidation(
+            "One side must be a container path such as web:/etc/nginx/nginx.conf".to_string(),
+        )),
+    }
+}
+
+/// Local file a download is written to: inside `local` when it is a
+/// directory, named after the remote file.
+fn download_target(local: &Path, remote_path: &str) -> PathBuf {
+    if local.is_dir() {
+        let name = remote_path
+            .rsplit('/')
+            .find(|segment| !segment.is_empty())
+            .unwrap_or("download");
+        local.join(name)
+    } else {
+        local.to_path_buf()
+    }
+}
+
+fn partial_download_path(target: &Path) -> PathBuf {
+    let mut name = target.as_os_str().to_os_string();
+    name.push(PARTIAL_DOWNLOAD_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn transfer_error(errors: &[StatusPanelCommandError]) -> String {
+    if errors.is_empty() {
+        "the agent reported a failure".to_string()
+    } else {
+        errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+fn parse_report<T: serde::de::DeserializeOwned>(info: &AgentCommandInfo) -> Result<T, CliError> {
+    info.result
+        .clone()
+        .and_then(|result| serde_json::from_value(result).ok())
+        .ok_or_else(|| {
+            CliError::FileTransferFailed(format!(
+                "command {} returned no transfer report",
+                info.command_id
+            ))
+        })
+}
+
+/// `stacker agent cp <local> <app>:<path>` / `stacker agent cp <app>:<path> <local> [--json] [--deployment <hash>]`
+///
+/// Copies one file into or out of an app container, one checksummed chunk
+/// per agent command. Files are limited to [`MAX_FILE_TRANSFER_BYTES`].
+/// Rerunning an interrupted copy resumes it.
+pub struct AgentCpCommand {
+    pub source: String,
+    pub destination: String,
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl AgentCpCommand {
+    pub fn new(
+        source: String,
+        destination: String,
+        json: bool,
+        deployment: Option<String>,
+    ) -> Self {
+        Self {
+            source,
+            destination,
+            json,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for AgentCpCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let direction = copy_direction(&self.source, &self.destination)?;
+        let ctx = CliRuntime::new("agent cp")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+
+        let summary = match direction {
+            CopyDirection::Upload {
+                local,
+                app_code,
+                path,
+            } => upload_file(&ctx, &hash, Path::new(local), app_code, path)?,
+            CopyDirection::Download {
+                app_code,
+                path,
+                local,
+            } => download_file(&ctx, &hash, app_code, path, Path::new(local))?,
+        };
+
+        if self.json {
+            println!("{}", fmt::pretty_json(&summary));
+        }
+        Ok(())
+    }
+}
+
+fn upload_file(
+    ctx: &CliRuntime,
+    hash: &str,
+    local: &Path,
+    app_code: &str,
+    path: &str,
+) -> Result<serde_json::Value, CliError> {
+    let size = std::fs::metadata(local)?.len();
+    if size > MAX_FILE_TRANSFER_BYTES {
+        return Err(CliError::FileTransferFailed(format!(
+            "{} is {}; copies are limited to {}",
+            local.display(),
+            fmt::bytes(size),
+            fmt::bytes(MAX_FILE_TRANSFER_BYTES)
+        )));
+    }
+    let data = std::fs::read(local)?;
+    let sha256 = sha256_hex(&data);
+    let transfer_id = upload_transfer_id(hash, app_code, path, &sha256);
+
+    let message = format!("Copying {} to {}:{}", local.display(), app_code, path);
+    let pb = progress::spinner(&message);
+    let result = ctx.block_on(async {
+        let mut offset = 0u64;
+        loop {
+            let start = offset as usize;
+            let chunk = &data[start..(start + DEFAULT_CHUNK_BYTES).min(data.len())];
+            let params = UploadFileCommandRequest {
+                transfer_id: transfer_id.clone(),
+                app_code: app_code.to_string(),
+                path: path.to_string(),
+                offset,
+                total_size: size,
+                sha256: sha256.clone(),
+                data: general_purpose::STANDARD.encode(chunk),
+                chunk_sha256: sha256_hex(chunk),
+            };
+            let request = AgentEnqueueRequest::new(hash, UPLOAD_FILE_COMMAND_TYPE)
+                .with_parameters(&params)
+                .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+            let info = execute_agent_command(ctx, &request, CHUNK_TIMEOUT_SECS).await?;
+            let report: UploadFileCommandReport = parse_report(&info)?;
+
+            if report.status == FileTransferStatus::Failed {
+                return Err(CliError::FileTransferFailed(transfer_error(&report.errors)));
+            }
+            if report.complete {
+                return Ok(report);
+            }
+            // The agent may already hold later chunks from an earlier run.
+            let stalled = report.received_bytes <= offset && !chunk.is_empty();
+            if stalled || report.received_bytes >= size {
+                return Err(CliError::FileTransferFailed(format!(
+                    "the agent stopped at byte {} of {}",
+                    report.received_bytes, size
+                )));
+            }
+            offset = report.received_bytes;
+            progress::update_message(
+                &pb,
+                &format!(
+                    "{} [{} / {}]",
+                    message,
+                    fmt::bytes(offset),
+                    fmt::bytes(size)
+                ),
+            );
+        }
+    });
+
+    match &result {
+        Ok(_) => progress::finish_success(&pb, &format!("{} ({})", message, fmt::bytes(size))),
+        Err(e) => progress::finish_error(&pb, &format!("{} — {}", message, e)),
+    }
+    let report = result?;
+    Ok(serde_json::json!({
+        "direction": "upload",
+        "transfer_id": report.transfer_id,
+        "app_code": app_code,
+        "path": path,
+        "size_bytes": size,
+        "sha256": sha256,
+    }))
+}
+
+fn download_file(
+    ctx: &CliRuntime,
+    hash: &str,
+    app_code: &str,
+    path: &str,
+    local: &Path,
+) -> Result<serde_json::Value, CliError> {
+    use std::io::Write;
+
+    let target = download_target(local, path);
+    let partial = partial_download_path(&target);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&partial)?;
+    let transfer_id = download_transfer_id(hash, app_code, path);
+
+    let message = format!("Copying {}:{} to {}", app_code, path, target.display());
+    let pb = progress::spinner(&message);
+    let result = ctx.block_on(async {
+        let mut offset = file.metadata()?.len();
+        loop {
+            let params = DownloadFileCommandRequest {
+                transfer_id: transfer_id.clone(),
+                app_code: app_code.to_string(),
+                path: path.to_string(),
+                offset,
+                length: DEFAULT_CHUNK_BYTES as u64,
+                max_size: MAX_FILE_TRANSFER_BYTES,
+            };
+            let request = AgentEnqueueRequest::new(hash, DOWNLOAD_FILE_COMMAND_TYPE)
+                .with_parameters(&params)
+                .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+            let info = execute_agent_command(ctx, &request, CHUNK_TIMEOUT_SECS).await?;
+            let report: DownloadFileCommandReport = parse_report(&info)?;
+
+            if report.status == FileTransferStatus::Failed {
+                return Err(CliError::FileTransferFailed(transfer_error(&report.errors)));
+            }
+            if report.offset != offset || offset > report.total_size {
+                return Err(CliError::FileTransferFailed(format!(
+                    "the agent sent bytes from {} but {} were expected; remove {} and run again",
+                    report.offset,
+                    offset,
+                    partial.display()
+                )));
+            }
+            let chunk = decode_file_chunk("download_file", &report.data, &report.chunk_sha256)
+                .map_err(CliError::FileTransferFailed)?;
+            file.write_all(&chunk)?;
+            offset += chunk.len() as u64;
+            if offset >= report.total_size {
+                return Ok(report);
+            }
+            if chunk.is_empty() {
+                return Err(CliError::FileTransferFailed(format!(
+                    "the agent sent no data at byte {} of {}",
+                    offset, report.total_size
+                )));
+            }
+            progress::update_message(
+                &pb,
+                &format!(
+                    "{} [{} / {}]",
+                    message,
+                    fmt::bytes(offset),
+                    fmt::bytes(report.total_size)
+                ),
+            );
+        }
+    });
+    drop(file);
+
+    let result = result.and_then(|report| {
+        if sha256_hex(&std::fs::read(&partial)?) != report.sha256 {
+            let _ = std::fs::remove_file(&partial);
+            return Err(CliError::FileTransferFailed(
+                "checksum mismatch, the file changed while it was copied; run the copy again"
+                    .to_string(),
+            ));
+        }
+        std::fs::rename(&partial, &target)?;
+        Ok(report)
+    });
+    match &result {
+        Ok(report) => progress::finish_success(
+            &pb,
+            &format!("{} ({})", message, fmt::bytes(report.total_size)),
+        ),
+        Err(e) => progress::finish_error(&pb, &format!("{} — {}", message, e)),
+    }
+    let report = result?;
+    Ok(serde_json::json!({
+        "direction": "download",
+        "transfer_id": report.transfer_id,
+        "app_code": app_code,
+        "path": path,
+        "local": target.display().to_string(),
+        "size_bytes": report.total_size,
+        "sha256": report.sha256,
+    }))
+}
+
 // ── Command History ──────────────────────────────────
 
 /// `stacker agent history [--json] [--deployment <hash>]`
@@ -2378,21 +3091,39 @@ impl CallableTrait for AgentHistoryCommand {
             );
             println!("{}", fmt::separator(80));
 
+            let mut printed_batches = std::collections::HashSet::new();
             for c in commands {
-                let id = c.get("command_id").and_then(|v| v.as_str()).unwrap_or("-");
-                let ctype = c.get("type").and_then(|v| v.as_str()).unwrap_or("-");
-                let status = c.get("status").and_then(|v| v.as_str()).unwrap_or("-");
-                let priority = c.get("priority").and_then(|v| v.as_str()).unwrap_or("-");
-                let created = c.get("created_at").and_then(|v| v.as_str()).unwrap_or("-");
-                println!(
-                    "{:<24} {:<14} {} {:<8} {:<10} {}",
-                    fmt::truncate(id, 22),
-                    ctype,
-                    progress::status_icon(status),
-                    status,
-                    priority,
-                    fmt::truncate(created, 19),
-                );
+                match history_batch_id(c) {
+                    Some(batch_id) => {
+                        if !printed_batches.insert(batch_id.to_string()) {
+                            continue;
+                        }
+                        let members: Vec<&serde_json::Value> = commands
+                            .iter()
+                            .filter(|other| history_batch_id(other) == Some(batch_id))
+                            .collect();
+                        let status = crate::models::CommandBatchStatus::from_statuses(
+                            members
+                                .iter()
+                                .map(|m| m.get("status").and_then(|v| v.as_str()).unwrap_or("")),
+                        )
+                        .to_string();
+                        let created = c.get("created_at").and_then(|v| v.as_str()).unwrap_or("-");
+                        println!(
+                            "{:<24} {:<14} {} {:<8} {:<10} {}",
+                            fmt::truncate(batch_id, 22),
+                            format!("batch({})", members.len()),
+                            progress::status_icon(&status),
+                            status,
+                            "-",
+                            fmt::truncate(created, 19),
+                        );
+                        for member in members {
+                            print_history_row(member, "  ");
+                        }
+                    }
+                    None => print_history_row(c, ""),
+                }
             }
         } else {
             println!("No commands found.");
@@ -2402,6 +3133,271 @@ impl CallableTrait for AgentHistoryCommand {
     }
 }
 
+/// Batch id recorded under `metadata.batch.id`, for commands queued as part
+/// of a command batch.
+fn history_batch_id(command: &serde_json::Value) -> Option<&str> {
+    command
+        .get("metadata")
+        .and_then(|m| m.get("batch"))
+        .and_then(|b| b.get("id"))
+        .and_then(|v| v.as_str())
+}
+
+fn print_history_row(command: &serde_json::Value, indent: &str) {
+    let field = |key: &str| command.get(key).and_then(|v| v.as_str()).unwrap_or("-");
+    let label = match command
+        .get("metadata")
+        .and_then(|m| m.get("batch"))
+        .and_then(|b| b.get("step"))
+        .and_then(|v| v.as_str())
+    {
+        Some(step) => format!("{}{}", indent, step),
+        None => format!("{}{}", indent, field("command_id")),
+    };
+    let status = field("status");
+    println!(
+        "{:<24} {:<14} {} {:<8} {:<10} {}",
+        fmt::truncate(&label, 22),
+        field("type"),
+        progress::status_icon(status),
+        status,
+        field("priority"),
+        fmt::truncate(field("created_at"), 19),
+    );
+}
+
+// ── Schedules ────────────────────────────────────────
+
+/// `stacker agent schedule add <cron> <command_type> [--params <json>] ...`
+///
+/// Queues the command on the server every time the cron expression (UTC)
+/// matches, whether or not this machine is online.
+pub struct AgentScheduleAddCommand {
+    pub cron: String,
+    pub command_type: String,
+    pub params: Option<String>,
+    pub name: Option<String>,
+    pub priority: Option<String>,
+    pub timeout: Option<i32>,
+    pub missed_run: MissedRunPolicy,
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl AgentScheduleAddCommand {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        cron: String,
+        command_type: String,
+        params: Option<String>,
+        name: Option<String>,
+        priority: Option<String>,
+        timeout: Option<i32>,
+        missed_run: MissedRunPolicy,
+        json: bool,
+        deployment: Option<String>,
+    ) -> Self {
+        Self {
+            cron,
+            command_type,
+            params,
+            name,
+            priority,
+            timeout,
+            missed_run,
+            json,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for AgentScheduleAddCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Catch typos before the round trip.
+        let cron = CronSchedule::parse(&self.cron).map_err(CliError::ConfigValidation)?;
+        let parameters = match &self.params {
+            Some(raw) => Some(serde_json::from_str(raw).map_err(|e| {
+                CliError::ConfigValidation(format!("Invalid JSON parameters: {}", e))
+            })?),
+            None => None,
+        };
+
+        let ctx = CliRuntime::new("agent schedule add")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+        let request = ScheduleRequest {
+            cron: cron.expression().to_string(),
+            command_type: self.command_type.clone(),
+            name: self.name.clone(),
+            parameters,
+            priority: self.priority.clone(),
+            timeout_seconds: self.timeout,
+            missed_run_policy: Some(self.missed_run),
+        };
+        let schedule = ctx.block_on(ctx.client.create_command_schedule(&hash, &request))?;
+
+        if self.json {
+            println!("{}", fmt::pretty_json(&serde_json::to_value(&schedule)?));
+        } else {
+            println!(
+                "Scheduled {} ({}) as {}. Next run: {} UTC",
+                schedule.command_type,
+                schedule.cron,
+                schedule.schedule_id,
+                schedule.next_run_at.format("%Y-%m-%d %H:%M")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `stacker agent schedule list [--json] [--deployment <hash>]`
+pub struct AgentScheduleListCommand {
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl AgentScheduleListCommand {
+    pub fn new(json: bool, deployment: Option<String>) -> Self {
+        Self { json, deployment }
+    }
+}
+
+impl CallableTrait for AgentScheduleListCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = CliRuntime::new("agent schedule list")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+        let schedules = ctx.block_on(ctx.client.list_command_schedules(&hash))?;
+
+        if self.json {
+            println!("{}", fmt::pretty_json(&serde_json::to_value(&schedules)?));
+            return Ok(());
+        }
+        if schedules.is_empty() {
+            println!("No schedules. Add one with `stacker agent schedule add`.");
+            return Ok(());
+        }
+
+        println!(
+            "{:<42} {:<16} {:<16} {:<17} {}",
+            "SCHEDULE", "CRON", "TYPE", "NEXT RUN (UTC)", "NAME"
+        );
+        println!("{}", fmt::separator(100));
+        for schedule in &schedules {
+            println!(
+                "{:<42} {:<16} {:<16} {:<17} {}",
+                schedule.schedule_id,
+                fmt::truncate(&schedule.cron, 15),
+                fmt::truncate(&schedule.command_type, 15),
+                schedule.next_run_at.format("%Y-%m-%d %H:%M"),
+                schedule.name.as_deref().unwrap_or("-"),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `stacker agent schedule rm <schedule_id> [--deployment <hash>]`
+pub struct AgentScheduleRemoveCommand {
+    pub schedule_id: String,
+    pub deployment: Option<String>,
+}
+
+impl AgentScheduleRemoveCommand {
+    pub fn new(schedule_id: String, deployment: Option<String>) -> Self {
+        Self {
+            schedule_id,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for AgentScheduleRemoveCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = CliRuntime::new("agent schedule rm")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+        ctx.block_on(ctx.client.delete_command_schedule(&hash, &self.schedule_id))?;
+        println!("Removed schedule {}", self.schedule_id);
+        Ok(())
+    }
+}
+
+// ── Upgrade ──────────────────────────────────────────
+
+/// `stacker agent upgrade [--version <version>] [--image <image>] [--json] [--deployment <hash>]`
+///
+/// Queues `upgrade_agent` for `--version` or the latest release known to the
+/// server. The agent re-registers with its new capability manifest.
+pub struct AgentUpgradeCommand {
+    pub version: Option<String>,
+    pub image: Option<String>,
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl AgentUpgradeCommand {
+    pub fn new(
+        version: Option<String>,
+        image: Option<String>,
+        json: bool,
+        deployment: Option<String>,
+    ) -> Self {
+        Self {
+            version,
+            image,
+            json,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for AgentUpgradeCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = CliRuntime::new("agent upgrade")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+
+        let compatibility = ctx
+            .block_on(ctx.client.deployment_capabilities(&hash))?
+            .compatibility
+            .unwrap_or_default();
+        if self.version.is_none() && !compatibility.outdated {
+            println!(
+                "Agent is up to date ({}).",
+                compatibility
+                    .version
+                    .as_deref()
+                    .unwrap_or("unknown version")
+            );
+            return Ok(());
+        }
+        let version = self
+            .version
+            .clone()
+            .or(compatibility.latest_version)
+            .ok_or_else(|| {
+                CliError::ConfigValidation(
+                    "The server does not know the latest agent release; pass --version".to_string(),
+                )
+            })?;
+
+        let params = UpgradeAgentCommandRequest {
+            version: version.clone(),
+            image: self.image.clone(),
+        };
+        let request = AgentEnqueueRequest::new(&hash, UPGRADE_AGENT_COMMAND_TYPE)
+            .with_parameters(&params)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+
+        let info = run_agent_command(
+            &ctx,
+            &request,
+            &format!("Upgrading agent to {}", version),
+            UPGRADE_TIMEOUT_SECS,
+        )?;
+        print_command_result(&info, self.json);
+        Ok(())
+    }
+}
+
 // ── Install (deploy Status Panel to existing server) ─
 
 /// `stacker agent install [--file <path>] [--persist-config] [--json] [--local]`
@@ -2628,13 +3624,14 @@ struct AgentInstallConfigPersistence {
 fn persist_agent_install_config(
     config_path: &Path,
 ) -> Result<AgentInstallConfigPersistence, CliError> {
-    let mut config = crate::cli::config_parser::StackerConfig::from_file_raw(config_path)?;
+    let (mut config, layer) =
+        crate::cli::config_parser::StackerConfig::from_file_layer(config_path)?;
     let changed = !config.monitoring.status_panel;
     let backup_path = PathBuf::from(format!("{}.bak", config_path.display()));
 
     if changed {
         config.monitoring.status_panel = true;
-        let yaml = serde_yaml::to_string(&config).map_err(|e| {
+        let yaml = layer.render(&config).map_err(|e| {
             CliError::ConfigValidation(format!("Failed to serialize config: {}", e))
         })?;
         std::fs::copy(config_path, &backup_path)?;
@@ -3455,6 +4452,7 @@ environments:
             container: None,
             include_metrics: true,
             include_system: false,
+            rollout: None,
         };
 
         let req = AgentEnqueueRequest::new("hash", "health")
@@ -3473,6 +4471,25 @@ environments:
         print_snapshot_summary(&snap, None);
     }
 
+    #[test]
+    fn agent_outdated_notice_lists_reasons() {
+        let agent = serde_json::json!({
+            "status": "online",
+            "compatibility": {
+                "outdated": true,
+                "reasons": ["version 0.3.9, latest is 0.4.0"]
+            }
+        });
+        assert_eq!(
+            agent_outdated_notice(&agent).as_deref(),
+            Some("version 0.3.9, latest is 0.4.0 (run `stacker agent upgrade`)")
+        );
+        assert_eq!(
+            agent_outdated_notice(&serde_json::json!({"status": "online"})),
+            None
+        );
+    }
+
     #[test]
     fn agent_display_version_suppresses_placeholder_version() {
         let agent = serde_json::json!({
@@ -4104,4 +5121,33 @@ monitoring:
             None => std::env::remove_var("STACKER_DOCKER_REGISTRY"),
         }
     }
+
+    #[test]
+    fn copy_direction_needs_exactly_one_container_path() {
+        assert_eq!(
+            copy_direction("dump.sql", "db:/tmp/dump.sql").unwrap(),
+            CopyDirection::Upload {
+                local: "dump.sql",
+                app_code: "db",
+                path: "/tmp/dump.sql",
+            }
+        );
+        assert!(matches!(
+            copy_direction("web:/var/log/app.log", ".").unwrap(),
+            CopyDirection::Download {
+                app_code: "web",
+                ..
+            }
+        ));
+        assert!(copy_direction("web:/a", "db:/b").is_err());
+        assert!(copy_direction("a.txt", "b.txt").is_err());
+
+        let dir = TempDir::new().unwrap();
+        let target = download_target(dir.path(), "/var/log/app.log");
+        assert_eq!(target, dir.path().join("app.log"));
+        assert_eq!(
+            partial_download_path(&target),
+            dir.path().join("app.log.stacker-part")
+        );
+    }
 }
@@ -268,7 +268,7 @@ fn configure_ai_interactive(config_path: &str) -> Result<AiConfig, CliError> {
         });
     }
 
-    let mut config = StackerConfig::from_file_raw(path)?;
+    let (mut config, layer) = StackerConfig::from_file_layer(path)?;
     let current = config.ai.clone();
 
     eprintln!("AI interactive setup for {}", config_path);
@@ -329,7 +329,8 @@ fn configure_ai_interactive(config_path: &str) -> Result<AiConfig, CliError> {
 
     let backup_path = format!("{}.bak", config_path);
     std::fs::copy(config_path, &backup_path)?;
-    let yaml = serde_yaml::to_string(&config)
+    let yaml = layer
+        .render(&config)
         .map_err(|e| CliError::ConfigValidation(format!("Failed to serialize config: {}", e)))?;
     std::fs::write(config_path, yaml)?;
 
@@ -838,8 +839,8 @@ fn execute_tool(call: &ToolCall, cwd: &Path) -> String {
                 );
             }
 
-            match StackerConfig::from_file_raw(&config_path) {
-                Ok(mut config) => {
+            match StackerConfig::from_file_layer(&config_path) {
+                Ok((mut config, layer)) => {
                     // Duplicate check
                     if config.services.iter().any(|s| s.name == svc.name) {
                         return format!(
@@ -866,7 +867,7 @@ fn execute_tool(call: &ToolCall, cwd: &Path) -> String {
                     let backup = config_path.with_extension("yml.bak");
                     let _ = std::fs::copy(&config_path, &backup);
 
-                    match serde_yaml::to_string(&config) {
+                    match layer.render(&config) {
                         Ok(yaml) => match std::fs::write(&config_path, &yaml) {
                             Ok(()) => {
                                 let mut msg = format!(
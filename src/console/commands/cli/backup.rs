@@ -0,0 +1,296 @@
+//! `stacker backup` — volume backups taken and restored by the Status Panel
+//! agent.
+//!
+//! `create` queues `backup_volume`, dumping a known database first, and
+//! `restore` queues `restore_volume` for a backup listed by `list`. Backups
+//! are kept on the server or in an S3-compatible bucket.
+
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::error::CliError;
+use crate::cli::fmt;
+use crate::cli::runtime::CliRuntime;
+use crate::cli::stacker_client::AgentEnqueueRequest;
+use crate::console::commands::cli::agent::{
+    print_command_result, resolve_deployment_hash, run_agent_command,
+};
+use crate::console::commands::CallableTrait;
+use crate::forms::status_panel::{
+    BackupRetention, BackupTarget, BackupTargetKind, BackupVolumeCommandRequest, DatabaseEngine,
+    RestoreVolumeCommandRequest,
+};
+use crate::services::backup::{BACKUP_COMMAND_TYPE, RESTORE_COMMAND_TYPE};
+
+/// Default poll timeout for backup and restore commands (seconds).
+pub const DEFAULT_BACKUP_TIMEOUT_SECS: u64 = 900;
+
+/// S3 target flags; no bucket means a local backup on the server.
+#[derive(Debug, Clone, Default)]
+pub struct BackupS3Options {
+    pub bucket: Option<String>,
+    pub prefix: Option<String>,
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+}
+
+impl BackupS3Options {
+    fn target(&self) -> BackupTarget {
+        match &self.bucket {
+            Some(bucket) => BackupTarget {
+                kind: BackupTargetKind::S3,
+                bucket: Some(bucket.clone()),
+                prefix: self.prefix.clone(),
+                endpoint: self.endpoint.clone(),
+                region: self.region.clone(),
+            },
+            None => BackupTarget::default(),
+        }
+    }
+}
+
+/// Database engine of `app_code` from its image in `./stacker.yml`.
+fn detect_database(app_code: &str) -> Option<DatabaseEngine> {
+    let config_path = std::env::current_dir().ok()?.join("stacker.yml");
+    let config = StackerConfig::from_file(&config_path).ok()?;
+    config
+        .services
+        .iter()
+        .find(|service| service.name == app_code)
+        .and_then(|service| DatabaseEngine::detect(&service.image))
+}
+
+// ── Create ───────────────────────────────────────────
+
+/// `stacker backup create <app> [--volume <name>]... [--database <engine>|--no-dump]
+/// [--s3-bucket <bucket>] [--keep-last <n>] [--max-age-days <n>] [--stop] [--json] [--deployment <hash>]`
+pub struct BackupCreateCommand {
+    pub app_code: String,
+    pub volumes: Vec<String>,
+    pub database: Option<DatabaseEngine>,
+    pub no_dump: bool,
+    pub s3: BackupS3Options,
+    pub retention: BackupRetention,
+    pub stop_container: bool,
+    pub timeout: u64,
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl BackupCreateCommand {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        app_code: String,
+        volumes: Vec<String>,
+        database: Option<DatabaseEngine>,
+        no_dump: bool,
+        s3: BackupS3Options,
+        retention: BackupRetention,
+        stop_container: bool,
+        timeout: u64,
+        json: bool,
+        deployment: Option<String>,
+    ) -> Self {
+        Self {
+            app_code,
+            volumes,
+            database,
+            no_dump,
+            s3,
+            retention,
+            stop_container,
+            timeout,
+            json,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for BackupCreateCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = CliRuntime::new("backup create")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+
+        let database = if self.no_dump {
+            None
+        } else {
+            self.database.or_else(|| detect_database(&self.app_code))
+        };
+        let params = BackupVolumeCommandRequest {
+            app_code: self.app_code.clone(),
+            volumes: self.volumes.clone(),
+            database,
+            target: self.s3.target(),
+            retention: self.retention.clone(),
+            stop_container: self.stop_container,
+        };
+
+        let request = AgentEnqueueRequest::new(&hash, BACKUP_COMMAND_TYPE)
+            .with_parameters(&params)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+
+        let spinner = match database {
+            Some(engine) => format!("Backing up {} ({} dump)", self.app_code, engine.as_str()),
+            None => format!("Backing up {}", self.app_code),
+        };
+        let info = run_agent_command(&ctx, &request, &spinner, self.timeout)?;
+        print_command_result(&info, self.json);
+        Ok(())
+    }
+}
+
+// ── List ─────────────────────────────────────────────
+
+/// `stacker backup list [--app <app>] [--json] [--deployment <hash>]`
+pub struct BackupListCommand {
+    pub app_code: Option<String>,
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl BackupListCommand {
+    pub fn new(app_code: Option<String>, json: bool, deployment: Option<String>) -> Self {
+        Self {
+            app_code,
+            json,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for BackupListCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = CliRuntime::new("backup list")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+        let mut backups = ctx.block_on(ctx.client.list_backups(&hash))?;
+        if let Some(app_code) = &self.app_code {
+            backups.retain(|backup| &backup.app_code == app_code);
+        }
+
+        if self.json {
+            println!("{}", fmt::pretty_json(&serde_json::to_value(&backups)?));
+            return Ok(());
+        }
+        if backups.is_empty() {
+            println!("No backups. Take one with `stacker backup create <app>`.");
+            return Ok(());
+        }
+
+        println!(
+            "{:<40} {:<16} {:<17} {:<10} {:<10} {}",
+            "BACKUP", "APP", "CREATED (UTC)", "DATABASE", "SIZE", "TARGET"
+        );
+        println!("{}", fmt::separator(110));
+        for backup in &backups {
+            let target = match backup.target.kind {
+                BackupTargetKind::Local => "local".to_string(),
+                BackupTargetKind::S3 => format!(
+                    "s3://{}",
+                    backup.target.bucket.as_deref().unwrap_or_default()
+                ),
+            };
+            println!(
+                "{:<40} {:<16} {:<17} {:<10} {:<10} {}",
+                backup.backup_id,
+                fmt::truncate(&backup.app_code, 15),
+                backup.created_at.format("%Y-%m-%d %H:%M"),
+                backup.database.map(|db| db.as_str()).unwrap_or("-"),
+                fmt::bytes(backup.size_bytes),
+                target,
+            );
+        }
+        Ok(())
+    }
+}
+
+// ── Restore ──────────────────────────────────────────
+
+/// `stacker backup restore <backup_id> [--volume <name>]... [--no-database] [--json] [--deployment <hash>]`
+pub struct BackupRestoreCommand {
+    pub backup_id: String,
+    pub volumes: Vec<String>,
+    pub no_database: bool,
+    pub timeout: u64,
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl BackupRestoreCommand {
+    pub fn new(
+        backup_id: String,
+        volumes: Vec<String>,
+        no_database: bool,
+        timeout: u64,
+        json: bool,
+        deployment: Option<String>,
+    ) -> Self {
+        Self {
+            backup_id,
+            volumes,
+            no_database,
+            timeout,
+            json,
+            deployment,
+        }
+    }
+}
+
+impl CallableTrait for BackupRestoreCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = CliRuntime::new("backup restore")?;
+        let hash = resolve_deployment_hash(&self.deployment, &ctx)?;
+
+        let backups = ctx.block_on(ctx.client.list_backups(&hash))?;
+        let backup = backups
+            .into_iter()
+            .find(|backup| backup.backup_id == self.backup_id)
+            .ok_or_else(|| {
+                CliError::ConfigValidation(format!(
+                    "Backup '{}' not found. Run `stacker backup list` to see available backups.",
+                    self.backup_id
+                ))
+            })?;
+
+        let params = RestoreVolumeCommandRequest {
+            app_code: backup.app_code.clone(),
+            backup_id: backup.backup_id.clone(),
+            target: backup.target.clone(),
+            volumes: self.volumes.clone(),
+            restore_database: backup.database.is_some() && !self.no_database,
+        };
+
+        let request = AgentEnqueueRequest::new(&hash, RESTORE_COMMAND_TYPE)
+            .with_parameters(&params)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+
+        let info = run_agent_command(
+            &ctx,
+            &request,
+            &format!("Restoring {} from {}", backup.app_code, backup.backup_id),
+            self.timeout,
+        )?;
+        print_command_result(&info, self.json);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s3_flags_select_an_s3_target() {
+        assert_eq!(
+            BackupS3Options::default().target().kind,
+            BackupTargetKind::Local
+        );
+
+        let target = BackupS3Options {
+            bucket: Some("backups".to_string()),
+            prefix: Some("prod".to_string()),
+            ..Default::default()
+        }
+        .target();
+        assert_eq!(target.kind, BackupTargetKind::S3);
+        assert_eq!(target.bucket.as_deref(), Some("backups"));
+        assert_eq!(target.prefix.as_deref(), Some("prod"));
+    }
+}
@@ -0,0 +1,175 @@
+//! `stacker build` — build, tag and push app images ahead of a deploy.
+//!
+//! ```text
+//! stacker build                                         # build + push for the host platform
+//! stacker build --platform linux/amd64,linux/arm64      # multi-arch manifest
+//! stacker build --no-push                               # load into the local image store only
+//! ```
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::error::CliError;
+use crate::cli::generator::dockerfile::DockerfileBuilder;
+use crate::cli::image_build::{
+    git_revision, image_tags, plan_build_targets, resolve_repository, run_builds, BuildOptions,
+    BuildRecord,
+};
+use crate::cli::install_runner::{resolve_docker_registry_credentials, ShellExecutor};
+use crate::console::commands::CallableTrait;
+
+const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
+const OUTPUT_DIR: &str = ".stacker";
+
+/// `stacker build [--file stacker.yml] [--platform ...] [--repository REPO] [--no-push]`
+pub struct BuildCommand {
+    pub file: Option<String>,
+    pub platforms: Vec<String>,
+    pub repository: Option<String>,
+    pub no_push: bool,
+}
+
+impl BuildCommand {
+    pub fn new(
+        file: Option<String>,
+        platforms: Vec<String>,
+        repository: Option<String>,
+        no_push: bool,
+    ) -> Self {
+        Self {
+            file,
+            platforms,
+            repository,
+            no_push,
+        }
+    }
+}
+
+impl CallableTrait for BuildCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let project_dir = std::env::current_dir()?;
+        let config_path = project_dir.join(self.file.as_deref().unwrap_or(DEFAULT_CONFIG_FILE));
+        if !config_path.exists() {
+            return Err(Box::new(CliError::ConfigNotFound { path: config_path }));
+        }
+        let config = StackerConfig::from_file(&config_path)?.with_resolved_deploy_target(None)?;
+
+        let generated_dockerfile = ensure_generated_dockerfile(&project_dir, &config)?;
+        let repository = resolve_repository(&config, self.repository.as_deref())?;
+        let targets =
+            plan_build_targets(&project_dir, &config, &repository, &generated_dockerfile)?;
+        if targets.is_empty() {
+            return Err(Box::new(CliError::ConfigValidation(
+                "Nothing to build: app.image is set and the compose file has no build: services"
+                    .to_string(),
+            )));
+        }
+
+        let executor = ShellExecutor;
+        let (git_sha, dirty) = git_revision(&project_dir, &executor);
+        let version = config
+            .version
+            .clone()
+            .unwrap_or_else(|| "latest".to_string());
+        let options = BuildOptions {
+            platforms: self.platforms.clone(),
+            push: !self.no_push,
+            tags: image_tags(&version, git_sha.as_deref(), dirty),
+        };
+
+        if options.push {
+            registry_login(&config)?;
+        }
+
+        eprintln!("Building {} image(s):", targets.len());
+        for target in &targets {
+            eprintln!(
+                "  {} → {}:{{{}}}",
+                target.service,
+                target.repository,
+                options.tags.join(",")
+            );
+        }
+        if !options.platforms.is_empty() {
+            eprintln!("  Platforms: {}", options.platforms.join(", "));
+        }
+        if dirty {
+            eprintln!("  Warning: working tree has uncommitted changes (tagged -dirty)");
+        }
+
+        let mut record = BuildRecord::new(version, git_sha, &options);
+        run_builds(
+            &targets,
+            &options,
+            &project_dir.join(OUTPUT_DIR),
+            &executor,
+            &mut record,
+        )?;
+        let record_path = record.save(&project_dir)?;
+
+        for (service, image) in &record.images {
+            println!("✓ {} {}", service, image.reference());
+        }
+        eprintln!("  Build record: {}", record_path.display());
+        if record.pushed {
+            eprintln!("  `stacker deploy` to cloud/server targets will deploy these digests.");
+        }
+
+        Ok(())
+    }
+}
+
+/// The generated compose builds the app from `.stacker/Dockerfile`; make sure
+/// it exists so `stacker build` works before the first deploy.
+fn ensure_generated_dockerfile(
+    project_dir: &Path,
+    config: &StackerConfig,
+) -> Result<std::path::PathBuf, CliError> {
+    let path = project_dir.join(OUTPUT_DIR).join("Dockerfile");
+    let needs_dockerfile = config.deploy.compose_file.is_none()
+        && config.app.image.is_none()
+        && config.app.dockerfile.is_none();
+    if needs_dockerfile && !path.exists() {
+        std::fs::create_dir_all(project_dir.join(OUTPUT_DIR))?;
+        DockerfileBuilder::for_project(project_dir, config.app.app_type).write_to(&path, false)?;
+        eprintln!("  Generated {}", path.display());
+    }
+    Ok(path)
+}
+
+/// `docker login` with the deploy registry credentials, when configured.
+/// The password goes over stdin so it never appears in the process list.
+fn registry_login(config: &StackerConfig) -> Result<(), CliError> {
+    let creds = resolve_docker_registry_credentials(config);
+    let (Some(username), Some(password)) = (
+        creds.get("docker_username").and_then(|v| v.as_str()),
+        creds.get("docker_password").and_then(|v| v.as_str()),
+    ) else {
+        return Ok(());
+    };
+    let server = creds
+        .get("docker_registry")
+        .and_then(|v| v.as_str())
+        .unwrap_or("docker.io");
+
+    let mut child = Command::new("docker")
+        .args(["login", server, "--username", username, "--password-stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|_| CliError::ContainerRuntimeUnavailable)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(password.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(CliError::CommandFailed {
+            command: format!("docker login {server}"),
+            exit_code: output.status.code().unwrap_or(-1),
+        });
+    }
+    Ok(())
+}
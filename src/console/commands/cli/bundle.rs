@@ -0,0 +1,203 @@
+//! Air-gapped offline bundles.
+//!
+//! ```text
+//! stacker bundle export                      # writes ./<project>-bundle.tar.gz
+//! stacker bundle export --output dist/shop.tar.gz
+//! stacker bundle install shop-bundle.tar.gz --dir /opt/stacker
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::cli::acme;
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::error::CliError;
+use crate::cli::generator::compose::ComposeDefinition;
+use crate::cli::image_build::{pin_compose_images, BuildRecord};
+use crate::cli::install_runner::ShellExecutor;
+use crate::cli::offline_bundle::{
+    compose_project_name, export_offline_bundle, install_offline_bundle, BundleExport,
+    BUNDLE_INSTALL_SCRIPT,
+};
+use crate::cli::proxy_manager;
+use crate::console::commands::cli::status::resolve_project_name;
+use crate::console::commands::CallableTrait;
+
+const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
+const OUTPUT_DIR: &str = ".stacker";
+
+/// `stacker bundle export [--file <stacker.yml>] [--output <tar.gz>]`
+pub struct BundleExportCommand {
+    pub file: Option<String>,
+    pub output: Option<String>,
+}
+
+impl BundleExportCommand {
+    pub fn new(file: Option<String>, output: Option<String>) -> Self {
+        Self { file, output }
+    }
+}
+
+/// `stacker bundle install <tarball> [--dir <dir>] [--no-start]`
+pub struct BundleInstallCommand {
+    pub tarball: String,
+    pub dir: String,
+    pub no_start: bool,
+}
+
+impl BundleInstallCommand {
+    pub fn new(tarball: String, dir: String, no_start: bool) -> Self {
+        Self {
+            tarball,
+            dir,
+            no_start,
+        }
+    }
+}
+
+/// The compose to bundle, the base its bind mounts resolve against, and the
+/// generated proxy asset directories it mounts. Mirrors `stacker deploy`: a
+/// configured compose file is used as-is, otherwise `.stacker/` is rendered.
+fn resolve_bundle_compose(
+    project_dir: &Path,
+    config: &StackerConfig,
+) -> Result<(PathBuf, PathBuf, Vec<PathBuf>), CliError> {
+    if let Some(compose_file) = &config.deploy.compose_file {
+        let compose_path = project_dir.join(compose_file);
+        if !compose_path.exists() {
+            return Err(CliError::ConfigValidation(format!(
+                "Compose file not found: {}",
+                compose_path.display()
+            )));
+        }
+        let base = compose_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| project_dir.to_path_buf());
+        return Ok((compose_path, base, Vec::new()));
+    }
+
+    let output_dir = project_dir.join(OUTPUT_DIR);
+    std::fs::create_dir_all(&output_dir)?;
+    let compose_path = output_dir.join("docker-compose.yml");
+    if !compose_path.exists() {
+        ComposeDefinition::try_from(config)?.write_to(&compose_path, false)?;
+    }
+    acme::write_nginx_assets(&output_dir, config)?;
+    proxy_manager::write_caddy_assets(&output_dir, config)?;
+    let assets = [acme::NGINX_ASSETS_DIR, proxy_manager::CADDY_ASSETS_DIR]
+        .iter()
+        .map(PathBuf::from)
+        .filter(|dir| output_dir.join(dir).is_dir())
+        .collect();
+    Ok((compose_path, project_dir.to_path_buf(), assets))
+}
+
+/// Swap built services to the images `stacker build` pushed, as remote
+/// deploys do, so the bundle carries exactly what was built.
+fn pin_built_images(project_dir: &Path, compose_path: &Path) -> Result<PathBuf, CliError> {
+    let Some(record) = BuildRecord::load(project_dir)?.filter(|record| record.pushed) else {
+        return Ok(compose_path.to_path_buf());
+    };
+    let (pinned_yaml, pinned) =
+        pin_compose_images(&std::fs::read_to_string(compose_path)?, &record)?;
+    if pinned.is_empty() {
+        return Ok(compose_path.to_path_buf());
+    }
+    let target = compose_path.with_file_name(".docker-compose.bundle.yml");
+    std::fs::write(&target, pinned_yaml)?;
+    Ok(target)
+}
+
+impl CallableTrait for BundleExportCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let project_dir = std::env::current_dir()?;
+        let config_path = project_dir.join(self.file.as_deref().unwrap_or(DEFAULT_CONFIG_FILE));
+        let config = StackerConfig::from_file(&config_path)?.with_resolved_deploy_target(None)?;
+
+        let (compose_path, reference_base, proxy_assets) =
+            resolve_bundle_compose(&project_dir, &config)?;
+        let compose_path = pin_built_images(&project_dir, &compose_path)?;
+        let env_file = config
+            .env_file
+            .as_ref()
+            .map(|file| project_dir.join(file))
+            .unwrap_or_else(|| project_dir.join(".env"));
+        let project_name = resolve_project_name(&config);
+        let output = self.output.as_ref().map(PathBuf::from).unwrap_or_else(|| {
+            PathBuf::from(format!(
+                "{}-bundle.tar.gz",
+                compose_project_name(&project_name)
+            ))
+        });
+
+        eprintln!("Exporting offline bundle from {}", compose_path.display());
+        let manifest = export_offline_bundle(
+            &BundleExport {
+                project_dir: &project_dir,
+                project_name: &project_name,
+                compose_path: &compose_path,
+                reference_base: &reference_base,
+                env_file: Some(&env_file),
+                proxy_assets,
+                output: &output,
+            },
+            &ShellExecutor,
+        )?;
+
+        let size_mb = std::fs::metadata(&output)?.len() as f64 / (1024.0 * 1024.0);
+        println!("✓ Wrote {} ({:.1} MB)", output.display(), size_mb);
+        for image in &manifest.images {
+            println!(
+                "  {:<40} {} ({})",
+                image.reference,
+                &image.id[..image.id.len().min(19)],
+                image.services.join(", ")
+            );
+        }
+        if !manifest.required_files.is_empty() {
+            println!(
+                "  Not bundled (provide on the target): {}",
+                manifest.required_files.join(", ")
+            );
+        }
+        println!(
+            "\nOn the target: `stacker bundle install {}` or unpack it and run ./{}",
+            output.display(),
+            BUNDLE_INSTALL_SCRIPT
+        );
+        Ok(())
+    }
+}
+
+impl CallableTrait for BundleInstallCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let installed = install_offline_bundle(
+            Path::new(&self.tarball),
+            Path::new(&self.dir),
+            !self.no_start,
+            &ShellExecutor,
+        )?;
+
+        println!(
+            "✓ Verified and loaded {} image(s) into {}",
+            installed.manifest.images.len(),
+            installed.root.display()
+        );
+        if !installed.pending.is_empty() {
+            println!("  Fill in before starting:");
+            for file in &installed.pending {
+                println!("    {}", installed.root.join(file).display());
+            }
+            println!("  Then re-run `stacker bundle install {}`", self.tarball);
+        } else if self.no_start {
+            println!(
+                "  Start with: ./{} (in {})",
+                BUNDLE_INSTALL_SCRIPT,
+                installed.root.display()
+            );
+        } else {
+            println!("✓ Started compose project {}", installed.manifest.project);
+        }
+        Ok(())
+    }
+}
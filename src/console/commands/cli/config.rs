@@ -2,8 +2,10 @@ use std::collections::BTreeSet;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
+use crate::cli::acme::acme_issues;
 use crate::cli::cloud_env;
 use crate::cli::config_check::{check_inventory, load_check, ConfigCheckItem, ConfigCheckResult};
+use crate::cli::config_compose::{load_composed, render_origins};
 use crate::cli::config_contract::{suggest_contract_yaml, ContractSuggestOptions};
 use crate::cli::config_diff::{diff_inventories, load_diff, ConfigDiff, DiffItem};
 use crate::cli::config_inventory::{
@@ -19,6 +21,8 @@ use crate::cli::config_promote::{
 use crate::cli::debug::cli_debug_enabled;
 use crate::cli::deployment_lock::DeploymentLock;
 use crate::cli::error::CliError;
+use crate::cli::generator::kubernetes;
+use crate::cli::proxy_manager::{runtime_compatibility_issues, RuntimeKind};
 use crate::cli::runtime::CliRuntime;
 use crate::cli::stacker_client::ProjectAppInfo;
 use crate::console::commands::cli::init::full_config_reference_example;
@@ -363,7 +367,7 @@ pub fn run_generate_remote_payload(
         });
     }
 
-    let mut config = StackerConfig::from_file_raw(path)?;
+    let (mut config, layer) = StackerConfig::from_file_layer(path)?;
     let config_dir = path.parent().unwrap_or_else(|| Path::new("."));
 
     let output_path = match output {
@@ -476,7 +480,8 @@ pub fn run_generate_remote_payload(
 
     let backup_path = format!("{}.bak", config_path);
     std::fs::copy(config_path, &backup_path)?;
-    let yaml = serde_yaml::to_string(&config)
+    let yaml = layer
+        .render(&config)
         .map_err(|e| CliError::ConfigValidation(format!("Failed to serialize config: {}", e)))?;
     std::fs::write(config_path, yaml)?;
 
@@ -551,7 +556,7 @@ pub fn run_setup_ai(
         });
     }
 
-    let mut config = StackerConfig::from_file_raw(path)?;
+    let (mut config, layer) = StackerConfig::from_file_layer(path)?;
     let interactive = options.provider.is_none()
         && options.endpoint.is_none()
         && options.model.is_none()
@@ -647,7 +652,8 @@ pub fn run_setup_ai(
 
     let backup_path = format!("{}.bak", config_path);
     std::fs::copy(config_path, &backup_path)?;
-    let yaml = serde_yaml::to_string(&config)
+    let yaml = layer
+        .render(&config)
         .map_err(|e| CliError::ConfigValidation(format!("Failed to serialize config: {}", e)))?;
     std::fs::write(config_path, yaml)?;
 
@@ -666,7 +672,7 @@ pub fn run_setup_cloud_interactive(config_path: &str) -> Result<Vec<String>, Cli
         });
     }
 
-    let mut config = StackerConfig::from_file_raw(path)?;
+    let (mut config, layer) = StackerConfig::from_file_layer(path)?;
     let mut applied = Vec::new();
 
     eprintln!("Cloud setup wizard:");
@@ -731,7 +737,8 @@ pub fn run_setup_cloud_interactive(config_path: &str) -> Result<Vec<String>, Cli
     let backup_path = format!("{}.bak", config_path);
     std::fs::copy(config_path, &backup_path)?;
 
-    let yaml = serde_yaml::to_string(&config)
+    let yaml = layer
+        .render(&config)
         .map_err(|e| CliError::ConfigValidation(format!("Failed to serialize config: {}", e)))?;
     std::fs::write(config_path, yaml)?;
 
@@ -758,8 +765,8 @@ pub fn run_fix_interactive(config_path: &str) -> Result<Vec<String>, CliError> {
         return Ok(raw_applied);
     }
 
-    let mut config = match StackerConfig::from_file_raw(path) {
-        Ok(config) => config,
+    let (mut config, layer) = match StackerConfig::from_file_layer(path) {
+        Ok(loaded) => loaded,
         Err(CliError::ConfigParseFailed { .. }) => {
             let issues = load_raw_path_issues(path)?;
             if !issues.is_empty() {
@@ -923,7 +930,8 @@ pub fn run_fix_interactive(config_path: &str) -> Result<Vec<String>, CliError> {
     let backup_path = format!("{}.bak", config_path);
     std::fs::copy(config_path, &backup_path)?;
 
-    let yaml = serde_yaml::to_string(&config)
+    let yaml = layer
+        .render(&config)
         .map_err(|e| CliError::ConfigValidation(format!("Failed to serialize config: {}", e)))?;
     std::fs::write(config_path, yaml)?;
 
@@ -960,6 +968,38 @@ pub fn run_validate(config_path: &str) -> Result<Vec<String>, CliError> {
     let config = StackerConfig::from_file(path)?;
     let issues = config.validate_semantics();
     messages.extend(issues.iter().map(|i| format!("{:?}", i)));
+    messages.extend(
+        runtime_compatibility_issues(&config, RuntimeKind::resolve()?)
+            .iter()
+            .map(|issue| issue.to_string()),
+    );
+    messages.extend(acme_issues(&config).iter().map(|issue| issue.to_string()));
+    Ok(messages)
+}
+
+/// Validate and additionally report features that cannot be mapped onto
+/// a non-Docker target (`--target k8s`).
+pub fn run_validate_for_target(
+    config_path: &str,
+    target: Option<&str>,
+) -> Result<Vec<String>, CliError> {
+    let mut messages = run_validate(config_path)?;
+    match target.map(|t| t.to_lowercase()) {
+        None => {}
+        Some(t) if matches!(t.as_str(), "k8s" | "kubernetes" | "helm") => {
+            let config = StackerConfig::from_file(Path::new(config_path))?;
+            messages.extend(
+                kubernetes::unsupported_features(&config)
+                    .iter()
+                    .map(|issue| issue.to_string()),
+            );
+        }
+        Some(other) => {
+            return Err(CliError::ConfigValidation(format!(
+                "Unknown validation target '{other}'. Supported: k8s"
+            )));
+        }
+    }
     Ok(messages)
 }
 
@@ -986,7 +1026,8 @@ pub fn run_show_resolved(config_path: &str) -> Result<String, CliError> {
         });
     }
 
-    let config = StackerConfig::from_file(path)?;
+    let composed = load_composed(path)?;
+    let config = StackerConfig::from_composed(path, composed.clone())?;
     let config_dir = path.parent().unwrap_or_else(|| Path::new("."));
     let local_env_file = config
         .resolve_environment_config(None)?
@@ -1007,14 +1048,23 @@ pub fn run_show_resolved(config_path: &str) -> Result<String, CliError> {
         .collect::<Vec<_>>()
         .join("\n");
 
+    let sources = composed
+        .sources
+        .iter()
+        .map(|source| format!("    - {}", source))
+        .collect::<Vec<_>>()
+        .join("\n");
+
     Ok(format!(
-        "resolved_config:\n  local_env_file: {}\n  remote_runtime_env_file: {}\n  compose_env_file: {}\n  config_version: local\n  config_hash: unavailable_until_deploy\n  runtime_env_contract_version: {}\n  runtime_env_contract_order: {}\n  layers:\n{}\n",
+        "resolved_config:\n  local_env_file: {}\n  remote_runtime_env_file: {}\n  compose_env_file: {}\n  config_version: local\n  config_hash: unavailable_until_deploy\n  runtime_env_contract_version: {}\n  runtime_env_contract_order: {}\n  layers:\n{}\n  config_sources:\n{}\n  values:\n{}\n",
         local_env_file,
         remote_runtime_env_path(),
         compose_env_file_reference(),
         runtime_env_contract.version,
         runtime_env_contract.order,
-        layers
+        layers,
+        sources,
+        render_origins(&composed)
     ))
 }
 
@@ -1026,23 +1076,30 @@ fn resolve_display_path(config_dir: &Path, env_file: &Path) -> String {
     }
 }
 
-/// `stacker config validate [--file stacker.yml]`
+/// `stacker config validate [--file stacker.yml] [--target k8s]`
 ///
 /// Validates a stacker.yml configuration file.
 pub struct ConfigValidateCommand {
     pub file: Option<String>,
+    pub target: Option<String>,
 }
 
 impl ConfigValidateCommand {
     pub fn new(file: Option<String>) -> Self {
-        Self { file }
+        Self { file, target: None }
+    }
+
+    /// Also report features unsupported by an export target (e.g. `k8s`).
+    pub fn with_target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
     }
 }
 
 impl CallableTrait for ConfigValidateCommand {
     fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
         let path = resolve_config_path(&self.file);
-        let issues = run_validate(&path)?;
+        let issues = run_validate_for_target(&path, self.target.as_deref())?;
 
         if issues.is_empty() {
             eprintln!("✓ Configuration is valid");
@@ -2156,6 +2213,18 @@ mod tests {
         assert!(result.len() < 5);
     }
 
+    #[test]
+    fn test_validate_target_k8s_reports_unmappable_features() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_config(dir.path(), minimal_config_yaml());
+
+        let plain = run_validate(&path).unwrap();
+        let k8s = run_validate_for_target(&path, Some("k8s")).unwrap();
+        assert!(k8s.len() > plain.len());
+        assert!(k8s.iter().any(|line| line.contains("K8S001")));
+        assert!(run_validate_for_target(&path, Some("nomad")).is_err());
+    }
+
     #[test]
     fn test_validate_missing_file_returns_error() {
         let result = run_validate("/nonexistent/stacker.yml");
@@ -2193,6 +2262,23 @@ app:
         assert!(yaml.contains("test-app"));
     }
 
+    #[test]
+    fn test_show_resolved_reports_value_origins_for_extends() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("base.yml"),
+            "name: base\nproxy:\n  type: nginx\n",
+        )
+        .unwrap();
+        let path = write_config(dir.path(), "extends: base.yml\nname: child\n");
+
+        let output = run_show_resolved(&path).unwrap();
+        assert!(output.contains("config_sources:"));
+        assert!(output.contains("base.yml"));
+        assert!(output.contains("proxy.type: nginx  #"));
+        assert!(output.contains("name: child  #"));
+    }
+
     #[test]
     fn test_show_missing_file_returns_error() {
         let result = run_show("/nonexistent/stacker.yml");
@@ -3,9 +3,11 @@ use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use crate::cli::acme;
 use crate::cli::ai_client::{
     build_prompt, create_provider, ollama_complete_streaming, AiTask, PromptContext,
 };
+use crate::cli::auto_rollback::{self, DeployBundle};
 use crate::cli::cloud_env;
 #[cfg(test)]
 use crate::cli::compose_targets::extract_compose_secret_target_services;
@@ -15,20 +17,26 @@ use crate::cli::config_parser::{
     ServerConfig, StackerConfig,
 };
 use crate::cli::credentials::{CredentialStore, CredentialsManager, StoredCredentials};
+use crate::cli::deploy_lease::{self, DeployLeaseGuard};
 use crate::cli::deployment_lock::DeploymentLock;
 use crate::cli::error::CliError;
 use crate::cli::generator::compose::ComposeDefinition;
 use crate::cli::generator::dockerfile::DockerfileBuilder;
+use crate::cli::image_build::{git_revision, pin_compose_images, BuildRecord};
 use crate::cli::install_runner::{
-    resolve_docker_registry_credentials, strategy_for, CommandExecutor, DeployContext,
-    DeployResult, HookPolicy, ShellExecutor,
+    resolve_compose_cmd, resolve_docker_registry_credentials, strategy_for, CommandExecutor,
+    DeployContext, DeployResult, HookPolicy, ShellExecutor,
 };
+use crate::cli::placement::{self, PlacementHost};
 use crate::cli::progress;
+use crate::cli::proxy_manager;
+use crate::cli::release_record;
 use crate::cli::stacker_client::{self, StackerClient};
 use crate::console::commands::CallableTrait;
 use crate::helpers::ip::extract_ipv4_from_text;
 use crate::helpers::security_validator::validate_shell_scripts;
 use crate::helpers::ssh_client;
+use crate::services::release::ReleaseRecord;
 
 /// Default config filename.
 const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
@@ -2187,6 +2195,12 @@ pub struct DeployCommand {
     /// Run hooks even when the stacker.yml is marketplace-generated
     /// (--allow-untrusted-hooks). No-op for user-authored files.
     pub allow_untrusted_hooks: bool,
+    /// Gate remote deploys on app health and roll failed apps back (--auto-rollback).
+    pub auto_rollback: bool,
+    /// Seconds to watch app health with --auto-rollback (--health-window).
+    pub health_window: u64,
+    /// Break another client's deploy lease (--force).
+    pub force_lease: bool,
 }
 
 impl DeployCommand {
@@ -2215,6 +2229,9 @@ impl DeployCommand {
             apply_plan: None,
             no_hooks: false,
             allow_untrusted_hooks: false,
+            auto_rollback: false,
+            health_window: auto_rollback::DEFAULT_HEALTH_WINDOW_SECS,
+            force_lease: false,
         }
     }
 
@@ -2312,12 +2329,65 @@ impl DeployCommand {
         self
     }
 
+    /// Builder method for --auto-rollback / --health-window.
+    pub fn with_auto_rollback(mut self, auto_rollback: bool, health_window: u64) -> Self {
+        self.auto_rollback = auto_rollback;
+        self.health_window = health_window;
+        self
+    }
+
+    /// Builder method for --force (break another client's deploy lease).
+    pub fn with_force_lease(mut self, force: bool) -> Self {
+        self.force_lease = force;
+        self
+    }
+
+    fn lease_ttl_secs(&self) -> i64 {
+        let gate = if self.auto_rollback {
+            self.health_window
+        } else {
+            0
+        };
+        deploy_lease::DEPLOY_LEASE_TTL_SECS + gate as i64
+    }
+
+    /// Take the deploy lease before a full remote deploy. A first deploy has
+    /// no deployment hash yet and a logged-out run fails later anyway, so
+    /// both go ahead without a lease.
+    fn acquire_deploy_lease(
+        &self,
+        project_dir: &Path,
+    ) -> Result<Option<DeployLeaseGuard>, CliError> {
+        use crate::console::commands::cli::agent::resolve_deployment_hash;
+
+        if self.dry_run
+            || self.target.as_deref() == Some("local")
+            || !crate::console::commands::cli::status::is_remote_deployment(project_dir)
+        {
+            return Ok(None);
+        }
+        let Ok(ctx) = crate::cli::runtime::CliRuntime::new("deploy") else {
+            return Ok(None);
+        };
+        let Ok(hash) = resolve_deployment_hash(&None, &ctx) else {
+            return Ok(None);
+        };
+        DeployLeaseGuard::acquire(
+            &hash,
+            "stacker deploy",
+            self.lease_ttl_secs(),
+            self.force_lease,
+        )
+        .map(Some)
+    }
+
     /// Surgical single-service deploy: read local compose, inject the named service into the
     /// remote deployment's compose, and start only that container.
     fn deploy_single_service(&self, service: &str) -> Result<(), Box<dyn std::error::Error>> {
         use crate::cli::stacker_client::AgentEnqueueRequest;
         use crate::console::commands::cli::agent::{
-            resolve_deployment_hash, resolve_registry_auth_for_agent_deploy, run_agent_command,
+            print_deploy_phases, resolve_blue_green_for_agent_deploy, resolve_deployment_hash,
+            resolve_registry_auth_for_agent_deploy, run_agent_command,
         };
 
         let project_dir = std::env::current_dir()?;
@@ -2330,16 +2400,7 @@ impl DeployCommand {
             None
         };
 
-        // Find compose file: prefer deploy.compose_file from stacker.yml, else default.
-        let compose_path = stacker_config
-            .as_ref()
-            .and_then(|c| {
-                c.deploy
-                    .compose_file
-                    .as_deref()
-                    .map(|f| project_dir.join(f))
-            })
-            .unwrap_or_else(|| project_dir.join("docker-compose.yml"));
+        let compose_path = service_deploy_compose_path(&project_dir, stacker_config.as_ref());
 
         if !compose_path.exists() {
             return Err(Box::new(CliError::ConfigValidation(format!(
@@ -2392,6 +2453,12 @@ impl DeployCommand {
 
         let ctx = crate::cli::runtime::CliRuntime::new("deploy")?;
         let hash = resolve_deployment_hash(&None, &ctx)?;
+        let lease = DeployLeaseGuard::acquire(
+            &hash,
+            &format!("stacker deploy {}", service),
+            self.lease_ttl_secs(),
+            self.force_lease,
+        )?;
 
         let params = crate::forms::status_panel::DeployAppCommandRequest {
             app_code: service.to_string(),
@@ -2404,20 +2471,53 @@ impl DeployCommand {
             runtime: self.runtime.clone(),
             registry_auth: resolve_registry_auth_for_agent_deploy(&project_dir),
             config_files: None,
+            blue_green: resolve_blue_green_for_agent_deploy(&project_dir),
+            rollout: None,
+            compose_project: None,
         };
 
         let request = AgentEnqueueRequest::new(&hash, "deploy_app")
             .with_parameters(&params)
             .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?
-            .with_timeout(300);
+            .with_timeout(300)
+            .with_lease(Some(lease.lease_id()));
 
         let info = run_agent_command(&ctx, &request, &format!("Deploying {}", service), 300)?;
-        if let Some(output) = info.result.as_ref().and_then(|r| r.as_str()) {
-            if !output.is_empty() {
-                println!("{}", output);
+        if let Some(result) = info.result.as_ref() {
+            if !print_deploy_phases(result) {
+                if let Some(output) = result.as_str().filter(|output| !output.is_empty()) {
+                    println!("{}", output);
+                }
             }
         }
 
+        if self.auto_rollback {
+            let shipped = params.compose_content.clone().unwrap_or_default();
+            let candidates = DeployBundle::for_service(service, &hash, &shipped, None)?
+                .into_iter()
+                .collect();
+            run_auto_rollback_gate(
+                &ctx,
+                &project_dir,
+                DeployTarget::Server,
+                candidates,
+                self.health_window,
+                &self.runtime,
+                Some(lease.lease_id()),
+            )?;
+
+            // The gate passed: record the compose this deploy_app shipped,
+            // tied to the command so the server can check it.
+            let stacker_yml = std::fs::read_to_string(&stacker_config_path).unwrap_or_default();
+            let compose_dir = compose_path.parent().unwrap_or(project_dir.as_path());
+            let record = release_record::build_release_record(&shipped, compose_dir, &stacker_yml)
+                .map(|record| ReleaseRecord {
+                    command_id: Some(info.command_id.clone()),
+                    ..record
+                });
+            record_release(&ctx, &hash, record);
+        }
+
         // Register the service as a tracked app in the project.
         let config_path = project_dir.join(DEFAULT_CONFIG_FILE);
         if config_path.exists() {
@@ -2860,6 +2960,57 @@ pub fn run_deploy_for_environment_with_policy(
     )
 }
 
+/// A full-project remote deploy runs the install playbook, which recreates
+/// containers; blue/green rollouts are done by the agent's `deploy_app`. Only
+/// the first install, with no old set to switch from, may take that path.
+fn check_full_deploy_strategy(
+    config: &StackerConfig,
+    deploy_target: DeployTarget,
+    project_dir: &Path,
+    force_new: bool,
+) -> Result<(), CliError> {
+    if config.deploy.strategy != crate::cli::config_parser::DeployStrategy::BlueGreen
+        || deploy_target == DeployTarget::Local
+        || force_new
+    {
+        return Ok(());
+    }
+    let target = deploy_target.to_string();
+    let deployed = config.deploy.deployment_hash.is_some()
+        || DeploymentLock::exists_for_target(project_dir, &target);
+    if !deployed {
+        eprintln!(
+            "  ℹ deploy.strategy blue_green: the first install recreates containers; later rollouts use `stacker deploy --service <name>`"
+        );
+        return Ok(());
+    }
+    let services = std::fs::read_to_string(service_deploy_compose_path(project_dir, Some(config)))
+        .ok()
+        .and_then(|compose| auto_rollback::compose_app_codes(&compose).ok())
+        .filter(|services| !services.is_empty())
+        .unwrap_or_else(|| vec!["<name>".to_string()]);
+    let commands: Vec<String> = services
+        .iter()
+        .map(|service| format!("  stacker deploy --service {}", service))
+        .collect();
+    Err(CliError::ConfigValidation(format!(
+        "deploy.strategy blue_green cannot be honoured by a full {} redeploy, which recreates containers.\n\
+         Roll out each service instead:\n{}\n\
+         Or set deploy.strategy: recreate to redeploy the whole project.",
+        target,
+        commands.join("\n")
+    )))
+}
+
+/// Compose file `stacker deploy --service` reads: `deploy.compose_file`, else
+/// `docker-compose.yml` in the project directory.
+fn service_deploy_compose_path(project_dir: &Path, config: Option<&StackerConfig>) -> PathBuf {
+    config
+        .and_then(|c| c.deploy.compose_file.as_deref())
+        .map(|f| project_dir.join(f))
+        .unwrap_or_else(|| project_dir.join("docker-compose.yml"))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn run_deploy_with_credentials_manager<S: CredentialStore>(
     project_dir: &Path,
@@ -3009,6 +3160,8 @@ fn run_deploy_with_credentials_manager<S: CredentialStore>(
         }
     }
 
+    check_full_deploy_strategy(&config, deploy_target, project_dir, force_new)?;
+
     // 3. Cloud/server prerequisites — verify login and keep credentials for later use.
     let cloud_creds: Option<StoredCredentials> =
         if matches!(deploy_target, DeployTarget::Cloud | DeployTarget::Server) {
@@ -3210,6 +3363,10 @@ fn run_deploy_with_credentials_manager<S: CredentialStore>(
                     OUTPUT_DIR
                 );
             }
+            // nginx server blocks, ACME bootstrap scripts and the Caddyfile the
+            // generated proxy service mounts; always refreshed from stacker.yml.
+            acme::write_nginx_assets(&output_dir, &config)?;
+            proxy_manager::write_caddy_assets(&output_dir, &config)?;
             (compose_out, false)
         };
 
@@ -3219,6 +3376,12 @@ fn run_deploy_with_credentials_manager<S: CredentialStore>(
         validate_cross_source_port_collisions(&config, &compose_path)?;
     }
     ensure_compose_env_files_if_needed(&compose_path)?;
+    let compose_path = if matches!(deploy_target, DeployTarget::Cloud | DeployTarget::Server) {
+        let pinned = pin_built_images_for_deploy(project_dir, &compose_path, executor)?;
+        place_primary_compose(&config, target_override, &pinned)?
+    } else {
+        compose_path
+    };
     let image_env = build_image_env_lookup(project_dir, &config)?;
     merge_compose_public_ports_into_app_config(&mut config, &compose_path, &image_env)?;
     if matches!(deploy_target, DeployTarget::Cloud | DeployTarget::Server) {
@@ -3486,6 +3649,8 @@ impl CallableTrait for DeployCommand {
             server_name: self.server_name.clone(),
         };
 
+        let lease = self.acquire_deploy_lease(&project_dir)?;
+
         // ── Spinner while deploying ──────────────────
         let spin = progress::deploy_spinner("starting...");
 
@@ -3581,6 +3746,18 @@ impl CallableTrait for DeployCommand {
 
         // ── Deployment lock: persist deployment context ──
         self.save_deployment_lock(&project_dir, &result, should_fetch_remote_details)?;
+
+        if result.target != DeployTarget::Local {
+            if should_fetch_remote_details {
+                self.finish_remote_deploy(
+                    &project_dir,
+                    &result,
+                    lease.as_ref().map(DeployLeaseGuard::lease_id),
+                )?;
+            } else if self.auto_rollback {
+                eprintln!("  ⚠ Deployment failed before the health gate; nothing to roll back to automatically.");
+            }
+        }
         if should_fetch_remote_details && should_install_cloud_backup_key(&result, self.dry_run) {
             self.install_cloud_backup_key(&result);
         }
@@ -3589,6 +3766,347 @@ impl CallableTrait for DeployCommand {
     }
 }
 
+/// What a full remote deploy shipped: the compose with the same image pins
+/// `pin_built_images_for_deploy` applies, and the stacker.yml it came from.
+struct DeployedBundle {
+    compose_content: String,
+    compose_dir: PathBuf,
+    stacker_yml: String,
+    project_name: String,
+    /// Hosts of placed services with their slice of the compose.
+    peers: Vec<(PlacementHost, String)>,
+}
+
+impl DeployCommand {
+    fn deployed_bundle(&self, project_dir: &Path) -> Result<DeployedBundle, CliError> {
+        let config_path = match &self.file {
+            Some(file) => project_dir.join(file),
+            None => project_dir.join(DEFAULT_CONFIG_FILE),
+        };
+        let stacker_yml = std::fs::read_to_string(&config_path)?;
+        let config = StackerConfig::from_file(&config_path)?
+            .with_resolved_deploy_target(self.target.as_deref())?;
+        let compose_path = config
+            .deploy
+            .compose_file
+            .as_ref()
+            .map(|file| project_dir.join(file))
+            .filter(|path| path.exists())
+            .unwrap_or_else(|| project_dir.join(OUTPUT_DIR).join("docker-compose.yml"));
+
+        let mut compose_content = std::fs::read_to_string(&compose_path)?;
+        if let Some(record) = BuildRecord::load(project_dir)?.filter(|record| record.pushed) {
+            let (pinned_yaml, pinned) = pin_compose_images(&compose_content, &record)?;
+            if !pinned.is_empty() {
+                compose_content = pinned_yaml;
+            }
+        }
+
+        let mut peers = Vec::new();
+        if let Some(primary) = config.deploy.target_name(self.target.as_deref()) {
+            if let Some(plan) = placement::plan_placement(&config, &primary)? {
+                let mut split = placement::split_compose(&compose_content, &plan)?.into_iter();
+                if let Some((_, primary_slice)) = split.next() {
+                    compose_content = primary_slice;
+                }
+                peers = plan
+                    .peers()
+                    .iter()
+                    .cloned()
+                    .zip(split.map(|(_, slice)| slice))
+                    .collect();
+            }
+        }
+
+        Ok(DeployedBundle {
+            compose_content,
+            compose_dir: compose_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| project_dir.to_path_buf()),
+            stacker_yml,
+            project_name: crate::console::commands::cli::status::resolve_project_name(&config),
+            peers,
+        })
+    }
+
+    /// Ship every placed host its slice of the compose through its own
+    /// agent, holding that host's deploy lease for the duration.
+    fn deploy_placement_peers(
+        &self,
+        project_dir: &Path,
+        bundle: &DeployedBundle,
+    ) -> Result<(), CliError> {
+        use crate::cli::stacker_client::AgentEnqueueRequest;
+        use crate::console::commands::cli::agent::{
+            resolve_blue_green_for_agent_deploy, resolve_registry_auth_for_agent_deploy,
+            run_agent_command,
+        };
+
+        if bundle.peers.is_empty() {
+            return Ok(());
+        }
+        let ctx = crate::cli::runtime::CliRuntime::new("deploy")?;
+        for (host, compose_content) in &bundle.peers {
+            let hash = host.deployment_hash.as_deref().ok_or_else(|| {
+                CliError::ConfigValidation(format!(
+                    "deploy.targets.{} needs a deployment_hash to run {}",
+                    host.target,
+                    host.services.join(", ")
+                ))
+            })?;
+            let lease = DeployLeaseGuard::acquire(
+                hash,
+                &format!("stacker deploy (placement {})", host.target),
+                self.lease_ttl_secs(),
+                self.force_lease,
+            )?;
+            let params = crate::forms::status_panel::DeployAppCommandRequest {
+                app_code: bundle.project_name.clone(),
+                compose_content: Some(compose_content.clone()),
+                image: None,
+                env_vars: None,
+                pull: true,
+                force_recreate: false,
+                force_config_overwrite: false,
+                runtime: self.runtime.clone(),
+                registry_auth: resolve_registry_auth_for_agent_deploy(project_dir),
+                config_files: None,
+                blue_green: resolve_blue_green_for_agent_deploy(project_dir),
+                rollout: None,
+                compose_project: None,
+            };
+            let request = AgentEnqueueRequest::new(hash, "deploy_app")
+                .with_parameters(&params)
+                .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?
+                .with_timeout(300)
+                .with_lease(Some(lease.lease_id()));
+            run_agent_command(
+                &ctx,
+                &request,
+                &format!("Deploying {} on {}", host.services.join(", "), host.target),
+                300,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// After a successful remote deploy: ship placed services to their hosts,
+    /// run the `--auto-rollback` health gate when requested, then record the
+    /// deploy as a release.
+    fn finish_remote_deploy(
+        &self,
+        project_dir: &Path,
+        result: &DeployResult,
+        lease_id: Option<&str>,
+    ) -> Result<(), CliError> {
+        let bundle = self.deployed_bundle(project_dir)?;
+        self.deploy_placement_peers(project_dir, &bundle)?;
+        let ctx = crate::cli::runtime::CliRuntime::new("deploy")?;
+        let hash = match crate::console::commands::cli::agent::resolve_deployment_hash(&None, &ctx)
+        {
+            Ok(hash) => hash,
+            Err(err) if !self.auto_rollback => {
+                eprintln!("  ⚠ Release not recorded: {}", err);
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
+        if self.auto_rollback {
+            let candidates = auto_rollback::compose_app_codes(&bundle.compose_content)?
+                .iter()
+                .filter_map(|app_code| {
+                    DeployBundle::for_service(app_code, &hash, &bundle.compose_content, None)
+                        .transpose()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            run_auto_rollback_gate(
+                &ctx,
+                project_dir,
+                result.target,
+                candidates,
+                self.health_window,
+                &self.runtime,
+                lease_id,
+            )?;
+        }
+
+        // A full install carries no deploy_app command, so the server accepts
+        // the release once the deployment itself reports completed.
+        let record = release_record::build_release_record(
+            &bundle.compose_content,
+            &bundle.compose_dir,
+            &bundle.stacker_yml,
+        );
+        record_release(&ctx, &hash, record);
+        Ok(())
+    }
+}
+
+/// Record a successful deploy as a release. The deploy already succeeded, so
+/// failing to record it only warns.
+fn record_release(
+    ctx: &crate::cli::runtime::CliRuntime,
+    hash: &str,
+    record: Result<ReleaseRecord, CliError>,
+) {
+    let recorded =
+        record.and_then(|record| ctx.block_on(ctx.client.record_deployment_release(hash, &record)));
+    match recorded {
+        Ok(release) => eprintln!(
+            "  ✓ Recorded release {} (see `stacker releases list`)",
+            release.version
+        ),
+        Err(err) => eprintln!("  ⚠ Release not recorded: {}", err),
+    }
+}
+
+/// `--auto-rollback`: sample each app's health over the window. A failed app
+/// is re-deployed through `deploy_app` with its service from the deployment's
+/// latest release on the server; a passing deploy is recorded as the next
+/// release by the caller. Every check and rollback carries a `rollout`
+/// marker, so the steps show up in `stacker deployment events`.
+fn run_auto_rollback_gate(
+    ctx: &crate::cli::runtime::CliRuntime,
+    project_dir: &Path,
+    target: DeployTarget,
+    candidates: Vec<DeployBundle>,
+    window_secs: u64,
+    runtime: &str,
+    lease_id: Option<&str>,
+) -> Result<(), CliError> {
+    use crate::cli::stacker_client::AgentEnqueueRequest;
+    use crate::console::commands::cli::agent::{
+        resolve_blue_green_for_agent_deploy, resolve_registry_auth_for_agent_deploy,
+        run_agent_command,
+    };
+    use crate::forms::status_panel::{DeployAppCommandRequest, HealthCommandRequest, RolloutStep};
+
+    let Some(hash) = candidates.first().map(|c| c.deployment_hash.clone()) else {
+        return Ok(());
+    };
+    let gate = auto_rollback::HealthGate::new(window_secs);
+    eprintln!(
+        "  Watching health of {} app(s) for {}s (--auto-rollback)",
+        candidates.len(),
+        gate.window.as_secs()
+    );
+
+    let mut tracker = auto_rollback::GateTracker::default();
+    let mut last: std::collections::BTreeMap<_, _> = candidates
+        .iter()
+        .map(|c| (c.app_code.clone(), auto_rollback::HealthSample::Starting))
+        .collect();
+    let started = std::time::Instant::now();
+    'window: for _ in 0..gate.samples() {
+        std::thread::sleep(gate.interval);
+        for candidate in &candidates {
+            if tracker.is_failed(&candidate.app_code) {
+                continue;
+            }
+            // Probes run one after another; none may outlast the window.
+            let Some(timeout) = gate.probe_timeout(started.elapsed()) else {
+                break 'window;
+            };
+            let timeout = timeout.as_secs().max(1);
+            let params = HealthCommandRequest {
+                app_code: candidate.app_code.clone(),
+                container: None,
+                include_metrics: false,
+                include_system: false,
+                rollout: Some(RolloutStep::HealthGate),
+            };
+            let request = AgentEnqueueRequest::new(&hash, "health")
+                .with_parameters(&params)
+                .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?
+                .with_timeout(timeout as i32);
+            let result = run_agent_command(
+                ctx,
+                &request,
+                &format!("Health gate: {}", candidate.app_code),
+                timeout,
+            )
+            .ok()
+            .and_then(|info| info.result);
+            let sample = auto_rollback::classify_health(result.as_ref());
+            tracker.record(&candidate.app_code, sample);
+            last.insert(candidate.app_code.clone(), sample);
+        }
+    }
+    tracker.finish(&last);
+
+    let failed = tracker.failed_apps();
+    if failed.is_empty() {
+        eprintln!("  ✓ All apps healthy");
+        return Ok(());
+    }
+
+    let releases = ctx
+        .block_on(ctx.client.list_deployment_releases(&hash))
+        .unwrap_or_else(|err| {
+            eprintln!("  ⚠ Could not load releases to roll back to: {}", err);
+            Vec::new()
+        });
+    let mut rolled_back = Vec::new();
+    for candidate in candidates.iter().filter(|c| failed.contains(&c.app_code)) {
+        let Some(previous) = auto_rollback::rollback_source(&releases, candidate) else {
+            eprintln!(
+                "  ✗ {} is unhealthy and has no earlier release to roll back to; leaving it as deployed",
+                candidate.app_code
+            );
+            continue;
+        };
+
+        let params = DeployAppCommandRequest {
+            app_code: candidate.app_code.clone(),
+            compose_content: Some(previous.compose_content.clone()),
+            image: None,
+            env_vars: None,
+            config_files: previous.config_files.clone(),
+            pull: true,
+            force_recreate: true,
+            force_config_overwrite: true,
+            runtime: runtime.to_string(),
+            registry_auth: resolve_registry_auth_for_agent_deploy(project_dir),
+            blue_green: resolve_blue_green_for_agent_deploy(project_dir),
+            rollout: Some(RolloutStep::Rollback),
+            compose_project: None,
+        };
+        let request = AgentEnqueueRequest::new(&hash, "deploy_app")
+            .with_parameters(&params)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?
+            .with_timeout(300)
+            .with_lease(lease_id);
+        run_agent_command(
+            ctx,
+            &request,
+            &format!("Rolling back {}", candidate.app_code),
+            300,
+        )?;
+        eprintln!(
+            "  ↩ {} rolled back to release {} (recorded {})",
+            candidate.app_code,
+            previous.release_version.unwrap_or_default(),
+            previous.recorded_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        rolled_back.push(candidate.app_code.clone());
+    }
+
+    Err(CliError::DeployFailed {
+        target,
+        reason: if rolled_back.is_empty() {
+            format!("health gate failed for {}", failed.join(", "))
+        } else {
+            format!(
+                "health gate failed for {}; rolled back {}",
+                failed.join(", "),
+                rolled_back.join(", ")
+            )
+        },
+    })
+}
+
 fn should_install_cloud_backup_key(result: &DeployResult, dry_run: bool) -> bool {
     !dry_run && result.target == DeployTarget::Cloud && result.project_id.is_some()
 }
@@ -4121,9 +4639,12 @@ fn watch_local_containers(
 
     let spin = progress::spinner("Checking container health...");
 
+    let (compose_cmd, prefix) = resolve_compose_cmd(&executor)?;
+    let mut args: Vec<&str> = prefix;
+    args.extend(["-f", &compose_str, "ps", "--format", "json"]);
+
     loop {
-        let args = vec!["compose", "-f", &compose_str, "ps", "--format", "json"];
-        if let Ok(output) = executor.execute("docker", &args) {
+        if let Ok(output) = executor.execute(compose_cmd, &args) {
             if output.success() {
                 let stdout = output.stdout.trim();
                 if !stdout.is_empty() {
@@ -4158,6 +4679,90 @@ fn watch_local_containers(
     }
 }
 
+/// Point buildable compose services at the digests pushed by `stacker build`
+/// so remote targets run exactly those images instead of rebuilding.
+///
+/// The source compose is left untouched (local deploys keep building); a
+/// pinned copy is written next to it so relative bind-mount paths keep
+/// resolving.
+fn pin_built_images_for_deploy(
+    project_dir: &Path,
+    compose_path: &Path,
+    executor: &dyn CommandExecutor,
+) -> Result<PathBuf, CliError> {
+    let Some(record) = BuildRecord::load(project_dir)? else {
+        return Ok(compose_path.to_path_buf());
+    };
+    if !record.pushed {
+        eprintln!("  Build record was not pushed (--no-push); ignoring it for this deploy");
+        return Ok(compose_path.to_path_buf());
+    }
+
+    let content = std::fs::read_to_string(compose_path)?;
+    let (pinned_yaml, pinned) = pin_compose_images(&content, &record)?;
+    if pinned.is_empty() {
+        return Ok(compose_path.to_path_buf());
+    }
+
+    let stem = compose_path
+        .file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "docker-compose".to_string());
+    let target_path = compose_path.with_file_name(format!(".{}.pinned.yml", stem));
+    std::fs::write(&target_path, pinned_yaml)?;
+
+    for (service, reference) in &pinned {
+        eprintln!("  Pinned image: {} → {}", service, reference);
+    }
+    let (head, _) = git_revision(project_dir, executor);
+    if let (Some(built), Some(head)) = (record.git_sha.as_deref(), head.as_deref()) {
+        if built != head {
+            eprintln!(
+                "  Warning: images were built from {} but HEAD is {}; run `stacker build` to refresh",
+                &built[..built.len().min(12)],
+                &head[..head.len().min(12)]
+            );
+        }
+    }
+
+    Ok(target_path)
+}
+
+/// Multi-host stacks: write the primary host's slice of the compose next to
+/// it and deploy that. The placed services ship afterwards, see
+/// `DeployCommand::deploy_placement_peers`.
+fn place_primary_compose(
+    config: &StackerConfig,
+    target_override: Option<&str>,
+    compose_path: &Path,
+) -> Result<PathBuf, CliError> {
+    let Some(primary) = config.deploy.target_name(target_override) else {
+        return Ok(compose_path.to_path_buf());
+    };
+    let Some(plan) = placement::plan_placement(config, &primary)? else {
+        return Ok(compose_path.to_path_buf());
+    };
+
+    let content = std::fs::read_to_string(compose_path)?;
+    let split = placement::split_compose(&content, &plan)?;
+    let stem = compose_path
+        .file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "docker-compose".to_string());
+    let target_path =
+        compose_path.with_file_name(format!(".{}.placed.yml", stem.trim_start_matches('.')));
+    std::fs::write(&target_path, &split[0].1)?;
+
+    for host in plan.peers() {
+        eprintln!(
+            "  Placement: {} → {}",
+            host.services.join(", "),
+            host.target
+        );
+    }
+    Ok(target_path)
+}
+
 /// Parse `docker compose ps --format json` output and count running containers.
 /// Returns `(running_count, total_count)`.
 fn parse_container_statuses(json_str: &str) -> Option<(usize, usize)> {
@@ -4187,8 +4792,12 @@ fn parse_container_statuses(json_str: &str) -> Option<(usize, usize)> {
 
 /// Print a brief container summary table.
 fn print_container_summary(compose_str: &str, executor: &dyn CommandExecutor) {
-    let args = vec!["compose", "-f", compose_str, "ps", "--format", "table"];
-    if let Ok(output) = executor.execute("docker", &args) {
+    let Ok((cmd, prefix)) = resolve_compose_cmd(executor) else {
+        return;
+    };
+    let mut args: Vec<&str> = prefix;
+    args.extend(["-f", compose_str, "ps", "--format", "table"]);
+    if let Ok(output) = executor.execute(cmd, &args) {
         if output.success() && !output.stdout.trim().is_empty() {
             eprintln!();
             eprint!("{}", output.stdout);
@@ -4376,6 +4985,42 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn test_pin_built_images_writes_pinned_copy_for_user_compose() {
+        let dir = setup_local_project(&[(
+            "docker-compose.yml",
+            "services:\n  api:\n    build: ./api\n    volumes: ['./data:/data']\n",
+        )]);
+        let mut record = BuildRecord {
+            version: "1.0".to_string(),
+            git_sha: None,
+            platforms: vec!["linux/amd64".to_string()],
+            pushed: true,
+            built_at: "2026-01-01T00:00:00Z".to_string(),
+            images: Default::default(),
+        };
+        record.images.insert(
+            "api".to_string(),
+            crate::cli::image_build::BuiltImage {
+                repository: "ghcr.io/acme/api".to_string(),
+                tags: vec!["1.0".to_string()],
+                digest: Some("sha256:feed".to_string()),
+            },
+        );
+        record.save(dir.path()).unwrap();
+
+        let compose = dir.path().join("docker-compose.yml");
+        let executor = MockExecutor::success();
+        let pinned = pin_built_images_for_deploy(dir.path(), &compose, &executor).unwrap();
+
+        assert_eq!(pinned, dir.path().join(".docker-compose.pinned.yml"));
+        let content = std::fs::read_to_string(&pinned).unwrap();
+        assert!(content.contains("image: ghcr.io/acme/api@sha256:feed"));
+        assert!(std::fs::read_to_string(&compose)
+            .unwrap()
+            .contains("build: ./api"));
+    }
+
     #[test]
     fn test_scn_004_compose_image_services_register_as_remote_secret_targets() {
         let dir = setup_local_project(&[(
@@ -5002,6 +5647,36 @@ services:
         assert!(server.validate(&minimal_config).is_err());
     }
 
+    #[test]
+    fn test_blue_green_full_redeploy_lists_service_rollouts() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("docker-compose.yml"),
+            "services:\n  web:\n    image: nginx\n  worker:\n    image: busybox\n",
+        )
+        .unwrap();
+        let mut config = StackerConfig::from_str(
+            "name: test\napp:\n  type: static\ndeploy:\n  target: server\n  strategy: blue_green\n",
+        )
+        .unwrap();
+
+        // The first install has no old set to switch from.
+        assert!(
+            check_full_deploy_strategy(&config, DeployTarget::Server, dir.path(), false).is_ok()
+        );
+
+        config.deploy.deployment_hash = Some("dep-123".to_string());
+        let err = check_full_deploy_strategy(&config, DeployTarget::Server, dir.path(), false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("stacker deploy --service web"), "{err}");
+        assert!(err.contains("stacker deploy --service worker"), "{err}");
+
+        assert!(
+            check_full_deploy_strategy(&config, DeployTarget::Server, dir.path(), true).is_ok()
+        );
+    }
+
     #[test]
     fn test_deploy_hooks_dry_run_does_not_execute() {
         let config =
@@ -6068,8 +6743,10 @@ monitoring:
                 domain: "app.example.com".into(),
                 ssl: SslMode::Auto,
                 upstream: upstream.to_string(),
+                ..Default::default()
             }],
             config: None,
+            acme: None,
         }
     }
 
@@ -1,5 +1,6 @@
 use crate::cli::config_parser::StackerConfig;
 use crate::cli::credentials::CredentialsManager;
+use crate::cli::deploy_lease::{lease_request, with_deploy_lease, DEPLOY_LEASE_TTL_SECS};
 use crate::cli::error::CliError;
 use crate::cli::stacker_client::StackerClient;
 use crate::console::commands::cli::status::{
@@ -8,7 +9,8 @@ use crate::console::commands::cli::status::{
 };
 use crate::console::commands::CallableTrait;
 use crate::services::{
-    DeployPlan, DeployPlanOperation, DeploymentEventFeed, DeploymentState, TypedErrorEnvelope,
+    DeployPlan, DeployPlanOperation, DeploymentEventFeed, DeploymentState, RollbackTargetKind,
+    TypedErrorEnvelope,
 };
 
 const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
@@ -36,6 +38,7 @@ pub struct DeploymentRollbackCommand {
     pub apply_plan: Option<String>,
     pub confirm: bool,
     pub deployment: Option<String>,
+    pub force_lease: bool,
 }
 
 impl DeploymentRollbackCommand {
@@ -52,8 +55,14 @@ impl DeploymentRollbackCommand {
             apply_plan,
             confirm,
             deployment,
+            force_lease: false,
         }
     }
+
+    pub fn with_force_lease(mut self, force: bool) -> Self {
+        self.force_lease = force;
+        self
+    }
 }
 
 impl DeploymentEventsCommand {
@@ -84,6 +93,12 @@ fn print_state(state: &DeploymentState, json: bool) {
     println!("Agent:      {}", state.agent.status);
     println!("Compose:    {}", state.runtime.compose_path);
     println!("Env:        {}", state.runtime.env_path);
+    if let Some(lease) = &state.lease {
+        println!(
+            "Lease:      {} ({}) until {}",
+            lease.owner, lease.reason, lease.expires_at
+        );
+    }
 
     if !state.apps.is_empty() {
         println!("\nApps:");
@@ -179,7 +194,7 @@ pub(crate) async fn fetch_remote_deployment_plan(
         })
 }
 
-async fn resolve_deployment_hash(
+pub(crate) async fn resolve_deployment_hash(
     config: &StackerConfig,
     base_url: &str,
     client: &StackerClient,
@@ -484,15 +499,48 @@ impl CallableTrait for DeploymentRollbackCommand {
                 return Ok::<(), CliError>(());
             }
 
-            let resolved_version = plan
-                .rollback
-                .as_ref()
-                .map(|rollback| rollback.resolved_version.clone())
-                .ok_or_else(|| {
-                    CliError::from(TypedErrorEnvelope::internal_error(
-                        "Rollback plan did not include a resolved target version",
-                    ))
-                })?;
+            let rollback = plan.rollback.as_ref().ok_or_else(|| {
+                CliError::from(TypedErrorEnvelope::internal_error(
+                    "Rollback plan did not include a resolved target version",
+                ))
+            })?;
+            let resolved_version = rollback.resolved_version.clone();
+
+            if rollback.kind == RollbackTargetKind::Release {
+                eprintln!(
+                    "Rolling back deployment '{}' to release 'r{}'...",
+                    plan.deployment_hash, resolved_version
+                );
+                let request = lease_request(
+                    creds.email.as_deref(),
+                    &format!("stacker deployment rollback --to {}", self.to),
+                    DEPLOY_LEASE_TTL_SECS,
+                    self.force_lease,
+                );
+                let target = deploy_target.to_string();
+                let outcome =
+                    with_deploy_lease(&client, &plan.deployment_hash, &request, |lease_id| {
+                        let client = &client;
+                        let plan = &plan;
+                        let target = &target;
+                        async move {
+                            client
+                                .rollback_deployment_release(
+                                    &plan.deployment_hash,
+                                    &self.to,
+                                    target,
+                                    fingerprint,
+                                    Some(&lease_id),
+                                )
+                                .await
+                        }
+                    })
+                    .await?;
+                if let Some(release) = outcome.release {
+                    eprintln!("✓ Recorded rollback as release r{}", release.version);
+                }
+                return Ok::<(), CliError>(());
+            }
 
             let project = client.find_project_by_name(&project_name).await?;
             let project = project.ok_or_else(|| CliError::DeployFailed {
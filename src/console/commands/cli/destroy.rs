@@ -2,7 +2,7 @@ use std::path::Path;
 
 use crate::cli::config_parser::DeployTarget;
 use crate::cli::error::CliError;
-use crate::cli::install_runner::{CommandExecutor, ShellExecutor};
+use crate::cli::install_runner::{resolve_compose_cmd, CommandExecutor, ShellExecutor};
 use crate::cli::local_compose::resolve_local_compose_path;
 use crate::console::commands::CallableTrait;
 
@@ -23,10 +23,10 @@ impl DestroyCommand {
     }
 }
 
-/// Build `docker compose down` arguments.
+/// Build `compose down` arguments, without the compose command itself
+/// (see [`resolve_compose_cmd`]).
 pub fn build_destroy_args(compose_path: &str, volumes: bool) -> Vec<String> {
     let mut args = vec![
-        "compose".to_string(),
         "-f".to_string(),
         compose_path.to_string(),
         "down".to_string(),
@@ -62,9 +62,11 @@ pub fn run_destroy(
 
     let compose_str = compose_path.to_string_lossy().to_string();
     let args = build_destroy_args(&compose_str, volumes);
-    let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let (cmd, prefix) = resolve_compose_cmd(executor)?;
+    let mut full_args: Vec<&str> = prefix;
+    full_args.extend(args.iter().map(String::as_str));
 
-    let output = executor.execute("docker", &args_refs)?;
+    let output = executor.execute(cmd, &full_args)?;
 
     if !output.success() {
         return Err(CliError::DeployFailed {
@@ -141,10 +143,13 @@ mod tests {
 
         run_destroy(dir.path(), false, true, &executor).unwrap();
 
+        // The first call probes `<runtime> compose version`.
         let calls = executor.recorded_calls();
-        assert_eq!(calls.len(), 1);
-        assert_eq!(calls[0].0, "docker");
-        assert!(calls[0].1.contains(&"down".to_string()));
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].1, vec!["compose", "version"]);
+        assert_eq!(calls[1].0, calls[0].0);
+        assert_eq!(calls[1].1[0], "compose");
+        assert!(calls[1].1.contains(&"down".to_string()));
     }
 
     #[test]
@@ -194,9 +199,9 @@ mod tests {
         run_destroy(dir.path(), false, true, &executor).unwrap();
 
         let calls = executor.recorded_calls();
-        assert_eq!(calls.len(), 1);
+        assert_eq!(calls.len(), 2);
         assert_eq!(
-            calls[0].1[2],
+            calls[1].1[2],
             dir.path()
                 .join("docker/local/compose.yml")
                 .to_string_lossy()
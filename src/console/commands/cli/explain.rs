@@ -182,10 +182,12 @@ impl CallableTrait for ExplainTopologyCommand {
         let local_env_path = resolve_local_env_path(&project_dir, &config)?;
         let local_compose_path = resolve_local_compose_path(&project_dir, &config)?;
 
+        let primary = config.deploy.target_name(None);
         let mut services = vec![ExplainTopologyService {
             code: main_app_code(&config),
             name: config.name.clone(),
             enabled: true,
+            host: primary.clone(),
         }];
         services.extend(
             config
@@ -195,6 +197,7 @@ impl CallableTrait for ExplainTopologyCommand {
                     code: service.name.clone(),
                     name: service.name.clone(),
                     enabled: true,
+                    host: service.placement.clone().or_else(|| primary.clone()),
                 }),
         );
 
@@ -216,6 +219,16 @@ impl CallableTrait for ExplainTopologyCommand {
             println!("  runtime compose: {}", topology.runtime_compose_path);
             println!("  local env:      {}", topology.local_authoring_env_path);
             println!("  runtime env:    {}", topology.runtime_env_path);
+            if config.services.iter().any(|svc| svc.placement.is_some()) {
+                println!("  services:");
+                for service in &topology.services {
+                    println!(
+                        "    {:<20} on {}",
+                        service.code,
+                        service.host.as_deref().unwrap_or("-")
+                    );
+                }
+            }
         }
 
         Ok(())
@@ -0,0 +1,149 @@
+//! Orchestrator export commands.
+//!
+//! ```text
+//! stacker export k8s                    # writes k8s/*.yaml
+//! stacker export k8s --stdout           # prints one multi-document stream
+//! stacker export helm --output chart    # writes a Helm chart into ./chart
+//! ```
+
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::error::CliError;
+use crate::cli::generator::kubernetes::{unsupported_features, KubernetesManifests};
+use crate::console::commands::CallableTrait;
+
+const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
+const DEFAULT_K8S_DIR: &str = "k8s";
+const DEFAULT_HELM_DIR: &str = "helm";
+
+fn load_manifests(file: &Option<String>) -> Result<(StackerConfig, KubernetesManifests), CliError> {
+    let path = Path::new(file.as_deref().unwrap_or(DEFAULT_CONFIG_FILE));
+    if !path.exists() {
+        return Err(CliError::ConfigNotFound {
+            path: path.to_path_buf(),
+        });
+    }
+
+    let config = StackerConfig::from_file(path)?;
+    let manifests = KubernetesManifests::try_from(&config)?;
+    Ok((config, manifests))
+}
+
+/// Print skipped/approximated features so the export is never silently lossy.
+fn print_compatibility(config: &StackerConfig) {
+    let issues = unsupported_features(config);
+    if issues.is_empty() {
+        return;
+    }
+    eprintln!("Not fully mappable to Kubernetes:");
+    for issue in &issues {
+        eprintln!("  - {}", issue);
+    }
+}
+
+fn print_secret_hint(manifests: &KubernetesManifests) {
+    if manifests.secret_keys.is_empty() {
+        return;
+    }
+    eprintln!();
+    eprintln!("Create the referenced Secret before applying:");
+    eprintln!(
+        "  kubectl create secret generic {} {}",
+        manifests.secret_name,
+        manifests
+            .secret_keys
+            .iter()
+            .map(|key| format!("--from-literal={key}=..."))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// export k8s
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// `stacker export k8s [--file stacker.yml] [--output k8s] [--stdout]`
+pub struct ExportK8sCommand {
+    pub file: Option<String>,
+    pub output: Option<String>,
+    pub stdout: bool,
+}
+
+impl ExportK8sCommand {
+    pub fn new(file: Option<String>, output: Option<String>, stdout: bool) -> Self {
+        Self {
+            file,
+            output,
+            stdout,
+        }
+    }
+}
+
+impl CallableTrait for ExportK8sCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let (config, manifests) = load_manifests(&self.file)?;
+
+        if self.stdout {
+            print!("{}", manifests.render()?);
+            print_compatibility(&config);
+            return Ok(());
+        }
+
+        let dir = PathBuf::from(self.output.as_deref().unwrap_or(DEFAULT_K8S_DIR));
+        let written = manifests.write_to_dir(&dir)?;
+        for file in &written {
+            println!("✓ Generated {}", dir.join(file).display());
+        }
+        print_compatibility(&config);
+        print_secret_hint(&manifests);
+        eprintln!();
+        eprintln!("Apply with: kubectl apply -f {}", dir.display());
+
+        Ok(())
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// export helm
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// `stacker export helm [--file stacker.yml] [--output helm]`
+pub struct ExportHelmCommand {
+    pub file: Option<String>,
+    pub output: Option<String>,
+}
+
+impl ExportHelmCommand {
+    pub fn new(file: Option<String>, output: Option<String>) -> Self {
+        Self { file, output }
+    }
+}
+
+impl CallableTrait for ExportHelmCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let (config, manifests) = load_manifests(&self.file)?;
+
+        let dir = PathBuf::from(self.output.as_deref().unwrap_or(DEFAULT_HELM_DIR));
+        let version = config
+            .version
+            .clone()
+            .unwrap_or_else(|| "0.1.0".to_string());
+        let written = manifests.write_helm_chart(&dir, &version)?;
+        for file in &written {
+            println!("✓ Generated {}", dir.join(file).display());
+        }
+        print_compatibility(&config);
+        print_secret_hint(&manifests);
+        eprintln!();
+        eprintln!(
+            "Install with: helm install {} {}",
+            manifests.app_name,
+            dir.display()
+        );
+
+        Ok(())
+    }
+}
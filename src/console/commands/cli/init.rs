@@ -769,6 +769,7 @@ fn generate_config_template_path(
                 domain: format!("{}.localhost", project_name),
                 ssl: SslMode::Auto,
                 upstream: "app:80".to_string(),
+                ..Default::default()
             }],
             ..ProxyConfig::default()
         });
@@ -1137,6 +1138,7 @@ fn compose_service_to_definition(
         depends_on: service.depends_on.clone(),
         command: None,
         healthcheck,
+        placement: None,
     })
 }
 
@@ -1610,6 +1612,7 @@ fn convert_compose_to_stacker(ai_output: &str, repo_name: &str) -> Option<Stacke
                     depends_on: extract_yaml_strings(svc.get("depends_on")),
                     command: None,
                     healthcheck: crate::cli::github_fetcher::default_healthcheck(img),
+                    placement: None,
                 });
             } else {
                 app_image = Some(img.clone());
@@ -1625,6 +1628,8 @@ fn convert_compose_to_stacker(ai_output: &str, repo_name: &str) -> Option<Stacke
     }
 
     let mut config = StackerConfig {
+        extends: None,
+        include: Vec::new(),
         name: repo_name.to_string(),
         version: None,
         organization: None,
@@ -2978,6 +2983,7 @@ mod tests {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             }],
             ..Default::default()
         };
@@ -3018,6 +3024,7 @@ mod tests {
                 depends_on: vec![],
                 command: None,
                 healthcheck: None,
+                placement: None,
             }],
             ..Default::default()
         };
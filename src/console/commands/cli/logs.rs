@@ -2,24 +2,29 @@ use std::fmt::Write as _;
 use std::path::Path;
 
 use crate::cli::error::CliError;
-use crate::cli::install_runner::{CommandExecutor, CommandOutput, ShellExecutor};
+use crate::cli::install_runner::{
+    resolve_compose_cmd, CommandExecutor, CommandOutput, ShellExecutor,
+};
 use crate::cli::local_compose::resolve_local_compose_path;
 use crate::console::commands::CallableTrait;
 
 const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
 
-/// `stacker logs [--service <name>] [--follow] [--tail <n>] [--since <duration>]`
+/// `stacker logs [--service <name>] [--follow] [--tail <n>] [--since <duration>] [--search <text>]`
 ///
 /// Shows container logs for the deployed stack.
 ///
 /// - **Local deployments**: delegates to `docker compose logs`.
 /// - **Remote deployments**: fetches logs from the Status Panel agent via the
 ///   Stacker server API (same as `stacker agent logs`).
+/// - **`--search`**: searches the logs agents ship to the Stacker server,
+///   across all containers of the deployment.
 pub struct LogsCommand {
     pub service: Option<String>,
     pub follow: bool,
     pub tail: Option<u32>,
     pub since: Option<String>,
+    pub search: Option<String>,
 }
 
 impl LogsCommand {
@@ -34,11 +39,18 @@ impl LogsCommand {
             follow,
             tail,
             since,
+            search: None,
         }
     }
+
+    pub fn with_search(mut self, search: Option<String>) -> Self {
+        self.search = search;
+        self
+    }
 }
 
-/// Build the `docker compose logs` argument list.
+/// Build the `compose logs` argument list, without the compose command
+/// itself (see [`resolve_compose_cmd`]).
 pub fn build_logs_args(
     compose_path: &str,
     service: Option<&str>,
@@ -47,7 +59,6 @@ pub fn build_logs_args(
     since: Option<&str>,
 ) -> Vec<String> {
     let mut args = vec![
-        "compose".to_string(),
         "-f".to_string(),
         compose_path.to_string(),
         "logs".to_string(),
@@ -87,14 +98,25 @@ pub fn run_logs(
 
     let compose_str = compose_path.to_string_lossy().to_string();
     let args = build_logs_args(&compose_str, service, follow, tail, since);
-    let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let (cmd, prefix) = resolve_compose_cmd(executor)?;
+    let mut full_args: Vec<&str> = prefix;
+    full_args.extend(args.iter().map(String::as_str));
 
-    let output = executor.execute("docker", &args_refs)?;
+    let output = executor.execute(cmd, &full_args)?;
     Ok(output)
 }
 
 impl CallableTrait for LogsCommand {
     fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(search) = self.search.as_deref() {
+            return run_log_search(
+                search,
+                self.service.as_deref(),
+                self.since.as_deref(),
+                self.tail,
+            );
+        }
+
         let project_dir = std::env::current_dir()?;
 
         // Try local first — use the same compose resolution logic as local deploy/status.
@@ -423,6 +445,73 @@ fn print_logs_result(app_code: &str, info: &AgentCommandInfo, multi: bool) {
     }
 }
 
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Log search (retained logs shipped by the agent)
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+use crate::models::ContainerLogLine;
+
+/// Default search window.
+const DEFAULT_SEARCH_SINCE: &str = "1h";
+
+/// One search hit: `<time> <app> │ <message>`, stderr lines marked with `!`.
+pub fn format_search_line(line: &ContainerLogLine, app_width: usize) -> String {
+    let marker = if line.stream == "stderr" { '!' } else { ' ' };
+    format!(
+        "{} {:<width$}{}│ {}",
+        line.logged_at.format("%Y-%m-%d %H:%M:%S"),
+        line.app_code,
+        marker,
+        line.message,
+        width = app_width
+    )
+}
+
+fn run_log_search(
+    search: &str,
+    service: Option<&str>,
+    since: Option<&str>,
+    tail: Option<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ctx = CliRuntime::new("log search")?;
+    let hash = resolve_deployment_hash(&ctx)?;
+    let since = since.unwrap_or(DEFAULT_SEARCH_SINCE);
+
+    let spinner_msg = format!("Searching logs for '{}'", search);
+    let pb = progress::spinner(&spinner_msg);
+    let result = ctx.block_on(
+        ctx.client
+            .search_logs(&hash, Some(search), since, service, tail),
+    );
+    let lines = match result {
+        Ok(lines) => {
+            progress::finish_success(&pb, &format!("{} — {} lines", spinner_msg, lines.len()));
+            lines
+        }
+        Err(e) => {
+            progress::finish_error(&pb, &format!("{} — {}", spinner_msg, e));
+            return Err(Box::new(e));
+        }
+    };
+
+    if lines.is_empty() {
+        println!(
+            "No log lines match '{}' in the last {} of deployment {}.",
+            search, since, hash
+        );
+        return Ok(());
+    }
+    let app_width = lines
+        .iter()
+        .map(|line| line.app_code.len())
+        .max()
+        .unwrap_or(0);
+    for line in &lines {
+        println!("{}", format_search_line(line, app_width));
+    }
+    Ok(())
+}
+
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Tests
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -436,7 +525,7 @@ mod tests {
     #[test]
     fn test_logs_constructs_compose_command() {
         let args = build_logs_args("/path/compose.yml", None, false, None, None);
-        assert_eq!(args, vec!["compose", "-f", "/path/compose.yml", "logs"]);
+        assert_eq!(args, vec!["-f", "/path/compose.yml", "logs"]);
     }
 
     #[test]
@@ -536,6 +625,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_search_lines_align_apps_and_mark_stderr() {
+        let line = ContainerLogLine {
+            deployment_hash: "dep".to_string(),
+            app_code: "api".to_string(),
+            container: None,
+            logged_at: chrono::DateTime::parse_from_rfc3339("2026-10-18T12:00:01Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            stream: "stderr".to_string(),
+            message: "upstream timeout".to_string(),
+        };
+        assert_eq!(
+            format_search_line(&line, 5),
+            "2026-10-18 12:00:01 api  !│ upstream timeout"
+        );
+    }
+
     #[test]
     fn test_no_containers_messages_use_full_hash() {
         let hash = "deployment_5cc15f7d-8c87-464a-a7c5-ee6116201f22";
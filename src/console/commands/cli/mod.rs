@@ -1,5 +1,8 @@
 pub mod agent;
 pub mod ai;
+pub mod backup;
+pub mod build;
+pub mod bundle;
 pub mod ci;
 pub mod cloud_firewall;
 pub mod config;
@@ -8,13 +11,16 @@ pub mod deploy;
 pub mod deployment;
 pub mod destroy;
 pub mod explain;
+pub mod export;
 pub mod init;
 pub mod list;
 pub mod login;
 pub mod logs;
 pub mod marketplace;
 pub mod pipe;
+pub mod preview;
 pub mod proxy;
+pub mod releases;
 pub mod resolve;
 pub mod rollback;
 pub mod secrets;
@@ -5432,6 +5432,7 @@ mod tests {
                 pipes: true,
                 ..Default::default()
             },
+            compatibility: None,
         };
 
         assert!(validate_pipe_command_capabilities(&capabilities).is_ok());
@@ -5448,6 +5449,7 @@ mod tests {
                 "logs".to_string(),
             ],
             features: crate::cli::stacker_client::DeploymentCapabilityFeatures::default(),
+            compatibility: None,
         };
 
         let error = validate_pipe_command_capabilities(&capabilities)
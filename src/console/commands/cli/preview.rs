@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::error::CliError;
+use crate::cli::image_build::{pin_compose_images, BuildRecord};
+use crate::cli::install_runner::ShellExecutor;
+use crate::cli::preview::{
+    detect_branch, plan_preview, preview_compose, preview_deployment_hash, PreviewPlan,
+};
+use crate::cli::runtime::CliRuntime;
+use crate::cli::stacker_client::AgentEnqueueRequest;
+use crate::console::commands::cli::agent::{
+    resolve_registry_auth_for_agent_deploy, run_agent_command,
+};
+use crate::console::commands::CallableTrait;
+use crate::forms::status_panel::{ConfigureProxyCommandRequest, DeployAppCommandRequest};
+use crate::models::PreviewEnvironment;
+
+const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
+const PREVIEW_DEPLOY_TIMEOUT_SECS: u64 = 600;
+
+fn load_config(project_dir: &Path) -> Result<StackerConfig, CliError> {
+    let config_path = project_dir.join(DEFAULT_CONFIG_FILE);
+    if !config_path.exists() {
+        return Err(CliError::ConfigValidation(
+            "No stacker.yml found. Run 'stacker init' first.".to_string(),
+        ));
+    }
+    StackerConfig::from_file(&config_path)
+}
+
+/// The compose the preview runs: `compose_file` of the preview's target
+/// profile, else the one `stacker deploy` generated, with images pinned to
+/// the last pushed `stacker build`.
+fn load_preview_compose(
+    project_dir: &Path,
+    config: &StackerConfig,
+    plan: &PreviewPlan,
+) -> Result<String, CliError> {
+    let target = config
+        .deploy
+        .preview
+        .as_ref()
+        .and_then(|preview| preview.target.as_deref());
+    let compose_path = config
+        .deploy
+        .resolve(target)?
+        .compose_file
+        .map(|file| project_dir.join(file))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| project_dir.join(".stacker").join("docker-compose.yml"));
+    if !compose_path.exists() {
+        return Err(CliError::ConfigValidation(format!(
+            "Compose file not found: {}. Run `stacker deploy --dry-run` to generate it.",
+            compose_path.display()
+        )));
+    }
+
+    let mut compose_content = std::fs::read_to_string(&compose_path)?;
+    if let Some(record) = BuildRecord::load(project_dir)?.filter(|record| record.pushed) {
+        let (pinned_yaml, pinned) = pin_compose_images(&compose_content, &record)?;
+        if !pinned.is_empty() {
+            compose_content = pinned_yaml;
+        }
+    }
+    preview_compose(&compose_content, &plan.service)
+}
+
+// ── Up ───────────────────────────────────────────────
+
+/// `stacker preview up [--branch <name>] [--ttl <hours>]`
+///
+/// Deploys the current branch beside the main stack on the shared preview
+/// server and routes `<branch>.<base_domain>` to it. Re-running it redeploys
+/// the branch and extends the expiry.
+pub struct PreviewUpCommand {
+    pub branch: Option<String>,
+    pub ttl_hours: Option<u32>,
+}
+
+impl PreviewUpCommand {
+    pub fn new(branch: Option<String>, ttl_hours: Option<u32>) -> Self {
+        Self { branch, ttl_hours }
+    }
+}
+
+impl CallableTrait for PreviewUpCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let project_dir = std::env::current_dir()?;
+        let config = load_config(&project_dir)?;
+        let branch = detect_branch(self.branch.as_deref(), &project_dir, &ShellExecutor)?;
+        let plan = plan_preview(&config, &branch, self.ttl_hours)?;
+        let compose_content = load_preview_compose(&project_dir, &config, &plan)?;
+
+        let ctx = CliRuntime::new("preview up")?;
+        // Register first so a failed deploy is still reaped when the TTL runs out.
+        let preview = ctx.block_on(
+            ctx.client
+                .register_preview(&plan.deployment_hash, &plan.request()),
+        )?;
+
+        let params = DeployAppCommandRequest {
+            app_code: plan.app_code.clone(),
+            compose_content: Some(compose_content),
+            image: None,
+            env_vars: None,
+            config_files: None,
+            pull: true,
+            force_recreate: true,
+            force_config_overwrite: true,
+            runtime: "runc".to_string(),
+            registry_auth: resolve_registry_auth_for_agent_deploy(&project_dir),
+            blue_green: None,
+            rollout: None,
+            compose_project: Some(plan.compose_project.clone()),
+        };
+        let request = AgentEnqueueRequest::new(&plan.deployment_hash, "deploy_app")
+            .with_parameters(&params)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?
+            .with_timeout(PREVIEW_DEPLOY_TIMEOUT_SECS as i32);
+        run_agent_command(
+            &ctx,
+            &request,
+            &format!("Deploying preview {}", plan.slug),
+            PREVIEW_DEPLOY_TIMEOUT_SECS,
+        )?;
+
+        let proxy = ConfigureProxyCommandRequest {
+            app_code: plan.app_code.clone(),
+            domain_names: vec![plan.domain.clone()],
+            forward_host: Some(plan.forward_host.clone()),
+            forward_port: plan.forward_port,
+            ssl_enabled: true,
+            ssl_forced: true,
+            http2_support: true,
+            action: "create".to_string(),
+        };
+        let request = AgentEnqueueRequest::new(&plan.deployment_hash, "configure_proxy")
+            .with_parameters(&proxy)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid parameters: {}", e)))?;
+        run_agent_command(&ctx, &request, &format!("Routing {}", plan.domain), 120)?;
+
+        eprintln!(
+            "✓ Preview of '{}' is up at https://{} (expires {})",
+            plan.branch,
+            plan.domain,
+            preview.expires_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        println!("https://{}", plan.domain);
+        Ok(())
+    }
+}
+
+// ── Down ─────────────────────────────────────────────
+
+/// `stacker preview down [--branch <name>]`
+///
+/// Removes the branch's proxy host, containers and volumes.
+pub struct PreviewDownCommand {
+    pub branch: Option<String>,
+}
+
+impl PreviewDownCommand {
+    pub fn new(branch: Option<String>) -> Self {
+        Self { branch }
+    }
+}
+
+impl CallableTrait for PreviewDownCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let project_dir = std::env::current_dir()?;
+        let config = load_config(&project_dir)?;
+        let branch = detect_branch(self.branch.as_deref(), &project_dir, &ShellExecutor)?;
+        let plan = plan_preview(&config, &branch, None)?;
+
+        let ctx = CliRuntime::new("preview down")?;
+        let command_ids =
+            ctx.block_on(ctx.client.delete_preview(&plan.deployment_hash, &plan.slug))?;
+        eprintln!(
+            "✓ Teardown of preview '{}' ({}) queued as {} agent command(s)",
+            plan.branch,
+            plan.domain,
+            command_ids.len()
+        );
+        Ok(())
+    }
+}
+
+// ── List ─────────────────────────────────────────────
+
+/// `stacker preview list [--json]`
+pub struct PreviewListCommand {
+    pub json: bool,
+}
+
+impl PreviewListCommand {
+    pub fn new(json: bool) -> Self {
+        Self { json }
+    }
+}
+
+fn print_previews(previews: &[PreviewEnvironment], json: bool) -> Result<(), CliError> {
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(previews).map_err(|err| CliError::ConfigValidation(
+                format!("Failed to serialize previews: {err}")
+            ))?
+        );
+        return Ok(());
+    }
+
+    if previews.is_empty() {
+        println!("No active previews. Run `stacker preview up` on a branch to create one.");
+        return Ok(());
+    }
+
+    println!("{:<32} {:<44} {:<20}", "BRANCH", "URL", "EXPIRES");
+    for preview in previews {
+        println!(
+            "{:<32} {:<44} {:<20}",
+            preview.branch,
+            format!("https://{}", preview.domain),
+            preview.expires_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
+impl CallableTrait for PreviewListCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let project_dir = std::env::current_dir()?;
+        let config = load_config(&project_dir)?;
+        let deployment_hash = preview_deployment_hash(&config)?;
+
+        let ctx = CliRuntime::new("preview list")?;
+        let previews = ctx.block_on(ctx.client.list_previews(&deployment_hash))?;
+        print_previews(&previews, self.json)?;
+        Ok(())
+    }
+}
@@ -1,11 +1,14 @@
+use crate::cli::acme::{self, CertificateStatus};
 use crate::cli::config_parser::{
     CloudOrchestrator, DeployTarget, DomainConfig, ProxyType, SslMode, StackerConfig,
 };
 use crate::cli::deployment_lock::DeploymentLock;
 use crate::cli::error::CliError;
+use crate::cli::install_runner::ShellExecutor;
+use crate::cli::local_compose::resolve_local_compose_path;
 use crate::cli::proxy_manager::{
-    detect_proxy, detect_proxy_from_snapshot, generate_nginx_server_block, ContainerRuntime,
-    DockerCliRuntime, ProxyDetection,
+    detect_proxy, detect_proxy_from_snapshot, generate_proxy_snippet, local_runtime,
+    ContainerRuntime, ProxyDetection,
 };
 use crate::cli::runtime::CliRuntime;
 use crate::cli::stacker_client::AgentEnqueueRequest;
@@ -75,6 +78,7 @@ pub fn build_domain_config(
         domain: domain.to_string(),
         ssl: parse_ssl_mode(ssl),
         upstream: upstream.unwrap_or("http://app:8080").to_string(),
+        ..Default::default()
     }
 }
 
@@ -125,7 +129,7 @@ fn persist_proxy_config_to_stacker_yml(
         return Ok(None);
     }
 
-    let mut config = StackerConfig::from_file_raw(&config_path)?;
+    let (mut config, layer) = StackerConfig::from_file_layer(&config_path)?;
     let changed = upsert_proxy_domain_config(&mut config, proxy_type, domain_config);
     let backup_path = PathBuf::from(format!("{}.bak", config_path.display()));
 
@@ -137,7 +141,8 @@ fn persist_proxy_config_to_stacker_yml(
         }));
     }
 
-    let yaml = serde_yaml::to_string(&config)
+    let yaml = layer
+        .render(&config)
         .map_err(|e| CliError::ConfigValidation(format!("Failed to serialize config: {}", e)))?;
     std::fs::copy(&config_path, &backup_path)?;
     std::fs::write(&config_path, yaml)?;
@@ -289,16 +294,17 @@ impl CallableTrait for ProxyAddCommand {
             return Ok(());
         }
 
-        let block = generate_nginx_server_block(&domain_config)?;
+        let proxy_type = local_proxy_type(&project_dir);
+        let block = generate_proxy_snippet(proxy_type, &domain_config)?;
         let persistence =
-            persist_proxy_config_to_stacker_yml(&project_dir, ProxyType::Nginx, domain_config)?;
+            persist_proxy_config_to_stacker_yml(&project_dir, proxy_type, domain_config)?;
         println!("{}", block);
         if !self.json {
             print_proxy_config_persistence(persistence.as_ref());
         }
         eprintln!(
-            "✓ Proxy config generated for {}; apply this nginx snippet to configure a local proxy",
-            self.domain
+            "✓ Proxy config generated for {}; apply this {} snippet to configure a local proxy",
+            self.domain, proxy_type
         );
         Ok(())
     }
@@ -322,6 +328,22 @@ impl ProxyDetectCommand {
     }
 }
 
+/// The generated proxy `stacker proxy add` targets locally: the one already
+/// configured in stacker.yml when it is nginx, Traefik or Caddy, else nginx.
+fn local_proxy_type(project_dir: &Path) -> ProxyType {
+    match StackerConfig::from_file_raw(&project_dir.join("stacker.yml")) {
+        Ok(config)
+            if matches!(
+                config.proxy.proxy_type,
+                ProxyType::Nginx | ProxyType::Traefik | ProxyType::Caddy
+            ) =>
+        {
+            config.proxy.proxy_type
+        }
+        _ => ProxyType::Nginx,
+    }
+}
+
 /// Check whether the current project is configured for cloud/remote deployment.
 fn is_cloud_or_remote(project_dir: &std::path::Path) -> bool {
     // 1. Check deployment lock
@@ -398,13 +420,15 @@ fn resolve_deployment_hash_for_proxy(
     ))
 }
 
-/// Pretty-print a proxy detection result.
-fn print_detection(detection: &ProxyDetection, json: bool) {
+/// Pretty-print a proxy detection result, with ACME certificate expiry
+/// when the stack runs the certbot companion.
+fn print_detection(detection: &ProxyDetection, certificates: &[CertificateStatus], json: bool) {
     if json {
         let val = serde_json::json!({
             "proxy_type": format!("{:?}", detection.proxy_type),
             "container_name": detection.container_name,
             "ports": detection.ports,
+            "certificates": certificates,
         });
         println!("{}", serde_json::to_string_pretty(&val).unwrap_or_default());
         return;
@@ -417,6 +441,48 @@ fn print_detection(detection: &ProxyDetection, json: bool) {
     if !detection.ports.is_empty() {
         eprintln!("  Ports: {:?}", detection.ports);
     }
+    if !certificates.is_empty() {
+        eprintln!("  Certificates:");
+        for cert in certificates {
+            eprintln!("    {}", cert.summary());
+        }
+    }
+}
+
+/// Certificates held by the local certbot companion, if the stack has one.
+fn local_certificates(project_dir: &Path) -> Vec<CertificateStatus> {
+    match resolve_local_compose_path(project_dir) {
+        Ok(compose_path) if acme::compose_has_certbot(&compose_path) => {
+            acme::local_certificate_status(&compose_path, &ShellExecutor)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Whether the agent's container list includes the certbot companion.
+fn has_certbot_container(containers: &[serde_json::Value]) -> bool {
+    containers.iter().any(|c| {
+        ["name", "image"].iter().any(|field| {
+            c.get(*field)
+                .and_then(|v| v.as_str())
+                .is_some_and(|value| value.contains(acme::CERTBOT_SERVICE))
+        })
+    })
+}
+
+/// Certificates held by the remote certbot companion. A failed lookup is
+/// reported and treated as "no certificates" so detection still prints.
+fn remote_certificates(ctx: &CliRuntime, hash: &str) -> Vec<CertificateStatus> {
+    match ctx.block_on(acme::remote_certificate_status(&ctx.client, hash)) {
+        Ok(certificates) => certificates,
+        Err(err) => {
+            eprintln!(
+                "Warning: could not read certificates from the agent: {}",
+                err
+            );
+            Vec::new()
+        }
+    }
 }
 
 impl CallableTrait for ProxyDetectCommand {
@@ -449,13 +515,18 @@ impl CallableTrait for ProxyDetectCommand {
                 .and_then(|r| r.get("containers").and_then(|v| v.as_array()))
                 .cloned()
                 .unwrap_or_default();
+            let certificates = if has_certbot_container(&containers) {
+                remote_certificates(&ctx, &hash)
+            } else {
+                Vec::new()
+            };
             let fake_snapshot = serde_json::json!({ "containers": containers });
             let detection = detect_proxy_from_snapshot(&fake_snapshot);
-            print_detection(&detection, self.json);
+            print_detection(&detection, &certificates, self.json);
         } else {
-            let runtime = DockerCliRuntime;
-            let detection = run_detect(&runtime)?;
-            print_detection(&detection, self.json);
+            let runtime = local_runtime()?;
+            let detection = run_detect(runtime.as_ref())?;
+            print_detection(&detection, &local_certificates(&project_dir), self.json);
         }
 
         Ok(())
@@ -567,6 +638,7 @@ mod tests {
                     Some("off"),
                 )],
                 config: None,
+                acme: None,
             })
             .build()
             .unwrap();
@@ -585,6 +657,27 @@ mod tests {
         assert_eq!(config.proxy.domains[0].upstream, "web:8080");
     }
 
+    #[test]
+    fn local_proxy_type_keeps_configured_traefik_or_caddy() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(local_proxy_type(dir.path()), ProxyType::Nginx);
+
+        let config_path = dir.path().join("stacker.yml");
+        std::fs::write(
+            &config_path,
+            "name: demo\napp:\n  type: node\nproxy:\n  type: caddy\n",
+        )
+        .unwrap();
+        assert_eq!(local_proxy_type(dir.path()), ProxyType::Caddy);
+
+        std::fs::write(
+            &config_path,
+            "name: demo\napp:\n  type: node\nproxy:\n  type: nginx-proxy-manager\n",
+        )
+        .unwrap();
+        assert_eq!(local_proxy_type(dir.path()), ProxyType::Nginx);
+    }
+
     #[test]
     fn persist_proxy_config_to_stacker_yml_writes_backup_and_preserves_env_placeholders() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -0,0 +1,134 @@
+use crate::cli::config_parser::StackerConfig;
+use crate::cli::credentials::CredentialsManager;
+use crate::cli::error::CliError;
+use crate::cli::stacker_client::StackerClient;
+use crate::console::commands::cli::deployment::resolve_deployment_hash;
+use crate::console::commands::cli::status::{is_remote_deployment, resolve_stacker_base_url};
+use crate::console::commands::CallableTrait;
+use crate::models::DeploymentRelease;
+
+const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
+
+/// `stacker releases list [--json] [--deployment <hash>]`
+///
+/// Lists the immutable releases recorded after each successful remote deploy.
+pub struct ReleasesListCommand {
+    pub json: bool,
+    pub deployment: Option<String>,
+}
+
+impl ReleasesListCommand {
+    pub fn new(json: bool, deployment: Option<String>) -> Self {
+        Self { json, deployment }
+    }
+}
+
+fn print_releases(
+    deployment_hash: &str,
+    releases: &[DeploymentRelease],
+    json: bool,
+) -> Result<(), CliError> {
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(releases).map_err(|err| CliError::ConfigValidation(
+                format!("Failed to serialize releases: {err}")
+            ))?
+        );
+        return Ok(());
+    }
+
+    println!("Deployment: {}", deployment_hash);
+    if releases.is_empty() {
+        println!("No releases recorded yet. Run `stacker deploy` to record one.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<8} {:<20} {:<14} {:<16} IMAGES",
+        "RELEASE", "CREATED", "BUNDLE", "NOTE"
+    );
+    for (index, release) in releases.iter().enumerate() {
+        let mut note = release
+            .rollback_of
+            .map(|version| format!("rollback of r{}", version))
+            .unwrap_or_default();
+        if index == 0 {
+            note = if note.is_empty() {
+                "current".to_string()
+            } else {
+                format!("current, {}", note)
+            };
+        }
+        let images = release
+            .image_digests
+            .as_object()
+            .map(|images| {
+                images
+                    .iter()
+                    .map(|(service, image)| {
+                        format!("{}={}", service, image.as_str().unwrap_or_default())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default();
+        println!(
+            "{:<8} {:<20} {:<14} {:<16} {}",
+            format!("r{}", release.version),
+            release.created_at.format("%Y-%m-%d %H:%M:%S"),
+            &release.bundle_hash[..release.bundle_hash.len().min(12)],
+            note,
+            images
+        );
+    }
+
+    Ok(())
+}
+
+impl CallableTrait for ReleasesListCommand {
+    fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let project_dir = std::env::current_dir()?;
+        if !is_remote_deployment(&project_dir) {
+            return Err(Box::new(CliError::ConfigValidation(
+                "Releases are only recorded for cloud or server targets.".to_string(),
+            )));
+        }
+
+        let config_path = project_dir.join(DEFAULT_CONFIG_FILE);
+        if !config_path.exists() {
+            return Err(Box::new(CliError::ConfigValidation(
+                "No stacker.yml found. Run 'stacker init' first.".to_string(),
+            )));
+        }
+
+        let config = StackerConfig::from_file(&config_path)?
+            .with_resolved_deploy_target(None)
+            .map_err(|e| CliError::ConfigValidation(format!("Invalid stacker.yml: {}", e)))?;
+        let deploy_target = config.deploy.target;
+
+        let cred_manager = CredentialsManager::with_default_store();
+        let creds = cred_manager.require_valid_token("releases list")?;
+        let base_url = resolve_stacker_base_url(&creds);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| CliError::DeployFailed {
+                target: deploy_target,
+                reason: format!("Failed to initialize async runtime: {}", e),
+            })?;
+
+        rt.block_on(async {
+            let client = StackerClient::new(&base_url, &creds.access_token);
+            let deployment_hash =
+                resolve_deployment_hash(&config, &base_url, &client, self.deployment.as_deref())
+                    .await?;
+            let releases = client.list_deployment_releases(&deployment_hash).await?;
+            print_releases(&deployment_hash, &releases, self.json)?;
+            Ok::<(), CliError>(())
+        })?;
+
+        Ok(())
+    }
+}
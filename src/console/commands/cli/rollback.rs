@@ -1,23 +1,48 @@
 use crate::cli::config_parser::{DeployTarget, StackerConfig};
 use crate::cli::credentials::{CredentialsManager, StoredCredentials};
+use crate::cli::deploy_lease::{lease_request, with_deploy_lease, DEPLOY_LEASE_TTL_SECS};
 use crate::cli::error::CliError;
 use crate::cli::install_runner::normalize_stacker_server_url;
 use crate::cli::stacker_client::{self, StackerClient};
+use crate::console::commands::cli::deployment::fetch_remote_deployment_plan;
 use crate::console::commands::CallableTrait;
+use crate::services::DeployPlanOperation;
 
 const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
 
+/// `stacker rollback <RELEASE> [--plan] --confirm`
 /// `stacker rollback --version <VERSION> --confirm`
 ///
-/// Requests a safe marketplace rollback to a known template version.
+/// Re-applies a recorded release, or requests a safe marketplace rollback to
+/// a known template version.
 pub struct RollbackCommand {
-    pub version: String,
+    pub version: Option<String>,
+    pub release: Option<String>,
+    pub plan: bool,
     pub confirm: bool,
+    pub force_lease: bool,
 }
 
 impl RollbackCommand {
-    pub fn new(version: String, confirm: bool) -> Self {
-        Self { version, confirm }
+    pub fn new(version: Option<String>, confirm: bool) -> Self {
+        Self {
+            version,
+            release: None,
+            plan: false,
+            confirm,
+            force_lease: false,
+        }
+    }
+
+    pub fn with_release(mut self, release: Option<String>, plan: bool) -> Self {
+        self.release = release;
+        self.plan = plan;
+        self
+    }
+
+    pub fn with_force_lease(mut self, force: bool) -> Self {
+        self.force_lease = force;
+        self
     }
 }
 
@@ -37,34 +62,152 @@ fn resolve_stacker_base_url(creds: &StoredCredentials) -> String {
         .unwrap_or_else(|| stacker_client::DEFAULT_STACKER_URL.to_string())
 }
 
+fn load_config() -> Result<StackerConfig, CliError> {
+    let project_dir = std::env::current_dir()?;
+    let config_path = project_dir.join(DEFAULT_CONFIG_FILE);
+
+    if !config_path.exists() {
+        return Err(CliError::ConfigValidation(
+            "No stacker.yml found. Run 'stacker init' first.".to_string(),
+        ));
+    }
+
+    StackerConfig::from_file(&config_path)?
+        .with_resolved_deploy_target(None)
+        .map_err(|e| CliError::ConfigValidation(format!("Invalid stacker.yml: {}", e)))
+}
+
+/// Preview the release rollback plan, then apply it when confirmed. The
+/// plan fingerprint is sent back so a deployment that changed in between is
+/// rejected instead of rolled back blindly.
+fn run_release_rollback(
+    release: &str,
+    plan_only: bool,
+    confirm: bool,
+    force_lease: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config()?;
+    let deploy_target = config.deploy.target;
+
+    let cred_manager = CredentialsManager::with_default_store();
+    let creds = cred_manager.require_valid_token("rollback")?;
+    let base_url = resolve_stacker_base_url(&creds);
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CliError::DeployFailed {
+            target: deploy_target,
+            reason: format!("Failed to initialize async runtime: {}", e),
+        })?;
+
+    rt.block_on(async {
+        let client = StackerClient::new(&base_url, &creds.access_token);
+        let plan = fetch_remote_deployment_plan(
+            &config,
+            &base_url,
+            &client,
+            None,
+            DeployPlanOperation::RollbackDeploy,
+            None,
+            Some(release),
+            None,
+        )
+        .await?;
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&plan).map_err(|err| CliError::ConfigValidation(
+                format!("Failed to serialize rollback plan: {err}")
+            ))?
+        );
+
+        if !plan.has_changes {
+            println!(
+                "Deployment {} already runs release '{}'. Nothing to apply.",
+                plan.deployment_hash, release
+            );
+            return Ok::<(), CliError>(());
+        }
+        if plan_only {
+            return Ok(());
+        }
+        if !confirm {
+            return Err(CliError::ConfigValidation(format!(
+                "Rollback requires --confirm (-y). Re-run `stacker rollback {} --confirm` to apply the plan above.",
+                release
+            )));
+        }
+
+        eprintln!(
+            "Rolling back deployment '{}' to release '{}'...",
+            plan.deployment_hash, release
+        );
+        let request = lease_request(
+            creds.email.as_deref(),
+            &format!("stacker rollback {}", release),
+            DEPLOY_LEASE_TTL_SECS,
+            force_lease,
+        );
+        let outcome = with_deploy_lease(&client, &plan.deployment_hash, &request, |lease_id| {
+            let client = &client;
+            let plan = &plan;
+            let target = config.deploy.target.to_string();
+            async move {
+                client
+                    .rollback_deployment_release(
+                        &plan.deployment_hash,
+                        release,
+                        &target,
+                        &plan.fingerprint,
+                        Some(&lease_id),
+                    )
+                    .await
+            }
+        })
+        .await?;
+
+        match outcome.release {
+            Some(recorded) => eprintln!(
+                "✓ Queued {} deploy command(s); recorded as release r{} (rollback of r{})",
+                outcome.command_ids.len(),
+                recorded.version,
+                recorded.rollback_of.unwrap_or_default()
+            ),
+            None => eprintln!("✓ Deployment already runs the requested release"),
+        }
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
 impl CallableTrait for RollbackCommand {
     fn call(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if !self.confirm {
-            return Err(Box::new(CliError::ConfigValidation(
-                "Rollback requires --confirm (-y) flag. This will redeploy the selected marketplace version."
-                    .to_string(),
-            )));
+        if let Some(release) = self.release.as_deref() {
+            return run_release_rollback(release, self.plan, self.confirm, self.force_lease);
         }
 
-        let project_dir = std::env::current_dir()?;
-        let config_path = project_dir.join(DEFAULT_CONFIG_FILE);
+        let version = self.version.clone().ok_or_else(|| {
+            Box::new(CliError::ConfigValidation(
+                "Specify a release (`stacker rollback <release>`) or --version <VERSION>."
+                    .to_string(),
+            )) as Box<dyn std::error::Error>
+        })?;
 
-        if !config_path.exists() {
+        if !self.confirm {
             return Err(Box::new(CliError::ConfigValidation(
-                "No stacker.yml found. Run 'stacker init' first.".to_string(),
+                "Rollback requires --confirm (-y) flag. This will redeploy the selected marketplace version."
+                    .to_string(),
             )));
         }
 
-        let config = StackerConfig::from_file(&config_path)?
-            .with_resolved_deploy_target(None)
-            .map_err(|e| CliError::ConfigValidation(format!("Invalid stacker.yml: {}", e)))?;
-
+        let config = load_config()?;
         let project_name = resolve_project_name(&config);
 
         let cred_manager = CredentialsManager::with_default_store();
         let creds = cred_manager.require_valid_token("rollback")?;
         let base_url = resolve_stacker_base_url(&creds);
-        let version = self.version.clone();
 
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
@@ -1485,6 +1485,7 @@ mod tests {
             depends_on: vec!["postgres".to_string()],
             command: None,
             healthcheck: None,
+            placement: None,
         }
     }
 
@@ -1513,6 +1514,8 @@ mod tests {
     #[test]
     fn test_find_local_service_matches_case_insensitively() {
         let config = StackerConfig {
+            extends: None,
+            include: Vec::new(),
             name: "syncopia".to_string(),
             version: None,
             organization: None,
@@ -5,6 +5,9 @@
 //! `stacker.yml`.
 //!
 //! `stacker service list [--online]` shows available service templates.
+//!
+//! `stacker service import <name>` reviews a Docker Compose file from a local
+//! path, a GitHub repository or an HTTPS URL and appends selected services.
 
 use std::path::{Path, PathBuf};
 
@@ -14,17 +17,20 @@ use crate::cli::compose_service_sync::{
 use crate::cli::config_parser::{ServiceDefinition, StackerConfig};
 use crate::cli::credentials::CredentialsManager;
 use crate::cli::error::CliError;
+use crate::cli::github_fetcher::{fetch_compose_url, fetch_github_compose, FetchedCompose};
 use crate::cli::service_catalog::ServiceCatalog;
 use crate::cli::service_import::{
-    import_plan_from_compose_file, parse_renames, ComposeImportRequest, ServiceImportPlan,
-    ServiceImportReview,
+    import_plan_from_compose_str, parse_renames, upstream_changes, ComposeImportRequest,
+    ImportProvenance, ImportSourceKind, ServiceImportPlan, ServiceImportReview,
 };
 use crate::cli::stacker_client::{self, StackerClient};
 use crate::console::commands::CallableTrait;
 use dialoguer::{Confirm, FuzzySelect};
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 
 const DEFAULT_CONFIG_FILE: &str = "stacker.yml";
+const IMPORT_PROVENANCE_DIR: &str = ".stacker/imports";
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // service add
@@ -56,9 +62,10 @@ impl CallableTrait for ServiceAddCommand {
             }));
         }
 
-        // Load existing config without resolving ${VAR} placeholders so
-        // that sensitive values from .env are not written back to the file.
-        let mut config = StackerConfig::from_file_raw(path)?;
+        // Load the file's own layer without resolving ${VAR} placeholders so
+        // that sensitive values from .env and base values are not written
+        // back to the file.
+        let (mut config, layer) = StackerConfig::from_file_layer(path)?;
 
         // Resolve name — either from arg or interactive fuzzy picker
         let chosen_name = match &self.name {
@@ -129,7 +136,7 @@ impl CallableTrait for ServiceAddCommand {
         config.services.push(entry.service.clone());
 
         // Serialize back to YAML
-        let yaml = serde_yaml::to_string(&config).map_err(|e| {
+        let yaml = layer.render(&config).map_err(|e| {
             CliError::ConfigValidation(format!("Failed to serialize config: {}", e))
         })?;
 
@@ -292,15 +299,22 @@ impl CallableTrait for ServiceDeployCommand {
 // service import
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
-/// `stacker service import <name> --from-compose <path> [--service <compose-service>]`
+/// `stacker service import <name> --from-compose <path> | --from-github OWNER/REPO [--ref REF] | --from-url URL --sha256 HEX`
 ///
-/// Parses a local Docker Compose file, prints a safety review, and appends
+/// Parses a Docker Compose file, prints a safety review, and appends
 /// selected image-backed services to `stacker.yml` only after confirmation.
+/// GitHub sources are pinned to the commit the ref resolves to; the source
+/// is recorded under `.stacker/imports/` so a re-import can show what
+/// changed upstream.
 pub struct ServiceImportCommand {
     pub name: String,
     pub from_compose: Option<PathBuf>,
     pub from_github: Option<String>,
     pub from_url: Option<String>,
+    pub git_ref: Option<String>,
+    pub compose_path: Option<String>,
+    /// Expected SHA-256 of a `--from-url` compose file; required with it.
+    pub sha256: Option<String>,
     pub service: Option<String>,
     pub renames: Vec<String>,
     pub file: Option<String>,
@@ -328,6 +342,9 @@ impl ServiceImportCommand {
             from_compose,
             from_github,
             from_url,
+            git_ref: None,
+            compose_path: None,
+            sha256: None,
             service,
             renames,
             file,
@@ -336,6 +353,107 @@ impl ServiceImportCommand {
             json,
         }
     }
+
+    /// Pin a `--from-github` import to a branch, tag or commit and/or point
+    /// at a compose file other than the conventional names.
+    pub fn with_remote_options(
+        mut self,
+        git_ref: Option<String>,
+        compose_path: Option<String>,
+    ) -> Self {
+        self.git_ref = git_ref;
+        self.compose_path = compose_path;
+        self
+    }
+
+    /// Pin a `--from-url` import to the SHA-256 of the compose file.
+    pub fn with_sha256(mut self, sha256: Option<String>) -> Self {
+        self.sha256 = sha256;
+        self
+    }
+
+    /// Read the compose document from whichever source was selected and
+    /// describe where it came from.
+    fn load_source(&self) -> Result<(String, ImportProvenance), CliError> {
+        let selected = [
+            self.from_compose.is_some(),
+            self.from_github.is_some(),
+            self.from_url.is_some(),
+        ]
+        .iter()
+        .filter(|selected| **selected)
+        .count();
+        if selected != 1 {
+            return Err(CliError::ConfigValidation(
+                "Specify exactly one source: --from-compose <path>, --from-github <owner/repo> or --from-url <url>."
+                    .to_string(),
+            ));
+        }
+
+        if let Some(compose_path) = &self.from_compose {
+            let content = std::fs::read_to_string(compose_path).map_err(|err| {
+                CliError::ConfigValidation(format!(
+                    "Failed to read compose file '{}': {}",
+                    compose_path.display(),
+                    err
+                ))
+            })?;
+            let provenance = ImportProvenance {
+                import_name: self.name.clone(),
+                source: ImportSourceKind::Compose,
+                location: compose_path.display().to_string(),
+                requested_ref: None,
+                resolved_commit: None,
+                compose_path: None,
+                fetched_url: None,
+                sha256: format!("{:x}", Sha256::digest(content.as_bytes())),
+                imported_at: chrono::Utc::now().to_rfc3339(),
+                services: Vec::new(),
+            };
+            return Ok((content, provenance));
+        }
+
+        let (source, location, fetched) = if let Some(repo) = &self.from_github {
+            eprintln!("⬇ Fetching compose file from {}...", repo);
+            let fetched =
+                fetch_github_compose(repo, self.git_ref.as_deref(), self.compose_path.as_deref())?;
+            (ImportSourceKind::Github, repo.clone(), fetched)
+        } else {
+            let url = self.from_url.clone().unwrap_or_default();
+            let sha256 = self.sha256.as_deref().ok_or_else(|| {
+                CliError::ConfigValidation(
+                    "--from-url requires --sha256 <hex>, the SHA-256 of the compose file to import."
+                        .to_string(),
+                )
+            })?;
+            eprintln!("⬇ Fetching compose file from {}...", url);
+            let fetched = fetch_compose_url(&url, sha256)?;
+            (ImportSourceKind::Url, url.clone(), fetched)
+        };
+
+        let FetchedCompose {
+            content,
+            url,
+            requested_ref,
+            resolved_commit,
+            compose_path,
+            sha256,
+            ..
+        } = fetched;
+        let provenance = ImportProvenance {
+            import_name: self.name.clone(),
+            source,
+            location,
+            requested_ref,
+            resolved_commit,
+            compose_path,
+            fetched_url: Some(url),
+            sha256,
+            imported_at: chrono::Utc::now().to_rfc3339(),
+            services: Vec::new(),
+        };
+        Ok((content, provenance))
+    }
 }
 
 impl CallableTrait for ServiceImportCommand {
@@ -343,20 +461,6 @@ impl CallableTrait for ServiceImportCommand {
         let config_path = self.file.as_deref().unwrap_or(DEFAULT_CONFIG_FILE);
         let path = Path::new(config_path);
 
-        if self.from_github.is_some() || self.from_url.is_some() {
-            return Err(Box::new(CliError::ConfigValidation(
-                "Remote custom service import is planned but not implemented yet. Download or inspect the Compose file yourself, then run `stacker service import <name> --from-compose <path> --review`."
-                    .to_string(),
-            )));
-        }
-
-        let compose_path = self.from_compose.as_ref().ok_or_else(|| {
-            CliError::ConfigValidation(
-                "Specify a local Compose file with --from-compose <path>. Remote GitHub/URL import is not fetched by default."
-                    .to_string(),
-            )
-        })?;
-
         if !path.exists() {
             return Err(Box::new(CliError::ConfigNotFound {
                 path: path.to_path_buf(),
@@ -369,9 +473,32 @@ impl CallableTrait for ServiceImportCommand {
             selected_service: self.service.clone(),
             renames,
         };
-        let plan = import_plan_from_compose_file(compose_path, &request)?;
+        let (compose_content, mut provenance) = self.load_source()?;
+        let mut plan = import_plan_from_compose_str(&compose_content, &request)?;
+        let project_dir = project_dir_for_config(path);
+        let previous = load_import_provenance(&project_dir, &self.name);
+        if let Some(previous) = &previous {
+            plan.review.upstream_changes = upstream_changes(previous, &plan);
+        }
+        provenance.services = plan.services.clone();
+
+        if !self.json {
+            print_import_source(&provenance, previous.as_ref());
+        }
+
         let config = StackerConfig::from_file_raw(path)?;
-        validate_no_duplicate_services(&config, &plan)?;
+        // Re-importing replaces what the previous import of this name added.
+        let replaced = previous
+            .as_ref()
+            .map(|previous| {
+                previous
+                    .services
+                    .iter()
+                    .map(|service| service.name.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        validate_no_duplicate_services(&config, &plan, &replaced)?;
 
         if self.json && self.review {
             let output = ServiceImportCommandOutput {
@@ -415,7 +542,8 @@ impl CallableTrait for ServiceImportCommand {
             }
         }
 
-        let backup_path = import_services_into_config(path, config, &plan)?;
+        let backup_path = import_services_into_config(path, &plan, &replaced)?;
+        let provenance_path = write_import_provenance(&project_dir, &provenance)?;
         let updated_config = StackerConfig::from_file_raw(path)?;
         let imported_service_names: Vec<String> = plan
             .services
@@ -423,7 +551,7 @@ impl CallableTrait for ServiceImportCommand {
             .map(|service| service.name.clone())
             .collect();
         let compose_sync = sync_configured_compose_services(
-            &project_dir_for_config(path),
+            &project_dir,
             &updated_config,
             &imported_service_names,
         )?;
@@ -453,6 +581,7 @@ impl CallableTrait for ServiceImportCommand {
                 config_path
             );
             eprintln!("  Backup saved to {}", backup_path);
+            eprintln!("  Import source recorded in {}", provenance_path.display());
             print_compose_sync_result(&compose_sync);
         }
 
@@ -583,7 +712,7 @@ impl CallableTrait for ServiceRemoveCommand {
             }));
         }
 
-        let mut config = StackerConfig::from_file_raw(path)?;
+        let (mut config, layer) = StackerConfig::from_file_layer(path)?;
         let canonical = ServiceCatalog::resolve_alias(&self.name);
 
         if !config.services.iter().any(|s| s.name == canonical) {
@@ -604,7 +733,7 @@ impl CallableTrait for ServiceRemoveCommand {
 
         config.services.retain(|s| s.name != canonical);
 
-        let yaml = serde_yaml::to_string(&config).map_err(|e| {
+        let yaml = layer.render(&config).map_err(|e| {
             CliError::ConfigValidation(format!("Failed to serialize config: {}", e))
         })?;
 
@@ -633,8 +762,12 @@ struct ServiceImportCommandOutput<'a> {
 fn validate_no_duplicate_services(
     config: &StackerConfig,
     plan: &ServiceImportPlan,
+    replaced: &[String],
 ) -> Result<(), CliError> {
     for imported in &plan.services {
+        if replaced.contains(&imported.name) {
+            continue;
+        }
         if config.services.iter().any(|svc| svc.name == imported.name) {
             return Err(CliError::ConfigValidation(format!(
                 "Service '{}' already exists in stacker.yml. Use --rename old=new or choose a different import name.",
@@ -647,14 +780,19 @@ fn validate_no_duplicate_services(
 
 fn import_services_into_config(
     path: &Path,
-    mut config: StackerConfig,
     plan: &ServiceImportPlan,
+    replaced: &[String],
 ) -> Result<String, Box<dyn std::error::Error>> {
+    let (mut config, layer) = StackerConfig::from_file_layer(path)?;
+    config
+        .services
+        .retain(|service| !replaced.contains(&service.name));
     for service in &plan.services {
         config.services.push(service.clone());
     }
 
-    let yaml = serde_yaml::to_string(&config)
+    let yaml = layer
+        .render(&config)
         .map_err(|e| CliError::ConfigValidation(format!("Failed to serialize config: {}", e)))?;
 
     let config_path = path.to_string_lossy().to_string();
@@ -664,6 +802,74 @@ fn import_services_into_config(
     Ok(backup_path)
 }
 
+fn import_provenance_path(project_dir: &Path, import_name: &str) -> PathBuf {
+    project_dir
+        .join(IMPORT_PROVENANCE_DIR)
+        .join(format!("{}.json", import_name))
+}
+
+fn load_import_provenance(project_dir: &Path, import_name: &str) -> Option<ImportProvenance> {
+    let content = std::fs::read_to_string(import_provenance_path(project_dir, import_name)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_import_provenance(
+    project_dir: &Path,
+    provenance: &ImportProvenance,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = import_provenance_path(project_dir, &provenance.import_name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(provenance)?)?;
+    Ok(path)
+}
+
+fn print_import_source(provenance: &ImportProvenance, previous: Option<&ImportProvenance>) {
+    let pinned = match (&provenance.resolved_commit, &provenance.compose_path) {
+        (Some(commit), Some(compose_path)) => {
+            format!(
+                "{}@{} ({})",
+                provenance.location,
+                short_sha(commit),
+                compose_path
+            )
+        }
+        _ => provenance.location.clone(),
+    };
+    println!(
+        "Source: {} [sha256 {}]",
+        pinned,
+        short_sha(&provenance.sha256)
+    );
+
+    if let Some(previous) = previous {
+        let previous_pin = previous
+            .resolved_commit
+            .as_deref()
+            .unwrap_or(&previous.sha256);
+        if previous.sha256 == provenance.sha256 {
+            println!(
+                "  Unchanged since last import ({}, {})",
+                short_sha(previous_pin),
+                previous.imported_at
+            );
+        } else {
+            println!(
+                "  Previously imported from {} at {} ({})",
+                previous.location,
+                short_sha(previous_pin),
+                previous.imported_at
+            );
+        }
+    }
+    println!();
+}
+
+fn short_sha(value: &str) -> &str {
+    value.get(..12).unwrap_or(value)
+}
+
 fn print_import_plan(plan: &ServiceImportPlan) {
     let review = &plan.review;
     println!("Custom service import review: {}", review.import_name);
@@ -700,6 +906,17 @@ fn print_import_plan(plan: &ServiceImportPlan) {
         }
     }
 
+    if !review.upstream_changes.is_empty() {
+        println!();
+        println!("  Changes upstream since last import:");
+        for change in &review.upstream_changes {
+            println!(
+                "    - [{}] {}: {}",
+                change.service, change.kind, change.detail
+            );
+        }
+    }
+
     if !review.guidance.is_empty() {
         println!();
         println!("  Guidance:");
@@ -913,6 +1130,62 @@ services:
             service.environment.get("POSTMASTER_ADDRESS").unwrap(),
             "postmaster@example.com"
         );
+
+        let provenance = load_import_provenance(dir.path(), "smtp").unwrap();
+        assert_eq!(provenance.source, ImportSourceKind::Compose);
+        assert_eq!(provenance.services.len(), 1);
+        assert_eq!(
+            provenance.services[0]
+                .environment
+                .get("ACCOUNT_PASSWORD")
+                .unwrap(),
+            "${ACCOUNT_PASSWORD}"
+        );
+    }
+
+    #[test]
+    fn service_import_again_replaces_the_previous_import() {
+        let dir = TempDir::new().unwrap();
+        let config_path = write_config(
+            &dir,
+            r#"
+name: test-app
+app:
+  type: static
+services: []
+"#,
+        );
+        let compose = "services:\n  mailserver:\n    image: mail:1\n";
+        let compose_path = write_compose(&dir, compose);
+        import_command(&config_path, &compose_path, false, true)
+            .call()
+            .unwrap();
+
+        std::fs::write(&compose_path, compose.replace("mail:1", "mail:2")).unwrap();
+        import_command(&config_path, &compose_path, false, true)
+            .call()
+            .unwrap();
+
+        let config = StackerConfig::from_file_raw(&config_path).unwrap();
+        let smtp = config
+            .services
+            .iter()
+            .filter(|service| service.name == "smtp")
+            .collect::<Vec<_>>();
+        assert_eq!(smtp.len(), 1);
+        assert_eq!(smtp[0].image, "mail:2");
+    }
+
+    #[test]
+    fn service_import_requires_exactly_one_source() {
+        let dir = TempDir::new().unwrap();
+        let config_path = write_config(&dir, "name: test-app\n");
+        let compose_path = write_compose(&dir, "services: {}\n");
+
+        let mut command = import_command(&config_path, &compose_path, true, false);
+        command.from_url = Some("https://example.com/compose.yml".to_string());
+        let err = command.call().unwrap_err();
+        assert!(err.to_string().contains("exactly one source"));
     }
 }
 
@@ -1,9 +1,12 @@
 use std::path::Path;
 
+use crate::cli::acme;
 use crate::cli::config_parser::{CloudOrchestrator, DeployTarget, ProxyType, StackerConfig};
 use crate::cli::credentials::{CredentialsManager, StoredCredentials};
 use crate::cli::error::CliError;
-use crate::cli::install_runner::{CommandExecutor, CommandOutput, ShellExecutor};
+use crate::cli::install_runner::{
+    resolve_compose_cmd, CommandExecutor, CommandOutput, ShellExecutor,
+};
 use crate::cli::local_compose::resolve_local_compose_path;
 use crate::cli::stacker_client::{self, DeploymentStatusInfo, ServerInfo, StackerClient};
 use crate::console::commands::cli::ssh_key::{format_ssh_command, local_backup_private_key_path};
@@ -31,14 +34,10 @@ impl StatusCommand {
     }
 }
 
-/// Build `docker compose ps` arguments.
+/// Build `compose ps` arguments, without the compose command itself
+/// (see [`resolve_compose_cmd`]).
 pub fn build_status_args(compose_path: &str, json: bool) -> Vec<String> {
-    let mut args = vec![
-        "compose".to_string(),
-        "-f".to_string(),
-        compose_path.to_string(),
-        "ps".to_string(),
-    ];
+    let mut args = vec!["-f".to_string(), compose_path.to_string(), "ps".to_string()];
 
     if json {
         args.push("--format".to_string());
@@ -58,12 +57,48 @@ pub fn run_status(
 
     let compose_str = compose_path.to_string_lossy().to_string();
     let args = build_status_args(&compose_str, json);
-    let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let (cmd, prefix) = resolve_compose_cmd(executor)?;
+    let mut full_args: Vec<&str> = prefix;
+    full_args.extend(args.iter().map(String::as_str));
 
-    let output = executor.execute("docker", &args_refs)?;
+    let output = executor.execute(cmd, &full_args)?;
     Ok(output)
 }
 
+/// Show ACME certificate expiry when the local stack runs the certbot companion.
+fn print_local_certificates(project_dir: &Path, executor: &dyn CommandExecutor) {
+    let Ok(compose_path) = resolve_local_compose_path(project_dir) else {
+        return;
+    };
+    if !acme::compose_has_certbot(&compose_path) {
+        return;
+    }
+    print_certificates(&acme::local_certificate_status(&compose_path, executor));
+}
+
+/// Show ACME certificate expiry reported by a remote deployment's agent.
+async fn print_remote_certificates(client: &StackerClient, deployment_hash: &str) {
+    match acme::remote_certificate_status(client, deployment_hash).await {
+        Ok(certificates) => print_certificates(&certificates),
+        Err(err) => {
+            eprintln!();
+            eprintln!("Certificates: could not be read from the agent ({})", err);
+        }
+    }
+}
+
+fn print_certificates(certificates: &[acme::CertificateStatus]) {
+    eprintln!();
+    if certificates.is_empty() {
+        eprintln!("Certificates: none issued yet (certbot retries every 5 minutes)");
+        return;
+    }
+    eprintln!("Certificates:");
+    for cert in certificates {
+        eprintln!("  {}", cert.summary());
+    }
+}
+
 // ── Cloud deployment status ─────────────────────────
 
 /// Terminal statuses — once reached, `--watch` stops polling.
@@ -278,13 +313,19 @@ fn print_deployment_status_rich(info: &DeploymentStatusInfo, json: bool, ctx: &S
         // Services
         if !config.services.is_empty() {
             println!("\n── Services ───────────────────────────────");
+            let multi_host = config.services.iter().any(|svc| svc.placement.is_some());
+            let primary = config.deploy.target_name(None);
             for svc in &config.services {
                 let ports_str = if svc.ports.is_empty() {
                     String::new()
                 } else {
                     format!(" (ports: {})", svc.ports.join(", "))
                 };
-                println!("  • {}{}", svc.name, ports_str);
+                let host_str = match svc.placement.as_deref().or(primary.as_deref()) {
+                    Some(host) if multi_host => format!(" on {}", host),
+                    _ => String::new(),
+                };
+                println!("  • {}{}{}", svc.name, ports_str, host_str);
             }
         }
 
@@ -464,6 +505,9 @@ fn run_remote_status(json: bool, watch: bool) -> Result<(), Box<dyn std::error::
                     match status {
                         Some(info) => {
                             print_deployment_status_rich(&info, json, &ctx);
+                            if !json && acme::acme_enabled(&config) {
+                                print_remote_certificates(&client, &info.deployment_hash).await;
+                            }
                             return Ok(());
                         }
                         None => {
@@ -581,6 +625,9 @@ fn run_remote_status(json: bool, watch: bool) -> Result<(), Box<dyn std::error::
                         events: events.as_ref(),
                     };
                     print_deployment_status_rich(&info, json, &ctx);
+                    if !json && acme::acme_enabled(&config) {
+                        print_remote_certificates(&client, &info.deployment_hash).await;
+                    }
                     Ok(())
                 }
                 None => {
@@ -711,6 +758,9 @@ impl CallableTrait for StatusCommand {
             let executor = ShellExecutor;
             let output = run_status(&project_dir, self.json, &executor)?;
             print!("{}", output.stdout);
+            if !self.json {
+                print_local_certificates(&project_dir, &executor);
+            }
 
             if self.watch {
                 eprintln!("Note: --watch is only supported for cloud deployments.");
@@ -738,7 +788,7 @@ mod tests {
     #[test]
     fn test_status_local_constructs_query() {
         let args = build_status_args("/path/compose.yml", false);
-        assert_eq!(args, vec!["compose", "-f", "/path/compose.yml", "ps"]);
+        assert_eq!(args, vec!["-f", "/path/compose.yml", "ps"]);
     }
 
     #[test]
@@ -182,6 +182,8 @@ enum StackerCommands {
         tail: Option<u32>,
         #[arg(long)]
         since: Option<String>,
+        #[arg(long, conflicts_with = "follow")]
+        search: Option<String>,
     },
     /// Show deployment status
     Status {
@@ -494,10 +496,12 @@ fn get_command(
                 follow,
                 tail,
                 since,
+                search,
             } => Ok(Box::new(
                 stacker::console::commands::cli::logs::LogsCommand::new(
                     service, follow, tail, since,
-                ),
+                )
+                .with_search(search),
             )),
             StackerCommands::Status { json, watch } => Ok(Box::new(
                 stacker::console::commands::cli::status::StatusCommand::new(json, watch),
@@ -506,7 +510,7 @@ fn get_command(
                 stacker::console::commands::cli::destroy::DestroyCommand::new(volumes, confirm),
             )),
             StackerCommands::Rollback { version, confirm } => Ok(Box::new(
-                stacker::console::commands::cli::rollback::RollbackCommand::new(version, confirm),
+                stacker::console::commands::cli::rollback::RollbackCommand::new(Some(version), confirm),
             )),
             StackerCommands::Config { command: cfg_cmd } => match cfg_cmd {
                 StackerConfigCommands::Validate { file } => Ok(Box::new(
@@ -7,16 +7,17 @@ pub async fn insert(pool: &PgPool, agent: models::Agent) -> Result<models::Agent
     let query_span = tracing::info_span!("Inserting agent into database");
     sqlx::query_as::<_, models::Agent>(
         r#"
-        INSERT INTO agents (id, deployment_hash, capabilities, version, system_info, 
-                           last_heartbeat, status, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-        RETURNING id, deployment_hash, capabilities, version, system_info, 
+        INSERT INTO agents (id, deployment_hash, capabilities, capability_manifest, version,
+                           system_info, last_heartbeat, status, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id, deployment_hash, capabilities, capability_manifest, version, system_info, 
                   last_heartbeat, status, created_at, updated_at
         "#,
     )
     .bind(agent.id)
     .bind(agent.deployment_hash)
     .bind(agent.capabilities)
+    .bind(agent.capability_manifest)
     .bind(agent.version)
     .bind(agent.system_info)
     .bind(agent.last_heartbeat)
@@ -36,7 +37,7 @@ pub async fn fetch_by_id(pool: &PgPool, agent_id: Uuid) -> Result<Option<models:
     let query_span = tracing::info_span!("Fetching agent by ID");
     sqlx::query_as::<_, models::Agent>(
         r#"
-        SELECT id, deployment_hash, capabilities, version, system_info, 
+        SELECT id, deployment_hash, capabilities, capability_manifest, version, system_info, 
                last_heartbeat, status, created_at, updated_at
         FROM agents 
         WHERE id = $1
@@ -59,7 +60,7 @@ pub async fn fetch_by_deployment_hash(
     let query_span = tracing::info_span!("Fetching agent by deployment_hash");
     sqlx::query_as::<_, models::Agent>(
         r#"
-        SELECT id, deployment_hash, capabilities, version, system_info, 
+        SELECT id, deployment_hash, capabilities, capability_manifest, version, system_info, 
                last_heartbeat, status, created_at, updated_at
         FROM agents 
         WHERE deployment_hash = $1
@@ -83,7 +84,7 @@ pub async fn fetch_active_by_project(
     let query_span = tracing::info_span!("Fetching active agent by project");
     sqlx::query_as::<_, models::Agent>(
         r#"
-        SELECT a.id, a.deployment_hash, a.capabilities, a.version, a.system_info,
+        SELECT a.id, a.deployment_hash, a.capabilities, a.capability_manifest, a.version, a.system_info,
                a.last_heartbeat, a.status, a.created_at, a.updated_at
         FROM agents a
         JOIN deployment d ON a.deployment_hash = d.deployment_hash
@@ -129,15 +130,16 @@ pub async fn update(pool: &PgPool, agent: models::Agent) -> Result<models::Agent
     sqlx::query_as::<_, models::Agent>(
         r#"
         UPDATE agents 
-        SET capabilities = $2, version = $3, system_info = $4, 
-            last_heartbeat = $5, status = $6, updated_at = NOW()
+        SET capabilities = $2, capability_manifest = $3, version = $4, system_info = $5,
+            last_heartbeat = $6, status = $7, updated_at = NOW()
         WHERE id = $1
-        RETURNING id, deployment_hash, capabilities, version, system_info, 
+        RETURNING id, deployment_hash, capabilities, capability_manifest, version, system_info, 
                   last_heartbeat, status, created_at, updated_at
         "#,
     )
     .bind(agent.id)
     .bind(agent.capabilities)
+    .bind(agent.capability_manifest)
     .bind(agent.version)
     .bind(agent.system_info)
     .bind(agent.last_heartbeat)
@@ -52,6 +52,34 @@ pub async fn insert_batch(
     Ok(inserted)
 }
 
+/// Insert one event raised by the Stacker server itself rather than reported
+/// by the Status Panel, so it has no `status_panel_id`.
+#[tracing::instrument(name = "Insert agent audit event", skip(pool, payload))]
+pub async fn insert_event(
+    pool: &PgPool,
+    installation_hash: &str,
+    event_type: &str,
+    payload: &serde_json::Value,
+) -> Result<AgentAuditLog, sqlx::Error> {
+    sqlx::query_as::<_, AgentAuditLog>(
+        r#"
+        INSERT INTO agent_audit_log (installation_hash, event_type, payload, created_at)
+        VALUES ($1, $2, $3, NOW())
+        RETURNING id, installation_hash, event_type, payload, status_panel_id,
+                  received_at, created_at
+        "#,
+    )
+    .bind(installation_hash)
+    .bind(event_type)
+    .bind(payload)
+    .fetch_one(pool)
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to insert audit event: {:?}", err);
+        err
+    })
+}
+
 /// Fetch recent audit events with optional filters.
 /// `limit` is capped at 100.
 #[tracing::instrument(name = "Fetch recent audit events", skip(pool))]
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{AgentCertificate, AgentTokenRotation};
+
+pub async fn fetch_token_rotation(
+    pool: &PgPool,
+    agent_id: Uuid,
+) -> Result<Option<AgentTokenRotation>, String> {
+    sqlx::query_as::<_, AgentTokenRotation>(
+        r#"
+        SELECT *
+        FROM agent_token_rotation
+        WHERE agent_id = $1
+        "#,
+    )
+    .bind(agent_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch agent token rotation: {}", e))
+}
+
+/// Record a token offered to the agent, replacing an expired offer.
+pub async fn offer_token(
+    pool: &PgPool,
+    agent_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO agent_token_rotation (agent_id, pending_token_hash, pending_expires_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (agent_id) DO UPDATE
+        SET pending_token_hash = EXCLUDED.pending_token_hash,
+            pending_expires_at = EXCLUDED.pending_expires_at,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(agent_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .execute(pool)
+    .await
+    .map(|_| ())
+    .map_err(|e| format!("Failed to record offered agent token: {}", e))
+}
+
+/// The agent switched to the offered token: keep the replaced one until
+/// `previous_expires_at`. Returns false when the offer was already taken.
+pub async fn promote_token(
+    pool: &PgPool,
+    agent_id: Uuid,
+    pending_token_hash: &str,
+    previous_token_hash: &str,
+    previous_expires_at: DateTime<Utc>,
+) -> Result<bool, String> {
+    sqlx::query(
+        r#"
+        UPDATE agent_token_rotation
+        SET rotated_at = NOW(),
+            pending_token_hash = NULL,
+            pending_expires_at = NULL,
+            previous_token_hash = $3,
+            previous_expires_at = $4,
+            updated_at = NOW()
+        WHERE agent_id = $1
+          AND pending_token_hash = $2
+        "#,
+    )
+    .bind(agent_id)
+    .bind(pending_token_hash)
+    .bind(previous_token_hash)
+    .bind(previous_expires_at)
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(|e| format!("Failed to promote agent token: {}", e))
+}
+
+/// A token was set by hand: drop any offer and overlap window.
+pub async fn reset_token_rotation(pool: &PgPool, agent_id: Uuid) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO agent_token_rotation (agent_id, rotated_at)
+        VALUES ($1, NOW())
+        ON CONFLICT (agent_id) DO UPDATE
+        SET rotated_at = NOW(),
+            pending_token_hash = NULL,
+            pending_expires_at = NULL,
+            previous_token_hash = NULL,
+            previous_expires_at = NULL,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(agent_id)
+    .execute(pool)
+    .await
+    .map(|_| ())
+    .map_err(|e| format!("Failed to reset agent token rotation: {}", e))
+}
+
+pub async fn insert_certificate(
+    pool: &PgPool,
+    agent_id: Uuid,
+    serial_number: &str,
+    fingerprint_sha256: &str,
+    not_after: DateTime<Utc>,
+) -> Result<AgentCertificate, String> {
+    sqlx::query_as::<_, AgentCertificate>(
+        r#"
+        INSERT INTO agent_certificates (agent_id, serial_number, fingerprint_sha256, not_after)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(agent_id)
+    .bind(serial_number)
+    .bind(fingerprint_sha256)
+    .bind(not_after)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to record agent certificate: {}", e))
+}
+
+/// The agent's unexpired, unrevoked certificate with this fingerprint.
+pub async fn fetch_active_certificate(
+    pool: &PgPool,
+    agent_id: Uuid,
+    fingerprint_sha256: &str,
+) -> Result<Option<AgentCertificate>, String> {
+    sqlx::query_as::<_, AgentCertificate>(
+        r#"
+        SELECT *
+        FROM agent_certificates
+        WHERE agent_id = $1
+          AND fingerprint_sha256 = $2
+          AND revoked_at IS NULL
+          AND not_after > NOW()
+        "#,
+    )
+    .bind(agent_id)
+    .bind(fingerprint_sha256)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch agent certificate: {}", e))
+}
@@ -72,6 +72,84 @@ pub async fn add_to_queue(
     .map(|_| ())
 }
 
+/// Insert commands and queue them in one transaction, so a failure leaves
+/// none of them behind. Batch members that depend on other steps are inserted
+/// but held until [`release_to_queue`].
+#[tracing::instrument(name = "Insert and queue commands", skip_all)]
+pub async fn insert_and_queue(pool: &PgPool, commands: &[Command]) -> Result<Vec<Command>, String> {
+    let mut tx = pool.begin().await.map_err(|err| {
+        tracing::error!("Failed to start transaction: {:?}", err);
+        format!("Failed to start transaction: {}", err)
+    })?;
+
+    let mut saved = Vec::with_capacity(commands.len());
+    for command in commands {
+        let row = sqlx::query_as::<_, Command>(
+            r#"
+            INSERT INTO commands (
+                id, command_id, deployment_hash, type, status, priority,
+                parameters, result, error, created_by, created_at, updated_at,
+                timeout_seconds, metadata
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING id, command_id, deployment_hash, type, status, priority,
+                      parameters, result, error, created_by, created_at, updated_at,
+                      timeout_seconds, metadata
+            "#,
+        )
+        .bind(command.id)
+        .bind(&command.command_id)
+        .bind(&command.deployment_hash)
+        .bind(&command.r#type)
+        .bind(&command.status)
+        .bind(&command.priority)
+        .bind(&command.parameters)
+        .bind(&command.result)
+        .bind(&command.error)
+        .bind(&command.created_by)
+        .bind(command.created_at)
+        .bind(command.updated_at)
+        .bind(command.timeout_seconds)
+        .bind(&command.metadata)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to insert command: {:?}", err);
+            format!("Failed to insert command: {}", err)
+        })?;
+
+        let held = command
+            .batch()
+            .is_some_and(|batch| !batch.depends_on.is_empty());
+        if !held {
+            let priority =
+                CommandPriority::from_name(&command.priority).unwrap_or(CommandPriority::Normal);
+            sqlx::query(
+                r#"
+                INSERT INTO command_queue (command_id, deployment_hash, priority)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(&command.command_id)
+            .bind(&command.deployment_hash)
+            .bind(priority.to_int())
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to add command to queue: {:?}", err);
+                format!("Failed to add command to queue: {}", err)
+            })?;
+        }
+        saved.push(row);
+    }
+
+    tx.commit().await.map_err(|err| {
+        tracing::error!("Failed to commit transaction: {:?}", err);
+        format!("Failed to commit transaction: {}", err)
+    })?;
+    Ok(saved)
+}
+
 /// Fetch next command for a deployment (highest priority, oldest first)
 #[tracing::instrument(name = "Fetch next command for deployment", skip(pool))]
 pub async fn fetch_next_for_deployment(
@@ -123,6 +201,80 @@ pub async fn remove_from_queue(pool: &PgPool, command_id: &str) -> Result<(), St
     .map(|_| ())
 }
 
+/// Take the next queued command for a deployment and mark it `sent` in one
+/// statement. `SKIP LOCKED` keeps concurrent waiters (a long-poll and a
+/// command stream of the same agent) from delivering the same command twice.
+#[tracing::instrument(name = "Claim next command for deployment", skip(pool))]
+pub async fn claim_next_for_deployment(
+    pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Option<Command>, String> {
+    let query_span = tracing::info_span!("Claiming next command from queue");
+    sqlx::query_as::<_, Command>(
+        r#"
+        WITH next AS (
+            SELECT command_id
+            FROM command_queue
+            WHERE deployment_hash = $1
+            ORDER BY priority DESC, created_at ASC
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        ), claimed AS (
+            DELETE FROM command_queue q
+            USING next
+            WHERE q.command_id = next.command_id
+            RETURNING q.command_id
+        )
+        UPDATE commands c
+        SET status = $2, updated_at = NOW()
+        FROM claimed
+        WHERE c.command_id = claimed.command_id
+        RETURNING c.id, c.command_id, c.deployment_hash, c.type, c.status, c.priority,
+                  c.parameters, c.result, c.error, c.created_by, c.created_at, c.updated_at,
+                  c.timeout_seconds, c.metadata
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(CommandStatus::Sent.to_string())
+    .fetch_optional(pool)
+    .instrument(query_span)
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to claim next command: {:?}", err);
+        format!("Failed to claim next command: {}", err)
+    })
+}
+
+/// Put a claimed command that never reached the agent back in the queue,
+/// ahead of commands queued after it. Does nothing once the agent reported
+/// on it.
+#[tracing::instrument(name = "Return command to queue", skip(pool, command))]
+pub async fn return_to_queue(pool: &PgPool, command: &Command) -> Result<bool, String> {
+    let priority = CommandPriority::from_name(&command.priority).unwrap_or(CommandPriority::Normal);
+    sqlx::query(
+        r#"
+        WITH reverted AS (
+            UPDATE commands
+            SET status = 'queued', updated_at = NOW()
+            WHERE command_id = $1 AND status = 'sent'
+            RETURNING command_id, deployment_hash, created_at
+        )
+        INSERT INTO command_queue (command_id, deployment_hash, priority, created_at)
+        SELECT command_id, deployment_hash, $2, created_at
+        FROM reverted
+        "#,
+    )
+    .bind(&command.command_id)
+    .bind(priority.to_int())
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(|err| {
+        tracing::error!("Failed to return command to queue: {:?}", err);
+        format!("Failed to return command to queue: {}", err)
+    })
+}
+
 /// Update command status
 #[tracing::instrument(name = "Update command status", skip(pool))]
 pub async fn update_status(
@@ -450,3 +602,172 @@ pub async fn cancel(pool: &PgPool, command_id: &str) -> Result<Command, String>
 
     Ok(command)
 }
+
+/// Members of a command batch, oldest first.
+#[tracing::instrument(name = "Fetch commands of batch", skip(pool))]
+pub async fn fetch_by_batch(
+    pool: &PgPool,
+    deployment_hash: &str,
+    batch_id: &str,
+) -> Result<Vec<Command>, String> {
+    sqlx::query_as::<_, Command>(
+        r#"
+        SELECT id, command_id, deployment_hash, type, status, priority,
+               parameters, result, error, created_by, created_at, updated_at,
+               timeout_seconds, metadata
+        FROM commands
+        WHERE deployment_hash = $1
+          AND metadata->'batch'->>'id' = $2
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(batch_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to fetch batch commands: {:?}", err);
+        format!("Failed to fetch batch commands: {}", err)
+    })
+}
+
+/// Queue a held batch member. A no-op unless the command is still `queued`
+/// and not in the queue yet, so concurrent releases queue it once.
+#[tracing::instrument(name = "Release batch command", skip(pool))]
+pub async fn release_to_queue(
+    pool: &PgPool,
+    command_id: &str,
+    priority: &CommandPriority,
+) -> Result<bool, String> {
+    sqlx::query(
+        r#"
+        INSERT INTO command_queue (command_id, deployment_hash, priority)
+        SELECT c.command_id, c.deployment_hash, $2
+        FROM commands c
+        WHERE c.command_id = $1
+          AND c.status = 'queued'
+          AND NOT EXISTS (SELECT 1 FROM command_queue q WHERE q.command_id = c.command_id)
+        "#,
+    )
+    .bind(command_id)
+    .bind(priority.to_int())
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(|err| {
+        tracing::error!("Failed to release batch command: {:?}", err);
+        format!("Failed to release batch command: {}", err)
+    })
+}
+
+/// Cancel a command the agent has not picked up, recording why.
+#[tracing::instrument(name = "Cancel pending command", skip(pool, error))]
+pub async fn cancel_pending(
+    pool: &PgPool,
+    command_id: &str,
+    error: JsonValue,
+) -> Result<bool, String> {
+    let _ = remove_from_queue(pool, command_id).await;
+    sqlx::query(
+        r#"
+        UPDATE commands
+        SET status = 'cancelled', error = $2, updated_at = NOW()
+        WHERE command_id = $1
+          AND status = 'queued'
+        "#,
+    )
+    .bind(command_id)
+    .bind(error)
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(|err| {
+        tracing::error!("Failed to cancel pending command: {:?}", err);
+        format!("Failed to cancel pending command: {}", err)
+    })
+}
+
+/// Completed commands of one type for a deployment, newest first.
+#[tracing::instrument(name = "Fetch completed commands by type", skip(pool))]
+pub async fn fetch_completed_by_type(
+    pool: &PgPool,
+    deployment_hash: &str,
+    command_type: &str,
+    limit: i64,
+) -> Result<Vec<Command>, String> {
+    sqlx::query_as::<_, Command>(
+        r#"
+        SELECT id, command_id, deployment_hash, type, status, priority,
+               parameters, result, error, created_by, created_at, updated_at,
+               timeout_seconds, metadata
+        FROM commands
+        WHERE deployment_hash = $1
+          AND type = $2
+          AND status = 'completed'
+        ORDER BY updated_at DESC, id DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(command_type)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to fetch completed commands: {:?}", err);
+        format!("Failed to fetch completed commands: {}", err)
+    })
+}
+
+/// Drop the base64 chunk of a file transfer command once it was acknowledged:
+/// the `data` parameter of an `upload_file`, or the `data` result of a
+/// `download_file`. File bodies are not kept in the command log.
+#[tracing::instrument(name = "Clear file chunk", skip(pool))]
+pub async fn clear_file_chunk(pool: &PgPool, command_id: &str) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        UPDATE commands
+        SET parameters = CASE WHEN type = 'upload_file' THEN parameters - 'data' ELSE parameters END,
+            result = CASE WHEN type = 'download_file' THEN result - 'data' ELSE result END
+        WHERE command_id = $1
+          AND type IN ('upload_file', 'download_file')
+        "#,
+    )
+    .bind(command_id)
+    .execute(pool)
+    .await
+    .map(|_| ())
+    .map_err(|err| {
+        tracing::error!("Failed to clear file chunk: {:?}", err);
+        format!("Failed to clear file chunk: {}", err)
+    })
+}
+
+/// [`clear_file_chunk`] for every transfer command last updated before
+/// `cutoff`, including chunks nobody acknowledged. Returns how many were
+/// cleared.
+#[tracing::instrument(name = "Purge file chunks", skip(pool))]
+pub async fn purge_file_chunks(
+    pool: &PgPool,
+    cutoff: chrono::DateTime<chrono::Utc>,
+) -> Result<u64, String> {
+    sqlx::query(
+        r#"
+        UPDATE commands
+        SET parameters = CASE WHEN type = 'upload_file' THEN parameters - 'data' ELSE parameters END,
+            result = CASE WHEN type = 'download_file' THEN result - 'data' ELSE result END
+        WHERE type IN ('upload_file', 'download_file')
+          AND updated_at < $1
+          AND ((type = 'upload_file' AND parameters ? 'data')
+               OR (type = 'download_file' AND result ? 'data'))
+        "#,
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await
+    .map(|done| done.rows_affected())
+    .map_err(|err| {
+        tracing::error!("Failed to purge file chunks: {:?}", err);
+        format!("Failed to purge file chunks: {}", err)
+    })
+}
@@ -0,0 +1,136 @@
+use crate::models::CommandSchedule;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+pub async fn insert(pool: &PgPool, schedule: &CommandSchedule) -> Result<CommandSchedule, String> {
+    sqlx::query_as::<_, CommandSchedule>(
+        r#"
+        INSERT INTO agent_command_schedule (
+            schedule_id, deployment_hash, name, cron, command_type, parameters,
+            priority, timeout_seconds, missed_run_policy, enabled, created_by,
+            next_run_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING *
+        "#,
+    )
+    .bind(&schedule.schedule_id)
+    .bind(&schedule.deployment_hash)
+    .bind(&schedule.name)
+    .bind(&schedule.cron)
+    .bind(&schedule.command_type)
+    .bind(&schedule.parameters)
+    .bind(&schedule.priority)
+    .bind(schedule.timeout_seconds)
+    .bind(&schedule.missed_run_policy)
+    .bind(schedule.enabled)
+    .bind(&schedule.created_by)
+    .bind(schedule.next_run_at)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to save command schedule: {}", e))
+}
+
+/// Schedules of a deployment, next to run first.
+pub async fn list_by_deployment(
+    pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Vec<CommandSchedule>, String> {
+    sqlx::query_as::<_, CommandSchedule>(
+        r#"
+        SELECT *
+        FROM agent_command_schedule
+        WHERE deployment_hash = $1
+        ORDER BY next_run_at ASC
+        "#,
+    )
+    .bind(deployment_hash)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list command schedules: {}", e))
+}
+
+/// Delete a schedule of a deployment. Returns whether one was removed.
+pub async fn delete(
+    pool: &PgPool,
+    deployment_hash: &str,
+    schedule_id: &str,
+) -> Result<bool, String> {
+    sqlx::query(
+        r#"
+        DELETE FROM agent_command_schedule
+        WHERE deployment_hash = $1
+          AND schedule_id = $2
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(schedule_id)
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(|e| format!("Failed to delete command schedule: {}", e))
+}
+
+/// Enabled schedules whose next run has come, across all deployments.
+pub async fn list_due(pool: &PgPool, limit: i64) -> Result<Vec<CommandSchedule>, String> {
+    sqlx::query_as::<_, CommandSchedule>(
+        r#"
+        SELECT *
+        FROM agent_command_schedule
+        WHERE enabled = TRUE
+          AND next_run_at <= NOW()
+        ORDER BY next_run_at ASC
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list due command schedules: {}", e))
+}
+
+/// Move a schedule from the run it was due for to `next_run_at`. Only
+/// succeeds while the schedule is still due for `due_at`, so when several
+/// server processes pick up the same run exactly one of them gets it.
+pub async fn claim_run(
+    pool: &PgPool,
+    id: i32,
+    due_at: DateTime<Utc>,
+    next_run_at: DateTime<Utc>,
+) -> Result<bool, String> {
+    sqlx::query(
+        r#"
+        UPDATE agent_command_schedule
+        SET next_run_at = $3,
+            updated_at = NOW()
+        WHERE id = $1
+          AND next_run_at = $2
+        "#,
+    )
+    .bind(id)
+    .bind(due_at)
+    .bind(next_run_at)
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(|e| format!("Failed to claim command schedule run: {}", e))
+}
+
+/// Record the command queued for a run.
+pub async fn record_run(pool: &PgPool, id: i32, command_id: &str) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        UPDATE agent_command_schedule
+        SET last_run_at = NOW(),
+            last_command_id = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(command_id)
+    .execute(pool)
+    .await
+    .map(|_| ())
+    .map_err(|e| format!("Failed to record command schedule run: {}", e))
+}
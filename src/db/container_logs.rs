@@ -0,0 +1,85 @@
+use crate::db::day_partition::DayPartitionedTable;
+use crate::forms::status_panel::ShippedLogLine;
+use crate::models::ContainerLogLine;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Day partitions of `container_logs`, maintained by the retention task.
+pub const PARTITIONS: DayPartitionedTable = DayPartitionedTable {
+    table: "container_logs",
+    column: "logged_at",
+};
+
+/// Store one batch of shipped lines. Returns how many rows were written.
+#[tracing::instrument(name = "Insert container logs", skip(pool, lines))]
+pub async fn insert_lines(
+    pool: &PgPool,
+    deployment_hash: &str,
+    lines: &[ShippedLogLine],
+) -> Result<u64, String> {
+    if lines.is_empty() {
+        return Ok(0);
+    }
+    let app_codes: Vec<&str> = lines.iter().map(|l| l.app_code.as_str()).collect();
+    let containers: Vec<Option<&str>> = lines.iter().map(|l| l.container.as_deref()).collect();
+    let logged_at: Vec<DateTime<Utc>> = lines.iter().map(|l| l.ts).collect();
+    let streams: Vec<&str> = lines.iter().map(|l| l.stream.as_str()).collect();
+    let messages: Vec<&str> = lines.iter().map(|l| l.message.as_str()).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO container_logs (
+            deployment_hash, app_code, container, logged_at, stream, message
+        )
+        SELECT $1, *
+        FROM UNNEST($2::text[], $3::text[], $4::timestamptz[], $5::text[], $6::text[])
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(&app_codes)
+    .bind(&containers)
+    .bind(&logged_at)
+    .bind(&streams)
+    .bind(&messages)
+    .execute(pool)
+    .await
+    .map(|done| done.rows_affected())
+    .map_err(|e| {
+        tracing::error!("Failed to insert container logs: {:?}", e);
+        format!("Failed to insert container logs: {}", e)
+    })
+}
+
+/// Newest lines of a deployment since `since`, optionally of one app and
+/// matching a web-style full-text query (`timeout`, `"connection reset"`,
+/// `error -healthcheck`).
+pub async fn search(
+    pool: &PgPool,
+    deployment_hash: &str,
+    since: DateTime<Utc>,
+    app_code: Option<&str>,
+    text: Option<&str>,
+    limit: i64,
+) -> Result<Vec<ContainerLogLine>, String> {
+    sqlx::query_as::<_, ContainerLogLine>(
+        r#"
+        SELECT deployment_hash, app_code, container, logged_at, stream, message
+        FROM container_logs
+        WHERE deployment_hash = $1
+          AND logged_at >= $2
+          AND ($3::text IS NULL OR app_code = $3)
+          AND ($4::text IS NULL
+               OR to_tsvector('simple', message) @@ websearch_to_tsquery('simple', $4))
+        ORDER BY logged_at DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(since)
+    .bind(app_code)
+    .bind(text)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to search container logs: {}", e))
+}
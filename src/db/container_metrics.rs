@@ -0,0 +1,100 @@
+use crate::db::day_partition::DayPartitionedTable;
+use crate::forms::status_panel::ContainerStats;
+use crate::models::ContainerMetricSample;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Day partitions of `container_metrics`, maintained by the retention task.
+pub const PARTITIONS: DayPartitionedTable = DayPartitionedTable {
+    table: "container_metrics",
+    column: "recorded_at",
+};
+
+/// Store one batch of samples taken at `recorded_at`. Returns how many rows
+/// were written.
+#[tracing::instrument(name = "Insert container metrics", skip(pool, samples))]
+pub async fn insert_samples(
+    pool: &PgPool,
+    deployment_hash: &str,
+    recorded_at: DateTime<Utc>,
+    samples: &[ContainerStats],
+) -> Result<u64, String> {
+    if samples.is_empty() {
+        return Ok(0);
+    }
+    let app_codes: Vec<&str> = samples.iter().map(|s| s.app_code.as_str()).collect();
+    let containers: Vec<Option<&str>> = samples.iter().map(|s| s.container.as_deref()).collect();
+    let cpu: Vec<f64> = samples.iter().map(|s| s.cpu_percent).collect();
+    let memory: Vec<i64> = samples.iter().map(|s| s.memory_bytes as i64).collect();
+    let memory_limit: Vec<Option<i64>> = samples
+        .iter()
+        .map(|s| s.memory_limit_bytes.map(|limit| limit as i64))
+        .collect();
+    let net_rx: Vec<i64> = samples.iter().map(|s| s.net_rx_bytes as i64).collect();
+    let net_tx: Vec<i64> = samples.iter().map(|s| s.net_tx_bytes as i64).collect();
+    let block_read: Vec<i64> = samples.iter().map(|s| s.block_read_bytes as i64).collect();
+    let block_write: Vec<i64> = samples.iter().map(|s| s.block_write_bytes as i64).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO container_metrics (
+            deployment_hash, recorded_at, app_code, container, cpu_percent,
+            memory_bytes, memory_limit_bytes, net_rx_bytes, net_tx_bytes,
+            block_read_bytes, block_write_bytes
+        )
+        SELECT $1, $2, *
+        FROM UNNEST(
+            $3::text[], $4::text[], $5::float8[], $6::int8[], $7::int8[],
+            $8::int8[], $9::int8[], $10::int8[], $11::int8[]
+        )
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(recorded_at)
+    .bind(&app_codes)
+    .bind(&containers)
+    .bind(&cpu)
+    .bind(&memory)
+    .bind(&memory_limit)
+    .bind(&net_rx)
+    .bind(&net_tx)
+    .bind(&block_read)
+    .bind(&block_write)
+    .execute(pool)
+    .await
+    .map(|done| done.rows_affected())
+    .map_err(|e| {
+        tracing::error!("Failed to insert container metrics: {:?}", e);
+        format!("Failed to insert container metrics: {}", e)
+    })
+}
+
+/// Samples of a deployment since `since`, oldest first.
+pub async fn list_since(
+    pool: &PgPool,
+    deployment_hash: &str,
+    since: DateTime<Utc>,
+    app_code: Option<&str>,
+    limit: i64,
+) -> Result<Vec<ContainerMetricSample>, String> {
+    sqlx::query_as::<_, ContainerMetricSample>(
+        r#"
+        SELECT deployment_hash, app_code, container, recorded_at, cpu_percent,
+               memory_bytes, memory_limit_bytes, net_rx_bytes, net_tx_bytes,
+               block_read_bytes, block_write_bytes
+        FROM container_metrics
+        WHERE deployment_hash = $1
+          AND recorded_at >= $2
+          AND ($3::text IS NULL OR app_code = $3)
+        ORDER BY recorded_at ASC
+        LIMIT $4
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(since)
+    .bind(app_code)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list container metrics: {}", e))
+}
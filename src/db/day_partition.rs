@@ -0,0 +1,146 @@
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+
+/// A table range-partitioned by day on `column`, with a `<table>_default`
+/// partition catching rows of days that have no partition yet.
+pub struct DayPartitionedTable {
+    pub table: &'static str,
+    pub column: &'static str,
+}
+
+impl DayPartitionedTable {
+    /// Name of the daily partition holding `day`, e.g. `container_logs_p20261018`.
+    pub fn partition_name(&self, day: NaiveDate) -> String {
+        format!("{}_p{}", self.table, day.format("%Y%m%d"))
+    }
+
+    fn partition_day(&self, name: &str) -> Option<NaiveDate> {
+        let suffix = name.strip_prefix(self.table)?.strip_prefix("_p")?;
+        NaiveDate::parse_from_str(suffix, "%Y%m%d").ok()
+    }
+
+    fn default_partition(&self) -> String {
+        format!("{}_default", self.table)
+    }
+
+    /// Create the partition for `day` if it does not exist yet. Rows of that
+    /// day already in the default partition are moved into it, since Postgres
+    /// refuses to create a range the default partition still holds rows for.
+    pub async fn ensure_partition(&self, pool: &PgPool, day: NaiveDate) -> Result<(), String> {
+        let name = self.partition_name(day);
+        let exists: (bool,) = sqlx::query_as("SELECT to_regclass($1) IS NOT NULL")
+            .bind(&name)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to look up {}: {}", name, e))?;
+        if exists.0 {
+            return Ok(());
+        }
+
+        let (table, column, default) = (self.table, self.column, self.default_partition());
+        let from = day.format("%Y-%m-%d").to_string();
+        let to = (day + Duration::days(1)).format("%Y-%m-%d").to_string();
+        // Partition bounds cannot be bound parameters; both are formatted dates.
+        let statements = [
+            // Keep agents from writing into the default partition mid-move.
+            format!("LOCK TABLE {default} IN ACCESS EXCLUSIVE MODE"),
+            format!(
+                "CREATE TEMP TABLE {table}_moved ON COMMIT DROP AS \
+                 SELECT * FROM {default} WHERE {column} >= '{from}' AND {column} < '{to}'"
+            ),
+            format!("DELETE FROM {default} WHERE {column} >= '{from}' AND {column} < '{to}'"),
+            format!(
+                "CREATE TABLE IF NOT EXISTS {name} PARTITION OF {table} \
+                 FOR VALUES FROM ('{from}') TO ('{to}')"
+            ),
+            format!("INSERT INTO {table} SELECT * FROM {table}_moved"),
+        ];
+
+        let mut tx = pool.begin().await.map_err(|e| {
+            tracing::error!("Failed to start transaction: {:?}", e);
+            format!("Failed to start transaction: {}", e)
+        })?;
+        for statement in &statements {
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to create {}: {}", name, e))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", name, e))
+    }
+
+    /// Drop daily partitions of days before `cutoff` and delete older rows
+    /// that landed in the default partition. Returns the dropped partition
+    /// names.
+    pub async fn drop_before(
+        &self,
+        pool: &PgPool,
+        cutoff: NaiveDate,
+    ) -> Result<Vec<String>, String> {
+        let partitions: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT child.relname::text
+            FROM pg_inherits
+            JOIN pg_class parent ON parent.oid = pg_inherits.inhparent
+            JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+            WHERE parent.relname = $1
+            "#,
+        )
+        .bind(self.table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to list {} partitions: {}", self.table, e))?;
+
+        let mut dropped = Vec::new();
+        for (name,) in partitions {
+            if self.partition_day(&name).is_some_and(|day| day < cutoff) {
+                sqlx::query(&format!("DROP TABLE IF EXISTS {}", name))
+                    .execute(pool)
+                    .await
+                    .map_err(|e| format!("Failed to drop {}: {}", name, e))?;
+                dropped.push(name);
+            }
+        }
+
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE {} < $1",
+            self.default_partition(),
+            self.column
+        ))
+        .bind(cutoff.and_hms_opt(0, 0, 0).map(|t| t.and_utc()))
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to expire {}: {}", self.table, e))?;
+        Ok(dropped)
+    }
+
+    /// Create today's and tomorrow's partitions and drop days older than
+    /// `retention_days`. Expired days are dropped even when a partition could
+    /// not be created.
+    pub async fn maintain(
+        &self,
+        pool: &PgPool,
+        retention_days: i64,
+    ) -> Result<Vec<String>, String> {
+        let today = Utc::now().date_naive();
+        let mut errors = Vec::new();
+        for day in [today, today + Duration::days(1)] {
+            if let Err(err) = self.ensure_partition(pool, day).await {
+                errors.push(err);
+            }
+        }
+        match self
+            .drop_before(pool, today - Duration::days(retention_days))
+            .await
+        {
+            Ok(dropped) if errors.is_empty() => Ok(dropped),
+            Ok(_) => Err(errors.join("; ")),
+            Err(err) => {
+                errors.push(err);
+                Err(errors.join("; "))
+            }
+        }
+    }
+}
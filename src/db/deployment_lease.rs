@@ -0,0 +1,128 @@
+use crate::models::{DeploymentLease, DeploymentLeaseAudit};
+use sqlx::PgPool;
+
+/// Take the lease for `lease.deployment_hash`. Succeeds when no lease exists,
+/// the current one has expired, or it is the same lease being renewed;
+/// `force` takes it over regardless. Returns `None` when another holder
+/// keeps it.
+pub async fn acquire(
+    pool: &PgPool,
+    lease: &DeploymentLease,
+    force: bool,
+) -> Result<Option<DeploymentLease>, String> {
+    let guard = if force {
+        ""
+    } else {
+        "WHERE deployment_lease.expires_at <= NOW() OR deployment_lease.lease_id = EXCLUDED.lease_id"
+    };
+    let query = format!(
+        r#"
+        INSERT INTO deployment_lease (
+            deployment_hash, lease_id, owner, user_id, reason, acquired_at, expires_at
+        )
+        VALUES ($1, $2, $3, $4, $5, NOW(), $6)
+        ON CONFLICT (deployment_hash) DO UPDATE
+        SET lease_id = EXCLUDED.lease_id,
+            owner = EXCLUDED.owner,
+            user_id = EXCLUDED.user_id,
+            reason = EXCLUDED.reason,
+            acquired_at = CASE
+                WHEN deployment_lease.lease_id = EXCLUDED.lease_id THEN deployment_lease.acquired_at
+                ELSE NOW()
+            END,
+            expires_at = EXCLUDED.expires_at
+        {guard}
+        RETURNING *
+        "#
+    );
+    sqlx::query_as::<_, DeploymentLease>(&query)
+        .bind(&lease.deployment_hash)
+        .bind(&lease.lease_id)
+        .bind(&lease.owner)
+        .bind(&lease.user_id)
+        .bind(&lease.reason)
+        .bind(lease.expires_at)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to acquire deployment lease: {}", e))
+}
+
+/// The current lease, if it has not expired.
+pub async fn fetch_active(
+    pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Option<DeploymentLease>, String> {
+    sqlx::query_as::<_, DeploymentLease>(
+        r#"
+        SELECT *
+        FROM deployment_lease
+        WHERE deployment_hash = $1
+          AND expires_at > NOW()
+        "#,
+    )
+    .bind(deployment_hash)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch deployment lease: {}", e))
+}
+
+/// Drop the lease if `lease_id` still holds it. Returns whether a row was removed.
+pub async fn release(pool: &PgPool, deployment_hash: &str, lease_id: &str) -> Result<bool, String> {
+    sqlx::query(
+        r#"
+        DELETE FROM deployment_lease
+        WHERE deployment_hash = $1
+          AND lease_id = $2
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(lease_id)
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .map_err(|e| format!("Failed to release deployment lease: {}", e))
+}
+
+pub async fn insert_audit(
+    pool: &PgPool,
+    audit: &DeploymentLeaseAudit,
+) -> Result<DeploymentLeaseAudit, String> {
+    sqlx::query_as::<_, DeploymentLeaseAudit>(
+        r#"
+        INSERT INTO deployment_lease_audit (
+            deployment_hash, action, actor_user_id, actor_owner,
+            previous_owner, previous_reason, reason
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+    .bind(&audit.deployment_hash)
+    .bind(&audit.action)
+    .bind(&audit.actor_user_id)
+    .bind(&audit.actor_owner)
+    .bind(&audit.previous_owner)
+    .bind(&audit.previous_reason)
+    .bind(&audit.reason)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to record deployment lease audit: {}", e))
+}
+
+pub async fn list_audit_by_deployment_hash(
+    pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Vec<DeploymentLeaseAudit>, String> {
+    sqlx::query_as::<_, DeploymentLeaseAudit>(
+        r#"
+        SELECT *
+        FROM deployment_lease_audit
+        WHERE deployment_hash = $1
+        ORDER BY created_at
+        "#,
+    )
+    .bind(deployment_hash)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list deployment lease audit: {}", e))
+}
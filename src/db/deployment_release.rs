@@ -0,0 +1,111 @@
+use crate::models::DeploymentRelease;
+use sqlx::PgPool;
+
+/// Append a release, assigning the next version for its deployment.
+pub async fn insert(
+    pool: &PgPool,
+    release: &DeploymentRelease,
+) -> Result<DeploymentRelease, String> {
+    sqlx::query_as::<_, DeploymentRelease>(
+        r#"
+        INSERT INTO deployment_release (
+            deployment_hash, version, compose_content, env_hashes, image_digests,
+            stacker_yml, bundle_hash, rollback_of, command_id, created_by
+        )
+        SELECT $1, COALESCE(MAX(version), 0) + 1, $2, $3, $4, $5, $6, $7, $8, $9
+        FROM deployment_release
+        WHERE deployment_hash = $1
+        RETURNING *
+        "#,
+    )
+    .bind(&release.deployment_hash)
+    .bind(&release.compose_content)
+    .bind(&release.env_hashes)
+    .bind(&release.image_digests)
+    .bind(&release.stacker_yml)
+    .bind(&release.bundle_hash)
+    .bind(release.rollback_of)
+    .bind(&release.command_id)
+    .bind(&release.created_by)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to record deployment release: {}", e))
+}
+
+/// All releases of a deployment, newest first.
+pub async fn list_by_deployment_hash(
+    pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Vec<DeploymentRelease>, String> {
+    sqlx::query_as::<_, DeploymentRelease>(
+        r#"
+        SELECT *
+        FROM deployment_release
+        WHERE deployment_hash = $1
+        ORDER BY version DESC
+        "#,
+    )
+    .bind(deployment_hash)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list deployment releases: {}", e))
+}
+
+pub async fn fetch_by_version(
+    pool: &PgPool,
+    deployment_hash: &str,
+    version: i32,
+) -> Result<Option<DeploymentRelease>, String> {
+    sqlx::query_as::<_, DeploymentRelease>(
+        r#"
+        SELECT *
+        FROM deployment_release
+        WHERE deployment_hash = $1
+          AND version = $2
+        LIMIT 1
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(version)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch deployment release: {}", e))
+}
+
+pub async fn fetch_latest(
+    pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Option<DeploymentRelease>, String> {
+    sqlx::query_as::<_, DeploymentRelease>(
+        r#"
+        SELECT *
+        FROM deployment_release
+        WHERE deployment_hash = $1
+        ORDER BY version DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(deployment_hash)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch latest deployment release: {}", e))
+}
+
+/// The release a `deploy_app` command was recorded as, if any.
+pub async fn fetch_by_command_id(
+    pool: &PgPool,
+    command_id: &str,
+) -> Result<Option<DeploymentRelease>, String> {
+    sqlx::query_as::<_, DeploymentRelease>(
+        r#"
+        SELECT *
+        FROM deployment_release
+        WHERE command_id = $1
+        LIMIT 1
+        "#,
+    )
+    .bind(command_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch deployment release: {}", e))
+}
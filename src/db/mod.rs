@@ -1,14 +1,22 @@
 pub mod agent;
 pub mod agent_audit_log;
+pub mod agent_identity;
 pub(crate) mod agreement;
 pub mod chat;
 pub mod client;
 pub(crate) mod cloud;
 pub mod command;
+pub mod command_schedule;
+pub mod container_logs;
+pub mod container_metrics;
 pub mod dag;
+pub mod day_partition;
 pub(crate) mod deployment;
+pub mod deployment_lease;
+pub mod deployment_release;
 pub mod marketplace;
 pub mod pipe;
+pub mod preview_environment;
 pub mod product;
 pub mod project;
 pub mod project_app;
@@ -0,0 +1,143 @@
+use crate::models::PreviewEnvironment;
+use sqlx::PgPool;
+
+/// Create the preview for `(deployment_hash, slug)`, or refresh it when the
+/// branch is deployed again: the expiry moves forward and a torn-down
+/// preview becomes active again.
+pub async fn upsert(
+    pool: &PgPool,
+    preview: &PreviewEnvironment,
+) -> Result<PreviewEnvironment, String> {
+    sqlx::query_as::<_, PreviewEnvironment>(
+        r#"
+        INSERT INTO preview_environment (
+            deployment_hash, slug, branch, app_code, compose_project, domain,
+            forward_port, status, created_by, expires_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'active', $8, $9)
+        ON CONFLICT (deployment_hash, slug) DO UPDATE
+        SET branch = EXCLUDED.branch,
+            app_code = EXCLUDED.app_code,
+            compose_project = EXCLUDED.compose_project,
+            domain = EXCLUDED.domain,
+            forward_port = EXCLUDED.forward_port,
+            status = 'active',
+            created_by = EXCLUDED.created_by,
+            updated_at = NOW(),
+            expires_at = EXCLUDED.expires_at
+        RETURNING *
+        "#,
+    )
+    .bind(&preview.deployment_hash)
+    .bind(&preview.slug)
+    .bind(&preview.branch)
+    .bind(&preview.app_code)
+    .bind(&preview.compose_project)
+    .bind(&preview.domain)
+    .bind(preview.forward_port)
+    .bind(&preview.created_by)
+    .bind(preview.expires_at)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to save preview environment: {}", e))
+}
+
+/// Active previews of a deployment, soonest to expire first.
+pub async fn list_active(
+    pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Vec<PreviewEnvironment>, String> {
+    sqlx::query_as::<_, PreviewEnvironment>(
+        r#"
+        SELECT *
+        FROM preview_environment
+        WHERE deployment_hash = $1
+          AND status = 'active'
+        ORDER BY expires_at ASC
+        "#,
+    )
+    .bind(deployment_hash)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list preview environments: {}", e))
+}
+
+pub async fn fetch_active(
+    pool: &PgPool,
+    deployment_hash: &str,
+    slug: &str,
+) -> Result<Option<PreviewEnvironment>, String> {
+    sqlx::query_as::<_, PreviewEnvironment>(
+        r#"
+        SELECT *
+        FROM preview_environment
+        WHERE deployment_hash = $1
+          AND slug = $2
+          AND status = 'active'
+        LIMIT 1
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(slug)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch preview environment: {}", e))
+}
+
+/// The active preview of a deployment running under `compose_project`.
+pub async fn fetch_active_by_compose_project(
+    pool: &PgPool,
+    deployment_hash: &str,
+    compose_project: &str,
+) -> Result<Option<PreviewEnvironment>, String> {
+    sqlx::query_as::<_, PreviewEnvironment>(
+        r#"
+        SELECT *
+        FROM preview_environment
+        WHERE deployment_hash = $1
+          AND compose_project = $2
+          AND status = 'active'
+        LIMIT 1
+        "#,
+    )
+    .bind(deployment_hash)
+    .bind(compose_project)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch preview environment: {}", e))
+}
+
+/// Active previews past their expiry, across all deployments.
+pub async fn list_expired(pool: &PgPool, limit: i64) -> Result<Vec<PreviewEnvironment>, String> {
+    sqlx::query_as::<_, PreviewEnvironment>(
+        r#"
+        SELECT *
+        FROM preview_environment
+        WHERE status = 'active'
+          AND expires_at <= NOW()
+        ORDER BY expires_at ASC
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list expired preview environments: {}", e))
+}
+
+pub async fn set_status(pool: &PgPool, id: i32, status: &str) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        UPDATE preview_environment
+        SET status = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .execute(pool)
+    .await
+    .map(|_| ())
+    .map_err(|e| format!("Failed to update preview environment: {}", e))
+}
@@ -58,6 +58,18 @@ pub struct HealthCommandRequest {
     /// When true and app_code is "system" or empty, return system containers (status_panel, compose-agent)
     #[serde(default)]
     pub include_system: bool,
+    /// Set when the check is part of a `--auto-rollback` health gate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<RolloutStep>,
+}
+
+/// Marks commands issued by `stacker deploy --auto-rollback` so the
+/// deployment event feed can report health-gate and rollback steps.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutStep {
+    HealthGate,
+    Rollback,
 }
 
 fn default_health_app_code() -> String {
@@ -131,12 +143,139 @@ pub struct DeployAppCommandRequest {
     /// Optional private registry credentials reused for image pull refreshes.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub registry_auth: Option<RegistryAuthCommandRequest>,
+    /// When set, roll out blue/green instead of recreating in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blue_green: Option<BlueGreenCommandRequest>,
+    /// Set when re-applying a known-good bundle after a failed health gate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<RolloutStep>,
+    /// Compose project name (`docker compose -p`) for stacks that share a
+    /// server with the main deployment, such as preview environments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_project: Option<String>,
 }
 
 fn default_deploy_pull() -> bool {
     true
 }
 
+/// Blue/green rollout for `deploy_app`: start the new set beside the old one,
+/// switch the proxy upstream once healthy, then drain and remove the old set.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct BlueGreenCommandRequest {
+    /// HTTP path probed on the new set; the compose healthcheck is used when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_endpoint: Option<String>,
+    #[serde(default = "default_blue_green_health_timeout")]
+    pub health_timeout_secs: u32,
+    #[serde(default = "default_blue_green_drain")]
+    pub drain_secs: u32,
+}
+
+fn default_blue_green_health_timeout() -> u32 {
+    120
+}
+
+fn default_blue_green_drain() -> u32 {
+    30
+}
+
+/// Ordered phases of a blue/green `deploy_app`, reported by the agent.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployPhase {
+    StartNew,
+    HealthCheck,
+    SwitchUpstream,
+    Drain,
+    RemoveOld,
+    /// New set removed and the old set kept after a failed phase.
+    Rollback,
+}
+
+impl DeployPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::StartNew => "start_new",
+            Self::HealthCheck => "health_check",
+            Self::SwitchUpstream => "switch_upstream",
+            Self::Drain => "drain",
+            Self::RemoveOld => "remove_old",
+            Self::Rollback => "rollback",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeployPhaseStatus {
+    Completed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeployPhaseReport {
+    pub phase: DeployPhase,
+    pub status: DeployPhaseStatus,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Structured `deploy_app` result. Agents without blue/green support still
+/// report free-form output, which is passed through unvalidated.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeployAppCommandReport {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub deployment_hash: String,
+    pub app_code: String,
+    /// `recreate` or `blue_green`.
+    pub strategy: String,
+    /// Container set serving traffic after the command: `blue` or `green`.
+    #[serde(default)]
+    pub active_color: Option<String>,
+    pub phases: Vec<DeployPhaseReport>,
+    #[serde(default)]
+    pub errors: Vec<StatusPanelCommandError>,
+}
+
+impl DeployAppCommandReport {
+    /// Phases must arrive in order, and traffic may only switch after a
+    /// completed health check; a failed phase must be followed by a rollback.
+    fn validate_phases(&self) -> Result<(), String> {
+        let mut previous: Option<DeployPhase> = None;
+        let mut healthy = false;
+        let mut failed = false;
+        for report in &self.phases {
+            if previous.is_some_and(|prev| report.phase <= prev) {
+                return Err(format!(
+                    "deploy_app phases out of order at '{}'",
+                    report.phase.as_str()
+                ));
+            }
+            match (report.phase, report.status) {
+                (DeployPhase::HealthCheck, DeployPhaseStatus::Completed) => healthy = true,
+                (DeployPhase::SwitchUpstream, DeployPhaseStatus::Completed) if !healthy => {
+                    return Err(
+                        "deploy_app switched upstream without a completed health_check".to_string(),
+                    );
+                }
+                (DeployPhase::Rollback, _) => failed = false,
+                (_, DeployPhaseStatus::Failed) => failed = true,
+                _ => {}
+            }
+            previous = Some(report.phase);
+        }
+        if failed {
+            return Err("deploy_app failed phase must be followed by rollback".to_string());
+        }
+        Ok(())
+    }
+}
+
 fn default_runtime() -> String {
     "runc".to_string()
 }
@@ -167,6 +306,10 @@ pub struct RemoveAppCommandRequest {
     pub remove_volumes: bool,
     #[serde(default)]
     pub remove_image: bool,
+    /// Compose project the app runs under when it is not the deployment's
+    /// own, such as a preview environment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_project: Option<String>,
 }
 
 /// Request to configure nginx proxy manager for an app
@@ -296,6 +439,15 @@ pub enum LogStream {
     Stderr,
 }
 
+impl LogStream {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stdout => "stdout",
+            Self::Stderr => "stderr",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LogLine {
     pub ts: DateTime<Utc>,
@@ -353,6 +505,25 @@ fn ensure_app_code(kind: &str, value: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Compose project names must be valid for `docker compose -p`.
+fn ensure_compose_project(kind: &str, value: Option<&str>) -> Result<(), String> {
+    let Some(project) = value else {
+        return Ok(());
+    };
+    let valid = !project.is_empty()
+        && project
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        && project.starts_with(|c: char| c.is_ascii_alphanumeric());
+    if !valid {
+        return Err(format!(
+            "{}.compose_project must be lowercase letters, digits, '-' or '_'; got '{}'",
+            kind, project
+        ));
+    }
+    Ok(())
+}
+
 fn ensure_result_envelope(
     expected_type: &str,
     expected_hash: &str,
@@ -460,6 +631,29 @@ pub fn validate_command_parameters(
                 ));
             }
 
+            ensure_compose_project("deploy_app", params.compose_project.as_deref())?;
+
+            if let Some(blue_green) = params.blue_green.as_ref() {
+                if !(10..=1800).contains(&blue_green.health_timeout_secs) {
+                    return Err(
+                        "deploy_app.blue_green.health_timeout_secs must be between 10 and 1800"
+                            .to_string(),
+                    );
+                }
+                if blue_green.drain_secs > 600 {
+                    return Err("deploy_app.blue_green.drain_secs must be at most 600".to_string());
+                }
+                if blue_green
+                    .health_endpoint
+                    .as_deref()
+                    .is_some_and(|path| !path.starts_with('/'))
+                {
+                    return Err(
+                        "deploy_app.blue_green.health_endpoint must start with '/'".to_string()
+                    );
+                }
+            }
+
             serde_json::to_value(params)
                 .map(Some)
                 .map_err(|err| format!("Failed to encode deploy_app parameters: {}", err))
@@ -469,6 +663,7 @@ pub fn validate_command_parameters(
             let params: RemoveAppCommandRequest = serde_json::from_value(value)
                 .map_err(|err| format!("Invalid remove_app parameters: {}", err))?;
             ensure_app_code("remove_app", &params.app_code)?;
+            ensure_compose_project("remove_app", params.compose_project.as_deref())?;
 
             serde_json::to_value(params)
                 .map(Some)
@@ -563,6 +758,162 @@ pub fn validate_command_parameters(
                 .map(Some)
                 .map_err(|err| format!("Failed to encode probe_endpoints parameters: {}", err))
         }
+        "certificate_status" => {
+            let value = parameters.clone().unwrap_or_else(|| json!({}));
+            let params: CertificateStatusCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid certificate_status parameters: {}", err))?;
+            ensure_app_code("certificate_status", &params.app_code)?;
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode certificate_status parameters: {}", err))
+        }
+        "stats" => {
+            let value = parameters.clone().unwrap_or_else(|| json!({}));
+            let params: StatsCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid stats parameters: {}", err))?;
+            if let Some(app_code) = params.app_code.as_deref() {
+                ensure_app_code("stats", app_code)?;
+            }
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode stats parameters: {}", err))
+        }
+        "backup_volume" => {
+            let value = parameters.clone().unwrap_or_else(|| json!({}));
+            let params: BackupVolumeCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid backup_volume parameters: {}", err))?;
+            ensure_app_code("backup_volume", &params.app_code)?;
+            validate_backup_target("backup_volume", &params.target)?;
+            if params.retention.keep_last == Some(0) {
+                return Err("backup_volume: retention.keep_last must be at least 1".to_string());
+            }
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode backup_volume parameters: {}", err))
+        }
+        "restore_volume" => {
+            let value = parameters.clone().unwrap_or_else(|| json!({}));
+            let params: RestoreVolumeCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid restore_volume parameters: {}", err))?;
+            ensure_app_code("restore_volume", &params.app_code)?;
+            if params.backup_id.trim().is_empty() {
+                return Err("restore_volume: backup_id is required".to_string());
+            }
+            validate_backup_target("restore_volume", &params.target)?;
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode restore_volume parameters: {}", err))
+        }
+        "open_shell" => {
+            let value = parameters
+                .clone()
+                .ok_or_else(|| "open_shell requires parameters".to_string())?;
+            let params: OpenShellCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid open_shell parameters: {}", err))?;
+            ensure_app_code("open_shell", &params.app_code)?;
+            if params.session_id.trim().is_empty() {
+                return Err("open_shell: session_id is required".to_string());
+            }
+            if params.cols == 0 || params.rows == 0 {
+                return Err("open_shell: cols and rows must be at least 1".to_string());
+            }
+            if params.idle_timeout_secs == 0
+                || params.idle_timeout_secs > MAX_SHELL_IDLE_TIMEOUT_SECS
+            {
+                return Err(format!(
+                    "open_shell: idle_timeout_secs must be between 1 and {}",
+                    MAX_SHELL_IDLE_TIMEOUT_SECS
+                ));
+            }
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode open_shell parameters: {}", err))
+        }
+        "upgrade_agent" => {
+            let value = parameters
+                .clone()
+                .ok_or_else(|| "upgrade_agent requires parameters".to_string())?;
+            let params: UpgradeAgentCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid upgrade_agent parameters: {}", err))?;
+            let valid_version = !params.version.is_empty()
+                && params
+                    .version
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+            if !valid_version {
+                return Err(format!(
+                    "upgrade_agent: version must be letters, digits, '.', '-' or '+'; got '{}'",
+                    params.version
+                ));
+            }
+            if params
+                .image
+                .as_deref()
+                .is_some_and(|image| image.trim().is_empty())
+            {
+                return Err("upgrade_agent: image must not be empty".to_string());
+            }
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode upgrade_agent parameters: {}", err))
+        }
+        "upload_file" => {
+            let value = parameters
+                .clone()
+                .ok_or_else(|| "upload_file requires parameters".to_string())?;
+            let params: UploadFileCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid upload_file parameters: {}", err))?;
+            ensure_app_code("upload_file", &params.app_code)?;
+            validate_file_transfer("upload_file", &params.transfer_id, &params.path)?;
+            if params.total_size > MAX_FILE_TRANSFER_BYTES {
+                return Err(format!(
+                    "upload_file: files are limited to {} bytes",
+                    MAX_FILE_TRANSFER_BYTES
+                ));
+            }
+            if !is_sha256_hex(&params.sha256) {
+                return Err("upload_file: sha256 must be a lowercase hex digest".to_string());
+            }
+            let chunk = decode_file_chunk("upload_file", &params.data, &params.chunk_sha256)?;
+            if params.offset + chunk.len() as u64 > params.total_size {
+                return Err("upload_file: chunk ends past total_size".to_string());
+            }
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode upload_file parameters: {}", err))
+        }
+        "download_file" => {
+            let value = parameters
+                .clone()
+                .ok_or_else(|| "download_file requires parameters".to_string())?;
+            let params: DownloadFileCommandRequest = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid download_file parameters: {}", err))?;
+            ensure_app_code("download_file", &params.app_code)?;
+            validate_file_transfer("download_file", &params.transfer_id, &params.path)?;
+            if params.length == 0 || params.length > MAX_FILE_CHUNK_BYTES as u64 {
+                return Err(format!(
+                    "download_file: length must be between 1 and {}",
+                    MAX_FILE_CHUNK_BYTES
+                ));
+            }
+            if params.max_size > MAX_FILE_TRANSFER_BYTES {
+                return Err(format!(
+                    "download_file: max_size is limited to {} bytes",
+                    MAX_FILE_TRANSFER_BYTES
+                ));
+            }
+
+            serde_json::to_value(params)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode download_file parameters: {}", err))
+        }
         "check_connections" => {
             let value = parameters.clone().unwrap_or_else(|| json!({}));
             let params: CheckConnectionsCommandRequest = serde_json::from_value(value)
@@ -750,6 +1101,173 @@ pub fn validate_command_result(
                 .map(Some)
                 .map_err(|err| format!("Failed to encode restart result: {}", err))
         }
+        "deploy_app" => {
+            // Only structured (phase) reports are validated; older agents
+            // return plain output.
+            let Some(value) = result.clone().filter(|v| v.get("phases").is_some()) else {
+                return Ok(result.clone());
+            };
+            let report: DeployAppCommandReport = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid deploy_app result: {}", err))?;
+
+            ensure_result_envelope(
+                "deploy_app",
+                deployment_hash,
+                &report.command_type,
+                &report.deployment_hash,
+                &report.app_code,
+            )?;
+            report.validate_phases()?;
+
+            serde_json::to_value(report)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode deploy_app result: {}", err))
+        }
+        "certificate_status" => {
+            let value = result
+                .clone()
+                .ok_or_else(|| "certificate_status result payload is required".to_string())?;
+            let report: CertificateStatusCommandReport = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid certificate_status result: {}", err))?;
+
+            ensure_result_envelope(
+                "certificate_status",
+                deployment_hash,
+                &report.command_type,
+                &report.deployment_hash,
+                &report.app_code,
+            )?;
+            if report
+                .listing
+                .as_ref()
+                .is_some_and(|listing| listing.len() > MAX_CERTIFICATE_STATUS_BYTES)
+            {
+                return Err("certificate_status result listing exceeds the size cap".to_string());
+            }
+
+            serde_json::to_value(report)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode certificate_status result: {}", err))
+        }
+        "stats" => {
+            let value = result
+                .clone()
+                .ok_or_else(|| "stats result payload is required".to_string())?;
+            let report: StatsCommandReport = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid stats result: {}", err))?;
+
+            if report.command_type != "stats" {
+                return Err("stats result must include type='stats'".to_string());
+            }
+            if report.deployment_hash != deployment_hash {
+                return Err("stats result deployment_hash mismatch".to_string());
+            }
+            validate_container_stats("stats", &report.containers)?;
+
+            serde_json::to_value(report)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode stats result: {}", err))
+        }
+        "backup_volume" => {
+            let value = result
+                .clone()
+                .ok_or_else(|| "backup_volume result payload is required".to_string())?;
+            let report: BackupVolumeCommandReport = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid backup_volume result: {}", err))?;
+
+            ensure_result_envelope(
+                "backup_volume",
+                deployment_hash,
+                &report.command_type,
+                &report.deployment_hash,
+                &report.app_code,
+            )?;
+            if report.backup_id.trim().is_empty() {
+                return Err("backup_volume result must include backup_id".to_string());
+            }
+            if report.status == BackupStatus::Ok && report.artifacts.is_empty() {
+                return Err("backup_volume result must list at least one artifact".to_string());
+            }
+
+            serde_json::to_value(report)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode backup_volume result: {}", err))
+        }
+        "restore_volume" => {
+            let value = result
+                .clone()
+                .ok_or_else(|| "restore_volume result payload is required".to_string())?;
+            let report: RestoreVolumeCommandReport = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid restore_volume result: {}", err))?;
+
+            ensure_result_envelope(
+                "restore_volume",
+                deployment_hash,
+                &report.command_type,
+                &report.deployment_hash,
+                &report.app_code,
+            )?;
+
+            serde_json::to_value(report)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode restore_volume result: {}", err))
+        }
+        "upload_file" => {
+            let value = result
+                .clone()
+                .ok_or_else(|| "upload_file result payload is required".to_string())?;
+            let report: UploadFileCommandReport = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid upload_file result: {}", err))?;
+
+            ensure_result_envelope(
+                "upload_file",
+                deployment_hash,
+                &report.command_type,
+                &report.deployment_hash,
+                &report.app_code,
+            )?;
+            if report.received_bytes > MAX_FILE_TRANSFER_BYTES {
+                return Err("upload_file result received_bytes exceeds the size cap".to_string());
+            }
+
+            serde_json::to_value(report)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode upload_file result: {}", err))
+        }
+        "download_file" => {
+            let value = result
+                .clone()
+                .ok_or_else(|| "download_file result payload is required".to_string())?;
+            let report: DownloadFileCommandReport = serde_json::from_value(value)
+                .map_err(|err| format!("Invalid download_file result: {}", err))?;
+
+            ensure_result_envelope(
+                "download_file",
+                deployment_hash,
+                &report.command_type,
+                &report.deployment_hash,
+                &report.app_code,
+            )?;
+            if report.status == FileTransferStatus::Ok {
+                if report.total_size > MAX_FILE_TRANSFER_BYTES {
+                    return Err("download_file result exceeds the size cap".to_string());
+                }
+                if !is_sha256_hex(&report.sha256) {
+                    return Err(
+                        "download_file result sha256 must be a lowercase hex digest".to_string()
+                    );
+                }
+                let chunk =
+                    decode_file_chunk("download_file result", &report.data, &report.chunk_sha256)?;
+                if report.offset + chunk.len() as u64 > report.total_size {
+                    return Err("download_file result chunk ends past total_size".to_string());
+                }
+            }
+
+            serde_json::to_value(report)
+                .map(Some)
+                .map_err(|err| format!("Failed to encode download_file result: {}", err))
+        }
         "configure_firewall" => {
             let value = result
                 .clone()
@@ -1232,27 +1750,557 @@ pub struct TriggerPipeCommandReport {
     pub lifecycle: Option<serde_json::Value>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Certificates: certificate_status
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
-    fn fixture(path: &str) -> serde_json::Value {
-        let body = match path {
-            "activate_pipe.webhook.command.json" => include_str!(
-                "../../tests/fixtures/pipe-contract/activate_pipe.webhook.command.json"
-            ),
-            "activate_pipe.rabbitmq.command.json" => include_str!(
-                "../../tests/fixtures/pipe-contract/activate_pipe.rabbitmq.command.json"
-            ),
-            "activate_pipe.adapter.command.json" => include_str!(
-                "../../tests/fixtures/pipe-contract/activate_pipe.adapter.command.json"
-            ),
-            "deactivate_pipe.command.json" => {
-                include_str!("../../tests/fixtures/pipe-contract/deactivate_pipe.command.json")
-            }
-            "trigger_pipe.manual.command.json" => {
-                include_str!("../../tests/fixtures/pipe-contract/trigger_pipe.manual.command.json")
-            }
+/// Largest certificate listing an agent may report; it is a few lines per
+/// certificate.
+pub const MAX_CERTIFICATE_STATUS_BYTES: usize = 256 * 1024;
+
+/// Read-only: the `certbot certificates` listing that the certbot companion's
+/// renewal loop keeps at `/etc/letsencrypt/stacker-certificates.txt`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CertificateStatusCommandRequest {
+    pub app_code: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CertificateStatusCommandReport {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub deployment_hash: String,
+    pub app_code: String,
+    /// `None` until the renewal loop has written the listing once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listing: Option<String>,
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Backups: backup_volume / restore_volume
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Server-scoped secrets the agent reads for S3 backup targets.
+pub const BACKUP_S3_ACCESS_KEY_SECRET: &str = "BACKUP_S3_ACCESS_KEY_ID";
+pub const BACKUP_S3_SECRET_KEY_SECRET: &str = "BACKUP_S3_SECRET_ACCESS_KEY";
+
+/// Where backup archives are kept.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupTargetKind {
+    /// Under the agent's backup directory on the server.
+    #[default]
+    Local,
+    /// An S3-compatible bucket, with credentials from the server secrets
+    /// [`BACKUP_S3_ACCESS_KEY_SECRET`] and [`BACKUP_S3_SECRET_KEY_SECRET`].
+    S3,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct BackupTarget {
+    #[serde(default)]
+    pub kind: BackupTargetKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+    /// Key prefix inside the bucket; the deployment hash is appended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Endpoint of a non-AWS S3 service, e.g. `https://s3.eu-central-003.backblazeb2.com`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+/// Backups of the same app and target the agent prunes after a successful
+/// backup. Unset limits keep everything.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct BackupRetention {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+}
+
+/// Databases the agent dumps with their own tools before archiving volumes,
+/// so the backup is consistent without stopping the container.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseEngine {
+    /// `pg_dumpall`, restored with `psql`.
+    Postgres,
+    /// `mysqldump --all-databases`, restored with `mysql`. Also MariaDB.
+    Mysql,
+    /// `redis-cli SAVE` and a copy of `dump.rdb`.
+    Redis,
+}
+
+impl DatabaseEngine {
+    /// Engine of a known catalog image such as `postgres:16` or
+    /// `bitnami/mariadb`.
+    pub fn detect(image: &str) -> Option<Self> {
+        let name = image
+            .rsplit('/')
+            .next()
+            .unwrap_or(image)
+            .split([':', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "postgres" | "postgresql" | "postgis" | "timescaledb" => Some(Self::Postgres),
+            "mysql" | "mariadb" | "percona-server" => Some(Self::Mysql),
+            "redis" | "redis-stack-server" | "valkey" => Some(Self::Redis),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Some(Self::Postgres),
+            "mysql" | "mariadb" => Some(Self::Mysql),
+            "redis" => Some(Self::Redis),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Postgres => "postgres",
+            Self::Mysql => "mysql",
+            Self::Redis => "redis",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BackupVolumeCommandRequest {
+    pub app_code: String,
+    /// Volumes to archive; all volumes of the app when empty.
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    /// Dump this database before archiving.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<DatabaseEngine>,
+    #[serde(default)]
+    pub target: BackupTarget,
+    #[serde(default)]
+    pub retention: BackupRetention,
+    /// Stop the container while its volumes are archived.
+    #[serde(default)]
+    pub stop_container: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RestoreVolumeCommandRequest {
+    pub app_code: String,
+    pub backup_id: String,
+    #[serde(default)]
+    pub target: BackupTarget,
+    /// Volumes to restore; everything in the backup when empty.
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    /// Replay the database dump after restoring volumes.
+    #[serde(default = "default_restore_database")]
+    pub restore_database: bool,
+}
+
+fn default_restore_database() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupStatus {
+    Ok,
+    Failed,
+}
+
+/// One stored file of a backup: a volume archive or a database dump.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct BackupArtifact {
+    /// Volume name, or `database` for the dump.
+    pub name: String,
+    /// Path on the server or `s3://bucket/key`.
+    pub location: String,
+    pub size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BackupVolumeCommandReport {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub deployment_hash: String,
+    pub app_code: String,
+    pub backup_id: String,
+    pub status: BackupStatus,
+    #[serde(default)]
+    pub target: BackupTarget,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<DatabaseEngine>,
+    #[serde(default)]
+    pub artifacts: Vec<BackupArtifact>,
+    /// Backup ids removed by the retention policy.
+    #[serde(default)]
+    pub pruned: Vec<String>,
+    #[serde(default)]
+    pub errors: Vec<StatusPanelCommandError>,
+}
+
+impl BackupVolumeCommandReport {
+    pub fn size_bytes(&self) -> u64 {
+        self.artifacts
+            .iter()
+            .map(|artifact| artifact.size_bytes)
+            .sum()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RestoreVolumeCommandReport {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub deployment_hash: String,
+    pub app_code: String,
+    pub backup_id: String,
+    pub status: BackupStatus,
+    #[serde(default)]
+    pub restored: Vec<String>,
+    #[serde(default)]
+    pub errors: Vec<StatusPanelCommandError>,
+}
+
+fn validate_backup_target(kind: &str, target: &BackupTarget) -> Result<(), String> {
+    if target.kind == BackupTargetKind::S3
+        && !target
+            .bucket
+            .as_deref()
+            .is_some_and(|bucket| !bucket.trim().is_empty())
+    {
+        return Err(format!(
+            "{}: target.bucket is required for s3 targets",
+            kind
+        ));
+    }
+    if let Some(endpoint) = target.endpoint.as_deref() {
+        if !endpoint.starts_with("https://") && !endpoint.starts_with("http://") {
+            return Err(format!("{}: target.endpoint must be an http(s) URL", kind));
+        }
+    }
+    Ok(())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// File transfer: upload_file / download_file
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Largest file copied to or from a container.
+pub const MAX_FILE_TRANSFER_BYTES: u64 = 64 * 1024 * 1024;
+/// Largest chunk one command carries, before base64.
+pub const MAX_FILE_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// One chunk of a file copied into `app_code`'s container. The agent stages
+/// chunks under `transfer_id` and writes the file to `path` once all
+/// `total_size` bytes are staged and match `sha256`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UploadFileCommandRequest {
+    pub transfer_id: String,
+    pub app_code: String,
+    /// Absolute path inside the container.
+    pub path: String,
+    pub offset: u64,
+    pub total_size: u64,
+    /// SHA-256 of the whole file, lowercase hex.
+    pub sha256: String,
+    /// Base64-encoded chunk.
+    pub data: String,
+    pub chunk_sha256: String,
+}
+
+/// Read up to `length` bytes at `offset` of `path` in `app_code`'s container.
+/// The agent snapshots the file when `offset` is 0, so every chunk of a
+/// transfer comes from the same content.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DownloadFileCommandRequest {
+    pub transfer_id: String,
+    pub app_code: String,
+    pub path: String,
+    pub offset: u64,
+    pub length: u64,
+    /// Larger files are refused by the agent.
+    pub max_size: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileTransferStatus {
+    Ok,
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UploadFileCommandReport {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub deployment_hash: String,
+    pub app_code: String,
+    pub transfer_id: String,
+    pub status: FileTransferStatus,
+    /// Bytes staged so far; the next chunk starts here. A resumed upload
+    /// learns where to continue from this.
+    #[serde(default)]
+    pub received_bytes: u64,
+    /// The file was verified and written to its path.
+    #[serde(default)]
+    pub complete: bool,
+    #[serde(default)]
+    pub errors: Vec<StatusPanelCommandError>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DownloadFileCommandReport {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub deployment_hash: String,
+    pub app_code: String,
+    pub transfer_id: String,
+    pub status: FileTransferStatus,
+    #[serde(default)]
+    pub total_size: u64,
+    /// SHA-256 of the whole snapshot, lowercase hex.
+    #[serde(default)]
+    pub sha256: String,
+    #[serde(default)]
+    pub offset: u64,
+    /// Base64-encoded chunk.
+    #[serde(default)]
+    pub data: String,
+    #[serde(default)]
+    pub chunk_sha256: String,
+    #[serde(default)]
+    pub errors: Vec<StatusPanelCommandError>,
+}
+
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64
+        && value
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Decode a base64 chunk and check it against its SHA-256.
+pub fn decode_file_chunk(kind: &str, data: &str, chunk_sha256: &str) -> Result<Vec<u8>, String> {
+    use base64::{engine::general_purpose, Engine as _};
+    use sha2::{Digest, Sha256};
+
+    let chunk = general_purpose::STANDARD
+        .decode(data)
+        .map_err(|err| format!("{}: data is not valid base64: {}", kind, err))?;
+    if chunk.len() > MAX_FILE_CHUNK_BYTES {
+        return Err(format!(
+            "{}: chunks are limited to {} bytes",
+            kind, MAX_FILE_CHUNK_BYTES
+        ));
+    }
+    if format!("{:x}", Sha256::digest(&chunk)) != chunk_sha256 {
+        return Err(format!("{}: chunk_sha256 does not match data", kind));
+    }
+    Ok(chunk)
+}
+
+fn validate_file_transfer(kind: &str, transfer_id: &str, path: &str) -> Result<(), String> {
+    let valid_id = !transfer_id.is_empty()
+        && transfer_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid_id {
+        return Err(format!(
+            "{}: transfer_id must be letters, digits, '-' or '_'",
+            kind
+        ));
+    }
+    if !path.starts_with('/') || path.contains('\0') {
+        return Err(format!("{}: path must be an absolute container path", kind));
+    }
+    if path.split('/').any(|segment| segment == "..") {
+        return Err(format!("{}: path must not contain '..'", kind));
+    }
+    Ok(())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Resource usage: stats
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct StatsCommandRequest {
+    /// Only this app's containers; every container when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_code: Option<String>,
+}
+
+/// One `docker stats` sample of a container. Network and block I/O are
+/// counters since the container started.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ContainerStats {
+    pub app_code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_bytes: Option<u64>,
+    #[serde(default)]
+    pub net_rx_bytes: u64,
+    #[serde(default)]
+    pub net_tx_bytes: u64,
+    #[serde(default)]
+    pub block_read_bytes: u64,
+    #[serde(default)]
+    pub block_write_bytes: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StatsCommandReport {
+    #[serde(rename = "type")]
+    pub command_type: String,
+    pub deployment_hash: String,
+    pub collected_at: DateTime<Utc>,
+    #[serde(default)]
+    pub containers: Vec<ContainerStats>,
+    #[serde(default)]
+    pub errors: Vec<StatusPanelCommandError>,
+}
+
+/// Reject samples that cannot be real, so one bad reading does not skew
+/// averages and gauges.
+pub fn validate_container_stats(kind: &str, stats: &[ContainerStats]) -> Result<(), String> {
+    for sample in stats {
+        ensure_app_code(kind, &sample.app_code)?;
+        if !sample.cpu_percent.is_finite() || sample.cpu_percent < 0.0 {
+            return Err(format!(
+                "{}: cpu_percent of {} must be a non-negative number",
+                kind, sample.app_code
+            ));
+        }
+    }
+    Ok(())
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Interactive shell: open_shell
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Longest idle timeout a shell session may ask for (seconds).
+pub const MAX_SHELL_IDLE_TIMEOUT_SECS: u32 = 3600;
+
+/// Ask the agent to start a PTY in `app_code`'s container and attach it to
+/// `GET /api/v1/agent/shell/{session_id}`. Queued by the Stacker server when
+/// a user opens a shell, never by clients directly.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OpenShellCommandRequest {
+    pub session_id: String,
+    pub app_code: String,
+    /// Program to run instead of the container's default shell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    pub cols: u16,
+    pub rows: u16,
+    /// The agent ends the PTY after this long without input.
+    pub idle_timeout_secs: u32,
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Agent self-update: upgrade_agent
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Ask the agent to replace itself with `version`. The agent reports the
+/// result and re-registers with its new capability manifest.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UpgradeAgentCommandRequest {
+    pub version: String,
+    /// Image to pull instead of the default Status Panel image for `version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Log shipping
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Longest message accepted in a shipped log line; agents split or truncate
+/// longer lines.
+pub const MAX_SHIPPED_LOG_MESSAGE_BYTES: usize = 16 * 1024;
+/// Column widths of `container_logs`; a longer value would fail the whole
+/// batch insert.
+const MAX_SHIPPED_LOG_STREAM_LEN: usize = 16;
+const MAX_SHIPPED_LOG_NAME_LEN: usize = 255;
+
+/// A container log line streamed by the agent to `POST /api/v1/agent/logs`.
+/// Secrets are redacted by the agent before shipping, as for `logs`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ShippedLogLine {
+    pub app_code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub ts: DateTime<Utc>,
+    pub stream: LogStream,
+    pub message: String,
+}
+
+pub fn validate_shipped_log_lines(lines: &[ShippedLogLine]) -> Result<(), String> {
+    for line in lines {
+        ensure_app_code("logs", &line.app_code)?;
+        if line.stream.as_str().len() > MAX_SHIPPED_LOG_STREAM_LEN {
+            return Err(format!(
+                "logs: stream of {} is longer than {} characters",
+                line.app_code, MAX_SHIPPED_LOG_STREAM_LEN
+            ));
+        }
+        let names = [Some(line.app_code.as_str()), line.container.as_deref()];
+        if names
+            .into_iter()
+            .flatten()
+            .any(|name| name.chars().count() > MAX_SHIPPED_LOG_NAME_LEN)
+        {
+            return Err(format!(
+                "logs: app_code and container must be at most {} characters",
+                MAX_SHIPPED_LOG_NAME_LEN
+            ));
+        }
+        if line.message.len() > MAX_SHIPPED_LOG_MESSAGE_BYTES {
+            return Err(format!(
+                "logs: line of {} is longer than {} bytes",
+                line.app_code, MAX_SHIPPED_LOG_MESSAGE_BYTES
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(path: &str) -> serde_json::Value {
+        let body = match path {
+            "activate_pipe.webhook.command.json" => include_str!(
+                "../../tests/fixtures/pipe-contract/activate_pipe.webhook.command.json"
+            ),
+            "activate_pipe.rabbitmq.command.json" => include_str!(
+                "../../tests/fixtures/pipe-contract/activate_pipe.rabbitmq.command.json"
+            ),
+            "activate_pipe.adapter.command.json" => include_str!(
+                "../../tests/fixtures/pipe-contract/activate_pipe.adapter.command.json"
+            ),
+            "deactivate_pipe.command.json" => {
+                include_str!("../../tests/fixtures/pipe-contract/deactivate_pipe.command.json")
+            }
+            "trigger_pipe.manual.command.json" => {
+                include_str!("../../tests/fixtures/pipe-contract/trigger_pipe.manual.command.json")
+            }
             "trigger_pipe.adapter.command.json" => {
                 include_str!("../../tests/fixtures/pipe-contract/trigger_pipe.adapter.command.json")
             }
@@ -1340,6 +2388,25 @@ mod tests {
         assert!(err.contains("log_lines"));
     }
 
+    #[test]
+    fn shipped_log_lines_fit_their_columns() {
+        let line: ShippedLogLine = serde_json::from_value(json!({
+            "app_code": "web",
+            "ts": "2026-10-18T12:00:00Z",
+            "stream": "stderr",
+            "message": "boom"
+        }))
+        .unwrap();
+        assert!(validate_shipped_log_lines(&[line.clone()]).is_ok());
+
+        let long = ShippedLogLine {
+            container: Some("c".repeat(MAX_SHIPPED_LOG_NAME_LEN + 1)),
+            ..line
+        };
+        let err = validate_shipped_log_lines(&[long]).unwrap_err();
+        assert!(err.contains("container"));
+    }
+
     #[test]
     fn health_result_requires_matching_hash() {
         let err = validate_command_result(
@@ -1700,6 +2767,94 @@ mod tests {
         assert_eq!(val["force_config_overwrite"], false);
     }
 
+    #[test]
+    fn remove_app_validates_compose_project() {
+        let params = json!({"app_code": "web", "compose_project": "pr-12"});
+        let val = validate_command_parameters("remove_app", &Some(params))
+            .unwrap()
+            .unwrap();
+        assert_eq!(val["compose_project"], "pr-12");
+
+        let params = json!({"app_code": "web", "compose_project": "PR 12"});
+        assert!(validate_command_parameters("remove_app", &Some(params)).is_err());
+
+        let plain = validate_command_parameters("remove_app", &Some(json!({"app_code": "web"})))
+            .unwrap()
+            .unwrap();
+        assert!(plain.get("compose_project").is_none());
+    }
+
+    #[test]
+    fn deploy_app_validates_blue_green_parameters() {
+        let params = json!({
+            "app_code": "web",
+            "blue_green": {"health_endpoint": "/health"}
+        });
+        let val = validate_command_parameters("deploy_app", &Some(params))
+            .unwrap()
+            .unwrap();
+        assert_eq!(val["blue_green"]["health_timeout_secs"], 120);
+        assert_eq!(val["blue_green"]["drain_secs"], 30);
+
+        let params = json!({
+            "app_code": "web",
+            "blue_green": {"health_endpoint": "health"}
+        });
+        assert!(validate_command_parameters("deploy_app", &Some(params)).is_err());
+
+        let plain = validate_command_parameters("deploy_app", &Some(json!({"app_code": "web"})))
+            .unwrap()
+            .unwrap();
+        assert!(plain.get("blue_green").is_none());
+    }
+
+    #[test]
+    fn deploy_app_result_validates_phase_order() {
+        let report = |phases: Value| {
+            json!({
+                "type": "deploy_app",
+                "deployment_hash": "hash",
+                "app_code": "web",
+                "strategy": "blue_green",
+                "active_color": "green",
+                "phases": phases
+            })
+        };
+        let ok = report(json!([
+            {"phase": "start_new", "status": "completed"},
+            {"phase": "health_check", "status": "completed"},
+            {"phase": "switch_upstream", "status": "completed"},
+            {"phase": "drain", "status": "completed"},
+            {"phase": "remove_old", "status": "completed"}
+        ]));
+        assert!(validate_command_result("deploy_app", "hash", &Some(ok)).is_ok());
+
+        let rolled_back = report(json!([
+            {"phase": "start_new", "status": "completed"},
+            {"phase": "health_check", "status": "failed", "message": "timeout"},
+            {"phase": "rollback", "status": "completed"}
+        ]));
+        assert!(validate_command_result("deploy_app", "hash", &Some(rolled_back)).is_ok());
+
+        let unsafe_switch = report(json!([
+            {"phase": "start_new", "status": "completed"},
+            {"phase": "switch_upstream", "status": "completed"}
+        ]));
+        assert!(validate_command_result("deploy_app", "hash", &Some(unsafe_switch)).is_err());
+
+        let no_rollback = report(json!([
+            {"phase": "start_new", "status": "failed"}
+        ]));
+        assert!(validate_command_result("deploy_app", "hash", &Some(no_rollback)).is_err());
+
+        // Free-form output from older agents passes through.
+        let legacy = json!("deployed web");
+        assert_eq!(
+            validate_command_result("deploy_app", "hash", &Some(legacy.clone())).unwrap(),
+            Some(legacy)
+        );
+    }
+
     #[test]
     fn deploy_app_accepts_force_config_overwrite() {
         let params = json!({
@@ -2294,6 +3449,212 @@ mod tests {
         assert_eq!(payload["target_response"]["delivered"], true);
     }
 
+    #[test]
+    fn backup_volume_parameters_and_result_validate() {
+        assert_eq!(
+            DatabaseEngine::detect("docker.io/library/postgres:16-alpine"),
+            Some(DatabaseEngine::Postgres)
+        );
+        assert_eq!(
+            DatabaseEngine::detect("bitnami/mariadb@sha256:abc"),
+            Some(DatabaseEngine::Mysql)
+        );
+        assert_eq!(DatabaseEngine::detect("nginx:latest"), None);
+
+        let params = validate_command_parameters(
+            "backup_volume",
+            &Some(json!({
+                "app_code": "db",
+                "database": "postgres",
+                "target": {"kind": "s3", "bucket": "backups"},
+                "retention": {"keep_last": 7}
+            })),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(params["target"]["kind"], "s3");
+        assert_eq!(params["stop_container"], false);
+
+        let missing_bucket = validate_command_parameters(
+            "backup_volume",
+            &Some(json!({"app_code": "db", "target": {"kind": "s3"}})),
+        )
+        .unwrap_err();
+        assert!(missing_bucket.contains("target.bucket"));
+
+        let report = json!({
+            "type": "backup_volume",
+            "deployment_hash": "dep-1",
+            "app_code": "db",
+            "backup_id": "bkp_20261018T030000Z",
+            "status": "ok",
+            "database": "postgres",
+            "artifacts": [{
+                "name": "database",
+                "location": "/var/lib/status-panel/backups/db/bkp_20261018T030000Z/database.sql.gz",
+                "size_bytes": 2048
+            }],
+            "pruned": ["bkp_20261010T030000Z"]
+        });
+        assert!(validate_command_result("backup_volume", "dep-1", &Some(report)).is_ok());
+
+        let empty = json!({
+            "type": "backup_volume",
+            "deployment_hash": "dep-1",
+            "app_code": "db",
+            "backup_id": "bkp_1",
+            "status": "ok"
+        });
+        assert!(validate_command_result("backup_volume", "dep-1", &Some(empty)).is_err());
+    }
+
+    #[test]
+    fn certificate_status_result_is_capped() {
+        let report = |listing: String| {
+            json!({
+                "type": "certificate_status",
+                "deployment_hash": "dep-1",
+                "app_code": "certbot",
+                "listing": listing
+            })
+        };
+        let listing = "Found the following certs:\n".to_string();
+        assert!(
+            validate_command_result("certificate_status", "dep-1", &Some(report(listing))).is_ok()
+        );
+        let oversized = "x".repeat(MAX_CERTIFICATE_STATUS_BYTES + 1);
+        assert!(
+            validate_command_result("certificate_status", "dep-1", &Some(report(oversized)))
+                .is_err()
+        );
+        let pending = json!({
+            "type": "certificate_status",
+            "deployment_hash": "dep-1",
+            "app_code": "certbot"
+        });
+        assert!(validate_command_result("certificate_status", "dep-1", &Some(pending)).is_ok());
+        assert!(validate_command_parameters("certificate_status", &None).is_err());
+    }
+
+    #[test]
+    fn stats_result_rejects_impossible_samples() {
+        let report = |cpu: f64| {
+            json!({
+                "type": "stats",
+                "deployment_hash": "dep-1",
+                "collected_at": "2026-10-18T12:00:00Z",
+                "containers": [{
+                    "app_code": "web",
+                    "cpu_percent": cpu,
+                    "memory_bytes": 104857600,
+                    "memory_limit_bytes": 536870912,
+                    "net_rx_bytes": 1200,
+                    "net_tx_bytes": 800
+                }]
+            })
+        };
+        assert!(validate_command_result("stats", "dep-1", &Some(report(12.5))).is_ok());
+        assert!(validate_command_result("stats", "dep-1", &Some(report(-1.0))).is_err());
+        assert!(validate_command_result("stats", "dep-2", &Some(report(12.5))).is_err());
+        assert!(validate_command_parameters("stats", &None).is_ok());
+    }
+
+    #[test]
+    fn open_shell_parameters_require_a_session_and_terminal_size() {
+        let params = |cols: u16, idle: u32| {
+            Some(json!({
+                "session_id": "sh_1",
+                "app_code": "web",
+                "cols": cols,
+                "rows": 40,
+                "idle_timeout_secs": idle
+            }))
+        };
+        assert!(validate_command_parameters("open_shell", &params(120, 900)).is_ok());
+        assert!(validate_command_parameters("open_shell", &params(0, 900)).is_err());
+        assert!(validate_command_parameters("open_shell", &params(120, 7200)).is_err());
+        assert!(validate_command_parameters("open_shell", &None).is_err());
+    }
+
+    #[test]
+    fn upgrade_agent_parameters_require_a_plain_version() {
+        let ok = validate_command_parameters("upgrade_agent", &Some(json!({"version": "0.4.2"})));
+        assert_eq!(ok.unwrap().unwrap(), json!({"version": "0.4.2"}));
+        assert!(validate_command_parameters(
+            "upgrade_agent",
+            &Some(json!({"version": "0.4.2; rm -rf /"}))
+        )
+        .is_err());
+        assert!(validate_command_parameters("upgrade_agent", &None).is_err());
+    }
+
+    #[test]
+    fn file_transfer_chunks_are_checksummed_and_capped() {
+        use base64::{engine::general_purpose, Engine as _};
+        use sha2::{Digest, Sha256};
+
+        let chunk = b"hello";
+        let digest = format!("{:x}", Sha256::digest(chunk));
+        let upload = |offset: u64, total_size: u64, path: &str, chunk_sha256: &str| {
+            validate_command_parameters(
+                "upload_file",
+                &Some(json!({
+                    "transfer_id": "tr_1",
+                    "app_code": "web",
+                    "path": path,
+                    "offset": offset,
+                    "total_size": total_size,
+                    "sha256": digest,
+                    "data": general_purpose::STANDARD.encode(chunk),
+                    "chunk_sha256": chunk_sha256,
+                })),
+            )
+        };
+        assert!(upload(0, 5, "/etc/app.conf", &digest).is_ok());
+        assert!(upload(1, 5, "/etc/app.conf", &digest).is_err());
+        assert!(upload(0, 5, "/etc/../root/.ssh", &digest).is_err());
+        assert!(upload(0, 5, "etc/app.conf", &digest).is_err());
+        assert!(upload(0, 5, "/etc/app.conf", &"0".repeat(64)).is_err());
+        assert!(upload(0, MAX_FILE_TRANSFER_BYTES + 1, "/etc/app.conf", &digest).is_err());
+
+        let download = |length: u64| {
+            validate_command_parameters(
+                "download_file",
+                &Some(json!({
+                    "transfer_id": "tr_1",
+                    "app_code": "web",
+                    "path": "/var/log/app.log",
+                    "offset": 0,
+                    "length": length,
+                    "max_size": MAX_FILE_TRANSFER_BYTES,
+                })),
+            )
+        };
+        assert!(download(65536).is_ok());
+        assert!(download(0).is_err());
+
+        let report = |chunk_sha256: &str| {
+            validate_command_result(
+                "download_file",
+                "dep_1",
+                &Some(json!({
+                    "type": "download_file",
+                    "deployment_hash": "dep_1",
+                    "app_code": "web",
+                    "transfer_id": "tr_1",
+                    "status": "ok",
+                    "total_size": 5,
+                    "sha256": digest,
+                    "offset": 0,
+                    "data": general_purpose::STANDARD.encode(chunk),
+                    "chunk_sha256": chunk_sha256,
+                })),
+            )
+        };
+        assert!(report(&digest).is_ok());
+        assert!(report(&"f".repeat(64)).is_err());
+    }
+
     #[test]
     fn trigger_pipe_result_trigger_type_defaults_manual() {
         let result = validate_command_result(
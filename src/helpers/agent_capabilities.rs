@@ -1,7 +1,34 @@
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 
 pub const NPM_CREDENTIAL_SOURCE_KEY: &str = "npm_credential_source";
 pub const NPM_CREDENTIAL_SOURCE_VAULT: &str = "npm_credential_source=vault";
+/// Agent can receive commands over `/commands/stream` instead of long-polling.
+pub const COMMAND_STREAM_CAPABILITY: &str = "command_stream";
+/// Agent can run `open_shell` and relay a PTY over `/agent/shell`.
+pub const SHELL_CAPABILITY: &str = "shell";
+
+/// Protocol version of agents that register a flat capability list only.
+pub const LEGACY_AGENT_PROTOCOL_VERSION: u32 = 1;
+
+/// Structured capabilities an agent reports at registration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentCapabilityManifest {
+    pub protocol_version: u32,
+    /// Supported command types and the parameter schema version of each.
+    #[serde(default)]
+    pub commands: BTreeMap<String, u32>,
+    /// Feature flags in the flat capability format, e.g. `kata` or
+    /// `npm_credential_source=vault`.
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+/// The stored manifest of an agent; `None` for legacy agents.
+pub fn parse_manifest(value: Option<Value>) -> Option<AgentCapabilityManifest> {
+    value.and_then(|value| serde_json::from_value(value).ok())
+}
 
 pub fn extract_capabilities(value: Option<Value>) -> Vec<String> {
     value
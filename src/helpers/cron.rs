@@ -0,0 +1,260 @@
+//! Five-field cron expressions (`minute hour day-of-month month day-of-week`),
+//! evaluated in UTC.
+//!
+//! Fields accept `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`),
+//! lists (`1,15,30`) and three-letter month and weekday names. Day-of-week
+//! runs 0-7 with both 0 and 7 meaning Sunday. As in classic cron, when both
+//! day fields are restricted a day matches if either one does; a day field
+//! starting with `*` (including `*/2`) counts as unrestricted, so the other
+//! field and its step must both match. The shortcuts
+//! `@hourly`, `@daily`, `@midnight`, `@weekly`, `@monthly`, `@yearly` and
+//! `@annually` are expanded before parsing.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+/// Give up looking for a match this far ahead (covers `0 0 29 2 *`).
+const SEARCH_YEARS: i32 = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let trimmed = expression.trim();
+        let expanded = match trimmed.to_ascii_lowercase().as_str() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            _ => trimmed,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Cron expression '{}' must have 5 fields: minute hour day-of-month month day-of-week",
+                trimmed
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, WEEKDAY_NAMES, 0, "day-of-week")?;
+        // 7 is Sunday too.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            expression: trimmed.to_string(),
+            minutes: parse_field(fields[0], 0, 59, &[], 0, "minute")?,
+            hours: parse_field(fields[1], 0, 23, &[], 0, "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, &[], 0, "day-of-month")?,
+            months: parse_field(fields[3], 1, 12, MONTH_NAMES, 1, "month")?,
+            days_of_week,
+            dom_restricted: !fields[2].starts_with('*'),
+            dow_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The first matching minute strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start.year() + SEARCH_YEARS;
+        let mut t = start;
+        while t.year() <= limit {
+            if !bit(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                continue;
+            }
+            if !self.day_matches(t) {
+                t = (t + Duration::days(1)).with_hour(0)?.with_minute(0)?;
+                continue;
+            }
+            if !bit(self.hours, t.hour()) {
+                t = (t + Duration::hours(1)).with_minute(0)?;
+                continue;
+            }
+            if !bit(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    fn day_matches(&self, t: DateTime<Utc>) -> bool {
+        let dom = bit(self.days_of_month, t.day());
+        let dow = bit(self.days_of_week, t.weekday().num_days_from_sunday());
+        if self.dom_restricted && self.dow_restricted {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Parse one field into a bitmask over `min..=max`. `names[i]` stands for
+/// `i + name_offset`.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    name_offset: u32,
+    label: &str,
+) -> Result<u64, String> {
+    let value = |raw: &str| -> Result<u32, String> {
+        let lower = raw.to_ascii_lowercase();
+        if let Some(index) = names.iter().position(|name| *name == lower) {
+            return Ok(index as u32 + name_offset);
+        }
+        let parsed: u32 = raw
+            .parse()
+            .map_err(|_| format!("Invalid {} value '{}'", label, raw))?;
+        if parsed < min || parsed > max {
+            return Err(format!(
+                "{} value {} is outside {}-{}",
+                label, parsed, min, max
+            ));
+        }
+        Ok(parsed)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("Invalid {} step in '{}'", label, part))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            // `5/15` means every 15 starting at 5.
+            (start, if step > 1 { max } else { start })
+        };
+        if start > end {
+            return Err(format!("Invalid {} range '{}'", label, range));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn finds_next_matching_minute() {
+        let every_five = CronSchedule::parse("*/5 * * * *").unwrap();
+        assert_eq!(
+            every_five.next_after(at("2026-10-18T10:02:30Z")),
+            Some(at("2026-10-18T10:05:00Z"))
+        );
+        assert_eq!(
+            every_five.next_after(at("2026-10-18T10:05:00Z")),
+            Some(at("2026-10-18T10:10:00Z"))
+        );
+
+        let nightly = CronSchedule::parse("30 3 * * *").unwrap();
+        assert_eq!(
+            nightly.next_after(at("2026-10-18T04:00:00Z")),
+            Some(at("2026-10-19T03:30:00Z"))
+        );
+
+        let weekdays = CronSchedule::parse("0 9 * * mon-fri").unwrap();
+        // 2026-10-17 is a Saturday.
+        assert_eq!(
+            weekdays.next_after(at("2026-10-17T12:00:00Z")),
+            Some(at("2026-10-19T09:00:00Z"))
+        );
+
+        let leap_day = CronSchedule::parse("0 0 29 feb *").unwrap();
+        assert_eq!(
+            leap_day.next_after(at("2026-10-18T00:00:00Z")),
+            Some(at("2028-02-29T00:00:00Z"))
+        );
+
+        let weekly = CronSchedule::parse("@weekly").unwrap();
+        let sundays = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(
+            weekly.next_after(at("2026-10-18T10:00:00Z")),
+            sundays.next_after(at("2026-10-18T10:00:00Z"))
+        );
+        assert_eq!(weekly.expression(), "@weekly");
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn day_fields_combine_like_classic_cron() {
+        // 2026-10-19 is a Monday.
+        let after = at("2026-10-19T10:00:00Z");
+
+        // Both restricted: the 1st of the month or any Monday.
+        let either = CronSchedule::parse("0 0 1 * mon").unwrap();
+        assert_eq!(either.next_after(after), Some(at("2026-10-26T00:00:00Z")));
+
+        // `*/2` is unrestricted: odd days of the month that are Mondays.
+        let stepped = CronSchedule::parse("0 0 */2 * mon").unwrap();
+        assert_eq!(stepped.next_after(after), Some(at("2026-11-09T00:00:00Z")));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod agent_capabilities;
 pub mod agent_client;
 pub mod client;
+pub mod cron;
 pub mod db_pools;
 pub(crate) mod json;
 pub mod mq_manager;
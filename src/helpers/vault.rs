@@ -12,6 +12,17 @@ pub struct VaultClient {
     ssh_key_path_prefix: String,
 }
 
+/// Certificate returned by the PKI engine's `sign` endpoint.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct SignedCertificate {
+    pub certificate: String,
+    #[serde(default)]
+    pub ca_chain: Vec<String>,
+    pub serial_number: String,
+    /// Unix timestamp of `notAfter`.
+    pub expiration: i64,
+}
+
 impl std::fmt::Debug for VaultClient {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("VaultClient")
@@ -396,6 +407,63 @@ impl VaultClient {
         }
     }
 
+    // ============ PKI Methods ============
+
+    /// Sign an agent's CSR with the PKI engine.
+    /// Path: `{address}/{api_prefix}/{mount}/sign/{role}`
+    #[tracing::instrument(name = "Sign agent certificate in Vault", skip_all)]
+    pub async fn sign_certificate(
+        &self,
+        mount: &str,
+        role: &str,
+        csr: &str,
+        common_name: &str,
+        ttl_hours: u64,
+    ) -> Result<SignedCertificate, String> {
+        let base = self.address.trim_end_matches('/');
+        let api_prefix = self.api_prefix.trim_matches('/');
+        let mount = mount.trim_matches('/');
+        let path = if api_prefix.is_empty() {
+            format!("{}/{}/sign/{}", base, mount, role)
+        } else {
+            format!("{}/{}/{}/sign/{}", base, api_prefix, mount, role)
+        };
+
+        let payload = json!({
+            "csr": csr,
+            "common_name": common_name,
+            "ttl": format!("{}h", ttl_hours),
+        });
+
+        let vault_response: serde_json::Value = self
+            .client
+            .post(&path)
+            .header("X-Vault-Token", &self.token)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to sign certificate in Vault: {:?}", e);
+                format!("Vault sign error: {}", e)
+            })?
+            .error_for_status()
+            .map_err(|e| {
+                tracing::error!("Vault returned error status: {:?}", e);
+                format!("Vault error: {}", e)
+            })?
+            .json()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to parse Vault response: {:?}", e);
+                format!("Vault parse error: {}", e)
+            })?;
+
+        serde_json::from_value(vault_response["data"].clone()).map_err(|e| {
+            tracing::error!("Unexpected Vault sign response: {:?}", e);
+            format!("Vault sign response error: {}", e)
+        })
+    }
+
     // ============ SSH Key Management Methods ============
 
     /// Build the Vault API URL for SSH keys (KV v1).
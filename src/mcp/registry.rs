@@ -63,6 +63,7 @@ use crate::mcp::tools::{
     GetContainerExecTool,
     GetContainerHealthTool,
     GetContainerLogsTool,
+    GetContainerMetricsTool,
     GetDeploymentEventsTool,
     GetDeploymentPlanTool,
     GetDeploymentResourcesTool,
@@ -91,6 +92,7 @@ use crate::mcp::tools::{
     ListCloudServerSizesTool,
     ListCloudsTool,
     ListContainersTool,
+    ListDeploymentReleasesTool,
     ListFirewallRulesTool,
     ListInstallationsTool,
     ListPipeTemplatesTool,
@@ -112,7 +114,9 @@ use crate::mcp::tools::{
     ReplayPipeExecutionTool,
     RequestServerSnapshotTool,
     RestartContainerTool,
+    RollbackDeploymentReleaseTool,
     SearchApplicationsTool,
+    SearchLogsTool,
     SearchMarketplaceTemplatesTool,
     SetAppEnvVarTool,
     SetRemoteServiceSecretTool,
@@ -236,6 +240,14 @@ impl ToolRegistry {
         registry.register("get_deployment_plan", Box::new(GetDeploymentPlanTool));
         registry.register("get_deployment_events", Box::new(GetDeploymentEventsTool));
         registry.register("apply_deployment_plan", Box::new(ApplyDeploymentPlanTool));
+        registry.register(
+            "list_deployment_releases",
+            Box::new(ListDeploymentReleasesTool),
+        );
+        registry.register(
+            "rollback_deployment_release",
+            Box::new(RollbackDeploymentReleaseTool),
+        );
         registry.register("explain_env", Box::new(ExplainEnvTool));
         registry.register("explain_topology", Box::new(ExplainTopologyTool));
         registry.register("start_deployment", Box::new(StartDeploymentTool));
@@ -384,6 +396,8 @@ impl ToolRegistry {
             Box::new(GetDockerComposeYamlTool),
         );
         registry.register("get_server_resources", Box::new(GetServerResourcesTool));
+        registry.register("get_container_metrics", Box::new(GetContainerMetricsTool));
+        registry.register("search_logs", Box::new(SearchLogsTool));
         registry.register("get_container_exec", Box::new(GetContainerExecTool));
 
         // Marketplace Admin tools (admin role required)
@@ -433,6 +447,9 @@ impl ToolRegistry {
             Box::new(GetAgentCommandHistoryTool),
         );
         registry.register("execute_agent_command", Box::new(ExecuteAgentCommandTool));
+        registry.register("create_agent_schedule", Box::new(CreateAgentScheduleTool));
+        registry.register("list_agent_schedules", Box::new(ListAgentSchedulesTool));
+        registry.register("delete_agent_schedule", Box::new(DeleteAgentScheduleTool));
 
         // Firewall (iptables) management tools
         registry.register("configure_firewall", Box::new(ConfigureFirewallTool));
@@ -14,7 +14,9 @@ use crate::connectors::user_service::UserServiceDeploymentResolver;
 use crate::db;
 use crate::mcp::protocol::{Tool, ToolContent};
 use crate::mcp::registry::{ToolContext, ToolHandler};
-use crate::models::{Command, CommandPriority};
+use crate::models::Command;
+use crate::services::command_queue;
+use crate::services::command_schedule::{create_schedule, ScheduleRequest};
 use crate::services::{DeploymentIdentifier, DeploymentResolver};
 
 const COMMAND_RESULT_TIMEOUT_SECS: u64 = 15;
@@ -76,18 +78,9 @@ async fn enqueue_and_wait(
     )
     .with_parameters(parameters.clone());
 
-    let command = db::command::insert(&context.pg_pool, &command)
+    let command = command_queue::enqueue(&context.pg_pool, command, None)
         .await
-        .map_err(|e| format!("Failed to create command: {}", e))?;
-
-    db::command::add_to_queue(
-        &context.pg_pool,
-        &command.command_id,
-        deployment_hash,
-        &CommandPriority::Normal,
-    )
-    .await
-    .map_err(|e| format!("Failed to queue command: {}", e))?;
+        .map_err(|error| error.to_pretty_json())?;
 
     if let Some(cmd) =
         wait_for_command_result(&context.pg_pool, &command.command_id, timeout_secs).await?
@@ -131,18 +124,9 @@ async fn enqueue_request_and_wait(
         command = command.with_timeout(timeout_seconds);
     }
 
-    let command = db::command::insert(&context.pg_pool, &command)
+    let command = command_queue::enqueue(&context.pg_pool, command, None)
         .await
-        .map_err(|e| format!("Failed to create command: {}", e))?;
-
-    db::command::add_to_queue(
-        &context.pg_pool,
-        &command.command_id,
-        &request.deployment_hash,
-        &CommandPriority::Normal,
-    )
-    .await
-    .map_err(|e| format!("Failed to queue command: {}", e))?;
+        .map_err(|error| error.to_pretty_json())?;
 
     if let Some(cmd) =
         wait_for_command_result(&context.pg_pool, &command.command_id, timeout_secs).await?
@@ -738,3 +722,227 @@ impl ToolHandler for ExecuteAgentCommandTool {
         }
     }
 }
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// Command Schedule Tools
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+pub struct CreateAgentScheduleTool;
+
+#[async_trait]
+impl ToolHandler for CreateAgentScheduleTool {
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
+        #[derive(Deserialize)]
+        struct Args {
+            #[serde(default)]
+            deployment_id: Option<i64>,
+            #[serde(default)]
+            deployment_hash: Option<String>,
+            #[serde(flatten)]
+            schedule: ScheduleRequest,
+        }
+
+        let params: Args =
+            serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))?;
+
+        let identifier =
+            DeploymentIdentifier::try_from_options(params.deployment_hash, params.deployment_id)?;
+        let resolver = create_resolver(context);
+        let deployment_hash = resolver.resolve(&identifier).await?;
+
+        let schedule = create_schedule(
+            &context.pg_pool,
+            &deployment_hash,
+            &context.user.id,
+            &params.schedule,
+        )
+        .await
+        .map_err(|error| error.message)?;
+
+        tracing::info!(
+            user_id = %context.user.id,
+            deployment_hash = %deployment_hash,
+            schedule_id = %schedule.schedule_id,
+            "Created command schedule via MCP"
+        );
+
+        Ok(ToolContent::Text {
+            text: serde_json::to_string(&schedule).map_err(|e| e.to_string())?,
+        })
+    }
+
+    fn schema(&self) -> Tool {
+        Tool {
+            name: "create_agent_schedule".to_string(),
+            description: "Run a Status Panel agent command on a cron schedule (UTC), e.g. restart a worker nightly or run probe_endpoints every 5 minutes. The server queues the command each time the expression matches. deploy_app and remove_app cannot be scheduled.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "deployment_id": {
+                        "type": "number",
+                        "description": "The deployment/installation ID"
+                    },
+                    "deployment_hash": {
+                        "type": "string",
+                        "description": "The deployment hash"
+                    },
+                    "cron": {
+                        "type": "string",
+                        "description": "Five-field cron expression in UTC (e.g. '0 3 * * *', '*/5 * * * *') or @hourly/@daily/@weekly/@monthly"
+                    },
+                    "command_type": {
+                        "type": "string",
+                        "description": "Agent command type (e.g. 'restart', 'health', 'probe_endpoints')"
+                    },
+                    "parameters": {
+                        "type": "object",
+                        "description": "Command parameters, validated like a one-off agent command"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Optional label for the schedule"
+                    },
+                    "priority": {
+                        "type": "string",
+                        "enum": ["low", "normal", "high", "critical"]
+                    },
+                    "timeout_seconds": {
+                        "type": "number",
+                        "description": "Agent-side timeout for each queued command"
+                    },
+                    "missed_run_policy": {
+                        "type": "string",
+                        "enum": ["skip", "run_once"],
+                        "description": "What to do with a run the server missed: skip it (default) or queue one catch-up command"
+                    }
+                },
+                "required": ["cron", "command_type"]
+            }),
+        }
+    }
+}
+
+pub struct ListAgentSchedulesTool;
+
+#[async_trait]
+impl ToolHandler for ListAgentSchedulesTool {
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
+        #[derive(Deserialize)]
+        struct Args {
+            #[serde(default)]
+            deployment_id: Option<i64>,
+            #[serde(default)]
+            deployment_hash: Option<String>,
+        }
+
+        let params: Args =
+            serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))?;
+
+        let identifier =
+            DeploymentIdentifier::try_from_options(params.deployment_hash, params.deployment_id)?;
+        let resolver = create_resolver(context);
+        let deployment_hash = resolver.resolve(&identifier).await?;
+
+        let schedules =
+            db::command_schedule::list_by_deployment(&context.pg_pool, &deployment_hash).await?;
+
+        Ok(ToolContent::Text {
+            text: json!({
+                "deployment_hash": deployment_hash,
+                "schedules": schedules,
+            })
+            .to_string(),
+        })
+    }
+
+    fn schema(&self) -> Tool {
+        Tool {
+            name: "list_agent_schedules".to_string(),
+            description: "List the cron schedules of agent commands on a deployment, with their next run time and last queued command.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "deployment_id": {
+                        "type": "number",
+                        "description": "The deployment/installation ID"
+                    },
+                    "deployment_hash": {
+                        "type": "string",
+                        "description": "The deployment hash"
+                    }
+                }
+            }),
+        }
+    }
+}
+
+pub struct DeleteAgentScheduleTool;
+
+#[async_trait]
+impl ToolHandler for DeleteAgentScheduleTool {
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
+        #[derive(Deserialize)]
+        struct Args {
+            #[serde(default)]
+            deployment_id: Option<i64>,
+            #[serde(default)]
+            deployment_hash: Option<String>,
+            schedule_id: String,
+        }
+
+        let params: Args =
+            serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))?;
+
+        let identifier =
+            DeploymentIdentifier::try_from_options(params.deployment_hash, params.deployment_id)?;
+        let resolver = create_resolver(context);
+        let deployment_hash = resolver.resolve(&identifier).await?;
+
+        let deleted =
+            db::command_schedule::delete(&context.pg_pool, &deployment_hash, &params.schedule_id)
+                .await?;
+        if !deleted {
+            return Err(format!("No command schedule '{}'", params.schedule_id));
+        }
+
+        tracing::info!(
+            user_id = %context.user.id,
+            deployment_hash = %deployment_hash,
+            schedule_id = %params.schedule_id,
+            "Deleted command schedule via MCP"
+        );
+
+        Ok(ToolContent::Text {
+            text: json!({
+                "deleted": true,
+                "schedule_id": params.schedule_id,
+            })
+            .to_string(),
+        })
+    }
+
+    fn schema(&self) -> Tool {
+        Tool {
+            name: "delete_agent_schedule".to_string(),
+            description: "Delete a cron schedule of agent commands. Commands it already queued are not cancelled.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "deployment_id": {
+                        "type": "number",
+                        "description": "The deployment/installation ID"
+                    },
+                    "deployment_hash": {
+                        "type": "string",
+                        "description": "The deployment hash"
+                    },
+                    "schedule_id": {
+                        "type": "string",
+                        "description": "Schedule id from list_agent_schedules"
+                    }
+                },
+                "required": ["schedule_id"]
+            }),
+        }
+    }
+}
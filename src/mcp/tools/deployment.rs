@@ -7,13 +7,16 @@ use crate::connectors::user_service::UserServiceDeploymentResolver;
 use crate::db;
 use crate::mcp::protocol::{Tool, ToolContent};
 use crate::mcp::registry::{ToolContext, ToolHandler};
-use crate::models::{Command, CommandPriority, Deployment};
+use crate::models::{Command, Deployment};
+use crate::services::command_queue;
 use crate::services::{
-    build_deploy_plan, build_rollback_plan, resolve_rollback_plan_context, DeployPlan,
-    DeployPlanAction, DeployPlanOperation, DeployPlanRollback, DeployPlanScope,
-    DeploymentAgentState, DeploymentDriftState, DeploymentEvent, DeploymentEventFeed,
-    DeploymentIdentifier, DeploymentLastCommandState, DeploymentProjectState, DeploymentResolver,
-    DeploymentRuntimeState, DeploymentState, DeploymentStateDeployment, TypedErrorCode,
+    build_deploy_plan, build_rollback_plan, deployment_lease::ensure_lease_holder,
+    release::apply_release_rollback,
+    resolve_rollback_plan_context, DeployPlan, DeployPlanAction, DeployPlanOperation,
+    DeployPlanRollback, DeployPlanScope, DeploymentAgentState, DeploymentDriftState,
+    DeploymentEvent, DeploymentEventFeed, DeploymentIdentifier, DeploymentLastCommandState,
+    DeploymentLeaseState, DeploymentProjectState, DeploymentResolver, DeploymentRuntimeState,
+    DeploymentState, DeploymentStateDeployment, RollbackTargetKind, TypedErrorCode,
     TypedErrorEnvelope, TypedRemediationClass, DEPLOY_PLAN_SCHEMA_VERSION,
 };
 
@@ -23,6 +26,8 @@ pub struct GetDeploymentStateTool;
 pub struct GetDeploymentPlanTool;
 pub struct GetDeploymentEventsTool;
 pub struct ApplyDeploymentPlanTool;
+pub struct ListDeploymentReleasesTool;
+pub struct RollbackDeploymentReleaseTool;
 
 const COMMAND_RESULT_TIMEOUT_SECS: u64 = 15;
 const COMMAND_POLL_INTERVAL_MS: u64 = 500;
@@ -70,6 +75,8 @@ struct McpDeploymentStateResponse {
     drift: DeploymentDriftState,
     #[serde(skip_serializing_if = "Option::is_none")]
     last_command: Option<DeploymentLastCommandState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lease: Option<DeploymentLeaseState>,
 }
 
 #[derive(Serialize)]
@@ -137,6 +144,38 @@ struct DeploymentPlanArgs {
     rollback_target: Option<String>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct McpDeploymentReleaseSummary {
+    version: i32,
+    bundle_hash: String,
+    image_digests: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rollback_of: Option<i32>,
+    created_by: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct McpDeploymentReleasesResponse {
+    deployment_hash: String,
+    releases: Vec<McpDeploymentReleaseSummary>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseRollbackArgs {
+    #[serde(flatten)]
+    lookup: DeploymentLookupArgs,
+    release: String,
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(default)]
+    expected_fingerprint: Option<String>,
+    #[serde(default)]
+    confirm: bool,
+}
+
 #[derive(Deserialize)]
 struct ApplyDeploymentPlanArgs {
     #[serde(flatten)]
@@ -170,6 +209,20 @@ impl From<DeploymentState> for McpDeploymentStateResponse {
             apps: value.apps,
             drift: value.drift,
             last_command: value.last_command,
+            lease: value.lease,
+        }
+    }
+}
+
+impl From<crate::models::DeploymentRelease> for McpDeploymentReleaseSummary {
+    fn from(value: crate::models::DeploymentRelease) -> Self {
+        Self {
+            version: value.version,
+            bundle_hash: value.bundle_hash,
+            image_digests: value.image_digests,
+            rollback_of: value.rollback_of,
+            created_by: value.created_by,
+            created_at: value.created_at,
         }
     }
 }
@@ -254,18 +307,9 @@ async fn enqueue_and_wait(
     )
     .with_parameters(parameters.clone());
 
-    let command = db::command::insert(&context.pg_pool, &command)
+    let command = command_queue::enqueue(&context.pg_pool, command, None)
         .await
-        .map_err(|e| format!("Failed to create command: {}", e))?;
-
-    db::command::add_to_queue(
-        &context.pg_pool,
-        &command.command_id,
-        deployment_hash,
-        &CommandPriority::Normal,
-    )
-    .await
-    .map_err(|e| format!("Failed to queue command: {}", e))?;
+        .map_err(|error| error.to_pretty_json())?;
 
     if let Some(cmd) =
         wait_for_command_result(&context.pg_pool, &command.command_id, timeout_secs).await?
@@ -521,7 +565,7 @@ impl ToolHandler for GetDeploymentPlanTool {
                     },
                     "rollback_target": {
                         "type": "string",
-                        "description": "Required for rollback_deploy plans. Use 'previous' or a specific marketplace version."
+                        "description": "Required for rollback_deploy plans. Use 'previous', a specific marketplace version, or a release number for user-authored projects."
                     }
                 },
                 "required": []
@@ -557,6 +601,10 @@ impl ToolHandler for ApplyDeploymentPlanTool {
             });
         }
 
+        ensure_lease_holder(&context.pg_pool, &plan.deployment_hash, None)
+            .await
+            .map_err(|error| error.to_pretty_json())?;
+
         match plan.operation {
             DeployPlanOperation::DeployApp => {
                 let app_code = plan.scope.app_code.clone().ok_or_else(|| {
@@ -617,6 +665,35 @@ impl ToolHandler for ApplyDeploymentPlanTool {
                     )
                     .to_pretty_json()
                 })?;
+                if rollback.kind == RollbackTargetKind::Release {
+                    let outcome = apply_release_rollback(
+                        &context.pg_pool,
+                        &deployment,
+                        &context.user.id,
+                        &plan.target,
+                        &rollback.requested_target,
+                        Some(&plan.fingerprint),
+                        None,
+                    )
+                    .await
+                    .map_err(|error| error.to_pretty_json())?;
+                    return json_tool_content(&McpApplyDeploymentPlanResponse {
+                        schema_version: DEPLOY_PLAN_SCHEMA_VERSION.to_string(),
+                        deployment_hash: plan.deployment_hash,
+                        operation: plan.operation,
+                        fingerprint: plan.fingerprint,
+                        applied: true,
+                        has_changes: true,
+                        status: "queued".to_string(),
+                        message: format!(
+                            "Rollback to release {} queued as {} deploy_app command(s)",
+                            rollback.resolved_version,
+                            outcome.command_ids.len()
+                        ),
+                        command_id: outcome.command_ids.first().cloned(),
+                        rollback: Some(rollback),
+                    });
+                }
                 let client = stacker_client(context)?;
                 let response = client
                     .rollback_project(deployment.project_id, &rollback.resolved_version)
@@ -681,7 +758,7 @@ impl ToolHandler for ApplyDeploymentPlanTool {
                     },
                     "rollback_target": {
                         "type": "string",
-                        "description": "Required for rollback_deploy applies. Use 'previous' or a specific marketplace version."
+                        "description": "Required for rollback_deploy applies. Use 'previous', a specific marketplace version, or a release number for user-authored projects."
                     },
                     "confirm": {
                         "type": "boolean",
@@ -694,6 +771,133 @@ impl ToolHandler for ApplyDeploymentPlanTool {
     }
 }
 
+#[async_trait]
+impl ToolHandler for ListDeploymentReleasesTool {
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
+        let args: DeploymentLookupArgs =
+            serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))?;
+        let (deployment_hash, _) = resolve_owned_deployment(context, args).await?;
+        let releases =
+            db::deployment_release::list_by_deployment_hash(&context.pg_pool, &deployment_hash)
+                .await?;
+
+        json_tool_content(&McpDeploymentReleasesResponse {
+            deployment_hash,
+            releases: releases.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    fn schema(&self) -> Tool {
+        Tool {
+            name: "list_deployment_releases".to_string(),
+            description: "List the immutable release records of a deployment, newest first. Each successful deploy records one release.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "deployment_hash": {
+                        "type": "string",
+                        "description": "Deployment hash (preferred, e.g., 'deployment_abc123')"
+                    },
+                    "deployment_id": {
+                        "type": "number",
+                        "description": "Deployment ID (legacy numeric ID from User Service)"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for RollbackDeploymentReleaseTool {
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
+        let args: ReleaseRollbackArgs =
+            serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))?;
+        let target = args.target.unwrap_or_else(|| "cloud".to_string());
+        let (deployment_hash, deployment) = resolve_owned_deployment(context, args.lookup).await?;
+
+        if !args.confirm {
+            let state = DeploymentState::for_deployment_hash(&context.pg_pool, &deployment_hash)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?
+                .ok_or_else(|| "Deployment not found".to_string())?;
+            let rollback = crate::services::resolve_release_rollback_context(
+                &context.pg_pool,
+                &deployment,
+                &args.release,
+            )
+            .await
+            .map_err(|error| error.to_pretty_json())?;
+            let plan = build_rollback_plan(&state, &target, rollback, None)
+                .map_err(|error| error.to_pretty_json())?;
+            return json_tool_content(&McpDeploymentPlanResponse::from(plan));
+        }
+
+        let fingerprint = args
+            .expected_fingerprint
+            .as_deref()
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| {
+                TypedErrorEnvelope::invalid_request(
+                    "rollback_deployment_release requires expected_fingerprint from the preview",
+                )
+                .with_context("tool", "rollback_deployment_release")
+                .to_pretty_json()
+            })?;
+        let outcome = apply_release_rollback(
+            &context.pg_pool,
+            &deployment,
+            &context.user.id,
+            &target,
+            &args.release,
+            Some(fingerprint),
+            None,
+        )
+        .await
+        .map_err(|error| error.to_pretty_json())?;
+
+        json_tool_content(&outcome)
+    }
+
+    fn schema(&self) -> Tool {
+        Tool {
+            name: "rollback_deployment_release".to_string(),
+            description: "Roll a deployment back to a recorded release. Without confirm=true it returns the rollback plan; with confirm=true and the plan fingerprint it queues the rollback.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "deployment_hash": {
+                        "type": "string",
+                        "description": "Deployment hash (preferred, e.g., 'deployment_abc123')"
+                    },
+                    "deployment_id": {
+                        "type": "number",
+                        "description": "Deployment ID (legacy numeric ID from User Service)"
+                    },
+                    "release": {
+                        "type": "string",
+                        "description": "'previous' or a release number from list_deployment_releases."
+                    },
+                    "target": {
+                        "type": "string",
+                        "description": "Deployment target. Defaults to 'cloud'."
+                    },
+                    "expected_fingerprint": {
+                        "type": "string",
+                        "description": "Fingerprint from the preview; required with confirm=true."
+                    },
+                    "confirm": {
+                        "type": "boolean",
+                        "description": "Must be true to queue the rollback."
+                    }
+                },
+                "required": ["release"]
+            }),
+        }
+    }
+}
+
 #[async_trait]
 impl ToolHandler for GetDeploymentEventsTool {
     async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
@@ -968,6 +1172,7 @@ mod tests {
                 summary: "no drift detected".to_string(),
             },
             last_command: None,
+            lease: None,
         };
 
         let serialized = serde_json::to_value(McpDeploymentStateResponse::from(state))
@@ -985,6 +1190,8 @@ mod tests {
         assert!(registry.has_tool("get_deployment_plan"));
         assert!(registry.has_tool("get_deployment_events"));
         assert!(registry.has_tool("apply_deployment_plan"));
+        assert!(registry.has_tool("list_deployment_releases"));
+        assert!(registry.has_tool("rollback_deployment_release"));
     }
 
     #[test]
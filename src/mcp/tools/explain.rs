@@ -130,6 +130,7 @@ fn topology_services(apps: &[ProjectApp]) -> Vec<ExplainTopologyService> {
             code: app.code.clone(),
             name: app.name.clone(),
             enabled: app.enabled.unwrap_or(true),
+            host: None,
         })
         .collect()
 }
@@ -455,6 +456,7 @@ mod tests {
                 code: "upload".to_string(),
                 name: "Upload".to_string(),
                 enabled: true,
+                host: None,
             }],
         );
 
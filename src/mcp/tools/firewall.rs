@@ -18,11 +18,11 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::connectors::user_service::UserServiceDeploymentResolver;
-use crate::db;
 use crate::forms::status_panel::{ConfigureFirewallCommandRequest, FirewallPortRule};
 use crate::mcp::protocol::{Tool, ToolContent};
 use crate::mcp::registry::{ToolContext, ToolHandler};
 use crate::models::{Command, CommandPriority};
+use crate::services::command_queue;
 use crate::services::{DeploymentIdentifier, DeploymentResolver};
 
 /// Execution method for firewall commands
@@ -111,19 +111,9 @@ impl ToolHandler for ConfigureFirewallTool {
                 .with_priority(CommandPriority::High);
 
                 // Insert command
-                let saved = db::command::insert(&context.pg_pool, &command)
+                let saved = command_queue::enqueue(&context.pg_pool, command, None)
                     .await
-                    .map_err(|e| format!("Failed to create firewall command: {}", e))?;
-
-                // Add to queue
-                db::command::add_to_queue(
-                    &context.pg_pool,
-                    &saved.command_id,
-                    &saved.deployment_hash,
-                    &CommandPriority::High,
-                )
-                .await
-                .map_err(|e| format!("Failed to queue firewall command: {}", e))?;
+                    .map_err(|error| error.to_pretty_json())?;
 
                 tracing::info!(
                     command_id = %saved.command_id,
@@ -297,18 +287,9 @@ impl ToolHandler for ListFirewallRulesTool {
         .with_parameters(serde_json::to_value(&firewall_request).unwrap())
         .with_priority(CommandPriority::Normal);
 
-        let saved = db::command::insert(&context.pg_pool, &command)
+        let saved = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create list firewall command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &saved.command_id,
-            &saved.deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue list firewall command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         tracing::info!(
             command_id = %saved.command_id,
@@ -471,18 +452,9 @@ impl ToolHandler for ConfigureFirewallFromRoleTool {
         .with_parameters(serde_json::to_value(&firewall_request).unwrap())
         .with_priority(CommandPriority::High);
 
-        let saved = db::command::insert(&context.pg_pool, &command)
+        let saved = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create firewall command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &saved.command_id,
-            &saved.deployment_hash,
-            &CommandPriority::High,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue firewall command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         tracing::info!(
             command_id = %saved.command_id,
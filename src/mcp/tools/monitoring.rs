@@ -3,6 +3,7 @@
 //! These tools provide AI access to:
 //! - Container logs (paginated, redacted)
 //! - Container health metrics (CPU, RAM, network)
+//! - Retained logs shipped by the agent (full-text search)
 //! - Deployment-wide container status
 //!
 //! Commands are dispatched to Status Agent via Stacker's agent communication layer.
@@ -20,7 +21,11 @@ use crate::db;
 use crate::mcp::protocol::{Tool, ToolContent};
 use crate::mcp::registry::{ToolContext, ToolHandler};
 use crate::models::{Command, CommandPriority};
-use crate::services::{DeploymentIdentifier, DeploymentResolver, VaultService};
+use crate::services::command_queue;
+use crate::services::{
+    container_metrics, init_log_store, DeploymentIdentifier, DeploymentResolver, LogSearchQuery,
+    LogStore, VaultService,
+};
 use serde::Deserialize;
 
 const DEFAULT_LOG_LIMIT: usize = 100;
@@ -154,18 +159,9 @@ impl ToolHandler for GetContainerLogsTool {
         }));
 
         // Insert command and add to queue
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         // Wait for result or timeout
         let result = if let Some(cmd) =
@@ -281,18 +277,9 @@ impl ToolHandler for GetContainerHealthTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         // Wait for result or timeout
         let result = if let Some(cmd) =
@@ -404,18 +391,9 @@ impl ToolHandler for RestartContainerTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::High,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         let result = json!({
             "status": "queued",
@@ -758,18 +736,9 @@ impl ToolHandler for StopContainerTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::High,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         let result = json!({
             "status": "queued",
@@ -868,18 +837,9 @@ impl ToolHandler for StartContainerTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::High,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         let result = json!({
             "status": "queued",
@@ -975,18 +935,9 @@ impl ToolHandler for GetErrorSummaryTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         let result = json!({
             "status": "queued",
@@ -1073,6 +1024,7 @@ impl ToolHandler for ListContainersTool {
             "list_containers".to_string(),
             context.user.id.clone(),
         )
+        .with_priority(CommandPriority::High) // High priority for quick discovery
         .with_parameters(json!({
             "name": "stacker.list_containers",
             "params": {
@@ -1080,18 +1032,9 @@ impl ToolHandler for ListContainersTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::High, // High priority for quick discovery
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         // Also try to get containers from project_app table if we have a project
         let mut known_apps: Vec<serde_json::Value> = Vec::new();
@@ -1304,18 +1247,9 @@ impl ToolHandler for GetServerResourcesTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         // Wait for result or timeout
         let result = if let Some(cmd) =
@@ -1374,6 +1308,190 @@ impl ToolHandler for GetServerResourcesTool {
     }
 }
 
+/// Container CPU, memory and I/O over a recent window, summarised per app
+/// from the samples agents push periodically
+pub struct GetContainerMetricsTool;
+
+#[async_trait]
+impl ToolHandler for GetContainerMetricsTool {
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
+        #[derive(Deserialize)]
+        struct Args {
+            #[serde(default)]
+            deployment_id: Option<i64>,
+            #[serde(default)]
+            deployment_hash: Option<String>,
+            #[serde(default)]
+            app_code: Option<String>,
+            #[serde(default)]
+            since: Option<String>,
+        }
+
+        let params: Args =
+            serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))?;
+        let since = params.since.unwrap_or_else(|| "1h".to_string());
+        let window = container_metrics::parse_window(&since)?;
+
+        let identifier =
+            DeploymentIdentifier::try_from_options(params.deployment_hash, params.deployment_id)?;
+        let resolver = create_resolver(context);
+        let deployment_hash = resolver.resolve(&identifier).await?;
+
+        let samples = db::container_metrics::list_since(
+            &context.pg_pool,
+            &deployment_hash,
+            chrono::Utc::now() - window,
+            params.app_code.as_deref(),
+            container_metrics::MAX_QUERY_SAMPLES,
+        )
+        .await?;
+
+        let result = json!({
+            "deployment_hash": deployment_hash,
+            "since": since,
+            "samples": samples.len(),
+            "apps": container_metrics::summarize(&samples),
+            "message": if samples.is_empty() {
+                "No samples in this window. Use get_server_resources for a live reading."
+            } else {
+                "Averages and peaks per app; network bytes are totals for the window."
+            }
+        });
+
+        Ok(ToolContent::Text {
+            text: serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string()),
+        })
+    }
+
+    fn schema(&self) -> Tool {
+        Tool {
+            name: "get_container_metrics".to_string(),
+            description: "Get CPU, memory and network usage of deployment containers over a recent window (e.g. the last hour), summarised per app with averages and peaks. Useful for spotting memory growth or CPU spikes without a live reading.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "deployment_id": {
+                        "type": "number",
+                        "description": "The deployment/installation ID (for legacy User Service deployments)"
+                    },
+                    "deployment_hash": {
+                        "type": "string",
+                        "description": "The deployment hash (for Stack Builder deployments). Use this if available in context."
+                    },
+                    "app_code": {
+                        "type": "string",
+                        "description": "Only this app (default: all apps)"
+                    },
+                    "since": {
+                        "type": "string",
+                        "description": "Look-back window such as 15m, 1h or 7d (default 1h, at most 14d)"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+}
+
+/// Search retained logs shipped by the agent across a deployment's containers
+pub struct SearchLogsTool;
+
+#[async_trait]
+impl ToolHandler for SearchLogsTool {
+    async fn execute(&self, args: Value, context: &ToolContext) -> Result<ToolContent, String> {
+        #[derive(Deserialize)]
+        struct Args {
+            #[serde(default)]
+            deployment_id: Option<i64>,
+            #[serde(default)]
+            deployment_hash: Option<String>,
+            #[serde(default)]
+            query: Option<String>,
+            #[serde(default)]
+            app_code: Option<String>,
+            #[serde(default)]
+            since: Option<String>,
+            #[serde(default)]
+            limit: Option<i64>,
+        }
+
+        let params: Args =
+            serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))?;
+
+        let identifier =
+            DeploymentIdentifier::try_from_options(params.deployment_hash, params.deployment_id)?;
+        let resolver = create_resolver(context);
+        let deployment_hash = resolver.resolve(&identifier).await?;
+
+        let settings = &context.settings.log_shipping;
+        let query = LogSearchQuery::parse(
+            settings,
+            &deployment_hash,
+            params.since.as_deref(),
+            params.app_code.as_deref(),
+            params.query.as_deref(),
+            params.limit,
+        )?;
+        let lines = init_log_store(settings)?
+            .search(&context.pg_pool, &query)
+            .await?;
+
+        let result = json!({
+            "deployment_hash": deployment_hash,
+            "query": query.text,
+            "since": query.since,
+            "count": lines.len(),
+            "lines": lines,
+            "message": if lines.is_empty() {
+                "No matching lines. Widen `since`, drop `app_code`, or use get_container_logs for live output."
+            } else {
+                "Newest matching lines, oldest first."
+            }
+        });
+
+        Ok(ToolContent::Text {
+            text: serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string()),
+        })
+    }
+
+    fn schema(&self) -> Tool {
+        Tool {
+            name: "search_logs".to_string(),
+            description: "Search retained container logs of a deployment, across all its containers, for words or phrases (e.g. timeout, \"connection refused\") over a recent window. Use it to find when an error started and which services logged it; logs are streamed continuously by the agent, so containers that already restarted are included.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "deployment_id": {
+                        "type": "number",
+                        "description": "The deployment/installation ID (for legacy User Service deployments)"
+                    },
+                    "deployment_hash": {
+                        "type": "string",
+                        "description": "The deployment hash (for Stack Builder deployments). Use this if available in context."
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Words or quoted phrases to find; -word excludes (default: every line)"
+                    },
+                    "app_code": {
+                        "type": "string",
+                        "description": "Only this app (default: all apps)"
+                    },
+                    "since": {
+                        "type": "string",
+                        "description": "Look-back window such as 15m, 1h or 7d (default 1h)"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "Maximum lines to return (default 500)"
+                    }
+                },
+                "required": []
+            }),
+        }
+    }
+}
+
 /// Execute a command inside a running container
 /// Allows running diagnostic commands for troubleshooting
 pub struct GetContainerExecTool;
@@ -1449,18 +1567,9 @@ impl ToolHandler for GetContainerExecTool {
             }
         }));
 
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::High,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         let result = json!({
             "status": "queued",
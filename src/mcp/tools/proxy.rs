@@ -7,10 +7,10 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::connectors::user_service::UserServiceDeploymentResolver;
-use crate::db;
 use crate::mcp::protocol::{Tool, ToolContent};
 use crate::mcp::registry::{ToolContext, ToolHandler};
-use crate::models::{Command, CommandPriority};
+use crate::models::Command;
+use crate::services::command_queue;
 use crate::services::{DeploymentIdentifier, DeploymentResolver};
 
 /// Helper to create a resolver from context.
@@ -109,18 +109,9 @@ impl ToolHandler for ConfigureProxyTool {
         }));
 
         // Insert command and add to queue
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         tracing::info!(
             user_id = %context.user.id,
@@ -263,18 +254,9 @@ impl ToolHandler for DeleteProxyTool {
         }));
 
         // Insert command and add to queue
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         tracing::info!(
             user_id = %context.user.id,
@@ -383,18 +365,9 @@ impl ToolHandler for ListProxiesTool {
         }));
 
         // Insert command and add to queue
-        let command = db::command::insert(&context.pg_pool, &command)
+        let command = command_queue::enqueue(&context.pg_pool, command, None)
             .await
-            .map_err(|e| format!("Failed to create command: {}", e))?;
-
-        db::command::add_to_queue(
-            &context.pg_pool,
-            &command.command_id,
-            &deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|error| error.to_pretty_json())?;
 
         tracing::info!(
             user_id = %context.user.id,
@@ -1,6 +1,7 @@
 use lazy_static::lazy_static;
 use prometheus::{
-    register_counter_vec, register_gauge, register_histogram_vec, CounterVec, Gauge, HistogramVec,
+    register_counter_vec, register_gauge, register_gauge_vec, register_histogram_vec, CounterVec,
+    Gauge, GaugeVec, HistogramVec,
 };
 
 lazy_static! {
@@ -63,6 +64,56 @@ lazy_static! {
         "Number of currently active agents"
     )
     .expect("Failed to register active_agents");
+
+    // ── Container Resource Gauges (latest agent sample) ─────────
+    pub static ref CONTAINER_CPU_PERCENT: GaugeVec = register_gauge_vec!(
+        "stacker_container_cpu_percent",
+        "Container CPU usage in percent of one core",
+        &["deployment_hash", "app_code"]
+    )
+    .expect("Failed to register stacker_container_cpu_percent");
+
+    pub static ref CONTAINER_MEMORY_BYTES: GaugeVec = register_gauge_vec!(
+        "stacker_container_memory_bytes",
+        "Container memory usage in bytes",
+        &["deployment_hash", "app_code"]
+    )
+    .expect("Failed to register stacker_container_memory_bytes");
+
+    pub static ref CONTAINER_MEMORY_LIMIT_BYTES: GaugeVec = register_gauge_vec!(
+        "stacker_container_memory_limit_bytes",
+        "Container memory limit in bytes",
+        &["deployment_hash", "app_code"]
+    )
+    .expect("Failed to register stacker_container_memory_limit_bytes");
+
+    pub static ref CONTAINER_NETWORK_RX_BYTES: GaugeVec = register_gauge_vec!(
+        "stacker_container_network_receive_bytes",
+        "Bytes received by the container since it started",
+        &["deployment_hash", "app_code"]
+    )
+    .expect("Failed to register stacker_container_network_receive_bytes");
+
+    pub static ref CONTAINER_NETWORK_TX_BYTES: GaugeVec = register_gauge_vec!(
+        "stacker_container_network_transmit_bytes",
+        "Bytes sent by the container since it started",
+        &["deployment_hash", "app_code"]
+    )
+    .expect("Failed to register stacker_container_network_transmit_bytes");
+
+    pub static ref CONTAINER_BLOCK_READ_BYTES: GaugeVec = register_gauge_vec!(
+        "stacker_container_block_read_bytes",
+        "Bytes read from block devices since the container started",
+        &["deployment_hash", "app_code"]
+    )
+    .expect("Failed to register stacker_container_block_read_bytes");
+
+    pub static ref CONTAINER_BLOCK_WRITE_BYTES: GaugeVec = register_gauge_vec!(
+        "stacker_container_block_write_bytes",
+        "Bytes written to block devices since the container started",
+        &["deployment_hash", "app_code"]
+    )
+    .expect("Failed to register stacker_container_block_write_bytes");
 }
 
 /// Initialize all metrics (forces lazy_static registration).
@@ -75,6 +126,13 @@ pub fn init() {
     lazy_static::initialize(&DAG_STEPS_TOTAL);
     lazy_static::initialize(&ACTIVE_PIPE_INSTANCES);
     lazy_static::initialize(&ACTIVE_AGENTS);
+    lazy_static::initialize(&CONTAINER_CPU_PERCENT);
+    lazy_static::initialize(&CONTAINER_MEMORY_BYTES);
+    lazy_static::initialize(&CONTAINER_MEMORY_LIMIT_BYTES);
+    lazy_static::initialize(&CONTAINER_NETWORK_RX_BYTES);
+    lazy_static::initialize(&CONTAINER_NETWORK_TX_BYTES);
+    lazy_static::initialize(&CONTAINER_BLOCK_READ_BYTES);
+    lazy_static::initialize(&CONTAINER_BLOCK_WRITE_BYTES);
 
     // Pre-initialize CounterVec label combinations so they appear in /metrics output
     // even before first use (Prometheus best practice: expose all known label sets).
@@ -1,6 +1,8 @@
+use crate::configuration::{AgentIdentitySettings, AgentMtlsMode};
 use crate::helpers::{AgentPgPool, VaultClient};
 use crate::middleware::authentication::get_header;
-use crate::models;
+use crate::services::agent_identity::{accept_rotated_token, client_certificate_fingerprint};
+use crate::{db, models};
 use actix_web::{dev::ServiceRequest, web, HttpMessage};
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -12,7 +14,7 @@ async fn fetch_agent_by_id(db_pool: &PgPool, agent_id: Uuid) -> Result<models::A
 
     sqlx::query_as::<_, models::Agent>(
         r#"
-        SELECT id, deployment_hash, capabilities, version, system_info,
+        SELECT id, deployment_hash, capabilities, capability_manifest, version, system_info,
                last_heartbeat, status, created_at, updated_at
         FROM agents
         WHERE id = $1
@@ -61,7 +63,37 @@ async fn log_audit(
     }
 }
 
-#[tracing::instrument(name = "Authenticate agent via X-Agent-Id and Bearer token")]
+/// Fingerprint of the client certificate the proxy verified, if any. The
+/// certificate headers are plain request headers, so they only count on
+/// connections from a configured trusted proxy.
+fn verified_certificate_fingerprint(
+    req: &ServiceRequest,
+    identity: &AgentIdentitySettings,
+) -> Option<String> {
+    let peer = req.peer_addr()?.ip();
+    if !identity.is_trusted_proxy(peer) {
+        if req
+            .headers()
+            .contains_key(identity.client_cert_header.as_str())
+        {
+            tracing::warn!(%peer, "Ignoring client certificate header from untrusted peer");
+        }
+        return None;
+    }
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    if header(identity.client_verify_header.as_str()) != Some("SUCCESS") {
+        return None;
+    }
+    header(identity.client_cert_header.as_str()).and_then(client_certificate_fingerprint)
+}
+
+#[tracing::instrument(
+    name = "Authenticate agent via X-Agent-Id and Bearer token or client certificate"
+)]
 pub async fn try_agent(req: &mut ServiceRequest) -> Result<bool, String> {
     // Check for X-Agent-Id header
     let agent_id_header = get_header::<String>(req, "x-agent-id")?;
@@ -73,18 +105,28 @@ pub async fn try_agent(req: &mut ServiceRequest) -> Result<bool, String> {
     let agent_id =
         Uuid::parse_str(&agent_id_str).map_err(|_| "Invalid agent ID format".to_string())?;
 
-    // Check for Authorization header
-    let auth_header = get_header::<String>(req, "authorization")?;
-    if auth_header.is_none() {
+    let bearer_token = match get_header::<String>(req, "authorization")? {
+        Some(header) => Some(
+            header
+                .strip_prefix("Bearer ")
+                .ok_or("Invalid Authorization header format")?
+                .to_string(),
+        ),
+        None => None,
+    };
+
+    let settings = req
+        .app_data::<web::Data<crate::configuration::Settings>>()
+        .cloned();
+    let mtls_mode = settings.as_ref().map_or(AgentMtlsMode::Off, |settings| {
+        settings.agent_identity.mtls_mode
+    });
+
+    // Without mTLS the bearer token is the only credential
+    if bearer_token.is_none() && mtls_mode == AgentMtlsMode::Off {
         return Err("Authorization header required for agent".to_string());
     }
 
-    let bearer_token = auth_header
-        .unwrap()
-        .strip_prefix("Bearer ")
-        .ok_or("Invalid Authorization header format")?
-        .to_string();
-
     // Get agent database pool (separate pool for agent operations)
     let agent_pool = req
         .app_data::<web::Data<AgentPgPool>>()
@@ -94,58 +136,37 @@ pub async fn try_agent(req: &mut ServiceRequest) -> Result<bool, String> {
     // Fetch agent from database
     let agent = fetch_agent_by_id(db_pool, agent_id).await?;
 
-    // Get Vault client and settings from app data
-    let vault_client = req
-        .app_data::<web::Data<VaultClient>>()
-        .ok_or("Vault client not found")?;
-    let settings = req
-        .app_data::<web::Data<crate::configuration::Settings>>()
-        .ok_or("Settings not found")?;
-
-    // Fetch token from Vault; in test environments, allow fallback when Vault is unreachable
-    let stored_token = match vault_client.fetch_agent_token(&agent.deployment_hash).await {
-        Ok(tok) => tok,
-        Err(e) => {
-            let addr = &settings.vault.address;
-            // Fallback for local test setups without Vault
-            if addr.contains("127.0.0.1") || addr.contains("localhost") {
-                actix_web::rt::spawn(log_audit(
-                    agent_pool.inner().clone(),
-                    Some(agent_id),
-                    Some(agent.deployment_hash.clone()),
-                    "agent.auth_warning".to_string(),
-                    "vault_unreachable_test_mode".to_string(),
-                    serde_json::json!({"error": e}),
-                ));
-                bearer_token.clone()
-            } else {
-                actix_web::rt::spawn(log_audit(
-                    agent_pool.inner().clone(),
-                    Some(agent_id),
-                    Some(agent.deployment_hash.clone()),
-                    "agent.auth_failure".to_string(),
-                    "token_not_found".to_string(),
-                    serde_json::json!({"error": e}),
-                ));
-                return Err(format!("Token not found in Vault: {}", e));
+    let settings = settings.ok_or("Settings not found")?;
+    let certified = match mtls_mode {
+        AgentMtlsMode::Off => false,
+        _ => match verified_certificate_fingerprint(req, &settings.agent_identity) {
+            Some(fingerprint) => {
+                db::agent_identity::fetch_active_certificate(db_pool, agent_id, &fingerprint)
+                    .await?
+                    .is_some()
             }
-        }
+            None => false,
+        },
     };
 
-    // Compare tokens
-    if bearer_token != stored_token {
-        actix_web::rt::spawn(log_audit(
-            agent_pool.inner().clone(),
-            Some(agent_id),
-            Some(agent.deployment_hash.clone()),
-            "agent.auth_failure".to_string(),
-            "token_mismatch".to_string(),
-            serde_json::json!({}),
-        ));
-        return Err("Invalid agent token".to_string());
-    }
-
-    // Token matches, set up access control
+    if !certified {
+        if mtls_mode == AgentMtlsMode::Required {
+            actix_web::rt::spawn(log_audit(
+                agent_pool.inner().clone(),
+                Some(agent_id),
+                Some(agent.deployment_hash.clone()),
+                "agent.auth_failure".to_string(),
+                "certificate_required".to_string(),
+                serde_json::json!({}),
+            ));
+            return Err("Valid client certificate required for agent".to_string());
+        }
+        let bearer_token =
+            bearer_token.ok_or_else(|| "Authorization header required for agent".to_string())?;
+        verify_token(req, agent_pool, &settings, &agent, bearer_token).await?;
+    }
+
+    // Token or certificate verified, set up access control
     let acl_vals = actix_casbin_auth::CasbinVals {
         subject: "agent".to_string(),
         domain: None,
@@ -186,7 +207,7 @@ pub async fn try_agent(req: &mut ServiceRequest) -> Result<bool, String> {
         Some(agent.deployment_hash.clone()),
         "agent.auth_success".to_string(),
         "success".to_string(),
-        serde_json::json!({}),
+        serde_json::json!({ "method": if certified { "certificate" } else { "token" } }),
     ));
 
     tracing::debug!(
@@ -198,9 +219,88 @@ pub async fn try_agent(req: &mut ServiceRequest) -> Result<bool, String> {
     Ok(true)
 }
 
+/// Compare the bearer token with the one in Vault, accepting the offered
+/// and replaced tokens while a rotation is in progress.
+async fn verify_token(
+    req: &ServiceRequest,
+    agent_pool: &web::Data<AgentPgPool>,
+    settings: &crate::configuration::Settings,
+    agent: &models::Agent,
+    bearer_token: String,
+) -> Result<(), String> {
+    let db_pool: &PgPool = agent_pool.get_ref().as_ref();
+    let agent_id = agent.id;
+
+    // Get Vault client from app data
+    let vault_client = req
+        .app_data::<web::Data<VaultClient>>()
+        .ok_or("Vault client not found")?;
+
+    // Fetch token from Vault; in test environments, allow fallback when Vault is unreachable
+    let stored_token = match vault_client.fetch_agent_token(&agent.deployment_hash).await {
+        Ok(tok) => tok,
+        Err(e) => {
+            let addr = &settings.vault.address;
+            // Fallback for local test setups without Vault
+            if addr.contains("127.0.0.1") || addr.contains("localhost") {
+                actix_web::rt::spawn(log_audit(
+                    agent_pool.inner().clone(),
+                    Some(agent_id),
+                    Some(agent.deployment_hash.clone()),
+                    "agent.auth_warning".to_string(),
+                    "vault_unreachable_test_mode".to_string(),
+                    serde_json::json!({"error": e}),
+                ));
+                bearer_token.clone()
+            } else {
+                actix_web::rt::spawn(log_audit(
+                    agent_pool.inner().clone(),
+                    Some(agent_id),
+                    Some(agent.deployment_hash.clone()),
+                    "agent.auth_failure".to_string(),
+                    "token_not_found".to_string(),
+                    serde_json::json!({"error": e}),
+                ));
+                return Err(format!("Token not found in Vault: {}", e));
+            }
+        }
+    };
+
+    // Compare tokens
+    if bearer_token != stored_token {
+        let rotated = accept_rotated_token(
+            db_pool,
+            vault_client,
+            &settings.agent_identity,
+            agent,
+            &bearer_token,
+            &stored_token,
+        )
+        .await
+        .unwrap_or_else(|err| {
+            tracing::error!("Failed to check rotated agent token: {}", err);
+            false
+        });
+        if !rotated {
+            actix_web::rt::spawn(log_audit(
+                agent_pool.inner().clone(),
+                Some(agent_id),
+                Some(agent.deployment_hash.clone()),
+                "agent.auth_failure".to_string(),
+                "token_mismatch".to_string(),
+                serde_json::json!({}),
+            ));
+            return Err("Invalid agent token".to_string());
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::try_agent;
+    use super::{try_agent, verified_certificate_fingerprint};
+    use crate::configuration::AgentIdentitySettings;
     use actix_web::test::TestRequest;
 
     #[actix_web::test]
@@ -243,4 +343,68 @@ mod tests {
             Err("Invalid Authorization header format".to_string())
         );
     }
+
+    #[actix_web::test]
+    async fn client_certificate_is_ignored_unless_proxy_verified_it() {
+        let identity = AgentIdentitySettings {
+            trusted_proxies: vec!["10.0.0.0/24".to_string()],
+            ..Default::default()
+        };
+        let pem = "-----BEGIN CERTIFICATE-----\nZGVy\n-----END CERTIFICATE-----";
+        let proxy = "10.0.0.7:41000".parse().unwrap();
+
+        let unverified = TestRequest::default()
+            .peer_addr(proxy)
+            .insert_header(("x-client-cert", urlencoding::encode(pem).into_owned()))
+            .insert_header(("x-client-verify", "FAILED:unknown ca"))
+            .to_srv_request();
+        assert_eq!(
+            verified_certificate_fingerprint(&unverified, &identity),
+            None
+        );
+
+        let verified = TestRequest::default()
+            .peer_addr(proxy)
+            .insert_header(("x-client-cert", urlencoding::encode(pem).into_owned()))
+            .insert_header(("x-client-verify", "SUCCESS"))
+            .to_srv_request();
+        assert!(verified_certificate_fingerprint(&verified, &identity).is_some());
+    }
+
+    #[actix_web::test]
+    async fn forged_certificate_headers_from_untrusted_peers_are_rejected() {
+        let pem = "-----BEGIN CERTIFICATE-----\nZGVy\n-----END CERTIFICATE-----";
+        let forged = |peer: &str| {
+            TestRequest::default()
+                .peer_addr(peer.parse().unwrap())
+                .insert_header(("x-client-cert", urlencoding::encode(pem).into_owned()))
+                .insert_header(("x-client-verify", "SUCCESS"))
+                .to_srv_request()
+        };
+
+        // Direct connection to the backend, bypassing the proxy.
+        let identity = AgentIdentitySettings {
+            trusted_proxies: vec!["10.0.0.5".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            verified_certificate_fingerprint(&forged("203.0.113.9:5000"), &identity),
+            None
+        );
+        // No trusted proxy configured: certificates are never accepted.
+        assert_eq!(
+            verified_certificate_fingerprint(
+                &forged("10.0.0.5:5000"),
+                &AgentIdentitySettings::default()
+            ),
+            None
+        );
+        assert!(verified_certificate_fingerprint(&forged("10.0.0.5:5000"), &identity).is_some());
+        // Request without a peer address (e.g. a unix socket) is not trusted.
+        let no_peer = TestRequest::default()
+            .insert_header(("x-client-cert", urlencoding::encode(pem).into_owned()))
+            .insert_header(("x-client-verify", "SUCCESS"))
+            .to_srv_request();
+        assert_eq!(verified_certificate_fingerprint(&no_peer, &identity), None);
+    }
 }
@@ -8,6 +8,8 @@ pub struct Agent {
     pub id: Uuid,
     pub deployment_hash: String,
     pub capabilities: Option<Value>,
+    /// Structured capabilities; `None` for agents that predate manifests.
+    pub capability_manifest: Option<Value>,
     pub version: Option<String>,
     pub system_info: Option<Value>,
     pub last_heartbeat: Option<DateTime<Utc>>,
@@ -22,6 +24,7 @@ impl Agent {
             id: Uuid::new_v4(),
             deployment_hash,
             capabilities: Some(serde_json::json!([])),
+            capability_manifest: None,
             version: None,
             system_info: Some(serde_json::json!({})),
             last_heartbeat: None,
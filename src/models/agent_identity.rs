@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Token rotation state of one agent. Tokens are stored as SHA-256 hashes;
+/// the current token lives in Vault.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AgentTokenRotation {
+    pub agent_id: Uuid,
+    pub rotated_at: Option<DateTime<Utc>>,
+    /// Offered over the poll channel, not yet used by the agent.
+    pub pending_token_hash: Option<String>,
+    pub pending_expires_at: Option<DateTime<Utc>>,
+    /// Replaced token, accepted until `previous_expires_at`.
+    pub previous_token_hash: Option<String>,
+    pub previous_expires_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A client certificate signed for an agent in mTLS mode.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AgentCertificate {
+    pub id: i32,
+    pub agent_id: Uuid,
+    pub serial_number: String,
+    /// SHA-256 of the DER certificate, lowercase hex.
+    pub fingerprint_sha256: String,
+    pub not_after: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -49,6 +49,17 @@ pub enum CommandPriority {
 }
 
 impl CommandPriority {
+    /// Parse a priority name, case-insensitively.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "low" => Some(CommandPriority::Low),
+            "normal" => Some(CommandPriority::Normal),
+            "high" => Some(CommandPriority::High),
+            "critical" => Some(CommandPriority::Critical),
+            _ => None,
+        }
+    }
+
     /// Convert priority to integer for queue ordering
     pub fn to_int(&self) -> i32 {
         match self {
@@ -140,6 +151,12 @@ impl Command {
         self
     }
 
+    /// Batch membership recorded under `metadata.batch`, if any.
+    pub fn batch(&self) -> Option<CommandBatchRef> {
+        let value = self.metadata.as_ref()?.get(BATCH_METADATA_KEY)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
     /// Mark command as sent
     pub fn mark_sent(mut self) -> Self {
         self.status = CommandStatus::Sent.to_string();
@@ -176,6 +193,97 @@ impl Command {
     }
 }
 
+/// `metadata` key holding a command's [`CommandBatchRef`].
+pub const BATCH_METADATA_KEY: &str = "batch";
+
+/// Place of a command in a batch. Members of a batch are released to the
+/// agent once every step they depend on has completed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandBatchRef {
+    pub id: String,
+    pub step: String,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Deploy lease the batch was submitted under; held steps are released
+    /// only while it is still the active lease.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease_id: Option<String>,
+}
+
+/// Status of a batch as one unit, derived from its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandBatchStatus {
+    /// Nothing has been picked up by the agent yet.
+    Queued,
+    Running,
+    Completed,
+    /// A member failed; its dependents were cancelled.
+    Failed,
+    Cancelled,
+}
+
+impl CommandBatchStatus {
+    pub fn from_statuses<'a>(statuses: impl IntoIterator<Item = &'a str>) -> Self {
+        let statuses: Vec<&str> = statuses.into_iter().collect();
+        let is_terminal = |status: &&str| matches!(*status, "completed" | "failed" | "cancelled");
+        if statuses.iter().all(|status| *status == "queued") {
+            Self::Queued
+        } else if !statuses.iter().all(is_terminal) {
+            Self::Running
+        } else if statuses.contains(&"failed") {
+            Self::Failed
+        } else if statuses.contains(&"cancelled") {
+            Self::Cancelled
+        } else {
+            Self::Completed
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+impl std::fmt::Display for CommandBatchStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandBatchStatus::Queued => write!(f, "queued"),
+            CommandBatchStatus::Running => write!(f, "running"),
+            CommandBatchStatus::Completed => write!(f, "completed"),
+            CommandBatchStatus::Failed => write!(f, "failed"),
+            CommandBatchStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// A command batch with its members in step order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandBatch {
+    pub batch_id: String,
+    pub deployment_hash: String,
+    pub status: CommandBatchStatus,
+    pub commands: Vec<Command>,
+}
+
+impl CommandBatch {
+    pub fn from_commands(
+        batch_id: String,
+        deployment_hash: String,
+        commands: Vec<Command>,
+    ) -> Self {
+        let status = CommandBatchStatus::from_statuses(
+            commands.iter().map(|command| command.status.as_str()),
+        );
+        Self {
+            batch_id,
+            deployment_hash,
+            status,
+            commands,
+        }
+    }
+}
+
 /// Command result payload from agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandResult {
@@ -259,6 +367,38 @@ mod tests {
         assert!(CommandPriority::High.to_int() < CommandPriority::Critical.to_int());
     }
 
+    #[test]
+    fn test_batch_status_from_member_statuses() {
+        let status = |items: &[&str]| CommandBatchStatus::from_statuses(items.iter().copied());
+        assert_eq!(status(&["queued", "queued"]), CommandBatchStatus::Queued);
+        assert_eq!(
+            status(&["completed", "queued"]),
+            CommandBatchStatus::Running
+        );
+        assert_eq!(
+            status(&["completed", "completed"]),
+            CommandBatchStatus::Completed
+        );
+        assert_eq!(status(&["failed", "cancelled"]), CommandBatchStatus::Failed);
+        assert_eq!(
+            status(&["completed", "cancelled"]),
+            CommandBatchStatus::Cancelled
+        );
+
+        let cmd = Command::new(
+            "c".to_string(),
+            "h".to_string(),
+            "t".to_string(),
+            "u".to_string(),
+        )
+        .with_metadata(serde_json::json!({
+            "batch": {"id": "batch_1", "step": "proxy", "depends_on": ["deploy"]}
+        }));
+        let batch = cmd.batch().unwrap();
+        assert_eq!(batch.step, "proxy");
+        assert_eq!(batch.depends_on, vec!["deploy".to_string()]);
+    }
+
     // Command builder tests
     #[test]
     fn test_command_new_defaults() {
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// An agent command queued on a cron schedule for one deployment.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CommandSchedule {
+    pub id: i32,
+    pub schedule_id: String,
+    pub deployment_hash: String,
+    pub name: Option<String>,
+    /// Five-field cron expression, evaluated in UTC.
+    pub cron: String,
+    pub command_type: String,
+    pub parameters: Option<JsonValue>,
+    pub priority: String,
+    pub timeout_seconds: Option<i32>,
+    /// `skip` or `run_once`; see [`MissedRunPolicy`].
+    pub missed_run_policy: String,
+    pub enabled: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_command_id: Option<String>,
+}
+
+/// What the scheduler does with a run it noticed late, for example after the
+/// server was down over the scheduled time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// Drop the missed run and wait for the next match.
+    #[default]
+    Skip,
+    /// Queue one catch-up command, however many runs were missed.
+    RunOnce,
+}
+
+impl MissedRunPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "skip" => Some(Self::Skip),
+            "run_once" => Some(Self::RunOnce),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::RunOnce => "run_once",
+        }
+    }
+}
+
+impl std::fmt::Display for MissedRunPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
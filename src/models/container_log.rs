@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One retained log line of a deployment container.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ContainerLogLine {
+    pub deployment_hash: String,
+    pub app_code: String,
+    pub container: Option<String>,
+    pub logged_at: DateTime<Utc>,
+    /// `stdout` or `stderr`.
+    pub stream: String,
+    pub message: String,
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One stored resource sample of a deployment container.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ContainerMetricSample {
+    pub deployment_hash: String,
+    pub app_code: String,
+    pub container: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+    pub cpu_percent: f64,
+    pub memory_bytes: i64,
+    pub memory_limit_bytes: Option<i64>,
+    /// Network and block I/O counters since the container started.
+    pub net_rx_bytes: i64,
+    pub net_tx_bytes: i64,
+    pub block_read_bytes: i64,
+    pub block_write_bytes: i64,
+}
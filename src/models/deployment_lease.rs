@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Deploy mutex for one deployment. Held by `stacker deploy` and the
+/// rollback commands so two clients cannot interleave commands in the agent
+/// queue.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeploymentLease {
+    pub deployment_hash: String,
+    pub lease_id: String,
+    /// Human-readable holder, e.g. `alice@example.com (ci-runner-3)`.
+    pub owner: String,
+    pub user_id: String,
+    pub reason: String,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl DeploymentLease {
+    pub fn is_active(&self) -> bool {
+        self.expires_at > Utc::now()
+    }
+}
+
+/// A lease that was broken with `--force`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeploymentLeaseAudit {
+    pub id: i32,
+    pub deployment_hash: String,
+    pub action: String,
+    pub actor_user_id: String,
+    pub actor_owner: String,
+    pub previous_owner: String,
+    pub previous_reason: String,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
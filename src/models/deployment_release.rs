@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::JsonValue;
+
+/// Immutable record of one successful deploy: what was rendered and shipped,
+/// so it can be re-applied later by `stacker rollback <release>`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeploymentRelease {
+    pub id: i32,
+    pub deployment_hash: String,
+    /// Monotonic per deployment, starting at 1.
+    pub version: i32,
+    pub compose_content: String,
+    /// Compose service → SHA-256 of its resolved environment (values never stored).
+    pub env_hashes: JsonValue,
+    /// Compose service → image reference, digest-pinned when built by `stacker build`.
+    pub image_digests: JsonValue,
+    /// stacker.yml snapshot with secret-like values redacted.
+    pub stacker_yml: String,
+    pub bundle_hash: String,
+    /// Release this one re-applied, when it was recorded by a rollback.
+    pub rollback_of: Option<i32>,
+    /// The completed `deploy_app` command that shipped this release. `None`
+    /// for full installs, which run outside the agent queue, and rollbacks.
+    pub command_id: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,5 +1,6 @@
 mod agent;
 pub mod agent_audit_log;
+mod agent_identity;
 pub mod agent_protocol;
 mod agreement;
 pub mod cdc;
@@ -7,10 +8,16 @@ mod chat;
 mod client;
 mod cloud;
 mod command;
+mod command_schedule;
+mod container_log;
+mod container_metric;
 pub mod dag;
 pub(crate) mod deployment;
+mod deployment_lease;
+mod deployment_release;
 pub mod marketplace;
 pub mod pipe;
+mod preview_environment;
 mod product;
 pub mod project;
 pub mod project_app;
@@ -25,15 +32,22 @@ pub mod user;
 
 pub use agent::*;
 pub use agent_audit_log::AgentAuditLog;
+pub use agent_identity::*;
 pub use agreement::*;
 pub use chat::*;
 pub use client::*;
 pub use cloud::*;
 pub use command::*;
+pub use command_schedule::*;
+pub use container_log::*;
+pub use container_metric::*;
 pub use dag::*;
 pub use deployment::*;
+pub use deployment_lease::*;
+pub use deployment_release::*;
 pub use marketplace::*;
 pub use pipe::*;
+pub use preview_environment::*;
 pub use product::*;
 pub use project::*;
 pub use project_app::*;
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A branch deployed beside the main stack on a shared server, under its own
+/// compose project and `<slug>.<base-domain>` subdomain.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PreviewEnvironment {
+    pub id: i32,
+    pub deployment_hash: String,
+    /// DNS-safe form of the branch name; unique per deployment.
+    pub slug: String,
+    pub branch: String,
+    pub app_code: String,
+    pub compose_project: String,
+    pub domain: String,
+    /// Container port the proxy forwards the preview domain to.
+    pub forward_port: i32,
+    /// `active`, `expired` (torn down by the reaper) or `removed`.
+    pub status: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PreviewEnvironment {
+    pub const ACTIVE: &'static str = "active";
+    pub const EXPIRED: &'static str = "expired";
+    pub const REMOVED: &'static str = "removed";
+}
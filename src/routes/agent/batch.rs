@@ -0,0 +1,150 @@
+use crate::configuration::Settings;
+use crate::db;
+use crate::helpers::{AgentPgPool, JsonResponse};
+use crate::models::{CommandBatch, CommandBatchRef, User};
+use crate::routes::legacy_installations::resolve_owned_deployment_by_hash;
+use crate::services::command_batch::{enqueue_batch, validate_batch_steps, with_batch_ref};
+use crate::services::command_queue;
+use actix_web::{get, post, web, Responder, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::enqueue::{prepare_command, project_id_from_owned_deployment, EnqueueRequest};
+
+#[derive(Debug, Deserialize)]
+pub struct BatchStepRequest {
+    /// Name of the step within the batch, referenced by `depends_on`.
+    pub step: String,
+    pub command_type: String,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub parameters: Option<serde_json::Value>,
+    #[serde(default)]
+    pub timeout_seconds: Option<i32>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnqueueBatchRequest {
+    pub deployment_hash: String,
+    pub commands: Vec<BatchStepRequest>,
+    /// The caller's deploy lease, when it holds one.
+    #[serde(default)]
+    pub lease_id: Option<String>,
+}
+
+#[tracing::instrument(name = "Agent enqueue command batch", skip_all)]
+#[post("/commands/batch")]
+pub async fn enqueue_batch_handler(
+    user: web::ReqData<Arc<User>>,
+    payload: web::Json<EnqueueBatchRequest>,
+    agent_pool: web::Data<AgentPgPool>,
+    settings: web::Data<Settings>,
+) -> Result<impl Responder> {
+    let payload = payload.into_inner();
+    if payload.deployment_hash.trim().is_empty() {
+        return Err(JsonResponse::<()>::build().bad_request("deployment_hash is required"));
+    }
+    let steps: Vec<(String, Vec<String>)> = payload
+        .commands
+        .iter()
+        .map(|step| (step.step.clone(), step.depends_on.clone()))
+        .collect();
+    validate_batch_steps(&steps).map_err(|err| JsonResponse::<()>::build().bad_request(err))?;
+
+    let owned_deployment = resolve_owned_deployment_by_hash(
+        agent_pool.as_ref(),
+        settings.get_ref(),
+        user.as_ref(),
+        &payload.deployment_hash,
+    )
+    .await?;
+    let project_id = project_id_from_owned_deployment(&owned_deployment);
+
+    // Validate every step before anything is stored, so a rejected step
+    // leaves no partial batch behind.
+    let batch_id = format!("batch_{}", uuid::Uuid::new_v4());
+    let mut commands = Vec::with_capacity(payload.commands.len());
+    for step in payload.commands {
+        let request = EnqueueRequest {
+            deployment_hash: payload.deployment_hash.clone(),
+            command_type: step.command_type,
+            priority: step.priority,
+            parameters: step.parameters,
+            timeout_seconds: step.timeout_seconds,
+            lease_id: payload.lease_id.clone(),
+        };
+        let command = prepare_command(
+            user.as_ref(),
+            &request,
+            agent_pool.get_ref(),
+            settings.get_ref(),
+            project_id,
+        )
+        .await?;
+        let batch_ref = CommandBatchRef {
+            id: batch_id.clone(),
+            step: step.step,
+            depends_on: step.depends_on,
+            lease_id: payload.lease_id.clone(),
+        };
+        commands.push(with_batch_ref(command, &batch_ref));
+    }
+
+    let saved = enqueue_batch(agent_pool.as_ref(), commands, payload.lease_id.as_deref())
+        .await
+        .map_err(|error| {
+            tracing::error!("Failed to enqueue command batch: {}", error.message);
+            command_queue::api_error(error)
+        })?;
+
+    tracing::info!(
+        batch_id = %batch_id,
+        deployment_hash = %payload.deployment_hash,
+        commands = saved.len(),
+        "Command batch enqueued"
+    );
+
+    Ok(JsonResponse::build()
+        .set_item(Some(CommandBatch::from_commands(
+            batch_id,
+            payload.deployment_hash,
+            saved,
+        )))
+        .created("Command batch enqueued"))
+}
+
+#[tracing::instrument(name = "Get command batch", skip_all)]
+#[get("/commands/batch/{deployment_hash}/{batch_id}")]
+pub async fn get_batch_handler(
+    user: web::ReqData<Arc<User>>,
+    path: web::Path<(String, String)>,
+    agent_pool: web::Data<AgentPgPool>,
+    settings: web::Data<Settings>,
+) -> Result<impl Responder> {
+    let (deployment_hash, batch_id) = path.into_inner();
+    resolve_owned_deployment_by_hash(
+        agent_pool.as_ref(),
+        settings.get_ref(),
+        user.as_ref(),
+        &deployment_hash,
+    )
+    .await?;
+
+    let commands = db::command::fetch_by_batch(agent_pool.as_ref(), &deployment_hash, &batch_id)
+        .await
+        .map_err(|err| JsonResponse::<()>::build().internal_server_error(err))?;
+    if commands.is_empty() {
+        return Err(JsonResponse::<()>::build().not_found("Command batch not found"));
+    }
+
+    Ok(JsonResponse::build()
+        .set_item(Some(CommandBatch::from_commands(
+            batch_id,
+            deployment_hash,
+            commands,
+        )))
+        .ok("Command batch"))
+}
@@ -0,0 +1,64 @@
+use crate::configuration::{AgentMtlsMode, Settings};
+use crate::services::agent_identity::{self, IssuedAgentCertificate};
+use crate::{db, helpers, helpers::AgentPgPool, models};
+use actix_web::{post, web, Responder, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+pub struct AgentCertificateRequest {
+    /// PEM certificate signing request.
+    pub csr: String,
+}
+
+/// Renew the agent's mTLS client certificate before it expires.
+#[tracing::instrument(name = "Agent renew client certificate", skip_all)]
+#[post("/certificate")]
+pub async fn agent_certificate_handler(
+    agent: web::ReqData<Arc<models::Agent>>,
+    payload: web::Json<AgentCertificateRequest>,
+    agent_pool: web::Data<AgentPgPool>,
+    vault_client: web::Data<helpers::VaultClient>,
+    settings: web::Data<Settings>,
+) -> Result<impl Responder> {
+    let identity = &settings.agent_identity;
+    if identity.mtls_mode == AgentMtlsMode::Off {
+        return Err(helpers::JsonResponse::bad_request(
+            "Agent mTLS is not enabled on this server",
+        ));
+    }
+    if payload.csr.trim().is_empty() {
+        return Err(helpers::JsonResponse::bad_request("csr is required"));
+    }
+
+    let certificate = agent_identity::issue_certificate(
+        agent_pool.as_ref(),
+        vault_client.as_ref(),
+        identity,
+        &agent,
+        &payload.csr,
+    )
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to issue agent certificate: {}", err);
+        helpers::JsonResponse::internal_server_error("Failed to issue client certificate")
+    })?;
+
+    let audit_log = models::AuditLog::new(
+        Some(agent.id),
+        Some(agent.deployment_hash.clone()),
+        "agent.certificate_issued".to_string(),
+        Some("success".to_string()),
+    )
+    .with_details(serde_json::json!({
+        "serial_number": certificate.serial_number,
+        "expires_at": certificate.expires_at,
+    }));
+    if let Err(err) = db::agent::log_audit(agent_pool.as_ref(), audit_log).await {
+        tracing::warn!("Failed to log certificate audit: {:?}", err);
+    }
+
+    Ok(helpers::JsonResponse::<IssuedAgentCertificate>::build()
+        .set_item(certificate)
+        .ok("Certificate issued"))
+}
@@ -2,18 +2,21 @@ use crate::configuration::Settings;
 use crate::db;
 use crate::forms::status_panel;
 use crate::helpers::{
-    extract_capabilities, has_capability, has_capability_value, AgentPgPool, JsonResponse,
-    NPM_CREDENTIAL_SOURCE_KEY,
+    extract_capabilities, has_capability, has_capability_value, parse_manifest, AgentPgPool,
+    JsonResponse, NPM_CREDENTIAL_SOURCE_KEY,
 };
 use crate::models::{Command, CommandPriority, User};
 use crate::routes::command::enrich_deploy_app_with_compose;
 use crate::routes::legacy_installations::{resolve_owned_deployment_by_hash, OwnedDeployment};
+use crate::services::command_queue;
 use actix_web::{post, web, Responder, Result};
 use serde::Deserialize;
 use std::sync::Arc;
 
 const CONFIGURE_PROXY_CAPABILITY_MODE_ENV: &str = "STACKER_CONFIGURE_PROXY_CAPABILITY_MODE";
 const PIPE_COMMAND_TYPES: &[&str] = &["activate_pipe", "deactivate_pipe", "trigger_pipe"];
+/// Commands the server queues itself as part of a session it manages.
+const SERVER_ONLY_COMMAND_TYPES: &[&str] = &["open_shell"];
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum ConfigureProxyCapabilityMode {
@@ -56,6 +59,9 @@ pub struct EnqueueRequest {
     pub parameters: Option<serde_json::Value>,
     #[serde(default)]
     pub timeout_seconds: Option<i32>,
+    /// The caller's deploy lease, when it holds one.
+    #[serde(default)]
+    pub lease_id: Option<String>,
 }
 
 #[tracing::instrument(name = "Agent enqueue command", skip_all)]
@@ -70,10 +76,6 @@ pub async fn enqueue_handler(
         return Err(JsonResponse::<()>::build().bad_request("deployment_hash is required"));
     }
 
-    if payload.command_type.trim().is_empty() {
-        return Err(JsonResponse::<()>::build().bad_request("command_type is required"));
-    }
-
     let owned_deployment = resolve_owned_deployment_by_hash(
         agent_pool.as_ref(),
         settings.get_ref(),
@@ -83,6 +85,64 @@ pub async fn enqueue_handler(
     .await?;
     let project_id = project_id_from_owned_deployment(&owned_deployment);
 
+    let command = prepare_command(
+        user.as_ref(),
+        &payload,
+        agent_pool.get_ref(),
+        settings.get_ref(),
+        project_id,
+    )
+    .await?;
+
+    // Agent will poll and pick it up
+    let saved = command_queue::enqueue(agent_pool.as_ref(), command, payload.lease_id.as_deref())
+        .await
+        .map_err(|error| {
+            tracing::error!("Failed to enqueue command: {}", error.message);
+            command_queue::api_error(error)
+        })?;
+
+    // Extract runtime for tracing
+    let runtime = saved
+        .parameters
+        .as_ref()
+        .and_then(|p| p.get("runtime"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("runc");
+
+    tracing::info!(
+        command_id = %saved.command_id,
+        deployment_hash = %saved.deployment_hash,
+        command_type = %payload.command_type,
+        runtime = %runtime,
+        "Command enqueued, agent will poll"
+    );
+
+    Ok(JsonResponse::build()
+        .set_item(Some(saved))
+        .created("Command enqueued"))
+}
+
+/// Validate a command request against the deploy lease and the agent's
+/// capabilities and build the command to queue. Shared by single commands
+/// and batch steps.
+pub(crate) async fn prepare_command(
+    user: &User,
+    payload: &EnqueueRequest,
+    agent_pool: &AgentPgPool,
+    settings: &Settings,
+    project_id: Option<i32>,
+) -> Result<Command> {
+    if payload.command_type.trim().is_empty() {
+        return Err(JsonResponse::<()>::build().bad_request("command_type is required"));
+    }
+    if SERVER_ONLY_COMMAND_TYPES.contains(&payload.command_type.as_str()) {
+        return Err(JsonResponse::<()>::build().bad_request(format!(
+            "{} cannot be enqueued directly; open a session with `stacker agent shell`",
+            payload.command_type
+        )));
+    }
+
     // Validate parameters
     let validated_parameters =
         status_panel::validate_command_parameters(&payload.command_type, &payload.parameters)
@@ -90,23 +150,25 @@ pub async fn enqueue_handler(
 
     let requires_pipes_capability = command_requires_pipes_capability(&payload.command_type);
 
-    let agent = if payload.command_type == "configure_proxy"
-        || requires_pipes_capability
-        || validated_parameters
-            .as_ref()
-            .and_then(|params| params.get("runtime"))
-            .and_then(|value| value.as_str())
-            == Some("kata")
-    {
-        db::agent::fetch_by_deployment_hash(agent_pool.as_ref(), &payload.deployment_hash)
-            .await
-            .map_err(|err| {
-                tracing::error!("Failed to fetch agent: {}", err);
-                JsonResponse::<()>::build().internal_server_error(err)
-            })?
-    } else {
-        None
-    };
+    let agent = db::agent::fetch_by_deployment_hash(agent_pool.as_ref(), &payload.deployment_hash)
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to fetch agent: {}", err);
+            JsonResponse::<()>::build().internal_server_error(err)
+        })?;
+    let manifest = agent
+        .as_ref()
+        .and_then(|agent| parse_manifest(agent.capability_manifest.clone()));
+
+    let validated_parameters = command_queue::admit(
+        agent_pool.as_ref(),
+        &payload.deployment_hash,
+        &payload.command_type,
+        validated_parameters,
+        payload.lease_id.as_deref(),
+    )
+    .await
+    .map_err(command_queue::api_error)?;
 
     // If runtime=kata requested, verify agent supports it
     if let Some(ref params) = validated_parameters {
@@ -146,7 +208,13 @@ pub async fn enqueue_handler(
 
         if !configure_proxy_requires_vault_capability(&capabilities) {
             let message = "Agent does not advertise npm_credential_source=vault. Re-link the Status Panel agent or update the installer before running configure_proxy.";
-            match ConfigureProxyCapabilityMode::from_env() {
+            // Agents with a manifest have always been able to advertise it.
+            let mode = if manifest.is_some() {
+                ConfigureProxyCapabilityMode::Enforce
+            } else {
+                ConfigureProxyCapabilityMode::from_env()
+            };
+            match mode {
                 ConfigureProxyCapabilityMode::Warn => {
                     tracing::warn!(
                         deployment_hash = %payload.deployment_hash,
@@ -189,14 +257,8 @@ pub async fn enqueue_handler(
     // Parse priority
     let priority = payload
         .priority
-        .as_ref()
-        .and_then(|p| match p.to_lowercase().as_str() {
-            "low" => Some(CommandPriority::Low),
-            "normal" => Some(CommandPriority::Normal),
-            "high" => Some(CommandPriority::High),
-            "critical" => Some(CommandPriority::Critical),
-            _ => None,
-        })
+        .as_deref()
+        .and_then(CommandPriority::from_name)
         .unwrap_or(CommandPriority::Normal);
 
     // Build command
@@ -206,7 +268,7 @@ pub async fn enqueue_handler(
         payload.command_type.clone(),
         user.id.clone(),
     )
-    .with_priority(priority.clone());
+    .with_priority(priority);
 
     if let Some(params) = &final_parameters {
         command = command.with_parameters(params.clone());
@@ -216,48 +278,10 @@ pub async fn enqueue_handler(
         command = command.with_timeout(timeout);
     }
 
-    // Insert command
-    let saved = db::command::insert(agent_pool.as_ref(), &command)
-        .await
-        .map_err(|err| {
-            tracing::error!("Failed to insert command: {}", err);
-            JsonResponse::<()>::build().internal_server_error(err)
-        })?;
-
-    // Add to queue - agent will poll and pick it up
-    db::command::add_to_queue(
-        agent_pool.as_ref(),
-        &saved.command_id,
-        &saved.deployment_hash,
-        &priority,
-    )
-    .await
-    .map_err(|err| {
-        tracing::error!("Failed to add command to queue: {}", err);
-        JsonResponse::<()>::build().internal_server_error(err)
-    })?;
-
-    // Extract runtime for tracing
-    let runtime = final_parameters
-        .as_ref()
-        .and_then(|p| p.get("runtime"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("runc");
-
-    tracing::info!(
-        command_id = %saved.command_id,
-        deployment_hash = %saved.deployment_hash,
-        command_type = %payload.command_type,
-        runtime = %runtime,
-        "Command enqueued, agent will poll"
-    );
-
-    Ok(JsonResponse::build()
-        .set_item(Some(saved))
-        .created("Command enqueued"))
+    Ok(command)
 }
 
-fn project_id_from_owned_deployment(deployment: &OwnedDeployment) -> Option<i32> {
+pub(crate) fn project_id_from_owned_deployment(deployment: &OwnedDeployment) -> Option<i32> {
     match deployment {
         OwnedDeployment::Native(deployment) => Some(deployment.project_id),
         OwnedDeployment::Legacy(_) => None,
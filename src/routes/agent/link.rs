@@ -1,4 +1,5 @@
 use crate::connectors::user_service::UserServiceConnector;
+use crate::services::agent_identity::generate_agent_token;
 use crate::{db, helpers, helpers::AgentPgPool, models};
 use actix_web::{post, web, HttpRequest, HttpResponse, Result};
 use serde::{Deserialize, Serialize};
@@ -32,19 +33,6 @@ fn normalized_status_panel_capabilities(capabilities: &[String]) -> serde_json::
     serde_json::json!(normalized)
 }
 
-/// Generate a secure random agent token (86 characters)
-fn generate_agent_token() -> String {
-    use rand::Rng;
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
-    let mut rng = rand::thread_rng();
-    (0..86)
-        .map(|_| {
-            let idx = rng.gen_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect()
-}
-
 /// POST /api/v1/agent/link
 ///
 /// Link an agent to a specific deployment using a session token (OAuth access_token).
@@ -0,0 +1,64 @@
+use crate::{
+    configuration::Settings, db, forms::status_panel, forms::status_panel::ShippedLogLine, helpers,
+    helpers::AgentPgPool, models, services::log_shipping, services::LogStore,
+};
+use actix_web::{post, web, Responder, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Most lines accepted in one push.
+const MAX_LINES_PER_PUSH: usize = 2_000;
+
+/// Container log lines streamed by the Status Panel agent.
+#[derive(Debug, Deserialize)]
+pub struct LogsPushRequest {
+    pub deployment_hash: String,
+    #[serde(default)]
+    pub lines: Vec<ShippedLogLine>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct LogsPushResponse {
+    pub accepted: u64,
+}
+
+#[tracing::instrument(name = "Agent push container logs", skip_all)]
+#[post("/logs")]
+pub async fn logs_push_handler(
+    agent: web::ReqData<Arc<models::Agent>>,
+    payload: web::Json<LogsPushRequest>,
+    agent_pool: web::Data<AgentPgPool>,
+    log_store: web::Data<Arc<dyn LogStore>>,
+    settings: web::Data<Settings>,
+) -> Result<impl Responder> {
+    if agent.deployment_hash != payload.deployment_hash {
+        return Err(helpers::JsonResponse::forbidden(
+            "Not authorized for this deployment",
+        ));
+    }
+    if payload.lines.len() > MAX_LINES_PER_PUSH {
+        return Err(helpers::JsonResponse::bad_request(format!(
+            "At most {} lines per push",
+            MAX_LINES_PER_PUSH
+        )));
+    }
+    status_panel::validate_shipped_log_lines(&payload.lines)
+        .map_err(helpers::JsonResponse::bad_request)?;
+    log_shipping::check_line_times(&settings.log_shipping, &payload.lines, chrono::Utc::now())
+        .map_err(helpers::JsonResponse::bad_request)?;
+
+    let _ = db::agent::update_heartbeat(agent_pool.as_ref(), agent.id, "online").await;
+
+    let accepted = log_store
+        .push(
+            agent_pool.as_ref(),
+            &payload.deployment_hash,
+            &payload.lines,
+        )
+        .await
+        .map_err(helpers::JsonResponse::internal_server_error)?;
+
+    Ok(helpers::JsonResponse::build()
+        .set_item(LogsPushResponse { accepted })
+        .ok("Logs accepted"))
+}
@@ -0,0 +1,63 @@
+use crate::{
+    db, forms::status_panel, forms::status_panel::ContainerStats, helpers, helpers::AgentPgPool,
+    models, services::container_metrics,
+};
+use actix_web::{post, web, Responder, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Most containers accepted in one push.
+const MAX_SAMPLES_PER_PUSH: usize = 500;
+
+/// Periodic resource samples from the Status Panel agent.
+#[derive(Debug, Deserialize)]
+pub struct MetricsPushRequest {
+    pub deployment_hash: String,
+    pub collected_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub containers: Vec<ContainerStats>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct MetricsPushResponse {
+    pub accepted: u64,
+}
+
+#[tracing::instrument(name = "Agent push container metrics", skip_all)]
+#[post("/metrics")]
+pub async fn metrics_push_handler(
+    agent: web::ReqData<Arc<models::Agent>>,
+    payload: web::Json<MetricsPushRequest>,
+    agent_pool: web::Data<AgentPgPool>,
+) -> Result<impl Responder> {
+    if agent.deployment_hash != payload.deployment_hash {
+        return Err(helpers::JsonResponse::forbidden(
+            "Not authorized for this deployment",
+        ));
+    }
+    if payload.containers.len() > MAX_SAMPLES_PER_PUSH {
+        return Err(helpers::JsonResponse::bad_request(format!(
+            "At most {} containers per push",
+            MAX_SAMPLES_PER_PUSH
+        )));
+    }
+    status_panel::validate_container_stats("metrics", &payload.containers)
+        .map_err(helpers::JsonResponse::bad_request)?;
+    container_metrics::check_collected_at(payload.collected_at, chrono::Utc::now())
+        .map_err(helpers::JsonResponse::bad_request)?;
+
+    let _ = db::agent::update_heartbeat(agent_pool.as_ref(), agent.id, "online").await;
+
+    let accepted = container_metrics::record_samples(
+        agent_pool.as_ref(),
+        &payload.deployment_hash,
+        payload.collected_at,
+        &payload.containers,
+    )
+    .await
+    .map_err(helpers::JsonResponse::internal_server_error)?;
+
+    Ok(helpers::JsonResponse::build()
+        .set_item(MetricsPushResponse { accepted })
+        .ok("Metrics accepted"))
+}
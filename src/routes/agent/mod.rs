@@ -1,19 +1,31 @@
 mod audit;
+mod batch;
+mod certificate;
 mod enqueue;
 mod link;
 mod login;
+mod logs;
+mod metrics;
 mod notifications;
 mod register;
 mod report;
+mod shell;
 mod snapshot;
+mod stream;
 mod wait;
 
 pub use audit::*;
+pub use batch::*;
+pub use certificate::*;
 pub use enqueue::*;
 pub use link::*;
 pub use login::*;
+pub use logs::*;
+pub use metrics::*;
 pub use notifications::*;
 pub use register::*;
 pub use report::*;
+pub use shell::*;
 pub use snapshot::*;
+pub use stream::*;
 pub use wait::*;
@@ -1,4 +1,8 @@
-use crate::{db, helpers, helpers::AgentPgPool, models};
+use crate::configuration::{AgentMtlsMode, Settings};
+use crate::helpers::{AgentCapabilityManifest, AgentPgPool};
+use crate::services::agent_compatibility::AGENT_PROTOCOL_VERSION;
+use crate::services::agent_identity::{self, generate_agent_token, IssuedAgentCertificate};
+use crate::{db, helpers, models};
 use actix_web::{post, web, HttpRequest, HttpResponse, Result};
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +14,34 @@ pub struct RegisterAgentRequest {
     pub capabilities: Vec<String>,
     pub system_info: serde_json::Value,
     pub agent_version: String,
+    /// Structured capabilities; older agents send the flat list only.
+    #[serde(default)]
+    pub capability_manifest: Option<AgentCapabilityManifest>,
+    /// PEM certificate signing request; signed when mTLS is enabled.
+    #[serde(default)]
+    pub csr: Option<String>,
+}
+
+impl RegisterAgentRequest {
+    /// Flat capability list including the manifest's feature flags, so
+    /// capability checks work the same for both kinds of agent.
+    fn capabilities_value(&self) -> serde_json::Value {
+        let mut capabilities = self.capabilities.clone();
+        if let Some(manifest) = &self.capability_manifest {
+            for feature in &manifest.features {
+                if !capabilities.contains(feature) {
+                    capabilities.push(feature.clone());
+                }
+            }
+        }
+        serde_json::json!(capabilities)
+    }
+
+    fn manifest_value(&self) -> Option<serde_json::Value> {
+        self.capability_manifest
+            .as_ref()
+            .and_then(|manifest| serde_json::to_value(manifest).ok())
+    }
 }
 
 #[derive(Debug, Serialize, Default)]
@@ -18,6 +50,10 @@ pub struct RegisterAgentResponse {
     pub agent_token: String,
     pub dashboard_version: String,
     pub supported_api_versions: Vec<String>,
+    /// Capability manifest protocol version the server speaks.
+    pub protocol_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<IssuedAgentCertificate>,
 }
 
 #[derive(Debug, Serialize)]
@@ -30,27 +66,20 @@ pub struct RegisterAgentResponseData {
     pub item: RegisterAgentResponse,
 }
 
-/// Generate a secure random agent token (86 characters)
-fn generate_agent_token() -> String {
-    use rand::Rng;
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
-    let mut rng = rand::thread_rng();
-    (0..86)
-        .map(|_| {
-            let idx = rng.gen_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect()
-}
-
 #[tracing::instrument(name = "Register agent", skip_all)]
 #[post("/register")]
 pub async fn register_handler(
     payload: web::Json<RegisterAgentRequest>,
     agent_pool: web::Data<AgentPgPool>,
     vault_client: web::Data<helpers::VaultClient>,
+    settings: web::Data<Settings>,
     req: HttpRequest,
 ) -> Result<HttpResponse> {
+    if settings.agent_identity.mtls_mode == AgentMtlsMode::Required && payload.csr.is_none() {
+        return Err(helpers::JsonResponse::<RegisterAgentResponse>::build()
+            .bad_request("A certificate signing request (csr) is required"));
+    }
+
     // 1. Check if agent already registered (idempotent operation)
     let existing_agent =
         db::agent::fetch_by_deployment_hash(agent_pool.as_ref(), &payload.deployment_hash)
@@ -66,7 +95,8 @@ pub async fn register_handler(
         );
 
         // Refresh agent metadata for existing registrations
-        existing.capabilities = Some(serde_json::json!(payload.capabilities));
+        existing.capabilities = Some(payload.capabilities_value());
+        existing.capability_manifest = payload.manifest_value();
         existing.version = Some(payload.agent_version.clone());
         existing.system_info = Some(payload.system_info.clone());
         let existing = db::agent::update(agent_pool.as_ref(), existing)
@@ -99,6 +129,10 @@ pub async fn register_handler(
                 new_token
             });
 
+        let client_certificate =
+            issue_client_certificate(&payload, &agent_pool, &vault_client, &settings, &existing)
+                .await?;
+
         let response = RegisterAgentResponseWrapper {
             data: RegisterAgentResponseData {
                 item: RegisterAgentResponse {
@@ -106,6 +140,8 @@ pub async fn register_handler(
                     agent_token,
                     dashboard_version: "2.0.0".to_string(),
                     supported_api_versions: vec!["1.0".to_string()],
+                    protocol_version: AGENT_PROTOCOL_VERSION,
+                    client_certificate,
                 },
             },
         };
@@ -115,7 +151,8 @@ pub async fn register_handler(
 
     // 3. Create new agent
     let mut agent = models::Agent::new(payload.deployment_hash.clone());
-    agent.capabilities = Some(serde_json::json!(payload.capabilities));
+    agent.capabilities = Some(payload.capabilities_value());
+    agent.capability_manifest = payload.manifest_value();
     agent.version = Some(payload.agent_version.clone());
     agent.system_info = Some(payload.system_info.clone());
 
@@ -164,6 +201,10 @@ pub async fn register_handler(
     .with_details(serde_json::json!({
         "version": payload.agent_version,
         "capabilities": payload.capabilities,
+        "protocol_version": payload
+            .capability_manifest
+            .as_ref()
+            .map(|manifest| manifest.protocol_version),
     }))
     .with_ip(
         req.peer_addr()
@@ -175,6 +216,15 @@ pub async fn register_handler(
         tracing::warn!("Failed to log agent registration audit: {:?}", err);
     }
 
+    let client_certificate = issue_client_certificate(
+        &payload,
+        &agent_pool,
+        &vault_client,
+        &settings,
+        &saved_agent,
+    )
+    .await?;
+
     let response = RegisterAgentResponseWrapper {
         data: RegisterAgentResponseData {
             item: RegisterAgentResponse {
@@ -182,6 +232,8 @@ pub async fn register_handler(
                 agent_token,
                 dashboard_version: "2.0.0".to_string(),
                 supported_api_versions: vec!["1.0".to_string()],
+                protocol_version: AGENT_PROTOCOL_VERSION,
+                client_certificate,
             },
         },
     };
@@ -194,3 +246,37 @@ pub async fn register_handler(
 
     Ok(HttpResponse::Created().json(response))
 }
+
+/// Sign the registration CSR when mTLS is enabled. In optional mode a
+/// signing failure only costs the agent its certificate.
+async fn issue_client_certificate(
+    payload: &RegisterAgentRequest,
+    agent_pool: &AgentPgPool,
+    vault_client: &helpers::VaultClient,
+    settings: &Settings,
+    agent: &models::Agent,
+) -> Result<Option<IssuedAgentCertificate>> {
+    let identity = &settings.agent_identity;
+    let Some(csr) = payload.csr.as_deref() else {
+        return Ok(None);
+    };
+    if identity.mtls_mode == AgentMtlsMode::Off {
+        return Ok(None);
+    }
+
+    match agent_identity::issue_certificate(agent_pool, vault_client, identity, agent, csr).await {
+        Ok(certificate) => Ok(Some(certificate)),
+        Err(err) if identity.mtls_mode == AgentMtlsMode::Required => {
+            tracing::error!("Failed to issue agent certificate: {}", err);
+            Err(helpers::JsonResponse::<RegisterAgentResponse>::build()
+                .internal_server_error("Failed to issue client certificate"))
+        }
+        Err(err) => {
+            tracing::warn!(
+                "Failed to issue agent certificate, continuing without: {}",
+                err
+            );
+            Ok(None)
+        }
+    }
+}
@@ -163,7 +163,7 @@ pub async fn report_handler(
     )
     .await
     {
-        Ok(_) => {
+        Ok(updated) => {
             tracing::info!(
                 "Command {} updated to status '{}' by agent {}",
                 payload.command_id,
@@ -174,6 +174,17 @@ pub async fn report_handler(
             // Remove from queue if still there (shouldn't be, but cleanup)
             let _ = db::command::remove_from_queue(agent_pool.as_ref(), &payload.command_id).await;
 
+            // Release or cancel the steps of its batch that wait on this command
+            if let Err(err) =
+                crate::services::command_batch::advance_batch(agent_pool.as_ref(), &updated).await
+            {
+                tracing::warn!(
+                    "Failed to advance batch of command {}: {}",
+                    payload.command_id,
+                    err
+                );
+            }
+
             // Cleanup project_app record when remove_app command completes successfully
             if command.r#type == "remove_app" && status == models::CommandStatus::Completed {
                 if let Some(ref params) = command.parameters {
@@ -234,6 +245,53 @@ pub async fn report_handler(
                 }
             }
 
+            // Store on-demand stats with the periodic samples
+            if command.r#type == "stats" && status == models::CommandStatus::Completed {
+                if let Some(report) = result_payload.as_ref().and_then(|result| {
+                    serde_json::from_value::<status_panel::StatsCommandReport>(result.clone()).ok()
+                }) {
+                    let stored = match crate::services::container_metrics::check_collected_at(
+                        report.collected_at,
+                        chrono::Utc::now(),
+                    ) {
+                        Ok(()) => {
+                            crate::services::container_metrics::record_samples(
+                                agent_pool.as_ref(),
+                                &payload.deployment_hash,
+                                report.collected_at,
+                                &report.containers,
+                            )
+                            .await
+                        }
+                        Err(err) => Err(err),
+                    };
+                    if let Err(err) = stored {
+                        tracing::warn!(
+                            deployment_hash = %payload.deployment_hash,
+                            "Failed to store stats samples: {}",
+                            err
+                        );
+                    }
+                }
+            }
+
+            // Record finished and failed file transfers
+            if let Some(audit_log) = result_payload.as_ref().and_then(|result| {
+                crate::services::file_transfer::transfer_audit_log(agent.id, &command, result)
+            }) {
+                if let Err(err) = db::agent::log_audit(agent_pool.as_ref(), audit_log).await {
+                    tracing::warn!("Failed to log file transfer audit: {:?}", err);
+                }
+            }
+            // The agent has the upload chunk now; do not keep it in the command log
+            if command.r#type == crate::services::file_transfer::UPLOAD_FILE_COMMAND_TYPE {
+                if let Err(err) =
+                    db::command::clear_file_chunk(agent_pool.as_ref(), &command.command_id).await
+                {
+                    tracing::warn!("Failed to clear upload chunk: {}", err);
+                }
+            }
+
             // Persist trigger_pipe results as pipe execution history
             if command.r#type == "trigger_pipe" {
                 if let Some(ref result) = result_payload {
@@ -0,0 +1,162 @@
+use crate::services::shell_session::{ShellControl, ShellFrame};
+use crate::services::ShellSessionHub;
+use crate::{helpers, models};
+use actix::{Actor, ActorContext, AsyncContext, Handler, Recipient, StreamHandler};
+use actix_web::{get, web, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// The agent's end of a shell session opened by `open_shell`. Binary frames
+/// are PTY bytes; text frames are `exit`/`error` controls for the user.
+#[tracing::instrument(name = "Agent shell attach", skip_all)]
+#[get("/shell/{session_id}")]
+pub async fn shell_attach_handler(
+    agent: web::ReqData<Arc<models::Agent>>,
+    path: web::Path<String>,
+    hub: web::Data<Arc<ShellSessionHub>>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> Result<HttpResponse, Error> {
+    let session_id = path.into_inner();
+    if hub.is_held_elsewhere(&session_id) {
+        tracing::error!(
+            "Agent {} tried to attach shell session {} on replica {}; shell sockets must reach the replica that opened the session",
+            agent.id,
+            session_id,
+            hub.replica()
+        );
+        return Err(helpers::JsonResponse::<String>::build().conflict(
+            "Shell session is held by another Stacker server replica; run a single replica or route shell sessions by session id",
+        ));
+    }
+    if !hub.is_pending(&session_id, &agent.deployment_hash) {
+        return Err(helpers::JsonResponse::not_found(
+            "Unknown or expired shell session",
+        ));
+    }
+
+    let (addr, response) = ws::start_with_addr(
+        AgentShellSession {
+            session_id: session_id.clone(),
+            user: None,
+            backlog: Vec::new(),
+            hb: Instant::now(),
+        },
+        &req,
+        payload,
+    )?;
+
+    match hub.attach(
+        &session_id,
+        &agent.deployment_hash,
+        addr.clone().recipient(),
+    ) {
+        Some(user) => {
+            tracing::info!("Agent {} attached shell session {}", agent.id, session_id);
+            addr.do_send(PairWithUser(user));
+        }
+        // The user left between the check and the upgrade.
+        None => addr.do_send(ShellFrame::Closed("session closed".to_string())),
+    }
+    Ok(response)
+}
+
+pub struct AgentShellSession {
+    session_id: String,
+    user: Option<Recipient<ShellFrame>>,
+    /// Frames read before the user's side was paired.
+    backlog: Vec<ShellFrame>,
+    hb: Instant,
+}
+
+impl AgentShellSession {
+    fn forward(&mut self, frame: ShellFrame) {
+        match &self.user {
+            Some(user) => user.do_send(frame),
+            None => self.backlog.push(frame),
+        }
+    }
+}
+
+impl Actor for AgentShellSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
+                tracing::warn!(
+                    "Agent shell session {} heartbeat failed, disconnecting",
+                    act.session_id
+                );
+                ctx.stop();
+                return;
+            }
+            ctx.ping(b"");
+        });
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        self.forward(ShellFrame::Closed("disconnected".to_string()));
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for AgentShellSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        self.hb = Instant::now();
+        match msg {
+            Ok(ws::Message::Binary(data)) => self.forward(ShellFrame::Data(data.to_vec())),
+            Ok(ws::Message::Text(text)) => match serde_json::from_str::<ShellControl>(&text) {
+                Ok(control) => self.forward(ShellFrame::Control(control)),
+                Err(err) => tracing::debug!("Ignoring agent shell control frame: {}", err),
+            },
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!("Agent shell session protocol error: {}", err);
+                ctx.stop();
+            }
+        }
+    }
+}
+
+/// The user's side of the session, once claimed from the hub.
+#[derive(actix::Message)]
+#[rtype(result = "()")]
+struct PairWithUser(Recipient<ShellFrame>);
+
+impl Handler<PairWithUser> for AgentShellSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: PairWithUser, _ctx: &mut Self::Context) {
+        for frame in self.backlog.drain(..) {
+            msg.0.do_send(frame);
+        }
+        self.user = Some(msg.0);
+    }
+}
+
+impl Handler<ShellFrame> for AgentShellSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: ShellFrame, ctx: &mut Self::Context) {
+        match msg {
+            ShellFrame::Data(data) => ctx.binary(data),
+            ShellFrame::Control(control) => ctx.text(control.to_text()),
+            ShellFrame::Closed(reason) => {
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Normal,
+                    description: Some(reason),
+                }));
+                ctx.stop();
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
+use crate::configuration::Settings;
 use crate::db;
 use crate::forms::status_panel::HealthCommandReport;
 use crate::helpers::{AgentPgPool, JsonResponse};
 use crate::models::{Command, ProjectApp};
 use crate::project_app::is_platform_managed_app_code;
+use crate::services::agent_compatibility::{assess, AgentCompatibility};
 use actix_web::{get, web, Responder, Result};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -24,6 +26,7 @@ pub struct AgentSnapshot {
     pub status: Option<String>,
     pub last_heartbeat: Option<chrono::DateTime<chrono::Utc>>,
     pub deployment_hash: Option<String>,
+    pub compatibility: Option<AgentCompatibility>,
 }
 
 #[derive(Debug, Serialize, Default)]
@@ -59,6 +62,7 @@ pub async fn snapshot_handler(
     path: web::Path<String>,
     query: web::Query<SnapshotQuery>,
     agent_pool: web::Data<AgentPgPool>,
+    settings: web::Data<Settings>,
 ) -> Result<impl Responder> {
     tracing::info!(
         "[SNAPSHOT HANDLER] Called for deployment_hash: {}, limit: {}, include_results: {}",
@@ -174,6 +178,7 @@ pub async fn snapshot_handler(
             }
             None => "offline".to_string(), // Never had a heartbeat
         };
+        let compatibility = assess(&a, settings.agent_latest_version.as_deref());
         AgentSnapshot {
             id: Some(a.id),
             version: a.version,
@@ -182,6 +187,7 @@ pub async fn snapshot_handler(
             status: Some(effective_status),
             last_heartbeat: a.last_heartbeat,
             deployment_hash: Some(a.deployment_hash),
+            compatibility: Some(compatibility),
         }
     });
     tracing::debug!("[SNAPSHOT HANDLER] Agent Snapshot : {:?}", agent_snapshot);
@@ -206,6 +212,7 @@ pub async fn snapshot_handler(
 pub async fn project_snapshot_handler(
     path: web::Path<i32>,
     agent_pool: web::Data<AgentPgPool>,
+    settings: web::Data<Settings>,
 ) -> Result<impl Responder> {
     let project_id = path.into_inner();
 
@@ -233,6 +240,7 @@ pub async fn project_snapshot_handler(
                 None => "offline".to_string(),
             };
             let deployment_hash = a.deployment_hash.clone();
+            let compatibility = assess(&a, settings.agent_latest_version.as_deref());
 
             let snap = AgentSnapshot {
                 id: Some(a.id),
@@ -242,6 +250,7 @@ pub async fn project_snapshot_handler(
                 status: Some(effective_status),
                 last_heartbeat: a.last_heartbeat,
                 deployment_hash: Some(deployment_hash.clone()),
+                compatibility: Some(compatibility),
             };
             (snap, deployment_hash)
         }
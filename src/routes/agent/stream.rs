@@ -0,0 +1,210 @@
+use crate::helpers::agent_capabilities::{
+    extract_capabilities, has_capability, COMMAND_STREAM_CAPABILITY,
+};
+use crate::services::CommandNotifier;
+use crate::{configuration::Settings, db, helpers, helpers::AgentPgPool, models};
+use actix::{Actor, ActorContext, ActorState, Addr, AsyncContext, Handler, StreamHandler};
+use actix_web::{get, web, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Ping the agent and refresh its heartbeat this often.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Close the stream when the agent has not answered for this long.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(45);
+/// Re-check the queue this often even with a live listener, in case a
+/// notification was missed.
+const QUEUE_RECHECK: Duration = Duration::from_secs(60);
+
+/// Persistent command channel for agents advertising `command_stream`.
+/// Each queued command is pushed as a `{"type":"command","item":{..}}` text
+/// frame; results still go through `/commands/report`.
+#[tracing::instrument(name = "Agent command stream", skip_all)]
+#[get("/commands/stream/{deployment_hash}")]
+pub async fn stream_handler(
+    agent: web::ReqData<Arc<models::Agent>>,
+    path: web::Path<String>,
+    agent_pool: web::Data<AgentPgPool>,
+    notifier: web::Data<Arc<CommandNotifier>>,
+    settings: web::Data<Settings>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> Result<HttpResponse, Error> {
+    let deployment_hash = path.into_inner();
+    if agent.deployment_hash != deployment_hash {
+        return Err(helpers::JsonResponse::forbidden(
+            "Not authorized for this deployment",
+        ));
+    }
+    let capabilities = extract_capabilities(agent.capabilities.clone());
+    if !has_capability(&capabilities, COMMAND_STREAM_CAPABILITY) {
+        return Err(helpers::JsonResponse::bad_request(format!(
+            "Agent does not advertise the '{}' capability; poll /commands/wait instead",
+            COMMAND_STREAM_CAPABILITY
+        )));
+    }
+
+    let audit_log = models::AuditLog::new(
+        Some(agent.id),
+        Some(deployment_hash.clone()),
+        "agent.command_stream_opened".to_string(),
+        Some("success".to_string()),
+    );
+    let _ = db::agent::log_audit(agent_pool.as_ref(), audit_log).await;
+
+    let poll_interval = Duration::from_secs(settings.agent_command_poll_interval_secs.max(1));
+    ws::start(
+        AgentCommandStream {
+            agent: agent.into_inner(),
+            agent_pool: agent_pool.get_ref().clone(),
+            notifier: notifier.get_ref().clone(),
+            poll_interval,
+            hb: Instant::now(),
+        },
+        &req,
+        payload,
+    )
+}
+
+pub struct AgentCommandStream {
+    agent: Arc<models::Agent>,
+    agent_pool: AgentPgPool,
+    notifier: Arc<CommandNotifier>,
+    poll_interval: Duration,
+    hb: Instant,
+}
+
+impl AgentCommandStream {
+    fn hb(&self, ctx: &mut <Self as Actor>::Context) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
+                tracing::warn!(
+                    "Agent {} command stream heartbeat failed, disconnecting",
+                    act.agent.id
+                );
+                ctx.stop();
+                return;
+            }
+            ctx.ping(b"");
+
+            let pool = act.agent_pool.clone();
+            let agent_id = act.agent.id;
+            actix::spawn(async move {
+                let _ = db::agent::update_heartbeat(pool.as_ref(), agent_id, "online").await;
+            });
+        });
+    }
+
+    /// Claim queued commands and push them while the stream is open.
+    fn deliver(&self, addr: Addr<Self>) {
+        let pool = self.agent_pool.clone();
+        let notifier = self.notifier.clone();
+        let deployment_hash = self.agent.deployment_hash.clone();
+        let poll_interval = self.poll_interval;
+        actix::spawn(async move {
+            let mut queued = notifier.subscribe(&deployment_hash);
+            while addr.connected() {
+                match db::command::claim_next_for_deployment(pool.as_ref(), &deployment_hash).await
+                {
+                    Ok(Some(command)) => {
+                        tracing::info!(
+                            "Streaming command {} to deployment {}",
+                            command.command_id,
+                            deployment_hash
+                        );
+                        // A stream closing under us cannot take the command;
+                        // queue it again for the next connection or long-poll.
+                        let pushed = addr
+                            .send(PushCommand(command.clone()))
+                            .await
+                            .unwrap_or(false);
+                        if !pushed {
+                            if let Err(err) =
+                                db::command::return_to_queue(pool.as_ref(), &command).await
+                            {
+                                tracing::error!(
+                                    "Failed to requeue undelivered command {}: {}",
+                                    command.command_id,
+                                    err
+                                );
+                            }
+                            break;
+                        }
+                        continue;
+                    }
+                    Ok(None) => {}
+                    Err(err) => tracing::error!("Failed to fetch command from queue: {}", err),
+                }
+                let wait = if notifier.is_listening() {
+                    QUEUE_RECHECK
+                } else {
+                    poll_interval
+                };
+                notifier.wait(&mut queued, wait).await;
+            }
+        });
+    }
+}
+
+impl Actor for AgentCommandStream {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        tracing::info!(
+            "Agent {} command stream opened (deployment {})",
+            self.agent.id,
+            self.agent.deployment_hash
+        );
+        self.hb(ctx);
+        self.deliver(ctx.address());
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        tracing::info!("Agent {} command stream closed", self.agent.id);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for AgentCommandStream {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => {
+                self.hb = Instant::now();
+                ctx.pong(&msg);
+            }
+            Ok(ws::Message::Pong(_)) => {
+                self.hb = Instant::now();
+            }
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(_) => {
+                self.hb = Instant::now();
+            }
+            Err(err) => {
+                tracing::warn!("Agent command stream protocol error: {}", err);
+                ctx.stop();
+            }
+        }
+    }
+}
+
+/// A claimed command to write to the agent. Answers whether the frame was
+/// written to a stream that is still open.
+#[derive(actix::Message)]
+#[rtype(result = "bool")]
+struct PushCommand(models::Command);
+
+impl Handler<PushCommand> for AgentCommandStream {
+    type Result = bool;
+
+    fn handle(&mut self, msg: PushCommand, ctx: &mut Self::Context) -> bool {
+        if ctx.state() != ActorState::Running {
+            return false;
+        }
+        ctx.text(json!({ "type": "command", "item": msg.0 }).to_string());
+        true
+    }
+}
@@ -1,8 +1,13 @@
+use crate::helpers::agent_capabilities::{
+    extract_capabilities, has_capability, COMMAND_STREAM_CAPABILITY,
+};
+use crate::services::agent_identity::{self, TOKEN_ROTATION_META_KEY};
+use crate::services::CommandNotifier;
 use crate::{configuration::Settings, db, helpers, helpers::AgentPgPool, models};
 use actix_web::{get, web, HttpRequest, Responder, Result};
 use serde_json::json;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, serde::Deserialize)]
 pub struct WaitQuery {
@@ -17,6 +22,7 @@ pub async fn wait_handler(
     path: web::Path<String>,
     query: web::Query<WaitQuery>,
     agent_pool: web::Data<AgentPgPool>,
+    notifier: web::Data<Arc<CommandNotifier>>,
     settings: web::Data<Settings>,
     _req: HttpRequest,
 ) -> Result<impl Responder> {
@@ -41,7 +47,9 @@ pub async fn wait_handler(
     );
     let _ = db::agent::log_audit(agent_pool.as_ref(), audit_log).await;
 
-    // Long-polling: Check for pending commands with retries
+    // Long-polling: wait for a queued command. The command notifier wakes
+    // the waiter as soon as a command is queued; without a live listener it
+    // re-checks every interval.
     // IMPORTANT: Each check acquires and releases DB connection to avoid pool exhaustion
     let timeout_seconds = query
         .timeout
@@ -52,11 +60,24 @@ pub async fn wait_handler(
         .unwrap_or(settings.agent_command_poll_interval_secs)
         .clamp(1, 10);
     let check_interval = Duration::from_secs(interval_seconds);
-    let max_checks = (timeout_seconds / interval_seconds).max(1);
+    let deadline = Instant::now() + Duration::from_secs(timeout_seconds);
+    let mut queued = notifier.subscribe(&deployment_hash);
+    let mut meta = poll_meta(&agent, &deployment_hash, interval_seconds);
+    match agent_identity::offer_token_rotation(
+        agent_pool.as_ref(),
+        &settings.agent_identity,
+        &agent,
+    )
+    .await
+    {
+        Ok(Some(offer)) => meta[TOKEN_ROTATION_META_KEY] = json!(offer),
+        Ok(None) => {}
+        Err(err) => tracing::warn!("Failed to offer agent token rotation: {}", err),
+    }
 
-    for i in 0..max_checks {
-        // Acquire connection only for query, then release immediately
-        match db::command::fetch_next_for_deployment(agent_pool.as_ref(), &deployment_hash).await {
+    loop {
+        // Claiming takes the command off the queue and marks it sent in one statement
+        match db::command::claim_next_for_deployment(agent_pool.as_ref(), &deployment_hash).await {
             Ok(Some(command)) => {
                 tracing::info!(
                     "Found command {} for agent {} (deployment {})",
@@ -65,38 +86,29 @@ pub async fn wait_handler(
                     deployment_hash
                 );
 
-                // Update command status to 'sent' - separate connection
-                let updated_command = db::command::update_status(
-                    agent_pool.as_ref(),
-                    &command.command_id,
-                    &models::CommandStatus::Sent,
-                )
-                .await
-                .map_err(|err| {
-                    tracing::error!("Failed to update command status: {}", err);
-                    helpers::JsonResponse::internal_server_error(err)
-                })?;
-
-                // Remove from queue - separate connection
-                let _ =
-                    db::command::remove_from_queue(agent_pool.as_ref(), &command.command_id).await;
-
                 return Ok(helpers::JsonResponse::<Option<models::Command>>::build()
-                    .set_item(Some(updated_command))
-                    .set_meta(json!({ "next_poll_secs": interval_seconds }))
+                    .set_item(Some(command))
+                    .set_meta(meta)
                     .ok("Command available"));
             }
-            Ok(None) => {
-                // No command yet, sleep WITHOUT holding DB connection
-                if i < max_checks - 1 {
-                    tokio::time::sleep(check_interval).await;
-                }
-            }
+            Ok(None) => {}
             Err(err) => {
                 tracing::error!("Failed to fetch command from queue: {}", err);
                 return Err(helpers::JsonResponse::internal_server_error(err));
             }
         }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        // Sleep WITHOUT holding DB connection
+        let wait = if notifier.is_listening() {
+            remaining
+        } else {
+            remaining.min(check_interval)
+        };
+        notifier.wait(&mut queued, wait).await;
     }
 
     // No commands available after timeout
@@ -107,6 +119,23 @@ pub async fn wait_handler(
     );
     Ok(helpers::JsonResponse::<Option<models::Command>>::build()
         .set_item(None)
-        .set_meta(json!({ "next_poll_secs": interval_seconds }))
+        .set_meta(meta)
         .ok("No command available"))
 }
+
+/// Poll metadata. Agents that can stream commands are pointed at the stream.
+fn poll_meta(
+    agent: &models::Agent,
+    deployment_hash: &str,
+    interval_seconds: u64,
+) -> serde_json::Value {
+    let capabilities = extract_capabilities(agent.capabilities.clone());
+    if has_capability(&capabilities, COMMAND_STREAM_CAPABILITY) {
+        json!({
+            "next_poll_secs": interval_seconds,
+            "command_stream": format!("/api/v1/agent/commands/stream/{}", deployment_hash),
+        })
+    } else {
+        json!({ "next_poll_secs": interval_seconds })
+    }
+}
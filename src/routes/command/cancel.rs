@@ -63,6 +63,13 @@ pub async fn cancel_handler(
             JsonResponse::internal_server_error(err)
         })?;
 
+    // Steps of its batch that depend on it can no longer run
+    if let Err(err) =
+        crate::services::command_batch::advance_batch(pg_pool.get_ref(), &cancelled_command).await
+    {
+        tracing::warn!("Failed to advance batch of command {}: {}", command_id, err);
+    }
+
     tracing::info!(
         "Cancelled command {} for deployment {} by user {}",
         command_id,
@@ -9,6 +9,7 @@ use crate::project_app::{
     store_configs_to_vault_from_params, store_registry_auth_command_to_vault,
     upsert_app_config_for_deploy, REGISTRY_AUTH_VAULT_KEY,
 };
+use crate::services::command_queue;
 use crate::services::env_model::reconcile_env_file_content;
 use crate::services::{AppConfig, ConfigRenderer, ProjectAppService, VaultService};
 use actix_web::{post, web, Responder, Result};
@@ -30,6 +31,9 @@ pub struct CreateCommandRequest {
     pub timeout_seconds: Option<i32>,
     #[serde(default)]
     pub metadata: Option<serde_json::Value>,
+    /// The caller's deploy lease, when it holds one.
+    #[serde(default)]
+    pub lease_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Default)]
@@ -69,6 +73,17 @@ pub async fn create_handler(
             },
         )?;
 
+    // Check the lease and the agent before storing any config for the command.
+    let validated_parameters = command_queue::admit(
+        pg_pool.get_ref(),
+        &req.deployment_hash,
+        &req.command_type,
+        validated_parameters,
+        req.lease_id.as_deref(),
+    )
+    .await
+    .map_err(command_queue::api_error)?;
+
     // For deploy_app commands, upsert app config and sync to Vault before enriching parameters
     let final_parameters = if req.command_type == "deploy_app" {
         if let Some(registry_auth) = extract_registry_auth_from_params(&validated_parameters) {
@@ -321,7 +336,7 @@ pub async fn create_handler(
         req.command_type.clone(),
         user.id.clone(),
     )
-    .with_priority(priority.clone());
+    .with_priority(priority);
 
     if let Some(params) = &final_parameters {
         command = command.with_parameters(params.clone());
@@ -335,27 +350,14 @@ pub async fn create_handler(
         command = command.with_metadata(metadata.clone());
     }
 
-    // Insert command into database
-    let saved_command = db::command::insert(pg_pool.get_ref(), &command)
+    // Agent will poll and pick it up
+    let saved_command = command_queue::enqueue(pg_pool.get_ref(), command, req.lease_id.as_deref())
         .await
-        .map_err(|err| {
-            tracing::error!("Failed to create command: {}", err);
-            JsonResponse::<()>::build().internal_server_error(err)
+        .map_err(|error| {
+            tracing::error!("Failed to create command: {}", error.message);
+            command_queue::api_error(error)
         })?;
 
-    // Add to queue - agent will poll and pick it up
-    db::command::add_to_queue(
-        pg_pool.get_ref(),
-        &saved_command.command_id,
-        &saved_command.deployment_hash,
-        &priority,
-    )
-    .await
-    .map_err(|err| {
-        tracing::error!("Failed to add command to queue: {}", err);
-        JsonResponse::<()>::build().internal_server_error(err)
-    })?;
-
     tracing::info!(
         command_id = %saved_command.command_id,
         deployment_hash = %saved_command.deployment_hash,
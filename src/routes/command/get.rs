@@ -3,6 +3,7 @@ use crate::db;
 use crate::helpers::JsonResponse;
 use crate::models::User;
 use crate::routes::legacy_installations::resolve_owned_deployment_by_hash;
+use crate::services::file_transfer;
 use actix_web::{get, web, Responder, Result};
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -47,6 +48,15 @@ pub async fn get_handler(
                 ));
             }
 
+            // The owner has the download chunk now; do not keep it in the command log
+            if file_transfer::is_fetched_download(&cmd) {
+                if let Err(err) =
+                    db::command::clear_file_chunk(pg_pool.get_ref(), &cmd.command_id).await
+                {
+                    tracing::warn!("Failed to clear download chunk: {}", err);
+                }
+            }
+
             tracing::info!(
                 "Fetched command {} for deployment {} by user {}",
                 command_id,
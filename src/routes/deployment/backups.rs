@@ -0,0 +1,33 @@
+use actix_web::{get, web, Responder, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::{
+    helpers::JsonResponse,
+    models,
+    services::{backup::list_backups, ApiTypedError, TypedErrorEnvelope},
+};
+
+use super::fetch_owned_deployment;
+
+/// `GET /api/v1/deployments/{hash}/backups`
+///
+/// Volume backups still kept by the agent, newest first.
+#[tracing::instrument(name = "List volume backups", skip_all)]
+#[get("/{deployment_hash}/backups")]
+pub async fn list_backups_handler(
+    path: web::Path<String>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let backups = list_backups(pg_pool.get_ref(), &deployment_hash)
+        .await
+        .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build()
+        .set_list(backups)
+        .ok("Volume backups fetched"))
+}
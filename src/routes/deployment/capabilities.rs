@@ -7,12 +7,14 @@ use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::{
+    configuration::Settings,
     db,
     helpers::{
-        extract_capabilities, has_capability, has_capability_value, JsonResponse,
-        NPM_CREDENTIAL_SOURCE_KEY,
+        extract_capabilities, has_capability, has_capability_value, parse_manifest,
+        AgentCapabilityManifest, JsonResponse, NPM_CREDENTIAL_SOURCE_KEY,
     },
     models::Agent,
+    services::agent_compatibility::{assess, AgentCompatibility},
 };
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -44,6 +46,9 @@ pub struct CapabilitiesResponse {
     pub capabilities: Vec<String>,
     pub commands: Vec<CapabilityCommand>,
     pub features: CapabilityFeatures,
+    /// Structured capabilities of agents that report them.
+    pub manifest: Option<AgentCapabilityManifest>,
+    pub compatibility: Option<AgentCompatibility>,
 }
 
 async fn can_view_capabilities(
@@ -164,6 +169,7 @@ pub async fn capabilities_handler(
     path: web::Path<String>,
     user: web::ReqData<Arc<crate::models::User>>,
     pg_pool: web::Data<PgPool>,
+    settings: web::Data<Settings>,
 ) -> Result<impl Responder> {
     let deployment_hash = path.into_inner();
 
@@ -175,7 +181,11 @@ pub async fn capabilities_handler(
         .await
         .map_err(|err| JsonResponse::<CapabilitiesResponse>::build().internal_server_error(err))?;
 
-    let payload = build_capabilities_payload(deployment_hash, agent);
+    let compatibility = agent
+        .as_ref()
+        .map(|agent| assess(agent, settings.agent_latest_version.as_deref()));
+    let mut payload = build_capabilities_payload(deployment_hash, agent);
+    payload.compatibility = compatibility;
 
     Ok(JsonResponse::build()
         .set_item(payload)
@@ -205,6 +215,7 @@ fn build_capabilities_payload(
             CapabilitiesResponse {
                 deployment_hash,
                 agent_id: Some(agent.id.to_string()),
+                manifest: parse_manifest(agent.capability_manifest.clone()),
                 status: agent.status,
                 last_heartbeat: agent.last_heartbeat,
                 version: agent.version,
@@ -0,0 +1,68 @@
+use actix_web::{delete, post, web, Responder, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::{
+    helpers::JsonResponse,
+    models,
+    services::{
+        deployment_lease::{acquire_lease, LeaseRequest},
+        ApiTypedError, TypedErrorCode, TypedErrorEnvelope,
+    },
+};
+
+use super::fetch_owned_deployment;
+
+/// `POST /api/v1/deployments/{hash}/lease`
+///
+/// Acquire or renew the deploy lease. Returns 409 with a `deployment_locked`
+/// envelope while another client holds it, unless `force` is set.
+#[tracing::instrument(name = "Acquire deployment lease", skip_all)]
+#[post("/{deployment_hash}/lease")]
+pub async fn acquire_lease_handler(
+    path: web::Path<String>,
+    form: web::Json<LeaseRequest>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let lease = acquire_lease(pg_pool.get_ref(), &deployment_hash, &user.id, &form)
+        .await
+        .map_err(|error| match error.code {
+            TypedErrorCode::DeploymentLocked => ApiTypedError::conflict(error),
+            TypedErrorCode::InvalidRequest => ApiTypedError::bad_request(error),
+            _ => ApiTypedError::internal(error),
+        })?;
+
+    Ok(JsonResponse::build()
+        .set_item(lease)
+        .ok("Deployment lease acquired"))
+}
+
+/// `DELETE /api/v1/deployments/{hash}/lease/{lease_id}`
+///
+/// Release a lease. Releasing a lease that already expired or was broken is
+/// not an error.
+#[tracing::instrument(name = "Release deployment lease", skip_all)]
+#[delete("/{deployment_hash}/lease/{lease_id}")]
+pub async fn release_lease_handler(
+    path: web::Path<(String, String)>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let (deployment_hash, lease_id) = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let released =
+        crate::db::deployment_lease::release(pg_pool.get_ref(), &deployment_hash, &lease_id)
+            .await
+            .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::<()>::build().ok(if released {
+        "Deployment lease released"
+    } else {
+        "Deployment lease was not held"
+    }))
+}
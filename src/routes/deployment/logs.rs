@@ -0,0 +1,64 @@
+use actix_web::{get, web, Responder, Result};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::{
+    configuration::Settings,
+    helpers::JsonResponse,
+    models,
+    services::{ApiTypedError, LogSearchQuery, LogStore, TypedErrorEnvelope},
+};
+
+use super::fetch_owned_deployment;
+
+#[derive(Debug, Deserialize)]
+pub struct LogSearchParams {
+    /// Words or quoted phrases, e.g. `timeout` or `"connection reset"`.
+    #[serde(default)]
+    pub search: Option<String>,
+    /// Look-back window such as `15m`, `1h` or `7d` (default `1h`).
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Only lines of this app.
+    #[serde(default)]
+    pub service: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// `GET /api/v1/deployments/{hash}/logs?search=timeout&since=24h&service=api`
+///
+/// Retained log lines shipped by the agent across all containers of the
+/// deployment, the newest `limit` matches oldest first.
+#[tracing::instrument(name = "Search container logs", skip_all)]
+#[get("/{deployment_hash}/logs")]
+pub async fn search_logs_handler(
+    path: web::Path<String>,
+    query: web::Query<LogSearchParams>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+    settings: web::Data<Settings>,
+    log_store: web::Data<Arc<dyn LogStore>>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let search = LogSearchQuery::parse(
+        &settings.log_shipping,
+        &deployment_hash,
+        query.since.as_deref(),
+        query.service.as_deref(),
+        query.search.as_deref(),
+        query.limit,
+    )
+    .map_err(|err| ApiTypedError::bad_request(TypedErrorEnvelope::invalid_request(err)))?;
+    let lines = log_store
+        .search(pg_pool.get_ref(), &search)
+        .await
+        .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build()
+        .set_list(lines)
+        .ok("Container logs fetched"))
+}
@@ -0,0 +1,56 @@
+use actix_web::{get, web, Responder, Result};
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::{
+    helpers::JsonResponse,
+    models,
+    services::{
+        container_metrics::{parse_window, MAX_QUERY_SAMPLES},
+        ApiTypedError, TypedErrorEnvelope,
+    },
+};
+
+use super::fetch_owned_deployment;
+
+#[derive(Debug, Deserialize)]
+pub struct MetricsQuery {
+    /// Look-back window such as `15m`, `1h` or `7d` (default `1h`).
+    #[serde(default)]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub app: Option<String>,
+}
+
+/// `GET /api/v1/deployments/{hash}/metrics?since=1h&app=<code>`
+///
+/// Container resource samples pushed by the agent, oldest first.
+#[tracing::instrument(name = "List container metrics", skip_all)]
+#[get("/{deployment_hash}/metrics")]
+pub async fn container_metrics_handler(
+    path: web::Path<String>,
+    query: web::Query<MetricsQuery>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let window = parse_window(query.since.as_deref().unwrap_or("1h"))
+        .map_err(|err| ApiTypedError::bad_request(TypedErrorEnvelope::invalid_request(err)))?;
+    let samples = crate::db::container_metrics::list_since(
+        pg_pool.get_ref(),
+        &deployment_hash,
+        Utc::now() - window,
+        query.app.as_deref(),
+        MAX_QUERY_SAMPLES,
+    )
+    .await
+    .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build()
+        .set_list(samples)
+        .ok("Container metrics fetched"))
+}
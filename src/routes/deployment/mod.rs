@@ -1,13 +1,63 @@
+pub mod backups;
 pub mod capabilities;
 pub mod events;
 pub mod force_complete;
+pub mod lease;
+pub mod logs;
+pub mod metrics;
 pub mod plan;
+pub mod previews;
+pub mod releases;
+pub mod schedules;
+pub mod shell;
 pub mod state;
 pub mod status;
 
+pub use backups::*;
 pub use capabilities::*;
 pub use events::*;
 pub use force_complete::*;
+pub use lease::*;
+pub use logs::*;
+pub use metrics::*;
 pub use plan::*;
+pub use previews::*;
+pub use releases::*;
+pub use schedules::*;
+pub use shell::*;
 pub use state::*;
 pub use status::*;
+
+use sqlx::PgPool;
+
+use crate::models;
+use crate::services::{ApiTypedError, TypedErrorEnvelope};
+
+/// Load a deployment the requesting user owns. Someone else's deployment is
+/// reported as not found, so its existence is not revealed.
+pub(crate) async fn fetch_owned_deployment(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    user: &models::User,
+) -> Result<models::Deployment, ApiTypedError> {
+    let deployment = crate::db::deployment::fetch_by_deployment_hash(pg_pool, deployment_hash)
+        .await
+        .map_err(|_| {
+            ApiTypedError::internal(TypedErrorEnvelope::internal_error(
+                "Failed to load deployment",
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiTypedError::not_found(TypedErrorEnvelope::deployment_not_found(
+                "Deployment not found",
+            ))
+        })?;
+
+    if deployment.user_id.as_deref() != Some(&user.id) {
+        return Err(ApiTypedError::not_found(
+            TypedErrorEnvelope::deployment_not_found("Deployment not found"),
+        ));
+    }
+
+    Ok(deployment)
+}
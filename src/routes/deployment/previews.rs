@@ -0,0 +1,97 @@
+use actix_web::{delete, get, post, web, Responder, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::{
+    helpers::JsonResponse,
+    models,
+    services::{
+        preview::{register_preview, teardown_preview, PreviewRequest},
+        ApiTypedError, TypedErrorCode, TypedErrorEnvelope,
+    },
+};
+
+use super::fetch_owned_deployment;
+
+/// `POST /api/v1/deployments/{hash}/previews`
+///
+/// Register a branch preview (or extend an existing one) so it is torn down
+/// when its TTL runs out.
+#[tracing::instrument(name = "Register preview environment", skip_all)]
+#[post("/{deployment_hash}/previews")]
+pub async fn create_preview_handler(
+    path: web::Path<String>,
+    form: web::Json<PreviewRequest>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let preview = register_preview(pg_pool.get_ref(), &deployment_hash, &user.id, &form)
+        .await
+        .map_err(|error| match error.code {
+            TypedErrorCode::InvalidRequest => ApiTypedError::bad_request(error),
+            _ => ApiTypedError::internal(error),
+        })?;
+
+    Ok(JsonResponse::build()
+        .set_item(preview)
+        .ok("Preview environment registered"))
+}
+
+#[tracing::instrument(name = "List preview environments", skip_all)]
+#[get("/{deployment_hash}/previews")]
+pub async fn list_previews_handler(
+    path: web::Path<String>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let previews = crate::db::preview_environment::list_active(pg_pool.get_ref(), &deployment_hash)
+        .await
+        .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build()
+        .set_list(previews)
+        .ok("Preview environments fetched"))
+}
+
+/// `DELETE /api/v1/deployments/{hash}/previews/{slug}`
+///
+/// Queue removal of the preview's proxy host, containers and volumes.
+#[tracing::instrument(name = "Tear down preview environment", skip_all)]
+#[delete("/{deployment_hash}/previews/{slug}")]
+pub async fn delete_preview_handler(
+    path: web::Path<(String, String)>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let (deployment_hash, slug) = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let preview =
+        crate::db::preview_environment::fetch_active(pg_pool.get_ref(), &deployment_hash, &slug)
+            .await
+            .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?
+            .ok_or_else(|| {
+                ApiTypedError::not_found(TypedErrorEnvelope::deployment_not_found(format!(
+                    "No active preview '{}'",
+                    slug
+                )))
+            })?;
+
+    let command_ids = teardown_preview(
+        pg_pool.get_ref(),
+        &preview,
+        models::PreviewEnvironment::REMOVED,
+    )
+    .await
+    .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build()
+        .set_list(command_ids)
+        .ok("Preview teardown queued"))
+}
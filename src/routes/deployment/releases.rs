@@ -0,0 +1,118 @@
+use actix_web::{get, post, web, Responder, Result};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use super::fetch_owned_deployment;
+use crate::{
+    helpers::JsonResponse,
+    models,
+    services::{
+        release::{apply_release_rollback, record_release, verify_release_source, ReleaseRecord},
+        ApiTypedError, TypedErrorCode, TypedErrorEnvelope,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseRollbackRequest {
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default, rename = "expectedFingerprint")]
+    pub expected_fingerprint: Option<String>,
+    /// The caller's deploy lease, when it holds one.
+    #[serde(default, rename = "leaseId")]
+    pub lease_id: Option<String>,
+}
+
+#[tracing::instrument(name = "Record deployment release", skip_all)]
+#[post("/{deployment_hash}/releases")]
+pub async fn create_release_handler(
+    path: web::Path<String>,
+    form: web::Json<ReleaseRecord>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    let deployment = fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+    verify_release_source(pg_pool.get_ref(), &deployment, &form)
+        .await
+        .map_err(|error| match error.code {
+            TypedErrorCode::InvalidRequest => ApiTypedError::conflict(error),
+            _ => ApiTypedError::internal(error),
+        })?;
+
+    let (release, created) = record_release(
+        pg_pool.get_ref(),
+        &deployment_hash,
+        form.into_inner(),
+        &user.id,
+    )
+    .await
+    .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build().set_item(release).ok(if created {
+        "Release recorded"
+    } else {
+        "Release unchanged"
+    }))
+}
+
+#[tracing::instrument(name = "List deployment releases", skip_all)]
+#[get("/{deployment_hash}/releases")]
+pub async fn list_releases_handler(
+    path: web::Path<String>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let releases =
+        crate::db::deployment_release::list_by_deployment_hash(pg_pool.get_ref(), &deployment_hash)
+            .await
+            .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build()
+        .set_list(releases)
+        .ok("Deployment releases fetched"))
+}
+
+#[tracing::instrument(name = "Roll back deployment to release", skip_all)]
+#[post("/{deployment_hash}/releases/{version}/rollback")]
+pub async fn rollback_release_handler(
+    path: web::Path<(String, String)>,
+    form: web::Json<ReleaseRollbackRequest>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let (deployment_hash, version) = path.into_inner();
+    let deployment = fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let outcome = apply_release_rollback(
+        pg_pool.get_ref(),
+        &deployment,
+        &user.id,
+        form.target.as_deref().unwrap_or("cloud"),
+        &version,
+        form.expected_fingerprint.as_deref(),
+        form.lease_id.as_deref(),
+    )
+    .await
+    .map_err(|error| match error.code {
+        TypedErrorCode::PlanStale | TypedErrorCode::DeploymentLocked => {
+            ApiTypedError::conflict(error)
+        }
+        TypedErrorCode::RollbackTargetUnavailable | TypedErrorCode::DeploymentCapabilityMissing => {
+            ApiTypedError::bad_request(error)
+        }
+        TypedErrorCode::DeploymentNotFound => ApiTypedError::not_found(error),
+        _ => ApiTypedError::internal(error),
+    })?;
+
+    let message = if outcome.release.is_some() {
+        "Release rollback queued"
+    } else {
+        "Deployment already runs the requested release"
+    };
+    Ok(JsonResponse::build().set_item(outcome).ok(message))
+}
@@ -0,0 +1,89 @@
+use actix_web::{delete, get, post, web, Responder, Result};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::{
+    helpers::JsonResponse,
+    models,
+    services::{
+        command_schedule::{create_schedule, ScheduleRequest},
+        ApiTypedError, TypedErrorCode, TypedErrorEnvelope,
+    },
+};
+
+use super::fetch_owned_deployment;
+
+/// `POST /api/v1/deployments/{hash}/schedules`
+///
+/// Queue an agent command on a cron schedule.
+#[tracing::instrument(name = "Create command schedule", skip_all)]
+#[post("/{deployment_hash}/schedules")]
+pub async fn create_schedule_handler(
+    path: web::Path<String>,
+    form: web::Json<ScheduleRequest>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let schedule = create_schedule(pg_pool.get_ref(), &deployment_hash, &user.id, &form)
+        .await
+        .map_err(|error| match error.code {
+            TypedErrorCode::InvalidRequest => ApiTypedError::bad_request(error),
+            _ => ApiTypedError::internal(error),
+        })?;
+
+    Ok(JsonResponse::build()
+        .set_item(schedule)
+        .created("Command schedule created"))
+}
+
+#[tracing::instrument(name = "List command schedules", skip_all)]
+#[get("/{deployment_hash}/schedules")]
+pub async fn list_schedules_handler(
+    path: web::Path<String>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let deployment_hash = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let schedules =
+        crate::db::command_schedule::list_by_deployment(pg_pool.get_ref(), &deployment_hash)
+            .await
+            .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+
+    Ok(JsonResponse::build()
+        .set_list(schedules)
+        .ok("Command schedules fetched"))
+}
+
+/// `DELETE /api/v1/deployments/{hash}/schedules/{schedule_id}`
+///
+/// Stop a schedule. Commands it already queued are left alone.
+#[tracing::instrument(name = "Delete command schedule", skip_all)]
+#[delete("/{deployment_hash}/schedules/{schedule_id}")]
+pub async fn delete_schedule_handler(
+    path: web::Path<(String, String)>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<impl Responder, ApiTypedError> {
+    let (deployment_hash, schedule_id) = path.into_inner();
+    fetch_owned_deployment(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let deleted =
+        crate::db::command_schedule::delete(pg_pool.get_ref(), &deployment_hash, &schedule_id)
+            .await
+            .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+    if !deleted {
+        return Err(ApiTypedError::not_found(
+            TypedErrorEnvelope::schedule_not_found(format!(
+                "No command schedule '{}'",
+                schedule_id
+            )),
+        ));
+    }
+
+    Ok(JsonResponse::<models::CommandSchedule>::build().ok("Command schedule deleted"))
+}
@@ -0,0 +1,372 @@
+use actix::{Actor, ActorContext, AsyncContext, Handler, Recipient, StreamHandler};
+use actix_web::{get, web, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{
+    configuration::Settings,
+    db,
+    forms::status_panel::{validate_command_parameters, OpenShellCommandRequest},
+    helpers::{extract_capabilities, has_capability, SHELL_CAPABILITY},
+    models::{self, Command, CommandPriority},
+    services::{
+        command_queue,
+        shell_session::{
+            AgentAttached, ShellControl, ShellFrame, ShellTranscript,
+            DEFAULT_SHELL_IDLE_TIMEOUT_SECS, OPEN_SHELL_COMMAND_TYPE, SHELL_ATTACH_TIMEOUT,
+            SHELL_CLOSED_EVENT, SHELL_OPENED_EVENT,
+        },
+        ApiTypedError, ShellSessionHub, TypedErrorEnvelope,
+    },
+};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(45);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+pub struct ShellQuery {
+    pub app: String,
+    /// Program to run instead of the container's default shell.
+    #[serde(default)]
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub cols: Option<u16>,
+    #[serde(default)]
+    pub rows: Option<u16>,
+    /// Seconds without input before the session is closed.
+    #[serde(default)]
+    pub idle_timeout: Option<u32>,
+}
+
+/// Only the deployment owner may open a shell; project members are viewers.
+async fn authorize_shell(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    user: &models::User,
+) -> Result<models::Deployment, ApiTypedError> {
+    let deployment = db::deployment::fetch_by_deployment_hash(pg_pool, deployment_hash)
+        .await
+        .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?
+        .ok_or_else(|| {
+            ApiTypedError::not_found(TypedErrorEnvelope::deployment_not_found(
+                "Deployment not found",
+            ))
+        })?;
+    if deployment.user_id.as_deref() == Some(&user.id) {
+        return Ok(deployment);
+    }
+
+    let member = db::project_member::fetch(pg_pool, deployment.project_id, &user.id)
+        .await
+        .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+    match member {
+        Some(_) => Err(ApiTypedError::forbidden(
+            TypedErrorEnvelope::permission_denied(
+                "Project viewers cannot open shell sessions; ask the deployment owner",
+            ),
+        )),
+        None => Err(ApiTypedError::not_found(
+            TypedErrorEnvelope::deployment_not_found("Deployment not found"),
+        )),
+    }
+}
+
+/// `GET /api/v1/deployments/{hash}/shell?app=<code>&cols=&rows=&idle_timeout=`
+///
+/// Interactive shell in an app container, relayed to the agent. Binary
+/// frames carry terminal bytes; text frames carry `ShellControl` messages.
+#[tracing::instrument(name = "Open deployment shell", skip_all)]
+#[get("/{deployment_hash}/shell")]
+pub async fn shell_handler(
+    path: web::Path<String>,
+    query: web::Query<ShellQuery>,
+    user: web::ReqData<Arc<models::User>>,
+    pg_pool: web::Data<PgPool>,
+    hub: web::Data<Arc<ShellSessionHub>>,
+    settings: web::Data<Settings>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> Result<HttpResponse, Error> {
+    let deployment_hash = path.into_inner();
+    authorize_shell(pg_pool.get_ref(), &deployment_hash, &user).await?;
+
+    let agent = db::agent::fetch_by_deployment_hash(pg_pool.get_ref(), &deployment_hash)
+        .await
+        .map_err(|err| ApiTypedError::internal(TypedErrorEnvelope::internal_error(err)))?;
+    let capabilities = agent
+        .map(|agent| extract_capabilities(agent.capabilities))
+        .unwrap_or_default();
+    if !has_capability(&capabilities, SHELL_CAPABILITY) {
+        return Err(
+            ApiTypedError::bad_request(TypedErrorEnvelope::deployment_capability_missing(format!(
+                "The agent does not advertise the '{}' capability; upgrade the Status Panel agent",
+                SHELL_CAPABILITY
+            )))
+            .into(),
+        );
+    }
+
+    let session_id = hub.new_session_id();
+    let params = OpenShellCommandRequest {
+        session_id: session_id.clone(),
+        app_code: query.app.clone(),
+        shell: query.shell.clone(),
+        cols: query.cols.unwrap_or(80),
+        rows: query.rows.unwrap_or(24),
+        idle_timeout_secs: query
+            .idle_timeout
+            .unwrap_or(DEFAULT_SHELL_IDLE_TIMEOUT_SECS),
+    };
+    let parameters = validate_command_parameters(
+        OPEN_SHELL_COMMAND_TYPE,
+        &Some(serde_json::to_value(&params)?),
+    )
+    .map_err(|err| ApiTypedError::bad_request(TypedErrorEnvelope::invalid_request(err)))?
+    .unwrap_or_default();
+
+    let transcript = ShellTranscript::new(&session_id, &user.id, &params.app_code)
+        .with_input_capture(settings.shell.record_input);
+    let (addr, response) = ws::start_with_addr(
+        UserShellSession {
+            session_id: session_id.clone(),
+            hub: hub.get_ref().clone(),
+            pg_pool: pg_pool.get_ref().clone(),
+            deployment_hash: deployment_hash.clone(),
+            transcript: transcript.clone(),
+            agent: None,
+            idle_timeout: Duration::from_secs(params.idle_timeout_secs as u64),
+            last_input: Instant::now(),
+            hb: Instant::now(),
+            close_reason: None,
+        },
+        &req,
+        payload,
+    )?;
+    hub.register(
+        &session_id,
+        &deployment_hash,
+        addr.clone().recipient(),
+        addr.clone().recipient(),
+    );
+
+    let command = Command::new(
+        format!("cmd_{}", uuid::Uuid::new_v4()),
+        deployment_hash.clone(),
+        OPEN_SHELL_COMMAND_TYPE.to_string(),
+        user.id.clone(),
+    )
+    .with_priority(CommandPriority::High)
+    .with_parameters(parameters)
+    .with_timeout(SHELL_ATTACH_TIMEOUT.as_secs() as i32);
+    let command = match command_queue::enqueue(pg_pool.get_ref(), command, None).await {
+        Ok(command) => command,
+        Err(err) => {
+            tracing::error!(
+                "Failed to queue open_shell for {}: {}",
+                deployment_hash,
+                err.message
+            );
+            addr.do_send(ShellFrame::Control(ShellControl::Error {
+                message: "Failed to ask the agent for a shell".to_string(),
+            }));
+            return Ok(response);
+        }
+    };
+
+    let opened = transcript.opened_payload(&command.command_id, params.cols, params.rows);
+    if let Err(err) = db::agent_audit_log::insert_event(
+        pg_pool.get_ref(),
+        &deployment_hash,
+        SHELL_OPENED_EVENT,
+        &opened,
+    )
+    .await
+    {
+        tracing::warn!("Failed to audit shell session {}: {}", session_id, err);
+    }
+    tracing::info!(
+        session_id = %session_id,
+        user_id = %user.id,
+        app_code = %params.app_code,
+        "Shell session requested for deployment {}",
+        deployment_hash
+    );
+
+    Ok(response)
+}
+
+/// The user's end of a shell session.
+pub struct UserShellSession {
+    session_id: String,
+    hub: Arc<ShellSessionHub>,
+    pg_pool: PgPool,
+    deployment_hash: String,
+    transcript: ShellTranscript,
+    agent: Option<Recipient<ShellFrame>>,
+    idle_timeout: Duration,
+    last_input: Instant,
+    hb: Instant,
+    close_reason: Option<String>,
+}
+
+impl UserShellSession {
+    /// Close the socket, telling the user why when there is a message.
+    fn end(&mut self, ctx: &mut <Self as Actor>::Context, reason: &str, message: Option<String>) {
+        if let Some(message) = message {
+            ctx.text(ShellControl::Error { message }.to_text());
+        }
+        self.close_reason.get_or_insert_with(|| reason.to_string());
+        ctx.close(Some(ws::CloseReason {
+            code: ws::CloseCode::Normal,
+            description: Some(reason.to_string()),
+        }));
+        ctx.stop();
+    }
+
+    fn watch(&self, ctx: &mut <Self as Actor>::Context) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
+                act.end(ctx, "client_timeout", None);
+                return;
+            }
+            ctx.ping(b"");
+        });
+        ctx.run_interval(IDLE_CHECK_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.last_input) > act.idle_timeout {
+                let message = format!("Closed after {}s without input", act.idle_timeout.as_secs());
+                act.end(ctx, "idle_timeout", Some(message));
+            }
+        });
+        ctx.run_later(SHELL_ATTACH_TIMEOUT, |act, ctx| {
+            if act.agent.is_none() {
+                let message = format!(
+                    "The agent did not attach within {}s; check that it is online with `stacker agent status`",
+                    SHELL_ATTACH_TIMEOUT.as_secs()
+                );
+                act.end(ctx, "attach_timeout", Some(message));
+            }
+        });
+    }
+}
+
+impl Actor for UserShellSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.watch(ctx);
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        self.hub.remove(&self.session_id);
+        if let Some(agent) = &self.agent {
+            agent.do_send(ShellFrame::Closed("user disconnected".to_string()));
+        }
+
+        let reason = self.close_reason.as_deref().unwrap_or("client_closed");
+        let closed = self.transcript.closed_payload(reason);
+        let pool = self.pg_pool.clone();
+        let deployment_hash = self.deployment_hash.clone();
+        tracing::info!(
+            session_id = %self.session_id,
+            reason = %reason,
+            "Shell session closed for deployment {}",
+            deployment_hash
+        );
+        actix::spawn(async move {
+            if let Err(err) = db::agent_audit_log::insert_event(
+                &pool,
+                &deployment_hash,
+                SHELL_CLOSED_EVENT,
+                &closed,
+            )
+            .await
+            {
+                tracing::warn!("Failed to audit shell session close: {}", err);
+            }
+        });
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for UserShellSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Binary(data)) => {
+                self.hb = Instant::now();
+                self.last_input = Instant::now();
+                if let Some(agent) = &self.agent {
+                    self.transcript.record_input(&data);
+                    agent.do_send(ShellFrame::Data(data.to_vec()));
+                }
+            }
+            Ok(ws::Message::Text(text)) => {
+                self.hb = Instant::now();
+                match serde_json::from_str::<ShellControl>(&text) {
+                    Ok(control @ ShellControl::Resize { .. }) => {
+                        if let Some(agent) = &self.agent {
+                            agent.do_send(ShellFrame::Control(control));
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => tracing::debug!("Ignoring shell control frame: {}", err),
+                }
+            }
+            Ok(ws::Message::Ping(msg)) => {
+                self.hb = Instant::now();
+                ctx.pong(&msg);
+            }
+            Ok(ws::Message::Pong(_)) => {
+                self.hb = Instant::now();
+            }
+            Ok(ws::Message::Close(_)) => self.end(ctx, "client_closed", None),
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!("Shell session protocol error: {}", err);
+                self.end(ctx, "protocol_error", None);
+            }
+        }
+    }
+}
+
+impl Handler<AgentAttached> for UserShellSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: AgentAttached, ctx: &mut Self::Context) {
+        self.agent = Some(msg.0);
+        self.last_input = Instant::now();
+        ctx.text(
+            ShellControl::Ready {
+                session_id: self.session_id.clone(),
+            }
+            .to_text(),
+        );
+    }
+}
+
+impl Handler<ShellFrame> for UserShellSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: ShellFrame, ctx: &mut Self::Context) {
+        match msg {
+            ShellFrame::Data(data) => {
+                self.transcript.record_output(&data);
+                ctx.binary(data);
+            }
+            ShellFrame::Control(ShellControl::Exit { code }) => {
+                self.transcript.exit_code = code;
+                ctx.text(ShellControl::Exit { code }.to_text());
+                self.end(ctx, "exited", None);
+            }
+            ShellFrame::Control(ShellControl::Error { message }) => {
+                self.end(ctx, "error", Some(message));
+            }
+            ShellFrame::Control(control) => ctx.text(control.to_text()),
+            ShellFrame::Closed(reason) => {
+                self.end(ctx, "agent_disconnected", Some(format!("Agent {}", reason)));
+            }
+        }
+    }
+}
@@ -24,8 +24,12 @@ pub(crate) mod pipe;
 
 pub use agreement::*;
 pub use deployment::{
-    capabilities_handler, events_handler, force_complete_handler, list_handler, plan_handler,
-    state_handler, status_by_project_handler, status_handler, DeploymentListQuery,
+    acquire_lease_handler, capabilities_handler, container_metrics_handler, create_preview_handler,
+    create_release_handler, create_schedule_handler, delete_preview_handler,
+    delete_schedule_handler, events_handler, force_complete_handler, list_backups_handler,
+    list_handler, list_previews_handler, list_releases_handler, list_schedules_handler,
+    plan_handler, release_lease_handler, rollback_release_handler, search_logs_handler,
+    shell_handler, state_handler, status_by_project_handler, status_handler, DeploymentListQuery,
     DeploymentStatusResponse,
 };
 pub use marketplace::{
@@ -2,6 +2,7 @@ use crate::db;
 use crate::helpers::{AgentPgPool, JsonResponse};
 use crate::models::pipe::PipeExecution;
 use crate::models::{Command, CommandPriority, User};
+use crate::services::command_queue;
 use actix_web::{get, post, web, Responder, Result};
 use serde::Deserialize;
 use sqlx::PgPool;
@@ -170,19 +171,13 @@ pub async fn replay_execution_handler(
         .with_priority(CommandPriority::Normal)
         .with_parameters(trigger_params);
 
-        match db::command::insert(agent_pool.as_ref(), &command).await {
-            Ok(saved) => {
-                let _ = db::command::add_to_queue(
-                    agent_pool.as_ref(),
-                    &saved.command_id,
-                    &saved.deployment_hash,
-                    &CommandPriority::Normal,
-                )
-                .await;
-                Some(saved.command_id)
-            }
+        match command_queue::enqueue(agent_pool.as_ref(), command, None).await {
+            Ok(saved) => Some(saved.command_id),
             Err(e) => {
-                tracing::warn!("Failed to enqueue replay trigger_pipe command: {}", e);
+                tracing::warn!(
+                    "Failed to enqueue replay trigger_pipe command: {}",
+                    e.message
+                );
                 None
             }
         }
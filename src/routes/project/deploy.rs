@@ -615,6 +615,9 @@ async fn validate_template_server_capacity_requirements(
 #[derive(Debug, Deserialize)]
 pub struct RollbackRequest {
     pub version: String,
+    /// The caller's deploy lease, when it holds one.
+    #[serde(default)]
+    pub lease_id: Option<String>,
 }
 
 fn build_rollback_project_payload(
@@ -2172,6 +2175,21 @@ pub async fn rollback(
         JsonResponse::<models::Project>::build()
             .bad_request("Rollback is only available for marketplace projects")
     })?;
+
+    // A rollback redeploys the stack; refuse it while another client holds
+    // the deploy lease.
+    let deployment = db::deployment::fetch_by_project_id(pg_pool.get_ref(), project.id)
+        .await
+        .map_err(|err| JsonResponse::<models::Project>::build().internal_server_error(err))?;
+    if let Some(deployment) = deployment {
+        services::deployment_lease::ensure_lease_holder(
+            pg_pool.get_ref(),
+            &deployment.deployment_hash,
+            request.lease_id.as_deref(),
+        )
+        .await
+        .map_err(services::command_queue::api_error)?;
+    }
     let template = db::marketplace::get_by_id(pg_pool.get_ref(), template_id)
         .await
         .map_err(|err| JsonResponse::<models::Project>::build().internal_server_error(err))?
@@ -0,0 +1,273 @@
+//! Capability negotiation between Stacker and the Status Panel agent.
+//!
+//! Agents that register with an [`AgentCapabilityManifest`] declare the
+//! command types they run and the parameter schema version of each. Before a
+//! command is queued, [`negotiate`] compares it with the schema Stacker sends
+//! today: fields newer than the agent's schema are dropped when the agent can
+//! do without them and refused otherwise. Agents without a manifest predate
+//! negotiation and are not gated; [`assess`] reports them as outdated.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::helpers::{parse_manifest, AgentCapabilityManifest, LEGACY_AGENT_PROTOCOL_VERSION};
+use crate::models::Agent;
+
+/// Protocol version this server speaks.
+pub const AGENT_PROTOCOL_VERSION: u32 = 2;
+/// Oldest manifest protocol version commands are still sent to.
+pub const MIN_AGENT_PROTOCOL_VERSION: u32 = 1;
+pub const UPGRADE_AGENT_COMMAND_TYPE: &str = "upgrade_agent";
+
+/// A command parameter introduced in a later schema version.
+struct SchemaField {
+    command_type: &'static str,
+    version: u32,
+    field: &'static str,
+    /// Whether the command still does the right thing without the field.
+    droppable: bool,
+}
+
+const SCHEMA_FIELDS: &[SchemaField] = &[
+    SchemaField {
+        command_type: "deploy_app",
+        version: 2,
+        field: "blue_green",
+        droppable: false,
+    },
+    SchemaField {
+        command_type: "deploy_app",
+        version: 3,
+        field: "rollout",
+        droppable: true,
+    },
+    SchemaField {
+        command_type: "deploy_app",
+        version: 4,
+        field: "compose_project",
+        droppable: false,
+    },
+    SchemaField {
+        command_type: "remove_app",
+        version: 2,
+        field: "compose_project",
+        droppable: false,
+    },
+];
+
+/// Parameter schema version Stacker sends for `command_type`.
+pub fn schema_version(command_type: &str) -> u32 {
+    SCHEMA_FIELDS
+        .iter()
+        .filter(|field| field.command_type == command_type)
+        .map(|field| field.version)
+        .max()
+        .unwrap_or(1)
+}
+
+/// Parameters to queue after negotiation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegotiatedCommand {
+    pub parameters: Option<Value>,
+    /// Fields removed because the agent's schema predates them.
+    pub dropped_fields: Vec<&'static str>,
+}
+
+/// Check `command_type` against the agent's manifest, dropping parameters it
+/// cannot read. The error explains why the agent cannot run the command.
+pub fn negotiate(
+    manifest: Option<&AgentCapabilityManifest>,
+    command_type: &str,
+    parameters: Option<Value>,
+) -> Result<NegotiatedCommand, String> {
+    let supported = NegotiatedCommand {
+        parameters,
+        dropped_fields: Vec::new(),
+    };
+    let Some(manifest) = manifest else {
+        return Ok(supported);
+    };
+    // Outdated agents must stay upgradable.
+    if command_type == UPGRADE_AGENT_COMMAND_TYPE {
+        return Ok(supported);
+    }
+    if manifest.protocol_version < MIN_AGENT_PROTOCOL_VERSION {
+        return Err(format!(
+            "Agent speaks protocol v{}, but this server needs at least v{}. Run `stacker agent upgrade`.",
+            manifest.protocol_version, MIN_AGENT_PROTOCOL_VERSION
+        ));
+    }
+    let Some(&agent_version) = manifest.commands.get(command_type) else {
+        return Err(format!(
+            "Agent does not support {}. Run `stacker agent upgrade` to update it.",
+            command_type
+        ));
+    };
+
+    let mut negotiated = supported;
+    for schema_field in SCHEMA_FIELDS
+        .iter()
+        .filter(|field| field.command_type == command_type && field.version > agent_version)
+    {
+        let Some(params) = negotiated
+            .parameters
+            .as_mut()
+            .and_then(|params| params.as_object_mut())
+        else {
+            break;
+        };
+        if params.get(schema_field.field).is_none_or(Value::is_null) {
+            continue;
+        }
+        if !schema_field.droppable {
+            return Err(format!(
+                "Agent supports {} schema v{}, but {} needs v{}. Run `stacker agent upgrade`.",
+                command_type, agent_version, schema_field.field, schema_field.version
+            ));
+        }
+        params.remove(schema_field.field);
+        negotiated.dropped_fields.push(schema_field.field);
+    }
+    Ok(negotiated)
+}
+
+/// How an agent compares with this server and the latest agent release.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentCompatibility {
+    pub protocol_version: u32,
+    pub server_protocol_version: u32,
+    pub version: Option<String>,
+    pub latest_version: Option<String>,
+    pub outdated: bool,
+    pub reasons: Vec<String>,
+    /// Command types the agent runs with an older parameter schema.
+    pub outdated_commands: Vec<String>,
+}
+
+pub fn assess(agent: &Agent, latest_version: Option<&str>) -> AgentCompatibility {
+    let manifest = parse_manifest(agent.capability_manifest.clone());
+    let protocol_version = manifest
+        .as_ref()
+        .map_or(LEGACY_AGENT_PROTOCOL_VERSION, |manifest| {
+            manifest.protocol_version
+        });
+    let mut reasons = Vec::new();
+
+    if manifest.is_none() {
+        reasons.push("agent does not report a capability manifest".to_string());
+    } else if protocol_version < AGENT_PROTOCOL_VERSION {
+        reasons.push(format!(
+            "agent speaks protocol v{}, server speaks v{}",
+            protocol_version, AGENT_PROTOCOL_VERSION
+        ));
+    }
+
+    let mut outdated_commands = Vec::new();
+    if let Some(manifest) = &manifest {
+        for (command_type, version) in &manifest.commands {
+            let current = schema_version(command_type);
+            if *version < current {
+                reasons.push(format!(
+                    "{} schema v{}, server sends v{}",
+                    command_type, version, current
+                ));
+                outdated_commands.push(command_type.clone());
+            }
+        }
+    }
+
+    if let (Some(version), Some(latest)) = (agent.version.as_deref(), latest_version) {
+        if is_older_version(version, latest) {
+            reasons.push(format!("version {}, latest is {}", version, latest));
+        }
+    }
+
+    AgentCompatibility {
+        protocol_version,
+        server_protocol_version: AGENT_PROTOCOL_VERSION,
+        version: agent.version.clone(),
+        latest_version: latest_version.map(str::to_string),
+        outdated: !reasons.is_empty(),
+        reasons,
+        outdated_commands,
+    }
+}
+
+/// Compare dotted numeric versions such as `v0.4.2`; pre-release and build
+/// suffixes are ignored.
+pub fn is_older_version(version: &str, latest: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        let core = version.trim().trim_start_matches('v');
+        let core = core.split(['-', '+']).next().unwrap_or_default();
+        core.split('.')
+            .map(|part| {
+                part.chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    let (mut version, mut latest) = (parts(version), parts(latest));
+    let len = version.len().max(latest.len());
+    version.resize(len, 0);
+    latest.resize(len, 0);
+    version < latest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest(deploy_app: u32) -> AgentCapabilityManifest {
+        AgentCapabilityManifest {
+            protocol_version: AGENT_PROTOCOL_VERSION,
+            commands: [("deploy_app".to_string(), deploy_app)].into(),
+            features: vec!["kata".to_string()],
+        }
+    }
+
+    #[test]
+    fn negotiation_drops_optional_fields_and_refuses_required_ones() {
+        let params = Some(json!({"app_code": "web", "rollout": {"step": 1}}));
+        let negotiated = negotiate(Some(&manifest(2)), "deploy_app", params.clone()).unwrap();
+        assert_eq!(negotiated.dropped_fields, vec!["rollout"]);
+        assert_eq!(negotiated.parameters, Some(json!({"app_code": "web"})));
+        assert_eq!(
+            negotiate(Some(&manifest(4)), "deploy_app", params.clone())
+                .unwrap()
+                .dropped_fields,
+            Vec::<&str>::new()
+        );
+
+        let preview = Some(json!({"app_code": "web", "compose_project": "pr-12"}));
+        assert!(negotiate(Some(&manifest(3)), "deploy_app", preview.clone()).is_err());
+        assert!(negotiate(Some(&manifest(4)), "restart", None).is_err());
+        assert!(negotiate(Some(&manifest(1)), UPGRADE_AGENT_COMMAND_TYPE, None).is_ok());
+        assert!(negotiate(None, "deploy_app", preview).is_ok());
+    }
+
+    #[test]
+    fn assess_flags_legacy_and_behind_agents() {
+        let mut agent = Agent::new("hash".to_string());
+        agent.version = Some("0.3.9".to_string());
+        let legacy = assess(&agent, Some("0.4.0"));
+        assert!(legacy.outdated);
+        assert_eq!(legacy.protocol_version, LEGACY_AGENT_PROTOCOL_VERSION);
+        assert_eq!(legacy.reasons.len(), 2);
+
+        agent.version = Some("v0.4.0".to_string());
+        agent.capability_manifest = serde_json::to_value(manifest(4)).ok();
+        assert!(!assess(&agent, Some("0.4.0")).outdated);
+
+        agent.capability_manifest = serde_json::to_value(manifest(3)).ok();
+        let behind = assess(&agent, None);
+        assert_eq!(behind.outdated_commands, vec!["deploy_app".to_string()]);
+        assert!(is_older_version("0.9.1", "0.10.0"));
+        assert!(!is_older_version("1.0.0-rc.1", "1.0"));
+    }
+}
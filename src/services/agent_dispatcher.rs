@@ -1,6 +1,7 @@
 use crate::{
     db, helpers,
     models::{Command, CommandPriority},
+    services::command_queue,
 };
 use helpers::VaultClient;
 use serde_json::Value;
@@ -41,18 +42,9 @@ impl<'a> AgentDispatcher<'a> {
         .with_priority(CommandPriority::Normal)
         .with_parameters(parameters);
 
-        db::command::insert(self.pg, &command)
+        command_queue::enqueue(self.pg, command, None)
             .await
-            .map_err(|e| format!("Failed to insert command: {}", e))?;
-
-        db::command::add_to_queue(
-            self.pg,
-            &command_id,
-            &deployment.deployment_hash,
-            &CommandPriority::Normal,
-        )
-        .await
-        .map_err(|e| format!("Failed to queue command: {}", e))?;
+            .map_err(|e| format!("Failed to queue command: {}", e.message))?;
 
         tracing::info!(
             deployment_id = deployment_id,
@@ -75,7 +67,7 @@ pub async fn rotate_token(
     new_token: &str,
 ) -> Result<(), String> {
     // Ensure agent exists for the deployment
-    let _ = db::agent::fetch_by_deployment_hash(pg, deployment_hash)
+    let agent = db::agent::fetch_by_deployment_hash(pg, deployment_hash)
         .await
         .map_err(|e| format!("DB error: {}", e))?
         .ok_or_else(|| "Agent not found for deployment_hash".to_string())?;
@@ -86,5 +78,8 @@ pub async fn rotate_token(
         .await
         .map_err(|e| format!("Vault store error: {}", e))?;
 
+    // A manual rotation supersedes any offered or overlapping token
+    db::agent_identity::reset_token_rotation(pg, agent.id).await?;
+
     Ok(())
 }
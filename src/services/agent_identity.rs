@@ -0,0 +1,313 @@
+//! Agent token rotation and mTLS client certificates.
+//!
+//! Rotation is negotiated over the poll channel. When an agent's token is
+//! older than `token_rotation_hours`, [`offer_token_rotation`] hands it a new
+//! token in the poll meta and records its hash. The agent switches by simply
+//! sending the new token; [`accept_rotated_token`] then stores it in Vault and
+//! keeps the replaced token working for `token_overlap_secs`, so in-flight
+//! requests and streams are not cut off. Only agents that report the
+//! `token_rotation` capability are offered tokens.
+//!
+//! In mTLS mode Stacker signs agent CSRs through the Vault PKI engine and
+//! records the certificate fingerprints; `f_agent` matches the certificate
+//! forwarded by the TLS-terminating proxy against them.
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::configuration::AgentIdentitySettings;
+use crate::db;
+use crate::helpers::agent_capabilities::{extract_capabilities, has_capability};
+use crate::helpers::VaultClient;
+use crate::models::{Agent, AgentTokenRotation, AuditLog};
+
+pub const TOKEN_ROTATION_CAPABILITY: &str = "token_rotation";
+/// Poll meta key carrying a [`TokenRotationOffer`].
+pub const TOKEN_ROTATION_META_KEY: &str = "token_rotation";
+
+/// Generate a secure random agent token (86 characters)
+pub fn generate_agent_token() -> String {
+    use rand::Rng;
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut rng = rand::thread_rng();
+    (0..86)
+        .map(|_| {
+            let idx = rng.gen_range(0..CHARSET.len());
+            CHARSET[idx] as char
+        })
+        .collect()
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// New token offered to the agent. It must switch before `switch_by`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenRotationOffer {
+    pub token: String,
+    pub switch_by: DateTime<Utc>,
+}
+
+/// Whether the agent should be offered a new token now.
+pub fn rotation_due(
+    settings: &AgentIdentitySettings,
+    agent: &Agent,
+    state: Option<&AgentTokenRotation>,
+    now: DateTime<Utc>,
+) -> bool {
+    if settings.token_rotation_hours == 0 {
+        return false;
+    }
+    let offer_outstanding = state
+        .and_then(|state| state.pending_expires_at)
+        .is_some_and(|expires_at| expires_at > now);
+    if offer_outstanding {
+        return false;
+    }
+    let rotated_at = state
+        .and_then(|state| state.rotated_at)
+        .unwrap_or(agent.created_at);
+    now - rotated_at >= Duration::hours(settings.token_rotation_hours as i64)
+}
+
+/// Which rotation token a bearer token matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotatedTokenMatch {
+    /// The offered token: the agent has switched.
+    Pending,
+    /// The replaced token, still inside the overlap window.
+    Previous,
+}
+
+pub fn match_rotated_token(
+    state: &AgentTokenRotation,
+    token_hash: &str,
+    now: DateTime<Utc>,
+) -> Option<RotatedTokenMatch> {
+    let matches = |hash: &Option<String>, expires_at: Option<DateTime<Utc>>| {
+        hash.as_deref() == Some(token_hash) && expires_at.is_some_and(|expires_at| expires_at > now)
+    };
+    if matches(&state.pending_token_hash, state.pending_expires_at) {
+        Some(RotatedTokenMatch::Pending)
+    } else if matches(&state.previous_token_hash, state.previous_expires_at) {
+        Some(RotatedTokenMatch::Previous)
+    } else {
+        None
+    }
+}
+
+/// Offer the agent a new token when its current one is due for rotation.
+pub async fn offer_token_rotation(
+    pool: &PgPool,
+    settings: &AgentIdentitySettings,
+    agent: &Agent,
+) -> Result<Option<TokenRotationOffer>, String> {
+    let capabilities = extract_capabilities(agent.capabilities.clone());
+    if !has_capability(&capabilities, TOKEN_ROTATION_CAPABILITY) {
+        return Ok(None);
+    }
+    let state = db::agent_identity::fetch_token_rotation(pool, agent.id).await?;
+    let now = Utc::now();
+    if !rotation_due(settings, agent, state.as_ref(), now) {
+        return Ok(None);
+    }
+
+    let token = generate_agent_token();
+    let switch_by = now + Duration::seconds(settings.token_overlap_secs as i64);
+    db::agent_identity::offer_token(pool, agent.id, &hash_token(&token), switch_by).await?;
+    tracing::info!(agent_id = %agent.id, "Offering rotated token to agent");
+    Ok(Some(TokenRotationOffer { token, switch_by }))
+}
+
+/// Accept a bearer token that differs from the one in Vault when it is the
+/// offered token (completing the rotation) or the replaced one within the
+/// overlap window.
+pub async fn accept_rotated_token(
+    pool: &PgPool,
+    vault: &VaultClient,
+    settings: &AgentIdentitySettings,
+    agent: &Agent,
+    bearer_token: &str,
+    stored_token: &str,
+) -> Result<bool, String> {
+    let Some(state) = db::agent_identity::fetch_token_rotation(pool, agent.id).await? else {
+        return Ok(false);
+    };
+    let bearer_hash = hash_token(bearer_token);
+    match match_rotated_token(&state, &bearer_hash, Utc::now()) {
+        Some(RotatedTokenMatch::Previous) => Ok(true),
+        Some(RotatedTokenMatch::Pending) => {
+            // Vault first: if promotion fails the offer is still valid.
+            vault
+                .store_agent_token(&agent.deployment_hash, bearer_token)
+                .await?;
+            let previous_expires_at =
+                Utc::now() + Duration::seconds(settings.token_overlap_secs as i64);
+            let promoted = db::agent_identity::promote_token(
+                pool,
+                agent.id,
+                &bearer_hash,
+                &hash_token(stored_token),
+                previous_expires_at,
+            )
+            .await?;
+            if promoted {
+                let audit_log = AuditLog::new(
+                    Some(agent.id),
+                    Some(agent.deployment_hash.clone()),
+                    "agent.token_rotated".to_string(),
+                    Some("success".to_string()),
+                )
+                .with_details(serde_json::json!({
+                    "previous_token_expires_at": previous_expires_at,
+                }));
+                if let Err(err) = db::agent::log_audit(pool, audit_log).await {
+                    tracing::warn!("Failed to log token rotation audit: {:?}", err);
+                }
+            }
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// SHA-256 fingerprint (lowercase hex) of a certificate forwarded by the
+/// proxy: URL-encoded PEM (nginx `$ssl_client_escaped_cert`) or base64 DER,
+/// with or without PEM armor.
+pub fn client_certificate_fingerprint(raw: &str) -> Option<String> {
+    let decoded = urlencoding::decode(raw).ok()?;
+    let body = match decoded.find("-----BEGIN CERTIFICATE-----") {
+        Some(start) => {
+            let body = &decoded[start + "-----BEGIN CERTIFICATE-----".len()..];
+            &body[..body.find("-----END CERTIFICATE-----")?]
+        }
+        None => decoded.as_ref(),
+    };
+    let body: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+    if body.is_empty() {
+        return None;
+    }
+    let der = general_purpose::STANDARD.decode(body).ok()?;
+    Some(format!("{:x}", Sha256::digest(der)))
+}
+
+/// Client certificate handed to the agent.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedAgentCertificate {
+    pub certificate: String,
+    pub ca_chain: Vec<String>,
+    pub serial_number: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Sign the agent's CSR and record the certificate's fingerprint.
+pub async fn issue_certificate(
+    pool: &PgPool,
+    vault: &VaultClient,
+    settings: &AgentIdentitySettings,
+    agent: &Agent,
+    csr: &str,
+) -> Result<IssuedAgentCertificate, String> {
+    let signed = vault
+        .sign_certificate(
+            &settings.pki_mount,
+            &settings.pki_role,
+            csr,
+            &agent.id.to_string(),
+            settings.certificate_ttl_hours,
+        )
+        .await?;
+    let fingerprint = client_certificate_fingerprint(&signed.certificate)
+        .ok_or_else(|| "Vault returned an unreadable certificate".to_string())?;
+    let expires_at = DateTime::from_timestamp(signed.expiration, 0)
+        .ok_or_else(|| "Vault returned an invalid certificate expiration".to_string())?;
+    db::agent_identity::insert_certificate(
+        pool,
+        agent.id,
+        &signed.serial_number,
+        &fingerprint,
+        expires_at,
+    )
+    .await?;
+
+    Ok(IssuedAgentCertificate {
+        certificate: signed.certificate,
+        ca_chain: signed.ca_chain,
+        serial_number: signed.serial_number,
+        expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_is_due_after_interval_unless_an_offer_is_outstanding() {
+        let settings = AgentIdentitySettings::default();
+        let now = Utc::now();
+        let mut agent = Agent::new("hash".to_string());
+        agent.created_at = now - Duration::hours(1);
+        assert!(!rotation_due(&settings, &agent, None, now));
+
+        agent.created_at = now - Duration::hours(settings.token_rotation_hours as i64);
+        assert!(rotation_due(&settings, &agent, None, now));
+
+        let mut state = AgentTokenRotation {
+            pending_token_hash: Some(hash_token("next")),
+            pending_expires_at: Some(now + Duration::minutes(5)),
+            previous_token_hash: Some(hash_token("old")),
+            previous_expires_at: Some(now + Duration::minutes(5)),
+            ..Default::default()
+        };
+        assert!(!rotation_due(&settings, &agent, Some(&state), now));
+        assert_eq!(
+            match_rotated_token(&state, &hash_token("next"), now),
+            Some(RotatedTokenMatch::Pending)
+        );
+        assert_eq!(
+            match_rotated_token(&state, &hash_token("old"), now),
+            Some(RotatedTokenMatch::Previous)
+        );
+        assert_eq!(match_rotated_token(&state, &hash_token("other"), now), None);
+
+        state.previous_expires_at = Some(now - Duration::seconds(1));
+        assert_eq!(match_rotated_token(&state, &hash_token("old"), now), None);
+
+        let disabled = AgentIdentitySettings {
+            token_rotation_hours: 0,
+            ..Default::default()
+        };
+        assert!(!rotation_due(&disabled, &agent, None, now));
+    }
+
+    #[test]
+    fn fingerprint_accepts_escaped_pem_and_bare_base64() {
+        let der = b"not really a certificate";
+        let body = general_purpose::STANDARD.encode(der);
+        let expected = format!("{:x}", Sha256::digest(der));
+        let pem = format!(
+            "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+            body
+        );
+
+        assert_eq!(
+            client_certificate_fingerprint(&pem).as_deref(),
+            Some(expected.as_str())
+        );
+        assert_eq!(
+            client_certificate_fingerprint(&urlencoding::encode(&pem)).as_deref(),
+            Some(expected.as_str())
+        );
+        assert_eq!(
+            client_certificate_fingerprint(&body).as_deref(),
+            Some(expected.as_str())
+        );
+        assert_eq!(client_certificate_fingerprint(""), None);
+        assert_eq!(client_certificate_fingerprint("%%%"), None);
+    }
+}
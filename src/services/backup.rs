@@ -0,0 +1,137 @@
+//! Volume backups taken by the Status Panel agent.
+//!
+//! Backups are not stored in a table of their own: each successful
+//! `backup_volume` command result describes one backup, and later results
+//! list the backup ids their retention policy removed.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{
+    db,
+    forms::status_panel::{
+        BackupArtifact, BackupStatus, BackupTarget, BackupVolumeCommandReport, DatabaseEngine,
+    },
+    models::Command,
+};
+
+pub const BACKUP_COMMAND_TYPE: &str = "backup_volume";
+pub const RESTORE_COMMAND_TYPE: &str = "restore_volume";
+/// Backup commands scanned when listing; retention keeps far fewer.
+const BACKUP_HISTORY_LIMIT: i64 = 200;
+
+/// One stored backup, as returned by `GET /api/v1/deployments/{hash}/backups`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRecord {
+    pub backup_id: String,
+    pub app_code: String,
+    pub command_id: String,
+    pub created_at: DateTime<Utc>,
+    pub target: BackupTarget,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<DatabaseEngine>,
+    pub artifacts: Vec<BackupArtifact>,
+    pub size_bytes: u64,
+}
+
+/// Backups still present, newest first, from completed `backup_volume`
+/// commands ordered newest first.
+pub fn backups_from_commands(commands: &[Command]) -> Vec<BackupRecord> {
+    let reports: Vec<(&Command, BackupVolumeCommandReport)> = commands
+        .iter()
+        .filter_map(|command| {
+            let result = command.result.clone()?;
+            serde_json::from_value::<BackupVolumeCommandReport>(result)
+                .ok()
+                .map(|report| (command, report))
+        })
+        .collect();
+    let pruned: HashSet<&str> = reports
+        .iter()
+        .flat_map(|(_, report)| report.pruned.iter().map(String::as_str))
+        .collect();
+
+    reports
+        .iter()
+        .filter(|(_, report)| report.status == BackupStatus::Ok)
+        .filter(|(_, report)| !pruned.contains(report.backup_id.as_str()))
+        .map(|(command, report)| BackupRecord {
+            backup_id: report.backup_id.clone(),
+            app_code: report.app_code.clone(),
+            command_id: command.command_id.clone(),
+            created_at: command.updated_at,
+            target: report.target.clone(),
+            database: report.database,
+            artifacts: report.artifacts.clone(),
+            size_bytes: report.size_bytes(),
+        })
+        .collect()
+}
+
+pub async fn list_backups(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+) -> Result<Vec<BackupRecord>, String> {
+    let commands = db::command::fetch_completed_by_type(
+        pg_pool,
+        deployment_hash,
+        BACKUP_COMMAND_TYPE,
+        BACKUP_HISTORY_LIMIT,
+    )
+    .await?;
+    Ok(backups_from_commands(&commands))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn backup_command(command_id: &str, result: serde_json::Value) -> Command {
+        let mut command = Command::new(
+            command_id.to_string(),
+            "dep_1".to_string(),
+            BACKUP_COMMAND_TYPE.to_string(),
+            "user_1".to_string(),
+        );
+        command.status = "completed".to_string();
+        command.result = Some(result);
+        command
+    }
+
+    #[test]
+    fn lists_successful_backups_not_pruned_later() {
+        let report = |backup_id: &str, status: &str, pruned: &[&str]| {
+            json!({
+                "type": "backup_volume",
+                "deployment_hash": "dep_1",
+                "app_code": "db",
+                "backup_id": backup_id,
+                "status": status,
+                "database": "postgres",
+                "artifacts": [
+                    {"name": "database", "location": "/var/backups/db.sql.gz", "size_bytes": 300},
+                    {"name": "db_data", "location": "/var/backups/db_data.tar.gz", "size_bytes": 700}
+                ],
+                "pruned": pruned,
+            })
+        };
+        // Newest first, as the query returns them.
+        let commands = vec![
+            backup_command("cmd_4", report("bk_4", "ok", &["bk_1"])),
+            backup_command("cmd_3", report("bk_3", "failed", &[])),
+            backup_command("cmd_2", report("bk_2", "ok", &[])),
+            backup_command("cmd_1", report("bk_1", "ok", &[])),
+        ];
+
+        let backups = backups_from_commands(&commands);
+        let ids: Vec<&str> = backups.iter().map(|b| b.backup_id.as_str()).collect();
+        assert_eq!(ids, vec!["bk_4", "bk_2"]);
+        assert_eq!(backups[0].command_id, "cmd_4");
+        assert_eq!(backups[0].size_bytes, 1000);
+        assert_eq!(backups[0].database, Some(DatabaseEngine::Postgres));
+    }
+}
@@ -0,0 +1,317 @@
+//! Command batches: agent commands that declare `depends_on` within their
+//! batch.
+//!
+//! Membership is stored under `metadata.batch` of each command (see
+//! [`CommandBatchRef`]). Steps without dependencies are queued on creation;
+//! the rest are held and released by [`advance_batch`] once every step they
+//! depend on has completed. A failed or cancelled step cancels everything
+//! downstream of it, and so does a held step whose deploy lease changed
+//! hands before it was released.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::db;
+use crate::models::{Command, CommandBatchRef, CommandPriority, BATCH_METADATA_KEY};
+use crate::services::command_queue::{self, LEASED_COMMAND_TYPES};
+use crate::services::deployment_lease::ensure_lease_holder;
+use crate::services::{TypedErrorCode, TypedErrorEnvelope};
+
+/// Upper bound on steps in one batch.
+pub const MAX_BATCH_STEPS: usize = 50;
+
+/// Check step names are unique and non-empty, dependencies name earlier or
+/// later steps of the same batch, and there is no cycle.
+pub fn validate_batch_steps(steps: &[(String, Vec<String>)]) -> Result<(), String> {
+    if steps.is_empty() {
+        return Err("A batch needs at least one command".to_string());
+    }
+    if steps.len() > MAX_BATCH_STEPS {
+        return Err(format!(
+            "A batch holds at most {} commands, got {}",
+            MAX_BATCH_STEPS,
+            steps.len()
+        ));
+    }
+
+    let mut names = HashSet::new();
+    for (step, _) in steps {
+        if step.trim().is_empty() {
+            return Err("Every batch command needs a step name".to_string());
+        }
+        if !names.insert(step.as_str()) {
+            return Err(format!("Duplicate batch step '{}'", step));
+        }
+    }
+    for (step, depends_on) in steps {
+        for dependency in depends_on {
+            if dependency == step {
+                return Err(format!("Step '{}' depends on itself", step));
+            }
+            if !names.contains(dependency.as_str()) {
+                return Err(format!(
+                    "Step '{}' depends on unknown step '{}'",
+                    step, dependency
+                ));
+            }
+        }
+    }
+
+    // Kahn's algorithm: every step must become ready eventually.
+    let mut remaining: HashMap<&str, usize> = steps
+        .iter()
+        .map(|(step, depends_on)| (step.as_str(), depends_on.len()))
+        .collect();
+    let mut ready: Vec<&str> = remaining
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(step, _)| *step)
+        .collect();
+    let mut resolved = 0;
+    while let Some(done) = ready.pop() {
+        resolved += 1;
+        for (step, depends_on) in steps {
+            if depends_on.iter().any(|dependency| dependency == done) {
+                let count = remaining
+                    .get_mut(step.as_str())
+                    .expect("every step is counted");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(step.as_str());
+                }
+            }
+        }
+    }
+    if resolved != steps.len() {
+        return Err("Batch dependencies form a cycle".to_string());
+    }
+    Ok(())
+}
+
+/// What to do with held members after a step changed status.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BatchAdvance {
+    /// Steps whose dependencies all completed.
+    pub release: Vec<String>,
+    /// Steps to cancel, with the dependency that did not complete.
+    pub cancel: Vec<(String, String)>,
+}
+
+/// Decide which held members to release or cancel, given each member's
+/// batch reference and current status.
+pub fn plan_advance(members: &[(CommandBatchRef, String)]) -> BatchAdvance {
+    let mut status: HashMap<&str, &str> = members
+        .iter()
+        .map(|(batch, status)| (batch.step.as_str(), status.as_str()))
+        .collect();
+    let mut advance = BatchAdvance::default();
+
+    // Cancellation cascades: a cancelled step cancels its own dependents.
+    loop {
+        let mut changed = false;
+        for (batch, _) in members {
+            if status.get(batch.step.as_str()) != Some(&"queued") {
+                continue;
+            }
+            if let Some(failed) = batch.depends_on.iter().find(|dependency| {
+                matches!(
+                    status.get(dependency.as_str()),
+                    Some(&"failed") | Some(&"cancelled")
+                )
+            }) {
+                status.insert(batch.step.as_str(), "cancelled");
+                advance.cancel.push((batch.step.clone(), failed.clone()));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    for (batch, _) in members {
+        if batch.depends_on.is_empty() || status.get(batch.step.as_str()) != Some(&"queued") {
+            continue;
+        }
+        if batch
+            .depends_on
+            .iter()
+            .all(|dependency| status.get(dependency.as_str()) == Some(&"completed"))
+        {
+            advance.release.push(batch.step.clone());
+        }
+    }
+    advance
+}
+
+/// Insert the members of a new batch and queue the steps that depend on
+/// nothing, all in one transaction. `commands` must already carry their
+/// `metadata.batch` reference.
+pub async fn enqueue_batch(
+    pool: &PgPool,
+    commands: Vec<Command>,
+    lease_id: Option<&str>,
+) -> Result<Vec<Command>, TypedErrorEnvelope> {
+    command_queue::enqueue_all(pool, commands, lease_id).await
+}
+
+/// Release or cancel held members of `command`'s batch after it reached a
+/// terminal status. A no-op for commands outside a batch.
+pub async fn advance_batch(pool: &PgPool, command: &Command) -> Result<(), String> {
+    let Some(batch) = command.batch() else {
+        return Ok(());
+    };
+    let members = db::command::fetch_by_batch(pool, &command.deployment_hash, &batch.id).await?;
+    let refs: Vec<(CommandBatchRef, String)> = members
+        .iter()
+        .filter_map(|member| member.batch().map(|batch| (batch, member.status.clone())))
+        .collect();
+    let by_step: HashMap<String, &Command> = members
+        .iter()
+        .filter_map(|member| member.batch().map(|batch| (batch.step, member)))
+        .collect();
+
+    let advance = plan_advance(&refs);
+    for (step, dependency) in &advance.cancel {
+        if let Some(member) = by_step.get(step) {
+            let error = json!({
+                "code": "dependency_failed",
+                "message": format!(
+                    "Cancelled: step '{}' of batch {} did not complete",
+                    dependency, batch.id
+                ),
+            });
+            db::command::cancel_pending(pool, &member.command_id, error).await?;
+            tracing::info!(
+                batch_id = %batch.id,
+                command_id = %member.command_id,
+                "Cancelled batch step '{}' after '{}' did not complete",
+                step,
+                dependency
+            );
+        }
+    }
+    let mut lease_cancelled = false;
+    for step in &advance.release {
+        if let Some(member) = by_step.get(step) {
+            // The lease may have changed hands while the step was held.
+            if LEASED_COMMAND_TYPES.contains(&member.r#type.as_str()) {
+                let lease_id = member.batch().and_then(|batch| batch.lease_id);
+                let held = ensure_lease_holder(pool, &command.deployment_hash, lease_id.as_deref())
+                    .await
+                    .map_err(|error| match error.code {
+                        TypedErrorCode::DeploymentLocked => Ok(error),
+                        _ => Err(error.message),
+                    });
+                if let Err(locked) = held {
+                    let locked = locked?;
+                    let error = json!({
+                        "code": "deployment_locked",
+                        "message": format!("Cancelled: {}", locked.message),
+                    });
+                    db::command::cancel_pending(pool, &member.command_id, error).await?;
+                    tracing::info!(
+                        batch_id = %batch.id,
+                        command_id = %member.command_id,
+                        "Cancelled batch step '{}': deploy lease changed hands",
+                        step
+                    );
+                    lease_cancelled = true;
+                    continue;
+                }
+            }
+            let priority =
+                CommandPriority::from_name(&member.priority).unwrap_or(CommandPriority::Normal);
+            if db::command::release_to_queue(pool, &member.command_id, &priority).await? {
+                tracing::info!(
+                    batch_id = %batch.id,
+                    command_id = %member.command_id,
+                    "Released batch step '{}'",
+                    step
+                );
+            }
+        }
+    }
+    if lease_cancelled {
+        // Cancel the dependents of the steps cancelled above.
+        Box::pin(advance_batch(pool, command)).await?;
+    }
+    Ok(())
+}
+
+/// Attach a batch reference to a command's metadata.
+pub fn with_batch_ref(command: Command, batch: &CommandBatchRef) -> Command {
+    let mut metadata = command
+        .metadata
+        .clone()
+        .filter(|value| value.is_object())
+        .unwrap_or_else(|| json!({}));
+    metadata[BATCH_METADATA_KEY] = json!(batch);
+    command.with_metadata(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(step: &str, depends_on: &[&str], status: &str) -> (CommandBatchRef, String) {
+        (
+            CommandBatchRef {
+                id: "batch_1".to_string(),
+                step: step.to_string(),
+                depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+                lease_id: None,
+            },
+            status.to_string(),
+        )
+    }
+
+    #[test]
+    fn releases_dependents_in_order_and_cascades_cancellation() {
+        let steps = |items: &[(&str, &[&str])]| -> Vec<(String, Vec<String>)> {
+            items
+                .iter()
+                .map(|(step, deps)| {
+                    (
+                        step.to_string(),
+                        deps.iter().map(|dep| dep.to_string()).collect(),
+                    )
+                })
+                .collect()
+        };
+        assert!(validate_batch_steps(&steps(&[("deploy", &[]), ("proxy", &["deploy"])])).is_ok());
+        assert!(
+            validate_batch_steps(&steps(&[("a", &["b"]), ("b", &["a"])]))
+                .unwrap_err()
+                .contains("cycle")
+        );
+        assert!(validate_batch_steps(&steps(&[("a", &["missing"])]))
+            .unwrap_err()
+            .contains("unknown step"));
+
+        let advance = plan_advance(&[
+            member("deploy", &[], "completed"),
+            member("proxy", &["deploy"], "queued"),
+            member("probe", &["proxy"], "queued"),
+        ]);
+        assert_eq!(advance.release, vec!["proxy".to_string()]);
+        assert!(advance.cancel.is_empty());
+
+        let advance = plan_advance(&[
+            member("deploy", &[], "failed"),
+            member("proxy", &["deploy"], "queued"),
+            member("probe", &["proxy"], "queued"),
+            member("logs", &[], "queued"),
+        ]);
+        assert!(advance.release.is_empty());
+        assert_eq!(
+            advance.cancel,
+            vec![
+                ("proxy".to_string(), "deploy".to_string()),
+                ("probe".to_string(), "proxy".to_string()),
+            ]
+        );
+    }
+}
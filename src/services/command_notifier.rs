@@ -0,0 +1,144 @@
+//! Push wake-ups for agent command waiters.
+//!
+//! A trigger on `command_queue` publishes the deployment hash on
+//! [`COMMAND_QUEUED_CHANNEL`] for every queued command. One `PgListener` per
+//! server process fans those notifications out to the long-polls and command
+//! streams waiting on that deployment, so a waiter checks the queue when a
+//! command arrives instead of every poll interval. While the listener is
+//! down, waiters fall back to interval polling.
+
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Postgres channel the `command_queue` trigger notifies on.
+pub const COMMAND_QUEUED_CHANNEL: &str = "agent_command_queued";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct CommandNotifier {
+    deployments: Mutex<HashMap<String, watch::Sender<u64>>>,
+    listening: AtomicBool,
+}
+
+impl CommandNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start listening on the agent pool. Reconnects with a fixed delay.
+    pub fn spawn(pool: PgPool) -> Arc<Self> {
+        let notifier = Arc::new(Self::new());
+        let task_notifier = notifier.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = task_notifier.listen(&pool).await {
+                    tracing::warn!("Command queue listener failed: {}", err);
+                }
+                task_notifier.set_listening(false);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+        notifier
+    }
+
+    async fn listen(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(COMMAND_QUEUED_CHANNEL).await?;
+        self.set_listening(true);
+        tracing::info!("Listening for queued agent commands");
+        loop {
+            match listener.try_recv().await? {
+                Some(notification) => {
+                    self.set_listening(true);
+                    self.notify(notification.payload());
+                }
+                // The connection dropped and notifications may have been
+                // missed; the next try_recv reconnects and re-listens.
+                None => self.set_listening(false),
+            }
+        }
+    }
+
+    /// Whether queued commands currently wake waiters. Waiters poll on their
+    /// interval while this is false.
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Acquire)
+    }
+
+    fn set_listening(&self, listening: bool) {
+        let was_listening = self.listening.swap(listening, Ordering::AcqRel);
+        if was_listening != listening {
+            // Waiters size their sleep from the listener state; wake them so
+            // they re-check the queue and pick the right wait.
+            self.notify_all();
+        }
+    }
+
+    /// Register interest in a deployment. Subscribe before checking the
+    /// queue so a command queued in between still wakes the waiter.
+    pub fn subscribe(&self, deployment_hash: &str) -> watch::Receiver<u64> {
+        let mut deployments = self.deployments.lock().expect("command notifier lock");
+        deployments.retain(|_, sender| sender.receiver_count() > 0);
+        deployments
+            .entry(deployment_hash.to_string())
+            .or_insert_with(|| watch::channel(0).0)
+            .subscribe()
+    }
+
+    pub fn notify(&self, deployment_hash: &str) {
+        let deployments = self.deployments.lock().expect("command notifier lock");
+        if let Some(sender) = deployments.get(deployment_hash) {
+            sender.send_modify(|generation| *generation = generation.wrapping_add(1));
+        }
+    }
+
+    fn notify_all(&self) {
+        let deployments = self.deployments.lock().expect("command notifier lock");
+        for sender in deployments.values() {
+            sender.send_modify(|generation| *generation = generation.wrapping_add(1));
+        }
+    }
+
+    /// Sleep until a command is queued for the subscribed deployment or
+    /// `timeout` passes. Returns whether a notification arrived.
+    pub async fn wait(&self, queued: &mut watch::Receiver<u64>, timeout: Duration) -> bool {
+        match tokio::time::timeout(timeout, queued.changed()).await {
+            Ok(Ok(())) => true,
+            Ok(Err(_)) => {
+                tokio::time::sleep(timeout).await;
+                false
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn notify_wakes_only_the_matching_deployment() {
+        let notifier = CommandNotifier::new();
+        let mut first = notifier.subscribe("dep-a");
+        let mut other = notifier.subscribe("dep-b");
+
+        notifier.notify("dep-a");
+        assert!(notifier.wait(&mut first, Duration::from_millis(50)).await);
+        assert!(!notifier.wait(&mut other, Duration::from_millis(10)).await);
+
+        // A notification sent before the wait starts is not lost.
+        notifier.notify("dep-b");
+        assert!(notifier.wait(&mut other, Duration::from_millis(10)).await);
+
+        drop(first);
+        let _ = notifier.subscribe("dep-c");
+        assert!(!notifier.deployments.lock().unwrap().contains_key("dep-a"));
+    }
+}
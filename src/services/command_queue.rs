@@ -0,0 +1,172 @@
+//! The one way commands reach the agent queue.
+//!
+//! API routes, MCP tools, schedules, batches, releases and previews all queue
+//! through [`enqueue`] or [`enqueue_all`]. Before anything is written they
+//! refuse commands that change what runs while another client holds the
+//! deploy lease, and negotiate parameters against the agent's capability
+//! manifest. The commands are then inserted and queued in one transaction.
+
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::{
+    db,
+    helpers::parse_manifest,
+    models::Command,
+    services::{
+        agent_compatibility, deployment_lease::ensure_lease_holder, ApiTypedError, TypedErrorCode,
+        TypedErrorEnvelope,
+    },
+};
+
+/// Commands that change what runs or its data; refused while another client
+/// holds the deploy lease.
+pub const LEASED_COMMAND_TYPES: &[&str] = &[
+    "deploy_app",
+    "remove_app",
+    "restart",
+    "restore_volume",
+    "upload_file",
+];
+/// Commands whose parameters may name a compose project other than the
+/// deployment's own.
+const COMPOSE_PROJECT_COMMAND_TYPES: &[&str] = &["deploy_app", "remove_app"];
+
+/// Check a command before it is queued and return the parameters to send,
+/// downgraded for the agent's schema. `lease_id` is the caller's own lease.
+///
+/// Routes with side effects call this up front to fail early; [`enqueue`]
+/// checks again, so no path skips it.
+pub async fn admit(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    command_type: &str,
+    parameters: Option<Value>,
+    lease_id: Option<&str>,
+) -> Result<Option<Value>, TypedErrorEnvelope> {
+    let compose_project = parameters
+        .as_ref()
+        .and_then(|params| params.get("compose_project"))
+        .filter(|project| !project.is_null());
+    if compose_project.is_some() && !COMPOSE_PROJECT_COMMAND_TYPES.contains(&command_type) {
+        return Err(TypedErrorEnvelope::invalid_request(format!(
+            "{} does not accept compose_project",
+            command_type
+        )));
+    }
+
+    if LEASED_COMMAND_TYPES.contains(&command_type) {
+        match compose_project {
+            // Preview stacks run under their own compose project and do not
+            // touch the leased deployment.
+            Some(project) => {
+                let project = project.as_str().unwrap_or_default();
+                let preview = db::preview_environment::fetch_active_by_compose_project(
+                    pg_pool,
+                    deployment_hash,
+                    project,
+                )
+                .await
+                .map_err(TypedErrorEnvelope::internal_error)?;
+                if preview.is_none() {
+                    return Err(TypedErrorEnvelope::invalid_request(format!(
+                        "compose_project '{}' is not an active preview of this deployment",
+                        project
+                    )));
+                }
+            }
+            None => ensure_lease_holder(pg_pool, deployment_hash, lease_id).await?,
+        }
+    }
+
+    // Refuse commands the agent cannot run and drop parameters it predates.
+    let agent = db::agent::fetch_by_deployment_hash(pg_pool, deployment_hash)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?;
+    let manifest = agent.and_then(|agent| parse_manifest(agent.capability_manifest));
+    let negotiated = agent_compatibility::negotiate(manifest.as_ref(), command_type, parameters)
+        .map_err(TypedErrorEnvelope::deployment_capability_missing)?;
+    if !negotiated.dropped_fields.is_empty() {
+        tracing::warn!(
+            deployment_hash = %deployment_hash,
+            command_type = %command_type,
+            dropped_fields = ?negotiated.dropped_fields,
+            "Downgraded command parameters for an older agent schema"
+        );
+    }
+    Ok(negotiated.parameters)
+}
+
+/// Admit, insert and queue one command at the priority it carries.
+pub async fn enqueue(
+    pg_pool: &PgPool,
+    command: Command,
+    lease_id: Option<&str>,
+) -> Result<Command, TypedErrorEnvelope> {
+    let mut saved = enqueue_all(pg_pool, vec![command], lease_id).await?;
+    saved
+        .pop()
+        .ok_or_else(|| TypedErrorEnvelope::internal_error("Command was not saved"))
+}
+
+/// Admit every command, then insert and queue them all or none. Batch
+/// members that depend on other steps are inserted held.
+pub async fn enqueue_all(
+    pg_pool: &PgPool,
+    commands: Vec<Command>,
+    lease_id: Option<&str>,
+) -> Result<Vec<Command>, TypedErrorEnvelope> {
+    let mut admitted = Vec::with_capacity(commands.len());
+    for mut command in commands {
+        command.parameters = admit(
+            pg_pool,
+            &command.deployment_hash,
+            &command.r#type,
+            command.parameters.take(),
+            lease_id,
+        )
+        .await?;
+        admitted.push(command);
+    }
+    db::command::insert_and_queue(pg_pool, &admitted)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)
+}
+
+/// HTTP status for a command the queue refused.
+pub fn api_error(error: TypedErrorEnvelope) -> ApiTypedError {
+    match error.code {
+        TypedErrorCode::DeploymentLocked => ApiTypedError::conflict(error),
+        TypedErrorCode::DeploymentCapabilityMissing | TypedErrorCode::InvalidRequest => {
+            ApiTypedError::bad_request(error)
+        }
+        _ => ApiTypedError::internal(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    #[test]
+    fn refused_commands_map_to_client_errors() {
+        let locked = api_error(TypedErrorEnvelope::deployment_locked("locked by ci"));
+        assert_eq!(locked.status_code(), StatusCode::CONFLICT);
+        let missing = api_error(TypedErrorEnvelope::deployment_capability_missing(
+            "Agent does not support restart",
+        ));
+        assert_eq!(missing.status_code(), StatusCode::BAD_REQUEST);
+        let failed = api_error(TypedErrorEnvelope::internal_error("database is down"));
+        assert_eq!(failed.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn restore_and_upload_are_leased() {
+        for command_type in ["deploy_app", "remove_app", "restore_volume", "upload_file"] {
+            assert!(LEASED_COMMAND_TYPES.contains(&command_type));
+        }
+        assert!(!LEASED_COMMAND_TYPES.contains(&"logs"));
+    }
+}
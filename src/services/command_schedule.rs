@@ -0,0 +1,287 @@
+//! Recurring agent commands.
+//!
+//! A schedule pairs a cron expression with an agent command. The scheduler
+//! task wakes every [`SCHEDULER_INTERVAL_SECS`], queues a command for each
+//! schedule whose `next_run_at` has passed and moves `next_run_at` to the
+//! following match. Runs noticed more than [`MISSED_RUN_GRACE_SECS`] late
+//! follow the schedule's [`MissedRunPolicy`].
+
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::{
+    db,
+    forms::status_panel,
+    helpers::cron::CronSchedule,
+    models::{Command, CommandPriority, CommandSchedule, MissedRunPolicy},
+    services::command_queue,
+    services::deployment_lease::ensure_lease_holder,
+    services::{TypedErrorCode, TypedErrorEnvelope},
+};
+
+pub const SCHEDULER_INTERVAL_SECS: u64 = 30;
+const SCHEDULER_BATCH: i64 = 100;
+/// A run picked up within this long of its time counts as on time.
+pub const MISSED_RUN_GRACE_SECS: i64 = 300;
+/// Command metadata key linking a queued command to its schedule.
+pub const SCHEDULE_METADATA_KEY: &str = "schedule";
+/// Commands that need an interactive deploy context (compose rendering,
+/// the deploy lease), overwrite data or carry file chunks, and cannot run
+/// unattended.
+const UNSCHEDULABLE_COMMAND_TYPES: &[&str] = &[
+    "deploy_app",
+    "remove_app",
+    "restore_volume",
+    "open_shell",
+    "upload_file",
+    "download_file",
+];
+/// Statuses meaning the previous run has not finished yet.
+const PENDING_STATUSES: &[&str] = &["queued", "sent", "executing"];
+
+/// Body of `POST /api/v1/deployments/{hash}/schedules`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRequest {
+    pub cron: String,
+    pub command_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missed_run_policy: Option<MissedRunPolicy>,
+}
+
+/// Validate a schedule request and store it with its first run time.
+pub async fn create_schedule(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    user_id: &str,
+    request: &ScheduleRequest,
+) -> Result<CommandSchedule, TypedErrorEnvelope> {
+    let cron = CronSchedule::parse(&request.cron).map_err(TypedErrorEnvelope::invalid_request)?;
+    let command_type = request.command_type.trim();
+    if command_type.is_empty() {
+        return Err(TypedErrorEnvelope::invalid_request(
+            "command_type is required",
+        ));
+    }
+    if UNSCHEDULABLE_COMMAND_TYPES.contains(&command_type) {
+        return Err(TypedErrorEnvelope::invalid_request(format!(
+            "{} cannot be scheduled; run it with `stacker agent` instead",
+            command_type
+        )));
+    }
+    let parameters = status_panel::validate_command_parameters(command_type, &request.parameters)
+        .map_err(TypedErrorEnvelope::invalid_request)?;
+    let priority = match request.priority.as_deref() {
+        Some(name) => CommandPriority::from_name(name).ok_or_else(|| {
+            TypedErrorEnvelope::invalid_request(format!("Unknown priority '{}'", name))
+        })?,
+        None => CommandPriority::Normal,
+    };
+
+    let now = Utc::now();
+    let next_run_at = cron.next_after(now).ok_or_else(|| {
+        TypedErrorEnvelope::invalid_request(format!(
+            "Cron expression '{}' never matches",
+            request.cron
+        ))
+    })?;
+    let schedule = CommandSchedule {
+        id: 0,
+        schedule_id: format!("sched_{}", uuid::Uuid::new_v4()),
+        deployment_hash: deployment_hash.to_string(),
+        name: request
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string),
+        cron: cron.expression().to_string(),
+        command_type: command_type.to_string(),
+        parameters,
+        priority: priority.to_string(),
+        timeout_seconds: request.timeout_seconds,
+        missed_run_policy: request
+            .missed_run_policy
+            .unwrap_or_default()
+            .as_str()
+            .to_string(),
+        enabled: true,
+        created_by: user_id.to_string(),
+        created_at: now,
+        updated_at: now,
+        next_run_at,
+        last_run_at: None,
+        last_command_id: None,
+    };
+    db::command_schedule::insert(pg_pool, &schedule)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)
+}
+
+/// Whether a run due at `due_at` and picked up at `now` is queued.
+pub fn should_run(due_at: DateTime<Utc>, now: DateTime<Utc>, policy: MissedRunPolicy) -> bool {
+    now - due_at <= Duration::seconds(MISSED_RUN_GRACE_SECS) || policy == MissedRunPolicy::RunOnce
+}
+
+/// Queue a command for every due schedule. Returns how many were queued.
+pub async fn run_due_schedules(pg_pool: &PgPool) -> Result<usize, String> {
+    let due = db::command_schedule::list_due(pg_pool, SCHEDULER_BATCH).await?;
+    let mut queued = 0;
+    for schedule in &due {
+        match run_schedule(pg_pool, schedule).await {
+            Ok(Some(command_id)) => {
+                tracing::info!(
+                    deployment_hash = %schedule.deployment_hash,
+                    schedule_id = %schedule.schedule_id,
+                    command_id = %command_id,
+                    "Scheduled {} queued",
+                    schedule.command_type
+                );
+                queued += 1;
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!(
+                deployment_hash = %schedule.deployment_hash,
+                schedule_id = %schedule.schedule_id,
+                "Failed to run command schedule: {}",
+                err
+            ),
+        }
+    }
+    Ok(queued)
+}
+
+/// Advance one due schedule and queue its command unless the run is
+/// skipped. Returns the queued command id.
+async fn run_schedule(
+    pg_pool: &PgPool,
+    schedule: &CommandSchedule,
+) -> Result<Option<String>, String> {
+    let now = Utc::now();
+    let cron = CronSchedule::parse(&schedule.cron)?;
+    let next_run_at = cron
+        .next_after(now)
+        .ok_or_else(|| format!("Cron expression '{}' never matches", schedule.cron))?;
+    if !db::command_schedule::claim_run(pg_pool, schedule.id, schedule.next_run_at, next_run_at)
+        .await?
+    {
+        // Another server process took this run.
+        return Ok(None);
+    }
+
+    let policy = MissedRunPolicy::from_name(&schedule.missed_run_policy).unwrap_or_default();
+    if !should_run(schedule.next_run_at, now, policy) {
+        tracing::info!(
+            schedule_id = %schedule.schedule_id,
+            "Skipped run missed at {}",
+            schedule.next_run_at
+        );
+        return Ok(None);
+    }
+    if let Some(last_command_id) = &schedule.last_command_id {
+        let last = db::command::fetch_by_command_id(pg_pool, last_command_id).await?;
+        if let Some(last) = last.filter(|last| PENDING_STATUSES.contains(&last.status.as_str())) {
+            tracing::info!(
+                schedule_id = %schedule.schedule_id,
+                command_id = %last.command_id,
+                "Skipped run: previous run is still {}",
+                last.status
+            );
+            return Ok(None);
+        }
+    }
+    if let Err(error) = ensure_lease_holder(pg_pool, &schedule.deployment_hash, None).await {
+        tracing::info!(
+            schedule_id = %schedule.schedule_id,
+            "Skipped run: {}",
+            error.message
+        );
+        return Ok(None);
+    }
+    let priority =
+        CommandPriority::from_name(&schedule.priority).unwrap_or(CommandPriority::Normal);
+    let mut command = Command::new(
+        format!("cmd_{}", uuid::Uuid::new_v4()),
+        schedule.deployment_hash.clone(),
+        schedule.command_type.clone(),
+        schedule.created_by.clone(),
+    )
+    .with_priority(priority)
+    .with_metadata(json!({
+        SCHEDULE_METADATA_KEY: {
+            "id": schedule.schedule_id,
+            "scheduled_for": schedule.next_run_at,
+        }
+    }));
+    if let Some(parameters) = schedule.parameters.clone() {
+        command = command.with_parameters(parameters);
+    }
+    if let Some(timeout) = schedule.timeout_seconds {
+        command = command.with_timeout(timeout);
+    }
+
+    // The agent may have changed since the schedule was created.
+    let command = match command_queue::enqueue(pg_pool, command, None).await {
+        Ok(command) => command,
+        Err(error) if error.code == TypedErrorCode::InternalError => return Err(error.message),
+        Err(error) => {
+            tracing::info!(
+                schedule_id = %schedule.schedule_id,
+                "Skipped run: {}",
+                error.message
+            );
+            return Ok(None);
+        }
+    };
+    db::command_schedule::record_run(pg_pool, schedule.id, &command.command_id).await?;
+    Ok(Some(command.command_id))
+}
+
+/// Background task that queues scheduled commands as they come due.
+pub fn spawn_command_scheduler(pg_pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(StdDuration::from_secs(SCHEDULER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(err) = run_due_schedules(&pg_pool).await {
+                tracing::warn!("Command scheduler failed: {}", err);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn late_runs_follow_the_missed_run_policy() {
+        let due_at = DateTime::parse_from_rfc3339("2026-10-18T03:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let on_time = due_at + Duration::seconds(40);
+        let after_outage = due_at + Duration::hours(7);
+
+        assert!(should_run(due_at, on_time, MissedRunPolicy::Skip));
+        assert!(should_run(due_at, on_time, MissedRunPolicy::RunOnce));
+        assert!(!should_run(due_at, after_outage, MissedRunPolicy::Skip));
+        assert!(should_run(due_at, after_outage, MissedRunPolicy::RunOnce));
+
+        assert_eq!(
+            MissedRunPolicy::from_name("run-once"),
+            Some(MissedRunPolicy::RunOnce)
+        );
+        assert_eq!(MissedRunPolicy::from_name("later"), None);
+    }
+}
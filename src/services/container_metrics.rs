@@ -0,0 +1,280 @@
+//! Container resource usage over time.
+//!
+//! Agents push `docker stats` samples to `POST /api/v1/agent/metrics` on an
+//! interval, and completed `stats` commands are stored the same way. Samples
+//! go to the day-partitioned `container_metrics` table and the latest one per
+//! app is exported as `stacker_container_*` gauges on `/metrics`. A
+//! maintenance task creates upcoming partitions and drops days older than
+//! [`METRICS_RETENTION_DAYS`].
+
+use std::collections::BTreeMap;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{db, forms::status_panel::ContainerStats, metrics, models::ContainerMetricSample};
+
+pub const METRICS_RETENTION_DAYS: i64 = 14;
+const MAINTENANCE_INTERVAL_SECS: u64 = 3600;
+/// How far ahead of the server clock an agent timestamp may be.
+pub const MAX_CLOCK_SKEW_MINUTES: i64 = 5;
+/// Most samples returned by one query (a day of 30s samples for ~3 apps).
+pub const MAX_QUERY_SAMPLES: i64 = 10_000;
+
+/// Parse a look-back window such as `90s`, `15m`, `1h` or `7d`.
+pub fn parse_window(value: &str) -> Result<Duration, String> {
+    parse_window_within(value, METRICS_RETENTION_DAYS, "metrics")
+}
+
+/// [`parse_window`] for data of `kind` kept for `retention_days`.
+pub fn parse_window_within(
+    value: &str,
+    retention_days: i64,
+    kind: &str,
+) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .ok()
+        .filter(|amount| *amount > 0)
+        .ok_or_else(|| format!("Invalid window '{}': expected e.g. 30m, 1h or 7d", value))?;
+    let window = match unit {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" | "" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => return Err(format!("Invalid window unit '{}': use s, m, h or d", unit)),
+    };
+    if window > Duration::days(retention_days) {
+        return Err(format!(
+            "Window '{}' is longer than the {}-day {} retention",
+            value, retention_days, kind
+        ));
+    }
+    Ok(window)
+}
+
+/// Reject a sample time outside the retained window or more than
+/// [`MAX_CLOCK_SKEW_MINUTES`] ahead of `now`. Such rows would land in the
+/// default partition and never expire with their day.
+pub fn check_collected_at(collected_at: DateTime<Utc>, now: DateTime<Utc>) -> Result<(), String> {
+    check_timestamp_within("collected_at", collected_at, now, METRICS_RETENTION_DAYS)
+}
+
+/// [`check_collected_at`] for an agent-supplied `field` of data kept for
+/// `retention_days`.
+pub fn check_timestamp_within(
+    field: &str,
+    value: DateTime<Utc>,
+    now: DateTime<Utc>,
+    retention_days: i64,
+) -> Result<(), String> {
+    if value > now + Duration::minutes(MAX_CLOCK_SKEW_MINUTES) {
+        return Err(format!(
+            "{} {} is more than {} minutes in the future",
+            field,
+            value.to_rfc3339(),
+            MAX_CLOCK_SKEW_MINUTES
+        ));
+    }
+    if value < now - Duration::days(retention_days) {
+        return Err(format!(
+            "{} {} is older than the {}-day retention",
+            field,
+            value.to_rfc3339(),
+            retention_days
+        ));
+    }
+    Ok(())
+}
+
+/// Store samples and refresh the Prometheus gauges of their apps.
+pub async fn record_samples(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    collected_at: DateTime<Utc>,
+    samples: &[ContainerStats],
+) -> Result<u64, String> {
+    let stored =
+        db::container_metrics::insert_samples(pg_pool, deployment_hash, collected_at, samples)
+            .await?;
+    for sample in samples {
+        export_gauges(deployment_hash, sample);
+    }
+    Ok(stored)
+}
+
+fn export_gauges(deployment_hash: &str, sample: &ContainerStats) {
+    let labels = [deployment_hash, sample.app_code.as_str()];
+    metrics::CONTAINER_CPU_PERCENT
+        .with_label_values(&labels)
+        .set(sample.cpu_percent);
+    metrics::CONTAINER_MEMORY_BYTES
+        .with_label_values(&labels)
+        .set(sample.memory_bytes as f64);
+    if let Some(limit) = sample.memory_limit_bytes {
+        metrics::CONTAINER_MEMORY_LIMIT_BYTES
+            .with_label_values(&labels)
+            .set(limit as f64);
+    }
+    metrics::CONTAINER_NETWORK_RX_BYTES
+        .with_label_values(&labels)
+        .set(sample.net_rx_bytes as f64);
+    metrics::CONTAINER_NETWORK_TX_BYTES
+        .with_label_values(&labels)
+        .set(sample.net_tx_bytes as f64);
+    metrics::CONTAINER_BLOCK_READ_BYTES
+        .with_label_values(&labels)
+        .set(sample.block_read_bytes as f64);
+    metrics::CONTAINER_BLOCK_WRITE_BYTES
+        .with_label_values(&labels)
+        .set(sample.block_write_bytes as f64);
+}
+
+/// Usage of one app over a window, as printed by `stacker agent stats --since`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppUsageSummary {
+    pub app_code: String,
+    pub samples: usize,
+    pub cpu_avg_percent: f64,
+    pub cpu_max_percent: f64,
+    pub memory_latest_bytes: i64,
+    pub memory_max_bytes: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_bytes: Option<i64>,
+    /// Bytes received and sent during the window.
+    pub net_rx_bytes: i64,
+    pub net_tx_bytes: i64,
+}
+
+/// Summarise samples ordered oldest first, one entry per app.
+pub fn summarize(samples: &[ContainerMetricSample]) -> Vec<AppUsageSummary> {
+    let mut by_app: BTreeMap<&str, Vec<&ContainerMetricSample>> = BTreeMap::new();
+    for sample in samples {
+        by_app.entry(&sample.app_code).or_default().push(sample);
+    }
+
+    by_app
+        .into_iter()
+        .filter_map(|(app_code, samples)| {
+            let first = samples.first()?;
+            let last = samples.last()?;
+            let cpu_total: f64 = samples.iter().map(|s| s.cpu_percent).sum();
+            Some(AppUsageSummary {
+                app_code: app_code.to_string(),
+                samples: samples.len(),
+                cpu_avg_percent: cpu_total / samples.len() as f64,
+                cpu_max_percent: samples.iter().map(|s| s.cpu_percent).fold(0.0, f64::max),
+                memory_latest_bytes: last.memory_bytes,
+                memory_max_bytes: samples.iter().map(|s| s.memory_bytes).max().unwrap_or(0),
+                memory_limit_bytes: last.memory_limit_bytes,
+                net_rx_bytes: counter_delta(first.net_rx_bytes, last.net_rx_bytes),
+                net_tx_bytes: counter_delta(first.net_tx_bytes, last.net_tx_bytes),
+            })
+        })
+        .collect()
+}
+
+/// Growth of a counter; a restart resets it, leaving only the new count.
+fn counter_delta(first: i64, last: i64) -> i64 {
+    if last >= first {
+        last - first
+    } else {
+        last
+    }
+}
+
+/// Create today's and tomorrow's partitions and drop expired days.
+pub async fn maintain_partitions(pg_pool: &PgPool) -> Result<Vec<String>, String> {
+    db::container_metrics::PARTITIONS
+        .maintain(pg_pool, METRICS_RETENTION_DAYS)
+        .await
+}
+
+/// Background task running [`maintain_partitions`] every hour.
+pub fn spawn_metrics_maintenance(pg_pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(StdDuration::from_secs(MAINTENANCE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match maintain_partitions(&pg_pool).await {
+                Ok(dropped) if !dropped.is_empty() => {
+                    tracing::info!("Dropped expired container metrics: {}", dropped.join(", "))
+                }
+                Ok(_) => {}
+                Err(err) => tracing::warn!("Container metrics maintenance failed: {}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(
+        app_code: &str,
+        minute: u32,
+        cpu: f64,
+        memory: i64,
+        rx: i64,
+    ) -> ContainerMetricSample {
+        ContainerMetricSample {
+            deployment_hash: "dep_1".to_string(),
+            app_code: app_code.to_string(),
+            container: None,
+            recorded_at: DateTime::parse_from_rfc3339(&format!("2026-10-18T12:{:02}:00Z", minute))
+                .unwrap()
+                .with_timezone(&Utc),
+            cpu_percent: cpu,
+            memory_bytes: memory,
+            memory_limit_bytes: Some(1024),
+            net_rx_bytes: rx,
+            net_tx_bytes: 0,
+            block_read_bytes: 0,
+            block_write_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn summarizes_usage_per_app() {
+        let samples = vec![
+            sample("web", 0, 10.0, 300, 1_000),
+            sample("db", 0, 2.0, 500, 50),
+            sample("web", 1, 30.0, 400, 4_000),
+            // The web container restarted: its counter starts over.
+            sample("web", 2, 20.0, 350, 500),
+        ];
+
+        let summary = summarize(&samples);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].app_code, "db");
+        let web = &summary[1];
+        assert_eq!(web.samples, 3);
+        assert_eq!(web.cpu_avg_percent, 20.0);
+        assert_eq!(web.cpu_max_percent, 30.0);
+        assert_eq!(web.memory_latest_bytes, 350);
+        assert_eq!(web.memory_max_bytes, 400);
+        assert_eq!(web.net_rx_bytes, 500);
+
+        assert_eq!(parse_window("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_window("2").unwrap(), Duration::hours(2));
+        assert!(parse_window("1w").is_err());
+        assert!(parse_window("30d").is_err());
+    }
+
+    #[test]
+    fn collected_at_stays_within_retention() {
+        let now = Utc::now();
+        assert!(check_collected_at(now, now).is_ok());
+        assert!(check_collected_at(now + Duration::minutes(4), now).is_ok());
+        assert!(check_collected_at(now + Duration::minutes(6), now).is_err());
+        assert!(check_collected_at(now - Duration::days(METRICS_RETENTION_DAYS + 1), now).is_err());
+    }
+}
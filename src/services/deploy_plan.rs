@@ -4,7 +4,7 @@ use sqlx::PgPool;
 
 use crate::{
     db,
-    models::Deployment,
+    models::{Deployment, DeploymentRelease},
     services::{
         DeploymentAppState, DeploymentState, TypedErrorCode, TypedErrorEnvelope,
         TypedRemediationClass,
@@ -30,9 +30,35 @@ pub enum DeployPlanActionKind {
     SyncAppConfig,
 }
 
+/// What a rollback re-applies: a marketplace template version, or a release
+/// recorded by a previous deploy of a user-authored project.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RollbackTargetKind {
+    TemplateVersion,
+    Release,
+}
+
+impl Default for RollbackTargetKind {
+    fn default() -> Self {
+        Self::TemplateVersion
+    }
+}
+
+impl RollbackTargetKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::TemplateVersion => "marketplace template version",
+            Self::Release => "release",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct DeployPlanRollback {
+    #[serde(default)]
+    pub kind: RollbackTargetKind,
     pub requested_target: String,
     pub current_version: String,
     pub resolved_version: String,
@@ -40,6 +66,7 @@ pub struct DeployPlanRollback {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RollbackPlanContext {
+    pub kind: RollbackTargetKind,
     pub requested_target: String,
     pub current_version: String,
     pub resolved_version: String,
@@ -57,15 +84,9 @@ pub async fn resolve_rollback_plan_context(
             TypedErrorEnvelope::deployment_not_found("Project not found for deployment")
         })?;
 
-    let template_id = project.source_template_id.ok_or_else(|| {
-        TypedErrorEnvelope::new(
-            TypedErrorCode::RollbackTargetUnavailable,
-            "Rollback is only available for marketplace deployments with an older template version",
-            false,
-            TypedRemediationClass::State,
-        )
-        .with_context("rollbackTarget", requested_target)
-    })?;
+    let Some(template_id) = project.source_template_id else {
+        return resolve_release_rollback_context(pg_pool, deployment, requested_target).await;
+    };
 
     let versions = db::marketplace::list_versions_by_template(pg_pool, template_id)
         .await
@@ -138,12 +159,80 @@ pub async fn resolve_rollback_plan_context(
     };
 
     Ok(RollbackPlanContext {
+        kind: RollbackTargetKind::TemplateVersion,
         requested_target: requested_target.to_string(),
         current_version: current.version.clone(),
         resolved_version,
     })
 }
 
+/// Resolve `previous` or a release number against the deployment's recorded
+/// releases. The newest release is the one currently deployed.
+pub async fn resolve_release_rollback_context(
+    pg_pool: &PgPool,
+    deployment: &Deployment,
+    requested_target: &str,
+) -> Result<RollbackPlanContext, TypedErrorEnvelope> {
+    let releases =
+        db::deployment_release::list_by_deployment_hash(pg_pool, &deployment.deployment_hash)
+            .await
+            .map_err(|_| {
+                TypedErrorEnvelope::internal_error("Failed to load deployment releases")
+            })?;
+
+    resolve_release_target(&releases, requested_target)
+}
+
+fn resolve_release_target(
+    releases: &[DeploymentRelease],
+    requested_target: &str,
+) -> Result<RollbackPlanContext, TypedErrorEnvelope> {
+    let unavailable = |message: String| {
+        TypedErrorEnvelope::new(
+            TypedErrorCode::RollbackTargetUnavailable,
+            message,
+            false,
+            TypedRemediationClass::State,
+        )
+        .with_context("rollbackTarget", requested_target)
+    };
+
+    let current = releases.first().ok_or_else(|| {
+        unavailable("No releases have been recorded for this deployment yet".to_string())
+    })?;
+
+    let resolved = if requested_target == "previous" {
+        releases
+            .iter()
+            .find(|release| release.bundle_hash != current.bundle_hash)
+            .ok_or_else(|| {
+                unavailable("No earlier release with different contents is available".to_string())
+                    .with_context("currentVersion", current.version.to_string())
+            })?
+    } else {
+        let version = requested_target
+            .trim_start_matches('r')
+            .parse::<i32>()
+            .map_err(|_| {
+                unavailable(format!(
+                    "Rollback target '{}' is neither 'previous' nor a release number",
+                    requested_target
+                ))
+            })?;
+        releases
+            .iter()
+            .find(|release| release.version == version)
+            .ok_or_else(|| unavailable(format!("Release {} was not found", version)))?
+    };
+
+    Ok(RollbackPlanContext {
+        kind: RollbackTargetKind::Release,
+        requested_target: requested_target.to_string(),
+        current_version: current.version.to_string(),
+        resolved_version: resolved.version.to_string(),
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct DeployPlanScope {
@@ -301,14 +390,15 @@ pub fn build_rollback_plan(
     }
 
     let has_changes = rollback.current_version != rollback.resolved_version;
+    let label = rollback.kind.label();
     let mut reasoning = vec![
         format!(
-            "rollback preview resolved requested target '{}' to template version {}",
-            rollback.requested_target, rollback.resolved_version
+            "rollback preview resolved requested target '{}' to {} {}",
+            rollback.requested_target, label, rollback.resolved_version
         ),
         format!(
-            "current deployment template version is {}",
-            rollback.current_version
+            "current deployment {} is {}",
+            label, rollback.current_version
         ),
     ];
 
@@ -318,8 +408,8 @@ pub fn build_rollback_plan(
             target: "deployment".to_string(),
             app_code: None,
             reason: format!(
-                "rollback preview targets marketplace template version {}",
-                rollback.resolved_version
+                "rollback preview targets {} {}",
+                label, rollback.resolved_version
             ),
         }]
     } else {
@@ -342,6 +432,7 @@ pub fn build_rollback_plan(
         actions,
         reasoning,
         rollback: Some(DeployPlanRollback {
+            kind: rollback.kind,
             requested_target: rollback.requested_target,
             current_version: rollback.current_version,
             resolved_version: rollback.resolved_version,
@@ -409,6 +500,7 @@ fn rollback_fingerprint(
         "target": target,
         "operation": DeployPlanOperation::RollbackDeploy,
         "rollback": {
+            "kind": rollback.kind,
             "requestedTarget": rollback.requested_target,
             "currentVersion": rollback.current_version,
             "resolvedVersion": rollback.resolved_version,
@@ -488,6 +580,7 @@ mod tests {
                 summary: "no drift detected".to_string(),
             },
             last_command: None,
+            lease: None,
         }
     }
 
@@ -576,6 +669,7 @@ mod tests {
             &sample_state(),
             "cloud",
             RollbackPlanContext {
+                kind: RollbackTargetKind::TemplateVersion,
                 requested_target: "previous".to_string(),
                 current_version: "1.2.0".to_string(),
                 resolved_version: "1.1.0".to_string(),
@@ -597,4 +691,42 @@ mod tests {
             Some("1.1.0")
         );
     }
+
+    fn release(version: i32, bundle_hash: &str) -> DeploymentRelease {
+        DeploymentRelease {
+            id: version,
+            deployment_hash: "deployment_state_online".to_string(),
+            version,
+            compose_content: String::new(),
+            env_hashes: serde_json::json!({}),
+            image_digests: serde_json::json!({}),
+            stacker_yml: String::new(),
+            bundle_hash: bundle_hash.to_string(),
+            rollback_of: None,
+            command_id: None,
+            created_by: "user-1".to_string(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn release_rollback_resolves_previous_distinct_release() {
+        let releases = vec![release(3, "bbb"), release(2, "bbb"), release(1, "aaa")];
+
+        let rollback = resolve_release_target(&releases, "previous").unwrap();
+        assert_eq!(rollback.kind, RollbackTargetKind::Release);
+        assert_eq!(rollback.current_version, "3");
+        assert_eq!(rollback.resolved_version, "1");
+
+        let plan = build_rollback_plan(&sample_state(), "server", rollback, None).unwrap();
+        assert!(plan.has_changes);
+        assert!(plan.reasoning[0].contains("release 1"));
+
+        let explicit = resolve_release_target(&releases, "r2").unwrap();
+        assert_eq!(explicit.resolved_version, "2");
+
+        let missing = resolve_release_target(&releases, "9").unwrap_err();
+        assert_eq!(missing.code, TypedErrorCode::RollbackTargetUnavailable);
+        assert!(resolve_release_target(&[], "previous").is_err());
+    }
 }
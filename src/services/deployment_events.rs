@@ -1,10 +1,15 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::types::JsonValue;
 
 use crate::{
     db,
-    models::{Command, Deployment},
+    forms::status_panel::{
+        BackupStatus, BackupVolumeCommandReport, RestoreVolumeCommandReport, RolloutStep,
+    },
+    models::{Command, CommandBatchStatus, Deployment, DeploymentLeaseAudit},
     services::{TypedErrorEnvelope, TypedRemediationClass},
 };
 
@@ -20,6 +25,20 @@ pub enum DeploymentEventKind {
     CommandCompleted,
     CommandFailed,
     CommandCancelled,
+    HealthGatePassed,
+    HealthGateFailed,
+    RollbackStarted,
+    RollbackCompleted,
+    RollbackFailed,
+    LeaseBroken,
+    BatchQueued,
+    BatchCompleted,
+    BatchFailed,
+    BatchCancelled,
+    BackupCreated,
+    BackupFailed,
+    BackupRestored,
+    RestoreFailed,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -44,6 +63,8 @@ pub struct DeploymentEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retryable: Option<bool>,
@@ -67,6 +88,7 @@ struct DeploymentEventDraft {
     summary: String,
     command_id: Option<String>,
     command_type: Option<String>,
+    batch_id: Option<String>,
     status: Option<String>,
     retryable: Option<bool>,
     remediation_class: Option<TypedRemediationClass>,
@@ -74,7 +96,11 @@ struct DeploymentEventDraft {
 }
 
 impl DeploymentEventFeed {
-    pub fn from_parts(deployment: &Deployment, commands: &[Command]) -> Self {
+    pub fn from_parts(
+        deployment: &Deployment,
+        commands: &[Command],
+        lease_audit: &[DeploymentLeaseAudit],
+    ) -> Self {
         let mut drafts = Vec::new();
 
         if let Some(status_message) = deployment
@@ -91,6 +117,7 @@ impl DeploymentEventFeed {
                 summary: status_message.to_string(),
                 command_id: None,
                 command_type: None,
+                batch_id: None,
                 status: Some(deployment.status.clone()),
                 retryable: None,
                 remediation_class: None,
@@ -99,6 +126,7 @@ impl DeploymentEventFeed {
         }
 
         for command in commands {
+            let batch_id = command.batch().map(|batch| batch.id);
             drafts.push(DeploymentEventDraft {
                 kind: DeploymentEventKind::CommandQueued,
                 classification: DeploymentEventClassification::Info,
@@ -106,6 +134,7 @@ impl DeploymentEventFeed {
                 summary: format!("{} queued", command.r#type),
                 command_id: Some(command.command_id.clone()),
                 command_type: Some(command.r#type.clone()),
+                batch_id: batch_id.clone(),
                 status: Some("queued".to_string()),
                 retryable: None,
                 remediation_class: None,
@@ -124,12 +153,37 @@ impl DeploymentEventFeed {
                     summary,
                     command_id: Some(command.command_id.clone()),
                     command_type: Some(command.r#type.clone()),
+                    batch_id,
                     status: Some(command.status.clone()),
                     retryable,
                     remediation_class,
                     order_key,
                 });
             }
+
+            drafts.extend(rollout_drafts(command));
+            drafts.extend(backup_drafts(command));
+        }
+
+        drafts.extend(batch_drafts(commands));
+
+        for entry in lease_audit {
+            drafts.push(DeploymentEventDraft {
+                kind: DeploymentEventKind::LeaseBroken,
+                classification: DeploymentEventClassification::Info,
+                occurred_at: entry.created_at,
+                summary: format!(
+                    "{} broke the deploy lease held by {}",
+                    entry.actor_owner, entry.previous_owner
+                ),
+                command_id: None,
+                command_type: None,
+                batch_id: None,
+                status: None,
+                retryable: None,
+                remediation_class: None,
+                order_key: 0,
+            });
         }
 
         drafts.sort_by(|left, right| {
@@ -151,6 +205,7 @@ impl DeploymentEventFeed {
                 summary: draft.summary,
                 command_id: draft.command_id,
                 command_type: draft.command_type,
+                batch_id: draft.batch_id,
                 status: draft.status,
                 retryable: draft.retryable,
                 remediation_class: draft.remediation_class,
@@ -174,7 +229,9 @@ impl DeploymentEventFeed {
                 None => return Ok(None),
             };
         let commands = db::command::fetch_by_deployment(pool, deployment_hash).await?;
-        Ok(Some(Self::from_parts(&deployment, &commands)))
+        let lease_audit =
+            db::deployment_lease::list_audit_by_deployment_hash(pool, deployment_hash).await?;
+        Ok(Some(Self::from_parts(&deployment, &commands, &lease_audit)))
     }
 }
 
@@ -269,6 +326,264 @@ fn summarize_command_outcome(
     }
 }
 
+/// Health-gate and rollback steps of `stacker deploy --auto-rollback`,
+/// recognised by the `rollout` marker on the command parameters.
+fn rollout_drafts(command: &Command) -> Vec<DeploymentEventDraft> {
+    let parameters = command.parameters.as_ref();
+    let Some(step) = parameters
+        .and_then(|params| params.get("rollout"))
+        .and_then(|value| serde_json::from_value::<RolloutStep>(value.clone()).ok())
+    else {
+        return Vec::new();
+    };
+    let app_code = parameters
+        .and_then(|params| params.get("app_code"))
+        .and_then(|value| value.as_str())
+        .unwrap_or("app");
+    let draft =
+        |kind, classification, occurred_at, summary: String, order_key| DeploymentEventDraft {
+            kind,
+            classification,
+            occurred_at,
+            summary,
+            command_id: Some(command.command_id.clone()),
+            command_type: Some(command.r#type.clone()),
+            batch_id: command.batch().map(|batch| batch.id),
+            status: Some(command.status.clone()),
+            retryable: None,
+            remediation_class: None,
+            order_key,
+        };
+
+    match step {
+        RolloutStep::HealthGate => {
+            let result = command.result.as_ref();
+            let field = |name: &str| {
+                result
+                    .and_then(|value| value.get(name))
+                    .and_then(|value| value.as_str())
+                    .unwrap_or("unknown")
+                    .to_string()
+            };
+            match command.status.as_str() {
+                "completed" if field("status") == "ok" && field("container_state") == "running" => {
+                    vec![draft(
+                        DeploymentEventKind::HealthGatePassed,
+                        DeploymentEventClassification::Success,
+                        command.updated_at,
+                        format!("{app_code} passed health gate check"),
+                        4,
+                    )]
+                }
+                "completed" | "failed" => vec![draft(
+                    DeploymentEventKind::HealthGateFailed,
+                    DeploymentEventClassification::Failure,
+                    command.updated_at,
+                    format!(
+                        "{app_code} failed health gate check ({}, {})",
+                        field("container_state"),
+                        field("status")
+                    ),
+                    4,
+                )],
+                _ => Vec::new(),
+            }
+        }
+        RolloutStep::Rollback => {
+            let mut drafts = vec![draft(
+                DeploymentEventKind::RollbackStarted,
+                DeploymentEventClassification::Progress,
+                command.created_at,
+                format!("Rolling {app_code} back to its last known-good bundle"),
+                1,
+            )];
+            match command.status.as_str() {
+                "completed" => drafts.push(draft(
+                    DeploymentEventKind::RollbackCompleted,
+                    DeploymentEventClassification::Success,
+                    command.updated_at,
+                    format!("{app_code} rolled back to its last known-good bundle"),
+                    4,
+                )),
+                "failed" | "cancelled" => drafts.push(draft(
+                    DeploymentEventKind::RollbackFailed,
+                    DeploymentEventClassification::Failure,
+                    command.updated_at,
+                    format!("Rollback of {app_code} failed"),
+                    4,
+                )),
+                _ => {}
+            }
+            drafts
+        }
+    }
+}
+
+/// Outcome of `backup_volume` and `restore_volume`, read from the agent
+/// report so a completed command carrying a failed backup still shows as
+/// a failure.
+fn backup_drafts(command: &Command) -> Vec<DeploymentEventDraft> {
+    if !matches!(command.status.as_str(), "completed" | "failed") {
+        return Vec::new();
+    }
+    let app_code = command
+        .parameters
+        .as_ref()
+        .and_then(|params| params.get("app_code"))
+        .and_then(|value| value.as_str())
+        .unwrap_or("app");
+    let result = command.result.clone();
+    let (kind, classification, summary) = match command.r#type.as_str() {
+        "backup_volume" => {
+            match result
+                .and_then(|value| serde_json::from_value::<BackupVolumeCommandReport>(value).ok())
+            {
+                Some(report)
+                    if command.status == "completed" && report.status == BackupStatus::Ok =>
+                {
+                    (
+                        DeploymentEventKind::BackupCreated,
+                        DeploymentEventClassification::Success,
+                        format!(
+                            "Backup {} of {} created ({} artifacts, {} bytes)",
+                            report.backup_id,
+                            report.app_code,
+                            report.artifacts.len(),
+                            report.size_bytes()
+                        ),
+                    )
+                }
+                _ => (
+                    DeploymentEventKind::BackupFailed,
+                    DeploymentEventClassification::Failure,
+                    format!("Backup of {app_code} failed"),
+                ),
+            }
+        }
+        "restore_volume" => {
+            match result
+                .and_then(|value| serde_json::from_value::<RestoreVolumeCommandReport>(value).ok())
+            {
+                Some(report)
+                    if command.status == "completed" && report.status == BackupStatus::Ok =>
+                {
+                    (
+                        DeploymentEventKind::BackupRestored,
+                        DeploymentEventClassification::Success,
+                        format!(
+                            "{} restored from backup {}",
+                            report.app_code, report.backup_id
+                        ),
+                    )
+                }
+                _ => (
+                    DeploymentEventKind::RestoreFailed,
+                    DeploymentEventClassification::Failure,
+                    format!("Restore of {app_code} failed"),
+                ),
+            }
+        }
+        _ => return Vec::new(),
+    };
+    vec![DeploymentEventDraft {
+        kind,
+        classification,
+        occurred_at: command.updated_at,
+        summary,
+        command_id: Some(command.command_id.clone()),
+        command_type: Some(command.r#type.clone()),
+        batch_id: command.batch().map(|batch| batch.id),
+        status: Some(command.status.clone()),
+        retryable: None,
+        remediation_class: None,
+        order_key: 4,
+    }]
+}
+
+/// One queued event per command batch, and one outcome event once every
+/// member has finished.
+fn batch_drafts(commands: &[Command]) -> Vec<DeploymentEventDraft> {
+    let mut batches: BTreeMap<String, Vec<&Command>> = BTreeMap::new();
+    for command in commands {
+        if let Some(batch) = command.batch() {
+            batches.entry(batch.id).or_default().push(command);
+        }
+    }
+
+    let mut drafts = Vec::new();
+    for (batch_id, members) in batches {
+        let status = CommandBatchStatus::from_statuses(
+            members.iter().map(|command| command.status.as_str()),
+        );
+        let draft =
+            |kind, classification, occurred_at, summary: String, order_key| DeploymentEventDraft {
+                kind,
+                classification,
+                occurred_at,
+                summary,
+                command_id: None,
+                command_type: None,
+                batch_id: Some(batch_id.clone()),
+                status: Some(status.to_string()),
+                retryable: None,
+                remediation_class: None,
+                order_key,
+            };
+        let queued_at = members.iter().map(|command| command.created_at).min();
+        let finished_at = members.iter().map(|command| command.updated_at).max();
+        if let Some(queued_at) = queued_at {
+            drafts.push(draft(
+                DeploymentEventKind::BatchQueued,
+                DeploymentEventClassification::Info,
+                queued_at,
+                format!("Batch of {} commands queued", members.len()),
+                0,
+            ));
+        }
+        let Some(finished_at) = finished_at.filter(|_| status.is_terminal()) else {
+            continue;
+        };
+        let count = |wanted: &str| {
+            members
+                .iter()
+                .filter(|command| command.status == wanted)
+                .count()
+        };
+        drafts.push(match status {
+            CommandBatchStatus::Completed => draft(
+                DeploymentEventKind::BatchCompleted,
+                DeploymentEventClassification::Success,
+                finished_at,
+                format!("Batch completed ({} commands)", members.len()),
+                5,
+            ),
+            CommandBatchStatus::Failed => draft(
+                DeploymentEventKind::BatchFailed,
+                DeploymentEventClassification::Failure,
+                finished_at,
+                format!(
+                    "Batch failed: {} failed, {} cancelled, {} completed",
+                    count("failed"),
+                    count("cancelled"),
+                    count("completed")
+                ),
+                5,
+            ),
+            _ => draft(
+                DeploymentEventKind::BatchCancelled,
+                DeploymentEventClassification::Failure,
+                finished_at,
+                format!(
+                    "Batch cancelled ({} commands cancelled)",
+                    count("cancelled")
+                ),
+                5,
+            ),
+        });
+    }
+    drafts
+}
+
 fn extract_message(value: Option<&JsonValue>) -> Option<String> {
     let value = value?;
     if let Some(message) = value.get("message").and_then(|item| item.as_str()) {
@@ -295,7 +610,7 @@ fn parse_typed_error(value: Option<&JsonValue>) -> Option<TypedErrorEnvelope> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Command, Deployment};
+    use crate::models::{Command, Deployment, DeploymentLeaseAudit};
     use serde_json::json;
 
     fn sample_deployment() -> Deployment {
@@ -347,6 +662,7 @@ mod tests {
                 "2026-05-17T08:00:00Z",
                 "2026-05-17T08:05:00Z",
             )],
+            &[],
         );
 
         let json = serde_json::to_value(&feed).expect("event feed should serialize");
@@ -367,6 +683,7 @@ mod tests {
                 "2026-05-17T08:00:00Z",
                 "2026-05-17T08:01:00Z",
             )],
+            &[],
         );
 
         assert_eq!(feed.events[0].kind, DeploymentEventKind::CommandQueued);
@@ -389,7 +706,7 @@ mod tests {
             .unwrap(),
         );
 
-        let feed = DeploymentEventFeed::from_parts(&sample_deployment(), &[command]);
+        let feed = DeploymentEventFeed::from_parts(&sample_deployment(), &[command], &[]);
         let failure = feed
             .events
             .iter()
@@ -406,4 +723,175 @@ mod tests {
             Some(TypedRemediationClass::Capability)
         );
     }
+
+    #[test]
+    fn reports_health_gate_and_rollback_steps() {
+        let mut gate = sample_command(
+            "cmd-1",
+            "completed",
+            "2026-05-17T08:00:00Z",
+            "2026-05-17T08:00:10Z",
+        );
+        gate.r#type = "health".to_string();
+        gate.parameters = Some(json!({"app_code": "web", "rollout": "health_gate"}));
+        gate.result = Some(json!({"status": "unhealthy", "container_state": "exited"}));
+
+        let mut rollback = sample_command(
+            "cmd-2",
+            "completed",
+            "2026-05-17T08:00:20Z",
+            "2026-05-17T08:00:40Z",
+        );
+        rollback.parameters = Some(json!({"app_code": "web", "rollout": "rollback"}));
+
+        let feed = DeploymentEventFeed::from_parts(&sample_deployment(), &[gate, rollback], &[]);
+        let kinds: Vec<&DeploymentEventKind> = feed.events.iter().map(|e| &e.kind).collect();
+        assert!(kinds.contains(&&DeploymentEventKind::HealthGateFailed));
+        assert!(kinds.contains(&&DeploymentEventKind::RollbackStarted));
+        assert!(kinds.contains(&&DeploymentEventKind::RollbackCompleted));
+
+        let failed = feed
+            .events
+            .iter()
+            .find(|e| e.kind == DeploymentEventKind::HealthGateFailed)
+            .unwrap();
+        assert_eq!(
+            failed.summary,
+            "web failed health gate check (exited, unhealthy)"
+        );
+    }
+
+    #[test]
+    fn reports_broken_leases() {
+        let audit = DeploymentLeaseAudit {
+            id: 1,
+            deployment_hash: "deployment_events_online".to_string(),
+            action: "broken".to_string(),
+            actor_user_id: "user-b".to_string(),
+            actor_owner: "bob@example.com (ci)".to_string(),
+            previous_owner: "alice@example.com (laptop)".to_string(),
+            previous_reason: "stacker deploy".to_string(),
+            reason: "stacker deploy".to_string(),
+            created_at: DateTime::parse_from_rfc3339("2026-05-17T08:01:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        };
+
+        let feed = DeploymentEventFeed::from_parts(&sample_deployment(), &[], &[audit]);
+        let broken = feed
+            .events
+            .iter()
+            .find(|e| e.kind == DeploymentEventKind::LeaseBroken)
+            .expect("lease event should exist");
+        assert_eq!(
+            broken.summary,
+            "bob@example.com (ci) broke the deploy lease held by alice@example.com (laptop)"
+        );
+    }
+
+    #[test]
+    fn reports_batch_outcome_as_one_event() {
+        let batch = |command: Command, step: &str, depends_on: &[&str]| {
+            command.with_metadata(json!({
+                "batch": {"id": "batch_1", "step": step, "depends_on": depends_on}
+            }))
+        };
+        let commands = [
+            batch(
+                sample_command(
+                    "cmd-1",
+                    "failed",
+                    "2026-05-17T08:00:00Z",
+                    "2026-05-17T08:03:00Z",
+                ),
+                "deploy",
+                &[],
+            ),
+            batch(
+                sample_command(
+                    "cmd-2",
+                    "cancelled",
+                    "2026-05-17T08:00:00Z",
+                    "2026-05-17T08:03:01Z",
+                ),
+                "proxy",
+                &["deploy"],
+            ),
+        ];
+
+        let feed = DeploymentEventFeed::from_parts(&sample_deployment(), &commands, &[]);
+        let failed = feed
+            .events
+            .iter()
+            .find(|e| e.kind == DeploymentEventKind::BatchFailed)
+            .expect("batch outcome event should exist");
+        assert_eq!(failed.batch_id.as_deref(), Some("batch_1"));
+        assert_eq!(
+            failed.summary,
+            "Batch failed: 1 failed, 1 cancelled, 0 completed"
+        );
+        assert_eq!(
+            feed.events.last().unwrap().kind,
+            DeploymentEventKind::BatchFailed
+        );
+        assert!(feed
+            .events
+            .iter()
+            .any(|e| e.kind == DeploymentEventKind::BatchQueued));
+    }
+
+    #[test]
+    fn reports_backup_outcome_from_agent_report() {
+        let mut backup = sample_command(
+            "cmd-1",
+            "completed",
+            "2026-05-17T08:00:00Z",
+            "2026-05-17T08:02:00Z",
+        );
+        backup.r#type = "backup_volume".to_string();
+        backup.result = Some(json!({
+            "type": "backup_volume",
+            "deployment_hash": "deployment_events_online",
+            "app_code": "db",
+            "backup_id": "bk_1",
+            "status": "ok",
+            "artifacts": [{"name": "db_data", "location": "/var/backups/bk_1.tar.gz", "size_bytes": 2048}]
+        }));
+        let mut restore = sample_command(
+            "cmd-2",
+            "completed",
+            "2026-05-17T08:10:00Z",
+            "2026-05-17T08:11:00Z",
+        );
+        restore.r#type = "restore_volume".to_string();
+        restore.parameters = Some(json!({"app_code": "db", "backup_id": "bk_1"}));
+        restore.result = Some(json!({
+            "type": "restore_volume",
+            "deployment_hash": "deployment_events_online",
+            "app_code": "db",
+            "backup_id": "bk_1",
+            "status": "failed",
+            "errors": [{"code": "checksum", "message": "Archive checksum mismatch"}]
+        }));
+
+        let feed = DeploymentEventFeed::from_parts(&sample_deployment(), &[backup, restore], &[]);
+        let created = feed
+            .events
+            .iter()
+            .find(|e| e.kind == DeploymentEventKind::BackupCreated)
+            .expect("backup event should exist");
+        assert_eq!(
+            created.summary,
+            "Backup bk_1 of db created (1 artifacts, 2048 bytes)"
+        );
+        let restore_failed = feed
+            .events
+            .iter()
+            .find(|e| e.kind == DeploymentEventKind::RestoreFailed)
+            .expect("restore event should exist");
+        assert_eq!(
+            restore_failed.classification,
+            DeploymentEventClassification::Failure
+        );
+    }
 }
@@ -0,0 +1,187 @@
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{
+    db,
+    models::{DeploymentLease, DeploymentLeaseAudit},
+    services::TypedErrorEnvelope,
+};
+
+pub const DEFAULT_LEASE_TTL_SECS: i64 = 900;
+const MIN_LEASE_TTL_SECS: i64 = 60;
+const MAX_LEASE_TTL_SECS: i64 = 7200;
+
+/// Body of `POST /api/v1/deployments/{hash}/lease`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaseRequest {
+    pub owner: String,
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<i64>,
+    /// Renew this lease instead of taking a new one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease_id: Option<String>,
+    /// Take the lease even if someone else holds it; recorded in the audit log.
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn lease_ttl(requested: Option<i64>) -> Duration {
+    Duration::seconds(
+        requested
+            .unwrap_or(DEFAULT_LEASE_TTL_SECS)
+            .clamp(MIN_LEASE_TTL_SECS, MAX_LEASE_TTL_SECS),
+    )
+}
+
+pub fn deployment_locked_error(lease: &DeploymentLease) -> TypedErrorEnvelope {
+    let reason = if lease.reason.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", lease.reason)
+    };
+    TypedErrorEnvelope::deployment_locked(format!(
+        "Deployment is locked by {}{} until {}. Retry later or pass --force to break the lease.",
+        lease.owner,
+        reason,
+        lease.expires_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ))
+    .with_context("deploymentHash", lease.deployment_hash.clone())
+    .with_context("owner", lease.owner.clone())
+    .with_context("expiresAt", lease.expires_at.to_rfc3339())
+}
+
+/// Acquire or renew the deploy lease. A forced takeover of someone else's
+/// lease is written to `deployment_lease_audit`.
+pub async fn acquire_lease(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    user_id: &str,
+    request: &LeaseRequest,
+) -> Result<DeploymentLease, TypedErrorEnvelope> {
+    if request.owner.trim().is_empty() {
+        return Err(TypedErrorEnvelope::invalid_request(
+            "Lease owner is required",
+        ));
+    }
+
+    let current = db::deployment_lease::fetch_active(pg_pool, deployment_hash)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?;
+    let lease = DeploymentLease {
+        deployment_hash: deployment_hash.to_string(),
+        lease_id: request
+            .lease_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        owner: request.owner.trim().to_string(),
+        user_id: user_id.to_string(),
+        reason: request.reason.trim().to_string(),
+        acquired_at: Utc::now(),
+        expires_at: Utc::now() + lease_ttl(request.ttl_secs),
+    };
+
+    let Some(acquired) = db::deployment_lease::acquire(pg_pool, &lease, request.force)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?
+    else {
+        let holder = match current {
+            Some(holder) => holder,
+            None => db::deployment_lease::fetch_active(pg_pool, deployment_hash)
+                .await
+                .map_err(TypedErrorEnvelope::internal_error)?
+                .ok_or_else(|| {
+                    TypedErrorEnvelope::internal_error("Deployment lease changed; retry")
+                })?,
+        };
+        return Err(deployment_locked_error(&holder));
+    };
+
+    if let Some(previous) = current.filter(|previous| previous.lease_id != acquired.lease_id) {
+        tracing::warn!(
+            deployment_hash,
+            previous_owner = %previous.owner,
+            owner = %acquired.owner,
+            "Deployment lease broken with --force"
+        );
+        db::deployment_lease::insert_audit(
+            pg_pool,
+            &DeploymentLeaseAudit {
+                id: 0,
+                deployment_hash: deployment_hash.to_string(),
+                action: "broken".to_string(),
+                actor_user_id: user_id.to_string(),
+                actor_owner: acquired.owner.clone(),
+                previous_owner: previous.owner,
+                previous_reason: previous.reason,
+                reason: acquired.reason.clone(),
+                created_at: Utc::now(),
+            },
+        )
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?;
+    }
+
+    Ok(acquired)
+}
+
+/// Reject a mutation while another client holds the lease. `lease_id` is the
+/// caller's own lease, if it has one.
+pub async fn ensure_lease_holder(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    lease_id: Option<&str>,
+) -> Result<(), TypedErrorEnvelope> {
+    let current = db::deployment_lease::fetch_active(pg_pool, deployment_hash)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?;
+    match current {
+        Some(lease) if !holds_lease(&lease, lease_id) => Err(deployment_locked_error(&lease)),
+        _ => Ok(()),
+    }
+}
+
+fn holds_lease(lease: &DeploymentLease, lease_id: Option<&str>) -> bool {
+    !lease.is_active() || lease_id == Some(lease.lease_id.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lease_ttl_is_clamped_and_holder_is_matched_by_id() {
+        assert_eq!(lease_ttl(None), Duration::seconds(DEFAULT_LEASE_TTL_SECS));
+        assert_eq!(lease_ttl(Some(5)), Duration::seconds(MIN_LEASE_TTL_SECS));
+        assert_eq!(
+            lease_ttl(Some(86_400)),
+            Duration::seconds(MAX_LEASE_TTL_SECS)
+        );
+
+        let mut lease = DeploymentLease {
+            deployment_hash: "deployment_abc".to_string(),
+            lease_id: "lease-1".to_string(),
+            owner: "alice@example.com (laptop)".to_string(),
+            user_id: "user-1".to_string(),
+            reason: "stacker deploy".to_string(),
+            acquired_at: Utc::now(),
+            expires_at: Utc::now() + Duration::minutes(5),
+        };
+        assert!(holds_lease(&lease, Some("lease-1")));
+        assert!(!holds_lease(&lease, Some("lease-2")));
+        assert!(!holds_lease(&lease, None));
+
+        let error = deployment_locked_error(&lease);
+        assert!(error.retryable);
+        assert!(error
+            .message
+            .contains("alice@example.com (laptop) (stacker deploy)"));
+        assert_eq!(error.context["owner"], "alice@example.com (laptop)");
+
+        lease.expires_at = Utc::now() - Duration::minutes(1);
+        assert!(holds_lease(&lease, None));
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
         extract_capabilities, has_capability, has_capability_value, remote_runtime_compose_path,
         remote_runtime_env_path, NPM_CREDENTIAL_SOURCE_KEY,
     },
-    models::{Agent, Command, Deployment, Project, ProjectApp},
+    models::{Agent, Command, Deployment, DeploymentLease, Project, ProjectApp},
 };
 
 pub const DEPLOYMENT_STATE_SCHEMA_VERSION: &str = "v1alpha1";
@@ -24,6 +24,9 @@ pub struct DeploymentState {
     pub drift: DeploymentDriftState,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_command: Option<DeploymentLastCommandState>,
+    /// Active deploy lease, present while a deploy or rollback holds it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease: Option<DeploymentLeaseState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -101,6 +104,15 @@ pub struct DeploymentLastCommandState {
     pub finished_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentLeaseState {
+    pub owner: String,
+    pub reason: String,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
 impl DeploymentState {
     pub fn from_parts(
         project: &Project,
@@ -108,6 +120,7 @@ impl DeploymentState {
         agent: Option<&Agent>,
         apps: &[ProjectApp],
         last_command: Option<&Command>,
+        lease: Option<&DeploymentLease>,
     ) -> Self {
         let capabilities = agent
             .map(|item| extract_capabilities(item.capabilities.clone()))
@@ -179,6 +192,14 @@ impl DeploymentState {
                 status: command.status.clone(),
                 finished_at: command.updated_at,
             }),
+            lease: lease
+                .filter(|lease| lease.is_active())
+                .map(|lease| DeploymentLeaseState {
+                    owner: lease.owner.clone(),
+                    reason: lease.reason.clone(),
+                    acquired_at: lease.acquired_at,
+                    expires_at: lease.expires_at,
+                }),
         }
     }
 
@@ -201,6 +222,7 @@ impl DeploymentState {
             .await?
             .into_iter()
             .next();
+        let lease = db::deployment_lease::fetch_active(pool, deployment_hash).await?;
 
         Ok(Some(Self::from_parts(
             &project,
@@ -208,6 +230,7 @@ impl DeploymentState {
             agent.as_ref(),
             &apps,
             last_command.as_ref(),
+            lease.as_ref(),
         )))
     }
 }
@@ -215,7 +238,7 @@ impl DeploymentState {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Agent, Command, Deployment, Project, ProjectApp};
+    use crate::models::{Agent, Command, Deployment, DeploymentLease, Project, ProjectApp};
     use serde_json::json;
 
     fn sample_project() -> Project {
@@ -285,10 +308,20 @@ mod tests {
                 )
                 .mark_completed(),
             ),
+            Some(&DeploymentLease {
+                deployment_hash: "deployment_state_online".to_string(),
+                lease_id: "lease-1".to_string(),
+                owner: "alice@example.com (laptop)".to_string(),
+                user_id: "user-a".to_string(),
+                reason: "stacker deploy".to_string(),
+                acquired_at: Utc::now(),
+                expires_at: Utc::now() + chrono::Duration::minutes(15),
+            }),
         );
 
         let json = serde_json::to_value(&state).expect("state should serialize");
         assert_eq!(json["schemaVersion"], DEPLOYMENT_STATE_SCHEMA_VERSION);
+        assert_eq!(json["lease"]["owner"], "alice@example.com (laptop)");
         assert_eq!(
             json["deployment"]["deploymentHash"],
             "deployment_state_online"
@@ -305,11 +338,13 @@ mod tests {
             None,
             &[],
             None,
+            None,
         );
 
         let json = serde_json::to_value(&state).expect("state should serialize");
         assert_eq!(json["agent"]["status"], "offline");
         assert!(json["agent"].get("id").is_none());
         assert!(json.get("lastCommand").is_none());
+        assert!(json.get("lease").is_none());
     }
 }
@@ -68,6 +68,9 @@ pub struct ExplainTopologyService {
     pub code: String,
     pub name: String,
     pub enabled: bool,
+    /// `deploy.targets` profile running the service in a multi-host stack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
 }
 
 pub fn build_explain_env(
@@ -251,11 +254,13 @@ mod tests {
                     code: "device-api".to_string(),
                     name: "Device API".to_string(),
                     enabled: true,
+                    host: None,
                 },
                 ExplainTopologyService {
                     code: "upload".to_string(),
                     name: "Upload".to_string(),
                     enabled: true,
+                    host: None,
                 },
             ],
         );
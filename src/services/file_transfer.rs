@@ -0,0 +1,246 @@
+//! File copies between the CLI and containers (`stacker agent cp`).
+//!
+//! A file travels as a series of `upload_file` or `download_file` commands,
+//! one chunk each, through the regular command queue, so the CLI still never
+//! talks to the agent directly. Every chunk carries its SHA-256 and the
+//! whole file is checked against its digest at the end. Transfer ids are
+//! derived from what is copied: rerunning an interrupted copy reuses the
+//! agent's staged chunks instead of starting over.
+//!
+//! Chunk bodies are not kept in the command log: an upload chunk is cleared
+//! when the agent reports it, a download chunk once the owner has fetched it,
+//! and [`spawn_chunk_purge`] clears whatever nobody acknowledged.
+
+use std::time::Duration as StdDuration;
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db;
+use crate::forms::status_panel::{
+    DownloadFileCommandReport, FileTransferStatus, UploadFileCommandReport,
+    UploadFileCommandRequest,
+};
+use crate::models::{AuditLog, Command};
+
+pub const UPLOAD_FILE_COMMAND_TYPE: &str = "upload_file";
+pub const DOWNLOAD_FILE_COMMAND_TYPE: &str = "download_file";
+/// Chunk size the CLI sends and asks for.
+pub const DEFAULT_CHUNK_BYTES: usize = 512 * 1024;
+/// Chunks still stored this long after their command last changed are
+/// cleared; the CLI gives up on a chunk long before.
+const CHUNK_RETENTION_MINUTES: i64 = 30;
+const CHUNK_PURGE_INTERVAL_SECS: u64 = 600;
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn transfer_id(prefix: &str, parts: &[&str]) -> String {
+    format!(
+        "{}_{}",
+        prefix,
+        &sha256_hex(parts.join("\n").as_bytes())[..32]
+    )
+}
+
+/// Same file to the same place resumes the same upload.
+pub fn upload_transfer_id(
+    deployment_hash: &str,
+    app_code: &str,
+    path: &str,
+    sha256: &str,
+) -> String {
+    transfer_id("up", &[deployment_hash, app_code, path, sha256])
+}
+
+pub fn download_transfer_id(deployment_hash: &str, app_code: &str, path: &str) -> String {
+    transfer_id("down", &[deployment_hash, app_code, path])
+}
+
+/// Split `<app>:<absolute path>` into app code and path. Anything else,
+/// including Windows paths such as `C:\data`, is a local path.
+pub fn parse_container_path(spec: &str) -> Option<(&str, &str)> {
+    let (app_code, path) = spec.split_once(':')?;
+    let valid_app = !app_code.is_empty()
+        && app_code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    (valid_app && path.starts_with('/')).then_some((app_code, path))
+}
+
+/// Audit entry for a reported chunk that finished or failed a transfer.
+/// Intermediate chunks are not audited.
+pub fn transfer_audit_log(agent_id: Uuid, command: &Command, result: &Value) -> Option<AuditLog> {
+    let path = command
+        .parameters
+        .as_ref()
+        .and_then(|params| params.get("path"))
+        .cloned()
+        .unwrap_or(Value::Null);
+
+    let (action, status, mut details) = match command.r#type.as_str() {
+        UPLOAD_FILE_COMMAND_TYPE => {
+            let report: UploadFileCommandReport = serde_json::from_value(result.clone()).ok()?;
+            let request = command
+                .parameters
+                .clone()
+                .and_then(|params| serde_json::from_value::<UploadFileCommandRequest>(params).ok());
+            let action = match (report.status, report.complete) {
+                (FileTransferStatus::Failed, _) => "agent.file_upload_failed",
+                (FileTransferStatus::Ok, true) => "agent.file_uploaded",
+                (FileTransferStatus::Ok, false) => return None,
+            };
+            let details = json!({
+                "transfer_id": report.transfer_id,
+                "app_code": report.app_code,
+                "size_bytes": request.as_ref().map(|request| request.total_size),
+                "sha256": request.as_ref().map(|request| request.sha256.clone()),
+                "errors": report.errors,
+            });
+            (action, report.status, details)
+        }
+        DOWNLOAD_FILE_COMMAND_TYPE => {
+            let report: DownloadFileCommandReport = serde_json::from_value(result.clone()).ok()?;
+            let chunk_len = general_purpose::STANDARD
+                .decode(&report.data)
+                .map_or(0, |chunk| chunk.len() as u64);
+            let action = match report.status {
+                FileTransferStatus::Failed => "agent.file_download_failed",
+                FileTransferStatus::Ok if report.offset + chunk_len >= report.total_size => {
+                    "agent.file_downloaded"
+                }
+                FileTransferStatus::Ok => return None,
+            };
+            let details = json!({
+                "transfer_id": report.transfer_id,
+                "app_code": report.app_code,
+                "size_bytes": report.total_size,
+                "sha256": report.sha256,
+                "errors": report.errors,
+            });
+            (action, report.status, details)
+        }
+        _ => return None,
+    };
+
+    details["command_id"] = json!(command.command_id);
+    details["path"] = path;
+    details["requested_by"] = json!(command.created_by);
+    let status = match status {
+        FileTransferStatus::Ok => "success",
+        FileTransferStatus::Failed => "failed",
+    };
+    Some(
+        AuditLog::new(
+            Some(agent_id),
+            Some(command.deployment_hash.clone()),
+            action.to_string(),
+            Some(status.to_string()),
+        )
+        .with_details(details),
+    )
+}
+
+/// Whether the owner fetching `command` has received a download chunk, so
+/// it can be cleared.
+pub fn is_fetched_download(command: &Command) -> bool {
+    command.r#type == DOWNLOAD_FILE_COMMAND_TYPE
+        && command.status == "completed"
+        && command
+            .result
+            .as_ref()
+            .is_some_and(|result| result.get("data").is_some())
+}
+
+/// Background task clearing file chunks older than
+/// [`CHUNK_RETENTION_MINUTES`].
+pub fn spawn_chunk_purge(pg_pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(StdDuration::from_secs(CHUNK_PURGE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let cutoff = Utc::now() - Duration::minutes(CHUNK_RETENTION_MINUTES);
+            match db::command::purge_file_chunks(&pg_pool, cutoff).await {
+                Ok(0) => {}
+                Ok(cleared) => tracing::info!("Cleared {} expired file chunks", cleared),
+                Err(err) => tracing::warn!("File chunk purge failed: {}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn download_command() -> Command {
+        let mut command = Command::new(
+            "cmd_1".to_string(),
+            "dep_1".to_string(),
+            DOWNLOAD_FILE_COMMAND_TYPE.to_string(),
+            "user_1".to_string(),
+        );
+        command.parameters = Some(json!({"path": "/var/log/app.log"}));
+        command
+    }
+
+    fn download_result(offset: u64, total_size: u64) -> Value {
+        json!({
+            "type": "download_file",
+            "deployment_hash": "dep_1",
+            "app_code": "web",
+            "transfer_id": "down_1",
+            "status": "ok",
+            "total_size": total_size,
+            "sha256": "ab",
+            "offset": offset,
+            "data": general_purpose::STANDARD.encode(b"hello"),
+            "chunk_sha256": sha256_hex(b"hello"),
+        })
+    }
+
+    #[test]
+    fn only_the_last_chunk_of_a_transfer_is_audited() {
+        let agent_id = Uuid::new_v4();
+        let command = download_command();
+        assert!(transfer_audit_log(agent_id, &command, &download_result(0, 10)).is_none());
+
+        let audit = transfer_audit_log(agent_id, &command, &download_result(5, 10)).unwrap();
+        assert_eq!(audit.action, "agent.file_downloaded");
+        assert_eq!(audit.details["path"], "/var/log/app.log");
+        assert_eq!(audit.details["requested_by"], "user_1");
+    }
+
+    #[test]
+    fn only_completed_downloads_with_data_are_cleared_on_fetch() {
+        let mut command = download_command();
+        command.result = Some(download_result(0, 10));
+        assert!(!is_fetched_download(&command));
+
+        command.status = "completed".to_string();
+        assert!(is_fetched_download(&command));
+
+        command.result = Some(json!({"type": "download_file", "status": "ok"}));
+        assert!(!is_fetched_download(&command));
+    }
+
+    #[test]
+    fn container_paths_need_an_app_and_absolute_path() {
+        assert_eq!(
+            parse_container_path("web:/etc/nginx/nginx.conf"),
+            Some(("web", "/etc/nginx/nginx.conf"))
+        );
+        assert_eq!(parse_container_path("./web:/etc"), None);
+        assert_eq!(parse_container_path("C:\\data\\dump.sql"), None);
+        assert_eq!(parse_container_path("notes.txt"), None);
+        assert_ne!(
+            upload_transfer_id("dep", "web", "/a", "1"),
+            upload_transfer_id("dep", "web", "/a", "2")
+        );
+    }
+}
@@ -0,0 +1,461 @@
+//! Retained, searchable container logs.
+//!
+//! Unlike [`crate::services::log_cache`], which keeps a short-lived copy of
+//! the lines someone just fetched, agents stream every container's output to
+//! `POST /api/v1/agent/logs` as it is written. Lines go to the configured
+//! [`LogStore`]: the day-partitioned `container_logs` table with a full-text
+//! index on the message (kept for `log_shipping.retention_days`), or a Loki
+//! instance that applies its own retention. `stacker logs --search`, the
+//! deployment logs API and the `search_logs` MCP tool all read through the
+//! same store.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+use crate::configuration::{LogShippingBackend, LogShippingSettings};
+use crate::db;
+use crate::forms::status_panel::ShippedLogLine;
+use crate::models::ContainerLogLine;
+use crate::services::container_metrics::{check_timestamp_within, parse_window_within};
+
+/// Lines returned by a search unless fewer are asked for.
+pub const DEFAULT_SEARCH_LINES: i64 = 500;
+/// Most lines returned by one search.
+pub const MAX_SEARCH_LINES: i64 = 5_000;
+const MAINTENANCE_INTERVAL_SECS: u64 = 3600;
+const LOKI_TIMEOUT_SECS: u64 = 15;
+
+/// A validated log search over one deployment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSearchQuery {
+    pub deployment_hash: String,
+    pub since: DateTime<Utc>,
+    pub app_code: Option<String>,
+    /// Words or quoted phrases to match; `None` returns every line.
+    pub text: Option<String>,
+    pub limit: i64,
+}
+
+impl LogSearchQuery {
+    /// Build a search from user input. `since` defaults to `1h` and may not
+    /// reach further back than the retention.
+    pub fn parse(
+        settings: &LogShippingSettings,
+        deployment_hash: &str,
+        since: Option<&str>,
+        app_code: Option<&str>,
+        text: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<Self, String> {
+        let window = parse_window_within(since.unwrap_or("1h"), settings.retention_days, "log")?;
+        let non_empty = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        Ok(Self {
+            deployment_hash: deployment_hash.to_string(),
+            since: Utc::now() - window,
+            app_code: non_empty(app_code),
+            text: non_empty(text),
+            limit: limit
+                .unwrap_or(DEFAULT_SEARCH_LINES)
+                .clamp(1, MAX_SEARCH_LINES),
+        })
+    }
+}
+
+/// Reject lines stamped outside the retained window or ahead of `now`, which
+/// would otherwise sit in the default partition past their retention.
+pub fn check_line_times(
+    settings: &LogShippingSettings,
+    lines: &[ShippedLogLine],
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    lines.iter().try_for_each(|line| {
+        check_timestamp_within("logs.ts", line.ts, now, settings.retention_days)
+    })
+}
+
+/// Where shipped logs are written and searched.
+#[async_trait]
+pub trait LogStore: Send + Sync {
+    fn backend(&self) -> LogShippingBackend;
+
+    /// Store lines of one deployment. Returns how many were accepted.
+    async fn push(
+        &self,
+        pool: &PgPool,
+        deployment_hash: &str,
+        lines: &[ShippedLogLine],
+    ) -> Result<u64, String>;
+
+    /// The newest `query.limit` matching lines, oldest first.
+    async fn search(
+        &self,
+        pool: &PgPool,
+        query: &LogSearchQuery,
+    ) -> Result<Vec<ContainerLogLine>, String>;
+}
+
+pub struct PostgresLogStore;
+
+#[async_trait]
+impl LogStore for PostgresLogStore {
+    fn backend(&self) -> LogShippingBackend {
+        LogShippingBackend::Postgres
+    }
+
+    async fn push(
+        &self,
+        pool: &PgPool,
+        deployment_hash: &str,
+        lines: &[ShippedLogLine],
+    ) -> Result<u64, String> {
+        db::container_logs::insert_lines(pool, deployment_hash, lines).await
+    }
+
+    async fn search(
+        &self,
+        pool: &PgPool,
+        query: &LogSearchQuery,
+    ) -> Result<Vec<ContainerLogLine>, String> {
+        let mut lines = db::container_logs::search(
+            pool,
+            &query.deployment_hash,
+            query.since,
+            query.app_code.as_deref(),
+            query.text.as_deref(),
+            query.limit,
+        )
+        .await?;
+        lines.reverse();
+        Ok(lines)
+    }
+}
+
+/// Pushes to `{loki_url}/loki/api/v1/push` and searches with `query_range`.
+/// Lines are labelled with `deployment_hash`, `app_code`, `stream` and, when
+/// known, `container`; searches are case-insensitive substring matches.
+pub struct LokiLogStore {
+    http: reqwest::Client,
+    base_url: String,
+    tenant: Option<String>,
+}
+
+impl LokiLogStore {
+    pub fn new(base_url: &str, tenant: Option<String>) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(StdDuration::from_secs(LOKI_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| format!("Failed to build Loki client: {}", e))?;
+        Ok(Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            tenant,
+        })
+    }
+
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.tenant {
+            Some(tenant) => builder.header("X-Scope-OrgID", tenant),
+            None => builder,
+        }
+    }
+}
+
+#[async_trait]
+impl LogStore for LokiLogStore {
+    fn backend(&self) -> LogShippingBackend {
+        LogShippingBackend::Loki
+    }
+
+    async fn push(
+        &self,
+        _pool: &PgPool,
+        deployment_hash: &str,
+        lines: &[ShippedLogLine],
+    ) -> Result<u64, String> {
+        if lines.is_empty() {
+            return Ok(0);
+        }
+        let url = format!("{}/loki/api/v1/push", self.base_url);
+        let resp = self
+            .request(self.http.post(&url))
+            .json(&loki_push_body(deployment_hash, lines))
+            .send()
+            .await
+            .map_err(|e| format!("Loki unreachable: {}", e))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("Loki push failed ({}): {}", status, body));
+        }
+        Ok(lines.len() as u64)
+    }
+
+    async fn search(
+        &self,
+        _pool: &PgPool,
+        query: &LogSearchQuery,
+    ) -> Result<Vec<ContainerLogLine>, String> {
+        let url = format!("{}/loki/api/v1/query_range", self.base_url);
+        let start = unix_nanos(query.since).to_string();
+        let end = unix_nanos(Utc::now()).to_string();
+        let limit = query.limit.to_string();
+        let resp = self
+            .request(self.http.get(&url))
+            .query(&[
+                ("query", logql_query(query).as_str()),
+                ("start", start.as_str()),
+                ("end", end.as_str()),
+                ("limit", limit.as_str()),
+                ("direction", "backward"),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Loki unreachable: {}", e))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("Loki query failed ({}): {}", status, body));
+        }
+        let body: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Loki response: {}", e))?;
+        Ok(parse_loki_streams(&body, query.limit))
+    }
+}
+
+/// The configured log store.
+pub fn init_log_store(settings: &LogShippingSettings) -> Result<Arc<dyn LogStore>, String> {
+    match settings.backend {
+        LogShippingBackend::Postgres => Ok(Arc::new(PostgresLogStore)),
+        LogShippingBackend::Loki => {
+            let url = settings
+                .loki_url
+                .as_deref()
+                .filter(|url| !url.trim().is_empty())
+                .ok_or_else(|| {
+                    "log_shipping.backend is 'loki' but no loki_url (STACKER_LOKI_URL) is set"
+                        .to_string()
+                })?;
+            tracing::info!("Shipping container logs to Loki at {}", url);
+            Ok(Arc::new(LokiLogStore::new(
+                url,
+                settings.loki_tenant.clone(),
+            )?))
+        }
+    }
+}
+
+fn unix_nanos(at: DateTime<Utc>) -> i64 {
+    at.timestamp_nanos_opt().unwrap_or_default()
+}
+
+/// Loki push payload: one stream per app, container and output stream.
+pub fn loki_push_body(deployment_hash: &str, lines: &[ShippedLogLine]) -> Value {
+    let mut streams: BTreeMap<(&str, Option<&str>, &str), Vec<Value>> = BTreeMap::new();
+    for line in lines {
+        streams
+            .entry((
+                line.app_code.as_str(),
+                line.container.as_deref(),
+                line.stream.as_str(),
+            ))
+            .or_default()
+            .push(json!([unix_nanos(line.ts).to_string(), line.message]));
+    }
+    let streams: Vec<Value> = streams
+        .into_iter()
+        .map(|((app_code, container, stream), values)| {
+            let mut labels = json!({
+                "deployment_hash": deployment_hash,
+                "app_code": app_code,
+                "stream": stream,
+            });
+            if let Some(container) = container {
+                labels["container"] = json!(container);
+            }
+            json!({ "stream": labels, "values": values })
+        })
+        .collect();
+    json!({ "streams": streams })
+}
+
+fn logql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// LogQL selecting the query's lines, e.g.
+/// `{deployment_hash="abc", app_code="api"} |~ "(?i)timeout"`.
+pub fn logql_query(query: &LogSearchQuery) -> String {
+    let mut selector = format!("deployment_hash={}", logql_string(&query.deployment_hash));
+    if let Some(app_code) = &query.app_code {
+        selector.push_str(&format!(", app_code={}", logql_string(app_code)));
+    }
+    let mut logql = format!("{{{}}}", selector);
+    if let Some(text) = &query.text {
+        let pattern: String = text
+            .chars()
+            .flat_map(|c| {
+                let escape = "\\.+*?()|[]{}^$".contains(c);
+                escape.then_some('\\').into_iter().chain(Some(c))
+            })
+            .collect();
+        logql.push_str(&format!(
+            " |~ {}",
+            logql_string(&format!("(?i){}", pattern))
+        ));
+    }
+    logql
+}
+
+/// Lines of a `query_range` streams result, newest `limit` kept, oldest first.
+pub fn parse_loki_streams(body: &Value, limit: i64) -> Vec<ContainerLogLine> {
+    let mut lines = Vec::new();
+    let results = body["data"]["result"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    for result in &results {
+        let label = |name: &str| result["stream"][name].as_str().map(str::to_string);
+        for value in result["values"].as_array().into_iter().flatten() {
+            let (Some(ts), Some(message)) = (
+                value[0].as_str().and_then(|ts| ts.parse::<i64>().ok()),
+                value[1].as_str(),
+            ) else {
+                continue;
+            };
+            lines.push(ContainerLogLine {
+                deployment_hash: label("deployment_hash").unwrap_or_default(),
+                app_code: label("app_code").unwrap_or_default(),
+                container: label("container"),
+                logged_at: DateTime::from_timestamp_nanos(ts),
+                stream: label("stream").unwrap_or_else(|| "stdout".to_string()),
+                message: message.to_string(),
+            });
+        }
+    }
+    lines.sort_by(|a, b| b.logged_at.cmp(&a.logged_at));
+    lines.truncate(limit.max(0) as usize);
+    lines.reverse();
+    lines
+}
+
+/// Create today's and tomorrow's partitions and drop expired days.
+pub async fn maintain_partitions(
+    pg_pool: &PgPool,
+    retention_days: i64,
+) -> Result<Vec<String>, String> {
+    db::container_logs::PARTITIONS
+        .maintain(pg_pool, retention_days)
+        .await
+}
+
+/// Background task running [`maintain_partitions`] every hour. Loki keeps
+/// its own retention, so nothing runs for that backend.
+pub fn spawn_log_maintenance(pg_pool: PgPool, settings: &LogShippingSettings) {
+    if settings.backend != LogShippingBackend::Postgres {
+        return;
+    }
+    let retention_days = settings.retention_days;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(StdDuration::from_secs(MAINTENANCE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match maintain_partitions(&pg_pool, retention_days).await {
+                Ok(dropped) if !dropped.is_empty() => {
+                    tracing::info!("Dropped expired container logs: {}", dropped.join(", "))
+                }
+                Ok(_) => {}
+                Err(err) => tracing::warn!("Container logs maintenance failed: {}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forms::status_panel::LogStream;
+
+    fn line(app_code: &str, second: u32, stream: LogStream, message: &str) -> ShippedLogLine {
+        ShippedLogLine {
+            app_code: app_code.to_string(),
+            container: None,
+            ts: DateTime::parse_from_rfc3339(&format!("2026-10-18T12:00:{:02}Z", second))
+                .unwrap()
+                .with_timezone(&Utc),
+            stream,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn loki_streams_group_lines_and_round_trip() {
+        let lines = vec![
+            line("api", 1, LogStream::Stdout, "GET /health 200"),
+            line("api", 2, LogStream::Stderr, "upstream timeout"),
+            line("api", 3, LogStream::Stdout, "GET /orders 504"),
+        ];
+        let body = loki_push_body("dep_1", &lines);
+        let streams = body["streams"].as_array().unwrap();
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0]["stream"]["stream"], "stderr");
+        assert_eq!(streams[1]["values"].as_array().unwrap().len(), 2);
+        assert!(streams[0]["stream"].get("container").is_none());
+
+        let response = json!({ "data": { "result": body["streams"] } });
+        let parsed = parse_loki_streams(&response, 2);
+        let messages: Vec<&str> = parsed.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(messages, vec!["upstream timeout", "GET /orders 504"]);
+        assert_eq!(parsed[0].logged_at, lines[1].ts);
+        assert_eq!(parsed[0].deployment_hash, "dep_1");
+    }
+
+    #[test]
+    fn shipped_lines_must_be_recent() {
+        let settings = LogShippingSettings::default();
+        let lines = vec![line("api", 1, LogStream::Stdout, "ready")];
+        let now = lines[0].ts;
+        assert!(check_line_times(&settings, &lines, now).is_ok());
+        assert!(check_line_times(&settings, &lines, now - chrono::Duration::hours(1)).is_err());
+        assert!(check_line_times(&settings, &lines, now + chrono::Duration::days(30)).is_err());
+    }
+
+    #[test]
+    fn search_queries_are_bounded_by_retention() {
+        let settings = LogShippingSettings::default();
+        let query = LogSearchQuery::parse(
+            &settings,
+            "dep_1",
+            Some("24h"),
+            Some("api"),
+            Some(" "),
+            None,
+        )
+        .unwrap();
+        assert_eq!(query.text, None);
+        assert_eq!(query.limit, DEFAULT_SEARCH_LINES);
+        assert!(LogSearchQuery::parse(&settings, "dep_1", Some("30d"), None, None, None).is_err());
+
+        let query = LogSearchQuery {
+            text: Some("time\"out (5s)".to_string()),
+            limit: MAX_SEARCH_LINES,
+            ..query
+        };
+        assert_eq!(
+            logql_query(&query),
+            r#"{deployment_hash="dep_1", app_code="api"} |~ "(?i)time\"out \\(5s\\)""#
+        );
+    }
+}
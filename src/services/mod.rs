@@ -1,33 +1,49 @@
+pub mod agent_compatibility;
 pub mod agent_dispatcher;
+pub mod agent_identity;
+pub mod backup;
+pub mod command_batch;
+pub mod command_notifier;
+pub mod command_queue;
+pub mod command_schedule;
 pub mod config_renderer;
+pub mod container_metrics;
 pub mod dag_executor;
 pub mod deploy_plan;
 pub mod deployment_events;
 pub mod deployment_identifier;
+pub mod deployment_lease;
 pub mod deployment_state;
 pub mod env_contract;
 pub mod env_model;
 pub mod explain;
+pub mod file_transfer;
 pub mod grpc_pipe;
 pub mod handoff;
 pub mod log_cache;
+pub mod log_shipping;
 pub mod marketplace_access;
 pub mod marketplace_assets;
 pub mod payout_provider;
+pub mod preview;
 pub mod project;
 pub mod project_app_service;
 mod rating;
+pub mod release;
 pub mod resilience_engine;
+pub mod shell_session;
 pub mod step_executor;
 pub mod typed_error;
 pub mod vault_service;
 pub mod ws_pipe;
 
+pub use command_notifier::{CommandNotifier, COMMAND_QUEUED_CHANNEL};
 pub use config_renderer::{AppRenderContext, ConfigBundle, ConfigRenderer, SyncResult};
 pub use deploy_plan::{
-    build_deploy_plan, build_rollback_plan, resolve_rollback_plan_context, DeployPlan,
-    DeployPlanAction, DeployPlanActionKind, DeployPlanOperation, DeployPlanRollback,
-    DeployPlanScope, RollbackPlanContext, DEPLOY_PLAN_SCHEMA_VERSION,
+    build_deploy_plan, build_rollback_plan, resolve_release_rollback_context,
+    resolve_rollback_plan_context, DeployPlan, DeployPlanAction, DeployPlanActionKind,
+    DeployPlanOperation, DeployPlanRollback, DeployPlanScope, RollbackPlanContext,
+    RollbackTargetKind, DEPLOY_PLAN_SCHEMA_VERSION,
 };
 pub use deployment_events::{
     DeploymentEvent, DeploymentEventClassification, DeploymentEventFeed, DeploymentEventKind,
@@ -39,8 +55,9 @@ pub use deployment_identifier::{
 };
 pub use deployment_state::{
     DeploymentAgentFeatures, DeploymentAgentState, DeploymentAppState, DeploymentDriftState,
-    DeploymentLastCommandState, DeploymentProjectState, DeploymentRuntimeState, DeploymentState,
-    DeploymentStateDeployment, DEPLOYMENT_STATE_SCHEMA_VERSION,
+    DeploymentLastCommandState, DeploymentLeaseState, DeploymentProjectState,
+    DeploymentRuntimeState, DeploymentState, DeploymentStateDeployment,
+    DEPLOYMENT_STATE_SCHEMA_VERSION,
 };
 pub use env_contract::{
     runtime_env_contract_response, runtime_env_layer_names, RuntimeEnvContractResponse,
@@ -54,6 +71,7 @@ pub use explain::{
 };
 pub use handoff::InMemoryHandoffStore;
 pub use log_cache::LogCacheService;
+pub use log_shipping::{init_log_store, LogSearchQuery, LogStore};
 pub use marketplace_access::{validate_marketplace_template_access, MarketplaceAccessError};
 pub use marketplace_assets::{
     build_asset_key, presign_asset_download, presign_asset_upload, MarketplaceAssetStorageError,
@@ -65,6 +83,7 @@ pub use payout_provider::{
     PayoutProvider, PayoutProviderError, PayoutWebhookUpdate, StripeConnectPayoutProvider,
 };
 pub use project_app_service::{ProjectAppError, ProjectAppService, SyncSummary};
+pub use shell_session::ShellSessionHub;
 pub use typed_error::{
     ApiTypedError, TypedErrorCode, TypedErrorEnvelope, TypedRemediationClass,
     TYPED_ERROR_SCHEMA_VERSION,
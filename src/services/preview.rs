@@ -0,0 +1,217 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{
+    db,
+    forms::status_panel::{ConfigureProxyCommandRequest, RemoveAppCommandRequest},
+    models::{Command, PreviewEnvironment},
+    services::{command_queue, TypedErrorEnvelope},
+};
+
+pub const DEFAULT_PREVIEW_TTL_SECS: i64 = 72 * 3600;
+const MIN_PREVIEW_TTL_SECS: i64 = 3600;
+const MAX_PREVIEW_TTL_SECS: i64 = 30 * 24 * 3600;
+/// Longest slug kept from a branch name, leaving room for the base domain.
+const MAX_SLUG_LEN: usize = 40;
+const REAPER_INTERVAL_SECS: u64 = 300;
+const REAPER_BATCH: i64 = 50;
+
+/// Body of `POST /api/v1/deployments/{hash}/previews`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewRequest {
+    pub branch: String,
+    pub slug: String,
+    pub app_code: String,
+    pub compose_project: String,
+    pub domain: String,
+    pub forward_port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<i64>,
+}
+
+/// DNS label for a branch: `feature/Login_Page` becomes `feature-login-page`.
+pub fn preview_slug(branch: &str) -> String {
+    let mut slug = String::new();
+    for c in branch.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug.trim_matches('-').to_string()
+}
+
+fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= MAX_SLUG_LEN
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Record a preview, or refresh the expiry of an existing one for the same
+/// slug. The CLI deploys the stack itself; this only makes it reapable.
+pub async fn register_preview(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    user_id: &str,
+    request: &PreviewRequest,
+) -> Result<PreviewEnvironment, TypedErrorEnvelope> {
+    if !is_valid_slug(&request.slug) {
+        return Err(TypedErrorEnvelope::invalid_request(format!(
+            "Preview slug '{}' must be a lowercase DNS label of at most {} characters",
+            request.slug, MAX_SLUG_LEN
+        )));
+    }
+    if !request.domain.starts_with(&format!("{}.", request.slug)) {
+        return Err(TypedErrorEnvelope::invalid_request(
+            "Preview domain must be <slug>.<base-domain>",
+        ));
+    }
+    if request.app_code.trim().is_empty() || request.forward_port == 0 {
+        return Err(TypedErrorEnvelope::invalid_request(
+            "Preview app code and forward port are required",
+        ));
+    }
+
+    let ttl = request
+        .ttl_secs
+        .unwrap_or(DEFAULT_PREVIEW_TTL_SECS)
+        .clamp(MIN_PREVIEW_TTL_SECS, MAX_PREVIEW_TTL_SECS);
+    let now = Utc::now();
+    let preview = PreviewEnvironment {
+        id: 0,
+        deployment_hash: deployment_hash.to_string(),
+        slug: request.slug.clone(),
+        branch: request.branch.clone(),
+        app_code: request.app_code.clone(),
+        compose_project: request.compose_project.clone(),
+        domain: request.domain.clone(),
+        forward_port: i32::from(request.forward_port),
+        status: PreviewEnvironment::ACTIVE.to_string(),
+        created_by: user_id.to_string(),
+        created_at: now,
+        updated_at: now,
+        expires_at: now + Duration::seconds(ttl),
+    };
+    db::preview_environment::upsert(pg_pool, &preview)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)
+}
+
+/// Queue removal of the preview's proxy host and containers (including its
+/// volumes), then mark it `status`. Returns the queued command ids.
+pub async fn teardown_preview(
+    pg_pool: &PgPool,
+    preview: &PreviewEnvironment,
+    status: &str,
+) -> Result<Vec<String>, String> {
+    let proxy = ConfigureProxyCommandRequest {
+        app_code: preview.app_code.clone(),
+        domain_names: vec![preview.domain.clone()],
+        forward_host: None,
+        forward_port: u16::try_from(preview.forward_port).unwrap_or_default(),
+        ssl_enabled: true,
+        ssl_forced: true,
+        http2_support: true,
+        action: "delete".to_string(),
+    };
+    let remove = RemoveAppCommandRequest {
+        app_code: preview.app_code.clone(),
+        delete_config: true,
+        remove_volumes: true,
+        remove_image: false,
+        compose_project: Some(preview.compose_project.clone()),
+    };
+
+    let mut commands = Vec::new();
+    for (command_type, parameters) in [
+        ("configure_proxy", serde_json::to_value(&proxy)),
+        ("remove_app", serde_json::to_value(&remove)),
+    ] {
+        let parameters = parameters.map_err(|e| e.to_string())?;
+        commands.push(
+            Command::new(
+                uuid::Uuid::new_v4().to_string(),
+                preview.deployment_hash.clone(),
+                command_type.to_string(),
+                preview.created_by.clone(),
+            )
+            .with_parameters(parameters),
+        );
+    }
+    // The preview is still active here, so its compose project is admitted
+    // without the deployment's lease.
+    let command_ids = command_queue::enqueue_all(pg_pool, commands, None)
+        .await
+        .map_err(|error| error.message)?
+        .into_iter()
+        .map(|command| command.command_id)
+        .collect();
+
+    db::preview_environment::set_status(pg_pool, preview.id, status).await?;
+    Ok(command_ids)
+}
+
+/// Tear down every preview past its expiry. Returns how many were queued.
+pub async fn reap_expired_previews(pg_pool: &PgPool) -> Result<usize, String> {
+    let expired = db::preview_environment::list_expired(pg_pool, REAPER_BATCH).await?;
+    let mut reaped = 0;
+    for preview in &expired {
+        match teardown_preview(pg_pool, preview, PreviewEnvironment::EXPIRED).await {
+            Ok(_) => {
+                tracing::info!(
+                    deployment_hash = %preview.deployment_hash,
+                    slug = %preview.slug,
+                    "Preview environment expired; teardown queued"
+                );
+                reaped += 1;
+            }
+            Err(err) => tracing::warn!(
+                deployment_hash = %preview.deployment_hash,
+                slug = %preview.slug,
+                "Failed to tear down expired preview: {}",
+                err
+            ),
+        }
+    }
+    Ok(reaped)
+}
+
+/// Background task that reaps expired previews every few minutes.
+pub fn spawn_preview_reaper(pg_pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(StdDuration::from_secs(REAPER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(err) = reap_expired_previews(&pg_pool).await {
+                tracing::warn!("Preview reaper failed: {}", err);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_slug_is_a_dns_label() {
+        assert_eq!(preview_slug("feature/Login_Page"), "feature-login-page");
+        assert_eq!(preview_slug("--fix--"), "fix");
+        assert_eq!(preview_slug("dependabot/npm/a.b"), "dependabot-npm-a-b");
+        let long = preview_slug(&"x".repeat(80));
+        assert_eq!(long.len(), MAX_SLUG_LEN);
+        assert!(is_valid_slug(&long));
+        assert!(!is_valid_slug("Feature"));
+        assert!(!is_valid_slug(""));
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::{
+    cli::config_parser::BlueGreenConfig,
+    db,
+    forms::status_panel::{BlueGreenCommandRequest, DeployAppCommandRequest, RolloutStep},
+    models::{Command, CommandPriority, Deployment, DeploymentRelease},
+    services::{
+        build_rollback_plan, command_queue, deployment_lease::ensure_lease_holder,
+        resolve_release_rollback_context, DeployPlan, DeploymentState, TypedErrorEnvelope,
+    },
+};
+
+/// Body of `POST /api/v1/deployments/{hash}/releases`, sent by the CLI after
+/// a successful remote deploy.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReleaseRecord {
+    pub compose_content: String,
+    #[serde(default)]
+    pub env_hashes: BTreeMap<String, String>,
+    #[serde(default)]
+    pub image_digests: BTreeMap<String, String>,
+    #[serde(default)]
+    pub stacker_yml: String,
+    /// The `deploy_app` command that shipped this compose. Without it the
+    /// deployment itself must have completed (a full install).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_id: Option<String>,
+}
+
+impl ReleaseRecord {
+    /// SHA-256 over everything that changes what runs; identical redeploys
+    /// hash the same and do not add a new release.
+    pub fn bundle_hash(&self) -> String {
+        let payload = serde_json::json!({
+            "compose": self.compose_content,
+            "env": self.env_hashes,
+            "images": self.image_digests,
+        });
+        format!("{:x}", Sha256::digest(payload.to_string().as_bytes()))
+    }
+
+    pub fn into_release(self, deployment_hash: &str, created_by: &str) -> DeploymentRelease {
+        let bundle_hash = self.bundle_hash();
+        DeploymentRelease {
+            id: 0,
+            deployment_hash: deployment_hash.to_string(),
+            version: 0,
+            compose_content: self.compose_content,
+            env_hashes: serde_json::json!(self.env_hashes),
+            image_digests: serde_json::json!(self.image_digests),
+            stacker_yml: self.stacker_yml,
+            bundle_hash,
+            rollback_of: None,
+            command_id: self.command_id,
+            created_by: created_by.to_string(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseRollbackOutcome {
+    pub plan: DeployPlan,
+    /// The release recorded for the rollback itself; `None` for a no-op.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<DeploymentRelease>,
+    #[serde(default)]
+    pub command_ids: Vec<String>,
+}
+
+/// Check that the deploy a release describes actually finished. With a
+/// `command_id` it must be a completed, error-free `deploy_app` on this
+/// deployment that shipped exactly this compose and is not recorded yet;
+/// without one, the deployment itself must have completed.
+pub async fn verify_release_source(
+    pg_pool: &PgPool,
+    deployment: &Deployment,
+    record: &ReleaseRecord,
+) -> Result<(), TypedErrorEnvelope> {
+    let Some(command_id) = record.command_id.as_deref() else {
+        if deployment.status != "completed" {
+            return Err(TypedErrorEnvelope::invalid_request(format!(
+                "Deployment is {}; a release is recorded once the deploy completes",
+                deployment.status
+            )));
+        }
+        return Ok(());
+    };
+
+    let command = db::command::fetch_by_command_id(pg_pool, command_id)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?
+        .filter(|command| command.deployment_hash == deployment.deployment_hash)
+        .ok_or_else(|| {
+            TypedErrorEnvelope::invalid_request(format!(
+                "Command {} not found for this deployment",
+                command_id
+            ))
+        })?;
+    check_release_command(&command, record)?;
+
+    let recorded = db::deployment_release::fetch_by_command_id(pg_pool, command_id)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?;
+    if recorded.is_some() {
+        return Err(TypedErrorEnvelope::invalid_request(format!(
+            "Command {} is already recorded as a release",
+            command_id
+        )));
+    }
+    Ok(())
+}
+
+fn check_release_command(
+    command: &Command,
+    record: &ReleaseRecord,
+) -> Result<(), TypedErrorEnvelope> {
+    if command.r#type != "deploy_app" {
+        return Err(TypedErrorEnvelope::invalid_request(format!(
+            "Command {} is a {} command, not deploy_app",
+            command.command_id, command.r#type
+        )));
+    }
+    if command.status != "completed" || command.error.is_some() {
+        return Err(TypedErrorEnvelope::invalid_request(format!(
+            "Command {} has not completed successfully (status: {})",
+            command.command_id, command.status
+        )));
+    }
+    let shipped = command
+        .parameters
+        .as_ref()
+        .and_then(|parameters| parameters.get("compose_content"))
+        .and_then(|compose| compose.as_str());
+    if shipped != Some(record.compose_content.as_str()) {
+        return Err(TypedErrorEnvelope::invalid_request(format!(
+            "Command {} deployed a different compose than the release",
+            command.command_id
+        )));
+    }
+    Ok(())
+}
+
+/// Record a release unless the newest one already has the same contents.
+pub async fn record_release(
+    pg_pool: &PgPool,
+    deployment_hash: &str,
+    record: ReleaseRecord,
+    created_by: &str,
+) -> Result<(DeploymentRelease, bool), String> {
+    let release = record.into_release(deployment_hash, created_by);
+    if let Some(latest) = db::deployment_release::fetch_latest(pg_pool, deployment_hash).await? {
+        if latest.bundle_hash == release.bundle_hash {
+            return Ok((latest, false));
+        }
+    }
+    let release = db::deployment_release::insert(pg_pool, &release).await?;
+    Ok((release, true))
+}
+
+/// Revalidate the rollback plan, re-apply the target release through one
+/// `deploy_app` command per compose service, and record the rollback as a
+/// new release. Refused while another client holds the deploy lease.
+pub async fn apply_release_rollback(
+    pg_pool: &PgPool,
+    deployment: &Deployment,
+    user_id: &str,
+    target: &str,
+    requested_target: &str,
+    expected_fingerprint: Option<&str>,
+    lease_id: Option<&str>,
+) -> Result<ReleaseRollbackOutcome, TypedErrorEnvelope> {
+    let deployment_hash = deployment.deployment_hash.as_str();
+    let state = DeploymentState::for_deployment_hash(pg_pool, deployment_hash)
+        .await
+        .map_err(|_| TypedErrorEnvelope::internal_error("Failed to build deployment state"))?
+        .ok_or_else(|| TypedErrorEnvelope::deployment_not_found("Deployment not found"))?;
+    let rollback = resolve_release_rollback_context(pg_pool, deployment, requested_target).await?;
+    let resolved_version = rollback.resolved_version.clone();
+    let plan = build_rollback_plan(&state, target, rollback, expected_fingerprint)?;
+
+    if !plan.has_changes {
+        return Ok(ReleaseRollbackOutcome {
+            plan,
+            release: None,
+            command_ids: Vec::new(),
+        });
+    }
+
+    ensure_lease_holder(pg_pool, deployment_hash, lease_id).await?;
+
+    let version = resolved_version
+        .parse::<i32>()
+        .map_err(|_| TypedErrorEnvelope::internal_error("Resolved release is not a number"))?;
+    let source = db::deployment_release::fetch_by_version(pg_pool, deployment_hash, version)
+        .await
+        .map_err(TypedErrorEnvelope::internal_error)?
+        .ok_or_else(|| TypedErrorEnvelope::internal_error("Resolved release disappeared"))?;
+
+    let blue_green = release_blue_green(&source.stacker_yml);
+    let mut commands = Vec::new();
+    for app_code in release_app_codes(&source.compose_content)? {
+        let params = DeployAppCommandRequest {
+            app_code,
+            compose_content: Some(source.compose_content.clone()),
+            image: None,
+            env_vars: None,
+            config_files: None,
+            pull: true,
+            force_recreate: true,
+            force_config_overwrite: false,
+            runtime: deployment.runtime.clone(),
+            registry_auth: None,
+            blue_green: blue_green.clone(),
+            rollout: Some(RolloutStep::Rollback),
+            compose_project: None,
+        };
+        let parameters = serde_json::to_value(&params)
+            .map_err(|e| TypedErrorEnvelope::internal_error(e.to_string()))?;
+        let command = Command::new(
+            uuid::Uuid::new_v4().to_string(),
+            deployment_hash.to_string(),
+            "deploy_app".to_string(),
+            user_id.to_string(),
+        )
+        .with_priority(CommandPriority::High)
+        .with_parameters(parameters);
+        commands.push(command);
+    }
+    let command_ids = command_queue::enqueue_all(pg_pool, commands, lease_id)
+        .await?
+        .into_iter()
+        .map(|command| command.command_id)
+        .collect();
+
+    let release = db::deployment_release::insert(
+        pg_pool,
+        &DeploymentRelease {
+            rollback_of: Some(source.version),
+            command_id: None,
+            created_by: user_id.to_string(),
+            ..source
+        },
+    )
+    .await
+    .map_err(TypedErrorEnvelope::internal_error)?;
+
+    Ok(ReleaseRollbackOutcome {
+        plan,
+        release: Some(release),
+        command_ids,
+    })
+}
+
+fn release_app_codes(compose_content: &str) -> Result<Vec<String>, TypedErrorEnvelope> {
+    let doc: serde_yaml::Value = serde_yaml::from_str(compose_content).map_err(|e| {
+        TypedErrorEnvelope::internal_error(format!("Release compose is invalid: {}", e))
+    })?;
+    Ok(doc
+        .get("services")
+        .and_then(|services| services.as_mapping())
+        .map(|services| {
+            services
+                .keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Blue/green parameters from the release's stacker.yml snapshot, so a
+/// rollback rolls out the same way the release was deployed.
+fn release_blue_green(stacker_yml: &str) -> Option<BlueGreenCommandRequest> {
+    let doc: serde_yaml::Value = serde_yaml::from_str(stacker_yml).ok()?;
+    let deploy = doc.get("deploy")?;
+    if deploy.get("strategy").and_then(|value| value.as_str()) != Some("blue_green") {
+        return None;
+    }
+    let tuning = deploy
+        .get("blue_green")
+        .cloned()
+        .and_then(|value| serde_yaml::from_value::<BlueGreenConfig>(value).ok())
+        .unwrap_or_default();
+    Some(BlueGreenCommandRequest {
+        health_endpoint: doc
+            .get("monitoring")
+            .and_then(|monitoring| monitoring.get("healthcheck"))
+            .and_then(|healthcheck| healthcheck.get("endpoint"))
+            .and_then(|endpoint| endpoint.as_str())
+            .map(str::to_string),
+        health_timeout_secs: tuning.health_timeout,
+        drain_secs: tuning.drain_timeout,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_hash_ignores_stacker_yml_snapshot() {
+        let record = ReleaseRecord {
+            compose_content: "services:\n  web:\n    image: web@sha256:1\n".to_string(),
+            env_hashes: BTreeMap::from([("web".to_string(), "abc".to_string())]),
+            image_digests: BTreeMap::from([("web".to_string(), "web@sha256:1".to_string())]),
+            stacker_yml: "name: shop\n".to_string(),
+            command_id: None,
+        };
+        let mut edited = record.clone();
+        edited.stacker_yml = "name: shop\n# comment\n".to_string();
+        assert_eq!(record.bundle_hash(), edited.bundle_hash());
+
+        edited
+            .env_hashes
+            .insert("web".to_string(), "def".to_string());
+        assert_ne!(record.bundle_hash(), edited.bundle_hash());
+
+        let release = record.into_release("deployment_abc", "user-1");
+        assert_eq!(release.image_digests["web"], "web@sha256:1");
+        assert_eq!(
+            release_app_codes(&release.compose_content).unwrap(),
+            vec!["web"]
+        );
+    }
+
+    #[test]
+    fn release_command_must_be_a_completed_deploy_of_the_same_compose() {
+        let compose = "services:\n  web:\n    image: web:1\n";
+        let record = ReleaseRecord {
+            compose_content: compose.to_string(),
+            env_hashes: BTreeMap::new(),
+            image_digests: BTreeMap::new(),
+            stacker_yml: String::new(),
+            command_id: Some("cmd-1".to_string()),
+        };
+        let mut command = Command::new(
+            "cmd-1".to_string(),
+            "deployment_abc".to_string(),
+            "deploy_app".to_string(),
+            "user-1".to_string(),
+        )
+        .with_parameters(serde_json::json!({ "app_code": "web", "compose_content": compose }));
+        assert!(check_release_command(&command, &record).is_err());
+
+        command.status = "completed".to_string();
+        assert!(check_release_command(&command, &record).is_ok());
+
+        let mut other = record.clone();
+        other.compose_content = "services:\n  web:\n    image: web:2\n".to_string();
+        assert!(check_release_command(&command, &other).is_err());
+
+        command.error = Some(serde_json::json!({ "message": "pull failed" }));
+        assert!(check_release_command(&command, &record).is_err());
+
+        command.error = None;
+        command.r#type = "restart".to_string();
+        assert!(check_release_command(&command, &record).is_err());
+    }
+
+    #[test]
+    fn rollback_keeps_the_release_rollout_strategy() {
+        let blue_green = release_blue_green(
+            "name: shop\ndeploy:\n  strategy: blue_green\n  blue_green:\n    drain_timeout: 5\nmonitoring:\n  healthcheck:\n    endpoint: /health\n",
+        )
+        .unwrap();
+        assert_eq!(blue_green.health_endpoint.as_deref(), Some("/health"));
+        assert_eq!(blue_green.health_timeout_secs, 120);
+        assert_eq!(blue_green.drain_secs, 5);
+
+        assert!(release_blue_green("name: shop\ndeploy:\n  target: server\n").is_none());
+        assert!(release_blue_green("").is_none());
+    }
+}
@@ -0,0 +1,318 @@
+//! Interactive shell sessions relayed between a user and the Status Panel
+//! agent.
+//!
+//! A user opens `GET /api/v1/deployments/{hash}/shell` as a WebSocket; the
+//! server registers the session here and queues an `open_shell` command.
+//! The agent starts a PTY in the container and connects back to
+//! `GET /api/v1/agent/shell/{session_id}`, which claims the session and pairs
+//! the two sockets. From then on binary frames carry terminal bytes in both
+//! directions and text frames carry [`ShellControl`] messages. Sessions live
+//! in memory, so both sockets must reach the same server process: session
+//! ids carry the id of the replica that opened them, which lets a load
+//! balancer route the agent's socket and lets other replicas refuse it with
+//! a clear error.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use actix::{Message, Recipient};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+pub use crate::forms::status_panel::MAX_SHELL_IDLE_TIMEOUT_SECS;
+
+pub const OPEN_SHELL_COMMAND_TYPE: &str = "open_shell";
+pub const DEFAULT_SHELL_IDLE_TIMEOUT_SECS: u32 = 900;
+/// How long the agent has to attach after `open_shell` is queued.
+pub const SHELL_ATTACH_TIMEOUT: Duration = Duration::from_secs(30);
+/// Input kept for the audit record of one session.
+pub const SHELL_TRANSCRIPT_LIMIT: usize = 64 * 1024;
+
+pub const SHELL_OPENED_EVENT: &str = "shell_opened";
+pub const SHELL_CLOSED_EVENT: &str = "shell_closed";
+
+/// Text frames exchanged alongside the terminal bytes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShellControl {
+    /// Sent to the user once the agent attached the PTY.
+    Ready { session_id: String },
+    /// Terminal size change, from the user to the agent.
+    Resize { cols: u16, rows: u16 },
+    /// The shell process ended.
+    Exit {
+        #[serde(default)]
+        code: Option<i32>,
+    },
+    /// The session could not start or was ended by the server.
+    Error { message: String },
+}
+
+impl ShellControl {
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// A frame for the other end of a paired session.
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+pub enum ShellFrame {
+    Data(Vec<u8>),
+    Control(ShellControl),
+    /// The other end went away; close with this reason.
+    Closed(String),
+}
+
+/// Delivered to the user's socket when the agent attaches.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AgentAttached(pub Recipient<ShellFrame>);
+
+struct PendingShell {
+    deployment_hash: String,
+    frames: Recipient<ShellFrame>,
+    attach: Recipient<AgentAttached>,
+}
+
+/// Sessions waiting for their agent, keyed by session id.
+pub struct ShellSessionHub {
+    /// Id of this server process, embedded in the session ids it hands out.
+    replica: String,
+    pending: Mutex<HashMap<String, PendingShell>>,
+}
+
+impl Default for ShellSessionHub {
+    fn default() -> Self {
+        Self {
+            replica: uuid::Uuid::new_v4().simple().to_string()[..12].to_string(),
+            pending: Mutex::default(),
+        }
+    }
+}
+
+impl ShellSessionHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn replica(&self) -> &str {
+        &self.replica
+    }
+
+    /// A new session id owned by this replica: `sh_<replica>_<uuid>`.
+    pub fn new_session_id(&self) -> String {
+        format!("sh_{}_{}", self.replica, uuid::Uuid::new_v4())
+    }
+
+    /// Whether `session_id` was opened by another server replica, so only
+    /// that replica can pair it.
+    pub fn is_held_elsewhere(&self, session_id: &str) -> bool {
+        session_replica(session_id).is_some_and(|replica| replica != self.replica)
+    }
+
+    pub fn register(
+        &self,
+        session_id: &str,
+        deployment_hash: &str,
+        frames: Recipient<ShellFrame>,
+        attach: Recipient<AgentAttached>,
+    ) {
+        self.lock().insert(
+            session_id.to_string(),
+            PendingShell {
+                deployment_hash: deployment_hash.to_string(),
+                frames,
+                attach,
+            },
+        );
+    }
+
+    /// Pair the agent with a waiting session of its own deployment. Returns
+    /// the user's side, or `None` for unknown, expired or foreign sessions.
+    pub fn attach(
+        &self,
+        session_id: &str,
+        deployment_hash: &str,
+        agent: Recipient<ShellFrame>,
+    ) -> Option<Recipient<ShellFrame>> {
+        let mut pending = self.lock();
+        if pending.get(session_id)?.deployment_hash != deployment_hash {
+            return None;
+        }
+        let session = pending.remove(session_id)?;
+        session.attach.do_send(AgentAttached(agent));
+        Some(session.frames)
+    }
+
+    /// Whether a session of `deployment_hash` is waiting for its agent.
+    pub fn is_pending(&self, session_id: &str, deployment_hash: &str) -> bool {
+        self.lock()
+            .get(session_id)
+            .is_some_and(|session| session.deployment_hash == deployment_hash)
+    }
+
+    pub fn remove(&self, session_id: &str) {
+        self.lock().remove(session_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PendingShell>> {
+        self.pending.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn session_replica(session_id: &str) -> Option<&str> {
+    let (replica, _) = session_id.strip_prefix("sh_")?.split_once('_')?;
+    Some(replica)
+}
+
+/// What a session did, written to `agent_audit_log` when it closes. Typed
+/// input is kept only when capture was enabled with
+/// [`ShellTranscript::with_input_capture`].
+#[derive(Debug, Clone)]
+pub struct ShellTranscript {
+    pub session_id: String,
+    pub user_id: String,
+    pub app_code: String,
+    pub started_at: DateTime<Utc>,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub exit_code: Option<i32>,
+    capture_input: bool,
+    input: Vec<u8>,
+    input_truncated: bool,
+}
+
+impl ShellTranscript {
+    pub fn new(session_id: &str, user_id: &str, app_code: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            user_id: user_id.to_string(),
+            app_code: app_code.to_string(),
+            started_at: Utc::now(),
+            bytes_in: 0,
+            bytes_out: 0,
+            exit_code: None,
+            capture_input: false,
+            input: Vec::new(),
+            input_truncated: false,
+        }
+    }
+
+    /// Also keep the typed input, up to [`SHELL_TRANSCRIPT_LIMIT`].
+    pub fn with_input_capture(mut self, capture_input: bool) -> Self {
+        self.capture_input = capture_input;
+        self
+    }
+
+    /// Count user input; keep it too when capture is enabled.
+    pub fn record_input(&mut self, data: &[u8]) {
+        self.bytes_in += data.len() as u64;
+        if !self.capture_input {
+            return;
+        }
+        let room = SHELL_TRANSCRIPT_LIMIT.saturating_sub(self.input.len());
+        if data.len() > room {
+            self.input_truncated = true;
+        }
+        self.input.extend_from_slice(&data[..data.len().min(room)]);
+    }
+
+    pub fn record_output(&mut self, data: &[u8]) {
+        self.bytes_out += data.len() as u64;
+    }
+
+    pub fn opened_payload(&self, command_id: &str, cols: u16, rows: u16) -> serde_json::Value {
+        json!({
+            "session_id": self.session_id,
+            "user_id": self.user_id,
+            "app_code": self.app_code,
+            "command_id": command_id,
+            "cols": cols,
+            "rows": rows,
+        })
+    }
+
+    pub fn closed_payload(&self, reason: &str) -> serde_json::Value {
+        let mut payload = json!({
+            "session_id": self.session_id,
+            "user_id": self.user_id,
+            "app_code": self.app_code,
+            "reason": reason,
+            "exit_code": self.exit_code,
+            "started_at": self.started_at,
+            "duration_secs": (Utc::now() - self.started_at).num_seconds(),
+            "bytes_in": self.bytes_in,
+            "bytes_out": self.bytes_out,
+            "input_captured": self.capture_input,
+        });
+        if self.capture_input {
+            payload["input"] = json!(String::from_utf8_lossy(&self.input));
+            payload["input_truncated"] = json!(self.input_truncated);
+        }
+        payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcript_caps_recorded_input_but_counts_everything() {
+        let mut transcript = ShellTranscript::new("sh_1", "user_1", "web").with_input_capture(true);
+        transcript.record_input(b"ls -la\r");
+        transcript.record_input(&vec![b'x'; SHELL_TRANSCRIPT_LIMIT]);
+        transcript.record_output(b"total 0\r\n");
+        transcript.exit_code = Some(0);
+
+        let payload = transcript.closed_payload("exited");
+        assert_eq!(payload["bytes_in"], (SHELL_TRANSCRIPT_LIMIT + 7) as u64);
+        assert_eq!(payload["bytes_out"], 9);
+        assert_eq!(payload["input_truncated"], true);
+        assert_eq!(
+            payload["input"].as_str().unwrap().len(),
+            SHELL_TRANSCRIPT_LIMIT
+        );
+        assert!(payload["input"].as_str().unwrap().starts_with("ls -la\r"));
+
+        let resize: ShellControl =
+            serde_json::from_str(r#"{"type":"resize","cols":120,"rows":40}"#).unwrap();
+        assert_eq!(
+            resize,
+            ShellControl::Resize {
+                cols: 120,
+                rows: 40
+            }
+        );
+        assert_eq!(
+            ShellControl::Exit { code: Some(1) }.to_text(),
+            r#"{"type":"exit","code":1}"#
+        );
+    }
+
+    #[test]
+    fn transcript_keeps_only_byte_counts_by_default() {
+        let mut transcript = ShellTranscript::new("sh_1", "user_1", "web");
+        transcript.record_input(b"s3cret\r");
+
+        let payload = transcript.closed_payload("client_closed");
+        assert_eq!(payload["bytes_in"], 7);
+        assert_eq!(payload["input_captured"], false);
+        assert!(payload.get("input").is_none());
+    }
+
+    #[test]
+    fn session_ids_name_the_replica_that_holds_them() {
+        let hub = ShellSessionHub::new();
+        let other = ShellSessionHub::new();
+        let session_id = hub.new_session_id();
+
+        assert!(session_id.starts_with(&format!("sh_{}_", hub.replica())));
+        assert!(!hub.is_held_elsewhere(&session_id));
+        assert!(other.is_held_elsewhere(&session_id));
+        assert!(!other.is_held_elsewhere("not-a-session"));
+    }
+}
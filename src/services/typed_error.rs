@@ -12,6 +12,7 @@ pub const TYPED_ERROR_SCHEMA_VERSION: &str = "v1alpha1";
 pub enum TypedErrorCode {
     ComposePathUnresolved,
     DeploymentCapabilityMissing,
+    DeploymentLocked,
     DeploymentNotFound,
     InternalError,
     InvalidRequest,
@@ -20,6 +21,7 @@ pub enum TypedErrorCode {
     RegistryAuthMissing,
     RollbackTargetUnavailable,
     RuntimeEnvDriftDetected,
+    ScheduleNotFound,
     VaultSecretNotFound,
 }
 
@@ -82,6 +84,15 @@ impl TypedErrorEnvelope {
         )
     }
 
+    pub fn deployment_locked(message: impl Into<String>) -> Self {
+        Self::new(
+            TypedErrorCode::DeploymentLocked,
+            message,
+            true,
+            TypedRemediationClass::State,
+        )
+    }
+
     pub fn deployment_capability_missing(message: impl Into<String>) -> Self {
         Self::new(
             TypedErrorCode::DeploymentCapabilityMissing,
@@ -100,6 +111,15 @@ impl TypedErrorEnvelope {
         )
     }
 
+    pub fn schedule_not_found(message: impl Into<String>) -> Self {
+        Self::new(
+            TypedErrorCode::ScheduleNotFound,
+            message,
+            false,
+            TypedRemediationClass::State,
+        )
+    }
+
     pub fn vault_secret_not_found(message: impl Into<String>) -> Self {
         Self::new(
             TypedErrorCode::VaultSecretNotFound,
@@ -77,6 +77,14 @@ pub async fn run(
     let mq_manager = web::Data::new(mq_manager);
 
     let vault_client = helpers::VaultClient::new(&settings.vault);
+    if settings.agent_identity.mtls_mode != crate::configuration::AgentMtlsMode::Off
+        && settings.agent_identity.trusted_proxies.is_empty()
+    {
+        tracing::warn!(
+            "agent_identity.mtls_mode is enabled but trusted_proxies is empty: \
+             no agent client certificate will be accepted"
+        );
+    }
     let vault_client = web::Data::new(vault_client);
 
     let oauth_http_client = build_oauth_http_client(&settings).map_err(std::io::Error::other)?;
@@ -100,6 +108,33 @@ pub async fn run(
     let health_metrics = Arc::new(HealthMetrics::new(1000));
     let health_metrics = web::Data::new(health_metrics);
     let handoff_store = web::Data::new(Arc::new(InMemoryHandoffStore::new()));
+    let shell_sessions = web::Data::new(Arc::new(crate::services::ShellSessionHub::new()));
+
+    // Wake agent command waiters through LISTEN/NOTIFY instead of polling the queue.
+    let command_notifier = web::Data::new(crate::services::CommandNotifier::spawn(
+        agent_pool.get_ref().inner().clone(),
+    ));
+
+    // Tear down preview environments whose TTL ran out.
+    crate::services::preview::spawn_preview_reaper(api_pool.get_ref().clone());
+
+    // Queue recurring agent commands as their cron schedules come due.
+    crate::services::command_schedule::spawn_command_scheduler(api_pool.get_ref().clone());
+
+    // Clear file transfer chunks nobody acknowledged from the command log.
+    crate::services::file_transfer::spawn_chunk_purge(api_pool.get_ref().clone());
+
+    // Keep daily container metrics partitions ahead of time and drop expired days.
+    crate::services::container_metrics::spawn_metrics_maintenance(api_pool.get_ref().clone());
+
+    // Store for shipped container logs; Postgres partitions are maintained like metrics.
+    let log_store = crate::services::init_log_store(&settings.log_shipping)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+    let log_store = web::Data::new(log_store);
+    crate::services::log_shipping::spawn_log_maintenance(
+        api_pool.get_ref().clone(),
+        &settings.log_shipping,
+    );
 
     // Initialize external service connectors (plugin pattern)
     // Connector handles category sync on startup
@@ -150,9 +185,11 @@ pub async fn run(
             .app_data(health_checker.clone())
             .app_data(health_metrics.clone())
             .app_data(handoff_store.clone())
+            .app_data(shell_sessions.clone())
             .app_data(oauth_http_client.clone())
             .app_data(oauth_cache.clone())
             .app_data(payout_provider.clone())
+            .app_data(log_store.clone())
             .service(
                 web::scope("/health_check")
                     .service(routes::health_check)
@@ -277,9 +314,16 @@ pub async fn run(
                     .service(
                         web::scope("/v1/agent")
                             .service(routes::agent::register_handler)
+                            .service(routes::agent::agent_certificate_handler)
                             .service(routes::agent::enqueue_handler)
+                            .service(routes::agent::enqueue_batch_handler)
+                            .service(routes::agent::get_batch_handler)
                             .service(routes::agent::wait_handler)
+                            .service(routes::agent::stream_handler)
+                            .service(routes::agent::shell_attach_handler)
                             .service(routes::agent::report_handler)
+                            .service(routes::agent::metrics_push_handler)
+                            .service(routes::agent::logs_push_handler)
                             .service(routes::agent::notifications_handler)
                             .service(routes::agent::snapshot_handler)
                             .service(routes::agent::project_snapshot_handler)
@@ -315,6 +359,21 @@ pub async fn run(
                             .service(routes::deployment::events_handler)
                             .service(routes::deployment::list_handler)
                             .service(routes::deployment::plan_handler)
+                            .service(routes::deployment::acquire_lease_handler)
+                            .service(routes::deployment::release_lease_handler)
+                            .service(routes::deployment::list_previews_handler)
+                            .service(routes::deployment::create_preview_handler)
+                            .service(routes::deployment::delete_preview_handler)
+                            .service(routes::deployment::list_releases_handler)
+                            .service(routes::deployment::create_release_handler)
+                            .service(routes::deployment::rollback_release_handler)
+                            .service(routes::deployment::list_schedules_handler)
+                            .service(routes::deployment::create_schedule_handler)
+                            .service(routes::deployment::delete_schedule_handler)
+                            .service(routes::deployment::list_backups_handler)
+                            .service(routes::deployment::container_metrics_handler)
+                            .service(routes::deployment::search_logs_handler)
+                            .service(routes::deployment::shell_handler)
                             .service(routes::deployment::state_handler)
                             .service(routes::deployment::status_by_hash_handler)
                             .service(routes::deployment::status_handler)
@@ -455,6 +514,7 @@ pub async fn run(
             .app_data(json_config.clone())
             .app_data(api_pool.clone())
             .app_data(agent_pool.clone())
+            .app_data(command_notifier.clone())
             .app_data(mq_manager.clone())
             .app_data(vault_client.clone())
             .app_data(mcp_registry.clone())